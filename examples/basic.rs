@@ -11,6 +11,9 @@ fn main() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: NOT_DEALT,
+        player_names: ["BB".to_string(), "BTN".to_string()],
+        second_board: Vec::new(),
+        disable_isomorphism: false,
     };
 
     // bet sizes -> 60% of the pot, geometric size, and all-in
@@ -21,9 +24,11 @@ fn main() {
     let tree_config = TreeConfig {
         initial_state: BoardState::Turn, // must match `card_config`
         starting_pot: 200,
+        dead_money: 0, // part of the starting pot not contributed by the players
         effective_stack: 900,
         rake_rate: 0.0,
         rake_cap: 0.0,
+        payoff_model: PayoffModel::ChipEv, // chip EV (see `PayoffModel` for ICM)
         flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()], // [OOP, IP]
         turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
         river_bet_sizes: [bet_sizes.clone(), bet_sizes],
@@ -32,6 +37,9 @@ fn main() {
         add_allin_threshold: 1.5, // add all-in if (maximum bet size) <= 1.5x pot
         force_allin_threshold: 0.15, // force all-in if (SPR after the opponent's call) <= 0.15
         merging_threshold: 0.1,
+        allin_equity_adjudication: false, // deal all runouts after an all-in call
+        allin_chop_fee_rate: 0.0,         // no fee for chopping all-in pots
+        allin_chop_fee_cap: 0.0,
     };
 
     // build the game tree
@@ -67,8 +75,9 @@ fn main() {
     // solve the game
     let max_num_iterations = 1000;
     let target_exploitability = game.tree_config().starting_pot as f32 * 0.005; // 0.5% of the pot
-    let exploitability = solve(&mut game, max_num_iterations, target_exploitability, true);
-    println!("Exploitability: {:.2}", exploitability);
+    let summary = solve(&mut game, max_num_iterations, target_exploitability, true);
+    println!("Exploitability: {:.2}", summary.exploitability);
+    println!("Solved in {} iterations", summary.num_iterations);
 
     // solve the game manually
     // for i in 0..max_num_iterations {
@@ -144,6 +153,4 @@ fn main() {
 
     // back to the root node
     game.back_to_root();
-
-    game.save_to_file();
 }
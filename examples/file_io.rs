@@ -11,6 +11,7 @@ fn main() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: NOT_DEALT,
+        ..Default::default()
     };
 
     let bet_sizes = BetSizeOptions::try_from(("60%, e, a", "2.5x")).unwrap();
@@ -18,9 +19,11 @@ fn main() {
     let tree_config = TreeConfig {
         initial_state: BoardState::Turn,
         starting_pot: 200,
+        dead_money: 0,
         effective_stack: 900,
         rake_rate: 0.0,
         rake_cap: 0.0,
+        payoff_model: PayoffModel::ChipEv,
         flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
         turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
         river_bet_sizes: [bet_sizes.clone(), bet_sizes],
@@ -29,6 +32,9 @@ fn main() {
         add_allin_threshold: 1.5,
         force_allin_threshold: 0.15,
         merging_threshold: 0.1,
+        allin_equity_adjudication: false,
+        allin_chop_fee_rate: 0.0,
+        allin_chop_fee_cap: 0.0,
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
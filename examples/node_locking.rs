@@ -11,6 +11,7 @@ fn normal_node_locking() {
         flop: flop_from_str("2s3h4d").unwrap(),
         turn: card_from_str("6c").unwrap(),
         river: card_from_str("7c").unwrap(),
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
@@ -62,6 +63,7 @@ fn partial_node_locking() {
         flop: flop_from_str("2s3h4d").unwrap(),
         turn: card_from_str("6c").unwrap(),
         river: card_from_str("7c").unwrap(),
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use postflop_solver::*;
+
+fuzz_target!(|data: &str| {
+    if let Ok(board) = board_from_str(data) {
+        assert!((3..=5).contains(&board.len()));
+        let mask = board.iter().fold(0u64, |mask, &card| mask | (1 << card));
+        assert_eq!(mask.count_ones() as usize, board.len());
+    }
+
+    if let Ok(flop) = flop_from_str(data) {
+        assert!(flop[0] < flop[1] && flop[1] < flop[2] && flop[2] < 52);
+    }
+
+    if let Ok(card) = card_from_str(data) {
+        assert_eq!(card_from_str(&card_to_string(card).unwrap()), Ok(card));
+    }
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use postflop_solver::*;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(header) = read_file_header(&mut &data[..]) {
+        assert!(header.memo.len() <= MAX_MEMO_LENGTH);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use postflop_solver::Range;
+
+fuzz_target!(|data: &str| {
+    if let Ok(range) = data.parse::<Range>() {
+        // a parsed range must be printable and round-trip
+        let s = range.to_string();
+        assert_eq!(s.parse::<Range>(), Ok(range), "{s}");
+    }
+});
@@ -0,0 +1,483 @@
+//! Client of the Annual Computer Poker Competition (ACPC) protocol.
+//!
+//! The client connects to an ACPC dealer, receives `MATCHSTATE` messages, and answers with the
+//! actions of an [`AcpcAgent`], which plays heads-up no-limit hold'em using solved postflop
+//! blueprints. Only the postflop streets are covered by the blueprints; preflop decisions and
+//! flops without a blueprint are delegated to a fallback policy.
+//!
+//! Position 0 of the ACPC dealer (the big blind) is regarded as OOP and position 1 as IP.
+
+use crate::blueprint::*;
+use crate::card::*;
+use crate::error::*;
+use crate::game::*;
+use crate::range::*;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+/// An action in the ACPC protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcpcAction {
+    /// Fold action.
+    Fold,
+
+    /// Check or call action.
+    Call,
+
+    /// Raise action making the player's total commitment in the hand the specified amount.
+    Raise(i32),
+}
+
+/// Rules of the no-limit game played on the dealer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcpcGameDef {
+    /// The stack of each player at the beginning of the hand.
+    pub stack: i32,
+
+    /// The blinds posted by position 0 and position 1, respectively.
+    pub blinds: [i32; 2],
+}
+
+impl Default for AcpcGameDef {
+    /// Returns the rules of the ACPC heads-up no-limit competition.
+    #[inline]
+    fn default() -> Self {
+        Self {
+            stack: 20000,
+            blinds: [100, 50],
+        }
+    }
+}
+
+/// A parsed `MATCHSTATE` message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AcpcMatchState {
+    /// The message without the `MATCHSTATE:` prefix and the line terminator.
+    pub raw: String,
+
+    /// The position of the client.
+    pub position: usize,
+
+    /// The hand number.
+    pub hand_number: u64,
+
+    /// The actions of each betting round.
+    pub betting: Vec<Vec<AcpcAction>>,
+
+    /// The hole cards of the client, if known.
+    pub hole_cards: Option<(Card, Card)>,
+
+    /// The board cards.
+    pub board: Vec<Card>,
+}
+
+/// Summary of the betting computed from an [`AcpcMatchState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcpcBettingState {
+    /// The current betting round (0: preflop, 1: flop, 2: turn, 3: river).
+    pub round: usize,
+
+    /// The total commitment of each position in the hand.
+    pub committed: [i32; 2],
+
+    /// The position to act, or `None` if the hand is over.
+    pub to_act: Option<usize>,
+
+    /// The size of the last bet or raise in the current round (used for the minimum raise).
+    pub last_raise_size: i32,
+}
+
+impl AcpcMatchState {
+    /// Parses a `MATCHSTATE` message.
+    pub fn parse(message: &str) -> Result<Self, Error> {
+        let message = message.trim_end_matches(['\r', '\n']);
+        let raw = message
+            .strip_prefix("MATCHSTATE:")
+            .ok_or_else(|| Error::invalid_data(format!("Not a MATCHSTATE message: {message}")))?;
+
+        let fields = raw.split(':').collect::<Vec<_>>();
+        if fields.len() != 4 {
+            return Err(Error::invalid_data(format!(
+                "Invalid number of fields: {message}"
+            )));
+        }
+
+        let position = fields[0]
+            .parse::<usize>()
+            .ok()
+            .filter(|&p| p < 2)
+            .ok_or_else(|| Error::invalid_data(format!("Invalid position: {}", fields[0])))?;
+
+        let hand_number = fields[1]
+            .parse::<u64>()
+            .map_err(|_| Error::invalid_data(format!("Invalid hand number: {}", fields[1])))?;
+
+        let betting = parse_betting(fields[2])?;
+        let (hole_cards, board) = parse_cards(fields[3], position)?;
+
+        Ok(Self {
+            raw: raw.to_string(),
+            position,
+            hand_number,
+            betting,
+            hole_cards,
+            board,
+        })
+    }
+
+    /// Computes the betting state under the given rules.
+    pub fn betting_state(&self, def: &AcpcGameDef) -> AcpcBettingState {
+        let mut committed = def.blinds;
+        let mut to_act = Some(1);
+        let mut last_raise_size = def.blinds[0].max(def.blinds[1]);
+        let round = self.betting.len().max(1) - 1;
+
+        for (r, actions) in self.betting.iter().enumerate() {
+            let first = if r == 0 { 1 } else { 0 };
+            if r > 0 {
+                last_raise_size = def.blinds[0].max(def.blinds[1]);
+            }
+
+            for (i, &action) in actions.iter().enumerate() {
+                let player = (first + i) % 2;
+                match action {
+                    AcpcAction::Fold => return self.finished(round, committed, last_raise_size),
+                    AcpcAction::Call => committed[player] = committed[player ^ 1],
+                    AcpcAction::Raise(amount) => {
+                        last_raise_size = last_raise_size.max(amount - committed[player ^ 1]);
+                        committed[player] = amount;
+                    }
+                }
+            }
+
+            let is_round_over = actions.len() >= 2 && actions.last() == Some(&AcpcAction::Call);
+            let is_all_in = committed[0] == committed[1] && committed[0] >= def.stack;
+            to_act = if is_all_in || (is_round_over && r == 3) {
+                None
+            } else if is_round_over {
+                Some(0)
+            } else {
+                Some((first + actions.len()) % 2)
+            };
+        }
+
+        AcpcBettingState {
+            round,
+            committed,
+            to_act,
+            last_raise_size,
+        }
+    }
+
+    /// Returns the response message for the given action.
+    pub fn response(&self, action: AcpcAction) -> String {
+        let action = match action {
+            AcpcAction::Fold => "f".to_string(),
+            AcpcAction::Call => "c".to_string(),
+            AcpcAction::Raise(amount) => format!("r{amount}"),
+        };
+        format!("MATCHSTATE:{}:{action}\r\n", self.raw)
+    }
+
+    fn finished(&self, round: usize, committed: [i32; 2], size: i32) -> AcpcBettingState {
+        AcpcBettingState {
+            round,
+            committed,
+            to_act: None,
+            last_raise_size: size,
+        }
+    }
+}
+
+type FallbackPolicy = dyn FnMut(&AcpcMatchState, &AcpcBettingState) -> AcpcAction + Send;
+
+/// Agent that plays using solved postflop blueprints.
+///
+/// Each blueprint is a solved [`PostFlopGame`] rooted at the flop whose starting pot corresponds
+/// to the pot after the preflop betting. The betting is translated onto the blueprint and the
+/// agent's actions are sampled from the blueprint strategy and translated back in the same way
+/// as [`SlumbotAdapter`]. When no blueprint is applicable (e.g., on the preflop, on an unknown
+/// flop, or with a hand outside of the blueprint range), the action is delegated to the fallback
+/// policy.
+///
+/// [`SlumbotAdapter`]: crate::SlumbotAdapter
+pub struct AcpcAgent {
+    def: AcpcGameDef,
+    player: BlueprintPlayer,
+    fallback: Box<FallbackPolicy>,
+}
+
+impl AcpcAgent {
+    /// Creates a new agent. The fallback policy always checks or calls.
+    pub fn new(def: AcpcGameDef, seed: u64) -> Self {
+        Self {
+            def,
+            player: BlueprintPlayer::new(def.stack, seed),
+            fallback: Box::new(|_, _| AcpcAction::Call),
+        }
+    }
+
+    /// Returns the seed of the sampling of the actions.
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.player.seed()
+    }
+
+    /// Adds a solved blueprint. Returns an error if the game is not solved or not rooted at the
+    /// flop.
+    pub fn add_blueprint(&mut self, game: PostFlopGame) -> Result<(), Error> {
+        self.player.add_blueprint(game)
+    }
+
+    /// Sets the policy used for preflop decisions and when no blueprint is applicable.
+    pub fn set_fallback<F>(&mut self, fallback: F)
+    where
+        F: FnMut(&AcpcMatchState, &AcpcBettingState) -> AcpcAction + Send + 'static,
+    {
+        self.fallback = Box::new(fallback);
+    }
+
+    /// Returns the action to take in the given state, or `None` if it is not the agent's turn.
+    pub fn act(&mut self, state: &AcpcMatchState) -> Option<AcpcAction> {
+        let betting = state.betting_state(&self.def);
+        if betting.to_act != Some(state.position) {
+            return None;
+        }
+
+        let action = match self.blueprint_action(state, &betting) {
+            Ok(action) => action,
+            Err(_) => to_hand_action((self.fallback)(state, &betting)),
+        };
+
+        let action = legalize(
+            action,
+            betting.committed,
+            betting.last_raise_size,
+            self.def.stack,
+            state.position,
+        );
+
+        Some(match action {
+            HandAction::Fold => AcpcAction::Fold,
+            HandAction::Check | HandAction::Call => AcpcAction::Call,
+            HandAction::Raise(amount) => AcpcAction::Raise(amount),
+        })
+    }
+
+    /// Plays the current hand on a blueprint.
+    fn blueprint_action(
+        &mut self,
+        state: &AcpcMatchState,
+        betting: &AcpcBettingState,
+    ) -> Result<HandAction, Error> {
+        if betting.round == 0 {
+            return Err(Error::Config("No blueprint for the preflop".to_string()));
+        }
+
+        let hole_cards = state
+            .hole_cards
+            .ok_or_else(|| Error::Config("Hole cards are unknown".to_string()))?;
+
+        let preflop_committed = AcpcMatchState {
+            betting: state.betting[..1].to_vec(),
+            ..Default::default()
+        }
+        .betting_state(&self.def)
+        .committed[0];
+
+        let streets = state.betting[1..]
+            .iter()
+            .map(|actions| actions.iter().map(|&a| to_hand_action(a)).collect())
+            .collect::<Vec<_>>();
+
+        self.player
+            .action(hole_cards, &state.board, preflop_committed, &streets)
+    }
+}
+
+/// Connects to an ACPC dealer and plays until the connection is closed.
+pub fn run_acpc_client<A: ToSocketAddrs>(addr: A, agent: &mut AcpcAgent) -> Result<(), Error> {
+    let stream = TcpStream::connect(addr).map_err(|e| Error::io(e, "Failed to connect"))?;
+    let mut writer = stream
+        .try_clone()
+        .map_err(|e| Error::io(e, "Failed to clone stream"))?;
+    let reader = BufReader::new(stream);
+
+    writer
+        .write_all(b"VERSION:2.0.0\r\n")
+        .map_err(|e| Error::io(e, "Failed to send version"))?;
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| Error::io(e, "Failed to read message"))?;
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        let state = AcpcMatchState::parse(&line)?;
+        if let Some(action) = agent.act(&state) {
+            writer
+                .write_all(state.response(action).as_bytes())
+                .map_err(|e| Error::io(e, "Failed to send action"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts the action into an action of the blueprint player.
+fn to_hand_action(action: AcpcAction) -> HandAction {
+    match action {
+        AcpcAction::Fold => HandAction::Fold,
+        AcpcAction::Call => HandAction::Call,
+        AcpcAction::Raise(amount) => HandAction::Raise(amount),
+    }
+}
+
+fn parse_betting(s: &str) -> Result<Vec<Vec<AcpcAction>>, Error> {
+    let mut rounds = vec![Vec::new()];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            'f' => rounds.last_mut().unwrap().push(AcpcAction::Fold),
+            'c' | 'k' => rounds.last_mut().unwrap().push(AcpcAction::Call),
+            'r' => {
+                let mut digits = String::new();
+                while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                    digits.push(d);
+                    chars.next();
+                }
+                let amount = digits
+                    .parse::<i32>()
+                    .map_err(|_| Error::invalid_data(format!("Invalid raise amount: {s}")))?;
+                rounds.last_mut().unwrap().push(AcpcAction::Raise(amount));
+            }
+            '/' => rounds.push(Vec::new()),
+            _ => return Err(Error::invalid_data(format!("Invalid betting string: {s}"))),
+        }
+    }
+
+    Ok(rounds)
+}
+
+type ParsedCards = (Option<(Card, Card)>, Vec<Card>);
+
+fn parse_cards(s: &str, position: usize) -> Result<ParsedCards, Error> {
+    let mut parts = s.split('/');
+    let holes = parts
+        .next()
+        .unwrap_or_default()
+        .split('|')
+        .collect::<Vec<_>>();
+
+    let hole_cards = match holes.get(position) {
+        Some(hole) if hole.len() == 4 => {
+            Some((card_from_str(&hole[0..2])?, card_from_str(&hole[2..4])?))
+        }
+        Some(&"") => None,
+        _ => return Err(Error::invalid_data(format!("Invalid hole cards: {s}"))),
+    };
+
+    let mut board = Vec::new();
+    for part in parts {
+        let mut chars = part.chars();
+        while chars.as_str().len() >= 2 {
+            board.push(card_from_chars(&mut chars)?);
+        }
+    }
+
+    Ok((hole_cards, board))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action_tree::*;
+    use crate::bet_size::*;
+    use crate::solver::*;
+
+    #[test]
+    fn acpc_parse() {
+        let state = AcpcMatchState::parse("MATCHSTATE:1:31:r300c/cr600:|TdAs/2c6dTh\r\n").unwrap();
+        assert_eq!(state.position, 1);
+        assert_eq!(state.hand_number, 31);
+        assert_eq!(
+            state.betting,
+            vec![
+                vec![AcpcAction::Raise(300), AcpcAction::Call],
+                vec![AcpcAction::Call, AcpcAction::Raise(600)],
+            ]
+        );
+        let hole = (card_from_str("Td").unwrap(), card_from_str("As").unwrap());
+        assert_eq!(state.hole_cards, Some(hole));
+        assert_eq!(state.board, flop_from_str("2c6dTh").unwrap().to_vec());
+
+        let betting = state.betting_state(&AcpcGameDef::default());
+        assert_eq!(betting.round, 1);
+        assert_eq!(betting.committed, [300, 600]);
+        assert_eq!(betting.to_act, Some(0));
+        assert_eq!(betting.last_raise_size, 300);
+        assert_eq!(
+            state.response(AcpcAction::Raise(1200)),
+            "MATCHSTATE:1:31:r300c/cr600:|TdAs/2c6dTh:r1200\r\n"
+        );
+
+        let folded = AcpcMatchState::parse("MATCHSTATE:0:1:f:AsKs|").unwrap();
+        assert_eq!(folded.betting_state(&AcpcGameDef::default()).to_act, None);
+        assert!(AcpcMatchState::parse("MATCHSTATE:2:1::").is_err());
+        assert!(AcpcMatchState::parse("MATCHSTATE:0:1:x:AsKs|").is_err());
+    }
+
+    #[test]
+    fn acpc_agent() {
+        let card_config = CardConfig {
+            range: ["QQ+,AK".parse().unwrap(), "JJ+,AQs+".parse().unwrap()],
+            flop: flop_from_str("2c6dTh").unwrap(),
+            ..Default::default()
+        };
+
+        let bet_sizes = BetSizeOptions::try_from(("50%", "")).unwrap();
+        let tree_config = TreeConfig {
+            starting_pot: 600,
+            effective_stack: 19700,
+            flop_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 10, 0.0, false);
+
+        let mut agent = AcpcAgent::new(AcpcGameDef::default(), 0);
+        agent.set_fallback(|_, _| AcpcAction::Fold);
+        agent.add_blueprint(game).unwrap();
+
+        // preflop: fallback (folding is legal here)
+        let state = AcpcMatchState::parse("MATCHSTATE:1:0::|AsAh").unwrap();
+        assert_eq!(agent.act(&state), Some(AcpcAction::Fold));
+
+        // not our turn
+        let state = AcpcMatchState::parse("MATCHSTATE:1:0:r300c/:|AsAh/Th6d2c").unwrap();
+        assert_eq!(agent.act(&state), None);
+
+        // blueprint: facing a bet of 80% pot, which is translated to the 50% pot bet
+        let state = AcpcMatchState::parse("MATCHSTATE:1:0:r300c/r780:|AsAh/Th6d2c").unwrap();
+        match agent.act(&state).unwrap() {
+            AcpcAction::Fold => panic!("AA should not fold"),
+            AcpcAction::Call => {}
+            AcpcAction::Raise(amount) => assert!((780 + 480..=20000).contains(&amount)),
+        }
+
+        // facing an all-in, which is translated to the all-in of the blueprint
+        let state = AcpcMatchState::parse("MATCHSTATE:1:0:r300c/r20000:|AsAh/Th6d2c").unwrap();
+        assert!(matches!(
+            agent.act(&state).unwrap(),
+            AcpcAction::Call | AcpcAction::Fold
+        ));
+
+        // a hand outside of the blueprint range uses the fallback (checking instead of folding)
+        let state = AcpcMatchState::parse("MATCHSTATE:0:0:r300c/:7c8c|/Th6d2c").unwrap();
+        assert_eq!(agent.act(&state), Some(AcpcAction::Call));
+    }
+}
@@ -1,10 +1,16 @@
 use crate::bet_size::*;
 use crate::card::*;
+use crate::error::*;
 use crate::mutex_like::*;
+use crate::payoff::*;
+use crate::topology::*;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 pub(crate) const PLAYER_OOP: u8 = 0;
 pub(crate) const PLAYER_IP: u8 = 1;
 pub(crate) const PLAYER_CHANCE: u8 = 2; // only used with `PLAYER_CHANCE_FLAG`
@@ -16,6 +22,7 @@ pub(crate) const PLAYER_FOLD_FLAG: u8 = 24;
 /// Available actions of the postflop game.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Action {
     /// (Default value)
     #[default]
@@ -47,6 +54,7 @@ pub enum Action {
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BoardState {
     #[default]
     Flop = 0,
@@ -66,9 +74,11 @@ pub enum BoardState {
 /// let tree_config = TreeConfig {
 ///     initial_state: BoardState::Turn,
 ///     starting_pot: 200,
+///     dead_money: 0,
 ///     effective_stack: 900,
 ///     rake_rate: 0.05,
 ///     rake_cap: 30.0,
+///     payoff_model: PayoffModel::ChipEv,
 ///     flop_bet_sizes: Default::default(),
 ///     turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
 ///     river_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
@@ -77,10 +87,14 @@ pub enum BoardState {
 ///     add_allin_threshold: 1.5,
 ///     force_allin_threshold: 0.15,
 ///     merging_threshold: 0.1,
+///     allin_equity_adjudication: false,
+///     allin_chop_fee_rate: 0.0,
+///     allin_chop_fee_cap: 0.0,
 /// };
 /// ```
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TreeConfig {
     /// Initial state of the game tree (flop, turn, or river).
     pub initial_state: BoardState,
@@ -88,15 +102,34 @@ pub struct TreeConfig {
     /// Starting pot size. Must be greater than `0`.
     pub starting_pot: i32,
 
+    /// Part of the starting pot that was not contributed by the two players (e.g., antes and
+    /// folded blinds). The rest of the starting pot is regarded as contributed equally by both
+    /// players. Must be between `0` and `starting_pot`, inclusive.
+    ///
+    /// The dead money does not affect the solution; it only changes the amount that each player
+    /// is regarded as having invested, which is used for reporting net EVs.
+    pub dead_money: i32,
+
     /// Initial effective stack. Must be greater than `0`.
     pub effective_stack: i32,
 
     /// Rake rate. Must be between `0.0` and `1.0`, inclusive.
+    ///
+    /// The rake, i.e., `min(pot * rake_rate, rake_cap)`, is taken from the pot both at showdown
+    /// and when a player folds (the uncalled bet is not raked). Every pot of a postflop game has
+    /// seen a flop, so the "no flop, no drop" rule is always satisfied; the rake of the pots that
+    /// end preflop is configured by [`PreflopConfig`].
+    ///
+    /// [`PreflopConfig`]: crate::PreflopConfig
     pub rake_rate: f64,
 
     /// Rake cap. Must be non-negative.
     pub rake_cap: f64,
 
+    /// Model converting the chips won or lost at the terminal nodes into the payoffs (e.g., ICM
+    /// for tournaments). The default is [`PayoffModel::ChipEv`].
+    pub payoff_model: PayoffModel,
+
     /// Bet size options of each player for the flop.
     pub flop_bet_sizes: [BetSizeOptions; 2],
 
@@ -131,6 +164,37 @@ pub struct TreeConfig {
     ///
     /// Personal recommendation: around `0.1`
     pub merging_threshold: f64,
+
+    /// Stop dealing the remaining streets when both players are all-in before the river, and
+    /// instead evaluate the showdown by the exact equity over all runouts (the rake is computed
+    /// from the final pot as usual).
+    ///
+    /// This reduces the size of the game tree, while the solution is unchanged. On the other
+    /// hand, the runouts after the all-in cannot be navigated. The enumeration of the runouts
+    /// after a flop all-in can be shared by all nodes with
+    /// [`PostFlopGame::set_allin_equity_cache_enabled`].
+    ///
+    /// [`PostFlopGame::set_allin_equity_cache_enabled`]: crate::PostFlopGame::set_allin_equity_cache_enabled
+    pub allin_equity_adjudication: bool,
+    /// Fee rate of the equity chop when both players are all-in before the river. Must be
+    /// between `0.0` and `1.0`, inclusive, and requires [`allin_equity_adjudication`] if the fee
+    /// is enabled.
+    ///
+    /// This models an agreed EV chop or insurance, common in live games: instead of running out
+    /// the board, the players split the pot by their exact equity after paying a fee of
+    /// `min(pot * allin_chop_fee_rate, allin_chop_fee_cap)`. The fee is taken from the whole pot
+    /// in addition to the rake (both are computed from the pot before any deduction), so each
+    /// player effectively pays the fee in proportion to their equity. Unlike the plain
+    /// adjudication, a positive fee changes the solution by making all-in pots before the river
+    /// more costly.
+    ///
+    /// [`allin_equity_adjudication`]: #structfield.allin_equity_adjudication
+    pub allin_chop_fee_rate: f64,
+
+    /// Fee cap of the equity chop (see [`allin_chop_fee_rate`]). Must be non-negative.
+    ///
+    /// [`allin_chop_fee_rate`]: #structfield.allin_chop_fee_rate
+    pub allin_chop_fee_cap: f64,
 }
 
 /// A struct representing an abstract game tree.
@@ -147,7 +211,6 @@ pub struct ActionTree {
 }
 
 #[derive(Default)]
-#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub(crate) struct ActionTreeNode {
     pub(crate) player: u8,
     pub(crate) board_state: BoardState,
@@ -165,17 +228,34 @@ struct BuildTreeInfo {
     prev_amount: i32,
 }
 
-type EjectedActionTree = (
-    TreeConfig,
-    Vec<Vec<Action>>,
-    Vec<Vec<Action>>,
-    Box<MutexLike<ActionTreeNode>>,
-);
+impl TreeConfig {
+    /// Returns the amount of the starting pot contributed by each player, i.e., half of the
+    /// starting pot excluding the dead money.
+    #[inline]
+    pub fn contribution(&self) -> f64 {
+        0.5 * (self.starting_pot - self.dead_money) as f64
+    }
+
+    /// Returns whether the fee of the equity chop is enabled (see [`allin_chop_fee_rate`]).
+    ///
+    /// [`allin_chop_fee_rate`]: #structfield.allin_chop_fee_rate
+    #[inline]
+    pub fn is_allin_chop_fee_enabled(&self) -> bool {
+        self.allin_chop_fee_rate > 0.0 && self.allin_chop_fee_cap > 0.0
+    }
+
+    /// Returns the fee of the equity chop of the given pot when both players are all-in before
+    /// the river.
+    #[inline]
+    pub(crate) fn allin_chop_fee(&self, pot: f64) -> f64 {
+        (pot * self.allin_chop_fee_rate).min(self.allin_chop_fee_cap)
+    }
+}
 
 impl ActionTree {
     /// Creates a new [`ActionTree`] with the specified configuration.
     #[inline]
-    pub fn new(config: TreeConfig) -> Result<Self, String> {
+    pub fn new(config: TreeConfig) -> Result<Self, Error> {
         Self::check_config(&config)?;
         let mut ret = Self {
             config,
@@ -220,7 +300,7 @@ impl ActionTree {
     ///   must be a bet action (including raise and all-in action).
     /// - Chance actions (i.e., dealing turn and river cards) must be omitted from the `line`.
     #[inline]
-    pub fn add_line(&mut self, line: &[Action]) -> Result<(), String> {
+    pub fn add_line(&mut self, line: &[Action]) -> Result<(), Error> {
         let removed_index = self.removed_lines.iter().position(|x| x == line);
         let is_replaced = self.add_line_recursive(
             &mut self.root.lock(),
@@ -249,7 +329,7 @@ impl ActionTree {
     /// - If the current node is removed by this method, the current node is moved to the nearest
     ///   ancestor node that is not removed.
     #[inline]
-    pub fn remove_line(&mut self, line: &[Action]) -> Result<(), String> {
+    pub fn remove_line(&mut self, line: &[Action]) -> Result<(), Error> {
         Self::remove_line_recursive(&mut self.root.lock(), line)?;
         let was_added = self.added_lines.iter().any(|l| l == line);
         self.added_lines.retain(|l| !l.starts_with(line));
@@ -277,7 +357,7 @@ impl ActionTree {
 
     /// Applies the given action history from the root node.
     #[inline]
-    pub fn apply_history(&mut self, history: &[Action]) -> Result<(), String> {
+    pub fn apply_history(&mut self, history: &[Action]) -> Result<(), Error> {
         self.back_to_root();
         for &action in history {
             self.play(action)?;
@@ -311,10 +391,10 @@ impl ActionTree {
     /// If the current node is a chance node, the chance action is automatically played before
     /// playing the given action.
     #[inline]
-    pub fn play(&mut self, action: Action) -> Result<(), String> {
+    pub fn play(&mut self, action: Action) -> Result<(), Error> {
         let node = self.current_node_skip_chance();
         if !node.actions.contains(&action) {
-            return Err(format!("Action `{action:?}` is not available"));
+            return Err(Error::Tree(format!("Action `{action:?}` is not available")));
         }
 
         self.history.push(action);
@@ -323,9 +403,9 @@ impl ActionTree {
 
     /// Undoes the last action. Returns `Ok(())` if the action is successfully undone.
     #[inline]
-    pub fn undo(&mut self) -> Result<(), String> {
+    pub fn undo(&mut self) -> Result<(), Error> {
         if self.history.is_empty() {
-            return Err("No action to undo".to_string());
+            return Err(Error::Tree("No action to undo".to_string()));
         }
 
         self.history.pop();
@@ -339,7 +419,7 @@ impl ActionTree {
     ///
     /// [`add_line`]: #method.add_line
     #[inline]
-    pub fn add_action(&mut self, action: Action) -> Result<(), String> {
+    pub fn add_action(&mut self, action: Action) -> Result<(), Error> {
         let mut action_line = self.history.clone();
         action_line.push(action);
         self.add_line(&action_line)
@@ -352,7 +432,7 @@ impl ActionTree {
     ///
     /// [`remove_line`]: #method.remove_line
     #[inline]
-    pub fn remove_action(&mut self, action: Action) -> Result<(), String> {
+    pub fn remove_action(&mut self, action: Action) -> Result<(), Error> {
         let mut action_line = self.history.clone();
         action_line.push(action);
         self.remove_line(&action_line)
@@ -365,7 +445,7 @@ impl ActionTree {
     ///
     /// [`remove_line`]: #method.remove_line
     #[inline]
-    pub fn remove_current_node(&mut self) -> Result<(), String> {
+    pub fn remove_current_node(&mut self) -> Result<(), Error> {
         let history = self.history.clone();
         self.remove_line(&history)
     }
@@ -377,10 +457,18 @@ impl ActionTree {
         self.total_bet_amount_recursive(&self.root.lock(), &self.history, info)
     }
 
-    /// Ejects the fields.
+    /// Builds the [`TreeTopology`] of the current tree.
+    ///
+    /// The topology is a compact snapshot that does not change with later modifications of this
+    /// tree.
     #[inline]
-    pub(crate) fn eject(self) -> EjectedActionTree {
-        (self.config, self.added_lines, self.removed_lines, self.root)
+    pub fn topology(&self) -> TreeTopology {
+        TreeTopology::new(
+            self.config.clone(),
+            self.added_lines.clone(),
+            self.removed_lines.clone(),
+            &self.root.lock(),
+        )
     }
 
     /// Returns the reference to the current node.
@@ -413,61 +501,97 @@ impl ActionTree {
 
     /// Checks the configuration.
     #[inline]
-    fn check_config(config: &TreeConfig) -> Result<(), String> {
+    fn check_config(config: &TreeConfig) -> Result<(), Error> {
         if config.starting_pot <= 0 {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Starting pot must be positive: {}",
                 config.starting_pot
-            ));
+            )));
+        }
+
+        if config.dead_money < 0 || config.dead_money > config.starting_pot {
+            return Err(Error::Config(format!(
+                "Dead money must be between 0 and the starting pot: {}",
+                config.dead_money
+            )));
         }
 
         if config.effective_stack <= 0 {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Effective stack must be positive: {}",
                 config.effective_stack
-            ));
+            )));
         }
 
         if config.rake_rate < 0.0 {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Rake rate must be non-negative: {}",
                 config.rake_rate
-            ));
+            )));
         }
 
         if config.rake_rate > 1.0 {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Rake rate must be less than or equal to 1.0: {}",
                 config.rake_rate
-            ));
+            )));
         }
 
         if config.rake_cap < 0.0 {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Rake cap must be non-negative: {}",
                 config.rake_cap
+            )));
+        }
+
+        if config.allin_chop_fee_rate < 0.0 {
+            return Err(Error::Config(format!(
+                "All-in chop fee rate must be non-negative: {}",
+                config.allin_chop_fee_rate
+            )));
+        }
+
+        if config.allin_chop_fee_rate > 1.0 {
+            return Err(Error::Config(format!(
+                "All-in chop fee rate must be less than or equal to 1.0: {}",
+                config.allin_chop_fee_rate
+            )));
+        }
+
+        if config.allin_chop_fee_cap < 0.0 {
+            return Err(Error::Config(format!(
+                "All-in chop fee cap must be non-negative: {}",
+                config.allin_chop_fee_cap
+            )));
+        }
+
+        if config.is_allin_chop_fee_enabled() && !config.allin_equity_adjudication {
+            return Err(Error::Config(
+                "All-in chop fee requires all-in equity adjudication".to_string(),
             ));
         }
 
+        config.payoff_model.check(config.effective_stack)?;
+
         if config.add_allin_threshold < 0.0 {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Add all-in threshold must be non-negative: {}",
                 config.add_allin_threshold
-            ));
+            )));
         }
 
         if config.force_allin_threshold < 0.0 {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Force all-in threshold must be non-negative: {}",
                 config.force_allin_threshold
-            ));
+            )));
         }
 
         if config.merging_threshold < 0.0 {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Merging threshold must be non-negative: {}",
                 config.merging_threshold
-            ));
+            )));
         }
 
         Ok(())
@@ -701,6 +825,7 @@ impl ActionTree {
 
         let player_after_call = match node.board_state {
             BoardState::River => PLAYER_TERMINAL_FLAG,
+            _ if info.allin_flag && self.config.allin_equity_adjudication => PLAYER_TERMINAL_FLAG,
             _ => PLAYER_CHANCE_FLAG | player,
         };
 
@@ -767,13 +892,13 @@ impl ActionTree {
         line: &[Action],
         was_removed: bool,
         info: BuildTreeInfo,
-    ) -> Result<bool, String> {
+    ) -> Result<bool, Error> {
         if line.is_empty() {
-            return Err("Empty line".to_string());
+            return Err(Error::Tree("Empty line".to_string()));
         }
 
         if node.is_terminal() {
-            return Err("Unexpected terminal node".to_string());
+            return Err(Error::Tree("Unexpected terminal node".to_string()));
         }
 
         if node.is_chance() {
@@ -793,7 +918,7 @@ impl ActionTree {
 
         if line.len() > 1 {
             if search_result.is_err() {
-                return Err(format!("Action does not exist: {action:?}"));
+                return Err(Error::Tree(format!("Action does not exist: {action:?}")));
             }
 
             return self.add_line_recursive(
@@ -805,12 +930,12 @@ impl ActionTree {
         }
 
         if search_result.is_ok() {
-            return Err(format!("Action already exists: {action:?}"));
+            return Err(Error::Tree(format!("Action already exists: {action:?}")));
         }
 
         let is_bet_action = matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_));
         if info.allin_flag && is_bet_action {
-            return Err(format!("Bet action after all-in: {action:?}"));
+            return Err(Error::Tree(format!("Bet action after all-in: {action:?}")));
         }
 
         let player_stack = info.stack[player as usize];
@@ -847,23 +972,24 @@ impl ActionTree {
         if !was_removed && !is_valid_bet {
             match action {
                 Action::Bet(amount) | Action::Raise(amount) => {
-                    return Err(format!(
+                    return Err(Error::Tree(format!(
                         "Invalid bet amount: {amount} (min: {min_amount}, max: {max_amount})"
-                    ));
+                    )));
                 }
                 Action::AllIn(amount) => {
-                    return Err(format!(
+                    return Err(Error::Tree(format!(
                         "Invalid all-in amount: {amount} (expected: {max_amount})"
-                    ));
+                    )));
                 }
                 _ => {
-                    return Err(format!("Invalid action: {action:?}"));
+                    return Err(Error::Tree(format!("Invalid action: {action:?}")));
                 }
             };
         }
 
         let player_after_call = match node.board_state {
             BoardState::River => PLAYER_TERMINAL_FLAG,
+            _ if info.allin_flag && self.config.allin_equity_adjudication => PLAYER_TERMINAL_FLAG,
             _ => PLAYER_CHANCE_FLAG | player,
         };
 
@@ -911,13 +1037,13 @@ impl ActionTree {
     }
 
     /// Recursive function to remove a given line from the tree.
-    fn remove_line_recursive(node: &mut ActionTreeNode, line: &[Action]) -> Result<(), String> {
+    fn remove_line_recursive(node: &mut ActionTreeNode, line: &[Action]) -> Result<(), Error> {
         if line.is_empty() {
-            return Err("Empty line".to_string());
+            return Err(Error::Tree("Empty line".to_string()));
         }
 
         if node.is_terminal() {
-            return Err("Unexpected terminal node".to_string());
+            return Err(Error::Tree("Unexpected terminal node".to_string()));
         }
 
         if node.is_chance() {
@@ -927,7 +1053,7 @@ impl ActionTree {
         let action = line[0];
         let search_result = node.actions.binary_search(&action);
         if search_result.is_err() {
-            return Err(format!("Action does not exist: {action:?}"));
+            return Err(Error::Tree(format!("Action does not exist: {action:?}")));
         }
 
         if line.len() > 1 {
@@ -1039,31 +1165,6 @@ impl BuildTreeInfo {
     }
 }
 
-/// Returns the number of action nodes of [flop, turn, river].
-pub(crate) fn count_num_action_nodes(node: &ActionTreeNode) -> [u64; 3] {
-    let mut ret = [0, 0, 0];
-    count_num_action_nodes_recursive(node, 0, &mut ret);
-    if ret[1] == 0 {
-        ret = [0, 0, ret[0]];
-    } else if ret[2] == 0 {
-        ret = [0, ret[0], ret[1]];
-    }
-    ret
-}
-
-fn count_num_action_nodes_recursive(node: &ActionTreeNode, street: usize, count: &mut [u64; 3]) {
-    count[street] += 1;
-    if node.is_terminal() {
-        // do nothing
-    } else if node.is_chance() {
-        count_num_action_nodes_recursive(&node.children[0].lock(), street + 1, count);
-    } else {
-        for child in &node.children {
-            count_num_action_nodes_recursive(&child.lock(), street, count);
-        }
-    }
-}
-
 fn merge_bet_actions(actions: Vec<Action>, pot: i32, offset: i32, param: f64) -> Vec<Action> {
     const EPS: f64 = 1e-12;
 
@@ -0,0 +1,91 @@
+use crate::error::*;
+use core_affinity::CoreId;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::io;
+
+/// Returns the IDs of the logical processors to which the worker threads are pinned.
+///
+/// On Linux, only the first logical processor of each physical core is returned, so that two
+/// workers never share a core as SMT siblings: the CFR updates are bound by the memory bandwidth,
+/// and the siblings compete for the same caches. On the other platforms, the topology is not
+/// available and all logical processors are returned.
+///
+/// Returns an error if the logical processors cannot be enumerated.
+pub fn physical_core_ids() -> Result<Vec<usize>, Error> {
+    let core_ids = core_affinity::get_core_ids()
+        .ok_or_else(core_ids_error)?
+        .into_iter()
+        .map(|core| core.id)
+        .collect::<Vec<_>>();
+
+    if core_ids.is_empty() {
+        return Err(core_ids_error());
+    }
+
+    Ok(core_ids
+        .into_iter()
+        .filter(|&id| first_sibling(id).unwrap_or(id) == id)
+        .collect())
+}
+
+/// Builds a rayon thread pool whose workers are pinned to the physical cores (see
+/// [`physical_core_ids`]).
+///
+/// `num_threads` defaults to the number of physical cores. If it is larger, the workers are
+/// assigned to the cores in a round-robin manner.
+pub fn build_pinned_thread_pool(num_threads: Option<usize>) -> Result<ThreadPool, Error> {
+    pinned_thread_pool_builder(num_threads)?
+        .build()
+        .map_err(|e| Error::Solver(e.to_string()))
+}
+
+/// Initializes the global rayon thread pool with the workers pinned to the physical cores (see
+/// [`build_pinned_thread_pool`]).
+///
+/// This function must be called before the global thread pool is used; otherwise returns an
+/// error.
+pub fn init_pinned_global_thread_pool(num_threads: Option<usize>) -> Result<(), Error> {
+    pinned_thread_pool_builder(num_threads)?
+        .build_global()
+        .map_err(|e| Error::Solver(e.to_string()))
+}
+
+fn pinned_thread_pool_builder(num_threads: Option<usize>) -> Result<ThreadPoolBuilder, Error> {
+    if num_threads == Some(0) {
+        return Err(Error::Config(
+            "Number of threads must be positive".to_string(),
+        ));
+    }
+
+    let core_ids = physical_core_ids()?;
+    let num_threads = num_threads.unwrap_or(core_ids.len());
+
+    Ok(ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .start_handler(move |index| {
+            let id = core_ids[index % core_ids.len()];
+            core_affinity::set_for_current(CoreId { id });
+        }))
+}
+
+fn core_ids_error() -> Error {
+    Error::Io(io::Error::other("Failed to get core IDs"))
+}
+
+/// Returns the smallest ID of the SMT siblings of the logical processor `id`.
+#[cfg(target_os = "linux")]
+fn first_sibling(id: usize) -> Option<usize> {
+    let path = format!("/sys/devices/system/cpu/cpu{id}/topology/thread_siblings_list");
+    let list = std::fs::read_to_string(path).ok()?;
+
+    // e.g., "0,8" or "0-1"
+    list.trim()
+        .split([',', '-'])
+        .filter_map(|s| s.parse().ok())
+        .min()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn first_sibling(_id: usize) -> Option<usize> {
+    None
+}
@@ -0,0 +1,179 @@
+use crate::error::*;
+use crate::game::*;
+use arrow_array::{ArrayRef, Float32Array, RecordBatch, StringArray, UInt32Array, UInt8Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::io;
+use std::sync::Arc;
+
+#[cfg(feature = "parquet")]
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+
+#[cfg(feature = "parquet")]
+use crate::atomic_file::*;
+
+#[cfg(feature = "parquet")]
+use std::{io::Write, path::Path};
+
+/// The default number of rows in a record batch.
+pub const DEFAULT_BATCH_ROWS: usize = 1 << 16;
+
+/// Returns the Arrow schema of the strategy table (see [`StrategyTable`] for the columns).
+pub fn strategy_table_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("node", DataType::UInt32, false),
+        Field::new("line", DataType::Utf8, false),
+        Field::new("pio_path", DataType::Utf8, false),
+        Field::new("board", DataType::Utf8, false),
+        Field::new("player", DataType::UInt8, false),
+        Field::new("hand", DataType::Utf8, false),
+        Field::new("action", DataType::Utf8, false),
+        Field::new("frequency", DataType::Float32, false),
+        Field::new("ev", DataType::Float32, false),
+        Field::new("reach", DataType::Float32, false),
+    ]))
+}
+
+impl StrategyTable {
+    /// Converts the table into an Arrow record batch with [`strategy_table_schema`].
+    pub fn to_record_batch(&self) -> Result<RecordBatch, Error> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt32Array::from(self.node.clone())),
+            Arc::new(StringArray::from(self.line.clone())),
+            Arc::new(StringArray::from(self.pio_path.clone())),
+            Arc::new(StringArray::from(self.board.clone())),
+            Arc::new(UInt8Array::from(self.player.clone())),
+            Arc::new(StringArray::from(self.hand.clone())),
+            Arc::new(StringArray::from(self.action.clone())),
+            Arc::new(Float32Array::from(self.frequency.clone())),
+            Arc::new(Float32Array::from(self.ev.clone())),
+            Arc::new(Float32Array::from(self.reach.clone())),
+        ];
+
+        RecordBatch::try_new(strategy_table_schema(), columns).map_err(arrow_error)
+    }
+}
+
+/// Exports the strategy table of a solved game as Arrow record batches of at least
+/// `batch_rows` rows (except the last one).
+///
+/// See [`PostFlopGame::strategy_table_chunks`] for the rows and the errors.
+pub fn export_record_batches(
+    game: &mut PostFlopGame,
+    batch_rows: usize,
+) -> Result<Vec<RecordBatch>, Error> {
+    let mut ret = Vec::new();
+    game.strategy_table_chunks(batch_rows, |table| {
+        ret.push(table.to_record_batch()?);
+        Ok(())
+    })?;
+    Ok(ret)
+}
+
+/// Writes the strategy table of a solved game into `writer` in the Parquet format.
+///
+/// The table is streamed in record batches of [`DEFAULT_BATCH_ROWS`] rows, so the memory usage
+/// does not grow with the size of the tree. The string columns are dictionary-encoded and the
+/// file is compressed with Snappy, which keeps the files of large libraries (e.g., all 1,755
+/// strategically distinct flops) much smaller than CSV.
+///
+/// See [`PostFlopGame::strategy_table_chunks`] for the rows and the errors.
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W: Write + Send>(game: &mut PostFlopGame, writer: W) -> Result<(), Error> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut writer = ArrowWriter::try_new(writer, strategy_table_schema(), Some(properties))
+        .map_err(arrow_error)?;
+
+    game.strategy_table_chunks(DEFAULT_BATCH_ROWS, |table| {
+        let batch = table.to_record_batch()?;
+        writer.write(&batch).map_err(arrow_error)
+    })?;
+
+    writer.close().map_err(arrow_error)?;
+    Ok(())
+}
+
+/// Writes the strategy table of a solved game into the file at `path` in the Parquet format.
+///
+/// See [`write_parquet`] for the details. The file is replaced atomically, so an interrupted
+/// write leaves the previous file intact (see [`interrupted_save_path`]).
+#[cfg(feature = "parquet")]
+pub fn write_parquet_file<P: AsRef<Path>>(game: &mut PostFlopGame, path: P) -> Result<(), Error> {
+    let path = path.as_ref();
+    let mut file = AtomicFile::create(path)
+        .map_err(|e| Error::io(e, &format!("Failed to create file {}", path.display())))?;
+    write_parquet(game, &mut file)?;
+    file.commit()
+        .map_err(|e| Error::io(e, &format!("Failed to write file {}", path.display())))
+}
+
+/// Converts an error of Arrow or Parquet into an [`Error::Io`].
+#[inline]
+fn arrow_error<E: std::error::Error + Send + Sync + 'static>(e: E) -> Error {
+    Error::Io(io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action_tree::*;
+    use crate::card::*;
+    use crate::range::*;
+    use crate::solver::*;
+    use arrow_array::Array;
+
+    fn solved_game() -> PostFlopGame {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            river: card_from_str("2s").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 300,
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 10, 0.0, false);
+        game
+    }
+
+    #[test]
+    fn record_batches() {
+        let mut game = solved_game();
+        let table = game.strategy_table().unwrap();
+        let batches = export_record_batches(&mut game, 10).unwrap();
+
+        let num_rows = batches.iter().map(|b| b.num_rows()).sum::<usize>();
+        assert_eq!(num_rows, table.len());
+        assert!(batches
+            .iter()
+            .all(|b| b.schema() == strategy_table_schema()));
+
+        let frequency = batches[0]
+            .column(7)
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .unwrap();
+        assert_eq!(frequency.value(0), table.frequency[0]);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet() {
+        let mut game = solved_game();
+        let mut buf = Vec::new();
+        write_parquet(&mut game, &mut buf).unwrap();
+        assert_eq!(&buf[..4], b"PAR1");
+    }
+}
@@ -0,0 +1,181 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// The extension appended to the file name of the temporary file of a save in progress.
+const PARTIAL_EXTENSION: &str = ".partial";
+
+/// A file that replaces the file at the destination path only when the writing is completed.
+///
+/// The content is written to a temporary file next to the destination (the destination path
+/// with [`PARTIAL_EXTENSION`] appended), which is flushed to the disk and renamed over the
+/// destination by [`commit`]. Since the rename is atomic, the destination is either the previous
+/// file or the complete new file even if the process is killed or the machine loses power while
+/// saving. If the writing or the commit fails or the `AtomicFile` is dropped without being
+/// committed, the temporary file is removed; a temporary file left by a crashed process can be
+/// found by [`interrupted_save_path`].
+///
+/// Concurrent saves to the same destination are not supported.
+///
+/// [`commit`]: AtomicFile::commit
+pub(crate) struct AtomicFile {
+    writer: Option<BufWriter<File>>,
+    path: PathBuf,
+    temp_path: PathBuf,
+}
+
+impl AtomicFile {
+    /// Creates the temporary file of a save to `path`.
+    pub(crate) fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let temp_path = partial_path(&path);
+        let file = File::create(&temp_path)?;
+        Ok(Self {
+            writer: Some(BufWriter::new(file)),
+            path,
+            temp_path,
+        })
+    }
+
+    /// Flushes the content to the disk and renames the temporary file to the destination.
+    ///
+    /// If an error occurs before the rename, the temporary file is removed and the destination is
+    /// left untouched. A failure to sync the directory after the rename is ignored because the
+    /// destination has already been replaced; in that case, the rename may be lost on a power
+    /// failure, but the destination is still either the previous file or the new file.
+    pub(crate) fn commit(mut self) -> io::Result<()> {
+        let result = self
+            .writer
+            .take()
+            .unwrap()
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .and_then(|file| file.sync_all())
+            .and_then(|()| fs::rename(&self.temp_path, &self.path));
+
+        if let Err(e) = result {
+            let _ = fs::remove_file(&self.temp_path);
+            return Err(e);
+        }
+
+        // the rename itself is persisted by syncing the directory (not supported on Windows)
+        #[cfg(unix)]
+        if let Some(dir) = self.path.parent() {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            let _ = File::open(dir).and_then(|dir| dir.sync_all());
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for AtomicFile {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.as_mut().unwrap().write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.as_mut().unwrap().flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // not committed: discard the partially written file
+        if let Some(writer) = self.writer.take() {
+            drop(writer.into_parts());
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Returns the path of the temporary file of a save to `path`.
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(PARTIAL_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Returns the path of the partially written file left by an interrupted save to `path`, or
+/// `None` if there is no such file.
+///
+/// The files are saved to a temporary file next to the destination (the destination path with
+/// `.partial` appended), which replaces the destination only after it is completely written and
+/// flushed to the disk. Therefore, a process killed while saving leaves the previous file intact
+/// and the temporary file behind, which this function detects so that the application can
+/// report the interrupted save and remove the temporary file. This applies to the functions that
+/// save to a file, such as `save_data_to_file`, `save_strategy_only_to_file`,
+/// `GoldenStrategies::save`, and `write_parquet_file`.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::interrupted_save_path;
+///
+/// if let Some(partial) = interrupted_save_path("game.bin") {
+///     eprintln!("Previous save was interrupted: {}", partial.display());
+///     std::fs::remove_file(partial).unwrap();
+/// }
+/// ```
+pub fn interrupted_save_path<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
+    let partial = partial_path(path.as_ref());
+    partial.is_file().then_some(partial)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atomic_file() {
+        let path = Path::new("tmpfile.atomic");
+        fs::write(path, b"previous").unwrap();
+
+        // dropped without commit: the previous file is kept
+        let mut file = AtomicFile::create(path).unwrap();
+        file.write_all(b"interrupted").unwrap();
+        assert_eq!(interrupted_save_path(path), Some(partial_path(path)));
+        drop(file);
+        assert_eq!(fs::read(path).unwrap(), b"previous");
+        assert_eq!(interrupted_save_path(path), None);
+
+        // committed: the file is replaced
+        let mut file = AtomicFile::create(path).unwrap();
+        file.write_all(b"complete").unwrap();
+        file.commit().unwrap();
+        assert_eq!(fs::read(path).unwrap(), b"complete");
+        assert_eq!(interrupted_save_path(path), None);
+
+        // a crashed process leaves the partial file behind
+        let mut file = AtomicFile::create(path).unwrap();
+        file.write_all(b"crashed").unwrap();
+        std::mem::forget(file);
+        let partial = interrupted_save_path(path).unwrap();
+        assert_eq!(partial, Path::new("tmpfile.atomic.partial"));
+        assert_eq!(fs::read(path).unwrap(), b"complete");
+
+        fs::remove_file(partial).unwrap();
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn atomic_file_commit_failure() {
+        // a file cannot be renamed over a directory
+        let path = Path::new("tmpdir.atomic");
+        fs::create_dir_all(path.join("child")).unwrap();
+
+        let mut file = AtomicFile::create(path).unwrap();
+        file.write_all(b"content").unwrap();
+        assert!(file.commit().is_err());
+        assert_eq!(interrupted_save_path(path), None);
+        assert!(path.join("child").is_dir());
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,311 @@
+use crate::interface::*;
+use crate::rng::*;
+use crate::sliceop::*;
+use crate::solver::*;
+use crate::utility::*;
+
+/// Result of [`solve_averaged`].
+#[derive(Debug, Clone)]
+pub struct AveragedSolve<T> {
+    /// The finalized game holding the averaged strategy (the game built for the first run).
+    pub game: T,
+
+    /// The exploitability of the strategy obtained by each run, before averaging.
+    pub run_exploitabilities: Vec<f32>,
+
+    /// The exploitability of the averaged strategy.
+    pub exploitability: f32,
+}
+
+/// Solves `num_runs` independent instances of the same game and averages the resulting
+/// strategies.
+///
+/// `build` is called with the index of the run and must return a game that is ready to be solved
+/// (i.e., with the memory allocated); all runs must build the same tree. Each run is solved as
+/// in [`solve_with_config`] until `max_num_iterations` or `target_exploitability` is reached, but
+/// the runs start from different points: the first run starts from the uniform strategy, and the
+/// other runs from random regrets (see [`randomize_regrets`]; the hands exchanged by isomorphic
+/// chances share the same values). The seeds of the runs, including the seed of
+/// [`SolverAlgorithm::ChanceSamplingMccfr`], are derived from `seed` with [`SolverRng::split`],
+/// so the whole process is reproducible.
+///
+/// The strategies are averaged per decision node and private hand, weighted by the probabilities
+/// with which each run reaches the node with the hand. The averaged strategy is therefore
+/// equivalent to playing a uniformly chosen run, so its exploitability does not exceed the
+/// average exploitability of the runs, while the noise specific to each run (e.g., the rounding
+/// errors of the compressed storage and the variance of the sampling) is smoothed out. Note that
+/// a plain average of the action frequencies would not have this property when the runs converge
+/// to different equilibria. The averaged strategy is written into the game of the first run,
+/// which is then finalized, and its exploitability is computed as a check. Only two games are
+/// kept in memory at once.
+///
+/// Panics if `num_runs` is zero or a built game is not ready.
+///
+/// # Examples
+/// ```ignore
+/// let result = solve_averaged(
+///     |_| {
+///         let mut game = PostFlopGame::with_config(card_config.clone(), tree.clone()).unwrap();
+///         game.allocate_memory(true);
+///         game
+///     },
+///     4,
+///     1000,
+///     target_exploitability,
+///     &SolverConfig::default(),
+///     42,
+/// );
+/// println!("{:?} -> {}", result.run_exploitabilities, result.exploitability);
+/// ```
+pub fn solve_averaged<T, F>(
+    mut build: F,
+    num_runs: usize,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    config: &SolverConfig,
+    seed: u64,
+) -> AveragedSolve<T>
+where
+    T: Game,
+    F: FnMut(usize) -> T,
+{
+    if num_runs == 0 {
+        panic!("`num_runs` must be positive");
+    }
+
+    let rng = SolverRng::new(seed);
+    let mut first_game = None;
+    let mut sum = Vec::new();
+    let mut run_exploitabilities = Vec::with_capacity(num_runs);
+
+    for run in 0..num_runs {
+        let mut game = build(run);
+        let run_rng = rng.split(run as u64);
+
+        if run > 0 {
+            let classes = isomorphic_hand_classes(&game);
+            randomize_regrets_with_classes(&mut game, run_rng.seed(), &classes);
+        }
+
+        let mut run_config = *config;
+        if let SolverAlgorithm::ChanceSamplingMccfr { seed } = &mut run_config.algorithm {
+            *seed = run_rng.split(1).seed();
+        }
+
+        let exploitability = solve_without_finalization(
+            &game,
+            max_num_iterations,
+            target_exploitability,
+            &run_config,
+        );
+        run_exploitabilities.push(exploitability);
+
+        let reach = [0, 1].map(|player| game.initial_weights(player).to_vec());
+        let mut index = 0;
+        accumulate_strategy_recursive(&game, &game.root(), &reach, &mut sum, &mut index);
+
+        if run == 0 {
+            first_game = Some(game);
+        }
+    }
+
+    let mut game = first_game.unwrap();
+    let mut index = 0;
+    write_strategy_recursive(&game, &mut game.root(), &sum, &mut index);
+
+    finalize(&mut game);
+    let exploitability = compute_exploitability(&game);
+
+    AveragedSolve {
+        game,
+        run_exploitabilities,
+        exploitability,
+    }
+}
+
+/// Runs the iterations of [`solve_with_config`] without finalizing the game, and returns the
+/// exploitability of the obtained strategy.
+fn solve_without_finalization<T: Game>(
+    game: &T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    config: &SolverConfig,
+) -> f32 {
+    let mut exploitability = compute_exploitability(game);
+    game.update_frozen_streets(exploitability);
+
+    // the exploitability is checked at the same iterations as in `solve_with_config`
+    let mut controller = CheckController::new(config.check_schedule, target_exploitability);
+    let mut interval = controller.next_interval(0, exploitability);
+
+    let mut t = 0;
+    while t < max_num_iterations && exploitability > target_exploitability {
+        let num_iterations = (max_num_iterations - t).min(interval);
+        exploitability =
+            solve_steps(game, t, num_iterations, config).unwrap_or_else(|e| panic!("{e}"));
+        t += num_iterations;
+        interval = controller.next_interval(t, exploitability);
+    }
+
+    exploitability
+}
+
+/// Returns the classes of the private hands that are exchanged by the isomorphic chances, as
+/// the smallest index of the hands in the class of each hand.
+///
+/// The subtree of an isomorphic chance shares the strategies of the swapped hands, so a random
+/// initialization must not distinguish them for the averaged strategy to be well-defined.
+fn isomorphic_hand_classes<T: Game>(game: &T) -> [Vec<usize>; 2] {
+    let mut classes = [0, 1].map(|player| (0..game.num_private_hands(player)).collect());
+    isomorphic_hand_classes_recursive(game, &game.root(), &mut classes);
+
+    // every parent has a smaller index than its children, so a single pass flattens the trees
+    for class in &mut classes {
+        for hand in 0..class.len() {
+            class[hand] = class[class[hand]];
+        }
+    }
+
+    classes
+}
+
+fn isomorphic_hand_classes_recursive<T: Game>(
+    game: &T,
+    node: &T::Node,
+    classes: &mut [Vec<usize>; 2],
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if node.is_chance() {
+        for i in 0..game.isomorphic_chances(node).len() {
+            let swap = game.isomorphic_swap(node, i);
+            for player in 0..2 {
+                for &(a, b) in &swap[player] {
+                    union_classes(&mut classes[player], a as usize, b as usize);
+                }
+            }
+        }
+    }
+
+    for action in 0..node.num_actions() {
+        isomorphic_hand_classes_recursive(game, &node.play(action), classes);
+    }
+}
+
+/// Merges the classes of `a` and `b`, keeping the smaller index as the root.
+fn union_classes(class: &mut [usize], a: usize, b: usize) {
+    let find = |class: &[usize], mut x: usize| {
+        while class[x] != x {
+            x = class[x];
+        }
+        x
+    };
+
+    let (a, b) = (find(class, a), find(class, b));
+    class[a.max(b)] = a.min(b);
+}
+
+/// Adds the strategies of the decision nodes weighted by the reach probabilities of the acting
+/// player into `sum` in depth-first order.
+///
+/// `reach` is the reach probabilities of each player at `node`, including the initial weights.
+fn accumulate_strategy_recursive<T: Game>(
+    game: &T,
+    node: &T::Node,
+    reach: &[Vec<f32>; 2],
+    sum: &mut Vec<Vec<f32>>,
+    index: &mut usize,
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if node.is_chance() {
+        let isomorphic_chances = game.isomorphic_chances(node);
+        for action in 0..node.num_actions() {
+            // the subtree also stands for the isomorphic chances mapped to it
+            let mut child_reach = reach.clone();
+            for (i, &isomorphic_index) in isomorphic_chances.iter().enumerate() {
+                if isomorphic_index as usize == action {
+                    let swap = game.isomorphic_swap(node, i);
+                    for player in 0..2 {
+                        let mut tmp = reach[player].clone();
+                        apply_swap(&mut tmp, &swap[player]);
+                        add_slice(&mut child_reach[player], &tmp);
+                    }
+                }
+            }
+            accumulate_strategy_recursive(game, &node.play(action), &child_reach, sum, index);
+        }
+        return;
+    }
+
+    let player = node.player();
+    let num_actions = node.num_actions();
+    let num_hands = reach[player].len();
+    let strategy = if !game.is_node_allocated(node) {
+        vec![1.0 / num_actions as f32; num_actions * num_hands]
+    } else if game.is_node_compressed(node) {
+        normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+    } else {
+        normalized_strategy(node.strategy(), num_actions)
+    };
+
+    let child_reaches = (0..num_actions)
+        .map(|action| {
+            let mut child_reach = reach.clone();
+            child_reach[player].copy_from_slice(row(&strategy, action, num_hands));
+            mul_slice(&mut child_reach[player], &reach[player]);
+            child_reach
+        })
+        .collect::<Vec<_>>();
+
+    if *index == sum.len() {
+        sum.push(vec![0.0; strategy.len()]);
+    }
+
+    let entry = &mut sum[*index];
+    assert_eq!(entry.len(), strategy.len(), "Runs built different trees");
+    for (action, child_reach) in child_reaches.iter().enumerate() {
+        add_slice(row_mut(entry, action, num_hands), &child_reach[player]);
+    }
+
+    *index += 1;
+
+    for (action, child_reach) in child_reaches.iter().enumerate() {
+        accumulate_strategy_recursive(game, &node.play(action), child_reach, sum, index);
+    }
+}
+
+/// Overwrites the strategies of the decision nodes with `sum` in depth-first order.
+fn write_strategy_recursive<T: Game>(
+    game: &T,
+    node: &mut T::Node,
+    sum: &[Vec<f32>],
+    index: &mut usize,
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if !node.is_chance() {
+        // the nodes whose storage is not allocated keep the uniform strategy
+        if game.is_node_allocated(node) {
+            let strategy = &sum[*index];
+            if game.is_node_compressed(node) {
+                let scale = encode_unsigned_slice(node.strategy_compressed_mut(), strategy);
+                node.set_strategy_scale(scale);
+            } else {
+                node.strategy_mut().copy_from_slice(strategy);
+            }
+        }
+
+        *index += 1;
+    }
+
+    for action in 0..node.num_actions() {
+        write_strategy_recursive(game, &mut node.play(action), sum, index);
+    }
+}
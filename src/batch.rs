@@ -0,0 +1,1217 @@
+//! Batch solving of a set of flops.
+//!
+//! [`BatchRunner`] solves the same game configuration for each flop of a flop set (e.g., a
+//! subset of the 1755 strategically different flops) and collects the results into a
+//! [`BatchReport`]. Custom per-node metrics can be registered with [`BatchRunner::add_metric`];
+//! they are evaluated on every decision node while the solved game is still in memory, so bespoke
+//! statistics do not require re-traversing the solves.
+//!
+//! Multiple solves can run concurrently subject to a total memory budget; the memory of each
+//! solve is estimated with [`PostFlopGame::memory_usage`] before allocation.
+//!
+//! Long-running jobs can be made resumable with [`BatchRunner::run_resumable`], which records
+//! the outcome of each flop in a manifest file as soon as it is available.
+//!
+//! The root EVs of the solves can be combined into a session EV weighted by how often each flop
+//! occurs (e.g., in a hand-history database) with [`BatchReport::session_ev`]. The occurrence
+//! counts can be imported from a CSV file with [`load_flop_frequencies`].
+
+use crate::action_tree::*;
+use crate::card::*;
+use crate::error::*;
+use crate::game::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::topology::*;
+use crate::utility::*;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+#[cfg(feature = "io")]
+use std::fs::{File, OpenOptions};
+
+#[cfg(feature = "io")]
+use std::io::{Seek, SeekFrom, Write};
+
+#[cfg(feature = "io")]
+use std::path::Path;
+
+/// Configuration of a batch job.
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    /// The card configuration shared by all solves. The `flop` field is overwritten by each
+    /// flop of `flops`.
+    pub card_config: CardConfig,
+
+    /// The tree configuration shared by all solves. The initial state must be the flop.
+    pub tree_config: TreeConfig,
+
+    /// The flops to solve.
+    pub flops: Vec<[Card; 3]>,
+
+    /// The maximum number of iterations of each solve.
+    pub max_num_iterations: u32,
+
+    /// The target exploitability of each solve.
+    pub target_exploitability: f32,
+
+    /// Whether to enable the compression of the game trees.
+    pub enable_compression: bool,
+
+    /// The last street whose decision nodes are passed to the metrics. Evaluating the turn and
+    /// river nodes is considerably more expensive than evaluating the flop nodes.
+    pub metric_max_street: BoardState,
+
+    /// The maximum number of solves running concurrently.
+    pub max_concurrent_solves: usize,
+
+    /// The total memory budget of the concurrent solves in bytes (`0` means unlimited). A flop
+    /// whose estimated memory usage exceeds the budget by itself fails without being solved.
+    pub memory_budget: u64,
+}
+
+/// Information about a decision node passed to a metric.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeMetricInput<'a> {
+    /// The flop of the solve.
+    pub flop: [Card; 3],
+
+    /// The history leading to the node, in the format accepted by
+    /// [`PostFlopGame::apply_history`].
+    pub history: &'a [usize],
+
+    /// The street of the node.
+    pub street: BoardState,
+
+    /// The player to act.
+    pub player: usize,
+
+    /// The actions available at the node.
+    pub actions: &'a [Action],
+
+    /// The private hands of the player to act.
+    pub private_cards: &'a [(Card, Card)],
+
+    /// The strategy of the player in the same layout as [`PostFlopGame::strategy`].
+    pub strategy: &'a [f32],
+
+    /// The normalized weights of the player (see [`PostFlopGame::normalized_weights`]).
+    pub weights: &'a [f32],
+
+    /// The expected values of the player (see [`PostFlopGame::expected_values`]).
+    pub expected_values: &'a [f32],
+}
+
+/// Summary of the values of a metric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricSummary {
+    /// The number of values.
+    pub count: usize,
+
+    /// The sum of the values.
+    pub sum: f64,
+
+    /// The minimum value.
+    pub min: f64,
+
+    /// The maximum value.
+    pub max: f64,
+}
+
+/// Result of a single solve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlopResult {
+    /// The flop.
+    pub flop: [Card; 3],
+
+    /// The exploitability achieved.
+    pub exploitability: f32,
+
+    /// The expected value of each player at the root node.
+    pub ev: [f32; 2],
+
+    /// The summary of each registered metric, in the order of registration.
+    pub metrics: Vec<(String, MetricSummary)>,
+}
+
+/// Result of a batch job.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchReport {
+    /// The results of the successful solves.
+    pub results: Vec<FlopResult>,
+
+    /// The flops that failed with their error messages.
+    pub failures: Vec<([Card; 3], String)>,
+
+    /// The summary of each registered metric over all successful solves.
+    pub metrics: Vec<(String, MetricSummary)>,
+}
+
+/// Outcomes of the flops recorded in a manifest file of [`BatchRunner::run_resumable`].
+///
+/// The manifest is a text file with one line per flop: `done`, the flop, the exploitability, the
+/// EVs of both players, and the `count`, `sum`, `min`, and `max` of each metric for a successful
+/// solve, or `failed`, the flop, and the error message for a failed solve, separated by tabs.
+/// When a flop appears more than once, the last line takes precedence. A last line without the
+/// terminating newline is the partial write of an interrupted job, so it is ignored and removed
+/// when the job is resumed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchManifest {
+    /// The recorded outcomes in the order of the lines.
+    pub entries: Vec<Result<FlopResult, ([Card; 3], String)>>,
+}
+
+/// Weighted average of the root EVs over a set of solves, returned by
+/// [`BatchReport::session_ev`] and [`SessionEv::from_results`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SessionEv {
+    /// The weighted average of the EV of each player at the root node.
+    pub ev: [f64; 2],
+
+    /// The weighted standard deviation of the root EV of each player across the solves.
+    pub std_dev: [f64; 2],
+
+    /// The number of solves with a positive weight.
+    pub num_spots: usize,
+
+    /// The sum of the weights of the solves included in the average.
+    pub total_weight: f64,
+
+    /// The sum of the weights of the flops without a successful solve, which are excluded from
+    /// the average.
+    pub missing_weight: f64,
+}
+
+type MetricFn = dyn Fn(&NodeMetricInput) -> Option<f64> + Send + Sync;
+
+#[cfg(feature = "io")]
+const MANIFEST_HEADER: &str = "# postflop-solver batch manifest v1";
+
+/// Runner of a batch job.
+pub struct BatchRunner {
+    config: BatchConfig,
+    topology: TreeTopology,
+    metrics: Vec<(String, Box<MetricFn>)>,
+}
+
+impl Default for BatchConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            card_config: CardConfig::default(),
+            tree_config: TreeConfig::default(),
+            flops: Vec::new(),
+            max_num_iterations: 1000,
+            target_exploitability: 0.0,
+            enable_compression: false,
+            metric_max_street: BoardState::Flop,
+            max_concurrent_solves: 1,
+            memory_budget: 0,
+        }
+    }
+}
+
+impl Default for MetricSummary {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl MetricSummary {
+    /// Returns the mean of the values, or `NaN` if there is no value.
+    #[inline]
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+
+    #[inline]
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    #[inline]
+    fn merge(&mut self, other: &Self) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+}
+
+impl BatchRunner {
+    /// Creates a new runner. Returns an error if the configuration is invalid.
+    pub fn new(config: BatchConfig) -> Result<Self, Error> {
+        if config.tree_config.initial_state != BoardState::Flop {
+            return Err(Error::Config("Initial state must be the flop".to_string()));
+        }
+
+        if config.flops.is_empty() {
+            return Err(Error::Config("Flop set is empty".to_string()));
+        }
+
+        if config.max_concurrent_solves == 0 {
+            return Err(Error::Config(
+                "Maximum number of concurrent solves must be positive".to_string(),
+            ));
+        }
+
+        // the action tree does not depend on the flop, so it is built once and shared by the solves
+        let topology = ActionTree::new(config.tree_config.clone())?.topology();
+
+        Ok(Self {
+            config,
+            topology,
+            metrics: Vec::new(),
+        })
+    }
+
+    /// Returns the configuration.
+    #[inline]
+    pub fn config(&self) -> &BatchConfig {
+        &self.config
+    }
+
+    /// Registers a custom metric.
+    ///
+    /// `metric` is called for every decision node up to
+    /// [`BatchConfig::metric_max_street`] and returns the value of the node, or `None` to skip
+    /// the node. The values are summarized per flop and over the whole batch.
+    pub fn add_metric<F>(&mut self, name: &str, metric: F)
+    where
+        F: Fn(&NodeMetricInput) -> Option<f64> + Send + Sync + 'static,
+    {
+        self.metrics.push((name.to_string(), Box::new(metric)));
+    }
+
+    /// Solves all flops.
+    ///
+    /// Up to [`BatchConfig::max_concurrent_solves`] solves run concurrently, and a solve waits
+    /// until its estimated memory fits within [`BatchConfig::memory_budget`]. The results are
+    /// reported in the order of [`BatchConfig::flops`] regardless of the completion order.
+    ///
+    /// Note that each solve also uses the thread pool of `rayon` if the `rayon` feature is
+    /// enabled, so running many solves concurrently mainly pays off for small trees.
+    pub fn run(&self) -> BatchReport {
+        let results = self.run_flops(&self.config.flops, |_, _| Ok(()));
+        let mut report = BatchReport::default();
+        for (&flop, result) in self.config.flops.iter().zip(results) {
+            match result {
+                Ok(result) => report.push(result),
+                Err(e) => report.failures.push((flop, e.to_string())),
+            }
+        }
+        report
+    }
+
+    /// Solves all flops, recording the outcome of each flop in the manifest file at `path`.
+    ///
+    /// If the manifest already exists, the flops recorded as done are not solved again and
+    /// their recorded results are included in the report; the flops recorded as failed are
+    /// retried. Each outcome is appended to the manifest as soon as the solve finishes, so an
+    /// interrupted job can be resumed by calling this method again with the same path, and the
+    /// partial results can be read with [`BatchManifest::load`] in the meantime.
+    #[cfg(feature = "io")]
+    pub fn run_resumable<P: AsRef<Path>>(&self, path: P) -> Result<BatchReport, Error> {
+        let path = path.as_ref();
+        let (manifest, complete_len) = if path.exists() {
+            BatchManifest::load_complete(path)?
+        } else {
+            (BatchManifest::default(), 0)
+        };
+
+        // discard the partial line of an interrupted job so that the next line is not glued to it
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| Error::io(e, "Failed to open manifest"))?;
+        file.set_len(complete_len)
+            .and_then(|_| file.seek(SeekFrom::End(0)))
+            .map_err(|e| Error::io(e, "Failed to truncate manifest"))?;
+        if complete_len == 0 {
+            writeln!(file, "{MANIFEST_HEADER}")
+                .map_err(|e| Error::io(e, "Failed to write manifest"))?;
+        }
+
+        let pending = self
+            .config
+            .flops
+            .iter()
+            .copied()
+            .filter(|&flop| manifest.result(flop).is_none())
+            .collect::<Vec<_>>();
+
+        let file = Mutex::new(file);
+        let results = self.run_flops(&pending, |flop, result| {
+            let Some(line) = format_manifest_entry(flop, result) else {
+                return Ok(());
+            };
+            let mut file = file.lock().unwrap();
+            writeln!(file, "{line}")
+                .and_then(|_| file.flush())
+                .map_err(|e| Error::io(e, "Failed to write manifest"))
+        });
+
+        let mut results = pending.into_iter().zip(results).collect::<Vec<_>>();
+        let mut report = BatchReport::default();
+        for &flop in &self.config.flops {
+            if let Some(result) = manifest.result(flop) {
+                report.push(result.clone());
+            } else {
+                let index = results.iter().position(|(f, _)| *f == flop).unwrap();
+                match results.swap_remove(index).1 {
+                    Ok(result) => report.push(result),
+                    Err(e) => report.failures.push((flop, e.to_string())),
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Solves the given flops concurrently and returns the results in the same order. `on_result`
+    /// is called as soon as each solve finishes; its error is reported as the error of the flop.
+    fn run_flops<F>(&self, flops: &[[Card; 3]], on_result: F) -> Vec<Result<FlopResult, Error>>
+    where
+        F: Fn([Card; 3], &Result<FlopResult, Error>) -> Result<(), Error> + Sync,
+    {
+        let num_workers = self.config.max_concurrent_solves.min(flops.len());
+        let next = Mutex::new(0);
+        let results = Mutex::new((0..flops.len()).map(|_| None).collect::<Vec<_>>());
+        let quota = MemoryQuota::new(self.config.memory_budget);
+
+        thread::scope(|scope| {
+            for _ in 0..num_workers {
+                scope.spawn(|| loop {
+                    let index = {
+                        let mut next = next.lock().unwrap();
+                        let index = *next;
+                        *next += 1;
+                        index
+                    };
+
+                    if index >= flops.len() {
+                        break;
+                    }
+
+                    let mut result = self.solve_flop_with_quota(flops[index], &quota);
+                    if let Err(e) = on_result(flops[index], &result) {
+                        result = Err(e);
+                    }
+                    results.lock().unwrap()[index] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect()
+    }
+
+    /// Solves a single flop and evaluates the metrics.
+    pub fn solve_flop(&self, flop: [Card; 3]) -> Result<FlopResult, Error> {
+        self.solve_flop_with_quota(flop, &MemoryQuota::new(self.config.memory_budget))
+    }
+
+    /// Returns the estimated memory usage of the solve of the flop in bytes.
+    pub fn estimate_memory_usage(&self, flop: [Card; 3]) -> Result<u64, Error> {
+        let game = self.build_game(flop)?;
+        Ok(self.memory_usage_of(&game))
+    }
+
+    fn solve_flop_with_quota(
+        &self,
+        flop: [Card; 3],
+        quota: &MemoryQuota,
+    ) -> Result<FlopResult, Error> {
+        let mut game = self.build_game(flop)?;
+        let memory_usage = self.memory_usage_of(&game);
+        quota.acquire(memory_usage)?;
+        game.allocate_memory(self.config.enable_compression);
+        let result = self.finish_solve(&mut game, flop);
+        drop(game);
+        quota.release(memory_usage);
+        result
+    }
+
+    #[inline]
+    fn memory_usage_of(&self, game: &PostFlopGame) -> u64 {
+        let (uncompressed, compressed) = game.memory_usage();
+        if self.config.enable_compression {
+            compressed
+        } else {
+            uncompressed
+        }
+    }
+
+    /// Builds the game tree of the flop without allocating memory.
+    fn build_game(&self, flop: [Card; 3]) -> Result<PostFlopGame, Error> {
+        let card_config = CardConfig {
+            flop,
+            ..self.config.card_config.clone()
+        };
+        PostFlopGame::with_topology(card_config, self.topology.clone())
+    }
+
+    /// Solves the allocated game and collects the result.
+    fn finish_solve(&self, game: &mut PostFlopGame, flop: [Card; 3]) -> Result<FlopResult, Error> {
+        let exploitability = solve(
+            game,
+            self.config.max_num_iterations,
+            self.config.target_exploitability,
+            false,
+        )
+        .exploitability;
+
+        game.back_to_root();
+        game.cache_normalized_weights();
+        let ev = [0, 1].map(|player| {
+            compute_average(
+                &game.expected_values(player),
+                game.normalized_weights(player),
+            )
+        });
+
+        let mut summaries = vec![MetricSummary::default(); self.metrics.len()];
+        if !self.metrics.is_empty() {
+            let mut history = Vec::new();
+            self.evaluate_metrics(game, flop, &mut history, &mut summaries);
+            game.back_to_root();
+        }
+
+        Ok(FlopResult {
+            flop,
+            exploitability,
+            ev,
+            metrics: self.named(summaries),
+        })
+    }
+
+    fn evaluate_metrics(
+        &self,
+        game: &mut PostFlopGame,
+        flop: [Card; 3],
+        history: &mut Vec<usize>,
+        summaries: &mut [MetricSummary],
+    ) {
+        if game.is_terminal_node() {
+            return;
+        }
+
+        let street = game.current_board().len() - 3;
+
+        if game.is_chance_node() {
+            if street >= self.config.metric_max_street as usize {
+                return;
+            }
+
+            let possible_cards = game.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card);
+                    game.apply_history(history);
+                    self.evaluate_metrics(game, flop, history, summaries);
+                    history.pop();
+                }
+            }
+
+            return;
+        }
+
+        let actions = game.available_actions();
+        let player = game.current_player();
+        game.cache_normalized_weights();
+        let strategy = game.strategy();
+        let expected_values = game.expected_values(player);
+
+        let input = NodeMetricInput {
+            flop,
+            history,
+            street: [BoardState::Flop, BoardState::Turn, BoardState::River][street],
+            player,
+            actions: &actions,
+            private_cards: game.private_cards(player),
+            strategy: &strategy,
+            weights: game.normalized_weights(player),
+            expected_values: &expected_values,
+        };
+
+        for ((_, metric), summary) in self.metrics.iter().zip(summaries.iter_mut()) {
+            if let Some(value) = metric(&input) {
+                summary.add(value);
+            }
+        }
+
+        for action in 0..actions.len() {
+            history.push(action);
+            game.apply_history(history);
+            self.evaluate_metrics(game, flop, history, summaries);
+            history.pop();
+        }
+    }
+
+    fn named(&self, summaries: Vec<MetricSummary>) -> Vec<(String, MetricSummary)> {
+        self.metrics
+            .iter()
+            .map(|(name, _)| name.clone())
+            .zip(summaries)
+            .collect()
+    }
+}
+
+impl BatchManifest {
+    /// Loads a manifest file, ignoring a last line without the terminating newline.
+    #[cfg(feature = "io")]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(Self::load_complete(path)?.0)
+    }
+
+    /// Loads a manifest file and returns it with the length in bytes of its complete lines.
+    #[cfg(feature = "io")]
+    fn load_complete<P: AsRef<Path>>(path: P) -> Result<(Self, u64), Error> {
+        let mut content = String::new();
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut content))
+            .map_err(|e| Error::io(e, "Failed to read manifest"))?;
+
+        let mut manifest = Self::default();
+        let mut complete_len = 0;
+        for (line_number, line) in content.split_inclusive('\n').enumerate() {
+            let Some(line) = line.strip_suffix('\n') else {
+                break;
+            };
+            complete_len += line.len() + 1;
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_manifest_entry(line).map_err(|e| {
+                Error::invalid_data(format!("Invalid manifest at line {}: {e}", line_number + 1))
+            })?;
+            manifest.entries.push(entry);
+        }
+
+        Ok((manifest, complete_len as u64))
+    }
+
+    /// Returns the latest successful result of the flop, if the flop is recorded as done.
+    pub fn result(&self, flop: [Card; 3]) -> Option<&FlopResult> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| match entry {
+                Ok(result) => result.flop == flop,
+                Err((f, _)) => *f == flop,
+            })?
+            .as_ref()
+            .ok()
+    }
+
+    /// Returns the flops whose latest outcome is a failure, with their error messages.
+    pub fn failures(&self) -> Vec<([Card; 3], String)> {
+        let mut ret: Vec<([Card; 3], String)> = Vec::new();
+        for entry in &self.entries {
+            let flop = match entry {
+                Ok(result) => result.flop,
+                Err((flop, _)) => *flop,
+            };
+            ret.retain(|(f, _)| *f != flop);
+            if let Err(failure) = entry {
+                ret.push(failure.clone());
+            }
+        }
+        ret
+    }
+}
+
+/// Memory budget shared by the concurrent solves.
+struct MemoryQuota {
+    budget: u64,
+    used: Mutex<u64>,
+    condvar: Condvar,
+}
+
+impl MemoryQuota {
+    #[inline]
+    fn new(budget: u64) -> Self {
+        Self {
+            budget,
+            used: Mutex::new(0),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Blocks until `amount` bytes fit within the budget and reserves them.
+    fn acquire(&self, amount: u64) -> Result<(), Error> {
+        if self.budget == 0 {
+            return Ok(());
+        }
+
+        if amount > self.budget {
+            return Err(Error::Config(format!(
+                "Estimated memory usage exceeds the budget: {amount} > {}",
+                self.budget
+            )));
+        }
+
+        let mut used = self.used.lock().unwrap();
+        while *used + amount > self.budget {
+            used = self.condvar.wait(used).unwrap();
+        }
+        *used += amount;
+        Ok(())
+    }
+
+    fn release(&self, amount: u64) {
+        if self.budget == 0 {
+            return;
+        }
+
+        *self.used.lock().unwrap() -= amount;
+        self.condvar.notify_all();
+    }
+}
+
+impl SessionEv {
+    /// Computes the weighted average of the root EVs of `spots`, given as pairs of a result and
+    /// its occurrence frequency. The results may come from different batch jobs, e.g., the
+    /// solves of the different preflop lines of a position.
+    ///
+    /// Returns an error if a weight is negative or not finite, or the total weight is zero.
+    pub fn from_results<'a, I>(spots: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (&'a FlopResult, f64)>,
+    {
+        let mut ret = Self::default();
+        let mut sum = [0.0; 2];
+        let mut sum_sq = [0.0; 2];
+
+        for (result, weight) in spots {
+            check_weight(weight)?;
+            if weight == 0.0 {
+                continue;
+            }
+
+            ret.num_spots += 1;
+            ret.total_weight += weight;
+            for player in 0..2 {
+                let ev = result.ev[player] as f64;
+                sum[player] += weight * ev;
+                sum_sq[player] += weight * ev * ev;
+            }
+        }
+
+        if ret.total_weight == 0.0 {
+            return Err(Error::Config("Total weight is zero".to_string()));
+        }
+
+        for player in 0..2 {
+            let mean = sum[player] / ret.total_weight;
+            let variance = sum_sq[player] / ret.total_weight - mean * mean;
+            ret.ev[player] = mean;
+            ret.std_dev[player] = variance.max(0.0).sqrt();
+        }
+
+        Ok(ret)
+    }
+}
+
+impl BatchReport {
+    /// Computes the session EV, i.e., the average of the root EVs of the solves weighted by the
+    /// occurrence frequency of each flop in `frequencies`.
+    ///
+    /// The flops are matched regardless of the order of the cards and up to suit isomorphism,
+    /// so the counts of all flops observed in hand histories (e.g., imported with
+    /// [`load_flop_frequencies`]) are attributed to the solved representative flops. The flops of
+    /// the report that are not listed in `frequencies` are ignored, and the weights of the listed
+    /// flops that were not solved successfully are reported as [`SessionEv::missing_weight`]. If
+    /// a flop is listed more than once, the weights are summed.
+    ///
+    /// Returns an error if a weight is negative or not finite, or no solved flop has a positive
+    /// weight.
+    pub fn session_ev(&self, frequencies: &[([Card; 3], f64)]) -> Result<SessionEv, Error> {
+        let solved = self
+            .results
+            .iter()
+            .map(|result| canonical_flop(result.flop))
+            .collect::<Vec<_>>();
+
+        let mut spots = Vec::new();
+        let mut missing_weight = 0.0;
+
+        for &(flop, weight) in frequencies {
+            check_weight(weight)?;
+            let flop = canonical_flop(flop);
+            match solved.iter().position(|&f| f == flop) {
+                Some(index) => spots.push((&self.results[index], weight)),
+                None => missing_weight += weight,
+            }
+        }
+
+        let mut ret = SessionEv::from_results(spots)?;
+        ret.missing_weight = missing_weight;
+        Ok(ret)
+    }
+
+    /// Adds the result of a solve and merges its metrics into the batch summaries.
+    fn push(&mut self, result: FlopResult) {
+        if self.metrics.is_empty() {
+            self.metrics = result
+                .metrics
+                .iter()
+                .map(|(name, _)| (name.clone(), MetricSummary::default()))
+                .collect();
+        }
+
+        for ((_, total), (_, summary)) in self.metrics.iter_mut().zip(&result.metrics) {
+            total.merge(summary);
+        }
+
+        self.results.push(result);
+    }
+}
+
+/// Loads the occurrence counts of the flops from a CSV file, e.g., the flop statistics of a
+/// hand-history database, to be passed to [`BatchReport::session_ev`].
+///
+/// Each line of the file consists of a flop and its count separated by a comma, e.g.,
+/// `AhKd2s,120`. The cards of a flop may be separated by spaces. The first line is skipped if it
+/// is a header (i.e., its flop cannot be parsed), and empty lines and lines starting with `#` are
+/// ignored.
+#[cfg(feature = "io")]
+pub fn load_flop_frequencies<P: AsRef<Path>>(path: P) -> Result<Vec<([Card; 3], f64)>, Error> {
+    let file = File::open(path).map_err(|e| Error::io(e, "Failed to open CSV"))?;
+    parse_flop_frequencies(file)
+}
+
+/// Parses the occurrence counts of the flops in the CSV format of [`load_flop_frequencies`].
+pub fn parse_flop_frequencies<R: Read>(reader: R) -> Result<Vec<([Card; 3], f64)>, Error> {
+    let mut ret = Vec::new();
+
+    for (line_number, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|e| Error::io(e, "Failed to read CSV"))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |e: &dyn fmt::Display| {
+            Error::invalid_data(format!("Invalid CSV at line {}: {e}", line_number + 1))
+        };
+        let Some((flop, count)) = line.split_once(',') else {
+            return Err(invalid(&format!("Missing count: {line}")));
+        };
+
+        let flop = match flop_from_str(flop.trim().trim_matches('"')) {
+            Ok(flop) => flop,
+            Err(_) if line_number == 0 => continue, // header
+            Err(e) => return Err(invalid(&e)),
+        };
+
+        let count = count.trim();
+        let count = count
+            .parse::<f64>()
+            .map_err(|_| invalid(&format!("Invalid count: {count}")))?;
+        check_weight(count).map_err(|e| invalid(&e))?;
+
+        ret.push((flop, count));
+    }
+
+    Ok(ret)
+}
+
+/// Returns the representative of the flop up to the order of the cards and suit isomorphism.
+fn canonical_flop(flop: [Card; 3]) -> [Card; 3] {
+    (0..=u8::MAX)
+        .map(|perm| [0, 2, 4, 6].map(|shift| (perm >> shift) & 3))
+        .filter(|perm| perm.iter().fold(0, |mask, &suit| mask | 1 << suit) == 0b1111)
+        .map(|perm| {
+            let mut ret = flop.map(|card| card & !3 | perm[(card & 3) as usize]);
+            ret.sort_unstable();
+            ret
+        })
+        .min()
+        .unwrap()
+}
+
+#[inline]
+fn check_weight(weight: f64) -> Result<(), Error> {
+    if weight.is_finite() && weight >= 0.0 {
+        Ok(())
+    } else {
+        Err(Error::Config(format!("Invalid weight: {weight}")))
+    }
+}
+
+/// Converts the flop into a string in the standard format, which is used as a key of the results.
+#[cfg(feature = "io")]
+pub(crate) fn flop_to_string(flop: [Card; 3]) -> Result<String, Error> {
+    flop.iter()
+        .map(|&card| card_to_string_with(card, CardFormat::default()))
+        .collect()
+}
+
+#[cfg(feature = "io")]
+fn parse_flop(s: &str) -> Result<[Card; 3], Error> {
+    let mut chars = s.chars();
+    let flop = [
+        card_from_chars(&mut chars)?,
+        card_from_chars(&mut chars)?,
+        card_from_chars(&mut chars)?,
+    ];
+    if chars.next().is_some() {
+        return Err(Error::invalid_data(format!("Invalid flop: {s}")));
+    }
+    Ok(flop)
+}
+
+/// Returns `None` if the flop contains an invalid card and thus cannot be recorded.
+#[cfg(feature = "io")]
+fn format_manifest_entry(flop: [Card; 3], result: &Result<FlopResult, Error>) -> Option<String> {
+    let flop = flop_to_string(flop).ok()?;
+    Some(match result {
+        Ok(result) => {
+            let mut fields = vec![
+                "done".to_string(),
+                flop,
+                result.exploitability.to_string(),
+                result.ev[0].to_string(),
+                result.ev[1].to_string(),
+            ];
+            for (name, summary) in &result.metrics {
+                fields.push(name.replace(['\t', '\n', '\r'], " "));
+                fields.push(summary.count.to_string());
+                fields.push(summary.sum.to_string());
+                fields.push(summary.min.to_string());
+                fields.push(summary.max.to_string());
+            }
+            fields.join("\t")
+        }
+        Err(e) => format!(
+            "failed\t{}\t{}",
+            flop,
+            e.to_string().replace(['\t', '\n', '\r'], " ")
+        ),
+    })
+}
+
+#[cfg(feature = "io")]
+fn parse_manifest_entry(line: &str) -> Result<Result<FlopResult, ([Card; 3], String)>, Error> {
+    let fields = line.split('\t').collect::<Vec<_>>();
+    if fields.len() < 2 {
+        return Err(Error::invalid_data("Too few fields"));
+    }
+
+    let flop = parse_flop(fields[1])?;
+    let number = |s: &str| {
+        s.parse::<f64>()
+            .map_err(|_| Error::invalid_data(format!("Invalid number: {s}")))
+    };
+
+    match fields[0] {
+        "failed" => Ok(Err((flop, fields[2..].join("\t")))),
+        "done" if fields.len() >= 5 && (fields.len() - 5) % 5 == 0 => {
+            let mut metrics = Vec::new();
+            for chunk in fields[5..].chunks(5) {
+                let count = chunk[1]
+                    .parse::<usize>()
+                    .map_err(|_| Error::invalid_data(format!("Invalid count: {}", chunk[1])))?;
+                let summary = MetricSummary {
+                    count,
+                    sum: number(chunk[2])?,
+                    min: number(chunk[3])?,
+                    max: number(chunk[4])?,
+                };
+                metrics.push((chunk[0].to_string(), summary));
+            }
+
+            Ok(Ok(FlopResult {
+                flop,
+                exploitability: number(fields[2])? as f32,
+                ev: [number(fields[3])? as f32, number(fields[4])? as f32],
+                metrics,
+            }))
+        }
+        _ => Err(Error::invalid_data(format!("Invalid entry: {line}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bet_size::*;
+
+    fn config() -> BatchConfig {
+        let bet_sizes = BetSizeOptions::try_from(("50%", "")).unwrap();
+        BatchConfig {
+            card_config: CardConfig {
+                range: ["AA,KK,AKs".parse().unwrap(), "QQ,JJ,AQs".parse().unwrap()],
+                ..Default::default()
+            },
+            tree_config: TreeConfig {
+                starting_pot: 100,
+                effective_stack: 100,
+                flop_bet_sizes: [bet_sizes.clone(), bet_sizes],
+                ..Default::default()
+            },
+            flops: vec![
+                flop_from_str("2c6dTh").unwrap(),
+                flop_from_str("AhKd2s").unwrap(),
+                flop_from_str("QcQd2s").unwrap(),
+            ],
+            max_num_iterations: 20,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn batch_metrics() {
+        let mut config = config();
+        config.flops.push([0, 0, 1]); // invalid flop
+
+        let mut runner = BatchRunner::new(config).unwrap();
+        runner.add_metric("flop_nodes", |_| Some(1.0));
+        runner.add_metric("root_bet", |input| {
+            if !input.history.is_empty() {
+                return None;
+            }
+            let num_hands = input.weights.len();
+            let total = input.weights.iter().sum::<f32>();
+            let bet = (0..num_hands)
+                .map(|i| input.strategy[num_hands + i] * input.weights[i])
+                .sum::<f32>();
+            Some((bet / total) as f64)
+        });
+
+        let report = runner.run();
+        assert_eq!(report.results.len(), 3);
+        assert_eq!(report.failures.len(), 1);
+
+        for result in &report.results {
+            assert_eq!(result.metrics[0].0, "flop_nodes");
+            assert!(result.metrics[0].1.count >= 4);
+            assert_eq!(result.metrics[1].1.count, 1);
+            assert!((0.0..=1.0).contains(&result.metrics[1].1.mean()));
+        }
+
+        let total = report.metrics[0].1;
+        let sum = report
+            .results
+            .iter()
+            .map(|r| r.metrics[0].1.count)
+            .sum::<usize>();
+        assert_eq!(total.count, sum);
+        assert_eq!(report.metrics[1].1.count, 3);
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn batch_resumable() {
+        let path = std::env::temp_dir().join(format!(
+            "postflop-solver-manifest-{}.tsv",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut config = config();
+        let all_flops = config.flops.clone();
+        config.flops.truncate(2);
+        config.flops.push([0, 0, 1]); // invalid flop
+
+        let mut runner = BatchRunner::new(config.clone()).unwrap();
+        runner.add_metric("nodes", |_| Some(1.0));
+        let first = runner.run_resumable(&path).unwrap();
+        assert_eq!(first.results.len(), 2);
+        assert_eq!(first.failures.len(), 1);
+
+        // partial results can be read from the manifest
+        let manifest = BatchManifest::load(&path).unwrap();
+        assert_eq!(manifest.entries.len(), 3);
+        assert_eq!(manifest.result(all_flops[0]), Some(&first.results[0]));
+        assert_eq!(manifest.failures().len(), 1);
+
+        // resume with the full flop set: only the remaining flop and the failure are solved
+        config.flops = all_flops.clone();
+        config.flops.push([0, 0, 1]);
+        let mut runner = BatchRunner::new(config).unwrap();
+        runner.add_metric("nodes", |_| Some(1.0));
+        let second = runner.run_resumable(&path).unwrap();
+        assert_eq!(second.results.len(), 3);
+        assert_eq!(second.results[..2], first.results[..]);
+        assert_eq!(second.failures.len(), 1);
+        assert_eq!(
+            second.metrics[0].1.count,
+            second
+                .results
+                .iter()
+                .map(|r| r.metrics[0].1.count)
+                .sum::<usize>()
+        );
+
+        let manifest = BatchManifest::load(&path).unwrap();
+        assert_eq!(manifest.entries.len(), 5);
+        assert!(all_flops
+            .iter()
+            .all(|&flop| manifest.result(flop).is_some()));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn batch_resumable_truncated() {
+        let path = std::env::temp_dir().join(format!(
+            "postflop-solver-manifest-truncated-{}.tsv",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let mut config = config();
+        config.flops.truncate(2);
+        let runner = BatchRunner::new(config).unwrap();
+        let first = runner.run_resumable(&path).unwrap();
+        assert_eq!(first.results.len(), 2);
+
+        // the process is killed while writing the last number of the second line
+        let len = std::fs::metadata(&path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 3).unwrap();
+        drop(file);
+
+        let manifest = BatchManifest::load(&path).unwrap();
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(
+            manifest.result(first.results[0].flop),
+            Some(&first.results[0])
+        );
+        assert_eq!(manifest.result(first.results[1].flop), None);
+
+        // the partial line is discarded, and the flop is solved again
+        let second = runner.run_resumable(&path).unwrap();
+        assert_eq!(second.results, first.results);
+
+        let manifest = BatchManifest::load(&path).unwrap();
+        assert_eq!(manifest.entries.len(), 2);
+        assert_eq!(
+            manifest.result(first.results[1].flop),
+            Some(&first.results[1])
+        );
+        assert!(std::fs::read_to_string(&path).unwrap().ends_with('\n'));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn batch_session_ev() {
+        let config = config();
+        let flops = config.flops.clone();
+        let report = BatchRunner::new(config).unwrap().run();
+        let ev = |i: usize| report.results[i].ev.map(|x| x as f64);
+
+        let frequencies = [
+            (flops[0], 3.0),
+            (flops[1], 1.0),
+            (flops[2], 0.0),
+            (flop_from_str("2c6cTc").unwrap(), 2.0),
+        ];
+        let session = report.session_ev(&frequencies).unwrap();
+        assert_eq!(session.num_spots, 2);
+        assert_eq!(session.total_weight, 4.0);
+        assert_eq!(session.missing_weight, 2.0);
+        for player in 0..2 {
+            let expected = (3.0 * ev(0)[player] + ev(1)[player]) / 4.0;
+            assert!((session.ev[player] - expected).abs() < 1e-4);
+            let diff = (ev(0)[player] - ev(1)[player]).abs();
+            assert!((session.std_dev[player] - diff * 3f64.sqrt() / 4.0).abs() < 1e-3);
+        }
+
+        // the flops are matched up to the order of the cards and suit isomorphism
+        let permuted = [(flop_from_str("Ts6h2d").unwrap(), 1.0)];
+        assert_eq!(report.session_ev(&permuted).unwrap().ev, ev(0));
+
+        // a single spot has no deviation
+        let session = report.session_ev(&[(flops[2], 1.0)]).unwrap();
+        assert_eq!(session.ev, ev(2));
+        assert!(session.std_dev.iter().all(|&x| x < 1e-3));
+
+        assert!(report.session_ev(&[(flops[0], -1.0)]).is_err());
+        assert!(report.session_ev(&[(flops[0], f64::NAN)]).is_err());
+        assert!(report.session_ev(&[(flops[0], 0.0)]).is_err());
+        assert!(report.session_ev(&[]).is_err());
+    }
+
+    #[test]
+    fn flop_frequencies() {
+        let csv = "flop,count\n# comment\nAhKd2s,120\n\n\"Qc Qd 2s\", 7.5\nAhKd2s,3\n";
+        let frequencies = parse_flop_frequencies(csv.as_bytes()).unwrap();
+        assert_eq!(
+            frequencies,
+            vec![
+                (flop_from_str("AhKd2s").unwrap(), 120.0),
+                (flop_from_str("QcQd2s").unwrap(), 7.5),
+                (flop_from_str("AhKd2s").unwrap(), 3.0),
+            ]
+        );
+
+        assert!(parse_flop_frequencies("AhKd2s,120\nAhKd,1\n".as_bytes()).is_err());
+        assert!(parse_flop_frequencies("AhKd2s,-1\n".as_bytes()).is_err());
+        assert!(parse_flop_frequencies("AhKd2s\n".as_bytes()).is_err());
+        assert!(parse_flop_frequencies("AhKd2s,x\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn batch_parallel() {
+        let config = config();
+        let sequential = BatchRunner::new(config.clone()).unwrap().run();
+
+        // the budget admits only two of the three solves at a time
+        let runner = BatchRunner::new(config.clone()).unwrap();
+        let usages = config
+            .flops
+            .iter()
+            .map(|&flop| runner.estimate_memory_usage(flop).unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = usages.clone();
+        sorted.sort_unstable();
+
+        let runner = BatchRunner::new(BatchConfig {
+            max_concurrent_solves: 3,
+            memory_budget: sorted[1] + sorted[2],
+            ..config.clone()
+        })
+        .unwrap();
+        let parallel = runner.run();
+        assert_eq!(parallel.results.len(), sequential.results.len());
+        for (x, y) in parallel.results.iter().zip(&sequential.results) {
+            assert_eq!(x.flop, y.flop);
+            assert!((x.exploitability - y.exploitability).abs() < 1e-3);
+        }
+
+        // a flop exceeding the budget fails
+        let runner = BatchRunner::new(BatchConfig {
+            max_concurrent_solves: 2,
+            memory_budget: sorted[1],
+            ..config
+        })
+        .unwrap();
+        let report = runner.run();
+        let num_too_large = usages.iter().filter(|&&u| u > sorted[1]).count();
+        assert_eq!(report.failures.len(), num_too_large);
+        assert_eq!(report.results.len(), 3 - num_too_large);
+    }
+}
@@ -1,6 +1,11 @@
+use crate::error::*;
+
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// Bet size options for the first bets and raises.
 ///
 /// In the `try_from()` method, multiple bet sizes can be specified using a comma-separated string.
@@ -14,6 +19,7 @@ use bincode::{Decode, Encode};
 /// - e: Geometric size.
 ///   - e: Same as "3e" for the flop, "2e" for the turn, and "1e" (equivalent to "a") for the river.
 ///   - Xe: The geometric size with X streets remaining. X must be a positive integer. (e.g., "2e")
+///   - eX: Same as Xe. (e.g., "e2")
 ///   - XeY%: Same as Xe, but the maximum size is Y% of the pot. (e.g., "3e200%")
 ///   - If specified for raises, the number of previous raises is subtracted from X.
 /// - a: All-in. (e.g., "a")
@@ -39,6 +45,7 @@ use bincode::{Decode, Encode};
 /// ```
 #[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BetSizeOptions {
     /// Bet size options for first bet.
     pub bet: Vec<BetSize>,
@@ -52,6 +59,7 @@ pub struct BetSizeOptions {
 /// See the [`BetSizeOptions`] struct for the description and examples.
 #[derive(Debug, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DonkSizeOptions {
     pub donk: Vec<BetSize>,
 }
@@ -59,6 +67,7 @@ pub struct DonkSizeOptions {
 /// Bet size specification.
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BetSize {
     /// Bet size relative to the current pot size.
     PotRelative(f64),
@@ -81,7 +90,7 @@ pub enum BetSize {
 }
 
 impl TryFrom<(&str, &str)> for BetSizeOptions {
-    type Error = String;
+    type Error = Error;
 
     /// Attempts to convert comma-separated strings into bet sizes.
     ///
@@ -102,11 +111,11 @@ impl TryFrom<(&str, &str)> for BetSizeOptions {
         let mut raise = Vec::new();
 
         for bet_size in bet_sizes {
-            bet.push(bet_size_from_str(bet_size, false)?);
+            bet.push(bet_size_from_str(bet_size, false).map_err(Error::Config)?);
         }
 
         for raise_size in raise_sizes {
-            raise.push(bet_size_from_str(raise_size, true)?);
+            raise.push(bet_size_from_str(raise_size, true).map_err(Error::Config)?);
         }
 
         bet.sort_unstable_by(|l, r| l.partial_cmp(r).unwrap());
@@ -117,7 +126,7 @@ impl TryFrom<(&str, &str)> for BetSizeOptions {
 }
 
 impl TryFrom<&str> for DonkSizeOptions {
-    type Error = String;
+    type Error = Error;
 
     /// Attempts to convert comma-separated strings into bet sizes.
     ///
@@ -132,7 +141,7 @@ impl TryFrom<&str> for DonkSizeOptions {
         let mut donk = Vec::new();
 
         for donk_size in donk_sizes {
-            donk.push(bet_size_from_str(donk_size, false)?);
+            donk.push(bet_size_from_str(donk_size, false).map_err(Error::Config)?);
         }
 
         donk.sort_unstable_by(|l, r| l.partial_cmp(r).unwrap());
@@ -208,8 +217,16 @@ fn bet_size_from_str(s: &str, is_raise: bool) -> Result<BetSize, String> {
     } else if s_lower.contains('e') {
         // Geometric
         let mut split = s_lower.split('e');
-        let num_streets_str = split.next().ok_or(&err_msg)?;
-        let max_pot_rel_str = split.next().ok_or(&err_msg)?;
+        let mut num_streets_str = split.next().ok_or(&err_msg)?;
+        let mut max_pot_rel_str = split.next().ok_or(&err_msg)?;
+
+        // "eX" is an alias of "Xe"
+        if num_streets_str.is_empty()
+            && !max_pot_rel_str.is_empty()
+            && !max_pot_rel_str.ends_with('%')
+        {
+            (num_streets_str, max_pot_rel_str) = (max_pot_rel_str, "");
+        }
 
         let num_streets = if num_streets_str.is_empty() {
             0
@@ -270,6 +287,7 @@ mod tests {
             ("e", Geometric(0, f64::INFINITY)),
             ("E", Geometric(0, f64::INFINITY)),
             ("2e", Geometric(2, f64::INFINITY)),
+            ("e2", Geometric(2, f64::INFINITY)),
             ("E37.5%", Geometric(0, 0.375)),
             ("100e.5%", Geometric(100, 0.005)),
             ("a", AllIn),
@@ -283,7 +301,7 @@ mod tests {
         let error_tests = [
             "", "0", "1.23", "%", "+42%", "-30%", "x", "0x", "1x", "c", "12.3c", "10c10", "42cr",
             "c3r", "0c0r", "123c101r", "1c2r3", "12c3.4r", "0e", "2.7e", "101e", "3e7", "E%",
-            "1e2e3", "bet", "1a", "a1",
+            "1e2e3", "bet", "1a", "a1", "e0", "e2.5", "e2%x",
         ];
 
         for s in error_tests {
@@ -310,6 +328,20 @@ mod tests {
                     raise: vec![PotRelative(0.25), PrevBetRelative(2.5), Geometric(0, 2.0)],
                 },
             ),
+            (
+                "33%, 75%, 125%, e2, a",
+                "2.5x, a",
+                BetSizeOptions {
+                    bet: vec![
+                        PotRelative(0.33),
+                        PotRelative(0.75),
+                        PotRelative(1.25),
+                        Geometric(2, f64::INFINITY),
+                        AllIn,
+                    ],
+                    raise: vec![PrevBetRelative(2.5), AllIn],
+                },
+            ),
         ];
 
         for (bet, raise, expected) in tests {
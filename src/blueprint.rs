@@ -0,0 +1,217 @@
+//! Playing of solved postflop blueprints shared by the bot adapters.
+//!
+//! [`BlueprintPlayer`] translates the postflop betting of an actual hand onto a solved
+//! [`PostFlopGame`] rooted at the flop and samples the action of the agent from the blueprint
+//! strategy. The betting is expressed in the total commitments of the hand, so the adapters only
+//! convert the actions of their protocols.
+
+use crate::action_tree::*;
+use crate::card::*;
+use crate::error::*;
+use crate::game::*;
+use crate::rng::*;
+
+/// An action of a hand. The amount of a bet or raise is the player's total commitment in the
+/// hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HandAction {
+    Fold,
+    Check,
+    Call,
+    Raise(i32),
+}
+
+/// Player of solved postflop blueprints.
+///
+/// The chips of the actual hand are translated onto the blueprint by the ratio of the
+/// blueprint's starting pot to the actual pot after the preflop betting, so a blueprint can be
+/// used at different pot sizes and stack depths. Off-tree bets of the opponent are mapped to the
+/// bet size whose resulting commitment is closest to the translated amount, and an all-in of the
+/// opponent is mapped to the all-in action of the blueprint when available. The sampled action is
+/// translated back to the actual chips; an all-in of the blueprint is always translated to an
+/// actual all-in.
+pub(crate) struct BlueprintPlayer {
+    stack: i32,
+    blueprints: Vec<PostFlopGame>,
+    rng: SolverRng,
+}
+
+impl BlueprintPlayer {
+    /// Creates a new player for hands with the given stack.
+    pub(crate) fn new(stack: i32, seed: u64) -> Self {
+        Self {
+            stack,
+            blueprints: Vec::new(),
+            rng: SolverRng::new(seed),
+        }
+    }
+
+    /// Returns the seed of the sampling of the actions.
+    #[inline]
+    pub(crate) fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    /// Adds a solved blueprint. Returns an error if the game is not solved or not rooted at the
+    /// flop.
+    pub(crate) fn add_blueprint(&mut self, game: PostFlopGame) -> Result<(), Error> {
+        game.check_operation(GameOperation::Analyze)?;
+
+        if game.tree_config().initial_state != BoardState::Flop {
+            return Err(Error::Config(
+                "Blueprint must be rooted at the flop".to_string(),
+            ));
+        }
+
+        self.blueprints.push(game);
+        Ok(())
+    }
+
+    /// Plays the hand on a blueprint and returns the sampled action (not legalized).
+    ///
+    /// `preflop_committed` is the commitment of each player after the preflop betting, and
+    /// `streets` are the actions of each postflop street. Returns an error if no blueprint is
+    /// applicable (e.g., on an unknown flop, with the betting outside of the blueprint tree, or
+    /// with a hand outside of the blueprint range).
+    pub(crate) fn action(
+        &mut self,
+        hole_cards: (Card, Card),
+        board: &[Card],
+        preflop_committed: i32,
+        streets: &[Vec<HandAction>],
+    ) -> Result<HandAction, Error> {
+        if board.len() < 3 {
+            return Err(Error::Tree("Flop is not dealt".to_string()));
+        }
+
+        let mut flop = [board[0], board[1], board[2]];
+        flop.sort_unstable();
+
+        let index = self
+            .blueprints
+            .iter()
+            .position(|game| {
+                let mut blueprint_flop = game.card_config().flop;
+                blueprint_flop.sort_unstable();
+                blueprint_flop == flop
+            })
+            .ok_or_else(|| Error::Config("No blueprint for the flop".to_string()))?;
+
+        let stack = self.stack;
+        let game = &mut self.blueprints[index];
+        let half_pot = game.tree_config().starting_pot as f64 / 2.0;
+        let ratio = half_pot / preflop_committed as f64;
+        let not_in_tree = || Error::Tree("Betting is not in the blueprint tree".to_string());
+        game.back_to_root();
+
+        // translate the postflop betting onto the blueprint
+        let mut history = Vec::new();
+        for (street, actions) in streets.iter().enumerate() {
+            if street >= 1 {
+                let card = *board
+                    .get(street + 2)
+                    .ok_or_else(|| Error::Tree("Board is too short".to_string()))?;
+                if !game.is_chance_node() || game.possible_cards() & (1 << card) == 0 {
+                    return Err(not_in_tree());
+                }
+                history.push(card as usize);
+                game.apply_history(&history);
+            }
+
+            for &action in actions {
+                if game.is_terminal_node() || game.is_chance_node() {
+                    return Err(not_in_tree());
+                }
+
+                let player = game.current_player();
+                let available_actions = game.available_actions();
+                let find = |f: &dyn Fn(&Action) -> bool| available_actions.iter().position(f);
+                let index = match action {
+                    HandAction::Fold => find(&|a| *a == Action::Fold),
+                    HandAction::Check | HandAction::Call => {
+                        find(&|a| matches!(a, Action::Check | Action::Call))
+                    }
+                    HandAction::Raise(amount) => {
+                        let all_in = find(&|a| matches!(a, Action::AllIn(_)));
+                        if amount >= stack && all_in.is_some() {
+                            all_in
+                        } else {
+                            let target = half_pot + (amount - preflop_committed) as f64 * ratio;
+                            game.closest_bet_action(&history, player, target)
+                                .or_else(|| find(&|a| *a == Action::Call))
+                        }
+                    }
+                };
+
+                history.push(index.ok_or_else(not_in_tree)?);
+                game.apply_history(&history);
+            }
+        }
+
+        if game.is_terminal_node() || game.is_chance_node() {
+            return Err(not_in_tree());
+        }
+
+        // sample the action of the agent
+        let player = game.current_player();
+        let hand = game
+            .private_cards(player)
+            .iter()
+            .position(|&(c1, c2)| (c1, c2) == hole_cards || (c2, c1) == hole_cards)
+            .filter(|&hand| game.weights(player)[hand] > 0.0)
+            .ok_or_else(|| Error::Config("Hand is not in the blueprint range".to_string()))?;
+
+        let strategy = game.strategy();
+        let num_hands = game.num_private_hands(player);
+        let actions = game.available_actions();
+        let mut threshold = self.rng.next_f32();
+        let mut chosen = actions.len() - 1;
+        for i in 0..actions.len() {
+            threshold -= strategy[i * num_hands + hand];
+            if threshold <= 0.0 {
+                chosen = i;
+                break;
+            }
+        }
+
+        Ok(match actions[chosen] {
+            Action::Fold => HandAction::Fold,
+            Action::Check => HandAction::Check,
+            Action::Call => HandAction::Call,
+            Action::AllIn(_) => HandAction::Raise(stack),
+            _ => {
+                game.play(chosen);
+                let postflop = (game.committed_amount(player) as f64 - half_pot) / ratio;
+                game.apply_history(&history);
+                HandAction::Raise(preflop_committed + postflop.round() as i32)
+            }
+        })
+    }
+}
+
+/// Adjusts the action of `position` so that it is legal, given the total commitment of each
+/// position in the hand and the size of the last bet or raise on the current street.
+pub(crate) fn legalize(
+    action: HandAction,
+    committed: [i32; 2],
+    last_raise_size: i32,
+    stack: i32,
+    position: usize,
+) -> HandAction {
+    let to_call = committed[position ^ 1] - committed[position];
+    let check_or_call = if to_call > 0 {
+        HandAction::Call
+    } else {
+        HandAction::Check
+    };
+
+    match action {
+        HandAction::Fold if to_call > 0 => HandAction::Fold,
+        HandAction::Raise(_) if committed[position ^ 1] >= stack => check_or_call,
+        HandAction::Raise(amount) => {
+            let min_raise = committed[position ^ 1] + last_raise_size;
+            HandAction::Raise(amount.max(min_raise).min(stack))
+        }
+        _ => check_or_call,
+    }
+}
@@ -0,0 +1,247 @@
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::error::*;
+use crate::game::*;
+use crate::range::*;
+
+/// Fluent builder of a [`PostFlopGame`] for the common case in which both players use the same
+/// bet sizes.
+///
+/// The builder fills [`CardConfig`] and [`TreeConfig`] from strings: the ranges are in the
+/// format of [`Range`], the cards are in the format of [`card_from_str`], and the bet sizes are
+/// in the format of [`BetSizeOptions`]. The street of the root node is the last dealt street of
+/// the board. Errors are deferred to [`build`], which reports the first one, so the calls can be
+/// chained without intermediate `?`.
+///
+/// The other fields of the configurations keep their default values; use [`build_configs`] to
+/// modify them before building the game.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::prelude::*;
+///
+/// let game = GameBuilder::new()
+///     .oop_range("66+,A8s+,A5s-A4s,AJo+,K9s+,KQo,QTs+,JTs,96s+,85s+,75s+,65s,54s")
+///     .ip_range("QQ-22,AQs-A2s,ATo+,K5s+,KJo+,Q8s+,J8s+,T7s+,96s+,86s+,75s+,64s+,53s+")
+///     .flop("Td9d6h")
+///     .turn("Qc")
+///     .starting_pot(200)
+///     .effective_stack(900)
+///     .bets("60%,e,a")
+///     .raises("2.5x")
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(game.tree_config().initial_state, BoardState::Turn);
+/// ```
+///
+/// [`build`]: #method.build
+/// [`build_configs`]: #method.build_configs
+#[derive(Debug, Default)]
+pub struct GameBuilder {
+    card_config: CardConfig,
+    tree_config: TreeConfig,
+    sizes: [(String, String); 3],
+    error: Option<Error>,
+}
+
+impl GameBuilder {
+    /// Creates a new builder with no ranges, no board, and no bet sizes.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the range of OOP.
+    #[inline]
+    pub fn oop_range(self, range: &str) -> Self {
+        self.range(0, range)
+    }
+
+    /// Sets the range of IP.
+    #[inline]
+    pub fn ip_range(self, range: &str) -> Self {
+        self.range(1, range)
+    }
+
+    /// Sets the flop (e.g., `"Td9d6h"`).
+    #[inline]
+    pub fn flop(mut self, flop: &str) -> Self {
+        if let Some(flop) = self.check(flop_from_str(flop)) {
+            self.card_config.flop = flop;
+        }
+        self
+    }
+
+    /// Sets the turn card (e.g., `"Qc"`).
+    #[inline]
+    pub fn turn(mut self, turn: &str) -> Self {
+        if let Some(turn) = self.check(card_from_str(turn)) {
+            self.card_config.turn = turn;
+        }
+        self
+    }
+
+    /// Sets the river card.
+    #[inline]
+    pub fn river(mut self, river: &str) -> Self {
+        if let Some(river) = self.check(card_from_str(river)) {
+            self.card_config.river = river;
+        }
+        self
+    }
+
+    /// Sets the starting pot (see [`TreeConfig::starting_pot`]).
+    #[inline]
+    pub fn starting_pot(mut self, starting_pot: i32) -> Self {
+        self.tree_config.starting_pot = starting_pot;
+        self
+    }
+
+    /// Sets the effective stack (see [`TreeConfig::effective_stack`]).
+    #[inline]
+    pub fn effective_stack(mut self, effective_stack: i32) -> Self {
+        self.tree_config.effective_stack = effective_stack;
+        self
+    }
+
+    /// Sets the rake rate and the rake cap (see [`TreeConfig::rake_rate`]).
+    #[inline]
+    pub fn rake(mut self, rate: f64, cap: f64) -> Self {
+        self.tree_config.rake_rate = rate;
+        self.tree_config.rake_cap = cap;
+        self
+    }
+
+    /// Sets the bet sizes of all streets (e.g., `"33%,75%,e"`).
+    #[inline]
+    pub fn bets(mut self, bets: &str) -> Self {
+        for sizes in &mut self.sizes {
+            sizes.0 = bets.to_string();
+        }
+        self
+    }
+
+    /// Sets the raise sizes of all streets (e.g., `"2.5x"`).
+    #[inline]
+    pub fn raises(mut self, raises: &str) -> Self {
+        for sizes in &mut self.sizes {
+            sizes.1 = raises.to_string();
+        }
+        self
+    }
+
+    /// Sets the bet and raise sizes of the given street, overriding [`bets`] and [`raises`].
+    ///
+    /// [`bets`]: #method.bets
+    /// [`raises`]: #method.raises
+    #[inline]
+    pub fn street_sizes(mut self, street: BoardState, bets: &str, raises: &str) -> Self {
+        self.sizes[street as usize] = (bets.to_string(), raises.to_string());
+        self
+    }
+
+    /// Returns the configurations without building the game, or the first error.
+    pub fn build_configs(self) -> Result<(CardConfig, TreeConfig), Error> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let card_config = self.card_config;
+        let mut tree_config = self.tree_config;
+
+        tree_config.initial_state = if card_config.river != NOT_DEALT {
+            BoardState::River
+        } else if card_config.turn != NOT_DEALT {
+            BoardState::Turn
+        } else {
+            BoardState::Flop
+        };
+
+        let bet_sizes = [
+            &mut tree_config.flop_bet_sizes,
+            &mut tree_config.turn_bet_sizes,
+            &mut tree_config.river_bet_sizes,
+        ];
+
+        for (bet_sizes, (bets, raises)) in bet_sizes.into_iter().zip(&self.sizes) {
+            let sizes = BetSizeOptions::try_from((bets.as_str(), raises.as_str()))?;
+            *bet_sizes = [sizes.clone(), sizes];
+        }
+
+        Ok((card_config, tree_config))
+    }
+
+    /// Builds the game, or returns the first error. The memory is not allocated.
+    pub fn build(self) -> Result<PostFlopGame, Error> {
+        let (card_config, tree_config) = self.build_configs()?;
+        let action_tree = ActionTree::new(tree_config)?;
+        PostFlopGame::with_config(card_config, action_tree)
+    }
+
+    #[inline]
+    fn range(mut self, player: usize, range: &str) -> Self {
+        if let Some(range) = self.check(range.parse()) {
+            self.card_config.range[player] = range;
+        }
+        self
+    }
+
+    /// Records the error of `result` if no error is recorded yet.
+    #[inline]
+    fn check<T>(&mut self, result: Result<T, Error>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.error.get_or_insert(e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_configs() {
+        let (card_config, tree_config) = GameBuilder::new()
+            .oop_range("QQ+")
+            .ip_range("JJ-99")
+            .flop("Td9d6h")
+            .starting_pot(100)
+            .effective_stack(500)
+            .rake(0.05, 30.0)
+            .bets("33%,75%,e")
+            .raises("3x")
+            .street_sizes(BoardState::River, "a", "")
+            .build_configs()
+            .unwrap();
+
+        assert_eq!(card_config.flop, flop_from_str("Td9d6h").unwrap());
+        assert_eq!(card_config.turn, NOT_DEALT);
+        assert_eq!(tree_config.initial_state, BoardState::Flop);
+        assert_eq!(tree_config.rake_cap, 30.0);
+
+        let flop = BetSizeOptions::try_from(("33%,75%,e", "3x")).unwrap();
+        let river = BetSizeOptions::try_from(("a", "")).unwrap();
+        assert_eq!(tree_config.flop_bet_sizes, [flop.clone(), flop.clone()]);
+        assert_eq!(tree_config.turn_bet_sizes, [flop.clone(), flop]);
+        assert_eq!(tree_config.river_bet_sizes, [river.clone(), river]);
+    }
+
+    #[test]
+    fn test_builder_first_error() {
+        let result = GameBuilder::new()
+            .oop_range("QQ+")
+            .ip_range("invalid")
+            .flop("Td9d")
+            .build();
+        assert!(matches!(result, Err(Error::Card(_))));
+
+        let result = GameBuilder::new().bets("33%").raises("-1x").build();
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+}
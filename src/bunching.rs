@@ -1,5 +1,6 @@
 use crate::atomic_float::*;
 use crate::card::*;
+use crate::error::*;
 use crate::range::*;
 use crate::utility::*;
 use std::io::{self, Write};
@@ -123,6 +124,7 @@ pub struct BunchingData {
     // input
     fold_ranges: Vec<Range>,
     flop: [Card; 3],
+    is_deterministic: bool,
 
     // current status
     phase: u8,
@@ -187,35 +189,40 @@ impl BunchingData {
     ///
     /// `fold_ranges` can contain at most 4 ranges (6-max).
     #[inline]
-    pub fn new(fold_ranges: &[Range], mut flop: [Card; 3]) -> Result<Self, String> {
+    pub fn new(fold_ranges: &[Range], mut flop: [Card; 3]) -> Result<Self, Error> {
         let mut fold_ranges_vec = Vec::new();
 
         for range in fold_ranges {
             if !range.is_empty() {
                 if !range.is_suit_symmetric() {
-                    return Err("Fold ranges must be suit-symmetric".to_string());
+                    return Err(Error::Config(
+                        "Fold ranges must be suit-symmetric".to_string(),
+                    ));
                 }
                 fold_ranges_vec.push(*range);
             }
         }
 
         if fold_ranges_vec.is_empty() {
-            return Err("Fold ranges is empty".to_string());
+            return Err(Error::Config("Fold ranges is empty".to_string()));
         }
 
         if fold_ranges_vec.len() > 4 {
-            return Err("The number of folded players must be at most 4".to_string());
+            return Err(Error::Config(
+                "The number of folded players must be at most 4".to_string(),
+            ));
         }
 
         flop.sort_unstable();
 
         if flop[0] == flop[1] || flop[1] == flop[2] || flop[2] >= 52 {
-            return Err("Invalid flop".to_string());
+            return Err(Error::Config("Invalid flop".to_string()));
         }
 
         Ok(Self {
             fold_ranges: fold_ranges_vec,
             flop,
+            is_deterministic: false,
             phase: 0,
             progress_percent: 0,
             temp_table1: Vec::new(),
@@ -240,6 +247,27 @@ impl BunchingData {
         self.flop
     }
 
+    /// Sets whether to sum up the frequencies in a fixed order (default: `false`).
+    ///
+    /// Phases 1 and 2 accumulate the frequencies from multiple threads, so the order of the
+    /// floating-point additions (and thus the last bits of the result) can vary with the number
+    /// of threads and the scheduling. When enabled, these phases run in a single thread in a
+    /// fixed order, so the result is bit-identical on every machine at the cost of the speed of
+    /// the two phases. Phase 3 and the solver do not depend on the number of threads.
+    #[inline]
+    pub fn set_deterministic(&mut self, enable: bool) {
+        self.is_deterministic = enable;
+    }
+
+    /// Returns whether the frequencies are summed up in a fixed order (see
+    /// [`set_deterministic`]).
+    ///
+    /// [`set_deterministic`]: #method.set_deterministic
+    #[inline]
+    pub fn is_deterministic(&self) -> bool {
+        self.is_deterministic
+    }
+
     /// Returns whether the instance is ready to use.
     #[inline]
     pub fn is_ready(&self) -> bool {
@@ -669,7 +697,7 @@ impl BunchingData {
         let start_index = (src_len1 as f64 * self.progress_percent as f64 / 100.0) as usize;
         let end_index = (src_len1 as f64 * (self.progress_percent + 1) as f64 / 100.0) as usize;
 
-        into_par_iter(start_index..end_index).for_each(|src_index1| {
+        let process = |src_index1: usize| {
             let freq1 = self.temp_table1[src_index1];
             if freq1 == 0.0 {
                 return;
@@ -689,7 +717,13 @@ impl BunchingData {
                 }
                 mask2 = next_combination(mask2);
             }
-        });
+        };
+
+        if self.is_deterministic {
+            (start_index..end_index).for_each(process);
+        } else {
+            into_par_iter(start_index..end_index).for_each(process);
+        }
     }
 
     /* Phase 2: Main process */
@@ -710,45 +744,51 @@ impl BunchingData {
             .map(|i| i.count_ones() as u8)
             .collect::<Vec<_>>();
 
-        into_par_iter(start_index..end_index)
-            .step_by(100)
-            .for_each(|chunk_start_index| {
-                let chunk_end_index = usize::min(chunk_start_index + 100, end_index);
-                let mut src_mask = index_to_mask(chunk_start_index, K);
+        let process = |chunk_start_index: usize| {
+            let chunk_end_index = usize::min(chunk_start_index + 100, end_index);
+            let mut src_mask = index_to_mask(chunk_start_index, K);
 
-                for src_index in chunk_start_index..chunk_end_index {
-                    let mut src_mask_copy = src_mask;
-                    src_mask = next_combination(src_mask);
+            for src_index in chunk_start_index..chunk_end_index {
+                let mut src_mask_copy = src_mask;
+                src_mask = next_combination(src_mask);
 
-                    let freq = src_table[src_index].load();
-                    if freq == 0.0 {
-                        continue;
-                    }
+                let freq = src_table[src_index].load();
+                if freq == 0.0 {
+                    continue;
+                }
 
-                    let mut src_mask_bit = [0; K];
-                    for i in 0..K {
-                        let lsb = src_mask_copy & src_mask_copy.wrapping_neg();
-                        src_mask_copy ^= lsb;
-                        src_mask_bit[i] = lsb;
-                    }
+                let mut src_mask_bit = [0; K];
+                for i in 0..K {
+                    let lsb = src_mask_copy & src_mask_copy.wrapping_neg();
+                    src_mask_copy ^= lsb;
+                    src_mask_bit[i] = lsb;
+                }
 
-                    for i in 0..(1 << K) - 1 {
-                        if num_ones[i] > 6 {
-                            continue;
-                        }
+                for i in 0..(1 << K) - 1 {
+                    if num_ones[i] > 6 {
+                        continue;
+                    }
 
-                        let mut dst_mask = 0;
-                        for j in 0..K {
-                            if i & (1 << j) != 0 {
-                                dst_mask |= src_mask_bit[j];
-                            }
+                    let mut dst_mask = 0;
+                    for j in 0..K {
+                        if i & (1 << j) != 0 {
+                            dst_mask |= src_mask_bit[j];
                         }
-
-                        let dst_index = mask_to_index(dst_mask, num_ones[i] as usize);
-                        self.sum[num_ones[i] as usize][dst_index].add(freq);
                     }
+
+                    let dst_index = mask_to_index(dst_mask, num_ones[i] as usize);
+                    self.sum[num_ones[i] as usize][dst_index].add(freq);
                 }
-            });
+            }
+        };
+
+        if self.is_deterministic {
+            (start_index..end_index).step_by(100).for_each(process);
+        } else {
+            into_par_iter(start_index..end_index)
+                .step_by(100)
+                .for_each(process);
+        }
     }
 
     /* Phase 3: Main process */
@@ -1085,4 +1125,30 @@ mod tests {
 
         bunching.process(true);
     }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_bunching_deterministic() {
+        let range1 = "77:0.3,76:0.7,66:0.1,65,55:0.45,54:0.9,44,43:0.2,33:0.35,32:0.6,22";
+        let range2 = "AA:0.15,AK:0.55,AQ,KK:0.8,KQ:0.25,QQ,QJ:0.65,JJ:0.05,JT,TT:0.95";
+        let flop = flop_from_str("8s8h8d").unwrap();
+
+        let compute = |num_threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let fold_ranges = [range1.parse().unwrap(), range2.parse().unwrap()];
+                let mut bunching = BunchingData::new(&fold_ranges, flop).unwrap();
+                bunching.set_deterministic(true);
+                bunching.phase1(false);
+                bunching.phase2(false);
+                let to_bits = |v: Vec<AtomicF64>| v.iter().map(|a| a.load().to_bits()).collect();
+                bunching.sum.map::<_, Vec<u64>>(to_bits)
+            })
+        };
+
+        assert_eq!(compute(1), compute(4));
+    }
 }
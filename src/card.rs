@@ -5,6 +5,9 @@ use std::mem;
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// A type representing a card, defined as an alias of `u8`.
 ///
 /// The correspondence between the card and its ID is defined as follows:
@@ -30,10 +33,14 @@ pub const NOT_DEALT: Card = Card::MAX;
 ///     flop: flop_from_str("Td9d6h").unwrap(),
 ///     turn: card_from_str("Qc").unwrap(),
 ///     river: NOT_DEALT,
+///     player_names: ["BB".to_string(), "BTN".to_string()],
+///     second_board: Vec::new(),
+///     disable_isomorphism: false,
 /// };
 /// ```
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CardConfig {
     /// Initial range of each player.
     pub range: [Range; 2],
@@ -46,6 +53,29 @@ pub struct CardConfig {
 
     /// River card: must be in range [`0`, `52`) or `NOT_DEALT`.
     pub river: Card,
+
+    /// Labels of each player (e.g., `"BB"` and `"BTN"`) used in reports and exports. An empty
+    /// label falls back to `"OOP"` or `"IP"`.
+    pub player_names: [String; 2],
+
+    /// Second board of a double-board game (e.g., a double-board bomb pot): must be empty or
+    /// consist of five unique cards that differ from the first board. Each board takes half of
+    /// the pot at showdown.
+    ///
+    /// Currently, a second board requires the game to start on the river, i.e., both boards must
+    /// be fully dealt.
+    pub second_board: Vec<Card>,
+
+    /// Whether to disable the suit isomorphism of chance nodes. By default, turn and river
+    /// cards that are equivalent by swapping suits (e.g., 2c and 2d on a spade flop with
+    /// suit-symmetric ranges) share a single subtree.
+    ///
+    /// Disabling the isomorphism increases the memory usage and the solving time, but is useful
+    /// for debugging and required when the showdown is not symmetric with respect to suits (e.g.,
+    /// a suit-dependent [`Evaluator`]).
+    ///
+    /// [`Evaluator`]: crate::Evaluator
+    pub disable_isomorphism: bool,
 }
 
 impl Default for CardConfig {
@@ -56,6 +86,9 @@ impl Default for CardConfig {
             flop: [NOT_DEALT; 3],
             turn: NOT_DEALT,
             river: NOT_DEALT,
+            player_names: Default::default(),
+            second_board: Vec::new(),
+            disable_isomorphism: false,
         }
     }
 }
@@ -103,6 +136,18 @@ pub(crate) fn index_to_card_pair(index: usize) -> (Card, Card) {
 }
 
 impl CardConfig {
+    /// Returns the label of the player (0 = OOP, 1 = IP).
+    ///
+    /// Falls back to `"OOP"` or `"IP"` if no label is given in `player_names`.
+    #[inline]
+    pub fn player_name(&self, player: usize) -> &str {
+        match self.player_names[player].as_str() {
+            "" if player == 0 => "OOP",
+            "" => "IP",
+            name => name,
+        }
+    }
+
     pub(crate) fn valid_indices(
         &self,
         private_cards: &PrivateCards,
@@ -180,9 +225,12 @@ impl CardConfig {
         ret
     }
 
-    pub(crate) fn hand_strength(
+    /// Computes the hand strength information where the hands are ranked by `evaluate` (larger
+    /// is stronger).
+    pub(crate) fn hand_strength<F: Fn(&[Card; 5], (Card, Card)) -> u64>(
         &self,
         private_cards: &PrivateCards,
+        evaluate: F,
     ) -> Vec<[Vec<StrengthItem>; 2]> {
         let mut ret = vec![Default::default(); 52 * 51 / 2];
 
@@ -199,42 +247,55 @@ impl CardConfig {
                     && (self.river == NOT_DEALT || board1 == self.river || board2 == self.river)
                 {
                     let board = board.add_card(board1 as usize).add_card(board2 as usize);
-                    let mut strength = [
-                        Vec::with_capacity(private_cards[0].len() + 2),
-                        Vec::with_capacity(private_cards[1].len() + 2),
-                    ];
+                    let board_cards = [self.flop[0], self.flop[1], self.flop[2], board1, board2];
+
+                    let values = [0, 1].map(|player| {
+                        private_cards[player]
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(index, &(c1, c2))| {
+                                if board.contains(c1 as usize) || board.contains(c2 as usize) {
+                                    None
+                                } else {
+                                    let value = evaluate(&board_cards, (c1, c2));
+                                    Some((value, index as u16))
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    });
+
+                    // convert the values into dense ranks so that they fit in `u16`
+                    let mut ranks = values
+                        .iter()
+                        .flat_map(|v| v.iter().map(|&(value, _)| value))
+                        .collect::<Vec<_>>();
+                    ranks.sort_unstable();
+                    ranks.dedup();
+
+                    let strength = [0, 1].map(|player| {
+                        let mut strength = Vec::with_capacity(values[player].len() + 2);
 
-                    for player in 0..2 {
                         // add the weakest and strongest sentinels
-                        strength[player].push(StrengthItem {
+                        strength.push(StrengthItem {
                             strength: 0,
                             index: 0,
                         });
-                        strength[player].push(StrengthItem {
+                        strength.push(StrengthItem {
                             strength: u16::MAX,
                             index: u16::MAX,
                         });
 
-                        strength[player].extend(
-                            private_cards[player].iter().enumerate().filter_map(
-                                |(index, &(c1, c2))| {
-                                    let (c1, c2) = (c1 as usize, c2 as usize);
-                                    if board.contains(c1) || board.contains(c2) {
-                                        None
-                                    } else {
-                                        let hand = board.add_card(c1).add_card(c2);
-                                        Some(StrengthItem {
-                                            strength: hand.evaluate() + 1, // +1 to avoid 0
-                                            index: index as u16,
-                                        })
-                                    }
-                                },
-                            ),
-                        );
+                        strength.extend(values[player].iter().map(|&(value, index)| {
+                            let rank = ranks.binary_search(&value).unwrap();
+                            StrengthItem {
+                                strength: rank as u16 + 1, // +1 to avoid 0
+                                index,
+                            }
+                        }));
 
-                        strength[player].shrink_to_fit();
-                        strength[player].sort_unstable();
-                    }
+                        strength.sort_unstable();
+                        strength
+                    });
 
                     ret[card_pair_to_index(board1, board2)] = strength;
                 }
@@ -249,7 +310,8 @@ impl CardConfig {
         let mut next_index = 1;
         'outer: for suit2 in 1..4 {
             for suit1 in 0..suit2 {
-                if self.range[0].is_suit_isomorphic(suit1, suit2)
+                if !self.disable_isomorphism
+                    && self.range[0].is_suit_isomorphic(suit1, suit2)
                     && self.range[1].is_suit_isomorphic(suit1, suit2)
                 {
                     suit_isomorphism[suit2 as usize] = suit_isomorphism[suit1 as usize];
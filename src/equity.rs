@@ -0,0 +1,379 @@
+use crate::card::*;
+use crate::error::*;
+use crate::hand::*;
+use crate::range::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Range-vs-range equity on a board, produced by [`compute_equity`].
+///
+/// The vectors of each player are indexed by the private hands in [`hands`], which are the hands
+/// of the range with positive weights not overlapping with the board, in the order of
+/// [`Range::get_hands_weights`] (the same order as [`PostFlopGame::private_cards`]).
+///
+/// [`hands`]: #structfield.hands
+/// [`PostFlopGame::private_cards`]: crate::PostFlopGame::private_cards
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RangeEquity {
+    /// The private hands of each player.
+    pub hands: [Vec<(Card, Card)>; 2],
+
+    /// The equity of each hand against the range of the opponent (a tie counts as half a win),
+    /// or zero if the hand does not have any opponent hand not overlapping with it.
+    pub equity: [Vec<f32>; 2],
+
+    /// The weight of each hand multiplied by the sum of the weights of the opponent hands not
+    /// overlapping with it, i.e., the number of combinations with which the hand is dealt after
+    /// the card removal (same as [`PostFlopGame::normalized_weights`] at the root).
+    ///
+    /// [`PostFlopGame::normalized_weights`]: crate::PostFlopGame::normalized_weights
+    pub weights: [Vec<f32>; 2],
+
+    /// The overall equity of each player, i.e., the average of [`equity`] weighted by
+    /// [`weights`].
+    ///
+    /// [`equity`]: #structfield.equity
+    /// [`weights`]: #structfield.weights
+    pub overall: [f32; 2],
+}
+
+impl RangeEquity {
+    /// Returns the expected value of each hand of the given player when both players go all-in
+    /// immediately, i.e., `equity * (starting_pot + 2 * effective_stack) - effective_stack`.
+    ///
+    /// The value is in the same convention as [`PostFlopGame::expected_values`] at the root: the
+    /// starting pot belongs to neither player, and the chips put into the pot are subtracted.
+    ///
+    /// [`PostFlopGame::expected_values`]: crate::PostFlopGame::expected_values
+    pub fn all_in_ev(&self, player: usize, starting_pot: i32, effective_stack: i32) -> Vec<f32> {
+        let final_pot = (starting_pot + 2 * effective_stack) as f32;
+        self.equity[player]
+            .iter()
+            .map(|&equity| equity * final_pot - effective_stack as f32)
+            .collect()
+    }
+
+    /// Returns the overall expected value of the given player when both players go all-in
+    /// immediately (see [`all_in_ev`]).
+    ///
+    /// [`all_in_ev`]: #method.all_in_ev
+    #[inline]
+    pub fn overall_all_in_ev(&self, player: usize, starting_pot: i32, effective_stack: i32) -> f32 {
+        let final_pot = (starting_pot + 2 * effective_stack) as f32;
+        self.overall[player] * final_pot - effective_stack as f32
+    }
+}
+
+/// Computes the equity of each private hand of both players on the given board by enumerating
+/// all runouts, taking the card removal into account.
+///
+/// `board` must consist of three to five distinct cards. The hand ranking is the standard
+/// high-hand ranking, consistent with the showdowns of [`PostFlopGame`] with the default
+/// evaluator. Unlike [`PostFlopGame::equity`], this function does not require building a game.
+///
+/// Returns an error if the board is invalid or either range is empty after removing the hands
+/// overlapping with the board.
+///
+/// **Time complexity:** *O*(#(runouts) * (#(OOP hands) log #(OOP hands) + #(IP hands) log #(IP
+/// hands))), where #(runouts) is 1081 on the flop, 46 on the turn, and 1 on the river.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let oop_range = "AA".parse().unwrap();
+/// let ip_range = "KK".parse().unwrap();
+/// let board = board_from_str("Td9d6h").unwrap();
+///
+/// let result = compute_equity(&oop_range, &ip_range, &board).unwrap();
+/// assert_eq!(result.hands[0].len(), 6);
+/// assert!((result.overall[0] - 0.9).abs() < 0.02);
+/// assert!((result.overall[0] + result.overall[1] - 1.0).abs() < 1e-6);
+/// ```
+///
+/// [`PostFlopGame`]: crate::PostFlopGame
+/// [`PostFlopGame::equity`]: crate::PostFlopGame::equity
+pub fn compute_equity(
+    range_oop: &Range,
+    range_ip: &Range,
+    board: &[Card],
+) -> Result<RangeEquity, Error> {
+    if !(3..=5).contains(&board.len()) {
+        return Err(Error::Card(
+            "Board must consist of 3 to 5 cards".to_string(),
+        ));
+    }
+
+    let mut board_mask: u64 = 0;
+    for &card in board {
+        if card >= 52 {
+            return Err(Error::Card(format!("Invalid card: {card}")));
+        }
+        if board_mask & (1 << card) != 0 {
+            return Err(Error::Card("Board cards must be unique".to_string()));
+        }
+        board_mask |= 1 << card;
+    }
+
+    let (hands_oop, weights_oop) = range_oop.get_hands_weights(board_mask);
+    let (hands_ip, weights_ip) = range_ip.get_hands_weights(board_mask);
+    if hands_oop.is_empty() || hands_ip.is_empty() {
+        return Err(Error::Config(
+            "Range is empty after removing the hands overlapping with the board".to_string(),
+        ));
+    }
+
+    let hands = [hands_oop, hands_ip];
+    let weights = [weights_oop, weights_ip];
+
+    // numerators (win + tie / 2) and denominators (compatible weights) summed over the runouts
+    let mut numer = [vec![0.0f64; hands[0].len()], vec![0.0f64; hands[1].len()]];
+    let mut denom = [vec![0.0f64; hands[0].len()], vec![0.0f64; hands[1].len()]];
+
+    let board_hand = board
+        .iter()
+        .fold(Hand::new(), |hand, &card| hand.add_card(card as usize));
+
+    for_each_runout(board_mask, 5 - board.len(), &mut |runout| {
+        let mut runout_mask = board_mask;
+        let mut runout_hand = board_hand;
+        for &card in runout {
+            runout_mask |= 1 << card;
+            runout_hand = runout_hand.add_card(card as usize);
+        }
+
+        let strength = [0, 1].map(|player| {
+            hands[player]
+                .iter()
+                .enumerate()
+                .filter(|(_, &(c1, c2))| runout_mask & ((1 << c1) | (1 << c2)) == 0)
+                .map(|(index, &(c1, c2))| {
+                    let hand = runout_hand.add_card(c1 as usize).add_card(c2 as usize);
+                    (hand.evaluate_internal(), index)
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for player in 0..2 {
+            accumulate_runout(
+                &mut numer[player],
+                &mut denom[player],
+                &strength[player],
+                &strength[player ^ 1],
+                &hands,
+                &weights,
+                player,
+            );
+        }
+    });
+
+    let mut ret = RangeEquity {
+        hands: hands.clone(),
+        ..Default::default()
+    };
+
+    for player in 0..2 {
+        let opponent_weights = &weights[player ^ 1];
+        let opponent_hands = &hands[player ^ 1];
+        let total_weight = opponent_weights.iter().map(|&w| w as f64).sum::<f64>();
+        let mut weight_minus = [0.0f64; 52];
+        for (&(c1, c2), &w) in opponent_hands.iter().zip(opponent_weights) {
+            weight_minus[c1 as usize] += w as f64;
+            weight_minus[c2 as usize] += w as f64;
+        }
+
+        let mut sum_equity = 0.0;
+        let mut sum_weight = 0.0;
+
+        for (i, &(c1, c2)) in hands[player].iter().enumerate() {
+            let equity = if denom[player][i] > 0.0 {
+                numer[player][i] / denom[player][i]
+            } else {
+                0.0
+            };
+
+            let same_hand_weight = range_weight(opponent_hands, opponent_weights, (c1, c2));
+            let compatible = total_weight - weight_minus[c1 as usize] - weight_minus[c2 as usize]
+                + same_hand_weight;
+            let weight = weights[player][i] as f64 * compatible.max(0.0);
+
+            ret.equity[player].push(equity as f32);
+            ret.weights[player].push(weight as f32);
+            sum_equity += equity * weight;
+            sum_weight += weight;
+        }
+
+        ret.overall[player] = if sum_weight > 0.0 {
+            (sum_equity / sum_weight) as f32
+        } else {
+            0.0
+        };
+    }
+
+    Ok(ret)
+}
+
+/// Calls `f` for each combination of `num_cards` cards not in `dead_mask`.
+fn for_each_runout(dead_mask: u64, num_cards: usize, f: &mut impl FnMut(&[Card])) {
+    fn recurse(
+        dead_mask: u64,
+        start: Card,
+        runout: &mut Vec<Card>,
+        num_cards: usize,
+        f: &mut impl FnMut(&[Card]),
+    ) {
+        if runout.len() == num_cards {
+            f(runout);
+            return;
+        }
+        for card in start..52 {
+            if dead_mask & (1 << card) == 0 {
+                runout.push(card);
+                recurse(dead_mask, card + 1, runout, num_cards, f);
+                runout.pop();
+            }
+        }
+    }
+
+    recurse(dead_mask, 0, &mut Vec::with_capacity(2), num_cards, f);
+}
+
+/// Adds the wins (plus half the ties) and the compatible weights of the opponent hands to each
+/// hand of `player` on a single runout.
+///
+/// `player_strength` and `opponent_strength` are the strengths of the hands not overlapping with
+/// the runout.
+fn accumulate_runout(
+    numer: &mut [f64],
+    denom: &mut [f64],
+    player_strength: &[(i32, usize)],
+    opponent_strength: &[(i32, usize)],
+    hands: &[Vec<(Card, Card)>; 2],
+    weights: &[Vec<f32>; 2],
+    player: usize,
+) {
+    let player_cards = &hands[player];
+    let opponent_cards = &hands[player ^ 1];
+    let opponent_weights = &weights[player ^ 1];
+
+    let mut player_sorted = player_strength.to_vec();
+    let mut opponent_sorted = opponent_strength.to_vec();
+    player_sorted.sort_unstable();
+    opponent_sorted.sort_unstable();
+
+    // total weights of the opponent hands (and per card) on this runout
+    let mut total = 0.0;
+    let mut total_minus = [0.0f64; 52];
+    for &(_, index) in &opponent_sorted {
+        let (c1, c2) = opponent_cards[index];
+        let w = opponent_weights[index] as f64;
+        total += w;
+        total_minus[c1 as usize] += w;
+        total_minus[c2 as usize] += w;
+    }
+
+    // weights of the strictly weaker (`lt`) and weaker or equal (`le`) opponent hands
+    let mut lt = (0.0, [0.0f64; 52], 0);
+    let mut le = (0.0, [0.0f64; 52], 0);
+
+    for &(strength, index) in &player_sorted {
+        for (sum, minus, j, is_le) in [
+            (&mut lt.0, &mut lt.1, &mut lt.2, false),
+            (&mut le.0, &mut le.1, &mut le.2, true),
+        ] {
+            while *j < opponent_sorted.len()
+                && (opponent_sorted[*j].0 < strength
+                    || (is_le && opponent_sorted[*j].0 == strength))
+            {
+                let opponent_index = opponent_sorted[*j].1;
+                let (c1, c2) = opponent_cards[opponent_index];
+                let w = opponent_weights[opponent_index] as f64;
+                *sum += w;
+                minus[c1 as usize] += w;
+                minus[c2 as usize] += w;
+                *j += 1;
+            }
+        }
+
+        let (c1, c2) = player_cards[index];
+        let (c1, c2) = (c1 as usize, c2 as usize);
+
+        // the same hand as the opponent is counted twice in `minus[c1] + minus[c2]`; it is never
+        // strictly weaker, but it is always weaker or equal
+        let same = range_weight(opponent_cards, opponent_weights, player_cards[index]);
+        let win = lt.0 - lt.1[c1] - lt.1[c2];
+        let win_or_tie = le.0 - le.1[c1] - le.1[c2] + same;
+        let compatible = total - total_minus[c1] - total_minus[c2] + same;
+
+        numer[index] += 0.5 * (win + win_or_tie);
+        denom[index] += compatible;
+    }
+}
+
+/// Returns the weight of `hand` in the sorted list of hands, or zero if not found.
+#[inline]
+fn range_weight(hands: &[(Card, Card)], weights: &[f32], hand: (Card, Card)) -> f64 {
+    hands
+        .binary_search(&hand)
+        .map_or(0.0, |index| weights[index] as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equity_river() {
+        let board = board_from_str("Td9d6h2c3s").unwrap();
+        let oop = "AA,QQ".parse().unwrap();
+        let ip = "KK".parse().unwrap();
+        let result = compute_equity(&oop, &ip, &board).unwrap();
+
+        for (&(c1, _), &equity) in result.hands[0].iter().zip(&result.equity[0]) {
+            let expected = if c1 / 4 == 12 { 1.0 } else { 0.0 };
+            assert_eq!(equity, expected);
+        }
+        assert!((result.overall[0] - 0.5).abs() < 1e-6);
+        assert!(result.weights[0].iter().all(|&w| w == 6.0));
+
+        // ties
+        let result = compute_equity(&ip, &ip, &board).unwrap();
+        for player in 0..2 {
+            assert!(result.equity[player].iter().all(|&e| e == 0.5));
+            assert!(result.weights[player].iter().all(|&w| w == 1.0));
+        }
+    }
+
+    #[test]
+    fn test_equity_card_removal() {
+        let board = board_from_str("Td9d6h2c").unwrap();
+        let oop = "AsKs".parse().unwrap();
+        let ip = "AA,QQ".parse().unwrap();
+        let result = compute_equity(&oop, &ip, &board).unwrap();
+
+        // `As` blocks three combos of AA
+        assert_eq!(result.weights[0], vec![9.0]);
+        assert_eq!(result.weights[1].iter().filter(|&&w| w > 0.0).count(), 9);
+        assert!((result.overall[0] + result.overall[1] - 1.0).abs() < 1e-6);
+
+        // AK never beats AA and hits six outs against QQ
+        let expected = (6.0 * 6.0) / (9.0 * 44.0);
+        assert!((result.equity[0][0] - expected).abs() < 1e-6);
+
+        let ev = result.all_in_ev(0, 100, 200);
+        assert!((ev[0] - (expected * 500.0 - 200.0)).abs() < 1e-3);
+        assert!((result.overall_all_in_ev(0, 100, 200) - ev[0]).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_equity_invalid() {
+        let range = "AA".parse().unwrap();
+        assert!(compute_equity(&range, &range, &[0, 1]).is_err());
+        assert!(compute_equity(&range, &range, &[0, 1, 1]).is_err());
+        assert!(compute_equity(&range, &range, &[0, 1, 52]).is_err());
+        let board = board_from_str("AsAhAd").unwrap();
+        assert!(compute_equity(&range, &range, &board).is_err());
+    }
+}
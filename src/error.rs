@@ -0,0 +1,134 @@
+use crate::game::StateError;
+use std::io;
+use thiserror::Error;
+
+#[cfg(feature = "solver")]
+use crate::solver::NonFiniteError;
+
+/// The error type of the library.
+///
+/// All fallible functions of the crate return this type. The variants classify the cause of the
+/// failure so that the callers can branch on it, while the messages describe the details.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A configuration or an argument is invalid (e.g., [`CardConfig`], [`TreeConfig`], a bet
+    /// size string, or a report specification), or the game uses a feature that the operation
+    /// does not support.
+    ///
+    /// [`CardConfig`]: crate::CardConfig
+    /// [`TreeConfig`]: crate::TreeConfig
+    #[error("{0}")]
+    Config(String),
+
+    /// A card, a hand, or a range is invalid or failed to parse.
+    #[error("{0}")]
+    Card(String),
+
+    /// An action, a line, or a node of a game tree is invalid (e.g., an action that does not
+    /// exist or a history leading to a terminal node).
+    #[error("{0}")]
+    Tree(String),
+
+    /// Reading or writing a file, a stream, or a database failed, or the read data is malformed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The operation is not valid for the current state of the game (e.g., querying the
+    /// strategy of an unsolved game).
+    #[error(transparent)]
+    State(#[from] StateError),
+
+    /// NaN or infinity was found during solving (see [`SolverConfig::check_finite`]).
+    ///
+    /// [`SolverConfig::check_finite`]: crate::SolverConfig::check_finite
+    #[cfg(feature = "solver")]
+    #[error(transparent)]
+    NonFinite(#[from] NonFiniteError),
+
+    /// The solver could not be run or did not find a solution (e.g., a thread pool could not be
+    /// built, or the linear program is infeasible).
+    #[error("{0}")]
+    Solver(String),
+
+    /// A verification failed: the data do not match the game or the expected values (e.g., a
+    /// tampered certificate, strategies that differ from a golden file, or an inconsistent
+    /// [`Game`] implementation).
+    ///
+    /// [`Game`]: crate::Game
+    #[error("{0}")]
+    Verification(String),
+}
+
+impl Error {
+    /// Creates an [`Error::Io`] that keeps the kind of `e` and prefixes its message with `context`.
+    #[inline]
+    pub(crate) fn io(e: io::Error, context: &str) -> Self {
+        Self::Io(io::Error::new(e.kind(), format!("{context}: {e}")))
+    }
+
+    /// Creates an [`Error::Io`] of the kind [`io::ErrorKind::InvalidData`] with the message.
+    #[inline]
+    pub(crate) fn invalid_data(message: impl Into<String>) -> Self {
+        Self::Io(io::Error::new(io::ErrorKind::InvalidData, message.into()))
+    }
+}
+
+impl PartialEq for Error {
+    /// Compares the variants and the messages; I/O errors are compared by their kinds and
+    /// messages.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Config(a), Self::Config(b)) => a == b,
+            (Self::Card(a), Self::Card(b)) => a == b,
+            (Self::Tree(a), Self::Tree(b)) => a == b,
+            (Self::Io(a), Self::Io(b)) => a.kind() == b.kind() && a.to_string() == b.to_string(),
+            (Self::State(a), Self::State(b)) => a == b,
+            #[cfg(feature = "solver")]
+            (Self::NonFinite(a), Self::NonFinite(b)) => a == b,
+            (Self::Solver(a), Self::Solver(b)) => a == b,
+            (Self::Verification(a), Self::Verification(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn error_variants() {
+        assert!(matches!("AK+-AJ".parse::<Range>(), Err(Error::Card(_))));
+        assert!(matches!(card_from_str("Xx"), Err(Error::Card(_))));
+
+        let tree_config = TreeConfig {
+            starting_pot: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            ActionTree::new(tree_config),
+            Err(Error::Config(_))
+        ));
+
+        let card_config = CardConfig {
+            range: ["AA".parse().unwrap(), Range::new()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            starting_pot: 100,
+            effective_stack: 100,
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let err = PostFlopGame::with_config(card_config, action_tree).err();
+        assert_eq!(err, Some(Error::Config("IP range is empty".to_string())));
+
+        #[cfg(feature = "bincode")]
+        {
+            let err = load_data_from_std_read::<PostFlopGame, _>(&mut &b"broken"[..], None).err();
+            assert!(matches!(err, Some(Error::Io(e)) if e.kind() == io::ErrorKind::InvalidData));
+        }
+    }
+}
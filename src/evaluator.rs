@@ -0,0 +1,218 @@
+use crate::card::*;
+use crate::hand::*;
+
+/// A trait representing a ranking of the hands at showdown.
+///
+/// An evaluator can be plugged into [`PostFlopGame`] with [`PostFlopGame::set_evaluator`] to
+/// solve variants with non-standard showdowns (e.g., lowball). The standard high-hand ranking
+/// ([`HighHandEvaluator`]) is used by default.
+///
+/// Since the solver exploits the suit isomorphism of the game, the ranking must not depend on
+/// specific suits (checking whether the cards are suited is fine).
+///
+/// [`PostFlopGame`]: crate::PostFlopGame
+/// [`PostFlopGame::set_evaluator`]: crate::PostFlopGame::set_evaluator
+pub trait Evaluator: Send + Sync {
+    /// Returns the strength of `hand` on the five-card `board` (the order of the board cards is
+    /// unspecified). A larger value represents a stronger hand and hands with the same value
+    /// split the pot. The values are only compared between hands on the same board.
+    fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32;
+
+    /// Returns whether the pot is split between the high hand ranked by [`evaluate`] and the
+    /// low hand ranked by [`evaluate_low`]. The default implementation returns `false`.
+    ///
+    /// [`evaluate`]: #tymethod.evaluate
+    /// [`evaluate_low`]: #method.evaluate_low
+    #[inline]
+    fn is_split_pot(&self) -> bool {
+        false
+    }
+
+    /// Returns the strength of the low hand, or `None` if the hand does not qualify for low.
+    /// A larger value represents a stronger low hand. Only used when [`is_split_pot`] returns
+    /// `true`.
+    ///
+    /// At a split-pot showdown, the best high hand takes half of the pot and the best qualified
+    /// low hand takes the other half. If neither hand qualifies for low, the high hand takes the
+    /// whole pot. The default implementation returns `None`.
+    ///
+    /// [`is_split_pot`]: #method.is_split_pot
+    #[inline]
+    fn evaluate_low(&self, _board: &[Card; 5], _hand: (Card, Card)) -> Option<u32> {
+        None
+    }
+}
+
+/// The standard high-hand ranking.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HighHandEvaluator;
+
+/// The deuce-to-seven lowball ranking.
+///
+/// The best five-card hand out of the seven cards is used. Aces are always high, and straights
+/// and flushes count against the hand, so the best possible hand is 7-5-4-3-2 of mixed suits.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeuceToSevenEvaluator;
+
+/// The high-low split ranking with an eight-or-better qualifier for low (as in hold'em hi-lo).
+///
+/// The high half is ranked by the standard high-hand ranking. The low half is ranked by the
+/// ace-to-five ranking: aces are low, straights and flushes are ignored, and a low hand
+/// requires five cards of distinct ranks of eight or lower.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EightOrBetterEvaluator;
+
+impl Evaluator for HighHandEvaluator {
+    #[inline]
+    fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+        let hand = board
+            .iter()
+            .chain([hand.0, hand.1].iter())
+            .fold(Hand::new(), |hand, &card| hand.add_card(card as usize));
+        hand.evaluate() as u32
+    }
+}
+
+impl Evaluator for DeuceToSevenEvaluator {
+    fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+        let cards = [
+            board[0], board[1], board[2], board[3], board[4], hand.0, hand.1,
+        ];
+
+        // choose the five cards by excluding two of the seven cards
+        let mut best = 0;
+        for skip1 in 0..7 {
+            for skip2 in skip1 + 1..7 {
+                let hand = (0..7)
+                    .filter(|&i| i != skip1 && i != skip2)
+                    .fold(Hand::new(), |hand, i| hand.add_card(cards[i] as usize));
+                best = best.max(LOWBALL_OFFSET - deuce_to_seven_high_value(&hand));
+            }
+        }
+
+        best as u32
+    }
+}
+
+impl Evaluator for EightOrBetterEvaluator {
+    #[inline]
+    fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+        HighHandEvaluator.evaluate(board, hand)
+    }
+
+    #[inline]
+    fn is_split_pot(&self) -> bool {
+        true
+    }
+
+    fn evaluate_low(&self, board: &[Card; 5], hand: (Card, Card)) -> Option<u32> {
+        // bit 0: ace, bit 1: deuce, ..., bit 7: eight
+        let mut rankset = 0u32;
+        for &card in board.iter().chain([hand.0, hand.1].iter()) {
+            let rank = card >> 2;
+            if rank == 12 {
+                rankset |= 1;
+            } else if rank <= 6 {
+                rankset |= 1 << (rank + 1);
+            }
+        }
+
+        if rankset.count_ones() < 5 {
+            return None;
+        }
+
+        // keep the five lowest ranks; comparing the rank sets as integers compares the highest
+        // cards first
+        while rankset.count_ones() > 5 {
+            rankset ^= 1 << (31 - rankset.leading_zeros());
+        }
+
+        Some(256 - rankset)
+    }
+}
+
+/// Upper bound of the raw hand values.
+const LOWBALL_OFFSET: i32 = 1 << 30;
+
+/// Returns the raw high-hand value of the five-card `hand` where aces are always high (i.e.,
+/// A-2-3-4-5 is not a straight).
+fn deuce_to_seven_high_value(hand: &Hand) -> i32 {
+    const WHEEL: i32 = 0b1_0000_0000_1111;
+    let value = hand.evaluate_internal();
+    let category = value >> 26;
+    if (category == 4 || category == 8) && value & ((1 << 26) - 1) == 1 << 3 {
+        // A-2-3-4-5: ace-high (flush or high card)
+        let category = if category == 8 { 5 } else { 0 };
+        (category << 26) | WHEEL
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range::*;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.as_bytes()
+            .chunks(2)
+            .map(|c| card_from_chars(&mut c.iter().map(|&b| b as char)).unwrap())
+            .collect()
+    }
+
+    fn hand_of(s: &str) -> (Card, Card) {
+        let hand = cards(s);
+        (hand[0], hand[1])
+    }
+
+    fn evaluate(evaluator: &dyn Evaluator, board: &str, hand: &str) -> u32 {
+        evaluator.evaluate(&cards(board).try_into().unwrap(), hand_of(hand))
+    }
+
+    #[test]
+    fn high_hand_evaluator() {
+        let e = HighHandEvaluator;
+        let board = "2c3d4hTsKs";
+        assert!(evaluate(&e, board, "AcAd") > evaluate(&e, board, "7h5c"));
+        assert!(evaluate(&e, board, "5c6c") > evaluate(&e, board, "AcAd"));
+        assert_eq!(evaluate(&e, board, "8c9c"), evaluate(&e, board, "8d9d"));
+    }
+
+    #[test]
+    fn deuce_to_seven_evaluator() {
+        let e = DeuceToSevenEvaluator;
+        let board = "2c3d4hTsKs";
+        let nuts = evaluate(&e, board, "7h5c");
+        assert!(nuts > evaluate(&e, board, "8h5c"));
+        assert!(evaluate(&e, board, "8h5c") > evaluate(&e, board, "5c6c")); // T-6-4-3-2
+        assert!(evaluate(&e, board, "5c6c") > evaluate(&e, board, "AcAd")); // K-T-4-3-2
+        assert_eq!(evaluate(&e, board, "7d5d"), nuts);
+
+        // A-2-3-4-5 is ace-high, not a straight
+        let board = "2c3d4h5s5d";
+        assert!(evaluate(&e, board, "Ad5h") > evaluate(&e, board, "6d6h"));
+
+        // flushes count against the hand
+        let board = "2s3s4s9dTd";
+        assert!(evaluate(&e, board, "7d5c") > evaluate(&e, board, "7s5s"));
+    }
+
+    #[test]
+    fn eight_or_better_evaluator() {
+        let e = EightOrBetterEvaluator;
+        let board = "2c3d7hTsKs";
+        let low = |hand| e.evaluate_low(&cards(board).try_into().unwrap(), hand_of(hand));
+
+        assert!(e.is_split_pot());
+        assert!(low("Ac4c").unwrap() > low("5c4c").unwrap()); // 7-4-3-2-A vs 7-5-4-3-2
+        assert!(low("4c5c").unwrap() > low("Ac8c").unwrap()); // 7-5-4-3-2 vs 8-7-3-2-A
+        assert_eq!(low("Ac4c"), low("Ad4d"));
+        assert_eq!(low("Ac9c"), None);
+        assert_eq!(low("AcAd"), None);
+        assert_eq!(
+            e.evaluate(&cards(board).try_into().unwrap(), hand_of("AcAd")),
+            HighHandEvaluator.evaluate(&cards(board).try_into().unwrap(), hand_of("AcAd"))
+        );
+    }
+}
@@ -1,31 +1,103 @@
 // [File format]
 // The file consists of a header and a body. The header is as follows:
 //  - Magic number (4 bytes): 90 57 f1 09
-//  - Version number (1 byte): 1
+//  - Version number (1 byte): 11
 //  - Compression type (1 byte): 0 (none), 1 (zstd)
-//  - Data type (1 byte): 0 (game), 1 (bunching)
+//  - Data type (1 byte): 0 (game), 1 (bunching), 2 (exploitability certificate),
+//    3 (pruned solution), 4 (action translation table)
 //  - Estimated memory usage (`VarIntEncoding`)
 //  - Memo string
+//  - XXH64 checksum of the header fields above with `HEADER_CHECKSUM_SEED` as the seed (8 bytes,
+//    little endian)
+//
+// The body (the data, compressed with zstd if the compression type is 1) is split into chunks of
+// at most `CHUNK_SIZE` bytes. Each chunk is as follows:
+//  - Length (4 bytes, little endian)
+//  - Content (`length` bytes)
+//  - XXH64 checksum of the content with the index of the chunk as the seed (8 bytes, little endian)
+// The body ends with an empty chunk, so that truncated files are also detected.
+//
+// Changes in the recent versions (files of other versions are rejected):
+//  - 10: The body is split into checksummed chunks.
+//  - 11: `TreeConfig` stores `allin_chop_fee_rate` and `allin_chop_fee_cap`.
+//  - 12: The header is followed by its checksum.
 //
 // `VarIntEncoding`: https://github.com/bincode-org/bincode/blob/trunk/docs/spec.md#varintencoding
 
+use crate::action_tree::*;
 use crate::bunching::*;
+use crate::error::*;
 use crate::game::*;
 use crate::interface::*;
 use bincode::{Decode, Encode};
+use std::io::{self, Read, Write};
+use xxhash_rust::xxh64::xxh64;
+
+#[cfg(feature = "analysis")]
+use crate::card::*;
+
+#[cfg(feature = "analysis")]
+use std::mem;
+
+#[cfg(feature = "io")]
+use crate::atomic_file::*;
+
+#[cfg(feature = "io")]
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
+
+#[cfg(feature = "io")]
+use std::io::BufReader;
+
+#[cfg(feature = "io")]
 use std::path::Path;
 
 const MAGIC: u32 = 0x09f15790;
-const VERSION: u8 = 1;
+const VERSION: u8 = 12;
 
-#[doc(hidden)]
+/// The maximum number of bytes of the body covered by a single checksum.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// The seed of the checksum of the header, distinct from the seeds of the chunks of the body.
+const HEADER_CHECKSUM_SEED: u64 = u64::MAX;
+
+/// Type of the data stored in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataType {
+    /// [`PostFlopGame`].
     Game = 0,
+
+    /// [`BunchingData`].
     Bunching = 1,
+
+    /// [`ExploitabilityCertificate`].
+    Certificate = 2,
+
+    /// [`PrunedSolution`].
+    PrunedSolution = 3,
+
+    /// [`ActionTranslationTable`].
+    ActionTranslationTable = 4,
 }
 
+/// Header of a saved file, read by [`read_file_header`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHeader {
+    /// The type of the data.
+    pub data_type: DataType,
+
+    /// Whether the data is compressed with zstd.
+    pub is_compressed: bool,
+
+    /// The estimated memory usage of the data (in bytes) recorded by the writer.
+    pub estimated_memory_usage: u64,
+
+    /// The memo string.
+    pub memo: String,
+}
+
+/// Maximum length of a memo string (in bytes) accepted when loading a file.
+pub const MAX_MEMO_LENGTH: usize = 1 << 20;
+
 /// A trait for data that can be saved into a file.
 pub trait FileData: Decode + Encode {
     #[doc(hidden)]
@@ -40,9 +112,180 @@ fn encode_into_std_write<E: Encode, W: Write>(
     val: E,
     writer: &mut W,
     err_msg: &str,
-) -> Result<usize, String> {
+) -> Result<usize, Error> {
     bincode::encode_into_std_write(val, writer, bincode::config::standard())
-        .map_err(|e| format!("{}: {}", err_msg, e))
+        .map_err(|e| Error::Io(io::Error::other(format!("{}: {}", err_msg, e))))
+}
+
+/// Writes the header followed by its checksum.
+fn write_file_header<W: Write>(writer: &mut W, header: &FileHeader) -> Result<(), Error> {
+    let mut buf = Vec::new();
+    encode_into_std_write(MAGIC, &mut buf, "Failed to write magic number")?;
+    encode_into_std_write(VERSION, &mut buf, "Failed to write version number")?;
+
+    let compression_type = header.is_compressed as u8;
+    encode_into_std_write(
+        compression_type,
+        &mut buf,
+        "Failed to write compression type",
+    )?;
+
+    let data_type = header.data_type as u8;
+    encode_into_std_write(data_type, &mut buf, "Failed to write data type")?;
+    encode_into_std_write(
+        header.estimated_memory_usage,
+        &mut buf,
+        "Failed to write memory usage",
+    )?;
+
+    encode_into_std_write(header.memo.as_str(), &mut buf, "Failed to write memo")?;
+
+    let checksum = xxh64(&buf, HEADER_CHECKSUM_SEED);
+    writer
+        .write_all(&buf)
+        .and_then(|_| writer.write_all(&checksum.to_le_bytes()))
+        .map_err(|e| Error::io(e, "Failed to write header"))
+}
+
+/// A reader that records the bytes read, so that the checksum of the header can be computed.
+struct RecordingReader<'a, R: Read> {
+    reader: &'a mut R,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> Read for RecordingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.buf.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// A writer that splits the body into chunks followed by their checksums.
+struct ChecksumWriter<W: Write> {
+    writer: W,
+    buf: Vec<u8>,
+    chunk_index: u64,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    #[inline]
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buf: Vec::with_capacity(CHUNK_SIZE),
+            chunk_index: 0,
+        }
+    }
+
+    fn write_chunk(&mut self) -> io::Result<()> {
+        let checksum = xxh64(&self.buf, self.chunk_index);
+        self.writer
+            .write_all(&(self.buf.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&self.buf)?;
+        self.writer.write_all(&checksum.to_le_bytes())?;
+        self.buf.clear();
+        self.chunk_index += 1;
+        Ok(())
+    }
+
+    /// Writes the pending chunk and the terminating empty chunk, and returns the inner writer.
+    fn finish(mut self) -> io::Result<W> {
+        if !self.buf.is_empty() {
+            self.write_chunk()?;
+        }
+        self.write_chunk()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(CHUNK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&buf[..len]);
+        if self.buf.len() == CHUNK_SIZE {
+            self.write_chunk()?;
+        }
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        // the pending chunk is written by `finish`
+        self.writer.flush()
+    }
+}
+
+/// A reader that verifies the checksums of the chunks written by [`ChecksumWriter`].
+struct ChecksumReader<R: Read> {
+    reader: R,
+    buf: Vec<u8>,
+    pos: usize,
+    chunk_index: u64,
+    is_finished: bool,
+}
+
+impl<R: Read> ChecksumReader<R> {
+    #[inline]
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            pos: 0,
+            chunk_index: 0,
+            is_finished: false,
+        }
+    }
+
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let mut len = [0; 4];
+        self.reader.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len > CHUNK_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Chunk {} has an invalid length", self.chunk_index),
+            ));
+        }
+
+        self.buf.resize(len, 0);
+        self.reader.read_exact(&mut self.buf)?;
+
+        let mut checksum = [0; 8];
+        self.reader.read_exact(&mut checksum)?;
+        if u64::from_le_bytes(checksum) != xxh64(&self.buf, self.chunk_index) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Checksum mismatch in chunk {}", self.chunk_index),
+            ));
+        }
+
+        self.pos = 0;
+        self.chunk_index += 1;
+        self.is_finished = len == 0;
+        Ok(())
+    }
+
+    /// Reads and verifies the remaining chunks up to the terminating empty chunk.
+    fn finish(&mut self) -> io::Result<()> {
+        while !self.is_finished {
+            self.read_chunk()?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.buf.len() && !self.is_finished {
+            self.read_chunk()?;
+        }
+
+        let len = buf.len().min(self.buf.len() - self.pos);
+        buf[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
 }
 
 /// Saves data into a standard writer.
@@ -51,6 +294,9 @@ fn encode_into_std_write<E: Encode, W: Write>(
 /// This is useful if you want to save the data into a custom writer like `Vec<u8>`, but if you want
 /// to save the data into a file, use [`save_data_to_file`] instead.
 ///
+/// The serialized data is split into chunks of 1 MiB, each followed by its XXH64 checksum, which
+/// is verified at load time.
+///
 /// # Arguments
 ///
 /// - `data`: The data to be saved, which is either a [`PostFlopGame`] or a [`BunchingData`].
@@ -63,56 +309,52 @@ pub fn save_data_into_std_write<T: FileData, W: Write>(
     memo: &str,
     writer: &mut W,
     compression_level: Option<i32>,
-) -> Result<(), String> {
+) -> Result<(), Error> {
     if !data.is_ready_to_save() {
-        return Err("Data is not ready to save".to_string());
+        return Err(Error::Config("Data is not ready to save".to_string()));
     }
 
     #[cfg(not(feature = "zstd"))]
     if compression_level.is_some() {
-        return Err("Compression is not supported".to_string());
+        return Err(Error::Config("Compression is not supported".to_string()));
     }
 
-    encode_into_std_write(MAGIC, writer, "Failed to write magic number")?;
-    encode_into_std_write(VERSION, writer, "Failed to write version number")?;
-
-    let compression_type = compression_level.is_some() as u8;
-    encode_into_std_write(compression_type, writer, "Failed to write compression type")?;
-
-    encode_into_std_write(T::data_type() as u8, writer, "Failed to write data type")?;
-    encode_into_std_write(
-        data.estimated_memory_usage(),
-        writer,
-        "Failed to write memory usage",
-    )?;
+    let header = FileHeader {
+        data_type: T::data_type(),
+        is_compressed: compression_level.is_some(),
+        estimated_memory_usage: data.estimated_memory_usage(),
+        memo: memo.to_string(),
+    };
+    write_file_header(writer, &header)?;
 
-    encode_into_std_write(memo, writer, "Failed to write memo")?;
+    let mut checksum_writer = ChecksumWriter::new(writer);
 
     if compression_level.is_none() {
-        encode_into_std_write(data, writer, "Failed to write data")?;
-        writer
-            .flush()
-            .map_err(|e| format!("Failed to flush writer: {}", e))?;
+        encode_into_std_write(data, &mut checksum_writer, "Failed to write data")?;
     }
 
     #[cfg(feature = "zstd")]
     if let Some(compression_level) = compression_level {
-        let mut zstd_encoder = zstd::stream::Encoder::new(writer, compression_level)
-            .map_err(|e| format!("Failed to create zstd encoder: {}", e))?;
+        let mut zstd_encoder = zstd::stream::Encoder::new(&mut checksum_writer, compression_level)
+            .map_err(|e| Error::io(e, "Failed to create zstd encoder"))?;
 
         #[cfg(feature = "rayon")]
         zstd_encoder
             .multithread(rayon::current_num_threads() as u32)
-            .map_err(|e| format!("Failed to enable multithreaded zstd encoder: {}", e))?;
+            .map_err(|e| Error::io(e, "Failed to enable multithreaded zstd encoder"))?;
 
         encode_into_std_write(data, &mut zstd_encoder, "Failed to write data")?;
         zstd_encoder
             .finish()
-            .map_err(|e| format!("Failed to finish zstd encoder: {}", e))?
-            .flush()
-            .map_err(|e| format!("Failed to flush writer: {}", e))?;
+            .map_err(|e| Error::io(e, "Failed to finish zstd encoder"))?;
     }
 
+    checksum_writer
+        .finish()
+        .map_err(|e| Error::io(e, "Failed to write checksum"))?
+        .flush()
+        .map_err(|e| Error::io(e, "Failed to flush writer"))?;
+
     Ok(())
 }
 
@@ -121,6 +363,10 @@ pub fn save_data_into_std_write<T: FileData, W: Write>(
 /// This function serializes the `data` into a file specified by `path`.
 /// If the file already exists, it will be overwritten.
 ///
+/// The data is written to a temporary file next to `path`, which is flushed to the disk and then
+/// renamed to `path`, so an interrupted save never leaves a truncated file at `path` (see
+/// [`interrupted_save_path`]).
+///
 /// # Arguments
 ///
 /// - `data`: The data to be saved, which is either a [`PostFlopGame`] or a [`BunchingData`].
@@ -128,20 +374,99 @@ pub fn save_data_into_std_write<T: FileData, W: Write>(
 /// - `path`: The path to the file to save.
 /// - `compression_level`: The zstd compression level to use. If `None`, no compression is used.
 ///   `Some(level)` can only be specified if the `zstd` feature is enabled.
+#[cfg(feature = "io")]
 pub fn save_data_to_file<T: FileData, P: AsRef<Path>>(
     data: &T,
     memo: &str,
     path: P,
     compression_level: Option<i32>,
-) -> Result<(), String> {
-    let file = File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
-    let mut writer = BufWriter::new(file);
-    save_data_into_std_write(data, memo, &mut writer, compression_level)
+) -> Result<(), Error> {
+    let mut file = AtomicFile::create(path).map_err(|e| Error::io(e, "Failed to create file"))?;
+    save_data_into_std_write(data, memo, &mut file, compression_level)?;
+    file.commit()
+        .map_err(|e| Error::io(e, "Failed to commit file"))
 }
 
-fn decode_from_std_read<D: Decode, R: Read>(reader: &mut R, err_msg: &str) -> Result<D, String> {
+fn decode_from_std_read<D: Decode, R: Read>(reader: &mut R, err_msg: &str) -> Result<D, Error> {
     bincode::decode_from_std_read(reader, bincode::config::standard())
-        .map_err(|e| format!("{}: {}", err_msg, e))
+        .map_err(|e| Error::invalid_data(format!("{}: {}", err_msg, e)))
+}
+
+/// Reads and validates the header of a saved file without reading the data.
+///
+/// This function only reads a bounded number of bytes (the memo string is limited to
+/// [`MAX_MEMO_LENGTH`] bytes) and never panics on malformed input, so it can be used to inspect
+/// untrusted files before loading them. Compressed files are accepted even if the `zstd` feature
+/// is disabled. The header is protected by its own checksum, which is verified before the fields
+/// are validated, so a corrupted header results in an [`Error::Io`] of the kind
+/// [`io::ErrorKind::InvalidData`] reporting the checksum mismatch.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::read_file_header;
+///
+/// assert!(read_file_header(&mut &b"not a saved file"[..]).is_err());
+/// ```
+pub fn read_file_header<R: Read>(reader: &mut R) -> Result<FileHeader, Error> {
+    let mut recorder = RecordingReader {
+        reader,
+        buf: Vec::new(),
+    };
+
+    let magic: u32 = decode_from_std_read(&mut recorder, "Failed to read magic number")?;
+    if magic != MAGIC {
+        return Err(Error::invalid_data("Magic number is invalid"));
+    }
+
+    let version: u8 = decode_from_std_read(&mut recorder, "Failed to read version number")?;
+    if version != VERSION {
+        return Err(Error::invalid_data("Version number is invalid"));
+    }
+
+    let compression_type: u8 =
+        decode_from_std_read(&mut recorder, "Failed to read compression type")?;
+    let data_type: u8 = decode_from_std_read(&mut recorder, "Failed to read data type")?;
+    let estimated_memory_usage: u64 =
+        decode_from_std_read(&mut recorder, "Failed to read memory usage")?;
+
+    let memo: Vec<u8> = bincode::decode_from_std_read(
+        &mut recorder,
+        bincode::config::standard().with_limit::<MAX_MEMO_LENGTH>(),
+    )
+    .map_err(|e| Error::invalid_data(format!("Failed to read memo: {}", e)))?;
+
+    // the fields are validated after the checksum, so a corrupted header is reported as such
+    let mut checksum = [0; 8];
+    recorder
+        .reader
+        .read_exact(&mut checksum)
+        .map_err(|e| Error::io(e, "Failed to read header checksum"))?;
+    if u64::from_le_bytes(checksum) != xxh64(&recorder.buf, HEADER_CHECKSUM_SEED) {
+        return Err(Error::invalid_data("Checksum mismatch in header"));
+    }
+
+    if compression_type > 1 {
+        return Err(Error::invalid_data("Compression type is invalid"));
+    }
+
+    let data_type = match data_type {
+        0 => DataType::Game,
+        1 => DataType::Bunching,
+        2 => DataType::Certificate,
+        3 => DataType::PrunedSolution,
+        4 => DataType::ActionTranslationTable,
+        _ => return Err(Error::invalid_data("Data type is invalid")),
+    };
+
+    let memo = String::from_utf8(memo)
+        .map_err(|e| Error::invalid_data(format!("Failed to read memo: {}", e)))?;
+
+    Ok(FileHeader {
+        data_type,
+        is_compressed: compression_type == 1,
+        estimated_memory_usage,
+        memo,
+    })
 }
 
 /// Loads data from a standard reader.
@@ -150,6 +475,9 @@ fn decode_from_std_read<D: Decode, R: Read>(reader: &mut R, err_msg: &str) -> Re
 /// This is useful if you want to load the data from a custom reader like `Vec<u8>`, but if you want
 /// to load the data from a file, use [`load_data_from_file`] instead.
 ///
+/// The checksums of all chunks are verified, so a file corrupted or truncated in transit results
+/// in an [`Error::Io`] of the kind [`io::ErrorKind::InvalidData`] instead of silently wrong data.
+///
 /// # Arguments
 ///
 /// - `reader`: The reader to read the data from.
@@ -163,53 +491,45 @@ fn decode_from_std_read<D: Decode, R: Read>(reader: &mut R, err_msg: &str) -> Re
 pub fn load_data_from_std_read<T: FileData, R: Read>(
     reader: &mut R,
     max_memory_usage: Option<u64>,
-) -> Result<(T, String), String> {
-    let magic: u32 = decode_from_std_read(reader, "Failed to read magic number")?;
-    if magic != MAGIC {
-        return Err("Magic number is invalid".to_string());
-    }
-
-    let version: u8 = decode_from_std_read(reader, "Failed to read version number")?;
-    if version != VERSION {
-        return Err("Version number is invalid".to_string());
-    }
-
-    let compression_type: u8 = decode_from_std_read(reader, "Failed to read compression type")?;
-    if compression_type > 1 {
-        return Err("Compression type is invalid".to_string());
-    }
+) -> Result<(T, String), Error> {
+    let header = read_file_header(reader)?;
 
     #[cfg(not(feature = "zstd"))]
-    if compression_type == 1 {
-        return Err("Compression is not supported".to_string());
+    if header.is_compressed {
+        return Err(Error::Config("Compression is not supported".to_string()));
     }
 
-    let data_type: u8 = decode_from_std_read(reader, "Failed to read data type")?;
-    if data_type != T::data_type() as u8 {
-        return Err("Data type is invalid".to_string());
+    if header.data_type != T::data_type() {
+        return Err(Error::invalid_data("Data type is invalid"));
     }
 
-    let estimated_memory_usage: u64 = decode_from_std_read(reader, "Failed to read memory usage")?;
     if let Some(max_memory_usage) = max_memory_usage {
-        if estimated_memory_usage > max_memory_usage {
-            return Err("Estimated memory usage is too large".to_string());
+        if header.estimated_memory_usage > max_memory_usage {
+            return Err(Error::Config(
+                "Estimated memory usage is too large".to_string(),
+            ));
         }
     }
 
-    let memo: String = decode_from_std_read(reader, "Failed to read memo")?;
+    let mut checksum_reader = ChecksumReader::new(reader);
 
     #[cfg(not(feature = "zstd"))]
-    let data: T = decode_from_std_read(reader, "Failed to read data")?;
+    let data: T = decode_from_std_read(&mut checksum_reader, "Failed to read data")?;
     #[cfg(feature = "zstd")]
-    let data: T = if compression_type == 0 {
-        decode_from_std_read(reader, "Failed to read data")?
+    let data: T = if !header.is_compressed {
+        decode_from_std_read(&mut checksum_reader, "Failed to read data")?
     } else {
-        let mut zstd_decoder = zstd::stream::Decoder::new(reader)
-            .map_err(|e| format!("Failed to create zstd decoder: {}", e))?;
+        let mut zstd_decoder = zstd::stream::Decoder::new(&mut checksum_reader)
+            .map_err(|e| Error::io(e, "Failed to create zstd decoder"))?;
         decode_from_std_read(&mut zstd_decoder, "Failed to read data")?
     };
 
-    Ok((data, memo))
+    // the remaining chunks must also be intact
+    checksum_reader
+        .finish()
+        .map_err(|e| Error::io(e, "Failed to read data"))?;
+
+    Ok((data, header.memo))
 }
 
 /// Loads data from a file.
@@ -226,22 +546,226 @@ pub fn load_data_from_std_read<T: FileData, R: Read>(
 ///
 /// A tuple of the deserialized data (either a [`PostFlopGame`] or a [`BunchingData`]) and the memo
 /// string.
+#[cfg(feature = "io")]
 pub fn load_data_from_file<T: FileData, P: AsRef<Path>>(
     path: P,
     max_memory_usage: Option<u64>,
-) -> Result<(T, String), String> {
-    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+) -> Result<(T, String), Error> {
+    let file = File::open(path).map_err(|e| Error::io(e, "Failed to open file"))?;
     let mut reader = BufReader::new(file);
     load_data_from_std_read(&mut reader, max_memory_usage)
 }
 
+/// Verifies that a saved file is complete and intact without decoding the data, and returns its
+/// header.
+///
+/// The checksums of all chunks of the body are verified up to the terminating chunk, so a file
+/// truncated by an interrupted write (e.g., by a copy or a download, or by a save into a
+/// [`File`] through [`save_data_into_std_write`]) or corrupted on the disk results in an error.
+/// This is much faster than loading the data and does not allocate the memory of the data, so it
+/// is suitable for checking the files left by a crashed process. The files saved by
+/// [`save_data_to_file`] are never truncated, but a save may have been interrupted before
+/// replacing the file (see [`interrupted_save_path`]).
+#[cfg(feature = "io")]
+pub fn verify_saved_file<P: AsRef<Path>>(path: P) -> Result<FileHeader, Error> {
+    let file = File::open(path).map_err(|e| Error::io(e, "Failed to open file"))?;
+    let mut reader = BufReader::new(file);
+    let header = read_file_header(&mut reader)?;
+
+    let mut checksum_reader = ChecksumReader::new(&mut reader);
+    io::copy(&mut checksum_reader, &mut io::sink())
+        .map_err(|e| Error::io(e, "Failed to read data"))?;
+    checksum_reader
+        .finish()
+        .map_err(|e| Error::io(e, "Failed to read data"))?;
+
+    let mut trailing = [0];
+    if reader
+        .read(&mut trailing)
+        .map_err(|e| Error::io(e, "Failed to read data"))?
+        != 0
+    {
+        return Err(Error::invalid_data("File has trailing data"));
+    }
+
+    Ok(header)
+}
+
+/// Loads a solved game from a standard reader without allocating the storage of the
+/// counterfactual values.
+///
+/// See [`load_strategy_only_from_file`] for the details.
+pub fn load_strategy_only_from_std_read<R: Read>(
+    reader: &mut R,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, String), Error> {
+    PostFlopGame::with_strategy_only_decoding(|| load_data_from_std_read(reader, max_memory_usage))
+}
+
+/// Loads a solved game from a file without allocating the storage of the counterfactual values.
+///
+/// The loaded game is strategy-only (see [`PostFlopGame::is_strategy_only`]): only the strategies
+/// are kept in memory, which saves about a half of the memory usage (two thirds with 64-bit
+/// regrets), and the expected values are not recomputed at load time. The exploitability of the
+/// stored strategies can still be verified with [`compute_exploitability`], whose traversal only
+/// allocates transient buffers along the current path, so shared files can be checked on modest
+/// hardware. `max_memory_usage` is compared with the memory usage of the fully loaded game.
+///
+/// Games that are not solved or whose storage mode is not [`BoardState::River`] are loaded as
+/// with [`load_data_from_file`].
+///
+/// [`compute_exploitability`]: crate::compute_exploitability
+/// [`BoardState::River`]: crate::BoardState::River
+#[cfg(feature = "io")]
+pub fn load_strategy_only_from_file<P: AsRef<Path>>(
+    path: P,
+    max_memory_usage: Option<u64>,
+) -> Result<(PostFlopGame, String), Error> {
+    let file = File::open(path).map_err(|e| Error::io(e, "Failed to open file"))?;
+    let mut reader = BufReader::new(file);
+    load_strategy_only_from_std_read(&mut reader, max_memory_usage)
+}
+
+/// Precision of the strategies written by [`save_strategy_only_to_file`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrategyPrecision {
+    /// The strategies are stored as they are in memory (32-bit floating-point numbers, or 16-bit
+    /// integers for the compressed nodes).
+    #[default]
+    Full,
+
+    /// Each action probability is quantized to 8 bits relative to the most frequent action of
+    /// the hand. The absolute error of each probability is at most about `0.002`.
+    Quantized8,
+}
+
+/// Saves only the averaged strategies of a solved game into a standard writer.
+///
+/// See [`save_strategy_only_to_file`] for the details.
+pub fn save_strategy_only_into_std_write<W: Write>(
+    game: &PostFlopGame,
+    memo: &str,
+    writer: &mut W,
+    precision: StrategyPrecision,
+    compression_level: Option<i32>,
+) -> Result<(), Error> {
+    if !game.is_solved() || game.target_storage_mode() != BoardState::River {
+        return Err(Error::Config(
+            "Strategy-only save requires a solved game with the river target storage mode"
+                .to_string(),
+        ));
+    }
+
+    PostFlopGame::with_strategy_only_encoding(precision, || {
+        save_data_into_std_write(game, memo, writer, compression_level)
+    })
+}
+
+/// Saves only the averaged strategies of a solved game into a file.
+///
+/// The file contains the averaged strategies and the tree metadata, but neither the cumulative
+/// regrets nor the counterfactual values, so solving cannot be resumed. With
+/// [`StrategyPrecision::Quantized8`], each action probability takes a single byte, which makes the
+/// file about a quarter of the full save (a half with the compressed nodes) and suitable for
+/// shipping to a frontend or a mobile device.
+///
+/// The file is loaded by [`load_data_from_file`] as a strategy-only game (see
+/// [`PostFlopGame::is_strategy_only`]): the strategies can be navigated and aggregated right away,
+/// and the expected values are available after [`PostFlopGame::recompute_evs`].
+///
+/// Returns an error if the game is not solved or the target storage mode is not
+/// [`BoardState::River`]. The file is written atomically as with [`save_data_to_file`].
+///
+/// [`BoardState::River`]: crate::BoardState::River
+#[cfg(feature = "io")]
+pub fn save_strategy_only_to_file<P: AsRef<Path>>(
+    game: &PostFlopGame,
+    memo: &str,
+    path: P,
+    precision: StrategyPrecision,
+    compression_level: Option<i32>,
+) -> Result<(), Error> {
+    let mut file = AtomicFile::create(path).map_err(|e| Error::io(e, "Failed to create file"))?;
+    save_strategy_only_into_std_write(game, memo, &mut file, precision, compression_level)?;
+    file.commit()
+        .map_err(|e| Error::io(e, "Failed to commit file"))
+}
+
+#[cfg(feature = "io")]
+impl PostFlopGame {
+    /// Saves the game into a file specified by `path`.
+    ///
+    /// This is a shorthand for [`save_data_to_file`]. The file starts with a header containing the
+    /// magic number and the format version, so files written by an incompatible version are
+    /// rejected at load time. Besides the solved games, the games being solved can also be saved
+    /// if the target storage mode is [`BoardState::River`]: the cumulative regrets and the number
+    /// of performed iterations (see [`num_iterations`]) are stored as well, so that solving can be
+    /// resumed after loading:
+    ///
+    /// ```ignore
+    /// game.save_to_file("game.bin", "checkpoint", None)?;
+    ///
+    /// let (game, _memo) = PostFlopGame::load_from_file("game.bin", None)?;
+    /// for t in game.num_iterations()..max_num_iterations {
+    ///     solve_step(&game, t);
+    /// }
+    /// ```
+    ///
+    /// [`BoardState::River`]: crate::BoardState::River
+    /// [`num_iterations`]: #method.num_iterations
+    #[inline]
+    pub fn save_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        memo: &str,
+        compression_level: Option<i32>,
+    ) -> Result<(), Error> {
+        save_data_to_file(self, memo, path, compression_level)
+    }
+
+    /// Saves the subtree rooted at the node reached by `history` into a file specified by `path`.
+    ///
+    /// The subtree is saved as a standalone solved game (see [`extract_subtree`] for the
+    /// requirements on the node), which is loaded by [`load_from_file`] with its root at the node.
+    ///
+    /// [`extract_subtree`]: #method.extract_subtree
+    /// [`load_from_file`]: #method.load_from_file
+    pub fn save_subtree_to_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        history: &[usize],
+        memo: &str,
+        compression_level: Option<i32>,
+    ) -> Result<(), Error> {
+        let subtree = self.extract_subtree(history)?;
+        save_data_to_file(&subtree, memo, path, compression_level)
+    }
+
+    /// Loads a game saved by [`save_to_file`] and returns it with the memo string.
+    ///
+    /// This is a shorthand for [`load_data_from_file`]; see it for the meaning of
+    /// `max_memory_usage`.
+    ///
+    /// [`save_to_file`]: #method.save_to_file
+    #[inline]
+    pub fn load_from_file<P: AsRef<Path>>(
+        path: P,
+        max_memory_usage: Option<u64>,
+    ) -> Result<(Self, String), Error> {
+        load_data_from_file(path, max_memory_usage)
+    }
+}
+
 impl FileData for PostFlopGame {
     fn data_type() -> DataType {
         DataType::Game
     }
 
     fn is_ready_to_save(&self) -> bool {
+        // partially solved games can be saved with the cumulative regrets to resume solving
         self.is_solved()
+            || (self.game_state() == GameState::Solving
+                && self.target_storage_mode() == BoardState::River)
     }
 
     fn estimated_memory_usage(&self) -> u64 {
@@ -263,12 +787,62 @@ impl FileData for BunchingData {
     }
 }
 
-#[cfg(test)]
+#[cfg(feature = "analysis")]
+impl FileData for ExploitabilityCertificate {
+    fn data_type() -> DataType {
+        DataType::Certificate
+    }
+
+    fn is_ready_to_save(&self) -> bool {
+        !self.private_cards[0].is_empty() && !self.private_cards[1].is_empty()
+    }
+
+    fn estimated_memory_usage(&self) -> u64 {
+        let hands = self.private_cards[0].len() + self.private_cards[1].len();
+        let actions = self
+            .best_response
+            .iter()
+            .map(|e| e.actions.len())
+            .sum::<usize>();
+        let entries = self.best_response.len() * mem::size_of::<BestResponseEntry>();
+        (hands * (mem::size_of::<(Card, Card)>() + mem::size_of::<f32>()) + actions + entries)
+            as u64
+    }
+}
+
+impl FileData for PrunedSolution {
+    fn data_type() -> DataType {
+        DataType::PrunedSolution
+    }
+
+    fn is_ready_to_save(&self) -> bool {
+        !self.private_cards[0].is_empty() && !self.private_cards[1].is_empty()
+    }
+
+    fn estimated_memory_usage(&self) -> u64 {
+        self.memory_usage()
+    }
+}
+
+impl FileData for ActionTranslationTable {
+    fn data_type() -> DataType {
+        DataType::ActionTranslationTable
+    }
+
+    fn is_ready_to_save(&self) -> bool {
+        !self.is_empty()
+    }
+
+    fn estimated_memory_usage(&self) -> u64 {
+        self.memory_usage()
+    }
+}
+
+#[cfg(all(test, feature = "analysis", feature = "io", feature = "solver"))]
 mod tests {
     use super::*;
-    use crate::action_tree::*;
-    use crate::card::*;
     use crate::range::*;
+    use crate::solver::*;
     use crate::utility::*;
 
     #[test]
@@ -330,6 +904,391 @@ mod tests {
         assert!((root_ev_ip - 15.0).abs() < 1e-4);
     }
 
+    #[test]
+    fn load_strategy_only() {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 300,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        crate::solve(&mut game, 20, 0.0, false);
+        let exploitability = compute_exploitability(&game);
+
+        let mut buf = Vec::new();
+        save_data_into_std_write(&game, "", &mut buf, None).unwrap();
+
+        let (mut loaded, _) = load_strategy_only_from_std_read(&mut buf.as_slice(), None).unwrap();
+        assert!(loaded.is_strategy_only());
+        assert_eq!(compute_exploitability(&loaded), exploitability);
+        assert!(!loaded.is_current_node_finalized());
+
+        loaded.play(0);
+        assert_eq!(loaded.strategy(), {
+            game.play(0);
+            game.strategy()
+        });
+
+        // the expected values are available after recomputing them
+        loaded.recompute_evs().unwrap();
+        assert!(!loaded.is_strategy_only());
+        loaded.cache_normalized_weights();
+        game.cache_normalized_weights();
+        assert_eq!(loaded.expected_values(0), game.expected_values(0));
+    }
+
+    #[test]
+    fn save_strategy_only_quantized() {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 300,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+
+        let mut buf = Vec::new();
+        let precision = StrategyPrecision::Quantized8;
+        assert!(save_strategy_only_into_std_write(&game, "", &mut buf, precision, None).is_err());
+
+        crate::solve(&mut game, 20, 0.0, false);
+
+        let mut full = Vec::new();
+        save_data_into_std_write(&game, "", &mut full, None).unwrap();
+        let mut quantized = Vec::new();
+        save_strategy_only_into_std_write(&game, "", &mut quantized, precision, None).unwrap();
+        assert!(quantized.len() * 2 < full.len());
+
+        let (mut loaded, _): (PostFlopGame, _) =
+            load_data_from_std_read(&mut quantized.as_slice(), None).unwrap();
+        assert!(loaded.is_strategy_only());
+
+        loaded.play(0);
+        game.play(0);
+        let max_diff = loaded
+            .strategy()
+            .iter()
+            .zip(game.strategy())
+            .fold(0.0f32, |acc, (&a, b)| acc.max((a - b).abs()));
+        assert!(max_diff < 0.01);
+
+        // the full precision is lossless
+        let mut buf = Vec::new();
+        let precision = StrategyPrecision::Full;
+        save_strategy_only_into_std_write(&game, "", &mut buf, precision, None).unwrap();
+        let (mut loaded, _): (PostFlopGame, _) =
+            load_data_from_std_read(&mut buf.as_slice(), None).unwrap();
+        assert!(loaded.is_strategy_only());
+        loaded.play(0);
+        assert_eq!(loaded.strategy(), game.strategy());
+    }
+
+    #[test]
+    fn save_subtree() {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 300,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        crate::solve(&mut game, 50, 0.0, false);
+
+        // not the first decision of a street
+        assert!(game.extract_subtree(&[0]).is_err());
+
+        // check-check, then the river is the 2s
+        let river = card_from_str("2s").unwrap() as usize;
+        let history = [0, 0, river];
+        let mut buf = Vec::new();
+        let subtree = game.extract_subtree(&history).unwrap();
+        save_data_into_std_write(&subtree, "", &mut buf, None).unwrap();
+        assert!(game.history().is_empty());
+
+        let (mut loaded, _): (PostFlopGame, _) =
+            load_data_from_std_read(&mut buf.as_slice(), None).unwrap();
+        assert_eq!(loaded.tree_config().initial_state, BoardState::River);
+        assert_eq!(loaded.tree_config().starting_pot, 60);
+
+        game.apply_history(&history);
+        game.cache_normalized_weights();
+        loaded.cache_normalized_weights();
+
+        let weights = game.normalized_weights(0);
+        let ev = compute_average(&game.expected_values(0), weights);
+        let loaded_ev = compute_average(&loaded.expected_values(0), loaded.normalized_weights(0));
+        assert!((ev - loaded_ev).abs() < 1e-3);
+
+        loaded.play(1);
+        game.play(1);
+        let hand = loaded.private_cards(1)[0];
+        let index = game
+            .private_cards(1)
+            .iter()
+            .position(|&h| h == hand)
+            .unwrap();
+        let num_hands = [loaded.private_cards(1).len(), game.private_cards(1).len()];
+        assert_eq!(loaded.strategy()[0], game.strategy()[index]);
+        assert_eq!(
+            loaded.strategy()[num_hands[0]],
+            game.strategy()[num_hands[1] + index]
+        );
+    }
+
+    #[test]
+    fn save_and_load_suit_reduced() {
+        let card_config = CardConfig {
+            range: ["AA,KK,AK".parse().unwrap(), "QQ-JJ,AQs".parse().unwrap()],
+            flop: flop_from_str("Td9d6d").unwrap(),
+            turn: card_from_str("2d").unwrap(),
+            river: card_from_str("3h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 300,
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        assert!(game.is_suit_reduced());
+        game.allocate_memory(false);
+        crate::solve(&mut game, 50, 0.0, false);
+
+        let mut buf = Vec::new();
+        save_data_into_std_write(&game, "", &mut buf, None).unwrap();
+        let (mut loaded, _): (PostFlopGame, _) =
+            load_data_from_std_read(&mut buf.as_slice(), None).unwrap();
+        assert!(loaded.is_suit_reduced());
+        assert_eq!(
+            compute_exploitability(&loaded),
+            compute_exploitability(&game)
+        );
+
+        game.play(1);
+        loaded.play(1);
+        game.cache_normalized_weights();
+        loaded.cache_normalized_weights();
+        assert_eq!(loaded.strategy(), game.strategy());
+        assert_eq!(loaded.expected_values(1), game.expected_values(1));
+    }
+
+    #[test]
+    fn save_and_load_file_compressed_from_turn() {
+        let card_config = CardConfig {
+            range: [Range::ones(); 2],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 970,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+        game.allocate_memory_compressed_from(BoardState::Turn);
+        finalize(&mut game);
+
+        // save
+        save_data_to_file(&game, "", "tmpfile-turn.flop", None).unwrap();
+
+        // load
+        let mut game: PostFlopGame = load_data_from_file("tmpfile-turn.flop", None).unwrap().0;
+        assert_eq!(game.compression_street(), Some(BoardState::Turn));
+
+        // save (turn)
+        game.set_target_storage_mode(BoardState::Turn).unwrap();
+        save_data_to_file(&game, "", "tmpfile-turn.flop", None).unwrap();
+
+        // load (turn)
+        let mut game: PostFlopGame = load_data_from_file("tmpfile-turn.flop", None).unwrap().0;
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-turn.flop").unwrap();
+
+        game.cache_normalized_weights();
+        let weights_oop = game.normalized_weights(0);
+        let weights_ip = game.normalized_weights(1);
+        let root_equity_oop = compute_average(&game.equity(0), weights_oop);
+        let root_equity_ip = compute_average(&game.equity(1), weights_ip);
+        let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+        let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+        assert!((root_equity_oop - 0.5).abs() < 1e-4);
+        assert!((root_equity_ip - 0.5).abs() < 1e-4);
+        assert!((root_ev_oop - 45.0).abs() < 1e-2);
+        assert!((root_ev_ip - 15.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn read_header() {
+        let card_config = CardConfig {
+            range: [Range::ones(); 2],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            river: card_from_str("7s").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 970,
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+        game.allocate_memory(false);
+        finalize(&mut game);
+
+        let mut buf = Vec::new();
+        save_data_into_std_write(&game, "memo", &mut buf, None).unwrap();
+
+        let header = read_file_header(&mut buf.as_slice()).unwrap();
+        assert_eq!(header.data_type, DataType::Game);
+        assert!(!header.is_compressed);
+        assert_eq!(header.estimated_memory_usage, game.target_memory_usage());
+        assert_eq!(header.memo, "memo");
+
+        // truncated header
+        assert!(read_file_header(&mut &buf[..8]).is_err());
+
+        // corrupted compression type, data type, memory usage, memo, and checksum
+        let mut prefix = Vec::new();
+        encode_into_std_write(MAGIC, &mut prefix, "").unwrap();
+        encode_into_std_write(VERSION, &mut prefix, "").unwrap();
+        let mut header_buf = Vec::new();
+        write_file_header(&mut header_buf, &header).unwrap();
+        let checksum_index = header_buf.len() - 8;
+        let indices = [
+            prefix.len(),
+            prefix.len() + 1,
+            prefix.len() + 3,
+            checksum_index - 1,
+            checksum_index,
+        ];
+        for index in indices {
+            let mut corrupted = buf.clone();
+            corrupted[index] ^= 4;
+            for err in [
+                read_file_header(&mut corrupted.as_slice()).unwrap_err(),
+                load_data_from_std_read::<PostFlopGame, _>(&mut corrupted.as_slice(), None)
+                    .err()
+                    .unwrap(),
+            ] {
+                match err {
+                    Error::Io(e) => {
+                        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+                        assert_eq!(e.to_string(), "Checksum mismatch in header");
+                    }
+                    _ => panic!("corrupted header must be rejected: {err}"),
+                }
+            }
+        }
+
+        // memo length exceeding the limit
+        let mut buf = Vec::new();
+        encode_into_std_write(MAGIC, &mut buf, "").unwrap();
+        encode_into_std_write(VERSION, &mut buf, "").unwrap();
+        encode_into_std_write(0u8, &mut buf, "").unwrap();
+        encode_into_std_write(DataType::Game as u8, &mut buf, "").unwrap();
+        encode_into_std_write(0u64, &mut buf, "").unwrap();
+        encode_into_std_write(u64::MAX, &mut buf, "").unwrap();
+        assert!(read_file_header(&mut buf.as_slice()).is_err());
+    }
+
+    #[test]
+    fn save_and_load_pruned_solution() {
+        let card_config = CardConfig {
+            range: ["AA,QQ,AK".parse().unwrap(), "KK,JJ,AQs".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 300,
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+
+        let pruned = game.prune_solution(0.01).unwrap();
+        let mut buf = Vec::new();
+        save_data_into_std_write(&pruned, "", &mut buf, None).unwrap();
+
+        let header = read_file_header(&mut buf.as_slice()).unwrap();
+        assert_eq!(header.data_type, DataType::PrunedSolution);
+
+        let loaded: PrunedSolution = load_data_from_std_read(&mut buf.as_slice(), None)
+            .unwrap()
+            .0;
+        assert_eq!(loaded, pruned);
+
+        let table = game.action_translation_table().unwrap();
+        let mut buf = Vec::new();
+        save_data_into_std_write(&table, "", &mut buf, None).unwrap();
+
+        let header = read_file_header(&mut buf.as_slice()).unwrap();
+        assert_eq!(header.data_type, DataType::ActionTranslationTable);
+
+        let loaded: ActionTranslationTable = load_data_from_std_read(&mut buf.as_slice(), None)
+            .unwrap()
+            .0;
+        assert_eq!(loaded, table);
+    }
+
     #[test]
     #[cfg(feature = "zstd")]
     fn save_and_load_file_compressed() {
@@ -375,4 +1334,196 @@ mod tests {
         assert!((root_ev_oop - 45.0).abs() < 1e-4);
         assert!((root_ev_ip - 15.0).abs() < 1e-4);
     }
+
+    #[test]
+    fn save_and_resume_solving() {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 300,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+
+        // games that are not being solved cannot be saved
+        let mut buf = Vec::new();
+        assert!(save_data_into_std_write(&game, "", &mut buf, None).is_err());
+
+        for t in 0..10 {
+            crate::solve_step(&game, t);
+        }
+        assert_eq!(game.num_iterations(), 10);
+
+        // save a checkpoint
+        game.save_to_file("tmpfile-resume.flop", "checkpoint", None)
+            .unwrap();
+        let (mut loaded, memo) = PostFlopGame::load_from_file("tmpfile-resume.flop", None).unwrap();
+        std::fs::remove_file("tmpfile-resume.flop").unwrap();
+
+        assert_eq!(memo, "checkpoint");
+        assert_eq!(loaded.game_state(), GameState::Solving);
+        assert_eq!(loaded.num_iterations(), 10);
+
+        // resuming from the checkpoint continues from the same regrets
+        for t in 10..20 {
+            crate::solve_step(&game, t);
+            crate::solve_step(&loaded, t);
+        }
+
+        assert_eq!(loaded.num_iterations(), 20);
+        let exploitability = compute_exploitability(&game);
+        assert!((compute_exploitability(&loaded) - exploitability).abs() < 1e-4);
+
+        finalize(&mut game);
+        finalize(&mut loaded);
+        for (x, y) in loaded.strategy().iter().zip(game.strategy()) {
+            assert!((x - y).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn checksum_chunks() {
+        let data = (0..CHUNK_SIZE * 5 / 2)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect::<Vec<_>>();
+
+        let mut writer = ChecksumWriter::new(Vec::new());
+        writer.write_all(&data).unwrap();
+        let buf = writer.finish().unwrap();
+        assert_eq!(buf.len(), data.len() + 4 * 12);
+
+        let mut reader = ChecksumReader::new(buf.as_slice());
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        reader.finish().unwrap();
+        assert_eq!(decoded, data);
+
+        // a flipped bit in the second chunk
+        let mut corrupted = buf.clone();
+        corrupted[CHUNK_SIZE + 100] ^= 1;
+        let mut reader = ChecksumReader::new(corrupted.as_slice());
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "Checksum mismatch in chunk 1");
+
+        // missing terminating chunk
+        let mut reader = ChecksumReader::new(&buf[..buf.len() - 12]);
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn load_corrupted_file() {
+        let card_config = CardConfig {
+            range: [Range::ones(); 2],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            river: card_from_str("7s").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 970,
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+        game.allocate_memory(false);
+        solve(&mut game, 10, 0.0, false);
+
+        let mut buf = Vec::new();
+        save_data_into_std_write(&game, "memo", &mut buf, None).unwrap();
+        assert!(load_data_from_std_read::<PostFlopGame, _>(&mut buf.as_slice(), None).is_ok());
+
+        // corrupt a byte of the storage (near the end of the data)
+        let mut corrupted = buf.clone();
+        let index = corrupted.len() - 100;
+        corrupted[index] ^= 0x40;
+        let err = load_data_from_std_read::<PostFlopGame, _>(&mut corrupted.as_slice(), None);
+        match err {
+            Err(Error::Io(e)) => {
+                assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+                assert!(e.to_string().contains("Checksum mismatch"));
+            }
+            _ => panic!("corrupted data must be rejected"),
+        }
+
+        // truncated file
+        let truncated = &buf[..buf.len() - 12];
+        let err = load_data_from_std_read::<PostFlopGame, _>(&mut &truncated[..], None);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn verify_saved_files() {
+        let card_config = CardConfig {
+            range: [Range::ones(); 2],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            river: card_from_str("7s").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 970,
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+        game.allocate_memory(false);
+        solve(&mut game, 10, 0.0, false);
+
+        let path = "tmpfile_verify.flop";
+        save_data_to_file(&game, "memo", path, None).unwrap();
+        assert!(interrupted_save_path(path).is_none());
+        let header = verify_saved_file(path).unwrap();
+        assert_eq!(header.data_type, DataType::Game);
+        assert_eq!(header.memo, "memo");
+
+        let buf = std::fs::read(path).unwrap();
+
+        // truncated in the middle of the body
+        std::fs::write(path, &buf[..buf.len() / 2]).unwrap();
+        assert!(verify_saved_file(path).is_err());
+
+        // the terminating chunk is missing
+        std::fs::write(path, &buf[..buf.len() - 12]).unwrap();
+        assert!(verify_saved_file(path).is_err());
+
+        // trailing data
+        let mut extended = buf.clone();
+        extended.push(0);
+        std::fs::write(path, &extended).unwrap();
+        assert!(verify_saved_file(path).is_err());
+
+        // a failed save keeps the previous file
+        std::fs::write(path, &buf).unwrap();
+        let unsolved = PostFlopGame::new();
+        assert!(save_data_to_file(&unsolved, "memo", path, None).is_err());
+        assert!(interrupted_save_path(path).is_none());
+        assert!(verify_saved_file(path).is_ok());
+
+        std::fs::remove_file(path).unwrap();
+    }
 }
@@ -0,0 +1,220 @@
+use super::*;
+use crate::error::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A class of decision nodes over which action frequencies are aggregated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionClass {
+    /// The first decision of the player on the street.
+    FirstDecision(BoardState),
+
+    /// No bet has been made on the street yet (e.g., a continuation bet opportunity).
+    Unopened(BoardState),
+
+    /// The player is facing a single bet on the street.
+    FacingBet(BoardState),
+
+    /// The player is facing a raise on the street.
+    FacingRaise(BoardState),
+}
+
+/// Overall action frequencies of a player at a [`DecisionClass`].
+///
+/// The frequencies are weighted by the number of combinations with which the player reaches each
+/// node (taking the opponent's range and card removal into account), so they match the summary
+/// numbers such as "flop c-bet 54%". Bets and raises of all sizes are combined; an all-in is
+/// counted as a bet if there is no outstanding bet and as a raise otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AggregateFrequencies {
+    /// The number of aggregated decision nodes (isomorphic chances are counted separately).
+    pub num_nodes: usize,
+
+    /// The frequency of folding.
+    pub fold: f64,
+
+    /// The frequency of checking.
+    pub check: f64,
+
+    /// The frequency of calling.
+    pub call: f64,
+
+    /// The frequency of betting.
+    pub bet: f64,
+
+    /// The frequency of raising.
+    pub raise: f64,
+}
+
+/// Betting state of the current street used for classifying the decision nodes.
+#[derive(Clone, Copy, Default)]
+struct StreetState {
+    has_acted: [bool; 2],
+    num_bets: usize,
+}
+
+impl DecisionClass {
+    /// Returns the street of the class.
+    #[inline]
+    pub fn street(&self) -> BoardState {
+        match *self {
+            DecisionClass::FirstDecision(street)
+            | DecisionClass::Unopened(street)
+            | DecisionClass::FacingBet(street)
+            | DecisionClass::FacingRaise(street) => street,
+        }
+    }
+
+    #[inline]
+    fn matches(&self, state: &StreetState, player: usize) -> bool {
+        match self {
+            DecisionClass::FirstDecision(_) => !state.has_acted[player],
+            DecisionClass::Unopened(_) => state.num_bets == 0,
+            DecisionClass::FacingBet(_) => state.num_bets == 1,
+            DecisionClass::FacingRaise(_) => state.num_bets >= 2,
+        }
+    }
+}
+
+impl PostFlopGame {
+    /// Computes the overall action frequencies of `player` at the decision nodes of `class`.
+    ///
+    /// The current node is restored after the call. Returns an error if the game is not solved
+    /// or no decision node of the class is reachable.
+    ///
+    /// **Time complexity:** *O*(#(nodes up to the street of `class`) * #(private hands)).
+    pub fn aggregate_frequencies(
+        &mut self,
+        player: usize,
+        class: DecisionClass,
+    ) -> Result<AggregateFrequencies, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if player >= 2 {
+            return Err(Error::Config(format!("Invalid player: {player}")));
+        }
+
+        let saved_history = self.history().to_vec();
+        let mut history = Vec::new();
+        let mut sums = [0.0; 5];
+        let mut total = 0.0;
+        let mut num_nodes = 0;
+
+        self.back_to_root();
+        self.aggregate_recursive(
+            &mut history,
+            StreetState::default(),
+            player,
+            class,
+            &mut sums,
+            &mut total,
+            &mut num_nodes,
+        );
+        self.apply_history(&saved_history);
+
+        if total == 0.0 {
+            return Err(Error::Tree(
+                "No reachable decision node in the class".to_string(),
+            ));
+        }
+
+        Ok(AggregateFrequencies {
+            num_nodes,
+            fold: sums[0] / total,
+            check: sums[1] / total,
+            call: sums[2] / total,
+            bet: sums[3] / total,
+            raise: sums[4] / total,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn aggregate_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        street_state: StreetState,
+        player: usize,
+        class: DecisionClass,
+        sums: &mut [f64; 5],
+        total: &mut f64,
+        num_nodes: &mut usize,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        let street = self.current_board().len() - 3;
+
+        if self.is_chance_node() {
+            // the next street is beyond the class
+            if street >= class.street() as usize {
+                return;
+            }
+
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card);
+                    self.apply_history(history);
+                    let state = StreetState::default();
+                    self.aggregate_recursive(history, state, player, class, sums, total, num_nodes);
+                    history.pop();
+                }
+            }
+
+            return;
+        }
+
+        let actions = self.available_actions();
+        let current_player = self.current_player();
+
+        if current_player == player
+            && street == class.street() as usize
+            && class.matches(&street_state, player)
+        {
+            self.cache_normalized_weights();
+            let weights = self.normalized_weights(player);
+            let strategy = self.strategy();
+            let num_hands = weights.len();
+
+            let mut node_total = 0.0;
+            for (i, action) in actions.iter().enumerate() {
+                let category = match action {
+                    Action::Fold => 0,
+                    Action::Check => 1,
+                    Action::Call => 2,
+                    Action::Bet(_) => 3,
+                    Action::AllIn(_) if street_state.num_bets == 0 => 3,
+                    _ => 4,
+                };
+                let row = &strategy[i * num_hands..(i + 1) * num_hands];
+                let sum = row
+                    .iter()
+                    .zip(weights)
+                    .fold(0.0, |acc, (&s, &w)| acc + s as f64 * w as f64);
+                sums[category] += sum;
+                node_total += sum;
+            }
+
+            if node_total > 0.0 {
+                *total += node_total;
+                *num_nodes += 1;
+            }
+        }
+
+        for (i, action) in actions.iter().enumerate() {
+            let mut next_state = street_state;
+            next_state.has_acted[current_player] = true;
+            if matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_)) {
+                next_state.num_bets += 1;
+            }
+
+            history.push(i);
+            self.apply_history(history);
+            self.aggregate_recursive(history, next_state, player, class, sums, total, num_nodes);
+            history.pop();
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use super::*;
+use crate::utility::*;
+use std::mem;
+use std::sync::OnceLock;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Cache of the showdown outcomes of the flop all-in pots, shared by all terminal nodes where
+/// both players are all-in on the flop.
+///
+/// With [`TreeConfig::allin_equity_adjudication`], such a node is evaluated by enumerating all
+/// turn and river runouts, which depends only on the board and the private hands and not on the
+/// node or the reach probabilities. The table stores, for each pair of OOP and IP hands, the
+/// numbers of runouts (and showdown rankings) that OOP wins, ties, and loses, so that the
+/// evaluation of every such node becomes a single pass over the pairs of hands. The table is
+/// built on the first use and invalidated when the hand strengths change.
+#[derive(Default)]
+pub(super) struct AllinEquityCache {
+    is_enabled: bool,
+    table: OnceLock<ShowdownTable>,
+}
+
+/// The numbers of wins, ties, and losses of OOP for each pair of hands (OOP-major).
+struct ShowdownTable {
+    num_hands_ip: usize,
+    counts: Vec<[u16; 3]>,
+}
+
+impl AllinEquityCache {
+    /// Removes the table.
+    #[inline]
+    pub(super) fn clear(&mut self) {
+        self.table = OnceLock::new();
+    }
+
+    /// Returns whether the cache is enabled.
+    #[inline]
+    pub(super) fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+}
+
+impl PostFlopGame {
+    /// Enables or disables the cache of the showdown outcomes of the flop all-in pots (disabled
+    /// by default).
+    ///
+    /// This only affects games with [`TreeConfig::allin_equity_adjudication`], where the
+    /// terminal nodes after both players are all-in on the flop are evaluated by the exact
+    /// equity over all runouts. When enabled, the outcomes of the runouts are enumerated once
+    /// for each pair of hands and reused by all such nodes and iterations, which removes
+    /// redundant work in trees with many all-in lines (e.g., raise-heavy flop trees). The
+    /// solution is unchanged up to floating-point errors.
+    ///
+    /// The table uses 6 bytes per pair of OOP and IP hands (see
+    /// [`allin_equity_cache_memory_usage`]) and is built on the first evaluation. The cache is
+    /// not used with the bunching effect.
+    ///
+    /// [`allin_equity_cache_memory_usage`]: #method.allin_equity_cache_memory_usage
+    pub fn set_allin_equity_cache_enabled(&mut self, enabled: bool) {
+        self.allin_equity_cache.is_enabled = enabled;
+        self.allin_equity_cache.clear();
+    }
+
+    /// Returns whether the cache of the showdown outcomes of the flop all-in pots is enabled.
+    #[inline]
+    pub fn is_allin_equity_cache_enabled(&self) -> bool {
+        self.allin_equity_cache.is_enabled
+    }
+
+    /// Returns the memory usage of the cache of the showdown outcomes of the flop all-in pots in
+    /// bytes (zero until the table is built).
+    #[inline]
+    pub fn allin_equity_cache_memory_usage(&self) -> u64 {
+        self.allin_equity_cache
+            .table
+            .get()
+            .map_or(0, |table| mem::size_of_val(table.counts.as_slice()) as u64)
+    }
+
+    /// Adds the counterfactual values of a flop all-in showdown to `result` using the cached
+    /// table, where `amounts` are the payoffs of a win, a tie, and a loss of `player` per runout
+    /// and ranking.
+    pub(super) fn evaluate_allin_cached(
+        &self,
+        result: &mut [f32],
+        player: usize,
+        cfreach: &[f32],
+        amounts: [f64; 3],
+    ) {
+        let table = self
+            .allin_equity_cache
+            .table
+            .get_or_init(|| self.build_showdown_table());
+        let num_hands_ip = table.num_hands_ip;
+
+        if player == 0 {
+            for (r, row) in result
+                .iter_mut()
+                .zip(table.counts.chunks_exact(num_hands_ip))
+            {
+                let cfvalue = row.iter().zip(cfreach).fold(0.0, |acc, (c, &w)| {
+                    if w == 0.0 {
+                        acc
+                    } else {
+                        acc + w as f64 * dot_counts(*c, amounts)
+                    }
+                });
+                *r += cfvalue as f32;
+            }
+        } else {
+            // the wins of OOP are the losses of IP
+            let amounts = [amounts[2], amounts[1], amounts[0]];
+            let mut cfvalues = vec![0.0; num_hands_ip];
+            for (row, &w) in table.counts.chunks_exact(num_hands_ip).zip(cfreach) {
+                if w == 0.0 {
+                    continue;
+                }
+                cfvalues.iter_mut().zip(row).for_each(|(v, c)| {
+                    *v += w as f64 * dot_counts(*c, amounts);
+                });
+            }
+            result.iter_mut().zip(&cfvalues).for_each(|(r, &v)| {
+                *r += v as f32;
+            });
+        }
+    }
+
+    /// Enumerates the showdown outcomes of all runouts from the flop for each pair of hands.
+    fn build_showdown_table(&self) -> ShowdownTable {
+        let num_hands = [0, 1].map(|player| self.private_cards[player].len());
+        let pair_indices = self.runout_pair_indices(NOT_DEALT).collect::<Vec<_>>();
+        let num_rankings = self.num_showdown_rankings();
+        let num_slots = pair_indices.len() * num_rankings;
+
+        // the strength of each hand in each runout and ranking (hand-major), or zero if the hand
+        // conflicts with the runout
+        let mut strength = num_hands.map(|n| vec![0u16; n * num_slots]);
+        for (i, &pair_index) in pair_indices.iter().enumerate() {
+            for (k, ranking) in self.showdown_rankings(pair_index).enumerate() {
+                let slot = i * num_rankings + k;
+                for player in 0..2 {
+                    let items = &ranking[player];
+                    for item in &items[1..items.len() - 1] {
+                        strength[player][item.index as usize * num_slots + slot] = item.strength;
+                    }
+                }
+            }
+        }
+
+        let masks = [0, 1].map(|player| {
+            self.private_cards[player]
+                .iter()
+                .map(|&(c1, c2)| (1u64 << c1) | (1u64 << c2))
+                .collect::<Vec<_>>()
+        });
+
+        let rows = into_par_iter(0..num_hands[0])
+            .map(|i| {
+                let strength_oop = &strength[0][i * num_slots..(i + 1) * num_slots];
+                (0..num_hands[1])
+                    .map(|j| {
+                        let mut counts = [0u16; 3];
+                        if masks[0][i] & masks[1][j] != 0 {
+                            return counts;
+                        }
+                        let strength_ip = &strength[1][j * num_slots..(j + 1) * num_slots];
+                        for (&s, &t) in strength_oop.iter().zip(strength_ip) {
+                            if s != 0 && t != 0 {
+                                counts[(s <= t) as usize + (s < t) as usize] += 1;
+                            }
+                        }
+                        counts
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        ShowdownTable {
+            num_hands_ip: num_hands[1],
+            counts: rows.concat(),
+        }
+    }
+}
+
+#[inline]
+fn dot_counts(counts: [u16; 3], amounts: [f64; 3]) -> f64 {
+    counts[0] as f64 * amounts[0] + counts[1] as f64 * amounts[1] + counts[2] as f64 * amounts[2]
+}
@@ -0,0 +1,269 @@
+use super::*;
+use crate::hand_class::*;
+use crate::utility::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Analysis of a single private hand, part of [`NodeAnalysis`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HandAnalysis {
+    /// The private hand.
+    pub hand: (Card, Card),
+
+    /// The class of the hand on the current board.
+    pub class: Option<HandClass>,
+
+    /// The number of combinations with which the hand reaches the current node.
+    pub combos: f64,
+
+    /// The equity of the hand.
+    pub equity: f64,
+
+    /// The expected value of the hand.
+    pub ev: f64,
+
+    /// The equity realization of the hand, i.e., `ev / (equity * pot)`.
+    pub eqr: f64,
+
+    /// The probability of each action if the player acts at the current node; otherwise empty.
+    pub strategy: Vec<f32>,
+}
+
+/// Aggregate of a [`HandClass`], part of [`NodeAnalysis`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClassAnalysis {
+    /// The hand class.
+    pub class: Option<HandClass>,
+
+    /// The number of combinations of the class reaching the current node.
+    pub combos: f64,
+
+    /// The fraction of the range that the class accounts for.
+    pub range_fraction: f64,
+
+    /// The average equity of the class.
+    pub equity: f64,
+
+    /// The average expected value of the class.
+    pub ev: f64,
+
+    /// The equity realization of the class.
+    pub eqr: f64,
+
+    /// The frequency of each action within the class if the player acts at the current node;
+    /// otherwise empty.
+    pub frequencies: Vec<f64>,
+}
+
+/// Aggregate of an action of the player, part of [`NodeAnalysis`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ActionAnalysis {
+    /// The action.
+    pub action: Action,
+
+    /// The number of combinations taking the action.
+    pub combos: f64,
+
+    /// The frequency of the action over the range.
+    pub frequency: f64,
+
+    /// The average equity of the hands taking the action (at the current node).
+    pub equity: f64,
+
+    /// The average expected value of taking the action.
+    pub ev: f64,
+}
+
+/// Aggregate tables of a player's range at the current node, returned by
+/// [`PostFlopGame::analyze_node`].
+///
+/// All averages are weighted by the number of combinations with which each hand reaches the
+/// current node, taking the opponent's range and card removal into account (see
+/// [`PostFlopGame::normalized_weights`]). The equity realization is the expected value divided by
+/// the share of the pot that the equity represents, so a value above `1.0` means that the hands
+/// win more than their equity (e.g., by having position), and it is `0.0` if the equity is zero.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeAnalysis {
+    /// The analyzed player.
+    pub player: usize,
+
+    /// The size of the pot at the current node, including the uncalled bet.
+    pub pot: f64,
+
+    /// The number of combinations reaching the current node.
+    pub combos: f64,
+
+    /// The equity of the range.
+    pub equity: f64,
+
+    /// The expected value of the range.
+    pub ev: f64,
+
+    /// The equity realization of the range.
+    pub eqr: f64,
+
+    /// The aggregate of each action if the player acts at the current node; otherwise empty.
+    pub actions: Vec<ActionAnalysis>,
+
+    /// The aggregate of each hand class with at least one combination, in the order of
+    /// [`HandClass::ALL`].
+    pub classes: Vec<ClassAnalysis>,
+
+    /// The analysis of each private hand reaching the current node, in the order of
+    /// [`PostFlopGame::private_cards`].
+    pub hands: Vec<HandAnalysis>,
+}
+
+#[inline]
+fn equity_realization(ev: f64, equity: f64, pot: f64) -> f64 {
+    if equity > 0.0 && pot > 0.0 {
+        ev / (equity * pot)
+    } else {
+        0.0
+    }
+}
+
+impl PostFlopGame {
+    /// Analyzes the range of the given player at the current node: the range equity, EV, and
+    /// equity realization, bucketed by hand class and by action. See [`NodeAnalysis`] for the
+    /// conventions.
+    ///
+    /// The hand classes are computed with [`HandClass::classify`], which assumes the standard
+    /// high-hand ranking. For a game with a custom evaluator (see [`set_evaluator`]) or a second
+    /// board, `class` is `None` and all hands are aggregated into a single class.
+    ///
+    /// Panics if the current node is not finalized (see [`is_current_node_finalized`]) or the
+    /// normalized weights are not cached (see [`cache_normalized_weights`]).
+    ///
+    /// **Time complexity:** that of [`equity`] plus that of [`expected_values_detail`].
+    ///
+    /// [`set_evaluator`]: #method.set_evaluator
+    /// [`is_current_node_finalized`]: #method.is_current_node_finalized
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`equity`]: #method.equity
+    /// [`expected_values_detail`]: #method.expected_values_detail
+    pub fn analyze_node(&self, player: usize) -> NodeAnalysis {
+        let is_acting =
+            !self.is_terminal_node() && !self.is_chance_node() && self.current_player() == player;
+
+        let normalized_weights = self.normalized_weights(player);
+        let equity = self.equity(player);
+        let ev = self.expected_values(player);
+        let pot = self.pot() as f64;
+        let num_hands = normalized_weights.len();
+
+        let (actions, strategy, ev_detail) = if is_acting {
+            (
+                self.available_actions(),
+                self.strategy(),
+                self.expected_values_detail(player),
+            )
+        } else {
+            (Vec::new(), Vec::new(), Vec::new())
+        };
+        let num_actions = actions.len();
+
+        let board = self.current_board();
+        let is_classified = self.evaluator.is_none() && self.card_config.second_board.is_empty();
+
+        let mut hands = Vec::new();
+        let mut classes = HandClass::ALL
+            .iter()
+            .map(|&class| ClassAnalysis {
+                class: is_classified.then_some(class),
+                frequencies: vec![0.0; num_actions],
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let mut action_rows = actions
+            .iter()
+            .map(|&action| ActionAnalysis {
+                action,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        for (index, &hand) in self.private_cards(player).iter().enumerate() {
+            let w = normalized_weights[index] as f64;
+            if w <= 0.0 {
+                continue;
+            }
+
+            let class = is_classified.then(|| HandClass::classify(&board, hand));
+            let (hand_equity, hand_ev) = (equity[index] as f64, ev[index] as f64);
+            let hand_strategy = (0..num_actions)
+                .map(|action| strategy[action * num_hands + index])
+                .collect::<Vec<_>>();
+
+            let row = &mut classes[class.map_or(HandClass::ALL.len() - 1, |c| c as usize)];
+            row.combos += w;
+            row.equity += hand_equity * w;
+            row.ev += hand_ev * w;
+            for (action, &s) in hand_strategy.iter().enumerate() {
+                let combos = s as f64 * w;
+                row.frequencies[action] += combos;
+                let action_row = &mut action_rows[action];
+                action_row.combos += combos;
+                action_row.equity += hand_equity * combos;
+                action_row.ev += ev_detail[action * num_hands + index] as f64 * combos;
+            }
+
+            hands.push(HandAnalysis {
+                hand,
+                class,
+                combos: w,
+                equity: hand_equity,
+                ev: hand_ev,
+                eqr: equity_realization(hand_ev, hand_equity, pot),
+                strategy: hand_strategy,
+            });
+        }
+
+        let sum = |f: fn(&HandAnalysis) -> f64| Summation::Compensated.sum(hands.iter().map(f));
+        let total = sum(|h| h.combos);
+        let range_equity = sum(|h| h.equity * h.combos);
+        let range_ev = sum(|h| h.ev * h.combos);
+
+        classes.retain(|row| row.combos > 0.0);
+        for row in &mut classes {
+            row.range_fraction = row.combos / total;
+            row.equity /= row.combos;
+            row.ev /= row.combos;
+            row.eqr = equity_realization(row.ev, row.equity, pot);
+            row.frequencies.iter_mut().for_each(|f| *f /= row.combos);
+        }
+
+        for row in &mut action_rows {
+            if total > 0.0 {
+                row.frequency = row.combos / total;
+            }
+            if row.combos > 0.0 {
+                row.equity /= row.combos;
+                row.ev /= row.combos;
+            }
+        }
+
+        let (range_equity, range_ev) = if total > 0.0 {
+            (range_equity / total, range_ev / total)
+        } else {
+            (0.0, 0.0)
+        };
+
+        NodeAnalysis {
+            player,
+            pot,
+            combos: total,
+            equity: range_equity,
+            ev: range_ev,
+            eqr: equity_realization(range_ev, range_equity, pot),
+            actions: action_rows,
+            classes,
+            hands,
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// Opponent archetype defined by biasing rules, evaluated by
+/// [`PostFlopGame::evaluate_archetypes`].
+///
+/// The archetype plays the solved strategy with the frequency of each action multiplied by the
+/// multiplier of its kind, renormalized for each hand. For example, a `fold` multiplier of `2.0`
+/// doubles the odds of folding against the other actions. Since the rules are multiplicative,
+/// actions that the solved strategy never takes with a hand are never taken by the archetype
+/// either; if the multipliers zero out all actions taken with a hand, the hand keeps the solved
+/// strategy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Archetype {
+    /// The name of the archetype.
+    pub name: String,
+
+    /// The multiplier of the frequency of folding.
+    pub fold: f32,
+
+    /// The multiplier of the frequency of checking.
+    pub check: f32,
+
+    /// The multiplier of the frequency of calling.
+    pub call: f32,
+
+    /// The multiplier of the frequencies of betting, raising, and going all-in.
+    pub aggression: f32,
+}
+
+/// Expected value against an [`Archetype`], contained in [`ArchetypeReport`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchetypeEv {
+    /// The name of the archetype.
+    pub name: String,
+
+    /// The expected value of the solved strategy against the archetype.
+    pub ev: f32,
+
+    /// The difference from the expected value against the solved strategy, i.e.,
+    /// `ev - ArchetypeReport::equilibrium_ev`. Non-negative up to the exploitability for a
+    /// converged solution, since the deviations of the archetype cannot gain against an
+    /// equilibrium strategy.
+    pub gain: f32,
+
+    /// The expected value of the best response against the archetype, i.e., the maximum expected
+    /// value that an exploitative strategy could achieve.
+    pub best_response_ev: f32,
+}
+
+/// Result of [`PostFlopGame::evaluate_archetypes`].
+///
+/// The expected values follow the convention of [`compute_current_ev`]: half the starting pot is
+/// subtracted.
+///
+/// [`compute_current_ev`]: crate::compute_current_ev
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchetypeReport {
+    /// The player whose solved strategy is evaluated.
+    pub player: usize,
+
+    /// The expected value of the solved strategy against the solved strategy of the opponent.
+    pub equilibrium_ev: f32,
+
+    /// The expected values against each archetype, in the order of the archetypes.
+    pub archetypes: Vec<ArchetypeEv>,
+}
+
+impl Archetype {
+    /// Creates an archetype with all multipliers set to `1.0`, i.e., playing the solved strategy.
+    #[inline]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            fold: 1.0,
+            check: 1.0,
+            call: 1.0,
+            aggression: 1.0,
+        }
+    }
+
+    /// A tight-passive player: rarely bets or raises and folds somewhat more often.
+    #[inline]
+    pub fn tight_passive() -> Self {
+        Self {
+            fold: 1.5,
+            aggression: 0.25,
+            ..Self::new("tight-passive")
+        }
+    }
+
+    /// An overfolder: folds much more often when facing a bet.
+    #[inline]
+    pub fn overfolder() -> Self {
+        Self {
+            fold: 3.0,
+            ..Self::new("overfolder")
+        }
+    }
+
+    /// A calling station: rarely folds, calls much more often, and raises less often.
+    #[inline]
+    pub fn station() -> Self {
+        Self {
+            fold: 0.2,
+            call: 2.0,
+            aggression: 0.5,
+            ..Self::new("station")
+        }
+    }
+
+    /// Returns the tight-passive, overfolder, and station archetypes.
+    #[inline]
+    pub fn presets() -> Vec<Self> {
+        vec![Self::tight_passive(), Self::overfolder(), Self::station()]
+    }
+
+    /// Returns the multiplier of `action`.
+    #[inline]
+    pub fn multiplier(&self, action: Action) -> f32 {
+        match action {
+            Action::Fold => self.fold,
+            Action::Check => self.check,
+            Action::Call => self.call,
+            Action::Bet(_) | Action::Raise(_) | Action::AllIn(_) => self.aggression,
+            _ => 1.0,
+        }
+    }
+
+    fn check_multipliers(&self) -> Result<(), Error> {
+        let multipliers = [self.fold, self.check, self.call, self.aggression];
+        if multipliers.iter().any(|m| !m.is_finite() || *m < 0.0) {
+            return Err(Error::Config(format!(
+                "Invalid multipliers of archetype '{}'",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl ArchetypeReport {
+    /// Returns the archetype against which the solved strategy gains the least, or `None` if no
+    /// archetype is evaluated.
+    #[inline]
+    pub fn worst(&self) -> Option<&ArchetypeEv> {
+        self.archetypes
+            .iter()
+            .min_by(|a, b| a.gain.total_cmp(&b.gain))
+    }
+}
+
+impl PostFlopGame {
+    /// Computes the expected values of the solved strategy of `player` against each of
+    /// `archetypes` played by the opponent (see [`Archetype`]), summarizing how robust the
+    /// solution is against typical deviations of a player pool.
+    ///
+    /// The biasing rules are applied on top of the locked strategies, i.e., the locked hands keep
+    /// their locked strategies. The strategies of the opponent are temporarily overwritten during
+    /// the evaluation, which requires a copy of the strategy storage; they are restored before
+    /// returning.
+    ///
+    /// Returns an error if `player` is invalid, the game is not solved, the storage mode is not
+    /// [`BoardState::River`], the memory is allocated lazily, or an archetype has a negative or
+    /// non-finite multiplier.
+    ///
+    /// **Time complexity:** *O*(#(archetypes) * #(nodes) * #(private hands)), two traversals of
+    /// the game tree for each archetype.
+    pub fn evaluate_archetypes(
+        &mut self,
+        player: usize,
+        archetypes: &[Archetype],
+    ) -> Result<ArchetypeReport, Error> {
+        if player >= 2 {
+            return Err(Error::Config(format!("Invalid player: {player}")));
+        }
+
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        if self.is_lazy_allocation {
+            return Err(Error::Config(
+                "Lazy allocation is not supported".to_string(),
+            ));
+        }
+
+        for archetype in archetypes {
+            archetype.check_multipliers()?;
+        }
+
+        let mut report = ArchetypeReport {
+            player,
+            equilibrium_ev: compute_current_ev(self)[player],
+            archetypes: Vec::with_capacity(archetypes.len()),
+        };
+
+        let saved_storage = self.storage1.to_vec();
+
+        for archetype in archetypes {
+            self.bias_strategies(player ^ 1, archetype);
+
+            let ev = compute_current_ev(self)[player];
+            let best_cfvalues = compute_best_cfvalues(self, player, &|_, _| {});
+            let best_response_ev = best_cfvalues
+                .iter()
+                .zip(Game::initial_weights(self, player))
+                .fold(0.0, |sum, (&v, &w)| sum + v as f64 * w as f64);
+
+            report.archetypes.push(ArchetypeEv {
+                name: archetype.name.clone(),
+                ev,
+                gain: ev - report.equilibrium_ev,
+                best_response_ev: best_response_ev as f32,
+            });
+
+            self.storage1.copy_from_slice(&saved_storage);
+        }
+
+        self.lock_decoded_cache().clear();
+
+        Ok(report)
+    }
+
+    /// Overwrites the strategies of `player` with those biased by `archetype`.
+    fn bias_strategies(&mut self, player: usize, archetype: &Archetype) {
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            if node.is_terminal() || node.is_chance() || node.player() != player {
+                continue;
+            }
+
+            let multipliers = node
+                .children()
+                .iter()
+                .map(|child| archetype.multiplier(child.lock().prev_action))
+                .collect::<Vec<_>>();
+
+            if multipliers.iter().all(|&m| m == 1.0) {
+                continue;
+            }
+
+            let mut strategy = if self.is_node_compressed(&node) {
+                node.strategy_compressed()
+                    .iter()
+                    .map(|&s| s as f32)
+                    .collect::<Vec<_>>()
+            } else {
+                node.strategy().to_vec()
+            };
+
+            let num_hands = strategy.len() / multipliers.len();
+            for hand in 0..num_hands {
+                let biased_sum = multipliers
+                    .iter()
+                    .enumerate()
+                    .fold(0.0, |sum, (i, &m)| sum + strategy[i * num_hands + hand] * m);
+                if biased_sum > 0.0 {
+                    for (i, &m) in multipliers.iter().enumerate() {
+                        strategy[i * num_hands + hand] *= m;
+                    }
+                }
+            }
+
+            if self.is_node_compressed(&node) {
+                encode_unsigned_slice(node.strategy_compressed_mut(), &strategy);
+            } else {
+                node.strategy_mut().copy_from_slice(&strategy);
+            }
+        }
+    }
+}
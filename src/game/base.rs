@@ -1,12 +1,19 @@
 use super::*;
 use crate::bunching::*;
+use crate::error::*;
 use crate::interface::*;
+use crate::range::*;
 use crate::utility::*;
+use std::io;
 use std::mem::{self, MaybeUninit};
+use std::sync::atomic::Ordering;
 
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
+/// Swap list used for the abstracted turn cards (no swap is performed).
+static EMPTY_SWAP_LIST: SwapList = [Vec::new(), Vec::new()];
+
 #[derive(Default)]
 struct BuildTreeInfo {
     flop_index: usize,
@@ -27,12 +34,21 @@ impl Game for PostFlopGame {
 
     #[inline]
     fn num_private_hands(&self, player: usize) -> usize {
-        self.private_cards[player].len()
+        self.num_stored_hands(player)
     }
 
     #[inline]
     fn initial_weights(&self, player: usize) -> &[f32] {
-        &self.initial_weights[player]
+        if self.is_suit_reduced() {
+            &self.hand_classes[player].initial_weights
+        } else {
+            &self.initial_weights[player]
+        }
+    }
+
+    #[inline]
+    fn stored_hand_indices(&self, player: usize) -> &[u16] {
+        &self.hand_classes[player].index
     }
 
     #[inline]
@@ -43,10 +59,11 @@ impl Game for PostFlopGame {
         player: usize,
         cfreach: &[f32],
     ) {
-        if self.bunching_num_dead_cards == 0 {
-            self.evaluate_internal(result, node, player, cfreach);
+        let rake = self.terminal_rake(node);
+        if self.is_suit_reduced() {
+            self.evaluate_stored_hands(result, node, player, cfreach, rake);
         } else {
-            self.evaluate_internal_bunching(result, node, player, cfreach);
+            self.evaluate_with_rake(result, node, player, cfreach, rake);
         }
     }
 
@@ -67,18 +84,31 @@ impl Game for PostFlopGame {
     #[inline]
     fn set_solved(&mut self) {
         self.state = State::Solved;
+        self.staged_finalization = None;
+        self.is_solving.store(false, Ordering::Relaxed);
+        self.lock_decoded_cache().clear();
         let history = self.action_history.clone();
         self.apply_history(&history);
     }
 
+    #[inline]
+    fn check_operation(&self, operation: GameOperation) -> Result<(), StateError> {
+        PostFlopGame::check_operation(self, operation)
+    }
+
     #[inline]
     fn is_ready(&self) -> bool {
-        self.state == State::MemoryAllocated && self.storage_mode == BoardState::River
+        self.state == State::MemoryAllocated
+            && self.storage_mode == BoardState::River
+            && self.staged_finalization.is_none()
     }
 
     #[inline]
     fn is_raked(&self) -> bool {
+        // the chop fee and the payoffs other than the chip EV are not zero-sum either
         self.tree_config.rake_rate > 0.0 && self.tree_config.rake_cap > 0.0
+            || self.tree_config.is_allin_chop_fee_enabled()
+            || !self.tree_config.payoff_model.is_chip_ev()
     }
 
     #[inline]
@@ -92,7 +122,9 @@ impl Game for PostFlopGame {
 
     #[inline]
     fn isomorphic_swap(&self, node: &Self::Node, index: usize) -> &[Vec<(u16, u16)>; 2] {
-        if node.turn == NOT_DEALT {
+        if node.turn == NOT_DEALT && index >= self.num_isomorphic_turn {
+            &EMPTY_SWAP_LIST
+        } else if node.turn == NOT_DEALT {
             &self.isomorphism_swap_turn[self.isomorphism_card_turn[index] as usize & 3]
         } else {
             &self.isomorphism_swap_river[node.turn as usize & 3]
@@ -115,20 +147,68 @@ impl Game for PostFlopGame {
         self.is_compression_enabled
     }
 
-    fn save_to_file(&self)
-    {
-        println!("Wow!");
-        println!("{:?}",self.available_actions());
-        for (h,hand) in self.private_cards(self.current_player()).iter().enumerate()
-        {
-            let mut hand_strategy = format!("{},{}:",hand.0,hand.1);
-            for (a,action) in self.available_actions().iter().enumerate()
-            {
-                hand_strategy += &format!("{:?}-{},",action,self.strategy()[a*self.num_private_hands(self.current_player())+h])
-            }
-            println!("{}",hand_strategy);
-        }
-        
+    #[inline]
+    fn is_node_compressed(&self, node: &Self::Node) -> bool {
+        self.is_compression_enabled && node.street() >= self.compression_street
+    }
+
+    #[inline]
+    fn is_f64_regrets_enabled(&self) -> bool {
+        self.is_f64_regrets_enabled
+    }
+
+    #[inline]
+    fn enable_parallelization(&self, node: &Self::Node) -> bool {
+        let index = self.node_index(node);
+        self.parallel_nodes.get(index).copied().unwrap_or(false)
+    }
+
+    #[inline]
+    fn mark_solving(&self) {
+        self.is_solving.store(true, Ordering::Relaxed);
+        self.num_iterations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn num_nodes_hint(&self) -> usize {
+        self.node_arena.len()
+    }
+
+    #[inline]
+    fn memory_usage_hint(&self) -> u64 {
+        let storage = [
+            &self.storage1,
+            &self.storage2,
+            &self.storage_ip,
+            &self.storage_chance,
+        ]
+        .iter()
+        .map(|storage| storage.len() as u64)
+        .sum::<u64>();
+        storage
+            + self.lazy_memory_usage()
+            + self.misc_memory_usage
+            + self.allin_equity_cache_memory_usage()
+    }
+
+    #[inline]
+    fn update_frozen_streets(&self, exploitability: f32) {
+        self.freeze_streets(exploitability);
+    }
+
+    #[inline]
+    fn is_node_frozen(&self, node: &Self::Node) -> bool {
+        node.street() as u8 + self.num_frozen_streets.load(Ordering::Relaxed) > 2
+    }
+
+    #[inline]
+    fn is_node_allocated(&self, node: &Self::Node) -> bool {
+        !self.is_lazy_allocation || node.is_terminal() || node.storage1 != self.lazy_zeros_ptr()
+    }
+
+    #[inline]
+    fn allocate_node(&self, node: &mut Self::Node, cfreach: &[f32]) -> bool {
+        self.is_node_allocated(node) || self.allocate_node_lazily(node, cfreach)
     }
 }
 
@@ -148,35 +228,56 @@ impl PostFlopGame {
 
     /// Creates a new [`PostFlopGame`] with the specified configuration.
     #[inline]
-    pub fn with_config(card_config: CardConfig, action_tree: ActionTree) -> Result<Self, String> {
+    pub fn with_config(card_config: CardConfig, action_tree: ActionTree) -> Result<Self, Error> {
         let mut game = Self::new();
         game.update_config(card_config, action_tree)?;
         Ok(game)
     }
 
+    /// Creates a new [`PostFlopGame`] with the specified card configuration and tree topology.
+    ///
+    /// The topology is shared with the caller, so the same [`TreeTopology`] can be used to build
+    /// games on many boards without rebuilding the action tree.
+    #[inline]
+    pub fn with_topology(card_config: CardConfig, topology: TreeTopology) -> Result<Self, Error> {
+        let mut game = Self::new();
+        game.update_config_with_topology(card_config, topology)?;
+        Ok(game)
+    }
+
     /// Updates the game configuration. The solved result will be lost.
     #[inline]
     pub fn update_config(
         &mut self,
         card_config: CardConfig,
         action_tree: ActionTree,
-    ) -> Result<(), String> {
+    ) -> Result<(), Error> {
+        self.update_config_with_topology(card_config, action_tree.topology())
+    }
+
+    /// Updates the game configuration with the specified tree topology. The solved result will be
+    /// lost.
+    pub fn update_config_with_topology(
+        &mut self,
+        card_config: CardConfig,
+        topology: TreeTopology,
+    ) -> Result<(), Error> {
         self.state = State::ConfigError;
 
-        if !action_tree.invalid_terminals().is_empty() {
-            return Err("Invalid terminal is found in action tree".to_string());
+        if topology.is_empty() || topology.has_invalid_terminals() {
+            return Err(Error::Config(
+                "Invalid terminal is found in action tree".to_string(),
+            ));
         }
 
         self.card_config = card_config;
-        (
-            self.tree_config,
-            self.added_lines,
-            self.removed_lines,
-            self.action_root,
-        ) = action_tree.eject();
+        self.turn_buckets.clear();
+        self.tree_config = topology.config().clone();
+        self.topology = topology;
 
         self.check_card_config()?;
         self.init_card_fields();
+        self.check_isomorphism()?;
         self.init_root()?;
 
         self.state = State::TreeBuilt;
@@ -194,27 +295,65 @@ impl PostFlopGame {
     /// *O*(#(OOP private hands) + #(IP private hands)) to *O*(#(OOP private hands) * #(IP private
     /// hands)).
     #[inline]
-    pub fn set_bunching_effect(&mut self, bunching_data: &BunchingData) -> Result<(), String> {
+    pub fn set_bunching_effect(&mut self, bunching_data: &BunchingData) -> Result<(), Error> {
         if self.state <= State::Uninitialized {
-            return Err("Game is not successfully initialized".to_string());
+            return Err(Error::Config(
+                "Game is not successfully initialized".to_string(),
+            ));
         }
 
         if !bunching_data.is_ready() {
-            return Err("Bunching configuration is not ready".to_string());
+            return Err(Error::Config(
+                "Bunching configuration is not ready".to_string(),
+            ));
         }
 
         let mut flop_sorted = self.card_config.flop;
         flop_sorted.sort_unstable();
         if flop_sorted != bunching_data.flop() {
-            return Err("Flop cards do not match".to_string());
+            return Err(Error::Config("Flop cards do not match".to_string()));
+        }
+
+        if !self.turn_buckets.is_empty() {
+            return Err(Error::Config(
+                "Bunching effect cannot be used with turn card abstraction".to_string(),
+            ));
         }
 
+        if self.num_showdown_rankings() > 1 {
+            return Err(Error::Config(
+                "Bunching effect cannot be used with split pot or second board".to_string(),
+            ));
+        }
+
+        self.check_hand_classes_bunching(bunching_data.fold_ranges())?;
+
         self.reset_bunching_effect();
         self.set_bunching_effect_internal(bunching_data)?;
 
         Ok(())
     }
 
+    /// Sets the bunching effect from the preflop ranges of the folded players (at most 4).
+    ///
+    /// This is a shorthand for building a [`BunchingData`] for the flop of the game, processing
+    /// it, and passing it to [`set_bunching_effect`]. The processing is the expensive part (the
+    /// convolution over the dead ranges), so use [`BunchingData`] directly to report its progress
+    /// or to reuse the result for multiple games with the same flop.
+    ///
+    /// [`set_bunching_effect`]: #method.set_bunching_effect
+    pub fn set_bunching_fold_ranges(&mut self, fold_ranges: &[Range]) -> Result<(), Error> {
+        if self.state <= State::Uninitialized {
+            return Err(Error::Config(
+                "Game is not successfully initialized".to_string(),
+            ));
+        }
+
+        let mut bunching_data = BunchingData::new(fold_ranges, self.card_config.flop)?;
+        bunching_data.process(false);
+        self.set_bunching_effect(&bunching_data)
+    }
+
     /// Resets the bunching effect configuration. The current node will also be reset to the root.
     #[inline]
     pub fn reset_bunching_effect(&mut self) {
@@ -245,13 +384,36 @@ impl PostFlopGame {
     /// Obtains the added lines.
     #[inline]
     pub fn added_lines(&self) -> &[Vec<Action>] {
-        &self.added_lines
+        self.topology.added_lines()
     }
 
     /// Obtains the removed lines.
     #[inline]
     pub fn removed_lines(&self) -> &[Vec<Action>] {
-        &self.removed_lines
+        self.topology.removed_lines()
+    }
+
+    /// Returns the number of private hands of the given player, i.e., the length of
+    /// [`private_cards`].
+    ///
+    /// The results of the game, e.g., [`strategy`], are laid out by this number even if the
+    /// symmetric hands share their storage (see [`is_suit_reduced`]).
+    ///
+    /// [`private_cards`]: #method.private_cards
+    /// [`strategy`]: #method.strategy
+    /// [`is_suit_reduced`]: #method.is_suit_reduced
+    #[inline]
+    pub fn num_private_hands(&self, player: usize) -> usize {
+        self.private_cards[player].len()
+    }
+
+    /// Returns the initial weights of the private hands of the given player, in the order of
+    /// [`private_cards`].
+    ///
+    /// [`private_cards`]: #method.private_cards
+    #[inline]
+    pub fn initial_weights(&self, player: usize) -> &[f32] {
+        &self.initial_weights[player]
     }
 
     /// Returns the card list of private hands of the given player.
@@ -271,6 +433,14 @@ impl PostFlopGame {
     }
 
     /// Returns the estimated memory usage in bytes (uncompressed, compressed).
+    ///
+    /// The estimate is computed from the shape of the tree and is available before the memory is
+    /// allocated, so that one can check it, rebuild the game with a smaller [`TreeConfig`] (or
+    /// [`remove_lines`]) if needed, and then commit the memory with [`allocate_memory`]. The
+    /// estimate does not change after the allocation.
+    ///
+    /// [`remove_lines`]: #method.remove_lines
+    /// [`allocate_memory`]: #method.allocate_memory
     #[inline]
     pub fn memory_usage(&self) -> (u64, u64) {
         if self.state <= State::Uninitialized {
@@ -298,11 +468,13 @@ impl PostFlopGame {
     ///
     /// This allows the removal of chance-specific lines (e.g., remove overbets on board-pairing
     /// turns) which we cannot do while building an action tree.
-    pub fn remove_lines(&mut self, lines: &[Vec<Action>]) -> Result<(), String> {
+    pub fn remove_lines(&mut self, lines: &[Vec<Action>]) -> Result<(), Error> {
         if self.state <= State::Uninitialized {
-            return Err("Game is not successfully initialized".to_string());
+            return Err(Error::Config(
+                "Game is not successfully initialized".to_string(),
+            ));
         } else if self.state >= State::MemoryAllocated {
-            return Err("Game has already been allocated".to_string());
+            return Err(Error::Config("Game has already been allocated".to_string()));
         }
 
         for line in lines {
@@ -316,6 +488,321 @@ impl PostFlopGame {
         Ok(())
     }
 
+    /// Sets the ranking of the hands at showdown (see [`Evaluator`]). The standard high-hand
+    /// ranking is used by default.
+    ///
+    /// This method must be called before allocating memory and setting the bunching effect. The
+    /// evaluator is kept across [`update_config`] but is not saved to files.
+    ///
+    /// Returns an error if the evaluator is not symmetric with respect to suits while the suit
+    /// isomorphism is enabled (see [`CardConfig::disable_isomorphism`]).
+    ///
+    /// [`update_config`]: #method.update_config
+    pub fn set_evaluator(&mut self, evaluator: Arc<dyn Evaluator>) -> Result<(), Error> {
+        if self.state <= State::Uninitialized {
+            return Err(Error::Config(
+                "Game is not successfully initialized".to_string(),
+            ));
+        } else if self.state >= State::MemoryAllocated {
+            return Err(Error::Config("Game has already been allocated".to_string()));
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            return Err(Error::Config(
+                "Evaluator must be set before the bunching effect".to_string(),
+            ));
+        }
+
+        let prev_evaluator = self.evaluator.replace(evaluator);
+        self.init_hand_strength();
+
+        if let Err(e) = self.check_isomorphism() {
+            self.evaluator = prev_evaluator;
+            self.init_hand_strength();
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the evaluator used at showdown.
+    #[inline]
+    pub(crate) fn evaluator(&self) -> &dyn Evaluator {
+        self.evaluator.as_deref().unwrap_or(&HighHandEvaluator)
+    }
+
+    /// Sets the backend that allocates the storage of the strategies, the regrets, and the
+    /// counterfactual values (see [`StorageBackend`]). The heap is used by default.
+    ///
+    /// This method must be called before allocating memory. The backend is kept across
+    /// [`update_config`] but is not saved to files; loaded games always use the heap. The lazy
+    /// allocation (see [`allocate_memory_lazy`]) allocates the nodes on the heap regardless of
+    /// the backend.
+    ///
+    /// [`update_config`]: #method.update_config
+    /// [`allocate_memory_lazy`]: #method.allocate_memory_lazy
+    pub fn set_storage_backend(&mut self, backend: Arc<dyn StorageBackend>) -> Result<(), Error> {
+        if self.state >= State::MemoryAllocated {
+            return Err(Error::Config("Game has already been allocated".to_string()));
+        }
+
+        self.storage_backend = Some(backend);
+        Ok(())
+    }
+
+    /// Returns whether the pot is split between the high and low hands at showdown.
+    #[inline]
+    pub fn is_split_pot(&self) -> bool {
+        !self.hand_strength_low.is_empty()
+    }
+
+    /// Computes the hand strength information with the current evaluator.
+    fn init_hand_strength(&mut self) {
+        let evaluator = self.evaluator();
+        let card_config = &self.card_config;
+        let private_cards = &self.private_cards;
+
+        let high = |board: &[Card; 5], hand| evaluator.evaluate(board, hand) as u64;
+
+        // the low half of the pot goes to the best qualified low hand, or to the best high hand
+        // if neither hand qualifies
+        let low = |board: &[Card; 5], hand| match evaluator.evaluate_low(board, hand) {
+            Some(low) => (1 << 32) | low as u64,
+            None => evaluator.evaluate(board, hand) as u64,
+        };
+
+        let hand_strength = card_config.hand_strength(private_cards, high);
+        let hand_strength_low = if evaluator.is_split_pot() {
+            card_config.hand_strength(private_cards, low)
+        } else {
+            Vec::new()
+        };
+
+        let mut second_board_strength = Vec::new();
+        if let [c1, c2, c3, turn, river] = card_config.second_board[..] {
+            let second_config = CardConfig {
+                flop: [c1, c2, c3],
+                turn,
+                river,
+                ..Default::default()
+            };
+            let pair_index = card_pair_to_index(turn, river);
+            let mut push = |mut strength: Vec<[Vec<StrengthItem>; 2]>| {
+                second_board_strength.push(mem::take(&mut strength[pair_index]));
+            };
+            push(second_config.hand_strength(private_cards, high));
+            if evaluator.is_split_pot() {
+                push(second_config.hand_strength(private_cards, low));
+            }
+        }
+
+        self.hand_strength = hand_strength;
+        self.hand_strength_low = hand_strength_low;
+        self.second_board_strength = second_board_strength;
+        self.allin_equity_cache.clear();
+    }
+
+    /// Checks that the hand strengths are invariant under the suit swaps exploited by the suit
+    /// isomorphism, i.e., that the isomorphism does not change the result of the showdowns.
+    ///
+    /// The built-in evaluators are symmetric, so the check is only performed with a custom
+    /// evaluator.
+    fn check_isomorphism(&self) -> Result<(), Error> {
+        if self.evaluator.is_none() || self.card_config.disable_isomorphism {
+            return Ok(());
+        }
+
+        self.check_hand_classes()?;
+
+        let config = &self.card_config;
+        let mut board_rankset = [0u16; 4];
+        for &card in config.flop.iter().chain([&config.turn, &config.river]) {
+            if card != NOT_DEALT {
+                board_rankset[card as usize & 3] |= 1 << (card >> 2);
+            }
+        }
+
+        let swap_card = |card: Card, suit1: Card, suit2: Card| match card & 3 {
+            suit if suit == suit1 => card - suit1 + suit2,
+            suit if suit == suit2 => card - suit2 + suit1,
+            _ => card,
+        };
+
+        for suit2 in 1..4 {
+            for suit1 in 0..suit2 {
+                // the swap must preserve the ranges and the dealt cards
+                if board_rankset[suit1 as usize] != board_rankset[suit2 as usize]
+                    || !config.range[0].is_suit_isomorphic(suit1, suit2)
+                    || !config.range[1].is_suit_isomorphic(suit1, suit2)
+                {
+                    continue;
+                }
+
+                // the hands are preserved by the swap, so their indices can be looked up
+                let swapped_index = [0, 1].map(|player| {
+                    let hands = &self.private_cards[player];
+                    hands
+                        .iter()
+                        .map(|&(c1, c2)| {
+                            let c1 = swap_card(c1, suit1, suit2);
+                            let c2 = swap_card(c2, suit1, suit2);
+                            hands.binary_search(&(c1.min(c2), c1.max(c2))).unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                });
+
+                let strength_lists = [&self.hand_strength, &self.hand_strength_low];
+                for strength_list in strength_lists {
+                    for board1 in 0..52 {
+                        for board2 in board1 + 1..52 {
+                            let index = card_pair_to_index(board1, board2);
+                            let swapped = card_pair_to_index(
+                                swap_card(board1, suit1, suit2),
+                                swap_card(board2, suit1, suit2),
+                            );
+                            let (Some(strength), Some(swapped_strength)) =
+                                (strength_list.get(index), strength_list.get(swapped))
+                            else {
+                                continue;
+                            };
+
+                            if index == swapped
+                                || strength[0].is_empty()
+                                || swapped_strength[0].is_empty()
+                            {
+                                continue;
+                            }
+
+                            for player in 0..2 {
+                                let mut values = vec![u16::MAX; self.num_private_hands(player)];
+                                let len = swapped_strength[player].len();
+                                for item in &swapped_strength[player][1..len - 1] {
+                                    values[item.index as usize] = item.strength;
+                                }
+
+                                let len = strength[player].len();
+                                for item in &strength[player][1..len - 1] {
+                                    let swapped_hand = swapped_index[player][item.index as usize];
+                                    if values[swapped_hand] != item.strength {
+                                        return Err(Error::Config(
+                                            "Evaluator is not symmetric with respect to suits; \
+                                             set `disable_isomorphism` of `CardConfig`"
+                                                .to_string(),
+                                        ));
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the turn card abstraction, which groups the turn cards into weighted buckets.
+    ///
+    /// The first card of each bucket is its representative. While solving, only the
+    /// representative is dealt, and its subtree also stands for the other cards of the bucket, so
+    /// each bucket is weighted by its number of cards. After solving, dealing any card of the
+    /// bucket moves to the subtree of the representative, i.e., the strategy of the bucket is
+    /// shared by all of its cards. Cards that do not belong to any bucket are dealt exactly.
+    /// Passing an empty slice disables the abstraction.
+    ///
+    /// This is an approximation, and unlike the suit isomorphism, the private hands are not
+    /// remapped between the cards of a bucket. The card removal effects of the representative
+    /// are applied to every card of its bucket: while solving, the hands holding the
+    /// representative are treated as blocked and the hands holding another card of the bucket as
+    /// alive, and the river cards are those of the representative's subtree. After solving,
+    /// [`strategy`] and [`expected_values`] on a non-representative card return the entries of
+    /// the representative's subtree unchanged: the hands holding the dealt card get zero
+    /// weights, but the hands holding the representative card, which are reachable on the dealt
+    /// card, get the entries of blocked hands (e.g., the untrained uniform strategy). Buckets are
+    /// therefore best formed from cards whose blockers matter little for the ranges.
+    ///
+    /// This method rebuilds the game tree, so it must be called before [`remove_lines`].
+    /// The bunching effect cannot be used together with the abstraction.
+    ///
+    /// [`remove_lines`]: #method.remove_lines
+    /// [`strategy`]: #method.strategy
+    /// [`expected_values`]: #method.expected_values
+    pub fn set_turn_abstraction(&mut self, buckets: &[Vec<Card>]) -> Result<(), Error> {
+        if self.state <= State::Uninitialized {
+            return Err(Error::Config(
+                "Game is not successfully initialized".to_string(),
+            ));
+        } else if self.state >= State::MemoryAllocated {
+            return Err(Error::Config("Game has already been allocated".to_string()));
+        }
+
+        if self.card_config.turn != NOT_DEALT {
+            return Err(Error::Config(
+                "Turn card abstraction requires the flop to be the root".to_string(),
+            ));
+        }
+
+        if self.bunching_num_dead_cards != 0 {
+            return Err(Error::Config(
+                "Turn card abstraction cannot be used with bunching effect".to_string(),
+            ));
+        }
+
+        let flop = self.card_config.flop;
+        let flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        let isomorphic_cards = &self.isomorphism_card_turn[..self.num_isomorphic_turn];
+
+        let mut seen_mask: u64 = 0;
+        for bucket in buckets {
+            if bucket.is_empty() {
+                return Err(Error::Config("Bucket must not be empty".to_string()));
+            }
+
+            for &card in bucket {
+                if 52 <= card {
+                    return Err(Error::Config(format!(
+                        "Turn card must be in [0, 52): card = {card}"
+                    )));
+                }
+
+                if (1 << card) & flop_mask != 0 {
+                    return Err(Error::Config(format!(
+                        "Turn card must be different from flop cards: card = {card}"
+                    )));
+                }
+
+                if (1 << card) & seen_mask != 0 {
+                    return Err(Error::Config(format!(
+                        "Turn card appears more than once: card = {card}"
+                    )));
+                }
+
+                seen_mask |= 1 << card;
+            }
+
+            if isomorphic_cards.contains(&bucket[0]) {
+                return Err(Error::Config(format!(
+                    "Representative card is removed by isomorphism: card = {}",
+                    bucket[0]
+                )));
+            }
+        }
+
+        self.turn_buckets = buckets.iter().filter(|b| b.len() > 1).cloned().collect();
+        self.init_card_fields();
+        self.init_root()?;
+        self.state = State::TreeBuilt;
+        self.init_interpreter();
+
+        Ok(())
+    }
+
+    /// Returns the buckets of the turn card abstraction.
+    #[inline]
+    pub fn turn_abstraction(&self) -> &[Vec<Card>] {
+        &self.turn_buckets
+    }
+
     /// Returns whether the memory is allocated.
     ///
     /// If the memory is allocated, returns `Some(is_compression_enabled)`;
@@ -330,97 +817,268 @@ impl PostFlopGame {
     }
 
     /// Allocates the memory.
+    ///
+    /// Panics if the game is not successfully initialized, the memory usage exceeds the maximum
+    /// size, or the storage backend fails; use [`try_allocate_memory`] to handle these cases.
+    ///
+    /// [`try_allocate_memory`]: #method.try_allocate_memory
     pub fn allocate_memory(&mut self, enable_compression: bool) {
+        let compression = enable_compression.then_some(BoardState::Flop);
+        self.allocate_memory_internal(compression, false, None)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Allocates the memory like [`allocate_memory`], returning an error instead of panicking.
+    ///
+    /// Returns [`Error::State`] if the allocation is not valid for the current state of the game
+    /// (see [`check_operation`]), e.g., the solving or the finalization is in progress, returns
+    /// [`Error::Config`] if the memory usage exceeds the maximum size, and returns [`Error::Io`]
+    /// if the storage backend fails to allocate the storage (e.g., the disk is full), in which
+    /// case the memory is left unallocated.
+    ///
+    /// [`allocate_memory`]: #method.allocate_memory
+    /// [`check_operation`]: #method.check_operation
+    pub fn try_allocate_memory(&mut self, enable_compression: bool) -> Result<(), Error> {
+        self.check_operation(GameOperation::AllocateMemory)?;
+        let compression = enable_compression.then_some(BoardState::Flop);
+        self.allocate_memory_internal(compression, false, None)
+    }
+
+    /// Allocates the memory, compressing only the storage of the nodes on `street` and later
+    /// streets.
+    ///
+    /// For example, `BoardState::Turn` keeps the flop nodes in 32-bit floating point and
+    /// compresses the turn and river nodes. The flop nodes are few but queried most, whereas the
+    /// turn and river nodes dominate the memory usage, so this retains the full precision where it
+    /// matters at a small cost of memory (see [`memory_usage_compressed_from`]).
+    /// `BoardState::Flop` is equivalent to `allocate_memory(true)`.
+    ///
+    /// [`memory_usage_compressed_from`]: #method.memory_usage_compressed_from
+    pub fn allocate_memory_compressed_from(&mut self, street: BoardState) {
+        self.allocate_memory_internal(Some(street), false, None)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Returns the estimated memory usage in bytes when the storage of the nodes on `street` and
+    /// later streets is compressed.
+    pub fn memory_usage_compressed_from(&self, street: BoardState) -> u64 {
         if self.state <= State::Uninitialized {
             panic!("Game is not successfully initialized");
         }
 
+        self.memory_usage_with(Some(street))
+    }
+
+    /// Returns the first compressed street, or `None` if the compression is disabled.
+    #[inline]
+    pub fn compression_street(&self) -> Option<BoardState> {
+        self.is_compression_enabled
+            .then_some(self.compression_street)
+    }
+
+    /// Allocates the memory, storing the cumulative regrets in 64-bit floating point.
+    ///
+    /// On huge trees or with an algorithm that does not discount the regrets (e.g.,
+    /// [`SolverAlgorithm::VanillaCfr`]), the regret updates of late iterations are tiny compared to
+    /// the cumulative values and are partially lost in 32-bit floating point. Accumulating them in
+    /// 64-bit floating point improves the convergence near tight exploitability targets at the
+    /// cost of [`memory_usage_f64_regrets`] bytes of memory. The strategy and the counterfactual
+    /// values are still stored in 32-bit floating point, and the compression is not supported.
+    ///
+    /// [`memory_usage_f64_regrets`]: #method.memory_usage_f64_regrets
+    /// [`SolverAlgorithm::VanillaCfr`]: crate::SolverAlgorithm::VanillaCfr
+    pub fn allocate_memory_f64_regrets(&mut self) {
+        self.allocate_memory_internal(None, true, None)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Returns the estimated memory usage in bytes when the cumulative regrets are stored in 64-bit
+    /// floating point.
+    #[inline]
+    pub fn memory_usage_f64_regrets(&self) -> u64 {
+        self.memory_usage().0 + 4 * self.num_storage
+    }
+
+    /// Returns whether the cumulative regrets are stored in 64-bit floating point.
+    #[inline]
+    pub fn is_f64_regrets_enabled(&self) -> bool {
+        self.is_f64_regrets_enabled
+    }
+
+    pub(super) fn allocate_memory_internal(
+        &mut self,
+        compression: Option<BoardState>,
+        enable_f64_regrets: bool,
+        lazy_threshold: Option<f32>,
+    ) -> Result<(), Error> {
+        if self.state <= State::Uninitialized {
+            let operation = GameOperation::AllocateMemory;
+            let state = self.game_state();
+            return Err(StateError { operation, state }.into());
+        }
+
         if self.state == State::MemoryAllocated
             && self.storage_mode == BoardState::River
-            && self.is_compression_enabled == enable_compression
+            && self.compression_street() == compression
+            && self.is_f64_regrets_enabled == enable_f64_regrets
+            && self.lazy_threshold() == lazy_threshold
         {
-            return;
+            return Ok(());
         }
 
-        let num_bytes = if enable_compression { 2 } else { 4 };
-        if num_bytes * self.num_storage > isize::MAX as u64
-            || num_bytes * self.num_storage_chance > isize::MAX as u64
+        let [storage_bytes, storage_ip_bytes, storage_chance_bytes] =
+            self.storage_bytes(compression);
+        let storage2_bytes = storage_bytes * if enable_f64_regrets { 2 } else { 1 };
+        if lazy_threshold.is_none()
+            && (storage2_bytes > isize::MAX as u64 || storage_chance_bytes > isize::MAX as u64)
         {
-            panic!("Memory usage exceeds maximum size");
+            return Err(Error::Config(
+                "Memory usage exceeds maximum size".to_string(),
+            ));
         }
 
         self.state = State::MemoryAllocated;
-        self.is_compression_enabled = enable_compression;
+        self.is_compression_enabled = compression.is_some();
+        self.compression_street = compression.unwrap_or_default();
+        self.is_f64_regrets_enabled = enable_f64_regrets;
+        self.staged_finalization = None;
+        self.is_solving.store(false, Ordering::Relaxed);
+        self.num_frozen_streets.store(0, Ordering::Relaxed);
+        self.num_iterations.store(0, Ordering::Relaxed);
 
         self.clear_storage();
 
-        let storage_bytes = (num_bytes * self.num_storage) as usize;
-        let storage_ip_bytes = (num_bytes * self.num_storage_ip) as usize;
-        let storage_chance_bytes = (num_bytes * self.num_storage_chance) as usize;
-
-        self.storage1 = vec![0; storage_bytes];
-        self.storage2 = vec![0; storage_bytes];
-        self.storage_ip = vec![0; storage_ip_bytes];
-        self.storage_chance = vec![0; storage_chance_bytes];
+        if let Some(threshold) = lazy_threshold {
+            self.init_lazy_storage(threshold);
+        } else {
+            let num_bytes = [
+                storage_bytes,
+                storage2_bytes,
+                storage_ip_bytes,
+                storage_chance_bytes,
+            ];
+            match self.allocate_storages(num_bytes) {
+                Ok([storage1, storage2, storage_ip, storage_chance]) => {
+                    self.storage1 = storage1;
+                    self.storage2 = storage2;
+                    self.storage_ip = storage_ip;
+                    self.storage_chance = storage_chance;
+                }
+                Err(e) => {
+                    // the previous storage has already been released
+                    self.state = State::TreeBuilt;
+                    return Err(e);
+                }
+            }
+            self.allocate_memory_nodes();
+        }
 
-        self.allocate_memory_nodes();
+        self.update_parallel_nodes();
 
         self.storage_mode = BoardState::River;
         self.target_storage_mode = BoardState::River;
+        Ok(())
     }
 
     /// Checks the card configuration.
-    pub(crate) fn check_card_config(&mut self) -> Result<(), String> {
+    pub(crate) fn check_card_config(&mut self) -> Result<(), Error> {
         let config = &self.card_config;
         let (flop, turn, river) = (config.flop, config.turn, config.river);
         let range = &config.range;
 
         if flop.contains(&NOT_DEALT) {
-            return Err("Flop cards not initialized".to_string());
+            return Err(Error::Config("Flop cards not initialized".to_string()));
         }
 
         if flop.iter().any(|&c| 52 <= c) {
-            return Err(format!("Flop cards must be in [0, 52): flop = {flop:?}"));
+            return Err(Error::Config(format!(
+                "Flop cards must be in [0, 52): flop = {flop:?}"
+            )));
         }
 
         if flop[0] == flop[1] || flop[0] == flop[2] || flop[1] == flop[2] {
-            return Err(format!("Flop cards must be unique: flop = {flop:?}"));
+            return Err(Error::Config(format!(
+                "Flop cards must be unique: flop = {flop:?}"
+            )));
         }
 
         if turn != NOT_DEALT {
             if 52 <= turn {
-                return Err(format!("Turn card must be in [0, 52): turn = {turn}"));
+                return Err(Error::Config(format!(
+                    "Turn card must be in [0, 52): turn = {turn}"
+                )));
             }
 
             if flop.contains(&turn) {
-                return Err(format!(
+                return Err(Error::Config(format!(
                     "Turn card must be different from flop cards: turn = {turn}"
-                ));
+                )));
             }
         }
 
         if river != NOT_DEALT {
             if 52 <= river {
-                return Err(format!("River card must be in [0, 52): river = {river}"));
+                return Err(Error::Config(format!(
+                    "River card must be in [0, 52): river = {river}"
+                )));
             }
 
             if flop.contains(&river) {
-                return Err(format!(
+                return Err(Error::Config(format!(
                     "River card must be different from flop cards: river = {river}"
-                ));
+                )));
             }
 
             if turn == river {
-                return Err(format!(
+                return Err(Error::Config(format!(
                     "River card must be different from turn card: river = {river}"
-                ));
+                )));
             }
 
             if turn == NOT_DEALT {
-                return Err(format!(
+                return Err(Error::Config(format!(
                     "River card specified without turn card: river = {river}"
+                )));
+            }
+        }
+
+        let second_board = &config.second_board;
+        if !second_board.is_empty() {
+            if second_board.len() != 5 {
+                return Err(Error::Config(format!(
+                    "Second board must consist of 5 cards: second_board = {second_board:?}"
+                )));
+            }
+
+            if second_board.iter().any(|&c| 52 <= c) {
+                return Err(Error::Config(format!(
+                    "Second board cards must be in [0, 52): second_board = {second_board:?}"
+                )));
+            }
+
+            let second_board_mask = second_board.iter().fold(0u64, |m, &c| m | (1 << c));
+            if second_board_mask.count_ones() != 5 {
+                return Err(Error::Config(format!(
+                    "Second board cards must be unique: second_board = {second_board:?}"
+                )));
+            }
+
+            if river == NOT_DEALT {
+                return Err(Error::Config(
+                    "Second board requires the game to start on the river".to_string(),
                 ));
             }
+
+            let board_mask = flop
+                .iter()
+                .chain([turn, river].iter())
+                .fold(0u64, |m, &c| m | (1 << c));
+            if second_board_mask & board_mask != 0 {
+                return Err(Error::Config(format!(
+                    "Second board cards must be different from the first board: \
+                     second_board = {second_board:?}"
+                )));
+            }
         }
 
         let expected_state = match (turn != NOT_DEALT, river != NOT_DEALT) {
@@ -430,26 +1088,37 @@ impl PostFlopGame {
         };
 
         if self.tree_config.initial_state != expected_state {
-            return Err(format!(
+            return Err(Error::Config(format!(
                 "Invalid initial state of `tree_config`: expected = {:?}, actual = {:?}",
                 expected_state, self.tree_config.initial_state
-            ));
+            )));
+        }
+
+        let names = &config.player_names;
+        if !names[0].is_empty() && names[0] == names[1] {
+            return Err(Error::Config(format!(
+                "Player names must be unique: {names:?}"
+            )));
         }
 
         if range[0].is_empty() {
-            return Err("OOP range is empty".to_string());
+            return Err(Error::Config("OOP range is empty".to_string()));
         }
 
         if range[1].is_empty() {
-            return Err("IP range is empty".to_string());
+            return Err(Error::Config("IP range is empty".to_string()));
         }
 
         if !range[0].is_valid() {
-            return Err("OOP range is invalid (loaded broken data?)".to_string());
+            return Err(Error::Config(
+                "OOP range is invalid (loaded broken data?)".to_string(),
+            ));
         }
 
         if !range[1].is_valid() {
-            return Err("IP range is invalid (loaded broken data?)".to_string());
+            return Err(Error::Config(
+                "IP range is invalid (loaded broken data?)".to_string(),
+            ));
         }
 
         self.init_hands();
@@ -472,7 +1141,9 @@ impl PostFlopGame {
         }
 
         if self.num_combinations == 0.0 {
-            return Err("Valid card assignment does not exist".to_string());
+            return Err(Error::Config(
+                "Valid card assignment does not exist".to_string(),
+            ));
         }
 
         Ok(())
@@ -492,6 +1163,9 @@ impl PostFlopGame {
         if river != NOT_DEALT {
             board_mask |= 1 << river;
         }
+        for &card in &config.second_board {
+            board_mask |= 1 << card;
+        }
 
         for player in 0..2 {
             let (hands, weights) = range[player].get_hands_weights(board_mask);
@@ -523,7 +1197,7 @@ impl PostFlopGame {
             self.valid_indices_river,
         ) = self.card_config.valid_indices(&self.private_cards);
 
-        self.hand_strength = self.card_config.hand_strength(&self.private_cards);
+        self.init_hand_strength();
 
         (
             self.isomorphism_ref_turn,
@@ -533,17 +1207,62 @@ impl PostFlopGame {
             self.isomorphism_card_river,
             self.isomorphism_swap_river,
         ) = self.card_config.isomorphism(&self.private_cards);
+
+        self.apply_turn_abstraction();
+        self.init_hand_classes();
+    }
+
+    /// Appends the non-representative cards of the turn buckets to the isomorphic chances.
+    fn apply_turn_abstraction(&mut self) {
+        self.num_isomorphic_turn = self.isomorphism_ref_turn.len();
+        if self.turn_buckets.is_empty() {
+            return;
+        }
+
+        let flop = self.card_config.flop;
+        let flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        let skip_mask: u64 = self.isomorphism_card_turn.iter().map(|&c| 1 << c).sum();
+
+        let mut representative = (0..52).collect::<Vec<Card>>();
+        for bucket in &self.turn_buckets {
+            for &card in &bucket[1..] {
+                representative[card as usize] = bucket[0];
+            }
+        }
+
+        let children = (0..52)
+            .filter(|&card| (1 << card) & (flop_mask | skip_mask) == 0)
+            .collect::<Vec<Card>>();
+        let new_children = children
+            .iter()
+            .copied()
+            .filter(|&card| representative[card as usize] == card)
+            .collect::<Vec<_>>();
+        let new_index = |card: Card| new_children.binary_search(&card).unwrap() as u8;
+
+        for index in &mut self.isomorphism_ref_turn {
+            let card = children[*index as usize];
+            *index = new_index(representative[card as usize]);
+        }
+
+        for &card in &children {
+            let repr_card = representative[card as usize];
+            if repr_card != card {
+                self.isomorphism_ref_turn.push(new_index(repr_card));
+                self.isomorphism_card_turn.push(card);
+            }
+        }
     }
 
     /// Initializes the root node of game tree.
-    fn init_root(&mut self) -> Result<(), String> {
+    fn init_root(&mut self) -> Result<(), Error> {
         let num_nodes = self.count_num_nodes();
         let total_num_nodes = num_nodes[0] + num_nodes[1] + num_nodes[2];
 
         if total_num_nodes > u32::MAX as u64
             || mem::size_of::<PostFlopNode>() as u64 * total_num_nodes > isize::MAX as u64
         {
-            return Err("Too many nodes".to_string());
+            return Err(Error::Config("Too many nodes".to_string()));
         }
 
         self.num_nodes = num_nodes;
@@ -568,7 +1287,7 @@ impl PostFlopGame {
         root.turn = self.card_config.turn;
         root.river = self.card_config.river;
 
-        self.build_tree_recursive(0, &self.action_root.lock(), &mut info);
+        self.build_tree_recursive(0, 0, &mut info);
 
         self.num_storage = info.num_storage;
         self.num_storage_ip = info.num_storage_ip;
@@ -594,10 +1313,29 @@ impl PostFlopGame {
     /// Clears the storage.
     #[inline]
     fn clear_storage(&mut self) {
-        self.storage1 = Vec::new();
-        self.storage2 = Vec::new();
-        self.storage_ip = Vec::new();
-        self.storage_chance = Vec::new();
+        self.storage1 = Storage::default();
+        self.storage2 = Storage::default();
+        self.storage_ip = Storage::default();
+        self.storage_chance = Storage::default();
+        self.clear_lazy_storage();
+    }
+
+    /// Allocates a zero-initialized storage of `num_bytes` bytes with the storage backend.
+    #[inline]
+    fn allocate_storage(&self, num_bytes: u64) -> io::Result<Storage> {
+        Storage::allocate(self.storage_backend.as_deref(), num_bytes as usize)
+    }
+
+    /// Allocates the storages of the given sizes in the order of `storage1`, `storage2`,
+    /// `storage_ip`, and `storage_chance`.
+    fn allocate_storages(&self, num_bytes: [u64; 4]) -> Result<[Storage; 4], Error> {
+        let mut ret: [Storage; 4] = Default::default();
+        for (storage, &num_bytes) in ret.iter_mut().zip(&num_bytes) {
+            *storage = self
+                .allocate_storage(num_bytes)
+                .map_err(|e| Error::io(e, "Failed to allocate storage"))?;
+        }
+        Ok(ret)
     }
 
     /// Counts the number of nodes in the game tree.
@@ -621,7 +1359,7 @@ impl PostFlopGame {
             _ => (0, 1),
         };
 
-        let num_action_nodes = count_num_action_nodes(&self.action_root.lock());
+        let num_action_nodes = self.topology.count_num_action_nodes();
 
         [
             num_action_nodes[0],
@@ -633,22 +1371,17 @@ impl PostFlopGame {
     /// Computes the memory usage of this struct.
     #[inline]
     fn memory_usage_internal(&self) -> u64 {
-        // untracked: tree_config, action_root
+        // untracked: tree_config, topology (shared)
 
         let mut memory_usage = mem::size_of::<Self>() as u64;
 
-        memory_usage += vec_memory_usage(&self.added_lines);
-        memory_usage += vec_memory_usage(&self.removed_lines);
-        for line in &self.added_lines {
-            memory_usage += vec_memory_usage(line);
-        }
-        for line in &self.removed_lines {
-            memory_usage += vec_memory_usage(line);
-        }
+        memory_usage += self.hand_classes_memory_usage();
 
         memory_usage += vec_memory_usage(&self.valid_indices_turn);
         memory_usage += vec_memory_usage(&self.valid_indices_river);
         memory_usage += vec_memory_usage(&self.hand_strength);
+        memory_usage += vec_memory_usage(&self.hand_strength_low);
+        memory_usage += vec_memory_usage(&self.second_board_strength);
         memory_usage += vec_memory_usage(&self.isomorphism_ref_turn);
         memory_usage += vec_memory_usage(&self.isomorphism_card_turn);
         memory_usage += vec_memory_usage(&self.isomorphism_ref_river);
@@ -672,7 +1405,8 @@ impl PostFlopGame {
             for indices in &self.valid_indices_river {
                 memory_usage += vec_memory_usage(&indices[player]);
             }
-            for strength in &self.hand_strength {
+            let strength_iter = self.hand_strength.iter().chain(&self.hand_strength_low);
+            for strength in strength_iter.chain(&self.second_board_strength) {
                 memory_usage += vec_memory_usage(&strength[player]);
             }
             for swap in &self.isomorphism_swap_turn {
@@ -694,12 +1428,14 @@ impl PostFlopGame {
     fn build_tree_recursive(
         &self,
         node_index: usize,
-        action_node: &ActionTreeNode,
+        topology_index: usize,
         info: &mut BuildTreeInfo,
     ) {
+        let topology_node = self.topology.node(topology_index);
+        let topology_children = self.topology.children(topology_index);
         let mut node = self.node_arena[node_index].lock();
-        node.player = action_node.player;
-        node.amount = action_node.amount;
+        node.player = topology_node.player_flags();
+        node.amount = topology_node.amount();
 
         if node.is_terminal() {
             return;
@@ -709,17 +1445,14 @@ impl PostFlopGame {
             self.push_chances(node_index, info);
             for action_index in 0..node.num_actions() {
                 let child_index = node_index + node.children_offset as usize + action_index;
-                self.build_tree_recursive(child_index, &action_node.children[0].lock(), info);
+                self.build_tree_recursive(child_index, topology_children.start, info);
             }
         } else {
-            self.push_actions(node_index, action_node, info);
+            self.push_actions(node_index, topology_index, info);
             for action_index in 0..node.num_actions() {
                 let child_index = node_index + node.children_offset as usize + action_index;
-                self.build_tree_recursive(
-                    child_index,
-                    &action_node.children[action_index].lock(),
-                    info,
-                );
+                let topology_child = topology_children.start + action_index;
+                self.build_tree_recursive(child_index, topology_child, info);
             }
         }
     }
@@ -769,18 +1502,13 @@ impl PostFlopGame {
 
         node.num_elements = node
             .cfvalue_storage_player()
-            .map_or(0, |player| self.num_private_hands(player)) as u32;
+            .map_or(0, |player| self.num_stored_hands(player)) as u32;
 
         info.num_storage_chance += node.num_elements as u64;
     }
 
     /// Pushes the actions to the `node`.
-    fn push_actions(
-        &self,
-        node_index: usize,
-        action_node: &ActionTreeNode,
-        info: &mut BuildTreeInfo,
-    ) {
+    fn push_actions(&self, node_index: usize, topology_index: usize, info: &mut BuildTreeInfo) {
         let mut node = self.node_arena[node_index].lock();
 
         let street = match (node.turn, node.river) {
@@ -796,20 +1524,21 @@ impl PostFlopGame {
         };
 
         node.children_offset = (*base - node_index) as u32;
-        node.num_children = action_node.children.len() as u16;
+        node.num_children = self.topology.node(topology_index).num_children() as u16;
         *base += node.num_children as usize;
 
-        for (child, action) in node.children().iter().zip(action_node.actions.iter()) {
+        let topology_children = self.topology.children(topology_index);
+        for (child, index) in node.children().iter().zip(topology_children) {
             let mut child = child.lock();
-            child.prev_action = *action;
+            child.prev_action = self.topology.node(index).action();
             child.turn = node.turn;
             child.river = node.river;
         }
 
-        let num_private_hands = self.num_private_hands(node.player as usize);
-        node.num_elements = (node.num_actions() * num_private_hands) as u32;
+        let num_stored_hands = self.num_stored_hands(node.player as usize);
+        node.num_elements = (node.num_actions() * num_stored_hands) as u32;
         node.num_elements_ip = match node.prev_action {
-            Action::None | Action::Chance(_) => self.num_private_hands(PLAYER_IP as usize) as u16,
+            Action::None | Action::Chance(_) => self.num_stored_hands(PLAYER_IP as usize) as u16,
             _ => 0,
         };
 
@@ -818,7 +1547,7 @@ impl PostFlopGame {
     }
 
     /// Sets the bunching effect.
-    fn set_bunching_effect_internal(&mut self, bunching_data: &BunchingData) -> Result<(), String> {
+    fn set_bunching_effect_internal(&mut self, bunching_data: &BunchingData) -> Result<(), Error> {
         self.bunching_num_dead_cards = bunching_data.fold_ranges().len() * 2;
         let mut arena = vec![0.0]; // store dummy element
 
@@ -873,7 +1602,7 @@ impl PostFlopGame {
                     self.bunching_num_combinations = arena.iter().fold(0.0, |a, &x| a + x as f64);
                     if self.bunching_num_combinations == 0.0 {
                         self.reset_bunching_effect();
-                        return Err("Valid combination not found".to_string());
+                        return Err(Error::Config("Valid combination not found".to_string()));
                     }
                 }
 
@@ -933,7 +1662,7 @@ impl PostFlopGame {
                     self.bunching_num_combinations = arena.iter().fold(0.0, |a, &x| a + x as f64);
                     if self.bunching_num_combinations == 0.0 {
                         self.reset_bunching_effect();
-                        return Err("Valid combination not found".to_string());
+                        return Err(Error::Config("Valid combination not found".to_string()));
                     }
                 }
             }
@@ -997,7 +1726,7 @@ impl PostFlopGame {
                 self.bunching_num_combinations = arena.iter().fold(0.0, |a, &x| a + x as f64);
                 if self.bunching_num_combinations == 0.0 {
                     self.reset_bunching_effect();
-                    return Err("Valid combination not found".to_string());
+                    return Err(Error::Config("Valid combination not found".to_string()));
                 }
             }
         }
@@ -1368,13 +2097,13 @@ impl PostFlopGame {
         &self,
         node: &mut PostFlopNode,
         line: &[Action],
-    ) -> Result<BuildTreeInfo, String> {
+    ) -> Result<BuildTreeInfo, Error> {
         if line.is_empty() {
-            return Err("Empty line".to_string());
+            return Err(Error::Config("Empty line".to_string()));
         }
 
         if node.is_terminal() {
-            return Err("Unexpected terminal node".to_string());
+            return Err(Error::Config("Unexpected terminal node".to_string()));
         }
 
         let action = line[0];
@@ -1383,7 +2112,7 @@ impl PostFlopGame {
             .binary_search_by(|child| child.lock().prev_action.cmp(&action));
 
         if search_result.is_err() {
-            return Err(format!("Action does not exist: {action:?}"));
+            return Err(Error::Config(format!("Action does not exist: {action:?}")));
         }
 
         let index = search_result.unwrap();
@@ -1393,11 +2122,15 @@ impl PostFlopGame {
         }
 
         if node.is_chance() {
-            return Err("Cannot remove a line ending in a chance action".to_string());
+            return Err(Error::Config(
+                "Cannot remove a line ending in a chance action".to_string(),
+            ));
         }
 
         if node.num_actions() <= 1 {
-            return Err("Cannot remove the last action from a node".to_string());
+            return Err(Error::Config(
+                "Cannot remove the last action from a node".to_string(),
+            ));
         }
 
         // Remove action/children at index. To do this we must
@@ -1407,7 +2140,7 @@ impl PostFlopGame {
 
         // STEP 1
         let mut info = BuildTreeInfo {
-            num_storage: self.num_private_hands(node.player as usize) as u64,
+            num_storage: self.num_stored_hands(node.player as usize) as u64,
             ..Default::default()
         };
 
@@ -1427,20 +2160,82 @@ impl PostFlopGame {
         node.num_children -= 1;
 
         // STEP 3
-        node.num_elements -= self.num_private_hands(node.player as usize) as u32;
+        node.num_elements -= self.num_stored_hands(node.player as usize) as u32;
 
         Ok(info)
     }
 
+    /// Returns the sizes of the strategy, IP counterfactual value, and chance storages in bytes
+    /// when the storage of the nodes on `compression` and later streets is compressed.
+    pub(super) fn storage_bytes(&self, compression: Option<BoardState>) -> [u64; 3] {
+        if compression.is_none_or(|street| street == BoardState::Flop) {
+            let num_bytes = if compression.is_some() { 2 } else { 4 };
+            return [
+                num_bytes * self.num_storage,
+                num_bytes * self.num_storage_ip,
+                num_bytes * self.num_storage_chance,
+            ];
+        }
+
+        let street = compression.unwrap();
+        let mut ret = [0; 3];
+
+        for node in &self.node_arena {
+            let node = node.lock();
+            let num_bytes = if node.street() >= street { 2 } else { 4 };
+            if node.is_terminal() {
+                // do nothing
+            } else if node.is_chance() {
+                ret[2] += num_bytes * node.num_elements as u64;
+            } else {
+                ret[0] += num_bytes * node.num_elements as u64;
+                ret[1] += num_bytes * node.num_elements_ip as u64;
+            }
+        }
+
+        ret
+    }
+
+    /// Returns the estimated memory usage in bytes with the given compression setting.
+    pub(super) fn memory_usage_with(&self, compression: Option<BoardState>) -> u64 {
+        let [storage_bytes, storage_ip_bytes, storage_chance_bytes] =
+            self.storage_bytes(compression);
+        2 * storage_bytes + storage_ip_bytes + storage_chance_bytes + self.misc_memory_usage
+    }
+
+    /// Allocates the storage of the counterfactual values of a strategy-only game.
+    pub(super) fn allocate_cfvalue_storage(&mut self) -> Result<(), Error> {
+        let [storage_bytes, storage_ip_bytes, storage_chance_bytes] =
+            self.storage_bytes(self.compression_street());
+        let storage2_bytes = storage_bytes * if self.is_f64_regrets_enabled { 2 } else { 1 };
+
+        let num_bytes = [0, storage2_bytes, storage_ip_bytes, storage_chance_bytes];
+        let [_, storage2, storage_ip, storage_chance] = self.allocate_storages(num_bytes)?;
+        self.storage2 = storage2;
+        self.storage_ip = storage_ip;
+        self.storage_chance = storage_chance;
+
+        // the layout of the strategies does not change
+        self.allocate_memory_nodes();
+        self.is_strategy_only = false;
+        Ok(())
+    }
+
     /// Allocates memory recursively.
     fn allocate_memory_nodes(&mut self) {
-        let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
         let mut action_counter = 0;
+        let mut regret_counter = 0;
         let mut ip_counter = 0;
         let mut chance_counter = 0;
 
         for node in &self.node_arena {
             let mut node = node.lock();
+            let num_bytes = if self.is_node_compressed(&node) { 2 } else { 4 };
+            let num_bytes_regrets = if self.is_f64_regrets_enabled {
+                8
+            } else {
+                num_bytes
+            };
             if node.is_terminal() {
                 // do nothing
             } else if node.is_chance() {
@@ -1455,10 +2250,11 @@ impl PostFlopGame {
                     let ptr2 = self.storage2.as_mut_ptr();
                     let ptr3 = self.storage_ip.as_mut_ptr();
                     node.storage1 = ptr1.add(action_counter);
-                    node.storage2 = ptr2.add(action_counter);
+                    node.storage2 = ptr2.add(regret_counter);
                     node.storage3 = ptr3.add(ip_counter);
                 }
                 action_counter += num_bytes * node.num_elements as usize;
+                regret_counter += num_bytes_regrets * node.num_elements as usize;
                 ip_counter += num_bytes * node.num_elements_ip as usize;
             }
         }
@@ -0,0 +1,281 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// Simple heuristic strategy evaluated against the equilibrium by
+/// [`PostFlopGame::evaluate_baselines`].
+///
+/// The heuristics are defined by the available actions and the amounts of each decision node, so
+/// they can be mapped onto any tree. When the preferred action is not available, the player
+/// checks if possible and calls otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Baseline {
+    /// Checks whenever possible and calls when facing a bet; never bets, raises, or folds.
+    AlwaysCheck,
+
+    /// Never bets or raises. When facing a bet, calls with the hands whose equity against the
+    /// range of the opponent at the node is at least the pot odds, i.e., (amount to call) /
+    /// (pot after calling), and folds the others.
+    PotOddsCaller,
+
+    /// Bets the size closest to the given ratio of the pot with all hands whenever possible
+    /// (going all-in counts as a bet), and calls when facing a bet; never raises or folds.
+    FixedBet(f64),
+}
+
+/// Expected value of a [`Baseline`], contained in [`BaselineReport`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BaselineEv {
+    /// The name of the baseline (see [`Baseline::name`]).
+    pub name: String,
+
+    /// The expected value of the baseline against the solved strategy of the opponent.
+    pub ev: f32,
+
+    /// The expected value lost by playing the baseline instead of the solved strategy, i.e.,
+    /// `BaselineReport::equilibrium_ev - ev`. Non-negative up to the exploitability for a
+    /// converged solution.
+    pub loss: f32,
+
+    /// The expected value of the best response of the opponent against the baseline, i.e., the
+    /// maximum expected value that the opponent could achieve by exploiting the baseline.
+    pub best_response_ev: f32,
+}
+
+/// Result of [`PostFlopGame::evaluate_baselines`].
+///
+/// The expected values follow the convention of [`compute_current_ev`]: half the starting pot is
+/// subtracted.
+///
+/// [`compute_current_ev`]: crate::compute_current_ev
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BaselineReport {
+    /// The player who plays the baselines.
+    pub player: usize,
+
+    /// The expected value of the solved strategy of the player.
+    pub equilibrium_ev: f32,
+
+    /// The expected values of each baseline, in the order of the baselines.
+    pub baselines: Vec<BaselineEv>,
+}
+
+impl Baseline {
+    /// A fixed continuation bet of 33% of the pot.
+    #[inline]
+    pub fn cbet_33() -> Self {
+        Self::FixedBet(0.33)
+    }
+
+    /// Returns the always-check, pot-odds-caller, and fixed 33% bet baselines.
+    #[inline]
+    pub fn presets() -> Vec<Self> {
+        vec![Self::AlwaysCheck, Self::PotOddsCaller, Self::cbet_33()]
+    }
+
+    /// Returns the name of the baseline (e.g., `"always-check"` or `"fixed-bet-33%"`).
+    pub fn name(&self) -> String {
+        match self {
+            Self::AlwaysCheck => "always-check".to_string(),
+            Self::PotOddsCaller => "pot-odds-caller".to_string(),
+            Self::FixedBet(ratio) => format!("fixed-bet-{}%", ratio * 100.0),
+        }
+    }
+
+    fn check_params(&self) -> Result<(), Error> {
+        match *self {
+            Self::FixedBet(ratio) if !ratio.is_finite() || ratio <= 0.0 => Err(Error::Config(
+                format!("Invalid bet ratio of baseline: {ratio}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the index of the action to take with all hands, or `None` if the action depends
+    /// on the hand.
+    fn uniform_action(&self, actions: &[Action], pot: i32, amount_to_call: i32) -> Option<usize> {
+        let position = |target: Action| actions.iter().position(|&a| a == target);
+        let passive = || {
+            position(Action::Check)
+                .or(position(Action::Call))
+                .unwrap_or(0)
+        };
+
+        match *self {
+            Self::AlwaysCheck => Some(passive()),
+            Self::PotOddsCaller if amount_to_call > 0 => None,
+            Self::PotOddsCaller => Some(passive()),
+            Self::FixedBet(_) if amount_to_call > 0 => Some(passive()),
+            Self::FixedBet(ratio) => {
+                let bet_ratio = |action: Action| match action {
+                    Action::Bet(amount) | Action::AllIn(amount) => Some(amount as f64 / pot as f64),
+                    _ => None,
+                };
+                let closest = actions
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, &action)| bet_ratio(action).map(|r| (i, (r - ratio).abs())))
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                Some(closest.map_or_else(passive, |(i, _)| i))
+            }
+        }
+    }
+}
+
+impl PostFlopGame {
+    /// Computes the expected values of `player` playing each of `baselines` (see [`Baseline`])
+    /// against the solved strategy of the opponent, quantifying how much a naive strategy loses
+    /// compared to the equilibrium.
+    ///
+    /// The baselines replace all strategies of the player, including the locked ones. The
+    /// strategies are temporarily overwritten during the evaluation, which requires a copy of the
+    /// strategy storage; they are restored before returning, and the current node (and the
+    /// cached normalized weights, if any) are restored as well.
+    ///
+    /// Returns an error if `player` is invalid, the game is not solved, the storage mode is not
+    /// [`BoardState::River`], the memory is allocated lazily, or a baseline has an invalid
+    /// parameter.
+    ///
+    /// **Time complexity:** *O*(#(baselines) * #(nodes) * #(private hands)), plus the equity
+    /// calculation at each decision node facing a bet for [`Baseline::PotOddsCaller`].
+    pub fn evaluate_baselines(
+        &mut self,
+        player: usize,
+        baselines: &[Baseline],
+    ) -> Result<BaselineReport, Error> {
+        if player >= 2 {
+            return Err(Error::Config(format!("Invalid player: {player}")));
+        }
+
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        if self.is_lazy_allocation {
+            return Err(Error::Config(
+                "Lazy allocation is not supported".to_string(),
+            ));
+        }
+
+        for baseline in baselines {
+            baseline.check_params()?;
+        }
+
+        let mut report = BaselineReport {
+            player,
+            equilibrium_ev: compute_current_ev(self)[player],
+            baselines: Vec::with_capacity(baselines.len()),
+        };
+
+        let saved_history = self.history().to_vec();
+        let is_cached = self.is_normalized_weight_cached;
+        let saved_storage = self.storage1.to_vec();
+
+        for baseline in baselines {
+            let mut visited = vec![false; self.node_arena.len()];
+            self.back_to_root();
+            self.write_baseline_recursive(&mut Vec::new(), &mut visited, player, baseline);
+
+            let ev = compute_current_ev(self)[player];
+            let best_cfvalues = compute_best_cfvalues(self, player ^ 1, &|_, _| {});
+            let best_response_ev = best_cfvalues
+                .iter()
+                .zip(Game::initial_weights(self, player ^ 1))
+                .fold(0.0, |sum, (&v, &w)| sum + v as f64 * w as f64);
+
+            report.baselines.push(BaselineEv {
+                name: baseline.name(),
+                ev,
+                loss: report.equilibrium_ev - ev,
+                best_response_ev: best_response_ev as f32,
+            });
+
+            self.storage1.copy_from_slice(&saved_storage);
+        }
+
+        self.lock_decoded_cache().clear();
+        self.apply_history(&saved_history);
+        if is_cached {
+            self.cache_normalized_weights();
+        }
+
+        Ok(report)
+    }
+
+    /// Overwrites the strategies of `player` in the subtree of the current node with `baseline`.
+    fn write_baseline_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        visited: &mut [bool],
+        player: usize,
+        baseline: &Baseline,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        // isomorphic chances share the same subtree
+        let index = self.node_index(&self.node());
+        if visited[index] {
+            return;
+        }
+        visited[index] = true;
+
+        let children = if self.is_chance_node() {
+            let possible_cards = self.possible_cards();
+            (0..52)
+                .filter(|&card| possible_cards & (1 << card) != 0)
+                .collect::<Vec<_>>()
+        } else {
+            let actions = self.available_actions();
+            if self.current_player() == player {
+                self.write_baseline_strategy(&actions, player, baseline);
+            }
+            (0..actions.len()).collect()
+        };
+
+        for child in children {
+            history.push(child);
+            self.apply_history(history);
+            self.write_baseline_recursive(history, visited, player, baseline);
+            history.pop();
+        }
+    }
+
+    /// Overwrites the strategy of the current node with `baseline`.
+    fn write_baseline_strategy(&mut self, actions: &[Action], player: usize, baseline: &Baseline) {
+        let num_hands = self.num_private_hands(player);
+        let amount_to_call = self.amount_to_call();
+        let mut strategy = vec![0.0; actions.len() * num_hands];
+
+        if let Some(action) = baseline.uniform_action(actions, self.pot(), amount_to_call) {
+            strategy[action * num_hands..(action + 1) * num_hands].fill(1.0);
+        } else {
+            // pot-odds caller facing a bet
+            self.cache_normalized_weights();
+            let equity = self.equity(player);
+            let pot_odds = amount_to_call as f32 / (self.pot() + amount_to_call) as f32;
+            let fold = actions.iter().position(|&a| a == Action::Fold).unwrap_or(0);
+            let call = actions.iter().position(|&a| a == Action::Call).unwrap_or(0);
+            for (hand, &equity) in equity.iter().enumerate() {
+                let action = if equity >= pot_odds { call } else { fold };
+                strategy[action * num_hands + hand] = 1.0;
+            }
+        }
+
+        strategy.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, true);
+        });
+
+        let strategy = self.select_stored_hands(&strategy, player);
+        let mut node = self.node();
+        if self.is_node_compressed(&node) {
+            encode_unsigned_slice(node.strategy_compressed_mut(), &strategy);
+        } else {
+            node.strategy_mut().copy_from_slice(&strategy);
+        }
+    }
+}
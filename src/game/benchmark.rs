@@ -0,0 +1,171 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::solver::*;
+use std::time::Instant;
+
+/// The relative slowdown from the fastest setting tolerated by
+/// [`ThreadScalingReport::optimal_num_threads`].
+const OPTIMAL_TOLERANCE: f64 = 0.05;
+
+/// A measurement of [`PostFlopGame::benchmark_iteration`] for one number of threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ThreadScalingSample {
+    /// The number of threads.
+    pub num_threads: usize,
+
+    /// The average wall-clock time of one iteration in seconds.
+    pub seconds_per_iteration: f64,
+
+    /// The speedup relative to the first measured number of threads.
+    pub speedup: f64,
+
+    /// The speedup divided by the relative increase of the number of threads (1.0 is the ideal
+    /// linear scaling).
+    pub efficiency: f64,
+}
+
+/// The result of [`PostFlopGame::benchmark_iteration`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThreadScalingReport {
+    /// The measurements in the order of the given thread counts.
+    pub samples: Vec<ThreadScalingSample>,
+
+    /// The smallest number of threads whose iteration time is within 5% of the fastest one.
+    ///
+    /// The speedup usually saturates well before all cores are used (the memory bandwidth is the
+    /// bottleneck for large trees, and small trees do not have enough parallel work), so the
+    /// extra threads beyond this setting are better spent on other games.
+    pub optimal_num_threads: usize,
+}
+
+impl PostFlopGame {
+    /// Measures the time of a solver iteration with each number of threads in `thread_counts`
+    /// and reports the optimal setting for this game.
+    ///
+    /// For each number of threads, a dedicated rayon thread pool is built, one warm-up iteration
+    /// is run, and then the average time of `num_iterations` iterations is measured. The
+    /// parallel granularity (see [`set_parallel_grain`]) is recomputed for each thread pool. After
+    /// the measurements, the storage is reset to the freshly allocated state (the locked
+    /// strategies are kept), so the method must be called after allocating the memory and before
+    /// solving.
+    ///
+    /// Returns an error if the game is not ready, solver iterations are already performed (see
+    /// [`check_operation`]), `thread_counts` is empty or contains zero, `num_iterations` is zero,
+    /// or a thread pool cannot be built.
+    ///
+    /// [`set_parallel_grain`]: #method.set_parallel_grain
+    /// [`check_operation`]: #method.check_operation
+    pub fn benchmark_iteration(
+        &mut self,
+        thread_counts: &[usize],
+        num_iterations: u32,
+    ) -> Result<ThreadScalingReport, Error> {
+        self.check_operation(GameOperation::Benchmark)?;
+
+        if !self.is_ready() {
+            return Err(Error::Config("Game is not ready".to_string()));
+        }
+
+        if thread_counts.is_empty() {
+            return Err(Error::Config("Thread counts must not be empty".to_string()));
+        }
+
+        if thread_counts.contains(&0) {
+            return Err(Error::Config(
+                "Number of threads must be positive".to_string(),
+            ));
+        }
+
+        if num_iterations == 0 {
+            return Err(Error::Config(
+                "Number of iterations must be positive".to_string(),
+            ));
+        }
+
+        let mut samples = Vec::with_capacity(thread_counts.len());
+        let mut current_iteration = 0;
+
+        for &num_threads in thread_counts {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .map_err(|e| Error::Solver(e.to_string()))?;
+
+            let seconds = pool.install(|| {
+                self.update_parallel_nodes();
+                let game = &*self;
+
+                // warm-up
+                solve_step(game, current_iteration);
+                current_iteration += 1;
+
+                let start = Instant::now();
+                for _ in 0..num_iterations {
+                    solve_step(game, current_iteration);
+                    current_iteration += 1;
+                }
+                start.elapsed().as_secs_f64()
+            });
+
+            samples.push(ThreadScalingSample {
+                num_threads,
+                seconds_per_iteration: seconds / num_iterations as f64,
+                ..Default::default()
+            });
+        }
+
+        self.reset_storage();
+        self.update_parallel_nodes();
+
+        let base = samples[0];
+        for sample in &mut samples {
+            sample.speedup = base.seconds_per_iteration / sample.seconds_per_iteration;
+            sample.efficiency =
+                sample.speedup * base.num_threads as f64 / sample.num_threads as f64;
+        }
+
+        let fastest = samples
+            .iter()
+            .map(|sample| sample.seconds_per_iteration)
+            .fold(f64::INFINITY, f64::min);
+
+        let optimal_num_threads = samples
+            .iter()
+            .filter(|sample| sample.seconds_per_iteration <= fastest * (1.0 + OPTIMAL_TOLERANCE))
+            .map(|sample| sample.num_threads)
+            .min()
+            .unwrap();
+
+        Ok(ThreadScalingReport {
+            samples,
+            optimal_num_threads,
+        })
+    }
+
+    /// Resets the storage and the scales of the nodes to the freshly allocated state.
+    fn reset_storage(&mut self) {
+        self.storage1.fill(0);
+        self.storage2.fill(0);
+        self.storage_ip.fill(0);
+        self.storage_chance.fill(0);
+
+        if let Some(threshold) = self.lazy_threshold() {
+            self.clear_lazy_storage();
+            self.init_lazy_storage(threshold);
+        }
+
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            node.scale1 = 0.0;
+            node.scale2 = 0.0;
+            node.scale3 = 0.0;
+        }
+
+        *self.is_solving.get_mut() = false;
+        *self.num_frozen_streets.get_mut() = 0;
+        *self.num_iterations.get_mut() = 0;
+
+        self.lock_decoded_cache().clear();
+    }
+}
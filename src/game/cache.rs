@@ -0,0 +1,134 @@
+use super::*;
+use std::collections::HashMap;
+use std::sync::MutexGuard;
+
+/// Kind of the values stored in the [`DecodedCache`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum DecodedKind {
+    /// The normalized (and locked) strategy of a decision node.
+    Strategy,
+
+    /// The decoded counterfactual values of the player to act at a decision node.
+    CfValues,
+}
+
+/// Decoded values of the nodes on one street, stored contiguously.
+#[derive(Default)]
+struct CacheLayer {
+    key: Option<(Card, Card)>,
+    ranges: HashMap<(usize, DecodedKind), (usize, usize)>,
+    arena: Vec<f32>,
+}
+
+/// Cache of the decoded strategies and counterfactual values of a solved game.
+///
+/// Report queries navigate the tree with [`PostFlopGame::apply_history`], which replays the
+/// strategies from the root node, and then read the strategies and the expected values at the
+/// current node. Decoding (and normalizing) the same nodes repeatedly dominates the latency, so
+/// the decoded values are kept in a columnar arena for each street. Each street has one layer
+/// keyed by the dealt turn and river cards; navigating to a different runout only clears the
+/// layers of the changed streets, which keeps the memory usage proportional to a single line of
+/// streets.
+#[derive(Default)]
+pub(super) struct DecodedCache {
+    is_disabled: bool,
+    layers: [CacheLayer; 3],
+}
+
+impl CacheLayer {
+    #[inline]
+    fn clear(&mut self) {
+        self.ranges.clear();
+        self.arena.clear();
+    }
+}
+
+impl DecodedCache {
+    /// Removes all cached values.
+    #[inline]
+    pub(super) fn clear(&mut self) {
+        self.layers.iter_mut().for_each(CacheLayer::clear);
+    }
+
+    /// Returns the number of cached values.
+    #[inline]
+    fn len(&self) -> usize {
+        self.layers.iter().map(|layer| layer.arena.len()).sum()
+    }
+}
+
+impl PostFlopGame {
+    /// Enables or disables the cache of the decoded strategies and expected values (enabled by
+    /// default).
+    ///
+    /// The cache is used only after the game is solved, and it holds the decoded values of the
+    /// nodes on the most recently navigated line of streets. Disabling the cache frees its memory
+    /// at the cost of decoding the storage on every query.
+    pub fn set_decoded_cache_enabled(&mut self, enabled: bool) {
+        let cache = self
+            .decoded_cache
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner());
+        cache.is_disabled = !enabled;
+        cache.clear();
+    }
+
+    /// Returns the memory usage of the cache of the decoded values in bytes.
+    pub fn decoded_cache_memory_usage(&self) -> u64 {
+        (self.lock_decoded_cache().len() * std::mem::size_of::<f32>()) as u64
+    }
+
+    #[inline]
+    pub(super) fn lock_decoded_cache(&self) -> MutexGuard<DecodedCache> {
+        self.decoded_cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the values of `kind` of `node`, decoding them with `decode` if they are not
+    /// cached. `node` must be an element of `node_arena`.
+    pub(super) fn cached_decode<F>(
+        &self,
+        node: &PostFlopNode,
+        kind: DecodedKind,
+        decode: F,
+    ) -> Vec<f32>
+    where
+        F: FnOnce() -> Vec<f32>,
+    {
+        if self.state != State::Solved {
+            return decode();
+        }
+
+        let mut cache = self.lock_decoded_cache();
+        if cache.is_disabled {
+            return decode();
+        }
+
+        let street = if node.river != NOT_DEALT {
+            2
+        } else if node.turn != NOT_DEALT {
+            1
+        } else {
+            0
+        };
+
+        let layer = &mut cache.layers[street];
+        let key = Some((node.turn, node.river));
+        if layer.key != key {
+            layer.clear();
+            layer.key = key;
+        }
+
+        let index = self.node_index(node);
+        if let Some(&(start, end)) = layer.ranges.get(&(index, kind)) {
+            return layer.arena[start..end].to_vec();
+        }
+
+        let ret = decode();
+        let start = layer.arena.len();
+        layer.arena.extend_from_slice(&ret);
+        layer
+            .ranges
+            .insert((index, kind), (start, layer.arena.len()));
+        ret
+    }
+}
@@ -0,0 +1,159 @@
+use super::*;
+use crate::bet_size::*;
+use crate::error::*;
+use crate::range::*;
+
+/// A small named configuration shipped as a reproducible baseline.
+///
+/// The canonical spots are small enough to be solved in seconds, and the tests of the crate
+/// record their exploitability and expected values after a fixed number of iterations. Solving
+/// them with the same number of iterations gives a baseline to compare the performance and the
+/// results across versions, hardware, and feature flags (e.g., `simd` or compression).
+///
+/// The amounts are in chips with 1 big blind = 10 chips.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let spot = CanonicalSpot::by_name("SRP Q9572 river 50bb").unwrap();
+/// let mut game = spot.build().unwrap();
+/// game.allocate_memory(false);
+/// # #[cfg(feature = "solver")]
+/// solve(&mut game, 10, 0.0, false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CanonicalSpot {
+    /// The name of the spot (e.g., `"SRP K72r 20bb"`).
+    pub name: String,
+
+    /// The card configuration.
+    pub card_config: CardConfig,
+
+    /// The tree configuration.
+    pub tree_config: TreeConfig,
+}
+
+impl CanonicalSpot {
+    /// A single-raised pot (button vs. big blind) on `Ks7d2c` with 20bb effective stacks. The
+    /// stack-to-pot ratio is about 3, and each street has a single bet size.
+    pub fn srp_k72r_20bb() -> Self {
+        Self::new(
+            "SRP K72r 20bb",
+            [
+                "88-22,A5s-A2s,KJs-K9s,KJo,QTs+,JTs,T9s,98s,87s,76s",
+                "AA-33,A8s+,AQo+,KTs+,KQo,QJs,JTs",
+            ],
+            "Ks7d2c",
+            [NOT_DEALT, NOT_DEALT],
+            55,
+            175,
+            ["33%", "66%", "75%"],
+            "",
+        )
+    }
+
+    /// A 3-bet pot (small blind vs. button) on the two-tone `Ah8h4d` with 100bb effective stacks,
+    /// solved with a minimal tree: a single bet size per street and no raises.
+    pub fn three_bet_a84tt_100bb_mini() -> Self {
+        Self::new(
+            "3BP A84tt 100bb-mini",
+            [
+                "QQ+,AKs,AKo,A5s-A4s,KQs",
+                "JJ-88,AQs-ATs,AQo,KQs,KJs,QJs,JTs,T9s",
+            ],
+            "Ah8h4d",
+            [NOT_DEALT, NOT_DEALT],
+            190,
+            910,
+            ["33%", "75%", "100%"],
+            "",
+        )
+    }
+
+    /// A single-raised pot on the river of `Qs9h5d7c2s` with 50bb effective stacks, with a
+    /// polarized betting range of the in-position player against the capped range of the
+    /// out-of-position player.
+    pub fn srp_q9572_river_50bb() -> Self {
+        Self::new(
+            "SRP Q9572 river 50bb",
+            [
+                "QJ,QT,99,55,T8s,JTs,A9s",
+                "QQ,99,77,55,AQ,KQ,86s,T8s,AKs,AJs,KJs",
+            ],
+            "Qs9h5d",
+            [card_from_str("7c").unwrap(), card_from_str("2s").unwrap()],
+            100,
+            450,
+            ["50%", "50%", "50%, 125%"],
+            "3x",
+        )
+    }
+
+    /// Returns all canonical spots.
+    #[inline]
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::srp_k72r_20bb(),
+            Self::three_bet_a84tt_100bb_mini(),
+            Self::srp_q9572_river_50bb(),
+        ]
+    }
+
+    /// Returns the canonical spot of the given name, or `None` if not found.
+    #[inline]
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|spot| spot.name == name)
+    }
+
+    /// Builds the game of the spot. The memory is not allocated.
+    pub fn build(&self) -> Result<PostFlopGame, Error> {
+        let action_tree = ActionTree::new(self.tree_config.clone())?;
+        PostFlopGame::with_config(self.card_config.clone(), action_tree)
+    }
+
+    /// Creates a spot in which both players use the same bet sizes; `bet_sizes` are those of the
+    /// flop, the turn, and the river.
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        name: &str,
+        ranges: [&str; 2],
+        flop: &str,
+        [turn, river]: [Card; 2],
+        starting_pot: i32,
+        effective_stack: i32,
+        bet_sizes: [&str; 3],
+        raise_sizes: &str,
+    ) -> Self {
+        let sizes = |bet: &str| {
+            let sizes = BetSizeOptions::try_from((bet, raise_sizes)).unwrap();
+            [sizes.clone(), sizes]
+        };
+
+        let initial_state = match (turn, river) {
+            (NOT_DEALT, _) => BoardState::Flop,
+            (_, NOT_DEALT) => BoardState::Turn,
+            _ => BoardState::River,
+        };
+
+        Self {
+            name: name.to_string(),
+            card_config: CardConfig {
+                range: ranges.map(|range| range.parse().unwrap()),
+                flop: flop_from_str(flop).unwrap(),
+                turn,
+                river,
+                ..Default::default()
+            },
+            tree_config: TreeConfig {
+                initial_state,
+                starting_pot,
+                effective_stack,
+                flop_bet_sizes: sizes(bet_sizes[0]),
+                turn_bet_sizes: sizes(bet_sizes[1]),
+                river_bet_sizes: sizes(bet_sizes[2]),
+                ..Default::default()
+            },
+        }
+    }
+}
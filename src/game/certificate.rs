@@ -0,0 +1,344 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::utility::*;
+use std::sync::Mutex;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+/// Best response action of each hand at a decision node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct BestResponseEntry {
+    /// The index of the node in the game tree.
+    pub node_index: u32,
+
+    /// The player who takes the best response at the node.
+    pub player: u8,
+
+    /// The index of the best response action of each hand, in the same order as
+    /// [`PostFlopGame::private_cards`]. `u8::MAX` indicates that the hand is locked by
+    /// node-locking and follows the locked strategy.
+    pub actions: Vec<u8>,
+}
+
+/// Certificate of the exploitability of a solved game.
+///
+/// The certificate records the result of the final best-response computation: the
+/// counterfactual values of the best response of each player, the pure best response strategies
+/// that achieve them, and a digest of the average strategies they were computed against. It can
+/// be exported alongside a shared solve file so that third parties can confirm the claimed
+/// exploitability with [`verify`].
+///
+/// The expected values exclude the bias, i.e., (starting pot) / 2, as with [`compute_mes_ev`].
+///
+/// [`verify`]: #method.verify
+/// [`compute_mes_ev`]: crate::compute_mes_ev
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct ExploitabilityCertificate {
+    /// FNV-1a digest of the average strategies of all decision nodes.
+    pub strategy_digest: u64,
+
+    /// The number of nodes in the game tree for each street.
+    pub num_nodes: [u64; 3],
+
+    /// The private hands of each player.
+    pub private_cards: [Vec<(Card, Card)>; 2],
+
+    /// The expected values of the average strategies.
+    pub current_ev: [f32; 2],
+
+    /// The expected values of the best responses.
+    pub best_response_ev: [f32; 2],
+
+    /// The counterfactual values of the best response of each hand at the root node.
+    pub best_response_cfvalues: [Vec<f32>; 2],
+
+    /// The exploitability of the average strategies.
+    pub exploitability: f32,
+
+    /// The best response strategies, sorted by node index.
+    pub best_response: Vec<BestResponseEntry>,
+}
+
+/// Relative tolerance used by [`ExploitabilityCertificate::verify`]: with respect to the starting
+/// pot for the values, and with respect to the best value of each hand for the best response
+/// actions.
+const VERIFY_TOLERANCE: f32 = 1e-5;
+
+/// Computes the exploitability certificate of a solved game.
+///
+/// Returns an error if the game is not solved or the storage mode is not
+/// [`BoardState::River`].
+pub fn compute_exploitability_certificate(
+    game: &PostFlopGame,
+) -> Result<ExploitabilityCertificate, Error> {
+    // the recursion may run in parallel, so a real mutex is needed here
+    let entries = Mutex::new(Vec::new());
+    let record = |node: &PostFlopNode, cfv_actions: &[f32]| {
+        let entry = best_response_entry(game, node, cfv_actions);
+        entries.lock().unwrap().push(entry);
+    };
+
+    let mut certificate = compute_certificate_with_record(game, &record)?;
+    certificate.best_response = entries.into_inner().unwrap();
+    certificate
+        .best_response
+        .sort_unstable_by_key(|entry| entry.node_index);
+
+    Ok(certificate)
+}
+
+/// Computes the exploitability certificate without the best response strategies, calling
+/// `record` at every decision node of the best responding player with the counterfactual values
+/// of each action.
+fn compute_certificate_with_record<R: Fn(&PostFlopNode, &[f32]) + Sync>(
+    game: &PostFlopGame,
+    record: &R,
+) -> Result<ExploitabilityCertificate, Error> {
+    game.check_operation(GameOperation::Analyze)?;
+
+    if game.storage_mode != BoardState::River {
+        return Err(Error::Config("Storage mode must be river".to_string()));
+    }
+
+    let best_response_cfvalues = [
+        compute_best_cfvalues(game, 0, record),
+        compute_best_cfvalues(game, 1, record),
+    ];
+
+    // the values are of the stored hands (see `Game::stored_hand_indices`)
+    let get_sum = |player: usize| {
+        let weights = Game::initial_weights(game, player);
+        let cfvalues = &best_response_cfvalues[player];
+        Summation::Compensated.weighted_sum(cfvalues, weights) as f32
+    };
+
+    let best_response_ev = [get_sum(0), get_sum(1)];
+    let [oop_cfvalues, ip_cfvalues] = best_response_cfvalues;
+    let oop_cfvalues = game.expand_stored_hands(oop_cfvalues, 0);
+    let ip_cfvalues = game.expand_stored_hands(ip_cfvalues, 1);
+    let current_ev = compute_current_ev(game);
+    let exploitability = if game.is_raked() {
+        ((best_response_ev[0] - current_ev[0]) + (best_response_ev[1] - current_ev[1])) * 0.5
+    } else {
+        (best_response_ev[0] + best_response_ev[1]) * 0.5
+    };
+
+    Ok(ExploitabilityCertificate {
+        strategy_digest: strategy_digest(game),
+        num_nodes: game.num_nodes,
+        private_cards: game.private_cards.clone(),
+        current_ev,
+        best_response_ev,
+        best_response_cfvalues: [oop_cfvalues, ip_cfvalues],
+        exploitability,
+        best_response: Vec::new(),
+    })
+}
+
+impl ExploitabilityCertificate {
+    /// Verifies the certificate against the given game.
+    ///
+    /// The game must be the solve that the certificate was computed from: the tree shape, the
+    /// private hands, and the strategy digest must match exactly. The best response is then
+    /// recomputed independently: the expected values, the exploitability, and the counterfactual
+    /// values at the root node are compared with a tolerance proportional to the starting pot,
+    /// and each best response action must achieve the recomputed best value of the hand within a
+    /// relative tolerance (so that ties may be broken differently).
+    pub fn verify(&self, game: &PostFlopGame) -> Result<(), Error> {
+        if game.num_nodes != self.num_nodes {
+            return Err(Error::Verification("Number of nodes mismatch".to_string()));
+        }
+
+        if game.private_cards != self.private_cards {
+            return Err(Error::Verification("Private cards mismatch".to_string()));
+        }
+
+        // the recursion may run in parallel, so a real mutex is needed here
+        let num_entries = Mutex::new(0);
+        let first_error = Mutex::new(None);
+        let record = |node: &PostFlopNode, cfv_actions: &[f32]| {
+            *num_entries.lock().unwrap() += 1;
+            if let Err(e) = self.check_best_response(game, node, cfv_actions) {
+                first_error.lock().unwrap().get_or_insert(e);
+            }
+        };
+
+        let actual = compute_certificate_with_record(game, &record)?;
+
+        if actual.strategy_digest != self.strategy_digest {
+            return Err(Error::Verification(format!(
+                "Strategy digest mismatch: expected = {:#018x}, actual = {:#018x}",
+                self.strategy_digest, actual.strategy_digest
+            )));
+        }
+
+        let num_entries = num_entries.into_inner().unwrap();
+        if num_entries != self.best_response.len() {
+            return Err(Error::Verification(format!(
+                "Number of best response entries mismatch: expected = {}, actual = {}",
+                self.best_response.len(),
+                num_entries
+            )));
+        }
+
+        if let Some(e) = first_error.into_inner().unwrap() {
+            return Err(e);
+        }
+
+        let tolerance = VERIFY_TOLERANCE * game.tree_config.starting_pot as f32;
+        let check = |name: &str, expected: f32, actual: f32| {
+            if (expected - actual).abs() <= tolerance {
+                Ok(())
+            } else {
+                Err(Error::Verification(format!(
+                    "{name} mismatch: expected = {expected}, actual = {actual}"
+                )))
+            }
+        };
+
+        for player in 0..2 {
+            let expected = &self.best_response_cfvalues[player];
+            let actual = &actual.best_response_cfvalues[player];
+            if expected.len() != actual.len() {
+                return Err(Error::Verification(
+                    "Best response counterfactual values length mismatch".to_string(),
+                ));
+            }
+            for (&expected, &actual) in expected.iter().zip(actual) {
+                check("Best response counterfactual value", expected, actual)?;
+            }
+        }
+
+        for player in 0..2 {
+            check(
+                "Current EV",
+                self.current_ev[player],
+                actual.current_ev[player],
+            )?;
+            check(
+                "Best response EV",
+                self.best_response_ev[player],
+                actual.best_response_ev[player],
+            )?;
+        }
+
+        check("Exploitability", self.exploitability, actual.exploitability)
+    }
+
+    /// Checks the best response entry of `node` against the recomputed counterfactual values of
+    /// each action.
+    fn check_best_response(
+        &self,
+        game: &PostFlopGame,
+        node: &PostFlopNode,
+        cfv_actions: &[f32],
+    ) -> Result<(), Error> {
+        let node_index = game.node_index(node) as u32;
+        let mismatch =
+            || Error::Verification(format!("Best response mismatch at node {node_index}"));
+
+        let entry = self
+            .best_response
+            .binary_search_by_key(&node_index, |entry| entry.node_index)
+            .map(|i| &self.best_response[i])
+            .map_err(|_| {
+                Error::Verification(format!("Best response of node {node_index} is missing"))
+            })?;
+
+        let expected = best_response_entry(game, node, cfv_actions);
+        if entry.player != expected.player || entry.actions.len() != expected.actions.len() {
+            return Err(mismatch());
+        }
+
+        let num_actions = node.num_actions();
+        let num_stored_hands = cfv_actions.len() / num_actions;
+        let stored_indices = game.stored_hand_indices(node.player());
+        for (hand, (&stored, &best)) in entry.actions.iter().zip(&expected.actions).enumerate() {
+            if stored == best {
+                continue;
+            }
+
+            // locked hands must match exactly
+            if stored == u8::MAX || best == u8::MAX || stored as usize >= num_actions {
+                return Err(mismatch());
+            }
+
+            let index = stored_indices
+                .get(hand)
+                .map_or(hand, |&index| index as usize);
+            let stored_value = cfv_actions[stored as usize * num_stored_hands + index];
+            let best_value = cfv_actions[best as usize * num_stored_hands + index];
+            if best_value - stored_value > VERIFY_TOLERANCE * best_value.abs() {
+                return Err(mismatch());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates the best response entry of `node` from the counterfactual values of each action of
+/// the stored hands (see [`Game::stored_hand_indices`]).
+fn best_response_entry(
+    game: &PostFlopGame,
+    node: &PostFlopNode,
+    cfv_actions: &[f32],
+) -> BestResponseEntry {
+    let num_hands = cfv_actions.len() / node.num_actions();
+    let locking = game.locking_strategy(node);
+
+    let actions = (0..num_hands)
+        .map(|hand| {
+            if !locking.is_empty() && locking[hand].is_sign_positive() {
+                return u8::MAX;
+            }
+
+            let mut best_action = 0;
+            for action in 1..node.num_actions() {
+                if cfv_actions[action * num_hands + hand]
+                    > cfv_actions[best_action * num_hands + hand]
+                {
+                    best_action = action;
+                }
+            }
+            best_action as u8
+        })
+        .collect();
+
+    BestResponseEntry {
+        node_index: game.node_index(node) as u32,
+        player: node.player() as u8,
+        actions: game.expand_stored_hands(actions, node.player()),
+    }
+}
+
+/// Computes the FNV-1a digest of the average strategies of all decision nodes.
+fn strategy_digest(game: &PostFlopGame) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut write = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash = (hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    };
+
+    for (index, node) in game.node_arena.iter().enumerate() {
+        let node = node.lock();
+        if node.is_terminal() || node.is_chance() || node.num_elements == 0 {
+            continue;
+        }
+
+        write(&(index as u32).to_le_bytes());
+        for prob in game.node_strategy(&node) {
+            write(&prob.to_bits().to_le_bytes());
+        }
+    }
+
+    hash
+}
@@ -0,0 +1,174 @@
+use super::*;
+use crate::error::*;
+
+/// Value/bluff composition of a river bet, returned by [`PostFlopGame::bet_compositions`].
+///
+/// The numbers of combinations are weighted by the reach probabilities, taking card removal into
+/// account (see [`PostFlopGame::normalized_weights`]). A betting hand is regarded as value if
+/// its equity against the opponent's calling range is more than 50% and as a bluff if it is
+/// less than 50%. Hands with exactly 50% equity and hands that no calling combination is
+/// compatible with are counted as neither.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BetComposition {
+    /// The bet, raise, or all-in action.
+    pub action: Action,
+
+    /// The amount of chips that the bettor puts into the pot with the action.
+    pub bet_amount: i32,
+
+    /// The pot before the action, including the bet that has not been called yet.
+    pub pot: i32,
+
+    /// The number of combinations taking the action.
+    pub bet_combos: f64,
+
+    /// The number of value combinations in the betting range.
+    pub value_combos: f64,
+
+    /// The number of bluff combinations in the betting range.
+    pub bluff_combos: f64,
+
+    /// The equity of each private hand of the bettor against the calling range. Zero if the hand
+    /// does not take the action or no calling combination is compatible with it.
+    pub equity: Vec<f32>,
+
+    /// The number of combinations with which the opponent calls the action.
+    pub call_combos: f64,
+
+    /// The frequency with which the opponent continues (calls or raises) against the action.
+    pub defense_frequency: f64,
+
+    /// The minimum defense frequency, i.e., `pot / (pot + bet_amount)`. Defending less often
+    /// lets the bettor profit from betting any two cards.
+    pub minimum_defense_frequency: f64,
+
+    /// The fraction of bluffs in the betting range that makes the opponent indifferent to
+    /// calling, i.e., `amount to call / (pot after the action + amount to call)`.
+    pub indifference_bluff_ratio: f64,
+}
+
+impl BetComposition {
+    /// Returns the fraction of value combinations among the value and bluff combinations, or
+    /// `None` if both are zero.
+    #[inline]
+    pub fn value_ratio(&self) -> Option<f64> {
+        let total = self.value_combos + self.bluff_combos;
+        (total > 0.0).then(|| self.value_combos / total)
+    }
+}
+
+impl PostFlopGame {
+    /// Computes the value/bluff composition of each bet, raise, and all-in action at the current
+    /// node against the opponent's calling range. See [`BetComposition`] for the conventions.
+    ///
+    /// Returns an error if the game is not solved or the current node is not a decision node on
+    /// the river. The current node is restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(actions) * (#(OOP private hands) + #(IP private hands))).
+    pub fn bet_compositions(&mut self) -> Result<Vec<BetComposition>, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree(
+                "Current node is not a decision node".to_string(),
+            ));
+        }
+
+        if self.current_street() != BoardState::River {
+            return Err(Error::Tree("Current node is not on the river".to_string()));
+        }
+
+        let saved_history = self.history().to_vec();
+        let player = self.current_player();
+        let opponent = player ^ 1;
+        let pot = self.pot();
+        let committed = self.committed_amount(player);
+
+        let mut ret = Vec::new();
+
+        for (i, action) in self.available_actions().into_iter().enumerate() {
+            if !matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_)) {
+                continue;
+            }
+
+            let mut history = saved_history.clone();
+            history.push(i);
+            self.apply_history(&history);
+
+            let bet_amount = self.committed_amount(player) - committed;
+            let amount_to_call = self.amount_to_call();
+            let pot_after = self.pot();
+
+            self.cache_normalized_weights();
+            let bet_weights = self.normalized_weights(player).to_vec();
+            let facing_weights = self.normalized_weights(opponent).to_vec();
+            let strategy = self.strategy();
+            let opponent_actions = self.available_actions();
+            let num_opponent_hands = facing_weights.len();
+
+            let mut call = 0;
+            let mut defense_combos = 0.0;
+            for (j, (action, row)) in opponent_actions
+                .iter()
+                .zip(strategy.chunks_exact(num_opponent_hands))
+                .enumerate()
+            {
+                if *action == Action::Fold {
+                    continue;
+                }
+                if *action == Action::Call {
+                    call = j;
+                }
+                defense_combos += row
+                    .iter()
+                    .zip(&facing_weights)
+                    .fold(0.0, |sum, (&s, &w)| sum + s as f64 * w as f64);
+            }
+
+            // showdown against the calling range
+            history.push(call);
+            self.apply_history(&history);
+            self.cache_normalized_weights();
+            let showdown_weights = self.normalized_weights(player);
+            let equity = self.equity(player);
+            let call_combos = self
+                .normalized_weights(opponent)
+                .iter()
+                .fold(0.0, |sum, &w| sum + w as f64);
+
+            let mut composition = BetComposition {
+                action,
+                bet_amount,
+                pot,
+                minimum_defense_frequency: pot as f64 / (pot + bet_amount) as f64,
+                indifference_bluff_ratio: amount_to_call as f64
+                    / (pot_after + amount_to_call) as f64,
+                call_combos,
+                ..Default::default()
+            };
+
+            for ((&w, &w_showdown), &e) in bet_weights.iter().zip(showdown_weights).zip(&equity) {
+                let w = w as f64;
+                composition.bet_combos += w;
+                if w_showdown > 0.0 {
+                    if e > 0.5 {
+                        composition.value_combos += w;
+                    } else if e < 0.5 {
+                        composition.bluff_combos += w;
+                    }
+                }
+            }
+
+            let facing_combos = facing_weights.iter().fold(0.0, |sum, &w| sum + w as f64);
+            if facing_combos > 0.0 {
+                composition.defense_frequency = defense_combos / facing_combos;
+            }
+
+            composition.equity = equity;
+            ret.push(composition);
+        }
+
+        self.apply_history(&saved_history);
+        Ok(ret)
+    }
+}
@@ -0,0 +1,317 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::sliceop::*;
+
+/// Distance between the strategies of two games at a single decision node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDistance {
+    /// The history leading to the node, in the format accepted by
+    /// [`PostFlopGame::apply_history`].
+    ///
+    /// For isomorphic chance events, the representative card is recorded.
+    pub history: Vec<usize>,
+
+    /// The street of the node.
+    pub street: BoardState,
+
+    /// The player to act at the node.
+    pub player: usize,
+
+    /// The probability that the acting player reaches the node in the first game, normalized so
+    /// that the root has a reach of 1.
+    pub reach: f64,
+
+    /// The reach-weighted average of the per-hand KL divergence `KL(first || second)`.
+    pub kl_divergence: f64,
+
+    /// The reach-weighted average of the per-hand L2 distance between the strategies.
+    pub l2_distance: f64,
+}
+
+/// Aggregated distance over a set of decision nodes.
+///
+/// The averages are weighted by the reach of each node.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DistanceSummary {
+    /// The number of aggregated nodes.
+    pub num_nodes: usize,
+
+    /// The sum of the reaches of the aggregated nodes.
+    pub reach: f64,
+
+    /// The reach-weighted average of the KL divergence.
+    pub kl_divergence: f64,
+
+    /// The reach-weighted average of the L2 distance.
+    pub l2_distance: f64,
+}
+
+/// Result of [`compute_strategy_distance`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StrategyDistance {
+    /// The distance of each decision node in depth-first order.
+    pub nodes: Vec<NodeDistance>,
+
+    /// The summaries of the flop, turn, and river nodes, respectively.
+    pub streets: [DistanceSummary; 3],
+
+    /// The summary of all decision nodes.
+    pub total: DistanceSummary,
+}
+
+impl DistanceSummary {
+    #[inline]
+    fn from_nodes<'a>(nodes: impl Iterator<Item = &'a NodeDistance>) -> Self {
+        let mut ret = Self::default();
+        for node in nodes {
+            ret.num_nodes += 1;
+            ret.reach += node.reach;
+            ret.kl_divergence += node.reach * node.kl_divergence;
+            ret.l2_distance += node.reach * node.l2_distance;
+        }
+        if ret.reach > 0.0 {
+            ret.kl_divergence /= ret.reach;
+            ret.l2_distance /= ret.reach;
+        }
+        ret
+    }
+}
+
+impl StrategyDistance {
+    /// Returns the summary of the decision nodes in the subtree specified by `history`.
+    ///
+    /// The nodes whose history starts with `history` are aggregated, so the empty history
+    /// returns the same summary as [`total`].
+    ///
+    /// [`total`]: #structfield.total
+    #[inline]
+    pub fn line(&self, history: &[usize]) -> DistanceSummary {
+        DistanceSummary::from_nodes(
+            self.nodes
+                .iter()
+                .filter(|node| node.history.starts_with(history)),
+        )
+    }
+}
+
+/// Compares the strategies of two games node by node.
+///
+/// Both games must have the same game tree and the same private hands, e.g., two solves of the
+/// same configuration with different solver parameters or locked nodes. To compare the games
+/// with different bet sizes, remove the differing lines with [`PostFlopGame::remove_lines`]
+/// beforehand so that the trees become identical.
+///
+/// For each decision node, the per-hand distances are weighted by the reach of the acting player
+/// in the first game. Chance nodes deal each representative card with its probability including
+/// the eliminated isomorphic cards. Nodes beyond the storage mode of either game are skipped.
+pub fn compute_strategy_distance(
+    game1: &PostFlopGame,
+    game2: &PostFlopGame,
+) -> Result<StrategyDistance, Error> {
+    game1.check_operation(GameOperation::QueryStrategy)?;
+    game2.check_operation(GameOperation::QueryStrategy)?;
+
+    if game1.num_nodes != game2.num_nodes {
+        return Err(Error::Config("Game trees do not match".to_string()));
+    }
+
+    if game1.private_cards != game2.private_cards {
+        return Err(Error::Config("Private hands do not match".to_string()));
+    }
+
+    let storage_mode = game1.storage_mode.min(game2.storage_mode);
+    let reach = game1.initial_weights.clone();
+    let root_reach = [
+        reach[0].iter().fold(0.0, |acc, &w| acc + w as f64),
+        reach[1].iter().fold(0.0, |acc, &w| acc + w as f64),
+    ];
+
+    let mut nodes = Vec::new();
+    let mut history = Vec::new();
+
+    distance_recursive(
+        &mut nodes,
+        &mut history,
+        game1,
+        game2,
+        &game1.node_arena[0].lock(),
+        &game2.node_arena[0].lock(),
+        &reach,
+        &root_reach,
+        1.0,
+        storage_mode,
+    )?;
+
+    let mut streets = [DistanceSummary::default(); 3];
+    for (i, street) in [BoardState::Flop, BoardState::Turn, BoardState::River]
+        .iter()
+        .enumerate()
+    {
+        streets[i] = DistanceSummary::from_nodes(nodes.iter().filter(|n| n.street == *street));
+    }
+
+    let total = DistanceSummary::from_nodes(nodes.iter());
+
+    Ok(StrategyDistance {
+        nodes,
+        streets,
+        total,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn distance_recursive(
+    result: &mut Vec<NodeDistance>,
+    history: &mut Vec<usize>,
+    game1: &PostFlopGame,
+    game2: &PostFlopGame,
+    node1: &PostFlopNode,
+    node2: &PostFlopNode,
+    reach: &[Vec<f32>; 2],
+    root_reach: &[f64; 2],
+    chance_reach: f64,
+    storage_mode: BoardState,
+) -> Result<(), Error> {
+    if node1.player != node2.player
+        || node1.num_children != node2.num_children
+        || node1.prev_action != node2.prev_action
+    {
+        return Err(Error::Config(format!(
+            "Game trees do not match at history {history:?}"
+        )));
+    }
+
+    if node1.is_terminal() {
+        return Ok(());
+    }
+
+    if node1.is_chance() {
+        let is_turn = node1.turn == NOT_DEALT;
+        if storage_mode == BoardState::Flop || (!is_turn && storage_mode == BoardState::Turn) {
+            return Ok(());
+        }
+
+        let isomorphism = game1.isomorphic_chances(node1);
+        let num_cards = node1.num_actions() + isomorphism.len();
+
+        for action in 0..node1.num_actions() {
+            let child1 = node1.play(action);
+            let child2 = node2.play(action);
+            let card = match child1.prev_action {
+                Action::Chance(card) => card,
+                _ => unreachable!(),
+            };
+
+            let multiplicity = 1 + isomorphism
+                .iter()
+                .filter(|&&i| i as usize == action)
+                .count();
+
+            let mut child_reach = reach.clone();
+            for (weights, cards) in child_reach.iter_mut().zip(&game1.private_cards) {
+                for (w, &(c1, c2)) in weights.iter_mut().zip(cards) {
+                    if c1 == card || c2 == card {
+                        *w = 0.0;
+                    }
+                }
+            }
+
+            history.push(card as usize);
+            distance_recursive(
+                result,
+                history,
+                game1,
+                game2,
+                &child1,
+                &child2,
+                &child_reach,
+                root_reach,
+                chance_reach * multiplicity as f64 / num_cards as f64,
+                storage_mode,
+            )?;
+            history.pop();
+        }
+
+        return Ok(());
+    }
+
+    let player = node1.player();
+    let num_hands = game1.num_private_hands(player);
+    let strategy1 = game1.node_strategy(node1);
+    let strategy2 = game2.node_strategy(node2);
+
+    let mut sum_reach = 0.0;
+    let mut sum_kl = 0.0;
+    let mut sum_l2 = 0.0;
+
+    for hand in 0..num_hands {
+        let w = reach[player][hand] as f64;
+        if w <= 0.0 {
+            continue;
+        }
+
+        let mut kl = 0.0;
+        let mut l2 = 0.0;
+        for action in 0..node1.num_actions() {
+            let p = strategy1[action * num_hands + hand] as f64;
+            let q = strategy2[action * num_hands + hand] as f64;
+            if p > 0.0 {
+                kl += p * (p / q.max(KL_EPSILON)).ln();
+            }
+            l2 += (p - q) * (p - q);
+        }
+
+        sum_reach += w;
+        sum_kl += w * kl.max(0.0);
+        sum_l2 += w * l2.sqrt();
+    }
+
+    let street = if node1.river != NOT_DEALT {
+        BoardState::River
+    } else if node1.turn != NOT_DEALT {
+        BoardState::Turn
+    } else {
+        BoardState::Flop
+    };
+
+    let (kl_divergence, l2_distance) = if sum_reach > 0.0 {
+        (sum_kl / sum_reach, sum_l2 / sum_reach)
+    } else {
+        (0.0, 0.0)
+    };
+
+    result.push(NodeDistance {
+        history: history.clone(),
+        street,
+        player,
+        reach: chance_reach * sum_reach / root_reach[player],
+        kl_divergence,
+        l2_distance,
+    });
+
+    for action in 0..node1.num_actions() {
+        let mut child_reach = reach.clone();
+        mul_slice(&mut child_reach[player], row(&strategy1, action, num_hands));
+
+        history.push(action);
+        distance_recursive(
+            result,
+            history,
+            game1,
+            game2,
+            &node1.play(action),
+            &node2.play(action),
+            &child_reach,
+            root_reach,
+            chance_reach,
+            storage_mode,
+        )?;
+        history.pop();
+    }
+
+    Ok(())
+}
+
+/// Lower bound of the probability of the second strategy used in the KL divergence.
+const KL_EPSILON: f64 = 1e-9;
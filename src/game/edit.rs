@@ -0,0 +1,182 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::utility::*;
+
+/// A manual edit of the strategy of a decision node, applied by
+/// [`PostFlopGame::edit_strategies`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StrategyEdit {
+    /// The history of the edited node (see [`PostFlopGame::apply_history`]).
+    pub history: Vec<usize>,
+
+    /// The new strategy of the player to act, with the same layout as
+    /// [`PostFlopGame::strategy`].
+    ///
+    /// The frequencies of each hand are normalized so that their sum is 1.0. A hand whose
+    /// frequencies are all zero keeps its current strategy, so a subset of the hands can be
+    /// edited.
+    pub strategy: Vec<f32>,
+}
+
+impl PostFlopGame {
+    /// Overwrites the strategy of the node specified by `history` of a solved game.
+    ///
+    /// This is a shorthand of [`edit_strategies`] with a single edit; see it for the details.
+    ///
+    /// [`edit_strategies`]: #method.edit_strategies
+    #[inline]
+    pub fn edit_strategy(&mut self, history: &[usize], strategy: &[f32]) -> Result<(), Error> {
+        self.edit_strategies(&[StrategyEdit {
+            history: history.to_vec(),
+            strategy: strategy.to_vec(),
+        }])
+    }
+
+    /// Overwrites the strategies of multiple nodes of a solved game (see [`StrategyEdit`]),
+    /// supporting "human tweak, machine evaluate" loops without re-running the solver.
+    ///
+    /// The edits are validated before any strategy is modified, so either all or none of them
+    /// are applied. The nodes of the isomorphic runouts share their strategies, so an edit also
+    /// applies to the suit-isomorphic counterparts of the node. The current node is preserved.
+    ///
+    /// The expected values are outdated after editing: [`is_current_node_finalized`] returns
+    /// `false` and the game cannot be saved with the expected values until
+    /// [`recompute_evs_and_exploitability`] (or [`recompute_evs`]) is called, which evaluates all
+    /// the edits in a single pass. The strategies can still be queried as usual.
+    ///
+    /// Returns an error if the game is not solved, the storage mode is not [`BoardState::River`],
+    /// a specified node is not a decision node, is locked, or is not allocated, or a strategy has an
+    /// invalid length, a negative or non-finite frequency, or different frequencies for symmetric
+    /// hands of a suit-reduced game (see [`is_suit_reduced`]). Panics if a history contains an
+    /// invalid action.
+    ///
+    /// [`is_current_node_finalized`]: #method.is_current_node_finalized
+    /// [`is_suit_reduced`]: #method.is_suit_reduced
+    /// [`recompute_evs_and_exploitability`]: #method.recompute_evs_and_exploitability
+    /// [`recompute_evs`]: #method.recompute_evs
+    pub fn edit_strategies(&mut self, edits: &[StrategyEdit]) -> Result<(), Error> {
+        self.check_operation(GameOperation::EditStrategy)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        let saved_history = self.history().to_vec();
+        let is_cached = self.is_normalized_weight_cached;
+
+        let prepared = edits
+            .iter()
+            .map(|edit| {
+                self.apply_history(&edit.history);
+                self.prepare_strategy_edit(edit)
+            })
+            .collect::<Result<Vec<_>, _>>();
+
+        if let Ok(prepared) = &prepared {
+            for (index, strategy) in prepared {
+                let mut node = self.node_arena[*index].lock();
+                if self.is_node_compressed(&node) {
+                    let scale = encode_unsigned_slice(node.strategy_compressed_mut(), strategy);
+                    node.set_strategy_scale(scale);
+                } else {
+                    node.strategy_mut().copy_from_slice(strategy);
+                }
+            }
+
+            if !prepared.is_empty() {
+                self.has_pending_strategy_edits = true;
+                self.lock_decoded_cache().clear();
+            }
+        }
+
+        // the reach probabilities below the edited nodes have changed
+        self.apply_history(&saved_history);
+        if is_cached {
+            self.cache_normalized_weights();
+        }
+
+        prepared.map(|_| ())
+    }
+
+    /// Returns whether the strategies have been edited since the expected values were last
+    /// computed (see [`edit_strategies`]).
+    ///
+    /// [`edit_strategies`]: #method.edit_strategies
+    #[inline]
+    pub fn has_pending_strategy_edits(&self) -> bool {
+        self.has_pending_strategy_edits
+    }
+
+    /// Recomputes the expected values of all nodes with the current (possibly edited) strategies
+    /// in a single pass, and returns the exploitability of the strategies.
+    ///
+    /// This is [`recompute_evs`] followed by [`compute_exploitability`]. Returns an error if the
+    /// game is not solved or the storage mode is not [`BoardState::River`].
+    ///
+    /// [`recompute_evs`]: #method.recompute_evs
+    /// [`compute_exploitability`]: crate::compute_exploitability
+    pub fn recompute_evs_and_exploitability(&mut self) -> Result<f32, Error> {
+        self.recompute_evs()?;
+        Ok(compute_exploitability(self))
+    }
+
+    /// Validates `edit` at the current node, and returns the index of the node and the new
+    /// strategy in the order of the storage.
+    fn prepare_strategy_edit(&self, edit: &StrategyEdit) -> Result<(usize, Vec<f32>), Error> {
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree(format!(
+                "Node is not a decision node: {:?}",
+                edit.history
+            )));
+        }
+
+        let node = self.node();
+        if node.is_locked {
+            return Err(Error::Tree(format!("Node is locked: {:?}", edit.history)));
+        }
+
+        if !self.is_node_allocated(&node) {
+            return Err(Error::Tree(format!(
+                "Node is not allocated: {:?}",
+                edit.history
+            )));
+        }
+
+        let player = self.current_player();
+        let num_actions = node.num_actions();
+        let num_hands = self.num_private_hands(player);
+
+        if edit.strategy.len() != num_actions * num_hands {
+            return Err(Error::Config(format!(
+                "Invalid strategy length: expected {}, got {}",
+                num_actions * num_hands,
+                edit.strategy.len()
+            )));
+        }
+
+        if let Some(&freq) = edit.strategy.iter().find(|&&x| x < 0.0 || !x.is_finite()) {
+            return Err(Error::Config(format!("Invalid frequency: {freq}")));
+        }
+
+        let mut strategy = self.strategy();
+
+        for hand in 0..num_hands {
+            let column = (0..num_actions).map(|action| edit.strategy[action * num_hands + hand]);
+            let sum = column.fold(0.0, |sum, freq| sum + freq as f64);
+            if sum > 0.0 {
+                for action in 0..num_actions {
+                    let freq = edit.strategy[action * num_hands + hand] as f64;
+                    strategy[action * num_hands + hand] = (freq / sum) as f32;
+                }
+            }
+        }
+
+        strategy.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, true);
+        });
+
+        let strategy = self.reduce_stored_hands(&strategy, player)?;
+        Ok((self.node_index(&self.node()), strategy))
+    }
+}
@@ -1,6 +1,11 @@
 use super::*;
 use crate::sliceop::*;
+use std::iter;
 use std::mem::MaybeUninit;
+use std::ops::{Add, Mul, Sub};
+
+#[cfg(feature = "simd")]
+use wide::f64x4;
 
 #[inline]
 fn min(x: f64, y: f64) -> f64 {
@@ -11,19 +16,260 @@ fn min(x: f64, y: f64) -> f64 {
     }
 }
 
+/// Returns the payoffs of a showdown (win, tie, lose) when the equity realization is modified.
+///
+/// The winner of the showdown takes the realized fraction of the raked pot and the loser takes the
+/// rest, so the payoffs remain zero-sum except for the rake.
+#[inline]
+fn realized_payoffs(pot: f64, rake: f64, realization: [f64; 2], player: usize) -> [f64; 3] {
+    let half_pot = 0.5 * pot;
+    let raked_pot = pot - rake;
+    let share_win = realization[player];
+    let share_lose = 1.0 - realization[player ^ 1];
+    [
+        share_win * raked_pot - half_pot,
+        0.5 * (share_win + share_lose) * raked_pot - half_pot,
+        share_lose * raked_pot - half_pot,
+    ]
+}
+
+/// A number type in which the terminal values are computed: `f64`, or `f64x4` with the `simd`
+/// feature.
+trait Lane: Copy + From<f64> + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {}
+
+impl Lane for f64 {}
+
+#[cfg(feature = "simd")]
+impl Lane for f64x4 {}
+
+/// The value of a hand at a terminal node computed from `N` operands gathered for the hand.
+///
+/// The same expression is evaluated in `f64` and in `f64x4` lanes, so the results do not depend
+/// on the `simd` feature.
+trait HandValue<const N: usize> {
+    fn value<T: Lane>(&self, operands: [T; N]) -> T;
+}
+
+/// The value of a hand when someone folded; the operands are the reach of the opponent's same
+/// hand and the reaches of the opponent's hands containing each card of the hand.
+struct FoldValue {
+    payoff: f64,
+    cfreach_sum: f64,
+}
+
+impl HandValue<3> for FoldValue {
+    #[inline]
+    fn value<T: Lane>(&self, [cfreach_same, minus1, minus2]: [T; 3]) -> T {
+        // inclusion-exclusion principle
+        let cfreach = T::from(self.cfreach_sum) + cfreach_same - minus1 - minus2;
+        T::from(self.payoff) * cfreach
+    }
+}
+
+/// The value of a hand against the opponent's hands whose reaches are accumulated (i.e., the
+/// weaker or the stronger hands in the 2-pass showdown); the operands are the accumulated reach
+/// and the reaches of the accumulated hands containing each card of the hand.
+struct AccumulatedValue {
+    amount: f64,
+}
+
+impl HandValue<3> for AccumulatedValue {
+    #[inline]
+    fn value<T: Lane>(&self, [cfreach_sum, minus1, minus2]: [T; 3]) -> T {
+        T::from(self.amount) * (cfreach_sum - minus1 - minus2)
+    }
+}
+
+/// The value of a hand in the 3-pass showdown; the operands are, for all, the weaker, and the
+/// equal or weaker hands of the opponent, the reach and the reaches of the hands containing each
+/// card of the hand, followed by the reach of the opponent's same hand.
+struct ShowdownValue {
+    amounts: [f64; 3],
+    cfreach_sum: f64,
+}
+
+impl HandValue<9> for ShowdownValue {
+    #[inline]
+    fn value<T: Lane>(&self, operands: [T; 9]) -> T {
+        let [minus1, minus2, sum_win, minus_win1, minus_win2] = [
+            operands[0],
+            operands[1],
+            operands[2],
+            operands[3],
+            operands[4],
+        ];
+        let [sum_tie, minus_tie1, minus_tie2, cfreach_same] =
+            [operands[5], operands[6], operands[7], operands[8]];
+        let [amount_win, amount_tie, amount_lose] = self.amounts.map(T::from);
+        let cfreach_total = T::from(self.cfreach_sum) - minus1 - minus2;
+        let cfreach_win = sum_win - minus_win1 - minus_win2;
+        let cfreach_tie = sum_tie - minus_tie1 - minus_tie2;
+        amount_win * cfreach_win
+            + amount_tie * (cfreach_tie - cfreach_win + cfreach_same)
+            + amount_lose * (cfreach_total - cfreach_tie)
+    }
+}
+
+/// Computes the value of each hand of `items` by `hand_value` from the hand index and the
+/// operands returned by `operands`, and writes it to `result` (adds it if `ADD` is `true`).
+///
+/// `operands` is called in the order of `items`, so it may advance a scan over the opponent's
+/// hands. With the `simd` feature, the operands of four hands are buffered and their values are
+/// computed together in `f64x4` lanes.
+#[cfg(not(feature = "simd"))]
+#[inline]
+fn write_hand_values<I, const N: usize, const ADD: bool>(
+    result: &mut [f32],
+    items: impl IntoIterator<Item = I>,
+    mut operands: impl FnMut(I) -> (usize, [f64; N]),
+    hand_value: &impl HandValue<N>,
+) {
+    for item in items {
+        let (index, operands) = operands(item);
+        let value = hand_value.value(operands) as f32;
+        unsafe {
+            if ADD {
+                *result.get_unchecked_mut(index) += value;
+            } else {
+                *result.get_unchecked_mut(index) = value;
+            }
+        }
+    }
+}
+
+/// Computes the value of each hand of `items` by `hand_value` from the hand index and the
+/// operands returned by `operands`, and writes it to `result` (adds it if `ADD` is `true`).
+///
+/// `operands` is called in the order of `items`, so it may advance a scan over the opponent's
+/// hands. With the `simd` feature, the operands of four hands are buffered and their values are
+/// computed together in `f64x4` lanes.
+#[cfg(feature = "simd")]
+#[inline]
+fn write_hand_values<I, const N: usize, const ADD: bool>(
+    result: &mut [f32],
+    items: impl IntoIterator<Item = I>,
+    mut operands: impl FnMut(I) -> (usize, [f64; N]),
+    hand_value: &impl HandValue<N>,
+) {
+    let mut indices = [0; 4];
+    let mut lanes = [[0.0; 4]; N];
+    let mut len = 0;
+
+    let mut write = |index: usize, value: f64| unsafe {
+        if ADD {
+            *result.get_unchecked_mut(index) += value as f32;
+        } else {
+            *result.get_unchecked_mut(index) = value as f32;
+        }
+    };
+
+    for item in items {
+        let (index, operands) = operands(item);
+        indices[len] = index;
+        for (lane, operand) in lanes.iter_mut().zip(operands) {
+            lane[len] = operand;
+        }
+        len += 1;
+
+        if len == 4 {
+            let values = hand_value.value(lanes.map(f64x4::new)).to_array();
+            for (&index, value) in indices.iter().zip(values) {
+                write(index, value);
+            }
+            len = 0;
+        }
+    }
+
+    for lane in 0..len {
+        let value = hand_value.value(lanes.map(|operands| operands[lane]));
+        write(indices[lane], value);
+    }
+}
+
 impl PostFlopGame {
-    pub(super) fn evaluate_internal(
+    /// Returns the number of rankings among which the pot is divided equally at showdown.
+    #[inline]
+    pub(super) fn num_showdown_rankings(&self) -> usize {
+        1 + !self.hand_strength_low.is_empty() as usize + self.second_board_strength.len()
+    }
+
+    /// Returns the rankings among which the pot is divided equally at showdown.
+    #[inline]
+    pub(super) fn showdown_rankings(
+        &self,
+        pair_index: usize,
+    ) -> impl Iterator<Item = &[Vec<StrengthItem>; 2]> {
+        iter::once(&self.hand_strength[pair_index])
+            .chain(self.hand_strength_low.get(pair_index))
+            .chain(&self.second_board_strength)
+    }
+
+    /// Converts the chips won by `player` in each outcome into the payoffs by the payoff model.
+    #[inline]
+    fn terminal_payoffs<const N: usize>(
+        &self,
+        nets: [f64; N],
+        rake: f64,
+        player: usize,
+    ) -> [f64; N] {
+        let starting_pot = self.tree_config.starting_pot as f64;
+        self.tree_config
+            .payoff_model
+            .payoffs(nets, rake, player, starting_pot)
+    }
+
+    #[inline]
+    fn showdown_realization(&self, node: &PostFlopNode) -> Option<[f64; 2]> {
+        if self.showdown_realization.is_empty() {
+            return None;
+        }
+        self.showdown_realization
+            .get(&self.node_index(node))
+            .copied()
+    }
+
+    /// Returns the rake taken from the pot at the terminal `node`.
+    #[inline]
+    pub(super) fn terminal_rake(&self, node: &PostFlopNode) -> f64 {
+        let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
+        min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap)
+    }
+
+    /// Evaluates the terminal `node` as [`Game::evaluate`], but with the given amount of `rake`
+    /// taken from the pot instead of [`terminal_rake`].
+    ///
+    /// [`Game::evaluate`]: crate::Game::evaluate
+    /// [`terminal_rake`]: #method.terminal_rake
+    #[inline]
+    pub(super) fn evaluate_with_rake(
+        &self,
+        result: &mut [MaybeUninit<f32>],
+        node: &PostFlopNode,
+        player: usize,
+        cfreach: &[f32],
+        rake: f64,
+    ) {
+        if self.bunching_num_dead_cards == 0 {
+            self.evaluate_internal(result, node, player, cfreach, rake);
+        } else {
+            self.evaluate_internal_bunching(result, node, player, cfreach, rake);
+        }
+    }
+
+    fn evaluate_internal(
         &self,
         result: &mut [MaybeUninit<f32>],
         node: &PostFlopNode,
         player: usize,
         cfreach: &[f32],
+        rake: f64,
     ) {
         let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
         let half_pot = 0.5 * pot;
-        let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
-        let amount_win = (half_pot - rake) / self.num_combinations;
-        let amount_lose = -half_pot / self.num_combinations;
+        let [amount_win, amount_lose] = self
+            .terminal_payoffs([half_pot - rake, -half_pot], rake, player)
+            .map(|amount| amount / self.num_combinations);
+        let realization = self.showdown_realization(node);
 
         let player_cards = &self.private_cards[player];
         let opponent_cards = &self.private_cards[player ^ 1];
@@ -72,10 +318,15 @@ impl PostFlopGame {
                 return;
             }
 
-            let player_indices = &valid_indices[player];
             let same_hand_index = &self.same_hand_index[player];
-            for &i in player_indices {
-                unsafe {
+            let fold_value = FoldValue {
+                payoff,
+                cfreach_sum,
+            };
+            write_hand_values::<_, 3, false>(
+                result,
+                &valid_indices[player],
+                |&i| unsafe {
                     let (c1, c2) = *player_cards.get_unchecked(i as usize);
                     let same_i = *same_hand_index.get_unchecked(i as usize);
                     let cfreach_same = if same_i == u16::MAX {
@@ -83,16 +334,19 @@ impl PostFlopGame {
                     } else {
                         *cfreach.get_unchecked(same_i as usize) as f64
                     };
-                    // inclusion-exclusion principle
-                    let cfreach = cfreach_sum + cfreach_same
-                        - *cfreach_minus.get_unchecked(c1 as usize)
-                        - *cfreach_minus.get_unchecked(c2 as usize);
-                    *result.get_unchecked_mut(i as usize) = (payoff * cfreach) as f32;
-                }
-            }
+                    let minus1 = *cfreach_minus.get_unchecked(c1 as usize);
+                    let minus2 = *cfreach_minus.get_unchecked(c2 as usize);
+                    (i as usize, [cfreach_same, minus1, minus2])
+                },
+                &fold_value,
+            );
         }
         // showdown (optimized for no rake; 2-pass)
-        else if rake == 0.0 {
+        else if rake == 0.0
+            && realization.is_none()
+            && self.num_showdown_rankings() == 1
+            && node.river != NOT_DEALT
+        {
             let pair_index = card_pair_to_index(node.turn, node.river);
             let hand_strength = &self.hand_strength[pair_index];
             let player_strength = &hand_strength[player];
@@ -101,8 +355,10 @@ impl PostFlopGame {
             let valid_player_strength = &player_strength[1..player_strength.len() - 1];
             let mut i = 1;
 
-            for &StrengthItem { strength, index } in valid_player_strength {
-                unsafe {
+            write_hand_values::<_, 3, false>(
+                result,
+                valid_player_strength,
+                |&StrengthItem { strength, index }| unsafe {
                     while opponent_strength.get_unchecked(i).strength < strength {
                         let opponent_index = opponent_strength.get_unchecked(i).index as usize;
                         let cfreach_i = *cfreach.get_unchecked(opponent_index);
@@ -116,19 +372,21 @@ impl PostFlopGame {
                         i += 1;
                     }
                     let (c1, c2) = *player_cards.get_unchecked(index as usize);
-                    let cfreach = cfreach_sum
-                        - cfreach_minus.get_unchecked(c1 as usize)
-                        - cfreach_minus.get_unchecked(c2 as usize);
-                    *result.get_unchecked_mut(index as usize) = (amount_win * cfreach) as f32;
-                }
-            }
+                    let minus1 = *cfreach_minus.get_unchecked(c1 as usize);
+                    let minus2 = *cfreach_minus.get_unchecked(c2 as usize);
+                    (index as usize, [cfreach_sum, minus1, minus2])
+                },
+                &AccumulatedValue { amount: amount_win },
+            );
 
             cfreach_sum = 0.0;
             cfreach_minus.fill(0.0);
             i = opponent_strength.len() - 2;
 
-            for &StrengthItem { strength, index } in valid_player_strength.iter().rev() {
-                unsafe {
+            write_hand_values::<_, 3, true>(
+                result,
+                valid_player_strength.iter().rev(),
+                |&StrengthItem { strength, index }| unsafe {
                     while opponent_strength.get_unchecked(i).strength > strength {
                         let opponent_index = opponent_strength.get_unchecked(i).index as usize;
                         let cfreach_i = *cfreach.get_unchecked(opponent_index);
@@ -142,129 +400,207 @@ impl PostFlopGame {
                         i -= 1;
                     }
                     let (c1, c2) = *player_cards.get_unchecked(index as usize);
-                    let cfreach = cfreach_sum
-                        - cfreach_minus.get_unchecked(c1 as usize)
-                        - cfreach_minus.get_unchecked(c2 as usize);
-                    *result.get_unchecked_mut(index as usize) += (amount_lose * cfreach) as f32;
-                }
-            }
+                    let minus1 = *cfreach_minus.get_unchecked(c1 as usize);
+                    let minus2 = *cfreach_minus.get_unchecked(c2 as usize);
+                    (index as usize, [cfreach_sum, minus1, minus2])
+                },
+                &AccumulatedValue {
+                    amount: amount_lose,
+                },
+            );
         }
-        // showdown (raked; 3-pass)
+        // showdown (raked, realization modified, split pot, second board, or all-in before the
+        // river; 3-pass)
         else {
-            let amount_tie = -0.5 * rake / self.num_combinations;
-            let same_hand_index = &self.same_hand_index[player];
-
-            let pair_index = card_pair_to_index(node.turn, node.river);
-            let hand_strength = &self.hand_strength[pair_index];
-            let player_strength = &hand_strength[player];
-            let opponent_strength = &hand_strength[player ^ 1];
-
-            let valid_player_strength = &player_strength[1..player_strength.len() - 1];
-            let valid_opponent_strength = &opponent_strength[1..opponent_strength.len() - 1];
+            // when both players are all-in before the river, the fee of the equity chop is taken
+            // in addition to the rake
+            let deduction = if node.river == NOT_DEALT {
+                rake + self.tree_config.allin_chop_fee(pot)
+            } else {
+                rake
+            };
 
-            for &StrengthItem { index, .. } in valid_opponent_strength {
-                unsafe {
-                    let cfreach_i = *cfreach.get_unchecked(index as usize);
-                    if cfreach_i != 0.0 {
-                        let (c1, c2) = *opponent_cards.get_unchecked(index as usize);
-                        let cfreach_i_f64 = cfreach_i as f64;
-                        cfreach_sum += cfreach_i_f64;
-                        *cfreach_minus.get_unchecked_mut(c1 as usize) += cfreach_i_f64;
-                        *cfreach_minus.get_unchecked_mut(c2 as usize) += cfreach_i_f64;
+            let payoffs = match realization {
+                Some(realization) => realized_payoffs(pot, deduction, realization, player),
+                None => [half_pot - deduction, -0.5 * deduction, -half_pot],
+            };
+            let amounts = self
+                .terminal_payoffs(payoffs, deduction, player)
+                .map(|amount| amount / self.num_combinations);
+
+            // the pot is divided equally among the rankings
+            let num_rankings = self.num_showdown_rankings() as f64;
+
+            if node.river != NOT_DEALT {
+                let pair_index = card_pair_to_index(node.turn, node.river);
+                let amounts = amounts.map(|amount| amount / num_rankings);
+                for hand_strength in self.showdown_rankings(pair_index) {
+                    self.evaluate_showdown(result, hand_strength, player, cfreach, amounts);
+                }
+            } else {
+                // both players are all-in before the river: average over all runouts, each of
+                // which is possible for every non-conflicting pair of hands
+                let num_runouts = if node.turn == NOT_DEALT {
+                    45 * 44 / 2
+                } else {
+                    44
+                };
+                let amounts = amounts.map(|amount| amount / (num_rankings * num_runouts as f64));
+                if node.turn == NOT_DEALT && self.allin_equity_cache.is_enabled() {
+                    self.evaluate_allin_cached(result, player, cfreach, amounts);
+                    return;
+                }
+                for pair_index in self.runout_pair_indices(node.turn) {
+                    for hand_strength in self.showdown_rankings(pair_index) {
+                        self.evaluate_showdown(result, hand_strength, player, cfreach, amounts);
                     }
                 }
             }
+        }
+    }
 
-            if cfreach_sum == 0.0 {
-                return;
-            }
+    /// Returns the pair indices of the turn and river cards of all runouts from the board of the
+    /// node (`turn` is `NOT_DEALT` on the flop).
+    pub(super) fn runout_pair_indices(&self, turn: Card) -> impl Iterator<Item = usize> + '_ {
+        (0..self.hand_strength.len()).filter(move |&pair_index| {
+            let (card1, card2) = index_to_card_pair(pair_index);
+            !self.hand_strength[pair_index][0].is_empty()
+                && (turn == NOT_DEALT || card1 == turn || card2 == turn)
+        })
+    }
+
+    /// Adds the counterfactual values of a showdown with the given ranking to `result`.
+    fn evaluate_showdown(
+        &self,
+        result: &mut [f32],
+        hand_strength: &[Vec<StrengthItem>; 2],
+        player: usize,
+        cfreach: &[f32],
+        amounts: [f64; 3],
+    ) {
+        let player_cards = &self.private_cards[player];
+        let opponent_cards = &self.private_cards[player ^ 1];
+        let same_hand_index = &self.same_hand_index[player];
 
-            let mut cfreach_sum_win = 0.0;
-            let mut cfreach_sum_tie = 0.0;
-            let mut cfreach_minus_win = [0.0; 52];
-            let mut cfreach_minus_tie = [0.0; 52];
+        let player_strength = &hand_strength[player];
+        let opponent_strength = &hand_strength[player ^ 1];
 
-            let mut i = 1;
-            let mut j = 1;
-            let mut prev_strength = 0; // strength is always > 0
+        let valid_player_strength = &player_strength[1..player_strength.len() - 1];
+        let valid_opponent_strength = &opponent_strength[1..opponent_strength.len() - 1];
 
-            for &StrengthItem { strength, index } in valid_player_strength {
-                unsafe {
-                    if strength > prev_strength {
-                        prev_strength = strength;
+        let mut cfreach_sum = 0.0;
+        let mut cfreach_minus = [0.0; 52];
 
-                        if i < j {
-                            cfreach_sum_win = cfreach_sum_tie;
-                            cfreach_minus_win = cfreach_minus_tie;
-                            i = j;
-                        }
+        for &StrengthItem { index, .. } in valid_opponent_strength {
+            unsafe {
+                let cfreach_i = *cfreach.get_unchecked(index as usize);
+                if cfreach_i != 0.0 {
+                    let (c1, c2) = *opponent_cards.get_unchecked(index as usize);
+                    let cfreach_i_f64 = cfreach_i as f64;
+                    cfreach_sum += cfreach_i_f64;
+                    *cfreach_minus.get_unchecked_mut(c1 as usize) += cfreach_i_f64;
+                    *cfreach_minus.get_unchecked_mut(c2 as usize) += cfreach_i_f64;
+                }
+            }
+        }
 
-                        while opponent_strength.get_unchecked(i).strength < strength {
-                            let opponent_index = opponent_strength.get_unchecked(i).index as usize;
-                            let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
-                            let cfreach_i = *cfreach.get_unchecked(opponent_index) as f64;
-                            cfreach_sum_win += cfreach_i;
-                            *cfreach_minus_win.get_unchecked_mut(c1 as usize) += cfreach_i;
-                            *cfreach_minus_win.get_unchecked_mut(c2 as usize) += cfreach_i;
-                            i += 1;
-                        }
+        if cfreach_sum == 0.0 {
+            return;
+        }
 
-                        if j < i {
-                            cfreach_sum_tie = cfreach_sum_win;
-                            cfreach_minus_tie = cfreach_minus_win;
-                            j = i;
-                        }
+        let mut cfreach_sum_win = 0.0;
+        let mut cfreach_sum_tie = 0.0;
+        let mut cfreach_minus_win = [0.0; 52];
+        let mut cfreach_minus_tie = [0.0; 52];
+
+        let mut i = 1;
+        let mut j = 1;
+        let mut prev_strength = 0; // strength is always > 0
+
+        let showdown_value = ShowdownValue {
+            amounts,
+            cfreach_sum,
+        };
+
+        write_hand_values::<_, 9, true>(
+            result,
+            valid_player_strength,
+            |&StrengthItem { strength, index }| unsafe {
+                if strength > prev_strength {
+                    prev_strength = strength;
+
+                    if i < j {
+                        cfreach_sum_win = cfreach_sum_tie;
+                        cfreach_minus_win = cfreach_minus_tie;
+                        i = j;
+                    }
 
-                        while opponent_strength.get_unchecked(j).strength == strength {
-                            let opponent_index = opponent_strength.get_unchecked(j).index as usize;
-                            let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
-                            let cfreach_j = *cfreach.get_unchecked(opponent_index) as f64;
-                            cfreach_sum_tie += cfreach_j;
-                            *cfreach_minus_tie.get_unchecked_mut(c1 as usize) += cfreach_j;
-                            *cfreach_minus_tie.get_unchecked_mut(c2 as usize) += cfreach_j;
-                            j += 1;
-                        }
+                    while opponent_strength.get_unchecked(i).strength < strength {
+                        let opponent_index = opponent_strength.get_unchecked(i).index as usize;
+                        let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
+                        let cfreach_i = *cfreach.get_unchecked(opponent_index) as f64;
+                        cfreach_sum_win += cfreach_i;
+                        *cfreach_minus_win.get_unchecked_mut(c1 as usize) += cfreach_i;
+                        *cfreach_minus_win.get_unchecked_mut(c2 as usize) += cfreach_i;
+                        i += 1;
                     }
 
-                    let (c1, c2) = *player_cards.get_unchecked(index as usize);
-                    let cfreach_total = cfreach_sum
-                        - cfreach_minus.get_unchecked(c1 as usize)
-                        - cfreach_minus.get_unchecked(c2 as usize);
-                    let cfreach_win = cfreach_sum_win
-                        - cfreach_minus_win.get_unchecked(c1 as usize)
-                        - cfreach_minus_win.get_unchecked(c2 as usize);
-                    let cfreach_tie = cfreach_sum_tie
-                        - cfreach_minus_tie.get_unchecked(c1 as usize)
-                        - cfreach_minus_tie.get_unchecked(c2 as usize);
-                    let same_i = *same_hand_index.get_unchecked(index as usize);
-                    let cfreach_same = if same_i == u16::MAX {
-                        0.0
-                    } else {
-                        *cfreach.get_unchecked(same_i as usize) as f64
-                    };
+                    if j < i {
+                        cfreach_sum_tie = cfreach_sum_win;
+                        cfreach_minus_tie = cfreach_minus_win;
+                        j = i;
+                    }
 
-                    let cfvalue = amount_win * cfreach_win
-                        + amount_tie * (cfreach_tie - cfreach_win + cfreach_same)
-                        + amount_lose * (cfreach_total - cfreach_tie);
-                    *result.get_unchecked_mut(index as usize) = cfvalue as f32;
+                    while opponent_strength.get_unchecked(j).strength == strength {
+                        let opponent_index = opponent_strength.get_unchecked(j).index as usize;
+                        let (c1, c2) = *opponent_cards.get_unchecked(opponent_index);
+                        let cfreach_j = *cfreach.get_unchecked(opponent_index) as f64;
+                        cfreach_sum_tie += cfreach_j;
+                        *cfreach_minus_tie.get_unchecked_mut(c1 as usize) += cfreach_j;
+                        *cfreach_minus_tie.get_unchecked_mut(c2 as usize) += cfreach_j;
+                        j += 1;
+                    }
                 }
-            }
-        }
+
+                let (c1, c2) = *player_cards.get_unchecked(index as usize);
+                let (c1, c2) = (c1 as usize, c2 as usize);
+                let same_i = *same_hand_index.get_unchecked(index as usize);
+                let cfreach_same = if same_i == u16::MAX {
+                    0.0
+                } else {
+                    *cfreach.get_unchecked(same_i as usize) as f64
+                };
+
+                let operands = [
+                    *cfreach_minus.get_unchecked(c1),
+                    *cfreach_minus.get_unchecked(c2),
+                    cfreach_sum_win,
+                    *cfreach_minus_win.get_unchecked(c1),
+                    *cfreach_minus_win.get_unchecked(c2),
+                    cfreach_sum_tie,
+                    *cfreach_minus_tie.get_unchecked(c1),
+                    *cfreach_minus_tie.get_unchecked(c2),
+                    cfreach_same,
+                ];
+                (index as usize, operands)
+            },
+            &showdown_value,
+        );
     }
 
-    pub(super) fn evaluate_internal_bunching(
+    fn evaluate_internal_bunching(
         &self,
         result: &mut [MaybeUninit<f32>],
         node: &PostFlopNode,
         player: usize,
         cfreach: &[f32],
+        rake: f64,
     ) {
         let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
         let half_pot = 0.5 * pot;
-        let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
-        let amount_win = ((half_pot - rake) / self.bunching_num_combinations) as f32;
-        let amount_lose = (-half_pot / self.bunching_num_combinations) as f32;
-        let amount_tie = (-0.5 * rake / self.bunching_num_combinations) as f32;
+        let [amount_win, amount_tie, amount_lose] = self
+            .terminal_payoffs([half_pot - rake, -0.5 * rake, -half_pot], rake, player)
+            .map(|amount| (amount / self.bunching_num_combinations) as f32);
         let opponent_len = self.private_cards[player ^ 1].len();
 
         // someone folded
@@ -300,6 +636,20 @@ impl PostFlopGame {
             let player_strength = &self.bunching_strength[pair_index][player];
             let opponent_strength = &self.bunching_strength[pair_index][player ^ 1];
 
+            let (amount_win, amount_lose, amount_tie) = match self.showdown_realization(node) {
+                Some(realization) => {
+                    let payoffs = realized_payoffs(pot, rake, realization, player);
+                    let [win, tie, lose] = self.terminal_payoffs(payoffs, rake, player);
+                    let num_combinations = self.bunching_num_combinations;
+                    (
+                        (win / num_combinations) as f32,
+                        (lose / num_combinations) as f32,
+                        (tie / num_combinations) as f32,
+                    )
+                }
+                None => (amount_win, amount_lose, amount_tie),
+            };
+
             result
                 .iter_mut()
                 .zip(indices)
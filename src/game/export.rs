@@ -0,0 +1,228 @@
+use super::*;
+use crate::error::*;
+use crate::json::*;
+use crate::pio::*;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// The number of rows written at once by [`PostFlopGame::export_strategy`].
+const EXPORT_CHUNK_ROWS: usize = 1 << 16;
+
+/// The columns of the strategy table in the order of the exported formats.
+const EXPORT_COLUMNS: [&str; 10] = [
+    "node",
+    "line",
+    "pio_path",
+    "board",
+    "player",
+    "hand",
+    "action",
+    "frequency",
+    "ev",
+    "reach",
+];
+
+/// Text format of [`PostFlopGame::export_strategy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row. Fields containing a comma or a double quote are
+    /// quoted.
+    Csv,
+
+    /// A JSON array of objects, one object per row (written one per line). Non-finite numbers
+    /// are written as `null`.
+    Json,
+}
+
+impl PostFlopGame {
+    /// Writes the strategy table (see [`StrategyTable`] for the columns) to `writer` in the given
+    /// text format.
+    ///
+    /// Each row holds the frequency and the expected value of an action with a hand at a
+    /// decision node, and the node is identified both by the pattern syntax of [`NodePattern`]
+    /// and by PioSolver's node notation, so that the results can be compared with other solvers
+    /// or loaded into other applications without walking the tree. The table is written in
+    /// chunks, so the memory usage is bounded even for large trees.
+    ///
+    /// Returns an error if the strategy table cannot be produced (see
+    /// [`strategy_table_chunks`]) or writing fails. The current node is restored after the call.
+    ///
+    /// [`strategy_table_chunks`]: #method.strategy_table_chunks
+    pub fn export_strategy<W: Write>(
+        &mut self,
+        format: ExportFormat,
+        mut writer: W,
+    ) -> Result<(), Error> {
+        let mut buf = String::new();
+        match format {
+            ExportFormat::Csv => buf.push_str(&EXPORT_COLUMNS.join(",")),
+            ExportFormat::Json => buf.push('['),
+        }
+
+        let mut is_first = true;
+        self.strategy_table_chunks(EXPORT_CHUNK_ROWS, |table| {
+            for row in 0..table.len() {
+                match format {
+                    ExportFormat::Csv => write_csv_row(&mut buf, &table, row),
+                    ExportFormat::Json => {
+                        buf.push_str(if is_first { "\n" } else { ",\n" });
+                        write_json_row(&mut buf, &table, row);
+                    }
+                }
+                is_first = false;
+            }
+            writer.write_all(buf.as_bytes())?;
+            buf.clear();
+            Ok(())
+        })?;
+
+        match format {
+            ExportFormat::Csv => buf.push('\n'),
+            ExportFormat::Json => buf.push_str(if is_first { "]\n" } else { "\n]\n" }),
+        }
+
+        writer.write_all(buf.as_bytes())?;
+        Ok(writer.flush()?)
+    }
+
+    /// Exports the strategies of the decision nodes up to `max_street` as PioSolver node
+    /// strategies.
+    ///
+    /// Each node has its path in PioSolver's notation and its strategy in PioSolver's hand order,
+    /// so it can be written in the format of the `show_strategy` command with
+    /// [`write_pio_strategy`]. The probabilities of the hands not in the range of the player to
+    /// act (including those overlapping with the board) are zero. Isomorphic chances are
+    /// expanded.
+    ///
+    /// Returns an error if the game is not solved. The current node is restored after the call.
+    pub fn export_pio_strategy(
+        &mut self,
+        max_street: BoardState,
+    ) -> Result<Vec<PioNodeStrategy>, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        let saved_history = self.history().to_vec();
+        let mut history = Vec::new();
+        let mut line = Vec::new();
+        let mut ret = Vec::new();
+        self.back_to_root();
+        self.export_pio_strategy_recursive(&mut history, &mut line, max_street, &mut ret);
+        self.apply_history(&saved_history);
+        Ok(ret)
+    }
+
+    fn export_pio_strategy_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+        max_street: BoardState,
+        result: &mut Vec<PioNodeStrategy>,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        let children = if self.is_chance_node() {
+            // the next street is beyond `max_street`
+            if self.current_board().len() - 3 >= max_street as usize {
+                return;
+            }
+
+            let possible_cards = self.possible_cards();
+            (0..52)
+                .filter(|&card| possible_cards & (1 << card) != 0)
+                .map(|card| (card as usize, Action::Chance(card)))
+                .collect::<Vec<_>>()
+        } else {
+            let actions = self.available_actions();
+            let hands = self.private_cards(self.current_player());
+            let strategy = self.strategy();
+            let mut pio_strategy = vec![0.0; actions.len() * PIO_NUM_HANDS];
+            for (action, row) in strategy.chunks_exact(hands.len()).enumerate() {
+                let pio_row = &mut pio_strategy[action * PIO_NUM_HANDS..];
+                for (&(c1, c2), &prob) in hands.iter().zip(row) {
+                    pio_row[pio_hand_index(c1, c2)] = prob;
+                }
+            }
+
+            result.push(PioNodeStrategy {
+                path: line_to_pio_path(line),
+                line: line.clone(),
+                actions: actions.clone(),
+                strategy: pio_strategy,
+            });
+
+            actions.into_iter().enumerate().collect()
+        };
+
+        for (index, action) in children {
+            history.push(index);
+            line.push(action);
+            self.apply_history(history);
+            self.export_pio_strategy_recursive(history, line, max_street, result);
+            line.pop();
+            history.pop();
+        }
+    }
+}
+
+/// Writes a row of the table in the CSV format, preceded by a newline.
+fn write_csv_row(dst: &mut String, table: &StrategyTable, row: usize) {
+    let fields = [
+        table.node[row].to_string(),
+        csv_field(&table.line[row]),
+        csv_field(&table.pio_path[row]),
+        csv_field(&table.board[row]),
+        table.player[row].to_string(),
+        csv_field(&table.hand[row]),
+        csv_field(&table.action[row]),
+        table.frequency[row].to_string(),
+        table.ev[row].to_string(),
+        table.reach[row].to_string(),
+    ];
+
+    dst.push('\n');
+    dst.push_str(&fields.join(","));
+}
+
+/// Returns `s` as a CSV field, quoted if necessary.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Writes a row of the table as a JSON object.
+fn write_json_row(dst: &mut String, table: &StrategyTable, row: usize) {
+    let string = |s: &str| JsonValue::String(s.to_string()).to_json_string();
+    let number = |x: f32| {
+        if x.is_finite() {
+            x.to_string()
+        } else {
+            "null".to_string()
+        }
+    };
+    let values = [
+        table.node[row].to_string(),
+        string(&table.line[row]),
+        string(&table.pio_path[row]),
+        string(&table.board[row]),
+        table.player[row].to_string(),
+        string(&table.hand[row]),
+        string(&table.action[row]),
+        number(table.frequency[row]),
+        number(table.ev[row]),
+        number(table.reach[row]),
+    ];
+
+    dst.push('{');
+    for (i, (column, value)) in EXPORT_COLUMNS.iter().zip(values).enumerate() {
+        if i > 0 {
+            dst.push(',');
+        }
+        write!(dst, "\"{column}\":{value}").unwrap();
+    }
+    dst.push('}');
+}
@@ -0,0 +1,445 @@
+use super::*;
+use crate::error::*;
+use crate::pio::*;
+
+#[cfg(feature = "solver")]
+use crate::interface::*;
+
+#[cfg(feature = "solver")]
+use crate::solver::*;
+
+#[cfg(feature = "solver")]
+use crate::utility::*;
+
+#[cfg(feature = "solver")]
+use std::mem::MaybeUninit;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Strategy of a single decision node supplied from outside of the solver (e.g., imported from
+/// another tool or handcrafted).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExternalNodeStrategy {
+    /// The history leading to the node, in the format accepted by
+    /// [`PostFlopGame::apply_history`].
+    pub history: Vec<usize>,
+
+    /// The strategy in the same layout as [`PostFlopGame::strategy`]. As in
+    /// [`PostFlopGame::lock_current_strategy`], a hand whose probabilities are all non-positive
+    /// is left to the solver.
+    pub strategy: Vec<f32>,
+}
+
+/// The result of [`PostFlopGame::external_strategy_exploitability_upper_bound`].
+#[cfg(feature = "solver")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExternalStrategyBound {
+    /// The exploitability of the completed strategy against best responses that are free to
+    /// deviate from the supplied strategy. This is an upper bound of the exploitability of the
+    /// best completion of the supplied strategy.
+    pub upper_bound: f32,
+
+    /// The number of iterations performed to complete the strategy.
+    pub num_iterations: u32,
+
+    /// The exploitability of the completed strategy within the locked game (see
+    /// [`compute_exploitability`]), i.e., how far the solve of the completion is from
+    /// converging. Zero for a strategy covering every decision node and hand.
+    ///
+    /// [`compute_exploitability`]: crate::compute_exploitability
+    pub convergence_gap: f32,
+}
+
+/// View of a game in which the locked strategies of `player` are ignored, so that the best
+/// response of `player` is free to deviate from them while the opponent's locked strategies are
+/// kept.
+#[cfg(feature = "solver")]
+struct UnlockedView<'a> {
+    game: &'a PostFlopGame,
+    player: usize,
+}
+
+impl PostFlopGame {
+    /// Locks the strategies of the given nodes.
+    ///
+    /// All nodes are validated before locking any of them. Returns an error if the memory is not
+    /// allocated, the game is already solved, a history does not lead to a decision node, or the
+    /// length of a strategy does not match the node. The current node is restored after the
+    /// call.
+    pub fn lock_external_strategy(&mut self, nodes: &[ExternalNodeStrategy]) -> Result<(), Error> {
+        self.check_operation(GameOperation::LockStrategy)?;
+
+        let saved_history = self.history().to_vec();
+        let result = self.lock_external_strategy_internal(nodes);
+        self.apply_history(&saved_history);
+        result
+    }
+
+    /// Computes an upper bound of the exploitability of the best completion of an externally
+    /// supplied strategy.
+    ///
+    /// The given nodes are locked with [`lock_external_strategy`] and the rest of the strategy is
+    /// solved for at most `max_num_iterations` iterations (or until the exploitability of the
+    /// locked game reaches `target_exploitability`). Then, the exploitability of the resulting
+    /// strategy is computed against best responses that are free to deviate from the locked
+    /// strategies, unlike [`compute_exploitability`] which measures the exploitability within the
+    /// locked game.
+    ///
+    /// For a strategy covering every decision node and hand, the returned bound is the
+    /// exploitability of the strategy itself and no iterations are needed. For a partial
+    /// strategy, the uncovered part is the solver's response to the supplied part, which is one
+    /// completion among many. Its exploitability is therefore only an upper bound of the
+    /// minimum over all completions (the distance from GTO of the supplied part), and the bound
+    /// is not tight in general: e.g., the responses to actions that the supplied strategy never
+    /// takes are not protected against deviations, and a finite solve does not converge exactly.
+    /// The number of iterations and the exploitability within the locked game are returned along
+    /// with the bound to tell how far the completion is solved.
+    ///
+    /// The game must be allocated and not solved. The game is solved after the call, so the
+    /// completed strategy can be inspected with the usual methods.
+    ///
+    /// [`lock_external_strategy`]: #method.lock_external_strategy
+    #[cfg(feature = "solver")]
+    pub fn external_strategy_exploitability_upper_bound(
+        &mut self,
+        nodes: &[ExternalNodeStrategy],
+        max_num_iterations: u32,
+        target_exploitability: f32,
+    ) -> Result<ExternalStrategyBound, Error> {
+        self.lock_external_strategy(nodes)?;
+        let summary = solve(self, max_num_iterations, target_exploitability, false);
+        Ok(ExternalStrategyBound {
+            upper_bound: self.unlocked_exploitability(),
+            num_iterations: summary.num_iterations,
+            convergence_gap: summary.exploitability,
+        })
+    }
+
+    /// Computes the exploitability of the current strategy, where the best responses are not
+    /// constrained by the locked strategies (unlike [`compute_exploitability`]).
+    #[cfg(feature = "solver")]
+    fn unlocked_exploitability(&self) -> f32 {
+        let mes_ev = [0, 1].map(|player| {
+            let view = UnlockedView { game: self, player };
+            let cfvalues = compute_best_cfvalues(&view, player, &|_, _| {});
+            let weights = Game::initial_weights(self, player);
+            Summation::Compensated.weighted_sum(&cfvalues, weights) as f32
+        });
+
+        if !self.is_raked() {
+            (mes_ev[0] + mes_ev[1]) * 0.5
+        } else {
+            let current_ev = compute_current_ev(self);
+            ((mes_ev[0] - current_ev[0]) + (mes_ev[1] - current_ev[1])) * 0.5
+        }
+    }
+
+    /// Converts node strategies read from PioSolver (see [`read_pio_node_strategy`]) into the
+    /// layout of this game.
+    ///
+    /// The nodes may cover only a part of the tree (e.g., the flop decisions only). To solve the
+    /// remaining nodes given the imported part, lock the returned strategies with
+    /// [`lock_external_strategy`] and solve the game as usual:
+    ///
+    /// ```ignore
+    /// let nodes = game.import_pio_strategy(&pio_nodes)?;
+    /// game.lock_external_strategy(&nodes)?;
+    /// solve(&mut game, 1000, target_exploitability, false);
+    /// ```
+    ///
+    /// Returns an error if a line does not exist in the tree or the actions of a node differ
+    /// from those of the tree. The current node is restored after the call.
+    ///
+    /// [`lock_external_strategy`]: #method.lock_external_strategy
+    pub fn import_pio_strategy(
+        &mut self,
+        nodes: &[PioNodeStrategy],
+    ) -> Result<Vec<ExternalNodeStrategy>, Error> {
+        self.check_operation(GameOperation::Navigate)?;
+
+        let saved_history = self.history().to_vec();
+        let result = nodes
+            .iter()
+            .map(|node| {
+                let history = self.line_to_history(&node.line)?;
+                if self.available_actions() != node.actions {
+                    return Err(Error::Tree(format!(
+                        "Actions mismatch at {}: expected = {:?}, actual = {:?}",
+                        node.path,
+                        self.available_actions(),
+                        node.actions
+                    )));
+                }
+                let player = self.current_player();
+                Ok(ExternalNodeStrategy {
+                    history,
+                    strategy: node.strategy_for_hands(self.private_cards(player)),
+                })
+            })
+            .collect();
+        self.apply_history(&saved_history);
+        result
+    }
+
+    /// Exports the strategies of the decision nodes up to `max_street` in the format accepted by
+    /// [`lock_external_strategy`], e.g., for fixing the flop strategy of a solution in another
+    /// game with the same configuration and solving the turn and the river again.
+    ///
+    /// Returns an error if the game is not solved. The current node is restored after the call.
+    ///
+    /// [`lock_external_strategy`]: #method.lock_external_strategy
+    pub fn export_external_strategy(
+        &mut self,
+        max_street: BoardState,
+    ) -> Result<Vec<ExternalNodeStrategy>, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        let saved_history = self.history().to_vec();
+        let mut history = Vec::new();
+        let mut ret = Vec::new();
+        self.back_to_root();
+        self.export_external_strategy_recursive(&mut history, max_street, &mut ret);
+        self.apply_history(&saved_history);
+        Ok(ret)
+    }
+
+    fn export_external_strategy_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        max_street: BoardState,
+        result: &mut Vec<ExternalNodeStrategy>,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        if self.is_chance_node() {
+            // the next street is beyond `max_street`
+            if self.current_board().len() - 3 >= max_street as usize {
+                return;
+            }
+
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card);
+                    self.apply_history(history);
+                    self.export_external_strategy_recursive(history, max_street, result);
+                    history.pop();
+                }
+            }
+
+            return;
+        }
+
+        result.push(ExternalNodeStrategy {
+            history: history.clone(),
+            strategy: self.strategy(),
+        });
+
+        for action in 0..self.available_actions().len() {
+            history.push(action);
+            self.apply_history(history);
+            self.export_external_strategy_recursive(history, max_street, result);
+            history.pop();
+        }
+    }
+
+    /// Converts `line` into a history and moves to the node. Chance actions are given as
+    /// [`Action::Chance`].
+    fn line_to_history(&mut self, line: &[Action]) -> Result<Vec<usize>, Error> {
+        self.back_to_root();
+        let mut history = Vec::with_capacity(line.len());
+
+        for &action in line {
+            let index = if self.is_terminal_node() {
+                None
+            } else if self.is_chance_node() {
+                match action {
+                    Action::Chance(card) if self.possible_cards() & (1 << card) != 0 => {
+                        Some(card as usize)
+                    }
+                    _ => None,
+                }
+            } else {
+                self.available_actions().iter().position(|&a| a == action)
+            };
+
+            let index = index
+                .ok_or_else(|| Error::Tree(format!("Line not found in the tree: {line:?}")))?;
+            history.push(index);
+            self.play(index);
+        }
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree(format!(
+                "Line does not lead to a decision node: {line:?}"
+            )));
+        }
+
+        Ok(history)
+    }
+
+    fn lock_external_strategy_internal(
+        &mut self,
+        nodes: &[ExternalNodeStrategy],
+    ) -> Result<(), Error> {
+        for node in nodes {
+            self.apply_history_checked(&node.history)?;
+            let expected =
+                self.available_actions().len() * self.num_private_hands(self.current_player());
+            if node.strategy.len() != expected {
+                return Err(Error::Config(format!(
+                    "Invalid strategy length at {:?}: expected = {expected}, actual = {}",
+                    node.history,
+                    node.strategy.len()
+                )));
+            }
+        }
+
+        for node in nodes {
+            self.apply_history(&node.history);
+            self.lock_current_strategy(&node.strategy);
+        }
+
+        Ok(())
+    }
+
+    /// Moves to the node of `history` from the root, checking that the history is valid and
+    /// leads to a decision node.
+    pub(crate) fn apply_history_checked(&mut self, history: &[usize]) -> Result<(), Error> {
+        self.try_apply_history(history).map_err(|e| match e {
+            Error::Tree(e) => Error::Tree(format!("{e} in history: {history:?}")),
+            e => e,
+        })?;
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree(format!(
+                "History does not lead to a decision node: {history:?}"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "solver")]
+impl Game for UnlockedView<'_> {
+    type Node = PostFlopNode;
+
+    #[inline]
+    fn root(&self) -> MutexGuardLike<Self::Node> {
+        self.game.root()
+    }
+
+    #[inline]
+    fn num_private_hands(&self, player: usize) -> usize {
+        Game::num_private_hands(self.game, player)
+    }
+
+    #[inline]
+    fn initial_weights(&self, player: usize) -> &[f32] {
+        Game::initial_weights(self.game, player)
+    }
+
+    #[inline]
+    fn stored_hand_indices(&self, player: usize) -> &[u16] {
+        self.game.stored_hand_indices(player)
+    }
+
+    #[inline]
+    fn evaluate(
+        &self,
+        result: &mut [MaybeUninit<f32>],
+        node: &Self::Node,
+        player: usize,
+        cfreach: &[f32],
+    ) {
+        self.game.evaluate(result, node, player, cfreach);
+    }
+
+    #[inline]
+    fn chance_factor(&self, node: &Self::Node) -> usize {
+        self.game.chance_factor(node)
+    }
+
+    #[inline]
+    fn is_solved(&self) -> bool {
+        self.game.is_solved()
+    }
+
+    fn set_solved(&mut self) {
+        unreachable!()
+    }
+
+    #[inline]
+    fn is_ready(&self) -> bool {
+        self.game.is_ready()
+    }
+
+    #[inline]
+    fn is_raked(&self) -> bool {
+        self.game.is_raked()
+    }
+
+    #[inline]
+    fn isomorphic_chances(&self, node: &Self::Node) -> &[u8] {
+        self.game.isomorphic_chances(node)
+    }
+
+    #[inline]
+    fn isomorphic_swap(&self, node: &Self::Node, index: usize) -> &[Vec<(u16, u16)>; 2] {
+        self.game.isomorphic_swap(node, index)
+    }
+
+    #[inline]
+    fn locking_strategy(&self, node: &Self::Node) -> &[f32] {
+        if node.player() == self.player {
+            &[]
+        } else {
+            self.game.locking_strategy(node)
+        }
+    }
+
+    #[inline]
+    fn is_compression_enabled(&self) -> bool {
+        self.game.is_compression_enabled()
+    }
+
+    #[inline]
+    fn is_node_compressed(&self, node: &Self::Node) -> bool {
+        self.game.is_node_compressed(node)
+    }
+
+    #[inline]
+    fn is_f64_regrets_enabled(&self) -> bool {
+        self.game.is_f64_regrets_enabled()
+    }
+
+    #[inline]
+    fn enable_parallelization(&self, node: &Self::Node) -> bool {
+        self.game.enable_parallelization(node)
+    }
+
+    #[inline]
+    fn mark_solving(&self) {
+        self.game.mark_solving();
+    }
+
+    #[inline]
+    fn num_nodes_hint(&self) -> usize {
+        self.game.num_nodes_hint()
+    }
+
+    #[inline]
+    fn update_frozen_streets(&self, exploitability: f32) {
+        self.game.update_frozen_streets(exploitability);
+    }
+
+    #[inline]
+    fn is_node_frozen(&self, node: &Self::Node) -> bool {
+        self.game.is_node_frozen(node)
+    }
+}
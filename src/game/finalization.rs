@@ -0,0 +1,193 @@
+use super::*;
+use crate::interface::*;
+use crate::sliceop::*;
+use crate::utility::*;
+use std::collections::HashMap;
+use std::mem::MaybeUninit;
+
+/// Progress of the staged finalization started by [`PostFlopGame::start_finalization`].
+#[derive(Default)]
+pub(super) struct StagedFinalization {
+    /// The subtrees to finalize: the node index of the root and the reach probabilities of the
+    /// opponent of each player at the root.
+    units: Vec<(usize, [Vec<f32>; 2])>,
+
+    /// The number of finalized subtrees.
+    num_finalized: usize,
+
+    /// The counterfactual values of each player at the roots of the finalized subtrees.
+    cfvalues: [HashMap<usize, Vec<f32>>; 2],
+}
+
+impl PostFlopGame {
+    /// Starts finalizing the solving process in steps, as an alternative to [`finalize`].
+    ///
+    /// The game tree is split into the subtrees below the first chance nodes (e.g., one subtree
+    /// for each turn card after each flop line), and each call of [`finalize_step`] finalizes one
+    /// of them; the last call finalizes the remaining nodes above and marks the game as solved.
+    /// Between the steps, the expected values of the nodes in the finalized subtrees can already
+    /// be queried (see [`is_current_node_finalized`]), and [`finalization_progress_percent`]
+    /// reports the progress. This keeps an interactive application responsive: the steps can be
+    /// interleaved with the queries, or run in a worker thread that owns the game.
+    ///
+    /// The strategies must not be changed until the finalization is complete. Panics if the game
+    /// is already solved or is not ready, as [`finalize`] does.
+    ///
+    /// [`finalize`]: crate::finalize
+    /// [`finalize_step`]: #method.finalize_step
+    /// [`is_current_node_finalized`]: #method.is_current_node_finalized
+    /// [`finalization_progress_percent`]: #method.finalization_progress_percent
+    pub fn start_finalization(&mut self) {
+        if self.is_solved() {
+            panic!("Game is already solved");
+        }
+
+        if !self.is_ready() {
+            panic!("Game is not ready");
+        }
+
+        let cfreach = [
+            Game::initial_weights(self, 1).to_vec(),
+            Game::initial_weights(self, 0).to_vec(),
+        ];
+
+        let mut units = Vec::new();
+        self.collect_finalization_units(&self.root(), cfreach, &mut units);
+
+        self.staged_finalization = Some(StagedFinalization {
+            units,
+            ..Default::default()
+        });
+    }
+
+    /// Performs one step of the finalization started by [`start_finalization`] and returns
+    /// whether the finalization is complete, i.e., the game is solved.
+    ///
+    /// Panics if the finalization is not started.
+    ///
+    /// [`start_finalization`]: #method.start_finalization
+    pub fn finalize_step(&mut self) -> bool {
+        let Some(mut staged) = self.staged_finalization.take() else {
+            panic!("Finalization is not started");
+        };
+
+        // finalize the next subtree
+        if staged.num_finalized < staged.units.len() {
+            let (index, cfreach) = &staged.units[staged.num_finalized];
+            for player in 0..2 {
+                let mut node = self.node_arena[*index].lock();
+                let cfvalues =
+                    save_cfvalues_subtree(&*self, &mut node, player, &cfreach[player], &no_memo);
+                staged.cfvalues[player].insert(*index, cfvalues);
+            }
+
+            free_cfvalue_buffer();
+            staged.num_finalized += 1;
+            self.staged_finalization = Some(staged);
+            return false;
+        }
+
+        // finalize the remaining nodes, reusing the values of the finalized subtrees
+        let memo =
+            |node: &PostFlopNode, player: usize, result: &mut [MaybeUninit<f32>]| match staged
+                .cfvalues[player]
+                .get(&self.node_index(node))
+            {
+                Some(cfvalues) => {
+                    result.iter_mut().zip(cfvalues).for_each(|(r, &v)| {
+                        r.write(v);
+                    });
+                    true
+                }
+                None => false,
+            };
+
+        for player in 0..2 {
+            let cfreach = Game::initial_weights(self, player ^ 1);
+            save_cfvalues_subtree(&*self, &mut self.root(), player, cfreach, &memo);
+        }
+
+        free_cfvalue_buffer();
+        self.set_solved();
+        true
+    }
+
+    /// Returns whether the finalization started by [`start_finalization`] is in progress.
+    ///
+    /// [`start_finalization`]: #method.start_finalization
+    #[inline]
+    pub fn is_finalizing(&self) -> bool {
+        self.staged_finalization.is_some()
+    }
+
+    /// Returns the progress of the finalization in percent (0-100).
+    ///
+    /// Returns 100 if the game is solved and 0 if the finalization is not started.
+    #[inline]
+    pub fn finalization_progress_percent(&self) -> u8 {
+        match &self.staged_finalization {
+            Some(staged) => (100 * staged.num_finalized / (staged.units.len() + 1)) as u8,
+            None if self.state == State::Solved => 100,
+            None => 0,
+        }
+    }
+
+    /// Returns whether the expected values of the current node are available.
+    ///
+    /// This is the case if the game is solved (and neither strategy-only nor edited since the
+    /// expected values were computed; see [`edit_strategies`]) or, during the finalization
+    /// started by [`start_finalization`], if the current node is in an already finalized subtree.
+    ///
+    /// [`edit_strategies`]: #method.edit_strategies
+    /// [`start_finalization`]: #method.start_finalization
+    pub fn is_current_node_finalized(&self) -> bool {
+        if self.state == State::Solved {
+            return !self.is_strategy_only && !self.has_pending_strategy_edits;
+        }
+
+        let Some(staged) = &self.staged_finalization else {
+            return false;
+        };
+
+        // the values at a chance node also depend on the decision node above the subtree
+        self.node_history.iter().any(|index| {
+            staged.cfvalues[0].contains_key(index) && !self.node_arena[*index].lock().is_chance()
+        })
+    }
+
+    /// Collects the subtrees below the first chance nodes with the reach probabilities of the
+    /// opponent of each player (of the stored hands).
+    fn collect_finalization_units(
+        &self,
+        node: &PostFlopNode,
+        cfreach: [Vec<f32>; 2],
+        units: &mut Vec<(usize, [Vec<f32>; 2])>,
+    ) {
+        if node.is_terminal() {
+            return;
+        }
+
+        if node.is_chance() {
+            let coef = 1.0 / self.chance_factor(node) as f32;
+            let cfreach = cfreach.map(|reach| reach.iter().map(|&r| r * coef).collect::<Vec<_>>());
+            for action in node.action_indices() {
+                let index = self.node_index(&node.play(action));
+                units.push((index, cfreach.clone()));
+            }
+            return;
+        }
+
+        // `cfreach[p]` is the reach of the opponent of `p`
+        let player = node.player();
+        let num_hands = cfreach[player ^ 1].len();
+        let strategy = (node.num_actions() > 1).then(|| self.stored_node_strategy(node));
+
+        for action in node.action_indices() {
+            let mut cfreach = cfreach.clone();
+            if let Some(strategy) = &strategy {
+                mul_slice(&mut cfreach[player ^ 1], row(strategy, action, num_hands));
+            }
+            self.collect_finalization_units(&node.play(action), cfreach, units);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use super::*;
+use crate::error::*;
+use crate::range::*;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "io")]
+use crate::atomic_file::*;
+
+#[cfg(feature = "io")]
+use std::fs;
+
+#[cfg(feature = "io")]
+use std::io::Write;
+
+#[cfg(feature = "io")]
+use std::path::Path;
+
+/// Per-node strategies of a solved game, captured for regression tests.
+///
+/// Golden files are plain text so that changes show up in diffs: a header line, one `hands`
+/// line for each player, and one `node` line for each decision node containing the history
+/// (`-` for the root) and the strategy in the same layout as [`PostFlopGame::strategy`]. The
+/// probabilities are written in the shortest representation that round-trips, so capturing,
+/// saving, and loading a game is lossless.
+///
+/// This is intended for changes to the numerics of the solver (e.g., SIMD or compression): solve
+/// a small configuration with a fixed number of iterations, and check with
+/// [`check_golden_strategies`] that the strategies match the golden file within a tolerance.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GoldenStrategies {
+    /// The private hands of each player.
+    pub private_cards: [Vec<(Card, Card)>; 2],
+
+    /// The strategies of all decision nodes in depth-first order.
+    pub nodes: Vec<ExternalNodeStrategy>,
+}
+
+const GOLDEN_HEADER: &str = "# postflop-solver golden strategies v1";
+
+impl GoldenStrategies {
+    /// Captures the strategies of all decision nodes of a solved game.
+    ///
+    /// Returns an error if the game is not solved. The current node is restored after the call.
+    pub fn capture(game: &mut PostFlopGame) -> Result<Self, Error> {
+        Ok(Self {
+            private_cards: [0, 1].map(|player| game.private_cards(player).to_vec()),
+            nodes: game.export_external_strategy(BoardState::River)?,
+        })
+    }
+
+    /// Loads golden strategies from the file at `path`.
+    #[cfg(feature = "io")]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|e| Error::io(e, &format!("Failed to read golden file {}", path.display())))?;
+        text.parse()
+    }
+
+    /// Saves the golden strategies to the file at `path`.
+    ///
+    /// The file is replaced atomically, so an interrupted save leaves the previous file intact
+    /// (see [`interrupted_save_path`]).
+    #[cfg(feature = "io")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let err = |e| {
+            Error::io(
+                e,
+                &format!("Failed to write golden file {}", path.display()),
+            )
+        };
+        let mut file = AtomicFile::create(path).map_err(err)?;
+        file.write_all(self.to_string().as_bytes()).map_err(err)?;
+        file.commit().map_err(err)
+    }
+
+    /// Compares `actual` with `self` (the expected strategies).
+    ///
+    /// The hands and the histories of the decision nodes must be identical, and each probability
+    /// must not differ by more than `tolerance`. On success, returns the maximum absolute
+    /// difference of the probabilities. On failure, the error describes the number of differing
+    /// nodes and the largest difference.
+    pub fn compare(&self, actual: &Self, tolerance: f32) -> Result<f32, Error> {
+        for player in 0..2 {
+            if self.private_cards[player] != actual.private_cards[player] {
+                return Err(Error::Verification(format!(
+                    "Hands of player {player} mismatch"
+                )));
+            }
+        }
+
+        if self.nodes.len() != actual.nodes.len() {
+            return Err(Error::Verification(format!(
+                "Number of nodes mismatch: expected = {}, actual = {}",
+                self.nodes.len(),
+                actual.nodes.len()
+            )));
+        }
+
+        let mut max_diff = 0.0f32;
+        let mut worst = None;
+        let mut num_mismatches = 0;
+
+        for (expected, actual) in self.nodes.iter().zip(&actual.nodes) {
+            if expected.history != actual.history {
+                return Err(Error::Verification(format!(
+                    "History mismatch: expected = {:?}, actual = {:?}",
+                    expected.history, actual.history
+                )));
+            }
+
+            if expected.strategy.len() != actual.strategy.len() {
+                return Err(Error::Verification(format!(
+                    "Strategy length mismatch at {:?}: expected = {}, actual = {}",
+                    expected.history,
+                    expected.strategy.len(),
+                    actual.strategy.len()
+                )));
+            }
+
+            let mut is_mismatch = false;
+            for (index, (&e, &a)) in expected.strategy.iter().zip(&actual.strategy).enumerate() {
+                let diff = (e - a).abs();
+                // NaN is always a mismatch
+                if diff.is_nan() || diff > tolerance {
+                    is_mismatch = true;
+                }
+                if diff.is_nan() || diff > max_diff {
+                    max_diff = diff;
+                    worst = Some((&expected.history, index, e, a));
+                }
+            }
+
+            num_mismatches += is_mismatch as usize;
+        }
+
+        if num_mismatches == 0 {
+            return Ok(max_diff);
+        }
+
+        let (history, index, expected, actual) = worst.unwrap();
+        Err(Error::Verification(format!(
+            "Strategies of {num_mismatches} nodes differ by more than {tolerance}: max difference \
+             = {max_diff} at {history:?} (index = {index}, expected = {expected}, actual = \
+             {actual})"
+        )))
+    }
+}
+
+impl fmt::Display for GoldenStrategies {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{GOLDEN_HEADER}")?;
+
+        for (player, hands) in self.private_cards.iter().enumerate() {
+            write!(f, "hands {player}")?;
+            for &hand in hands {
+                let (c1, c2) = (hand.0.max(hand.1), hand.0.min(hand.1));
+                let c1 = card_to_string_with(c1, CardFormat::default()).map_err(|_| fmt::Error)?;
+                let c2 = card_to_string_with(c2, CardFormat::default()).map_err(|_| fmt::Error)?;
+                write!(f, " {c1}{c2}")?;
+            }
+            writeln!(f)?;
+        }
+
+        for node in &self.nodes {
+            if node.history.is_empty() {
+                write!(f, "node -")?;
+            } else {
+                let history = node.history.iter().map(|a| a.to_string());
+                write!(f, "node {}", history.collect::<Vec<_>>().join(","))?;
+            }
+            for &p in &node.strategy {
+                write!(f, " {p}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for GoldenStrategies {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate();
+
+        match lines.next() {
+            Some((_, line)) if line.trim_end() == GOLDEN_HEADER => {}
+            _ => return Err(Error::invalid_data("Missing golden file header")),
+        }
+
+        let mut ret = Self::default();
+        let mut num_hand_lines = 0;
+
+        for (line_number, line) in lines {
+            let err = |message: &str| {
+                Error::invalid_data(format!("{message} (line {})", line_number + 1))
+            };
+            let mut tokens = line.split_whitespace();
+
+            match tokens.next() {
+                None => continue,
+                Some("hands") => {
+                    let player = tokens.next().and_then(|t| t.parse::<usize>().ok());
+                    if num_hand_lines >= 2 || player != Some(num_hand_lines) {
+                        return Err(err("Unexpected hands line"));
+                    }
+                    for token in tokens {
+                        let mut chars = token.chars();
+                        let c1 = card_from_chars(&mut chars).map_err(|e| err(&e.to_string()))?;
+                        let c2 = card_from_chars(&mut chars).map_err(|e| err(&e.to_string()))?;
+                        if chars.next().is_some() {
+                            return Err(err(&format!("Invalid hand: {token}")));
+                        }
+                        ret.private_cards[num_hand_lines].push((c1.min(c2), c1.max(c2)));
+                    }
+                    num_hand_lines += 1;
+                }
+                Some("node") => {
+                    if num_hand_lines != 2 {
+                        return Err(err("Node line before hands lines"));
+                    }
+                    let history = match tokens.next() {
+                        Some("-") => Vec::new(),
+                        Some(history) => history
+                            .split(',')
+                            .map(|a| a.parse::<usize>())
+                            .collect::<Result<_, _>>()
+                            .map_err(|_| err(&format!("Invalid history: {history}")))?,
+                        None => return Err(err("Missing history")),
+                    };
+                    let strategy = tokens
+                        .map(|p| p.parse::<f32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| err("Invalid probability"))?;
+                    ret.nodes.push(ExternalNodeStrategy { history, strategy });
+                }
+                Some(token) => return Err(err(&format!("Unexpected token: {token}"))),
+            }
+        }
+
+        if num_hand_lines != 2 {
+            return Err(Error::invalid_data("Missing hands lines"));
+        }
+
+        Ok(ret)
+    }
+}
+
+/// Checks the strategies of a solved game against the golden file at `path`.
+///
+/// If `update` is `true`, the golden file is (re)written from `game` instead, so that a golden
+/// file can be regenerated after an intentional change. Otherwise, the golden file must exist
+/// and the strategies are compared with [`GoldenStrategies::compare`]; the maximum absolute
+/// difference is returned on success.
+///
+/// Returns an error if the game is not solved, the golden file cannot be read or written, or
+/// the strategies do not match within `tolerance`.
+#[cfg(feature = "io")]
+pub fn check_golden_strategies<P: AsRef<Path>>(
+    game: &mut PostFlopGame,
+    path: P,
+    tolerance: f32,
+    update: bool,
+) -> Result<f32, Error> {
+    let actual = GoldenStrategies::capture(game)?;
+
+    if update {
+        actual.save(path)?;
+        return Ok(0.0);
+    }
+
+    GoldenStrategies::load(path)?.compare(&actual, tolerance)
+}
@@ -1,5 +1,8 @@
+use super::cache::DecodedKind;
 use super::*;
+use crate::error::*;
 use crate::interface::*;
+use crate::range::*;
 use crate::sliceop::*;
 use crate::utility::*;
 
@@ -31,6 +34,7 @@ impl PostFlopGame {
         self.weights[0].copy_from_slice(&self.initial_weights[0]);
         self.weights[1].copy_from_slice(&self.initial_weights[1]);
         self.assign_zero_weights();
+        self.prefetch_below_current_node();
     }
 
     /// Returns the history of the current node.
@@ -67,6 +71,25 @@ impl PostFlopGame {
         }
     }
 
+    /// Applies the given history from the root node like [`apply_history`], returning an error
+    /// instead of panicking.
+    ///
+    /// Returns an error if the memory is not yet allocated or `history` contains an invalid action
+    /// (see [`try_play`]). On error, the current node is left at the last valid node of `history`.
+    ///
+    /// [`apply_history`]: #method.apply_history
+    /// [`try_play`]: #method.try_play
+    pub fn try_apply_history(&mut self, history: &[usize]) -> Result<(), Error> {
+        self.check_operation(GameOperation::Navigate)?;
+
+        self.back_to_root();
+        for &action in history {
+            self.try_play(action)?;
+        }
+
+        Ok(())
+    }
+
     /// Returns whether the current node is a terminal node.
     ///
     /// Note that the turn/river node after the call action after the all-in action is considered
@@ -225,6 +248,109 @@ impl PostFlopGame {
         ((1 << 52) - 1) ^ dead_mask
     }
 
+    /// If the current node is a chance node, returns the cards that can be dealt in ascending
+    /// order.
+    ///
+    /// This is the list version of [`possible_cards`]: the board cards, the cards that leave no
+    /// valid pair of private hands (including the effect of bunching), and the dead cards are
+    /// removed. Unlike [`available_actions`], the cards isomorphic to another card are included;
+    /// use [`chance_card_representative`] to find the card whose subtree is shared. If the
+    /// current node is not a chance node, an empty list is returned.
+    ///
+    /// [`possible_cards`]: #method.possible_cards
+    /// [`available_actions`]: #method.available_actions
+    /// [`chance_card_representative`]: #method.chance_card_representative
+    pub fn possible_chance_cards(&self) -> Vec<Card> {
+        let possible_cards = self.possible_cards();
+        (0..52)
+            .filter(|&card| possible_cards & (1 << card) != 0)
+            .collect()
+    }
+
+    /// Returns the card whose subtree is used when `card` is dealt at the current chance node.
+    ///
+    /// The returned card differs from `card` if `card` is suit-isomorphic to another card or
+    /// abstracted into the representative card of its bucket. Returns `None` if the current node
+    /// is not a chance node or `card` cannot be dealt.
+    pub fn chance_card_representative(&self, card: Card) -> Option<Card> {
+        if card >= 52 || self.possible_cards() & (1 << card) == 0 {
+            return None;
+        }
+
+        let (_, action_index, _) = self.resolve_chance(card);
+        let representative = match self.available_actions().get(action_index) {
+            Some(&Action::Chance(representative)) => representative,
+            _ => return None,
+        };
+
+        // map back to the actual suits if swapping was performed in turn
+        Some(match self.turn_swapped_suit {
+            Some((suit1, suit2)) if representative & 3 == suit1 => representative - suit1 + suit2,
+            Some((suit1, suit2)) if representative & 3 == suit2 => representative + suit1 - suit2,
+            _ => representative,
+        })
+    }
+
+    /// Resolves the card dealt at the current chance node into the child node.
+    ///
+    /// Returns the card after applying the suit swap of the turn, the index of the child node
+    /// (`usize::MAX` if not found), and the index in the list of isomorphic chances if the card
+    /// is represented by an isomorphic card.
+    fn resolve_chance(&self, actual_card: Card) -> (Card, usize, Option<usize>) {
+        let is_turn = self.turn == NOT_DEALT;
+
+        // swap the suit if swapping was performed in turn
+        let action_card = if let Some((suit1, suit2)) = self.turn_swapped_suit {
+            if actual_card & 3 == suit1 {
+                actual_card - suit1 + suit2
+            } else if actual_card & 3 == suit2 {
+                actual_card + suit1 - suit2
+            } else {
+                actual_card
+            }
+        } else {
+            actual_card
+        };
+
+        // the turn card may be abstracted into a representative card of its bucket; in that
+        // case, the representative card cannot be dealt on the river, so the actual turn card
+        // is used instead
+        let node = self.node();
+        let action_card = if !is_turn
+            && self.turn_swap.is_none()
+            && node.turn != self.turn
+            && action_card == node.turn
+        {
+            self.turn
+        } else {
+            action_card
+        };
+
+        // find the action index from available actions
+        let actions = self.available_actions();
+        if let Some(i) = actions
+            .iter()
+            .position(|&action| action == Action::Chance(action_card))
+        {
+            return (action_card, i, None);
+        }
+
+        // find the action index from isomorphic chances
+        let isomorphism = self.isomorphic_chances(&node);
+        let isomorphic_cards = if node.turn == NOT_DEALT {
+            &self.isomorphism_card_turn
+        } else {
+            &self.isomorphism_card_river[node.turn as usize & 3]
+        };
+        for (i, &repr_index) in isomorphism.iter().enumerate() {
+            if action_card == isomorphic_cards[i] {
+                return (action_card, repr_index as usize, Some(i));
+            }
+        }
+
+        (action_card, usize::MAX, None)
+    }
+
     /// Returns the current player (0 = OOP, 1 = IP).
     ///
     /// If the current node is a terminal node or a chance node, returns an undefined value.
@@ -257,6 +383,126 @@ impl PostFlopGame {
         ret
     }
 
+    /// Returns the street of the current node, determined by the number of cards on the current
+    /// board.
+    ///
+    /// At a chance node, the card of the next street is not yet dealt, so the previous street is
+    /// returned.
+    #[inline]
+    pub fn current_street(&self) -> BoardState {
+        match self.current_board().len() {
+            3 => BoardState::Flop,
+            4 => BoardState::Turn,
+            _ => BoardState::River,
+        }
+    }
+
+    /// Deals the given card (e.g., `"Jd"`) at the current chance node.
+    ///
+    /// This is a fallible version of [`play`] for chance nodes. Returns an error if the memory is
+    /// not yet allocated, the current node is not a chance node, or the card cannot be dealt
+    /// (see [`possible_cards`]).
+    ///
+    /// [`play`]: #method.play
+    /// [`possible_cards`]: #method.possible_cards
+    pub fn apply_chance(&mut self, card: &str) -> Result<(), Error> {
+        self.check_operation(GameOperation::Navigate)?;
+
+        if !self.is_chance_node() {
+            return Err(Error::Tree("Current node is not a chance node".to_string()));
+        }
+
+        let is_turn = self.turn == NOT_DEALT;
+        if self.storage_mode == BoardState::Flop
+            || (!is_turn && self.storage_mode == BoardState::Turn)
+        {
+            return Err(Error::Config("Storage mode is not compatible".to_string()));
+        }
+
+        let card_id = card_from_str(card)?;
+        if self.possible_cards() & (1 << card_id) == 0 {
+            return Err(Error::Tree(format!("Card cannot be dealt: {card}")));
+        }
+
+        self.play(card_id as usize);
+        Ok(())
+    }
+
+    /// Plays the given action, which is specified by its value instead of its index.
+    ///
+    /// This is a fallible version of [`play`]. If the current node is a chance node, `action` must
+    /// be `Action::Chance(card)` where `card` can be dealt (see [`apply_chance`]); otherwise,
+    /// `action` must be one of [`available_actions`]. Returns an error if the memory is not yet
+    /// allocated, the current node is a terminal node, or the action is not available.
+    ///
+    /// [`play`]: #method.play
+    /// [`apply_chance`]: #method.apply_chance
+    /// [`available_actions`]: #method.available_actions
+    pub fn play_action(&mut self, action: Action) -> Result<(), Error> {
+        self.check_operation(GameOperation::Navigate)?;
+
+        if self.is_terminal_node() {
+            return Err(Error::Tree("Terminal node is not allowed".to_string()));
+        }
+
+        if self.is_chance_node() {
+            return match action {
+                Action::Chance(card) => self.apply_chance(&card_to_string(card)?),
+                _ => Err(Error::Tree(format!(
+                    "Current node is a chance node: {action:?}"
+                ))),
+            };
+        }
+
+        match self.available_actions().iter().position(|&a| a == action) {
+            Some(index) => {
+                self.play(index);
+                Ok(())
+            }
+            None => Err(Error::Tree(format!("Action is not available: {action:?}"))),
+        }
+    }
+
+    /// Plays the given action like [`play`], returning an error instead of panicking.
+    ///
+    /// Returns an error if the memory is not yet allocated, the current node is a terminal node,
+    /// the storage mode is not compatible with the chance node, or `action` is invalid. As in
+    /// [`play`], `usize::MAX` selects the possible card with the lowest index at a chance node.
+    ///
+    /// [`play`]: #method.play
+    pub fn try_play(&mut self, action: usize) -> Result<(), Error> {
+        self.check_operation(GameOperation::Navigate)?;
+
+        if self.is_terminal_node() {
+            return Err(Error::Tree("Terminal node is not allowed".to_string()));
+        }
+
+        if self.is_chance_node() {
+            let is_turn = self.turn == NOT_DEALT;
+            if self.storage_mode == BoardState::Flop
+                || (!is_turn && self.storage_mode == BoardState::Turn)
+            {
+                return Err(Error::Config("Storage mode is not compatible".to_string()));
+            }
+
+            let possible_cards = self.possible_cards();
+            let card = if action == usize::MAX {
+                possible_cards.trailing_zeros() as usize
+            } else {
+                action
+            };
+
+            if card >= 64 || possible_cards & (1 << card) == 0 {
+                return Err(Error::Tree(format!("Card cannot be dealt: {action}")));
+            }
+        } else if action >= self.node().num_actions() {
+            return Err(Error::Tree(format!("Invalid action: {action}")));
+        }
+
+        self.play(action);
+        Ok(())
+    }
+
     /// Plays the given action. Playing an action from a terminal node is not allowed.
     ///
     /// - `action`
@@ -266,11 +512,13 @@ impl PostFlopGame {
     ///   - If the current node is not a chance node, plays the `action`-th action of
     ///     [`available_actions`].
     ///
-    /// Panics if the memory is not yet allocated or the current node is a terminal node.
+    /// Panics if the memory is not yet allocated or the current node is a terminal node; use
+    /// [`try_play`] to handle these cases.
     ///
     /// **Time complexity:** *O*(#(OOP private hands) + #(IP private hands))
     ///
     /// [`available_actions`]: #method.available_actions
+    /// [`try_play`]: #method.try_play
     pub fn play(&mut self, action: usize) {
         if self.state < State::MemoryAllocated {
             panic!("Memory is not allocated");
@@ -295,59 +543,32 @@ impl PostFlopGame {
                 action as Card
             };
 
-            // swap the suit if swapping was performed in turn
-            let action_card = if let Some((suit1, suit2)) = self.turn_swapped_suit {
-                if actual_card & 3 == suit1 {
-                    actual_card - suit1 + suit2
-                } else if actual_card & 3 == suit2 {
-                    actual_card + suit1 - suit2
-                } else {
-                    actual_card
-                }
-            } else {
-                actual_card
-            };
-
-            let actions = self.available_actions();
-            let mut action_index = usize::MAX;
+            let (action_card, action_index, isomorphic_index) = self.resolve_chance(actual_card);
 
-            // find the action index from available actions
-            for (i, &action) in actions.iter().enumerate() {
-                if action == Action::Chance(action_card) {
-                    action_index = i;
-                    break;
-                }
-            }
-
-            // find the action index from isomorphic chances
-            if action_index == usize::MAX {
-                let node = self.node();
-                let isomorphism = self.isomorphic_chances(&node);
-                let isomorphic_cards = if node.turn == NOT_DEALT {
-                    &self.isomorphism_card_turn
-                } else {
-                    &self.isomorphism_card_river[node.turn as usize & 3]
-                };
-                for (i, &repr_index) in isomorphism.iter().enumerate() {
-                    if action_card == isomorphic_cards[i] {
-                        action_index = repr_index as usize;
-                        if is_turn {
-                            if let Action::Chance(repr_card) = actions[repr_index as usize] {
-                                self.turn_swapped_suit = Some((action_card & 3, repr_card & 3));
-                            }
-                            self.turn_swap = Some(action_card & 3);
-                        } else {
-                            // `self.turn != self.node().turn` if `self.turn_swap.is_some()`.
-                            // This is possible only when the flop is monotone.
-                            // In this case, there is only one suit that can be swapped and the
-                            // following code works correctly.
-                            self.river_swap = Some((
-                                self.turn & 3,
-                                self.isomorphism_card_river[self.turn as usize & 3][i] & 3,
-                            ));
-                        }
-                        break;
+            // update the suit swaps if the card is isomorphic to the representative card
+            if let Some(i) = isomorphic_index {
+                if is_turn && i >= self.num_isomorphic_turn {
+                    // abstracted turn card: no suit swap is needed
+                } else if is_turn {
+                    if let Action::Chance(repr_card) = self.available_actions()[action_index] {
+                        self.turn_swapped_suit = Some((action_card & 3, repr_card & 3));
                     }
+                    self.turn_swap = Some(action_card & 3);
+                } else {
+                    // `self.turn != self.node().turn` if `self.turn_swap.is_some()`.
+                    // This is possible only when the flop is monotone.
+                    // In this case, there is only one suit that can be swapped and the
+                    // following code works correctly.
+                    // If the turn card is abstracted, `self.turn != self.node().turn`
+                    // holds and the representative card should be used.
+                    let turn = match self.turn_swap {
+                        Some(_) => self.turn,
+                        None => self.node().turn,
+                    };
+                    self.river_swap = Some((
+                        turn & 3,
+                        self.isomorphism_card_river[turn as usize & 3][i] & 3,
+                    ));
                 }
             }
 
@@ -388,14 +609,17 @@ impl PostFlopGame {
 
             // cache the counterfactual values
             let node = self.node();
-            let vec = if self.is_compression_enabled {
-                let slice = row(node.cfvalues_compressed(), action, num_hands);
-                let scale = node.cfvalue_scale();
-                decode_signed_slice(slice, scale)
-            } else {
-                row(node.cfvalues(), action, num_hands).to_vec()
-            };
-            self.cfvalues_cache[player].copy_from_slice(&vec);
+            if !self.is_strategy_only {
+                let num_stored_hands = self.num_stored_hands(player);
+                let vec = if self.is_node_compressed(&node) {
+                    let vec = self.decoded_cfvalues(&node);
+                    row(&vec, action, num_stored_hands).to_vec()
+                } else {
+                    row(node.cfvalues(), action, num_stored_hands).to_vec()
+                };
+                let vec = self.expand_stored_hands(vec, player);
+                self.cfvalues_cache[player].copy_from_slice(&vec);
+            }
 
             // update the bet amounts
             let node = self.node();
@@ -421,6 +645,7 @@ impl PostFlopGame {
 
         self.action_history.push(action);
         self.is_normalized_weight_cached = false;
+        self.prefetch_below_current_node();
     }
 
     /// Computes the normalized weights and caches them.
@@ -649,7 +874,8 @@ impl PostFlopGame {
 
     /// Returns the expected values of each private hand of the given player.
     ///
-    /// Panics if the game is not solved.
+    /// Panics if the game is not solved and the current node is not finalized (see
+    /// [`is_current_node_finalized`]).
     ///
     /// After mutating the current node, you must call the [`cache_normalized_weights`] method
     /// before calling this method.
@@ -658,8 +884,9 @@ impl PostFlopGame {
     ///
     /// [`cache_normalized_weights`]: #method.cache_normalized_weights
     /// [`expected_values_detail`]: #method.expected_values_detail
+    /// [`is_current_node_finalized`]: #method.is_current_node_finalized
     pub fn expected_values(&self, player: usize) -> Vec<f32> {
-        if self.state != State::Solved {
+        if !self.is_current_node_finalized() {
             panic!("Game is not solved");
         }
 
@@ -699,7 +926,8 @@ impl PostFlopGame {
     /// Otherwise, this method is the same as the [`expected_values`] method, so the return vector
     /// is the length of `#(private hands)`.
     ///
-    /// Panics if the game is not solved.
+    /// Panics if the game is not solved and the current node is not finalized (see
+    /// [`is_current_node_finalized`]).
     ///
     /// After mutating the current node, you must call the [`cache_normalized_weights`] method
     /// before calling this method.
@@ -710,8 +938,9 @@ impl PostFlopGame {
     ///
     /// [`expected_values`]: #method.expected_value
     /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`is_current_node_finalized`]: #method.is_current_node_finalized
     pub fn expected_values_detail(&self, player: usize) -> Vec<f32> {
-        if self.state != State::Solved {
+        if !self.is_current_node_finalized() {
             panic!("Game is not solved");
         }
 
@@ -743,34 +972,36 @@ impl PostFlopGame {
             let mut ret = Vec::with_capacity(num_hands);
             let mut cfreach = self.weights[player ^ 1].clone();
             self.apply_swap(&mut cfreach, player ^ 1, true);
-            self.evaluate(ret.spare_capacity_mut(), &node, player, &cfreach);
+            let rake = self.terminal_rake(&node);
+            self.evaluate_with_rake(ret.spare_capacity_mut(), &node, player, &cfreach, rake);
             unsafe { ret.set_len(num_hands) };
             ret
         } else if node.is_chance() && node.cfvalue_storage_player() == Some(player) {
-            if self.is_compression_enabled {
+            let vec = if self.is_node_compressed(&node) {
                 let slice = node.cfvalues_chance_compressed();
                 let scale = node.cfvalue_chance_scale();
                 decode_signed_slice(slice, scale)
             } else {
                 node.cfvalues_chance().to_vec()
-            }
+            };
+            self.expand_stored_hands(vec, player)
         } else if node.has_cfvalues_ip() && player == PLAYER_IP as usize {
-            if self.is_compression_enabled {
+            let vec = if self.is_node_compressed(&node) {
                 let slice = node.cfvalues_ip_compressed();
                 let scale = node.cfvalue_ip_scale();
                 decode_signed_slice(slice, scale)
             } else {
                 node.cfvalues_ip().to_vec()
-            }
+            };
+            self.expand_stored_hands(vec, player)
         } else if player == self.current_player() {
             have_actions = true;
-            if self.is_compression_enabled {
-                let slice = node.cfvalues_compressed();
-                let scale = node.cfvalue_scale();
-                decode_signed_slice(slice, scale)
+            let vec = if self.is_node_compressed(&node) {
+                self.decoded_cfvalues(&node)
             } else {
                 node.cfvalues().to_vec()
-            }
+            };
+            self.expand_stored_hands(vec, player)
         } else {
             self.cfvalues_cache[player].to_vec()
         };
@@ -827,17 +1058,9 @@ impl PostFlopGame {
 
         let node = self.node();
         let player = self.current_player();
-        let num_actions = node.num_actions();
         let num_hands = self.num_private_hands(player);
 
-        let mut ret = if self.is_compression_enabled {
-            normalized_strategy_compressed(node.strategy_compressed(), num_actions)
-        } else {
-            normalized_strategy(node.strategy(), num_actions)
-        };
-
-        let locking = self.locking_strategy(&node);
-        apply_locking_strategy(&mut ret, locking);
+        let mut ret = self.node_strategy(&node);
 
         ret.chunks_exact_mut(num_hands).for_each(|chunk| {
             self.apply_swap(chunk, player, false);
@@ -846,12 +1069,182 @@ impl PostFlopGame {
         ret
     }
 
+    /// Returns the index of the private hand consisting of `card1` and `card2` in
+    /// [`private_cards`], or `None` if the hand is not in the range of the `player`.
+    ///
+    /// The order of the two cards does not matter.
+    ///
+    /// [`private_cards`]: #method.private_cards
+    #[inline]
+    pub fn private_hand_index(&self, player: usize, card1: Card, card2: Card) -> Option<usize> {
+        let hand = (card1.min(card2), card1.max(card2));
+        self.private_cards(player).binary_search(&hand).ok()
+    }
+
+    /// Returns the strategy of the current player for the private hand consisting of `card1` and
+    /// `card2`, i.e., the probability of each action of [`available_actions`].
+    ///
+    /// Returns an error if the memory is not yet allocated, the current node is a terminal node or
+    /// a chance node, or the hand is not in the range of the current player or overlaps with the
+    /// board.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands)).
+    ///
+    /// [`available_actions`]: #method.available_actions
+    pub fn strategy_for_hand(&self, card1: Card, card2: Card) -> Result<Vec<f32>, Error> {
+        self.check_operation(GameOperation::QueryStrategy)?;
+
+        if self.is_terminal_node() {
+            return Err(Error::Tree("Terminal node is not allowed".to_string()));
+        }
+
+        if self.is_chance_node() {
+            return Err(Error::Tree("Chance node is not allowed".to_string()));
+        }
+
+        let player = self.current_player();
+        let index = self.hand_index_checked(player, card1, card2)?;
+        let num_hands = self.num_private_hands(player);
+
+        let strategy = self.strategy();
+        Ok(strategy[index..]
+            .iter()
+            .step_by(num_hands)
+            .copied()
+            .collect())
+    }
+
+    /// Returns the expected value of the private hand consisting of `card1` and `card2` of the
+    /// `player` (see [`expected_values`]).
+    ///
+    /// Returns an error if the current node is not finalized, the normalized weights are not
+    /// cached, or the hand is not in the range of the `player` or overlaps with the board.
+    ///
+    /// [`expected_values`]: #method.expected_values
+    pub fn expected_value_for_hand(
+        &self,
+        player: usize,
+        card1: Card,
+        card2: Card,
+    ) -> Result<f32, Error> {
+        self.check_operation(GameOperation::QueryExpectedValues)?;
+
+        if !self.is_normalized_weight_cached {
+            return Err(Error::Config(
+                "Normalized weights are not cached".to_string(),
+            ));
+        }
+
+        let index = self.hand_index_checked(player, card1, card2)?;
+        Ok(self.expected_values(player)[index])
+    }
+
+    /// Returns the index of the private hand, checking that it does not overlap with the board.
+    fn hand_index_checked(&self, player: usize, card1: Card, card2: Card) -> Result<usize, Error> {
+        let Some(index) = self.private_hand_index(player, card1, card2) else {
+            let hand = hole_to_string((card1, card2))?;
+            return Err(Error::Card(format!("Hand is not in the range: {hand}")));
+        };
+
+        let board = self.current_board();
+        if board.contains(&card1) || board.contains(&card2) {
+            let hand = hole_to_string((card1, card2))?;
+            return Err(Error::Card(format!("Hand overlaps with the board: {hand}")));
+        }
+
+        Ok(index)
+    }
+
+    /// Recomputes the expected values of all nodes by traversing the game tree with the current
+    /// strategies.
+    ///
+    /// Only the strategies are needed, so this makes a strategy-only save (i.e., one with the
+    /// target storage mode [`BoardState::River`], which omits the counterfactual values) fully
+    /// analyzable again. Loading such a file calls this method automatically. The current node is
+    /// preserved.
+    ///
+    /// For a strategy-only game (see [`is_strategy_only`]), the storage of the counterfactual
+    /// values is allocated first.
+    ///
+    /// Returns an error if the game is not solved or the storage mode is not
+    /// [`BoardState::River`] (the strategies of the later streets are not available).
+    ///
+    /// [`is_strategy_only`]: #method.is_strategy_only
+    pub fn recompute_evs(&mut self) -> Result<(), Error> {
+        self.check_operation(GameOperation::RecomputeEvs)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        if self.is_strategy_only {
+            self.allocate_cfvalue_storage()?;
+        }
+
+        save_cfvalues(self);
+        self.has_pending_strategy_edits = false;
+        self.lock_decoded_cache().clear();
+
+        let history = self.action_history.clone();
+        self.apply_history(&history);
+
+        Ok(())
+    }
+
+    /// Returns whether the game is loaded without the counterfactual values (see
+    /// [`load_strategy_only_from_file`]).
+    ///
+    /// The strategies of a strategy-only game can be navigated, and the exploitability can be
+    /// computed with [`compute_exploitability`], but the expected values are not available until
+    /// [`recompute_evs`] is called.
+    ///
+    /// [`load_strategy_only_from_file`]: crate::load_strategy_only_from_file
+    /// [`compute_exploitability`]: crate::compute_exploitability
+    /// [`recompute_evs`]: #method.recompute_evs
+    #[inline]
+    pub fn is_strategy_only(&self) -> bool {
+        self.is_strategy_only
+    }
+
     /// Returns the total bet amount of each player (OOP, IP).
     #[inline]
     pub fn total_bet_amount(&self) -> [i32; 2] {
         self.total_bet_amount
     }
 
+    /// Returns the pot size at the current node, including the bet that has not been called yet.
+    #[inline]
+    pub fn pot(&self) -> i32 {
+        self.tree_config.starting_pot + 2 * self.node().amount + self.amount_to_call()
+    }
+
+    /// Returns the remaining stack of each player (OOP, IP) at the current node.
+    #[inline]
+    pub fn remaining_stacks(&self) -> [i32; 2] {
+        let amount = self.node().amount;
+        let bet = self.total_bet_amount;
+        [0, 1].map(|player| {
+            let uncalled = (bet[player] - bet[player ^ 1]).max(0);
+            self.tree_config.effective_stack - amount - uncalled
+        })
+    }
+
+    /// Returns the amount that the player to act needs to call at the current node, i.e., the
+    /// difference between the bet amounts of the two players (`0` if there is no bet to call).
+    #[inline]
+    pub fn amount_to_call(&self) -> i32 {
+        (self.total_bet_amount[0] - self.total_bet_amount[1]).abs()
+    }
+
+    /// Returns the stack-to-pot ratio (SPR) at the current node, i.e., the effective stack
+    /// divided by the pot after the bet to call (if any) is called.
+    #[inline]
+    pub fn spr(&self) -> f64 {
+        let stacks = self.remaining_stacks();
+        let effective_stack = stacks[0].min(stacks[1]);
+        effective_stack as f64 / (self.pot() + self.amount_to_call()) as f64
+    }
+
     /// Locks the strategy of the current node.
     ///
     /// The `strategy` argument must be a slice of the length of `#(actions) * #(private hands)`.
@@ -865,24 +1258,30 @@ impl PostFlopGame {
     ///   strategy of the `j`-th private hand.
     ///
     /// This method must be called after allocating memory and before solving the game.
-    /// Panics if the memory is not yet allocated or the game is already solved.
-    /// Also, panics if the current node is a terminal node or a chance node.
+    /// Panics if the memory is not yet allocated, the game is already solved, or the finalization
+    /// is in progress. Also, panics if the current node is a terminal node or a chance node, or
+    /// the length of `strategy` is invalid; use [`try_lock_current_strategy`] to handle these
+    /// cases.
+    ///
+    /// [`try_lock_current_strategy`]: #method.try_lock_current_strategy
     pub fn lock_current_strategy(&mut self, strategy: &[f32]) {
-        if self.state < State::MemoryAllocated {
-            panic!("Memory is not allocated");
-        }
-
-        if self.state == State::Solved {
-            panic!("Game is already solved");
-        }
-
-        if self.is_terminal_node() {
-            panic!("Terminal node is not allowed");
-        }
+        self.try_lock_current_strategy(strategy)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
 
-        if self.is_chance_node() {
-            panic!("Chance node is not allowed");
-        }
+    /// Locks the strategy of the current node like [`lock_current_strategy`], returning an error
+    /// instead of panicking.
+    ///
+    /// Returns [`Error::State`] if locking is not valid for the current state of the game (see
+    /// [`check_operation`]), and returns an error if the current node is not a decision node,
+    /// the length of `strategy` is invalid, or the locked strategies of the hands merged by the
+    /// suit reduction differ (see [`is_suit_reduced`]).
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`check_operation`]: #method.check_operation
+    /// [`is_suit_reduced`]: #method.is_suit_reduced
+    pub fn try_lock_current_strategy(&mut self, strategy: &[f32]) -> Result<(), Error> {
+        self.check_locking_node()?;
 
         let mut node = self.node();
         let player = self.current_player();
@@ -890,7 +1289,11 @@ impl PostFlopGame {
         let num_hands = self.num_private_hands(player);
 
         if strategy.len() != num_actions * num_hands {
-            panic!("Invalid strategy length");
+            return Err(Error::Config(format!(
+                "Invalid strategy length: expected {}, got {}",
+                num_actions * num_hands,
+                strategy.len()
+            )));
         }
 
         let mut locking = vec![-1.0; num_actions * num_hands];
@@ -919,42 +1322,59 @@ impl PostFlopGame {
             self.apply_swap(chunk, player, true);
         });
 
+        let locking = self.reduce_stored_hands(&locking, player)?;
+
         node.is_locked = true;
         let index = self.node_index(&node);
         self.locking_strategy.insert(index, locking);
+        Ok(())
     }
 
     /// Unlocks the strategy of the current node.
     ///
     /// This method must be called after allocating memory and before solving the game.
-    /// Panics if the memory is not yet allocated or the game is already solved.
-    /// Also, panics if the current node is a terminal node or a chance node.
+    /// Panics if the memory is not yet allocated, the game is already solved, or the finalization
+    /// is in progress. Also, panics if the current node is a terminal node or a chance node; use
+    /// [`try_unlock_current_strategy`] to handle these cases.
+    ///
+    /// [`try_unlock_current_strategy`]: #method.try_unlock_current_strategy
     #[inline]
     pub fn unlock_current_strategy(&mut self) {
-        if self.state < State::MemoryAllocated {
-            panic!("Memory is not allocated");
-        }
-
-        if self.state == State::Solved {
-            panic!("Game is already solved");
-        }
-
-        if self.is_terminal_node() {
-            panic!("Terminal node is not allowed");
-        }
+        self.try_unlock_current_strategy()
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
 
-        if self.is_chance_node() {
-            panic!("Chance node is not allowed");
-        }
+    /// Unlocks the strategy of the current node like [`unlock_current_strategy`], returning an
+    /// error instead of panicking.
+    ///
+    /// [`unlock_current_strategy`]: #method.unlock_current_strategy
+    pub fn try_unlock_current_strategy(&mut self) -> Result<(), Error> {
+        self.check_locking_node()?;
 
         let mut node = self.node();
         if !node.is_locked {
-            return;
+            return Ok(());
         }
 
         node.is_locked = false;
         let index = self.node_index(&node);
         self.locking_strategy.remove(&index);
+        Ok(())
+    }
+
+    /// Checks that the locking strategy of the current node can be modified.
+    fn check_locking_node(&self) -> Result<(), Error> {
+        self.check_operation(GameOperation::LockStrategy)?;
+
+        if self.is_terminal_node() {
+            return Err(Error::Tree("Terminal node is not allowed".to_string()));
+        }
+
+        if self.is_chance_node() {
+            return Err(Error::Tree("Chance node is not allowed".to_string()));
+        }
+
+        Ok(())
     }
 
     /// Returns the locking strategy of the current node.
@@ -982,8 +1402,8 @@ impl PostFlopGame {
 
         let index = self.node_index(&self.node());
         self.locking_strategy.get(&index).map(|s| {
-            let mut ret = s.clone();
             let player = self.current_player();
+            let mut ret = self.expand_stored_hands(s.clone(), player);
             let num_hands = self.num_private_hands(player);
             ret.chunks_exact_mut(num_hands).for_each(|chunk| {
                 self.apply_swap(chunk, player, false);
@@ -992,9 +1412,189 @@ impl PostFlopGame {
         })
     }
 
+    /// Locks the strategy of `player` at the node specified by `history`.
+    ///
+    /// This is a convenience wrapper of [`lock_current_strategy`] that does not move the current
+    /// node: the `strategy` argument has the same layout and semantics. The solver keeps the
+    /// locked hands on the given strategy, does not accumulate their regrets, and still propagates
+    /// the reach probabilities through the locked node, so the rest of the tree converges to the
+    /// maximally exploitative adjustment.
+    ///
+    /// Returns an error if the memory is not allocated, the game is already solved, the
+    /// finalization is in progress, `history` is invalid or does not lead to a decision node of
+    /// `player`, or the length of `strategy` is invalid.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    pub fn lock_strategy(
+        &mut self,
+        history: &[usize],
+        player: usize,
+        strategy: &[f32],
+    ) -> Result<(), Error> {
+        self.with_locking_node(history, |game| {
+            if game.current_player() != player {
+                return Err(Error::Tree(format!(
+                    "Node is not a decision node of player {player}"
+                )));
+            }
+
+            game.try_lock_current_strategy(strategy)
+        })
+    }
+
+    /// Unlocks the strategy at the node specified by `history` without moving the current node.
+    ///
+    /// Returns an error if the memory is not allocated, the game is already solved, the
+    /// finalization is in progress, or `history` is invalid or does not lead to a decision node.
+    pub fn unlock_strategy(&mut self, history: &[usize]) -> Result<(), Error> {
+        self.with_locking_node(history, Self::try_unlock_current_strategy)
+    }
+
+    /// Moves to the node specified by `history`, checks that the locking strategy can be modified
+    /// there, calls `f`, and restores the current node.
+    fn with_locking_node(
+        &mut self,
+        history: &[usize],
+        f: impl FnOnce(&mut Self) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.check_operation(GameOperation::LockStrategy)?;
+
+        let current_history = self.history().to_vec();
+        let ret = self.apply_history_checked(history).and_then(|()| f(self));
+        self.apply_history(&current_history);
+        ret
+    }
+
+    /// Sets the equity realization of each player (OOP, IP) at all showdown nodes reachable from
+    /// the current node.
+    ///
+    /// At a modified showdown node, the winner takes `realization[winner]` of the (raked) pot and
+    /// the loser takes the rest; a tie splits the sum of the two players' shares evenly. For
+    /// example, `[0.9, 1.0]` encodes an OOP player who realizes only 90% of the raw equity, e.g.,
+    /// after being checked back. `[1.0, 1.0]` restores the normal payoffs.
+    ///
+    /// This method must be called after allocating memory and before solving the game.
+    /// Panics if the memory is not yet allocated, the game is already solved, or the realization
+    /// is not in the range `[0.0, 1.0]`.
+    pub fn set_showdown_realization(&mut self, realization: [f64; 2]) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.state == State::Solved {
+            panic!("Game is already solved");
+        }
+
+        if realization.iter().any(|r| !(0.0..=1.0).contains(r)) {
+            panic!("Realization must be in the range [0.0, 1.0]");
+        }
+
+        let mut indices = Vec::new();
+        self.collect_showdown_nodes(&mut indices, &self.node());
+
+        for index in indices {
+            if realization == [1.0, 1.0] {
+                self.showdown_realization.remove(&index);
+            } else {
+                self.showdown_realization.insert(index, realization);
+            }
+        }
+    }
+
+    /// Returns the equity realization of each player (OOP, IP) at the current node.
+    ///
+    /// Panics if the memory is not yet allocated or the current node is not a showdown node.
+    #[inline]
+    pub fn current_showdown_realization(&self) -> [f64; 2] {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        let node = self.node();
+        if node.player & PLAYER_FOLD_FLAG != PLAYER_TERMINAL_FLAG {
+            panic!("Current node is not a showdown node");
+        }
+
+        let index = self.node_index(&node);
+        self.showdown_realization
+            .get(&index)
+            .copied()
+            .unwrap_or([1.0, 1.0])
+    }
+
+    /// Collects the indices of the showdown nodes in the subtree rooted at the given node.
+    fn collect_showdown_nodes(&self, indices: &mut Vec<usize>, node: &PostFlopNode) {
+        if node.player & PLAYER_FOLD_FLAG == PLAYER_TERMINAL_FLAG {
+            indices.push(self.node_index(node));
+            return;
+        }
+
+        if node.is_terminal() {
+            return;
+        }
+
+        if node.is_chance() {
+            let is_turn = node.turn == NOT_DEALT;
+            if self.storage_mode == BoardState::Flop
+                || (!is_turn && self.storage_mode == BoardState::Turn)
+            {
+                return;
+            }
+        }
+
+        for child in node.children() {
+            self.collect_showdown_nodes(indices, &child.lock());
+        }
+    }
+
+    /// Returns the normalized strategy of the given node, expanded to all private hands
+    /// (isomorphic swaps are not applied).
+    #[inline]
+    pub(super) fn node_strategy(&self, node: &PostFlopNode) -> Vec<f32> {
+        self.cached_decode(node, DecodedKind::Strategy, || {
+            self.decode_node_strategy(node)
+        })
+    }
+
+    /// Same as [`node_strategy`], but always decodes the storage without using the cache.
+    ///
+    /// [`node_strategy`]: #method.node_strategy
+    pub(super) fn decode_node_strategy(&self, node: &PostFlopNode) -> Vec<f32> {
+        let strategy = self.stored_node_strategy(node);
+        self.expand_stored_hands(strategy, node.player())
+    }
+
+    /// Same as [`decode_node_strategy`], but does not expand the stored hands (see
+    /// [`num_stored_hands`]).
+    ///
+    /// [`decode_node_strategy`]: #method.decode_node_strategy
+    /// [`num_stored_hands`]: #method.num_stored_hands
+    pub(super) fn stored_node_strategy(&self, node: &PostFlopNode) -> Vec<f32> {
+        let num_actions = node.num_actions();
+
+        let mut ret = if self.is_node_compressed(node) {
+            normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+        } else {
+            normalized_strategy(node.strategy(), num_actions)
+        };
+
+        let locking = self.locking_strategy(node);
+        apply_locking_strategy(&mut ret, locking);
+        ret
+    }
+
+    /// Returns the decoded counterfactual values of the player to act at the given node,
+    /// assuming the storage of the node is compressed (the private hands are not expanded).
+    #[inline]
+    fn decoded_cfvalues(&self, node: &PostFlopNode) -> Vec<f32> {
+        self.cached_decode(node, DecodedKind::CfValues, || {
+            decode_signed_slice(node.cfvalues_compressed(), node.cfvalue_scale())
+        })
+    }
+
     /// Returns the reference to the current node.
     #[inline]
-    fn node(&self) -> MutexGuardLike<PostFlopNode> {
+    pub(super) fn node(&self) -> MutexGuardLike<PostFlopNode> {
         self.node_arena[self.node_history.last().cloned().unwrap_or(0)].lock()
     }
 
@@ -1081,9 +1681,43 @@ impl PostFlopGame {
         }
     }
 
+    /// Returns the total amount that `player` has committed at the current node, including half
+    /// of the starting pot.
+    pub(crate) fn committed_amount(&self, player: usize) -> i32 {
+        let total_bet_amount = self.total_bet_amount();
+        let bias = (total_bet_amount[player] - total_bet_amount[player ^ 1]).max(0);
+        self.tree_config.starting_pot / 2 + self.node().amount + bias
+    }
+
+    /// Returns the index of the bet or raise action whose resulting commitment of `player` is
+    /// closest to `target`. The current node must be reached by `history`, which is reapplied
+    /// after probing each action.
+    pub(crate) fn closest_bet_action(
+        &mut self,
+        history: &[usize],
+        player: usize,
+        target: f64,
+    ) -> Option<usize> {
+        let actions = self.available_actions();
+        let mut best = None;
+        let mut best_distance = f64::INFINITY;
+        for (i, action) in actions.iter().enumerate() {
+            if matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_)) {
+                self.play(i);
+                let distance = (self.committed_amount(player) as f64 - target).abs();
+                self.apply_history(history);
+                if distance < best_distance {
+                    best = Some(i);
+                    best_distance = distance;
+                }
+            }
+        }
+        best
+    }
+
     /// Applies the swap.
     #[inline]
-    fn apply_swap(&self, slice: &mut [f32], player: usize, reverse: bool) {
+    pub(super) fn apply_swap(&self, slice: &mut [f32], player: usize, reverse: bool) {
         let turn_swap = self
             .turn_swap
             .map(|suit| &self.isomorphism_swap_turn[suit as usize][player]);
@@ -1115,7 +1749,20 @@ impl PostFlopGame {
         amount: f64,
     ) {
         let pair_index = card_pair_to_index(turn, river);
-        let hand_strength = &self.hand_strength[pair_index];
+        let amount = amount / self.num_showdown_rankings() as f64;
+        for hand_strength in self.showdown_rankings(pair_index) {
+            self.equity_internal_ranking(result, player, hand_strength, amount);
+        }
+    }
+
+    /// Internal method for calculating the equity with the given ranking.
+    fn equity_internal_ranking(
+        &self,
+        result: &mut [f64],
+        player: usize,
+        hand_strength: &[Vec<StrengthItem>; 2],
+        amount: f64,
+    ) {
         let player_strength = &hand_strength[player];
         let opponent_strength = &hand_strength[player ^ 1];
 
@@ -0,0 +1,148 @@
+use super::*;
+use crate::interface::*;
+use std::sync::MutexGuard;
+
+impl PostFlopGame {
+    /// Allocates the memory lazily: the storage of each node is allocated when the solver first
+    /// visits the node with a counterfactual reach probability greater than `reach_threshold`.
+    ///
+    /// The solver skips the subtree of an unallocated node while all the reach probabilities of
+    /// the opponent are at most `reach_threshold`, so the subtrees that are never reached, e.g.,
+    /// behind actions locked to zero frequency (see [`lock_current_strategy`]), do not consume
+    /// memory. With `reach_threshold == 0.0`, the skipped subtrees have no effect on the
+    /// counterfactual values; a positive threshold trades accuracy for memory. An unallocated node
+    /// behaves as freshly allocated (i.e., the uniform strategy and zero expected values), and the
+    /// average strategy of a node only accumulates the iterations after its allocation.
+    ///
+    /// The memory allocated so far is reported by [`lazy_memory_usage`]. Saving the game, the
+    /// archetype analysis, and the quantization are not supported.
+    ///
+    /// Panics if the game is not successfully initialized, or `reach_threshold` is negative or not
+    /// finite.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`lazy_memory_usage`]: #method.lazy_memory_usage
+    pub fn allocate_memory_lazy(&mut self, enable_compression: bool, reach_threshold: f32) {
+        if !reach_threshold.is_finite() || reach_threshold < 0.0 {
+            panic!("Threshold must be non-negative and finite: {reach_threshold}");
+        }
+
+        let compression = enable_compression.then_some(BoardState::Flop);
+        self.allocate_memory_internal(compression, false, Some(reach_threshold))
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Returns whether the memory is allocated lazily (see [`allocate_memory_lazy`]).
+    ///
+    /// [`allocate_memory_lazy`]: #method.allocate_memory_lazy
+    #[inline]
+    pub fn is_lazy_allocation_enabled(&self) -> bool {
+        self.is_lazy_allocation
+    }
+
+    /// Returns the memory usage in bytes of the node storage allocated lazily so far, or `0` if
+    /// the memory is not allocated lazily.
+    pub fn lazy_memory_usage(&self) -> u64 {
+        let buffers = self.lock_lazy_storage();
+        let num_words = buffers
+            .iter()
+            .fold(self.lazy_zeros.len(), |sum, b| sum + b.len());
+        8 * num_words as u64
+    }
+
+    /// Returns the threshold of the lazy allocation, or `None` if the memory is not allocated
+    /// lazily.
+    #[inline]
+    pub(super) fn lazy_threshold(&self) -> Option<f32> {
+        self.is_lazy_allocation.then_some(self.lazy_reach_threshold)
+    }
+
+    /// Points the storage of all nodes to the shared zero buffer.
+    pub(super) fn init_lazy_storage(&mut self, reach_threshold: f32) {
+        let mut max_bytes = 0;
+        for node in &self.node_arena {
+            let node = node.lock();
+            if !node.is_terminal() {
+                let bytes = self.node_storage_bytes(&node);
+                max_bytes = max_bytes.max(bytes[0].max(bytes[1]).max(bytes[2]));
+            }
+        }
+
+        self.is_lazy_allocation = true;
+        self.lazy_reach_threshold = reach_threshold;
+        self.lazy_zeros = vec![0; max_bytes.div_ceil(8) + 1];
+
+        let ptr = self.lazy_zeros_ptr();
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            if !node.is_terminal() {
+                node.storage1 = ptr;
+                node.storage2 = ptr;
+                node.storage3 = ptr;
+            }
+        }
+    }
+
+    /// Releases the lazily allocated storage.
+    pub(super) fn clear_lazy_storage(&mut self) {
+        self.is_lazy_allocation = false;
+        self.lazy_reach_threshold = 0.0;
+        self.lazy_zeros = Vec::new();
+        self.lock_lazy_storage().clear();
+    }
+
+    /// Returns the pointer to the shared zero buffer, which is never written.
+    #[inline]
+    pub(super) fn lazy_zeros_ptr(&self) -> *mut u8 {
+        self.lazy_zeros.as_ptr() as *mut u8
+    }
+
+    /// Allocates the storage of `node` if the reach probabilities exceed the threshold. Returns
+    /// whether the storage is allocated.
+    pub(super) fn allocate_node_lazily(&self, node: &mut PostFlopNode, cfreach: &[f32]) -> bool {
+        if cfreach.iter().all(|&r| r <= self.lazy_reach_threshold) {
+            return false;
+        }
+
+        // each region is aligned to 8 bytes
+        let [words1, words2, words3] = self.node_storage_bytes(node).map(|b| b.div_ceil(8));
+        let mut buffer = vec![0u64; words1 + words2 + words3];
+
+        unsafe {
+            let ptr = buffer.as_mut_ptr() as *mut u8;
+            node.storage1 = ptr;
+            node.storage2 = ptr.add(8 * words1);
+            node.storage3 = ptr.add(8 * (words1 + words2));
+        }
+
+        // moving `buffer` does not move its heap allocation
+        self.lock_lazy_storage().push(buffer);
+        true
+    }
+
+    /// Returns the sizes of the three storages of `node` in bytes.
+    #[inline]
+    fn node_storage_bytes(&self, node: &PostFlopNode) -> [usize; 3] {
+        let num_bytes = if self.is_node_compressed(node) { 2 } else { 4 };
+        let num_bytes_regrets = if self.is_f64_regrets_enabled {
+            8
+        } else {
+            num_bytes
+        };
+        let num_elements = node.num_elements as usize;
+        if node.is_chance() {
+            [num_bytes * num_elements, 0, 0]
+        } else {
+            [
+                num_bytes * num_elements,
+                num_bytes_regrets * num_elements,
+                num_bytes * node.num_elements_ip as usize,
+            ]
+        }
+    }
+
+    #[inline]
+    fn lock_lazy_storage(&self) -> MutexGuard<Vec<Vec<u64>>> {
+        self.lazy_storage.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,504 @@
+use super::*;
+use crate::error::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::mem;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Strategy of a decision node in a [`FlopStrategyTable`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TableNode {
+    /// The line leading to the node, including [`Action::Chance`] for the dealt cards.
+    pub line: Vec<Action>,
+
+    /// The player to act.
+    pub player: u8,
+
+    /// The number of actions at the node.
+    pub num_actions: u8,
+
+    /// The strategy quantized to 8 bits (`probability * 255`), in the same layout as
+    /// [`PostFlopGame::strategy`] over [`FlopStrategyTable::private_cards`].
+    pub data: Vec<u8>,
+}
+
+/// Strategies of all decision nodes of a solved flop, keyed by lines instead of node indices so
+/// that solves of different flops can be compared.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FlopStrategyTable {
+    /// The flop.
+    pub flop: [Card; 3],
+
+    /// The private hands of each player.
+    pub private_cards: [Vec<(Card, Card)>; 2],
+
+    /// The decision nodes in depth-first order.
+    pub nodes: Vec<TableNode>,
+}
+
+/// A node of a [`FlopLibrary`] entry.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub enum EncodedNode {
+    /// The node has no counterpart in the reference and is stored as is.
+    Raw(TableNode),
+
+    /// The node is encoded against the node of the reference with the same line.
+    Delta {
+        /// The index of the node in the reference table.
+        reference_node: u32,
+
+        /// The `(index, value)` pairs of the entries differing from the reference by more than
+        /// the tolerance.
+        patches: Vec<(u32, u8)>,
+    },
+}
+
+/// A flop stored in a [`FlopLibrary`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct LibraryEntry {
+    /// The flop.
+    pub flop: [Card; 3],
+
+    /// The index of the reference table in [`FlopLibrary::references`].
+    pub reference: u32,
+
+    /// The private hands of each player.
+    pub private_cards: [Vec<(Card, Card)>; 2],
+
+    /// The encoded nodes (empty for the reference flop itself).
+    pub nodes: Vec<EncodedNode>,
+}
+
+/// A library of solved flops that stores one reference solve per texture cluster and
+/// delta-encodes the other flops of the cluster against it.
+///
+/// Neighboring flops share most of their strategy structure, so most entries of a delta-encoded
+/// flop are within the tolerance of the reference and are not stored. Decoding is lossy: each
+/// probability of a decoded table differs from the original quantized value by at most the
+/// tolerance.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct FlopLibrary {
+    /// The tolerance of the quantized probabilities (`0` means lossless).
+    pub tolerance: u8,
+
+    /// The reference tables.
+    pub references: Vec<FlopStrategyTable>,
+
+    /// The entries of all flops, including the references.
+    pub entries: Vec<LibraryEntry>,
+}
+
+/// Texture of a flop used as the default clustering key of [`FlopLibrary::build`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FlopTexture {
+    /// The number of distinct suits (1: monotone, 2: two-tone, 3: rainbow).
+    pub num_suits: u8,
+
+    /// The number of distinct ranks (1: trips, 2: paired, 3: unpaired).
+    pub num_ranks: u8,
+
+    /// The rank of the highest card (0: deuce, ..., 12: ace).
+    pub high_rank: u8,
+
+    /// Whether the flop contains three cards within a five-rank window (i.e., a straight draw
+    /// with two hole cards is possible).
+    pub is_connected: bool,
+}
+
+impl FlopTexture {
+    /// Computes the texture of the flop.
+    pub fn new(flop: &[Card; 3]) -> Self {
+        let mut ranks = flop.map(|card| card >> 2);
+        ranks.sort_unstable();
+
+        let mut suits = flop.map(|card| card & 3);
+        suits.sort_unstable();
+
+        let count_distinct = |v: &[u8; 3]| 1 + (v[0] != v[1]) as u8 + (v[1] != v[2]) as u8;
+        let num_ranks = count_distinct(&ranks);
+
+        // the ace also plays as the lowest card
+        let mut mask = 0u16;
+        for &rank in &ranks {
+            mask |= 1 << (rank + 1);
+            if rank == 12 {
+                mask |= 1;
+            }
+        }
+        let is_connected =
+            num_ranks == 3 && (0..10).any(|low| ((mask >> low) & 0x1f).count_ones() >= 3);
+
+        Self {
+            num_suits: count_distinct(&suits),
+            num_ranks,
+            high_rank: ranks[2],
+            is_connected,
+        }
+    }
+}
+
+impl PostFlopGame {
+    /// Exports the strategies of all decision nodes into a [`FlopStrategyTable`].
+    ///
+    /// The isomorphic chances are expanded, so every node of the original game is included.
+    /// The current node is restored after the call. Returns an error if the game is not solved
+    /// or is not rooted at the flop.
+    pub fn export_strategy_table(&mut self) -> Result<FlopStrategyTable, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.tree_config.initial_state != BoardState::Flop {
+            return Err(Error::Config("Game must be rooted at the flop".to_string()));
+        }
+
+        let saved_history = self.history().to_vec();
+        let mut table = FlopStrategyTable {
+            flop: self.card_config.flop,
+            private_cards: [
+                self.private_cards(0).to_vec(),
+                self.private_cards(1).to_vec(),
+            ],
+            nodes: Vec::new(),
+        };
+
+        self.back_to_root();
+        let mut history = Vec::new();
+        let mut line = Vec::new();
+        self.export_table_recursive(&mut history, &mut line, &mut table.nodes);
+        self.apply_history(&saved_history);
+
+        Ok(table)
+    }
+
+    fn export_table_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+        nodes: &mut Vec<TableNode>,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        if self.is_chance_node() {
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card as usize);
+                    line.push(Action::Chance(card));
+                    self.apply_history(history);
+                    self.export_table_recursive(history, line, nodes);
+                    line.pop();
+                    history.pop();
+                }
+            }
+            return;
+        }
+
+        let actions = self.available_actions();
+        nodes.push(TableNode {
+            line: line.clone(),
+            player: self.current_player() as u8,
+            num_actions: actions.len() as u8,
+            data: quantize(&self.strategy()),
+        });
+
+        for (i, &action) in actions.iter().enumerate() {
+            history.push(i);
+            line.push(action);
+            self.apply_history(history);
+            self.export_table_recursive(history, line, nodes);
+            line.pop();
+            history.pop();
+        }
+    }
+}
+
+impl FlopStrategyTable {
+    /// Returns the node with the given line, if any.
+    pub fn node(&self, line: &[Action]) -> Option<&TableNode> {
+        self.nodes.iter().find(|node| node.line == line)
+    }
+
+    /// Returns the dequantized strategy of the node with the given line, if any.
+    pub fn strategy(&self, line: &[Action]) -> Option<Vec<f32>> {
+        let node = self.node(line)?;
+        Some(node.data.iter().map(|&q| q as f32 / 255.0).collect())
+    }
+
+    /// Returns the size of the strategy data in bytes.
+    pub fn memory_usage(&self) -> u64 {
+        let hands = self.private_cards.iter().map(Vec::len).sum::<usize>();
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                mem::size_of::<TableNode>()
+                    + node.line.len() * mem::size_of::<Action>()
+                    + node.data.len()
+            })
+            .sum::<usize>();
+        (hands * mem::size_of::<(Card, Card)>() + nodes) as u64
+    }
+}
+
+impl FlopLibrary {
+    /// Builds a library clustering the flops by [`FlopTexture`].
+    ///
+    /// See [`build_with`](#method.build_with) for details.
+    pub fn build(tables: Vec<FlopStrategyTable>, tolerance: u8) -> Result<Self, Error> {
+        Self::build_with(tables, tolerance, FlopTexture::new)
+    }
+
+    /// Builds a library clustering the flops by the given key.
+    ///
+    /// The first table of each cluster becomes the reference of the cluster. Returns an error
+    /// if a flop appears more than once.
+    pub fn build_with<K, F>(
+        tables: Vec<FlopStrategyTable>,
+        tolerance: u8,
+        cluster_key: F,
+    ) -> Result<Self, Error>
+    where
+        K: Eq + Hash,
+        F: Fn(&[Card; 3]) -> K,
+    {
+        let mut library = Self {
+            tolerance,
+            ..Default::default()
+        };
+
+        let mut clusters = HashMap::new();
+        for table in tables {
+            let mut sorted_flop = table.flop;
+            sorted_flop.sort_unstable();
+            if library.entries.iter().any(|entry| {
+                let mut flop = entry.flop;
+                flop.sort_unstable();
+                flop == sorted_flop
+            }) {
+                return Err(Error::Config(format!("Duplicate flop: {:?}", table.flop)));
+            }
+
+            match clusters.get(&cluster_key(&table.flop)) {
+                Some(&reference) => {
+                    let entry = library.encode(&table, reference);
+                    library.entries.push(entry);
+                }
+                None => {
+                    let reference = library.references.len();
+                    clusters.insert(cluster_key(&table.flop), reference);
+                    library.entries.push(LibraryEntry {
+                        flop: table.flop,
+                        reference: reference as u32,
+                        private_cards: Default::default(),
+                        nodes: Vec::new(),
+                    });
+                    library.references.push(table);
+                }
+            }
+        }
+
+        Ok(library)
+    }
+
+    /// Returns the number of flops in the library.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the library is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Decodes the table of the given flop (the order of the cards does not matter). Returns
+    /// `None` if the flop is not in the library.
+    pub fn get(&self, flop: [Card; 3]) -> Option<FlopStrategyTable> {
+        let mut sorted_flop = flop;
+        sorted_flop.sort_unstable();
+        let entry = self.entries.iter().find(|entry| {
+            let mut flop = entry.flop;
+            flop.sort_unstable();
+            flop == sorted_flop
+        })?;
+
+        let reference = &self.references[entry.reference as usize];
+        if entry.nodes.is_empty() && reference.flop == entry.flop {
+            return Some(reference.clone());
+        }
+
+        let hand_map = hand_map(&entry.private_cards, &reference.private_cards);
+        let nodes = entry
+            .nodes
+            .iter()
+            .map(|node| match node {
+                EncodedNode::Raw(node) => node.clone(),
+                EncodedNode::Delta {
+                    reference_node,
+                    patches,
+                } => {
+                    let ref_node = &reference.nodes[*reference_node as usize];
+                    let player = ref_node.player as usize;
+                    let num_hands = entry.private_cards[player].len();
+                    let ref_num_hands = reference.private_cards[player].len();
+                    let mut data = (0..ref_node.num_actions as usize * num_hands)
+                        .map(|i| {
+                            let (action, hand) = (i / num_hands, i % num_hands);
+                            hand_map[player][hand]
+                                .map_or(0, |h| ref_node.data[action * ref_num_hands + h])
+                        })
+                        .collect::<Vec<_>>();
+                    for &(index, value) in patches {
+                        data[index as usize] = value;
+                    }
+                    TableNode {
+                        line: ref_node.line.clone(),
+                        player: ref_node.player,
+                        num_actions: ref_node.num_actions,
+                        data,
+                    }
+                }
+            })
+            .collect();
+
+        Some(FlopStrategyTable {
+            flop: entry.flop,
+            private_cards: entry.private_cards.clone(),
+            nodes,
+        })
+    }
+
+    /// Returns the size of the stored data in bytes.
+    pub fn memory_usage(&self) -> u64 {
+        let references = self
+            .references
+            .iter()
+            .map(FlopStrategyTable::memory_usage)
+            .sum::<u64>();
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let hands = entry.private_cards.iter().map(Vec::len).sum::<usize>();
+                let nodes = entry
+                    .nodes
+                    .iter()
+                    .map(|node| match node {
+                        EncodedNode::Raw(node) => {
+                            node.line.len() * mem::size_of::<Action>() + node.data.len()
+                        }
+                        EncodedNode::Delta { patches, .. } => {
+                            patches.len() * mem::size_of::<(u32, u8)>()
+                        }
+                    })
+                    .sum::<usize>();
+                (mem::size_of::<LibraryEntry>()
+                    + hands * mem::size_of::<(Card, Card)>()
+                    + entry.nodes.len() * mem::size_of::<EncodedNode>()
+                    + nodes) as u64
+            })
+            .sum::<u64>();
+        references + entries
+    }
+
+    /// Encodes `table` against the reference table of the given index.
+    fn encode(&self, table: &FlopStrategyTable, reference: usize) -> LibraryEntry {
+        let ref_table = &self.references[reference];
+        let ref_nodes = ref_table
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.line.as_slice(), i))
+            .collect::<BTreeMap<_, _>>();
+        let hand_map = hand_map(&table.private_cards, &ref_table.private_cards);
+
+        let nodes = table
+            .nodes
+            .iter()
+            .map(|node| {
+                let ref_index = match ref_nodes.get(node.line.as_slice()) {
+                    Some(&i) => i,
+                    None => return EncodedNode::Raw(node.clone()),
+                };
+
+                let ref_node = &ref_table.nodes[ref_index];
+                if ref_node.player != node.player || ref_node.num_actions != node.num_actions {
+                    return EncodedNode::Raw(node.clone());
+                }
+
+                let player = node.player as usize;
+                let num_hands = table.private_cards[player].len();
+                let ref_num_hands = ref_table.private_cards[player].len();
+                let patches = node
+                    .data
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, &value)| {
+                        let (action, hand) = (i / num_hands, i % num_hands);
+                        match hand_map[player][hand] {
+                            Some(h) => {
+                                let ref_value = ref_node.data[action * ref_num_hands + h];
+                                value.abs_diff(ref_value) > self.tolerance
+                            }
+                            None => value != 0,
+                        }
+                    })
+                    .map(|(i, &value)| (i as u32, value))
+                    .collect();
+
+                EncodedNode::Delta {
+                    reference_node: ref_index as u32,
+                    patches,
+                }
+            })
+            .collect();
+
+        LibraryEntry {
+            flop: table.flop,
+            reference: reference as u32,
+            private_cards: table.private_cards.clone(),
+            nodes,
+        }
+    }
+}
+
+/// Quantizes the probabilities to 8 bits.
+#[inline]
+fn quantize(strategy: &[f32]) -> Vec<u8> {
+    strategy
+        .iter()
+        .map(|&p| (p.clamp(0.0, 1.0) * 255.0).round() as u8)
+        .collect()
+}
+
+/// Maps each hand of `hands` to the index of the same hand in `ref_hands`.
+fn hand_map(
+    hands: &[Vec<(Card, Card)>; 2],
+    ref_hands: &[Vec<(Card, Card)>; 2],
+) -> [Vec<Option<usize>>; 2] {
+    [0, 1].map(|player| {
+        let index = ref_hands[player]
+            .iter()
+            .enumerate()
+            .map(|(i, &hand)| (hand, i))
+            .collect::<HashMap<_, _>>();
+        hands[player]
+            .iter()
+            .map(|hand| index.get(hand).copied())
+            .collect()
+    })
+}
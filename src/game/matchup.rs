@@ -0,0 +1,213 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::sliceop::*;
+use crate::utility::*;
+use std::mem::MaybeUninit;
+
+/// Computes the expected values when the OOP player follows the strategy of `oop_game` and the IP
+/// player follows the strategy of `ip_game`.
+///
+/// The values are computed exactly by traversing the game tree, so two solves can be compared
+/// head-to-head (e.g., solves with different solver parameters, or different bet sizes whose
+/// differing lines were removed with [`PostFlopGame::remove_lines`]). Passing the same game twice
+/// gives the expected values of its own strategy.
+///
+/// Both games must have the same game tree, board, ranges, starting pot, and rake, and the
+/// strategies must be stored up to the river. The showdowns are evaluated with `oop_game`, so
+/// both games are expected to use the same evaluator. The locked strategies of each game are
+/// applied to its own player.
+///
+/// The return value is the expected value of each player in chips, including its share of the
+/// starting pot (i.e., the values sum to the starting pot if not raked).
+pub fn compute_matchup_ev(
+    oop_game: &PostFlopGame,
+    ip_game: &PostFlopGame,
+) -> Result<[f32; 2], Error> {
+    let games = [oop_game, ip_game];
+
+    for game in games {
+        game.check_operation(GameOperation::QueryStrategy)?;
+        if game.is_finalizing() {
+            let state = GameState::Finalizing;
+            let operation = GameOperation::QueryStrategy;
+            return Err(StateError { operation, state }.into());
+        }
+
+        if game.storage_mode != BoardState::River {
+            return Err(Error::Config(
+                "Strategies are not stored up to the river".to_string(),
+            ));
+        }
+    }
+
+    check_same_game(oop_game, ip_game)?;
+
+    let nodes = [
+        &*oop_game.node_arena[0].lock(),
+        &*ip_game.node_arena[0].lock(),
+    ];
+    let bias = oop_game.tree_config.starting_pot as f64 * 0.5;
+
+    Ok([0, 1].map(|player| {
+        let reach = &oop_game.initial_weights;
+        let mut cfvalues = Vec::with_capacity(oop_game.num_private_hands(player));
+        matchup_recursive(
+            cfvalues.spare_capacity_mut(),
+            games,
+            nodes,
+            player,
+            &reach[player ^ 1],
+        );
+        unsafe { cfvalues.set_len(oop_game.num_private_hands(player)) };
+
+        let sum = Summation::Compensated.weighted_sum(&cfvalues, &reach[player]);
+        (sum + bias) as f32
+    }))
+}
+
+/// Checks that the two games can be traversed together.
+fn check_same_game(game1: &PostFlopGame, game2: &PostFlopGame) -> Result<(), Error> {
+    let (card1, card2) = (&game1.card_config, &game2.card_config);
+    if card1.flop != card2.flop || card1.turn != card2.turn || card1.river != card2.river {
+        return Err(Error::Config("Boards do not match".to_string()));
+    }
+
+    if game1.private_cards != game2.private_cards
+        || game1.initial_weights != game2.initial_weights
+        || game1.bunching_num_dead_cards != game2.bunching_num_dead_cards
+    {
+        return Err(Error::Config("Ranges do not match".to_string()));
+    }
+
+    let (tree1, tree2) = (&game1.tree_config, &game2.tree_config);
+    if tree1.starting_pot != tree2.starting_pot
+        || tree1.rake_rate != tree2.rake_rate
+        || tree1.rake_cap != tree2.rake_cap
+    {
+        return Err(Error::Config(
+            "Starting pots or rakes do not match".to_string(),
+        ));
+    }
+
+    let is_same_tree = game1.turn_buckets == game2.turn_buckets
+        && game1.node_arena.len() == game2.node_arena.len()
+        && game1
+            .node_arena
+            .iter()
+            .zip(&game2.node_arena)
+            .all(|(node1, node2)| {
+                let (node1, node2) = (node1.lock(), node2.lock());
+                node1.prev_action == node2.prev_action
+                    && node1.player == node2.player
+                    && node1.amount == node2.amount
+                    && node1.num_children == node2.num_children
+                    && node1.children_offset == node2.children_offset
+            });
+
+    if !is_same_tree {
+        return Err(Error::Config("Game trees do not match".to_string()));
+    }
+
+    Ok(())
+}
+
+/// The recursive helper function of [`compute_matchup_ev`], computing the counterfactual values
+/// of `player` where each player follows the strategy of the corresponding game.
+fn matchup_recursive(
+    result: &mut [MaybeUninit<f32>],
+    games: [&PostFlopGame; 2],
+    nodes: [&PostFlopNode; 2],
+    player: usize,
+    cfreach: &[f32],
+) {
+    let node = nodes[0];
+
+    // terminal node
+    if node.is_terminal() {
+        let rake = games[0].terminal_rake(node);
+        games[0].evaluate_with_rake(result, node, player, cfreach, rake);
+        return;
+    }
+
+    let num_actions = node.num_actions();
+    let num_hands = result.len();
+    let cfv_actions = MutexLike::new(Vec::with_capacity(num_actions * num_hands));
+
+    let recurse = |action: usize, cfreach: &[f32]| {
+        let children = [nodes[0].play(action), nodes[1].play(action)];
+        matchup_recursive(
+            row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
+            games,
+            [&children[0], &children[1]],
+            player,
+            cfreach,
+        );
+    };
+
+    // chance node
+    if node.is_chance() {
+        let mut cfreach_updated = Vec::with_capacity(cfreach.len());
+        mul_slice_scalar_uninit(
+            cfreach_updated.spare_capacity_mut(),
+            cfreach,
+            1.0 / games[0].chance_factor(node) as f32,
+        );
+        unsafe { cfreach_updated.set_len(cfreach.len()) };
+
+        for_each_child(games[0], node, |action| recurse(action, &cfreach_updated));
+
+        let mut cfv_actions = cfv_actions.lock();
+        unsafe { cfv_actions.set_len(num_actions * num_hands) };
+        let mut result_f64 = Vec::with_capacity(num_hands);
+        sum_slices_f64_uninit(result_f64.spare_capacity_mut(), &cfv_actions);
+        unsafe { result_f64.set_len(num_hands) };
+
+        // process isomorphic chances
+        let isomorphic_chances = games[0].isomorphic_chances(node);
+        for (i, &isomorphic_index) in isomorphic_chances.iter().enumerate() {
+            let swap_list = &games[0].isomorphic_swap(node, i)[player];
+            let tmp = row_mut(&mut cfv_actions, isomorphic_index as usize, num_hands);
+
+            apply_swap(tmp, swap_list);
+            result_f64.iter_mut().zip(&*tmp).for_each(|(r, &v)| {
+                *r += v as f64;
+            });
+            apply_swap(tmp, swap_list);
+        }
+
+        result.iter_mut().zip(&result_f64).for_each(|(r, &v)| {
+            r.write(v as f32);
+        });
+        return;
+    }
+
+    // the acting player follows the strategy of the corresponding game
+    let acting = node.player();
+    let strategy = games[acting].decode_node_strategy(nodes[acting]);
+
+    // player node
+    if acting == player {
+        for_each_child(games[0], node, |action| recurse(action, cfreach));
+
+        let mut cfv_actions = cfv_actions.lock();
+        unsafe { cfv_actions.set_len(num_actions * num_hands) };
+        fma_slices_uninit(result, &strategy, &cfv_actions);
+    }
+    // opponent node
+    else {
+        let mut cfreach_actions = strategy;
+        let row_size = cfreach.len();
+        cfreach_actions.chunks_exact_mut(row_size).for_each(|row| {
+            mul_slice(row, cfreach);
+        });
+
+        for_each_child(games[0], node, |action| {
+            recurse(action, row(&cfreach_actions, action, row_size))
+        });
+
+        let mut cfv_actions = cfv_actions.lock();
+        unsafe { cfv_actions.set_len(num_actions * num_hands) };
+        sum_slices_uninit(result, &cfv_actions);
+    }
+}
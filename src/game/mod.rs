@@ -1,18 +1,192 @@
+mod allin_cache;
 mod base;
+mod cache;
+mod canonical;
+mod edit;
 mod evaluation;
+mod export;
+mod external;
+mod finalization;
+mod golden;
 mod interpreter;
+mod lazy;
+mod library;
 mod node;
+mod nodes;
+mod parallel;
+mod prefetch;
+mod prune;
+mod quantize;
+mod reset;
+mod runout_values;
+mod snapshot;
+mod state;
+mod street_targets;
+mod subtree;
+mod symmetry;
+mod table;
+mod target_nodes;
+mod translation;
+
+#[cfg(feature = "analysis")]
+mod aggregate;
+
+#[cfg(feature = "analysis")]
+mod analysis;
+
+#[cfg(feature = "analysis")]
+mod archetype;
+
+#[cfg(feature = "analysis")]
+mod baseline;
+
+#[cfg(feature = "analysis")]
+mod certificate;
+
+#[cfg(feature = "analysis")]
+mod composition;
+
+#[cfg(feature = "analysis")]
+mod distance;
+
+#[cfg(feature = "analysis")]
+mod matchup;
+
+#[cfg(feature = "analysis")]
+mod perspective;
+
+#[cfg(feature = "analysis")]
+mod plan;
+
+#[cfg(feature = "analysis")]
+mod quantile;
+
+#[cfg(feature = "analysis")]
+mod query;
+
+#[cfg(feature = "analysis")]
+mod rake;
+
+#[cfg(feature = "analysis")]
+mod report;
+
+#[cfg(feature = "analysis")]
+mod validation;
 
 #[cfg(feature = "bincode")]
 mod serialization;
 
-#[cfg(test)]
+#[cfg(all(feature = "rayon", feature = "solver"))]
+mod benchmark;
+
+#[cfg(feature = "solver")]
+mod resolve;
+
+#[cfg(feature = "solver")]
+mod runout;
+
+#[cfg(feature = "solver")]
+mod sizing;
+
+#[cfg(feature = "solver")]
+mod stream;
+
+#[cfg(feature = "solver")]
+mod watch;
+
+#[cfg(all(
+    test,
+    feature = "analysis",
+    feature = "bincode",
+    feature = "io",
+    feature = "solver"
+))]
 mod tests;
 
 use crate::action_tree::*;
 use crate::card::*;
+use crate::evaluator::*;
 use crate::mutex_like::*;
+use crate::storage::*;
+use crate::topology::*;
 use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub use canonical::*;
+pub use edit::*;
+pub use export::*;
+pub use external::*;
+pub use golden::*;
+pub use library::*;
+pub use nodes::*;
+pub use prune::*;
+pub use quantize::*;
+pub use runout_values::*;
+pub use snapshot::*;
+pub use state::*;
+pub use table::*;
+pub use target_nodes::*;
+pub use translation::*;
+
+#[cfg(feature = "analysis")]
+pub use aggregate::*;
+
+#[cfg(feature = "analysis")]
+pub use analysis::*;
+
+#[cfg(feature = "analysis")]
+pub use archetype::*;
+
+#[cfg(feature = "analysis")]
+pub use baseline::*;
+
+#[cfg(feature = "analysis")]
+pub use certificate::*;
+
+#[cfg(feature = "analysis")]
+pub use composition::*;
+
+#[cfg(feature = "analysis")]
+pub use distance::*;
+
+#[cfg(feature = "analysis")]
+pub use matchup::*;
+
+#[cfg(feature = "analysis")]
+pub use perspective::*;
+
+#[cfg(feature = "analysis")]
+pub use plan::*;
+
+#[cfg(feature = "analysis")]
+pub use quantile::*;
+
+#[cfg(feature = "analysis")]
+pub use query::*;
+
+#[cfg(feature = "analysis")]
+pub use rake::*;
+
+#[cfg(feature = "analysis")]
+pub use report::*;
+
+#[cfg(feature = "analysis")]
+pub use validation::*;
+
+#[cfg(all(feature = "rayon", feature = "solver"))]
+pub use benchmark::*;
+
+#[cfg(feature = "solver")]
+pub use resolve::*;
+
+#[cfg(feature = "solver")]
+pub use sizing::*;
+
+#[cfg(feature = "solver")]
+pub use stream::*;
+
+#[cfg(feature = "solver")]
+pub use watch::*;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
@@ -38,9 +212,7 @@ pub struct PostFlopGame {
     // postflop game configurations
     card_config: CardConfig,
     tree_config: TreeConfig,
-    added_lines: Vec<Vec<Action>>,
-    removed_lines: Vec<Vec<Action>>,
-    action_root: Box<MutexLike<ActionTreeNode>>,
+    topology: TreeTopology,
 
     // computed from configurations
     num_combinations: f64,
@@ -54,7 +226,14 @@ pub struct PostFlopGame {
     valid_indices_river: Vec<[Vec<u16>; 2]>,
 
     // hand strength information: indices are stored in ascending strength order
+    // - `hand_strength_low`: ranking for the low half of a split pot (empty if not split)
+    // - `second_board_strength`: rankings of the second board (high, and low if split)
     hand_strength: Vec<[Vec<StrengthItem>; 2]>,
+    hand_strength_low: Vec<[Vec<StrengthItem>; 2]>,
+    second_board_strength: Vec<[Vec<StrengthItem>; 2]>,
+
+    // showdown evaluator (`None` for the standard high-hand ranking)
+    evaluator: Option<Arc<dyn Evaluator>>,
 
     // isomorphism information
     // - `isomorphism_ref_*`: indices to which the eliminated events should refer
@@ -68,6 +247,20 @@ pub struct PostFlopGame {
     isomorphism_card_river: [Vec<Card>; 4],
     isomorphism_swap_river: [[SwapList; 4]; 4],
 
+    // turn card abstraction
+    // - `turn_buckets`: buckets of turn cards; the first card of each bucket is the representative
+    // - `num_isomorphic_turn`: number of elements of `isomorphism_*_turn` that come from the
+    //                          isomorphism; the remaining elements come from the abstraction
+    turn_buckets: Vec<Vec<Card>>,
+    num_isomorphic_turn: usize,
+
+    // suit reduction of a river spot
+    // - `suit_reduction`: suit permutations under which the board and the ranges are invariant
+    //                     (empty if the reduction is not applied)
+    // - `hand_classes`: classes of the private hands merged by the permutations
+    suit_reduction: Vec<[u8; 4]>,
+    hand_classes: [symmetry::HandClasses; 2],
+
     // bunching effect
     bunching_num_dead_cards: usize,
     bunching_num_combinations: f64,
@@ -84,6 +277,10 @@ pub struct PostFlopGame {
     target_storage_mode: BoardState,
     num_nodes: [u64; 3],
     is_compression_enabled: bool,
+    compression_street: BoardState,
+    is_f64_regrets_enabled: bool,
+    is_lazy_allocation: bool,
+    lazy_reach_threshold: f32,
     num_storage: u64,
     num_storage_ip: u64,
     num_storage_chance: u64,
@@ -92,12 +289,21 @@ pub struct PostFlopGame {
     // global storage
     // `storage*` are used as a global storage and are referenced by `PostFlopNode::storage*`.
     // Methods like `PostFlopNode::strategy` define how the storage is used.
+    // `storage_backend` allocates `storage*` (`None` for the heap).
     node_arena: Vec<MutexLike<PostFlopNode>>,
-    storage1: Vec<u8>,
-    storage2: Vec<u8>,
-    storage_ip: Vec<u8>,
-    storage_chance: Vec<u8>,
+    storage_backend: Option<Arc<dyn StorageBackend>>,
+    storage1: Storage,
+    storage2: Storage,
+    storage_ip: Storage,
+    storage_chance: Storage,
+    prefetch_depth: usize,
+    lazy_zeros: Vec<u64>,
+    lazy_storage: std::sync::Mutex<Vec<Vec<u64>>>,
     locking_strategy: BTreeMap<usize, Vec<f32>>,
+    showdown_realization: BTreeMap<usize, [f64; 2]>,
+    allin_equity_cache: allin_cache::AllinEquityCache,
+    parallel_grain: Option<usize>,
+    parallel_nodes: Vec<bool>,
 
     // result interpreter
     action_history: Vec<usize>,
@@ -112,6 +318,14 @@ pub struct PostFlopGame {
     weights: [Vec<f32>; 2],
     normalized_weights: [Vec<f32>; 2],
     cfvalues_cache: [Vec<f32>; 2],
+    decoded_cache: std::sync::Mutex<cache::DecodedCache>,
+    staged_finalization: Option<finalization::StagedFinalization>,
+    is_solving: std::sync::atomic::AtomicBool,
+    is_strategy_only: bool,
+    has_pending_strategy_edits: bool,
+    street_targets: [Option<f32>; 2],
+    num_frozen_streets: std::sync::atomic::AtomicU8,
+    num_iterations: std::sync::atomic::AtomicU32,
 }
 
 /// A struct representing a node in a postflop game tree.
@@ -59,6 +59,16 @@ impl GameNode for PostFlopNode {
         unsafe { slice::from_raw_parts_mut(self.storage2 as *mut f32, self.num_elements as usize) }
     }
 
+    #[inline]
+    fn regrets_f64(&self) -> &[f64] {
+        unsafe { slice::from_raw_parts(self.storage2 as *const f64, self.num_elements as usize) }
+    }
+
+    #[inline]
+    fn regrets_f64_mut(&mut self) -> &mut [f64] {
+        unsafe { slice::from_raw_parts_mut(self.storage2 as *mut f64, self.num_elements as usize) }
+    }
+
     #[inline]
     fn cfvalues(&self) -> &[f32] {
         unsafe { slice::from_raw_parts(self.storage2 as *const f32, self.num_elements as usize) }
@@ -229,6 +239,18 @@ impl Default for PostFlopNode {
 }
 
 impl PostFlopNode {
+    /// Returns the street on which the node is.
+    #[inline]
+    pub(super) fn street(&self) -> BoardState {
+        if self.river != NOT_DEALT {
+            BoardState::River
+        } else if self.turn != NOT_DEALT {
+            BoardState::Turn
+        } else {
+            BoardState::Flop
+        }
+    }
+
     #[inline]
     pub(super) fn children(&self) -> &[MutexLike<Self>] {
         // This is safe because `MutexLike<T>` is a `repr(transparent)` wrapper around `T`.
@@ -0,0 +1,176 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+
+/// Metadata of a node in the game tree, yielded by [`PostFlopGame::nodes`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// The index of the node in the game tree (the same index as
+    /// [`QuantizedNode::node_index`]).
+    pub node_index: usize,
+
+    /// The street of the node.
+    pub street: BoardState,
+
+    /// The player to act, or `None` for chance and terminal nodes.
+    pub player: Option<usize>,
+
+    /// The line leading to the node, including [`Action::Chance`] for the dealt cards.
+    pub line: Vec<Action>,
+
+    /// The available actions; [`Action::Chance`] for the stored cards of a chance node, and empty
+    /// for a terminal node.
+    pub actions: Vec<Action>,
+}
+
+/// An iterator over the nodes of a game tree, returned by [`PostFlopGame::nodes`].
+pub struct NodeIter<'a> {
+    game: &'a PostFlopGame,
+    stack: Vec<(usize, Vec<Action>)>,
+}
+
+impl PostFlopGame {
+    /// Returns an iterator over all nodes of the game tree in depth-first order (parents before
+    /// their children, children in the order of the actions).
+    ///
+    /// Isomorphic chances are not expanded: only the stored cards of a chance node are visited,
+    /// so each node of the tree is yielded exactly once. The nodes are available as soon as the
+    /// tree is built, even before the memory is allocated. The nodes beyond the storage mode of a
+    /// loaded game are not visited, and the chance nodes at the boundary have no actions.
+    ///
+    /// Panics if the game is not successfully initialized.
+    pub fn nodes(&self) -> NodeIter<'_> {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        NodeIter {
+            game: self,
+            stack: vec![(0, Vec::new())],
+        }
+    }
+
+    /// Returns the index of the current node (the same index as [`NodeInfo::node_index`]).
+    #[inline]
+    pub fn current_node_index(&self) -> usize {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        self.node_history.last().cloned().unwrap_or(0)
+    }
+
+    /// Returns the history leading to the node of `node_index`, i.e., the argument of
+    /// [`apply_history`] that moves to the node, or `None` if the index is out of range.
+    ///
+    /// The chance actions are the stored cards, which are the representatives of their
+    /// isomorphic cards.
+    ///
+    /// **Time complexity:** *O*(#(nodes))
+    ///
+    /// [`apply_history`]: #method.apply_history
+    pub fn path_to_node(&self, node_index: usize) -> Option<Vec<usize>> {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        if node_index >= self.node_arena.len() {
+            return None;
+        }
+
+        let parents = self.parent_indices();
+        let mut history = Vec::new();
+        let mut index = node_index;
+
+        while index != 0 {
+            let parent_index = parents[index];
+            let parent = self.node_arena[parent_index].lock();
+            if parent.is_chance() {
+                match self.node_arena[index].lock().prev_action {
+                    Action::Chance(card) => history.push(card as usize),
+                    _ => unreachable!(),
+                }
+            } else {
+                history.push(index - parent_index - parent.children_offset as usize);
+            }
+            index = parent_index;
+        }
+
+        history.reverse();
+        Some(history)
+    }
+
+    /// Moves the current node to the node of `node_index`, e.g., a node found by [`nodes`].
+    ///
+    /// This is equivalent to calling [`apply_history`] with the result of [`path_to_node`], so the
+    /// weights and the cached states are updated as if the node was reached by [`play`].
+    ///
+    /// Returns an error if the memory is not allocated or the index is out of range.
+    ///
+    /// **Time complexity:** *O*(#(nodes))
+    ///
+    /// [`nodes`]: #method.nodes
+    /// [`apply_history`]: #method.apply_history
+    /// [`path_to_node`]: #method.path_to_node
+    /// [`play`]: #method.play
+    pub fn seek_to_node(&mut self, node_index: usize) -> Result<(), Error> {
+        self.check_operation(GameOperation::Navigate)?;
+
+        let history = self
+            .path_to_node(node_index)
+            .ok_or_else(|| Error::Tree(format!("Node index out of range: {node_index}")))?;
+        self.apply_history(&history);
+
+        Ok(())
+    }
+
+    /// Returns the index of the parent of each node (the root is its own parent).
+    fn parent_indices(&self) -> Vec<usize> {
+        let len = self.node_arena.len();
+        let mut parents = vec![0; len];
+
+        for (index, node) in self.node_arena.iter().enumerate() {
+            let node = node.lock();
+            let offset = index + node.children_offset as usize;
+            for child in offset..(offset + node.num_actions()).min(len) {
+                parents[child] = index;
+            }
+        }
+
+        parents
+    }
+}
+
+impl Iterator for NodeIter<'_> {
+    type Item = NodeInfo;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node_index, line) = self.stack.pop()?;
+        let node = self.game.node_arena[node_index].lock();
+
+        // the children beyond the storage mode are not stored
+        let offset = node_index + node.children_offset as usize;
+        let num_children = node
+            .num_actions()
+            .min(self.game.node_arena.len().saturating_sub(offset));
+        let actions = (offset..offset + num_children)
+            .map(|index| self.game.node_arena[index].lock().prev_action)
+            .collect::<Vec<_>>();
+
+        for (i, &action) in actions.iter().enumerate().rev() {
+            let mut child_line = line.clone();
+            child_line.push(action);
+            self.stack.push((offset + i, child_line));
+        }
+
+        let player = (!node.is_terminal() && !node.is_chance()).then(|| node.player());
+
+        Some(NodeInfo {
+            node_index,
+            street: node.street(),
+            player,
+            line,
+            actions,
+        })
+    }
+}
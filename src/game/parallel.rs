@@ -0,0 +1,79 @@
+use super::*;
+use crate::interface::*;
+
+/// The minimum number of nodes in a subtree for the automatic granularity.
+const MIN_PARALLEL_GRAIN: usize = 256;
+
+/// The number of tasks per thread targeted by the automatic granularity.
+const TASKS_PER_THREAD: usize = 64;
+
+impl PostFlopGame {
+    /// Sets the granularity of the parallel traversal, i.e., the minimum number of nodes in a
+    /// subtree whose children are traversed in parallel.
+    ///
+    /// By default (`None`), the granularity is chosen from the size of the tree and the number of
+    /// threads of the current rayon thread pool, so that each thread receives enough subtrees to
+    /// balance the load even when some lines are much smaller than the others (e.g., lines with
+    /// early all-ins). A smaller value splits the work more finely at the cost of the scheduling
+    /// overhead. The automatic value is computed when the memory is allocated and when this
+    /// method is called; call it again after changing the thread pool.
+    ///
+    /// The setting does not affect the results.
+    pub fn set_parallel_grain(&mut self, grain: Option<usize>) {
+        self.parallel_grain = grain;
+        self.update_parallel_nodes();
+    }
+
+    /// Returns the granularity of the parallel traversal in use (see [`set_parallel_grain`]).
+    ///
+    /// [`set_parallel_grain`]: #method.set_parallel_grain
+    pub fn parallel_grain(&self) -> usize {
+        self.parallel_grain.unwrap_or_else(|| {
+            let num_tasks = num_threads() * TASKS_PER_THREAD;
+            (self.node_arena.len() / num_tasks).max(MIN_PARALLEL_GRAIN)
+        })
+    }
+
+    /// Returns the number of nodes whose children are traversed in parallel.
+    pub fn num_parallel_nodes(&self) -> usize {
+        self.parallel_nodes.iter().filter(|&&b| b).count()
+    }
+
+    /// Decides the nodes whose children are traversed in parallel from the subtree sizes.
+    pub(super) fn update_parallel_nodes(&mut self) {
+        let grain = self.parallel_grain();
+        let num_nodes = self.node_arena.len();
+
+        // the children are always stored after their parent; the children of the last stored
+        // street are missing when the game is loaded with a reduced storage mode
+        let mut subtree_size = vec![1; num_nodes];
+        for index in (0..num_nodes).rev() {
+            let node = self.node_arena[index].lock();
+            let offset = index + node.children_offset as usize;
+            if offset < num_nodes {
+                for child in 0..node.num_actions() {
+                    subtree_size[index] += subtree_size[offset + child];
+                }
+            }
+        }
+
+        self.parallel_nodes = (0..num_nodes)
+            .map(|index| {
+                let node = self.node_arena[index].lock();
+                cfg!(feature = "rayon") && node.num_actions() > 1 && subtree_size[index] >= grain
+            })
+            .collect();
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[inline]
+fn num_threads() -> usize {
+    rayon::current_num_threads()
+}
+
+#[cfg(not(feature = "rayon"))]
+#[inline]
+fn num_threads() -> usize {
+    1
+}
@@ -0,0 +1,195 @@
+use super::*;
+
+/// Read-only view of a [`PostFlopGame`] from the perspective of a hero, returned by
+/// [`PostFlopGame::perspective`].
+///
+/// The methods of the view take and return *relative* player indices: `0` is the hero and `1` is
+/// the villain, regardless of which seat (OOP or IP) the hero was configured as. Pairs of values
+/// are returned in the order (hero, villain). This allows tooling to always present the hero
+/// first without swapping the indices by hand; use [`seat`] and [`relative`] to convert between
+/// the relative indices and the seats (0 = OOP, 1 = IP) used by [`PostFlopGame`].
+///
+/// The view reflects the current node of the game; navigate the game (e.g., with
+/// [`PostFlopGame::play`]) and create a new view to query another node.
+///
+/// # Examples
+/// ```ignore
+/// let view = game.perspective(1); // the hero is IP
+/// let hero_ev = view.expected_values(0);
+/// let [hero_stack, villain_stack] = view.remaining_stacks();
+/// if view.is_hero_to_act() {
+///     println!("{} to act", view.player_name(0));
+/// }
+/// ```
+///
+/// [`seat`]: #method.seat
+/// [`relative`]: #method.relative
+#[derive(Clone, Copy)]
+pub struct Perspective<'a> {
+    game: &'a PostFlopGame,
+    hero: usize,
+}
+
+impl PostFlopGame {
+    /// Returns a view of the game whose player indices are relative to `hero` (see
+    /// [`Perspective`]). In particular, `perspective(1)` swaps the players.
+    ///
+    /// Panics if `hero` is not `0` or `1`.
+    #[inline]
+    pub fn perspective(&self, hero: usize) -> Perspective<'_> {
+        if hero >= 2 {
+            panic!("Invalid player: {hero}");
+        }
+
+        Perspective { game: self, hero }
+    }
+}
+
+impl<'a> Perspective<'a> {
+    /// Returns the underlying game.
+    #[inline]
+    pub fn game(&self) -> &'a PostFlopGame {
+        self.game
+    }
+
+    /// Returns the seat of the hero (0 = OOP, 1 = IP).
+    #[inline]
+    pub fn hero_seat(&self) -> usize {
+        self.hero
+    }
+
+    /// Converts a relative player index (0 = hero, 1 = villain) into the seat (0 = OOP, 1 = IP).
+    ///
+    /// Panics if `player` is not `0` or `1`.
+    #[inline]
+    pub fn seat(&self, player: usize) -> usize {
+        if player >= 2 {
+            panic!("Invalid player: {player}");
+        }
+
+        player ^ self.hero
+    }
+
+    /// Converts a seat (0 = OOP, 1 = IP) into the relative player index (0 = hero, 1 = villain).
+    ///
+    /// Panics if `seat` is not `0` or `1`.
+    #[inline]
+    pub fn relative(&self, seat: usize) -> usize {
+        if seat >= 2 {
+            panic!("Invalid player: {seat}");
+        }
+
+        seat ^ self.hero
+    }
+
+    /// Reorders a pair of values indexed by the seats (OOP, IP) into (hero, villain).
+    #[inline]
+    pub fn orient<T>(&self, values: [T; 2]) -> [T; 2] {
+        let [oop, ip] = values;
+        if self.hero == 0 {
+            [oop, ip]
+        } else {
+            [ip, oop]
+        }
+    }
+
+    /// Returns the label of the player (see [`CardConfig::player_name`]).
+    #[inline]
+    pub fn player_name(&self, player: usize) -> &'a str {
+        self.game.card_config.player_name(self.seat(player))
+    }
+
+    /// Returns the relative index of the player to act (see [`PostFlopGame::current_player`]).
+    ///
+    /// If the current node is a terminal node or a chance node, returns an undefined value.
+    #[inline]
+    pub fn current_player(&self) -> usize {
+        self.game.current_player() ^ self.hero
+    }
+
+    /// Returns whether the hero is to act at the current node, which must be a decision node.
+    #[inline]
+    pub fn is_hero_to_act(&self) -> bool {
+        self.current_player() == 0
+    }
+
+    /// Returns the private hands of the player (see [`PostFlopGame::private_cards`]).
+    #[inline]
+    pub fn private_cards(&self, player: usize) -> &'a [(Card, Card)] {
+        self.game.private_cards(self.seat(player))
+    }
+
+    /// Returns the index of the hand in [`private_cards`] (see
+    /// [`PostFlopGame::private_hand_index`]).
+    ///
+    /// [`private_cards`]: #method.private_cards
+    #[inline]
+    pub fn private_hand_index(&self, player: usize, card1: Card, card2: Card) -> Option<usize> {
+        self.game
+            .private_hand_index(self.seat(player), card1, card2)
+    }
+
+    /// Returns the weights of the player (see [`PostFlopGame::weights`]).
+    #[inline]
+    pub fn weights(&self, player: usize) -> &'a [f32] {
+        self.game.weights(self.seat(player))
+    }
+
+    /// Returns the normalized weights of the player (see [`PostFlopGame::normalized_weights`]).
+    #[inline]
+    pub fn normalized_weights(&self, player: usize) -> &'a [f32] {
+        self.game.normalized_weights(self.seat(player))
+    }
+
+    /// Returns the equity of the player (see [`PostFlopGame::equity`]).
+    #[inline]
+    pub fn equity(&self, player: usize) -> Vec<f32> {
+        self.game.equity(self.seat(player))
+    }
+
+    /// Returns the expected values of the player (see [`PostFlopGame::expected_values`]).
+    #[inline]
+    pub fn expected_values(&self, player: usize) -> Vec<f32> {
+        self.game.expected_values(self.seat(player))
+    }
+
+    /// Returns the expected values of each action of the player (see
+    /// [`PostFlopGame::expected_values_detail`]).
+    #[inline]
+    pub fn expected_values_detail(&self, player: usize) -> Vec<f32> {
+        self.game.expected_values_detail(self.seat(player))
+    }
+
+    /// Returns the expected net wins of the player (see
+    /// [`PostFlopGame::net_expected_values`]).
+    #[inline]
+    pub fn net_expected_values(&self, player: usize) -> Vec<f32> {
+        self.game.net_expected_values(self.seat(player))
+    }
+
+    /// Returns the expected value of the player in several units (see
+    /// [`PostFlopGame::ev_report`]).
+    #[inline]
+    pub fn ev_report(&self, player: usize, big_blind: f64) -> EvReport {
+        self.game.ev_report(self.seat(player), big_blind)
+    }
+
+    /// Returns the analysis of the range of the player (see [`PostFlopGame::analyze_node`]).
+    #[inline]
+    pub fn analyze_node(&self, player: usize) -> NodeAnalysis {
+        self.game.analyze_node(self.seat(player))
+    }
+
+    /// Returns the total bet amount of (hero, villain) (see
+    /// [`PostFlopGame::total_bet_amount`]).
+    #[inline]
+    pub fn total_bet_amount(&self) -> [i32; 2] {
+        self.orient(self.game.total_bet_amount())
+    }
+
+    /// Returns the remaining stack of (hero, villain) (see [`PostFlopGame::remaining_stacks`]).
+    #[inline]
+    pub fn remaining_stacks(&self) -> [i32; 2] {
+        self.orient(self.game.remaining_stacks())
+    }
+}
@@ -0,0 +1,268 @@
+use super::*;
+use crate::error::*;
+
+/// A class of the card dealt on the next street, used by [`PostFlopGame::future_plan`].
+///
+/// The classes are mutually exclusive and checked in the order of declaration, so a card that
+/// pairs the board is always [`RunoutClass::Pair`] even if it also completes a flush.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RunoutClass {
+    /// The card pairs the board.
+    Pair,
+
+    /// The card brings the third (or more) card of a suit to the board.
+    Flush,
+
+    /// The card is higher than every card on the board.
+    Overcard,
+
+    /// Any other card.
+    Blank,
+}
+
+/// The future-street plan of one action at the current node, returned by
+/// [`PostFlopGame::future_plan`].
+///
+/// The per-hand vectors have the length of `#(runout classes) * #(private hands)`, and the value
+/// for the `i`-th class of [`RunoutClass::ALL`] and the `j`-th private hand is stored in the
+/// `i * #(private hands) + j`-th element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionPlan {
+    /// The action at the current node.
+    pub action: Action,
+
+    /// The probability of the action with each private hand, as in [`PostFlopGame::strategy`].
+    pub frequency: Vec<f32>,
+
+    /// The opponent's passive actions (check or call) that lead from the action to the next
+    /// street. Empty if the next street is reached directly.
+    pub continuation: Vec<Action>,
+
+    /// The number of combinations with which each hand reaches its first decision on the next
+    /// street, summed over the cards of each class.
+    pub combos: Vec<f64>,
+
+    /// The frequency of betting or raising (including all-in) at the first decision on the
+    /// next street, averaged over the cards of each class. Zero if `combos` is zero.
+    pub aggression: Vec<f32>,
+}
+
+/// Projection of the next-street strategy onto the actions of the current node.
+///
+/// For each action of the current player, the opponent is assumed to continue passively (check
+/// or call) to the next street. Then, for each card dealt, the current player's first decision
+/// on the next street is examined (after the opponent checks, if the opponent acts first). This
+/// gives the "what's the plan" view, e.g., how often each combo that checks the flop bets the
+/// turn when an overcard comes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FuturePlan {
+    /// The player to act at the current node.
+    pub player: usize,
+
+    /// The private hands of the player.
+    pub private_cards: Vec<(Card, Card)>,
+
+    /// The plan of each available action. `None` if the action does not lead to a decision of
+    /// the player on the next street through the opponent's passive actions (e.g., folding or
+    /// going all-in).
+    pub plans: Vec<Option<ActionPlan>>,
+}
+
+impl RunoutClass {
+    /// All classes in the order of declaration.
+    pub const ALL: [RunoutClass; 4] = [
+        RunoutClass::Pair,
+        RunoutClass::Flush,
+        RunoutClass::Overcard,
+        RunoutClass::Blank,
+    ];
+
+    /// Classifies `card` dealt to `board`.
+    pub fn classify(board: &[Card], card: Card) -> Self {
+        let rank = card >> 2;
+        let suit = card & 3;
+        if board.iter().any(|&c| c >> 2 == rank) {
+            RunoutClass::Pair
+        } else if board.iter().filter(|&&c| c & 3 == suit).count() >= 2 {
+            RunoutClass::Flush
+        } else if board.iter().all(|&c| c >> 2 < rank) {
+            RunoutClass::Overcard
+        } else {
+            RunoutClass::Blank
+        }
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl ActionPlan {
+    /// Returns the frequency of betting or raising on the next street over the whole range for
+    /// the cards of `class`, or `None` if no combination reaches the next street.
+    pub fn class_aggression(&self, class: RunoutClass) -> Option<f64> {
+        let num_hands = self.frequency.len();
+        let range = class.index() * num_hands..(class.index() + 1) * num_hands;
+        let combos = &self.combos[range.clone()];
+        let aggression = &self.aggression[range];
+
+        let total = combos.iter().sum::<f64>();
+        let sum = combos
+            .iter()
+            .zip(aggression)
+            .fold(0.0, |sum, (&c, &a)| sum + c * a as f64);
+
+        (total > 0.0).then(|| sum / total)
+    }
+}
+
+impl PostFlopGame {
+    /// Computes the next-street plan of the current player for each action at the current node.
+    /// See [`FuturePlan`] for the details.
+    ///
+    /// Returns an error if the game is not solved, the current node is not a decision node, or
+    /// the current street is the river. The current node is restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(cards) * #(private hands)).
+    pub fn future_plan(&mut self) -> Result<FuturePlan, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree(
+                "Current node is not a decision node".to_string(),
+            ));
+        }
+
+        if self.current_street() == BoardState::River {
+            return Err(Error::Tree("No future street".to_string()));
+        }
+
+        let saved_history = self.history().to_vec();
+        let player = self.current_player();
+        let strategy = self.strategy();
+        let private_cards = self.private_cards(player).to_vec();
+        let num_hands = private_cards.len();
+
+        let mut plans = Vec::new();
+        for (i, action) in self.available_actions().into_iter().enumerate() {
+            let mut history = saved_history.clone();
+            history.push(i);
+            self.apply_history(&history);
+
+            let plan = self
+                .passive_continuation(&mut history, player)
+                .and_then(|continuation| {
+                    let (combos, aggression) = self.next_street_aggression(&history, player);
+                    combos.iter().any(|&c| c > 0.0).then(|| ActionPlan {
+                        action,
+                        frequency: strategy[i * num_hands..(i + 1) * num_hands].to_vec(),
+                        continuation,
+                        combos,
+                        aggression,
+                    })
+                });
+
+            plans.push(plan);
+        }
+
+        self.apply_history(&saved_history);
+
+        Ok(FuturePlan {
+            player,
+            private_cards,
+            plans,
+        })
+    }
+
+    /// Follows the opponent's check or call until the next chance node. Returns `None` if the
+    /// hand ends or the opponent has no passive action.
+    fn passive_continuation(
+        &mut self,
+        history: &mut Vec<usize>,
+        player: usize,
+    ) -> Option<Vec<Action>> {
+        let mut continuation = Vec::new();
+
+        while !self.is_chance_node() {
+            if self.is_terminal_node() || self.current_player() == player {
+                return None;
+            }
+
+            let actions = self.available_actions();
+            let passive = actions
+                .iter()
+                .position(|a| matches!(a, Action::Check | Action::Call))?;
+
+            continuation.push(actions[passive]);
+            history.push(passive);
+            self.apply_history(history);
+        }
+
+        Some(continuation)
+    }
+
+    /// Accumulates the player's aggression at the first decision on the next street for each
+    /// card dealt at the current chance node.
+    fn next_street_aggression(&mut self, history: &[usize], player: usize) -> (Vec<f64>, Vec<f32>) {
+        let board = self.current_board();
+        let num_hands = self.private_cards(player).len();
+        let num_classes = RunoutClass::ALL.len();
+        let mut combos = vec![0.0; num_classes * num_hands];
+        let mut sums = vec![0.0; num_classes * num_hands];
+
+        let possible_cards = self.possible_cards();
+        for card in 0..52 {
+            if possible_cards & (1 << card) == 0 {
+                continue;
+            }
+
+            let mut history = history.to_vec();
+            history.push(card as usize);
+            self.apply_history(&history);
+
+            // the opponent acts first on the next street
+            if !self.is_terminal_node() && !self.is_chance_node() && self.current_player() != player
+            {
+                match self
+                    .available_actions()
+                    .iter()
+                    .position(|a| a == &Action::Check)
+                {
+                    Some(check) => self.play(check),
+                    None => continue,
+                }
+            }
+
+            if self.is_terminal_node() || self.is_chance_node() {
+                continue;
+            }
+
+            self.cache_normalized_weights();
+            let weights = self.normalized_weights(player);
+            let strategy = self.strategy();
+            let offset = RunoutClass::classify(&board, card).index() * num_hands;
+
+            let actions = self.available_actions();
+            for (action, row) in actions.iter().zip(strategy.chunks_exact(num_hands)) {
+                if matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_)) {
+                    for (hand, (&w, &s)) in weights.iter().zip(row).enumerate() {
+                        sums[offset + hand] += w as f64 * s as f64;
+                    }
+                }
+            }
+
+            for (hand, &w) in weights.iter().enumerate() {
+                combos[offset + hand] += w as f64;
+            }
+        }
+
+        let aggression = sums
+            .iter()
+            .zip(&combos)
+            .map(|(&s, &c)| if c > 0.0 { (s / c) as f32 } else { 0.0 })
+            .collect();
+
+        (combos, aggression)
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+use crate::interface::*;
+
+impl PostFlopGame {
+    /// Sets the number of levels below the current node whose storage is prefetched after each
+    /// navigation (default: `0`, disabled).
+    ///
+    /// When the storage is backed by files (e.g., `MmapStorage` with the `mmap` feature), the
+    /// first access to a node that is not in the page cache blocks on the disk. With a positive
+    /// depth, [`play`], [`apply_history`], and [`back_to_root`] hint the storage backend (see
+    /// [`StorageBuffer::prefetch`]) to load the strategies and the counterfactual values of the
+    /// nodes up to `depth` actions below the new current node in the background, so that the
+    /// likely next nodes are already in memory when they are viewed. A depth of `1` (the
+    /// children) is usually enough for interactive browsing; note that the number of prefetched
+    /// nodes grows exponentially with the depth, and that a chance node counts as a level.
+    ///
+    /// The setting does not affect the results and is not saved to files. It has no effect with
+    /// the heap storage.
+    ///
+    /// [`play`]: #method.play
+    /// [`apply_history`]: #method.apply_history
+    /// [`back_to_root`]: #method.back_to_root
+    #[inline]
+    pub fn set_prefetch_depth(&mut self, depth: usize) {
+        self.prefetch_depth = depth;
+    }
+
+    /// Returns the number of levels below the current node whose storage is prefetched (see
+    /// [`set_prefetch_depth`]).
+    ///
+    /// [`set_prefetch_depth`]: #method.set_prefetch_depth
+    #[inline]
+    pub fn prefetch_depth(&self) -> usize {
+        self.prefetch_depth
+    }
+
+    /// Prefetches the storage of the nodes below the current node.
+    pub(super) fn prefetch_below_current_node(&self) {
+        if self.prefetch_depth == 0
+            || self.storage_backend.is_none()
+            || self.state < State::MemoryAllocated
+        {
+            return;
+        }
+
+        self.prefetch_recursive(&self.node(), self.prefetch_depth);
+    }
+
+    fn prefetch_recursive(&self, node: &PostFlopNode, depth: usize) {
+        if depth == 0 || node.is_terminal() {
+            return;
+        }
+
+        for action in 0..node.num_actions() {
+            let child = node.play(action);
+            self.prefetch_node(&child);
+            self.prefetch_recursive(&child, depth - 1);
+        }
+    }
+
+    /// Prefetches the storage of `node`.
+    fn prefetch_node(&self, node: &PostFlopNode) {
+        if node.is_terminal() {
+            return;
+        }
+
+        let num_bytes = if self.is_node_compressed(node) { 2 } else { 4 };
+        let num_bytes2 = if self.is_f64_regrets_enabled && num_bytes == 4 {
+            8
+        } else {
+            num_bytes
+        };
+
+        let ranges = [
+            (node.storage1, node.num_elements as usize * num_bytes),
+            (node.storage2, node.num_elements as usize * num_bytes2),
+            (node.storage3, node.num_elements_ip as usize * num_bytes),
+        ];
+
+        // each pointer is prefetched only by the storage containing it
+        for storage in [
+            &self.storage1,
+            &self.storage2,
+            &self.storage_ip,
+            &self.storage_chance,
+        ] {
+            for &(ptr, len) in &ranges {
+                storage.prefetch(ptr, len);
+            }
+        }
+    }
+}
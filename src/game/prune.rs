@@ -0,0 +1,213 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use std::mem;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A decision node retained by [`PostFlopGame::prune_solution`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PrunedNode {
+    /// The line leading to the node, including [`Action::Chance`] for the dealt cards.
+    pub line: Vec<Action>,
+
+    /// The player to act.
+    pub player: u8,
+
+    /// The probability that the node is reached under the solved strategies.
+    pub reach_probability: f32,
+
+    /// The strategy in the same layout as [`PostFlopGame::strategy`].
+    pub strategy: Vec<f32>,
+
+    /// The expected values of each player's hands at the node, as returned by
+    /// [`PostFlopGame::expected_values`].
+    pub expected_values: [Vec<f32>; 2],
+}
+
+/// A subtree removed by [`PostFlopGame::prune_solution`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PrunedSubtree {
+    /// The line leading to the root of the subtree.
+    pub line: Vec<Action>,
+
+    /// The probability that the root of the subtree is reached under the solved strategies.
+    pub reach_probability: f32,
+}
+
+/// The strategy-relevant part of a solved game, extracted by [`PostFlopGame::prune_solution`].
+///
+/// The retained nodes keep their strategies and expected values, which are those of the full
+/// solution since pruning happens after solving. The removed subtrees are recorded with their
+/// reach probabilities so that the discarded mass is known.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PrunedSolution {
+    /// The private hands of each player.
+    pub private_cards: [Vec<(Card, Card)>; 2],
+
+    /// The threshold of the reach probability used for pruning.
+    pub threshold: f32,
+
+    /// The retained decision nodes in depth-first order.
+    pub nodes: Vec<PrunedNode>,
+
+    /// The removed subtrees in depth-first order.
+    pub pruned_subtrees: Vec<PrunedSubtree>,
+
+    /// The total reach probability of the removed subtrees.
+    pub discarded_reach: f32,
+}
+
+impl PostFlopGame {
+    /// Extracts the decision nodes reached with probability at least `threshold` under the
+    /// solved strategies.
+    ///
+    /// The reach probability of a node is the probability that the node is reached when both
+    /// players follow the solved strategies and the cards are dealt at random. Subtrees rooted at
+    /// a decision or chance node reached with probability less than `threshold` are removed and
+    /// recorded in [`PrunedSolution::pruned_subtrees`]. Isomorphic chances are expanded, so the
+    /// result does not depend on the suit isomorphism.
+    ///
+    /// Returns an error if the game is not solved, the storage mode is not
+    /// [`BoardState::River`], or `threshold` is not in [0, 1]. The current node is restored after
+    /// the call.
+    pub fn prune_solution(&mut self, threshold: f32) -> Result<PrunedSolution, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(Error::Config(format!("Invalid threshold: {threshold}")));
+        }
+
+        let saved_history = self.history().to_vec();
+        self.back_to_root();
+        let root_mass = self.current_mass();
+
+        let mut result = PrunedSolution {
+            private_cards: [0, 1].map(|player| self.private_cards(player).to_vec()),
+            threshold,
+            ..Default::default()
+        };
+
+        let mut history = Vec::new();
+        let mut line = Vec::new();
+        let scale = 1.0 / root_mass;
+        self.prune_recursive(&mut history, &mut line, scale, &mut result);
+
+        result.discarded_reach = result
+            .pruned_subtrees
+            .iter()
+            .fold(0.0, |sum, subtree| sum + subtree.reach_probability as f64)
+            as f32;
+
+        self.apply_history(&saved_history);
+        Ok(result)
+    }
+
+    /// Returns the number of card combinations reaching the current node.
+    fn current_mass(&mut self) -> f64 {
+        self.cache_normalized_weights();
+        let weights = self.normalized_weights(0);
+        weights.iter().fold(0.0, |sum, &w| sum + w as f64)
+    }
+
+    /// `scale` converts the number of combinations at the current node into the reach probability.
+    fn prune_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+        scale: f64,
+        result: &mut PrunedSolution,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        let reach_probability = (self.current_mass() * scale) as f32;
+
+        if !line.is_empty() && reach_probability < result.threshold {
+            result.pruned_subtrees.push(PrunedSubtree {
+                line: line.clone(),
+                reach_probability,
+            });
+            return;
+        }
+
+        if self.is_chance_node() {
+            let scale = scale / self.chance_factor(&self.node()) as f64;
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card as usize);
+                    line.push(Action::Chance(card));
+                    self.apply_history(history);
+                    self.prune_recursive(history, line, scale, result);
+                    line.pop();
+                    history.pop();
+                }
+            }
+            return;
+        }
+
+        result.nodes.push(PrunedNode {
+            line: line.clone(),
+            player: self.current_player() as u8,
+            reach_probability,
+            strategy: self.strategy(),
+            expected_values: [self.expected_values(0), self.expected_values(1)],
+        });
+
+        for (i, action) in self.available_actions().into_iter().enumerate() {
+            history.push(i);
+            line.push(action);
+            self.apply_history(history);
+            self.prune_recursive(history, line, scale, result);
+            line.pop();
+            history.pop();
+        }
+    }
+}
+
+impl PrunedSolution {
+    /// Returns the retained node with the given line, if any.
+    pub fn node(&self, line: &[Action]) -> Option<&PrunedNode> {
+        self.nodes.iter().find(|node| node.line == line)
+    }
+
+    /// Returns the size of the data in bytes.
+    pub fn memory_usage(&self) -> u64 {
+        let hands = self.private_cards.iter().map(Vec::len).sum::<usize>();
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                mem::size_of::<PrunedNode>()
+                    + node.line.len() * mem::size_of::<Action>()
+                    + (node.strategy.len()
+                        + node.expected_values.iter().map(Vec::len).sum::<usize>())
+                        * mem::size_of::<f32>()
+            })
+            .sum::<usize>();
+        let subtrees = self
+            .pruned_subtrees
+            .iter()
+            .map(|subtree| {
+                mem::size_of::<PrunedSubtree>() + subtree.line.len() * mem::size_of::<Action>()
+            })
+            .sum::<usize>();
+        (hands * mem::size_of::<(Card, Card)>() + nodes + subtrees) as u64
+    }
+}
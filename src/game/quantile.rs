@@ -0,0 +1,216 @@
+use super::*;
+use crate::error::*;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The value by which the hands are ranked in [`PostFlopGame::quantile_summary`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum QuantileKey {
+    /// The equity of each hand (see [`PostFlopGame::equity`]).
+    #[default]
+    Equity,
+
+    /// The expected value of each hand (see [`PostFlopGame::expected_values`]).
+    ExpectedValue,
+}
+
+/// A band of the range between two quantiles, part of [`QuantileSummary`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuantileBand {
+    /// The fraction of the range above the band, i.e., `0.0` for the band of the strongest hands.
+    pub lower: f64,
+
+    /// The fraction of the range above the end of the band, i.e., `1.0` for the band of the
+    /// weakest hands.
+    pub upper: f64,
+
+    /// The number of combinations in the band.
+    pub combos: f64,
+
+    /// The average equity of the band.
+    pub equity: f64,
+
+    /// The average expected value of the band.
+    pub ev: f64,
+
+    /// The frequency of each action within the band, in the order of
+    /// [`QuantileSummary::actions`].
+    pub frequencies: Vec<f64>,
+}
+
+/// Action frequencies of the acting player by quantile band of the range, returned by
+/// [`PostFlopGame::quantile_summary`].
+///
+/// The hands are ranked from the strongest to the weakest by the key, weighted by the number of
+/// combinations with which each hand reaches the current node (see
+/// [`PostFlopGame::normalized_weights`]). The bands partition the weighted range, so the
+/// combinations of a hand lying on a boundary are split between the two adjacent bands. This
+/// produces summaries such as "the top 20% of the range bets 70% of the time", which the
+/// [`Display`](fmt::Display) implementation writes one band per line.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuantileSummary {
+    /// The acting player.
+    pub player: usize,
+
+    /// The key by which the hands are ranked.
+    pub key: QuantileKey,
+
+    /// The available actions.
+    pub actions: Vec<Action>,
+
+    /// The bands from the strongest to the weakest.
+    pub bands: Vec<QuantileBand>,
+}
+
+impl fmt::Display for QuantileSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for band in &self.bands {
+            write!(f, "{:.0}-{:.0}%:", band.lower * 100.0, band.upper * 100.0)?;
+            let rows = self.actions.iter().zip(&band.frequencies);
+            for (i, (&action, &frequency)) in rows.enumerate() {
+                let sep = if i == 0 { " " } else { ", " };
+                let action = action_to_string(action);
+                write!(f, "{sep}{action} {:.1}%", frequency * 100.0)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl PostFlopGame {
+    /// Summarizes the strategy of the player to act at the current node by quantile band of the
+    /// range ranked by `key` (see [`QuantileSummary`]).
+    ///
+    /// `bounds` are the quantiles between the bands in increasing order, as fractions of the
+    /// range from the strongest hands: e.g., `&[0.2]` gives the top 20% and the rest, and
+    /// `&[0.2, 0.4, 0.6, 0.8]` gives the quintiles. An empty `bounds` gives a single band.
+    ///
+    /// Returns an error if the current node is not a decision node, the expected values of the
+    /// current node are not available (see [`is_current_node_finalized`]), the normalized weights
+    /// are not cached (see [`cache_normalized_weights`]), or `bounds` are not strictly increasing
+    /// values in the open interval (0, 1).
+    ///
+    /// **Time complexity:** that of [`equity`] plus that of [`expected_values`].
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let summary = game.quantile_summary(QuantileKey::Equity, &[0.2, 0.5])?;
+    /// print!("{summary}");
+    /// // 0-20%: Check 30.0%, Bet 100 70.0%
+    /// // 20-50%: Check 85.2%, Bet 100 14.8%
+    /// // 50-100%: Check 62.5%, Bet 100 37.5%
+    /// ```
+    ///
+    /// [`is_current_node_finalized`]: #method.is_current_node_finalized
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    /// [`equity`]: #method.equity
+    /// [`expected_values`]: #method.expected_values
+    pub fn quantile_summary(
+        &self,
+        key: QuantileKey,
+        bounds: &[f64],
+    ) -> Result<QuantileSummary, Error> {
+        self.check_operation(GameOperation::QueryExpectedValues)?;
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree(
+                "Current node is not a decision node".to_string(),
+            ));
+        }
+
+        if !self.is_normalized_weight_cached {
+            return Err(Error::Config(
+                "Normalized weights are not cached".to_string(),
+            ));
+        }
+
+        let mut prev = 0.0;
+        for &bound in bounds {
+            if !(bound > prev && bound < 1.0) {
+                return Err(Error::Config(format!("Invalid quantile bound: {bound}")));
+            }
+            prev = bound;
+        }
+
+        let player = self.current_player();
+        let actions = self.available_actions();
+        let num_actions = actions.len();
+        let weights = self.normalized_weights(player);
+        let num_hands = weights.len();
+        let strategy = self.strategy();
+        let equity = self.equity(player);
+        let ev = self.expected_values(player);
+        let keys = match key {
+            QuantileKey::Equity => &equity,
+            QuantileKey::ExpectedValue => &ev,
+        };
+
+        // the strongest hands first; ties are broken by the hand index for determinism
+        let mut order = (0..num_hands)
+            .filter(|&index| weights[index] > 0.0)
+            .collect::<Vec<_>>();
+        order.sort_by(|&i, &j| keys[j].total_cmp(&keys[i]).then(i.cmp(&j)));
+
+        let total = order
+            .iter()
+            .fold(0.0, |sum, &index| sum + weights[index] as f64);
+        let edges = [0.0]
+            .iter()
+            .chain(bounds)
+            .chain(&[1.0])
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut bands = edges
+            .windows(2)
+            .map(|w| QuantileBand {
+                lower: w[0],
+                upper: w[1],
+                frequencies: vec![0.0; num_actions],
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut cumulative = 0.0;
+        for &index in &order {
+            let w = weights[index] as f64;
+            let (start, end) = (cumulative, cumulative + w);
+            cumulative = end;
+
+            for band in &mut bands {
+                let overlap = end.min(band.upper * total) - start.max(band.lower * total);
+                if overlap <= 0.0 {
+                    continue;
+                }
+
+                band.combos += overlap;
+                band.equity += equity[index] as f64 * overlap;
+                band.ev += ev[index] as f64 * overlap;
+                for (action, frequency) in band.frequencies.iter_mut().enumerate() {
+                    *frequency += strategy[action * num_hands + index] as f64 * overlap;
+                }
+            }
+        }
+
+        for band in &mut bands {
+            if band.combos > 0.0 {
+                band.equity /= band.combos;
+                band.ev /= band.combos;
+                band.frequencies.iter_mut().for_each(|f| *f /= band.combos);
+            }
+        }
+
+        Ok(QuantileSummary {
+            player,
+            key,
+            actions,
+            bands,
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::utility::*;
+use std::mem;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Strategy of a decision node quantized to 8 bits.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuantizedNode {
+    /// The index of the node in the game tree.
+    pub node_index: u32,
+
+    /// The number of actions at the node.
+    pub num_actions: u8,
+
+    /// The per-node scale, i.e., the largest probability of the node.
+    pub scale: f32,
+
+    /// The quantized probabilities in the same layout as [`PostFlopGame::strategy`] (without the
+    /// isomorphic swap). The probability is recovered as `data[i] as f32 * scale / 255.0` and
+    /// then normalized for each hand.
+    pub data: Vec<u8>,
+}
+
+/// Strategies of all decision nodes quantized to 8 bits.
+///
+/// This is intended for consumers with tight size budgets (e.g., mobile or embedded) that can
+/// tolerate a small loss in EV. The loss caused by the quantization is measured when exporting.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuantizedStrategy {
+    /// The quantized nodes, sorted by node index.
+    pub nodes: Vec<QuantizedNode>,
+
+    /// The EV loss of each player caused by the quantization, i.e., the increase in the
+    /// expected value of the opponent's best response against the quantized strategy.
+    pub ev_loss: [f32; 2],
+
+    /// The maximum of `ev_loss`.
+    pub max_ev_loss: f32,
+}
+
+impl QuantizedStrategy {
+    /// Returns the dequantized strategy of the node with the given index, or `None` if the node
+    /// is not found.
+    pub fn strategy(&self, node_index: usize) -> Option<Vec<f32>> {
+        let pos = self
+            .nodes
+            .binary_search_by_key(&node_index, |node| node.node_index as usize)
+            .ok()?;
+        Some(self.nodes[pos].dequantize())
+    }
+
+    /// Returns the size of the quantized data in bytes.
+    pub fn memory_usage(&self) -> u64 {
+        self.nodes
+            .iter()
+            .map(|node| (mem::size_of::<QuantizedNode>() + node.data.len()) as u64)
+            .sum()
+    }
+}
+
+impl QuantizedNode {
+    /// Returns the dequantized (and normalized) strategy.
+    pub fn dequantize(&self) -> Vec<f32> {
+        let coef = self.scale / u8::MAX as f32;
+        let strategy = self
+            .data
+            .iter()
+            .map(|&q| q as f32 * coef)
+            .collect::<Vec<_>>();
+        normalized_strategy(&strategy, self.num_actions as usize)
+    }
+}
+
+impl PostFlopGame {
+    /// Exports the strategies of all decision nodes quantized to 8 bits.
+    ///
+    /// Each node uses its own scale so that the largest probability of the node is represented
+    /// exactly. The maximum EV loss is measured by temporarily replacing the strategies with the
+    /// dequantized ones and recomputing the best responses; the strategies are restored before
+    /// returning.
+    ///
+    /// Returns an error if the game is not solved, the storage mode is not [`BoardState::River`],
+    /// or the memory is allocated lazily.
+    pub fn export_quantized_strategy(&mut self) -> Result<QuantizedStrategy, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        if self.is_lazy_allocation {
+            return Err(Error::Config(
+                "Lazy allocation is not supported".to_string(),
+            ));
+        }
+
+        let mut nodes = Vec::new();
+        for (index, node) in self.node_arena.iter().enumerate() {
+            let node = node.lock();
+            if node.is_terminal() || node.is_chance() || node.num_elements == 0 {
+                continue;
+            }
+
+            let strategy = self.node_strategy(&node);
+            let scale = strategy.iter().fold(0.0f32, |m, &v| m.max(v));
+            let coef = if scale > 0.0 {
+                u8::MAX as f32 / scale
+            } else {
+                0.0
+            };
+
+            nodes.push(QuantizedNode {
+                node_index: index as u32,
+                num_actions: node.num_actions() as u8,
+                scale,
+                data: strategy.iter().map(|&v| (v * coef).round() as u8).collect(),
+            });
+        }
+
+        let ev_loss = self.quantization_ev_loss(&nodes);
+
+        Ok(QuantizedStrategy {
+            nodes,
+            ev_loss,
+            max_ev_loss: ev_loss[0].max(ev_loss[1]),
+        })
+    }
+
+    /// Computes the EV loss of each player caused by replacing the strategies with `nodes`.
+    fn quantization_ev_loss(&mut self, nodes: &[QuantizedNode]) -> [f32; 2] {
+        let original_ev = compute_mes_ev(self);
+
+        // swap in the dequantized strategies
+        let mut backup = Vec::with_capacity(nodes.len());
+        for quantized in nodes {
+            let mut node = self.node_arena[quantized.node_index as usize].lock();
+            let strategy = self.select_stored_hands(&quantized.dequantize(), node.player());
+            if self.is_node_compressed(&node) {
+                let original = node.strategy_compressed().to_vec();
+                let dst = node.strategy_compressed_mut();
+                dst.iter_mut().zip(&strategy).for_each(|(d, &s)| {
+                    *d = (s * u16::MAX as f32).round() as u16;
+                });
+                backup.push((original, Vec::new()));
+            } else {
+                let original = node.strategy().to_vec();
+                node.strategy_mut().copy_from_slice(&strategy);
+                backup.push((Vec::new(), original));
+            }
+        }
+
+        let quantized_ev = compute_mes_ev(self);
+
+        // restore the original strategies
+        for (quantized, (compressed, raw)) in nodes.iter().zip(backup) {
+            let mut node = self.node_arena[quantized.node_index as usize].lock();
+            if self.is_node_compressed(&node) {
+                node.strategy_compressed_mut().copy_from_slice(&compressed);
+            } else {
+                node.strategy_mut().copy_from_slice(&raw);
+            }
+        }
+
+        self.lock_decoded_cache().clear();
+
+        // the loss of `player` is the gain of the opponent's best response
+        [
+            (quantized_ev[1] - original_ev[1]).max(0.0),
+            (quantized_ev[0] - original_ev[0]).max(0.0),
+        ]
+    }
+}
@@ -0,0 +1,554 @@
+use super::*;
+use crate::error::*;
+use crate::json::*;
+use crate::range::*;
+use crate::utility::*;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A pattern of lines matching decision nodes, used by [`Query`].
+///
+/// A pattern is a list of segments separated by `:`. The first segment must be `r` (the root
+/// node), and each following segment matches one action or dealt card:
+///
+/// - `f`, `x`, `c`: fold, check, and call.
+/// - `b`, `r`, `a`: any bet, raise, and all-in. `b75` and `r150` match the bet or raise to the
+///   given amount, in the same unit as [`Action::Bet`] and [`Action::Raise`].
+/// - `Ah`, `7c`, ...: the specific dealt card.
+/// - `*`: any single action or dealt card.
+/// - `**`: any number (including zero) of actions and dealt cards.
+///
+/// For example, `r:b75:c:*` matches the decision nodes right after the turn card is dealt when
+/// the OOP player bets 75 on the flop and the IP player calls, and `r:**:x` matches every node
+/// right after a check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodePattern {
+    segments: Vec<PatternSegment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PatternSegment {
+    Fold,
+    Check,
+    Call,
+    Bet(Option<i32>),
+    Raise(Option<i32>),
+    AllIn,
+    Card(Card),
+    Any,
+    AnyDepth,
+}
+
+/// A quantity evaluated by [`Query`] at each matched node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryMetric {
+    /// The frequency of folding (`fold_frequency`).
+    FoldFrequency,
+
+    /// The frequency of checking (`check_frequency`).
+    CheckFrequency,
+
+    /// The frequency of calling (`call_frequency`).
+    CallFrequency,
+
+    /// The frequency of betting or raising, including all-in (`bet_frequency`).
+    BetFrequency,
+
+    /// The average expected value (`ev`), as returned by [`PostFlopGame::expected_values`].
+    ExpectedValue,
+
+    /// The average equity (`equity`).
+    Equity,
+
+    /// The number of combinations reaching the node (`combos`).
+    Combos,
+}
+
+/// A grouping of the matched nodes used by [`Query`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryGroupBy {
+    /// All matched nodes form a single group (`none`).
+    None,
+
+    /// Each matched node forms its own group, keyed by its line (`node`).
+    Node,
+
+    /// Grouped by the turn card (`turn_card`).
+    TurnCard,
+
+    /// Grouped by the river card (`river_card`).
+    RiverCard,
+
+    /// Grouped by the [`RunoutClass`] of the turn card relative to the flop
+    /// (`turn_card_class`).
+    TurnCardClass,
+
+    /// Grouped by the [`RunoutClass`] of the river card relative to the flop and the turn
+    /// (`river_card_class`).
+    RiverCardClass,
+}
+
+/// A query evaluated by [`PostFlopGame::run_query`].
+///
+/// A query can be parsed from a JSON object, so that report tooling can ask questions without
+/// pulling the full strategies over FFI:
+///
+/// ```
+/// use postflop_solver::*;
+///
+/// let query: Query =
+///     r#"{"node": "r:b75:c:*", "metric": "bet_frequency", "group_by": "turn_card_class"}"#
+///         .parse()
+///         .unwrap();
+/// assert_eq!(query.metric, QueryMetric::BetFrequency);
+/// assert_eq!(query.group_by, QueryGroupBy::TurnCardClass);
+/// ```
+///
+/// The optional `player` member (`0` or `1`) selects the player whose quantity is evaluated; by
+/// default, the player to act at each matched node is used. Action frequencies are only
+/// evaluated at the nodes where the player is to act.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    /// The pattern of the matched nodes.
+    pub node: NodePattern,
+
+    /// The evaluated quantity.
+    pub metric: QueryMetric,
+
+    /// The player whose quantity is evaluated, or `None` for the player to act.
+    pub player: Option<usize>,
+
+    /// The grouping of the matched nodes.
+    pub group_by: QueryGroupBy,
+}
+
+/// A group of matched nodes in the result of [`PostFlopGame::run_query`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QueryRow {
+    /// The key of the group (e.g., `Ah`, `overcard`, or `r:b75:c:Ah`); `all` if not grouped, and
+    /// `-` if the grouping card is not dealt yet.
+    pub key: String,
+
+    /// The number of matched nodes in the group with a positive number of combinations.
+    pub num_nodes: usize,
+
+    /// The number of combinations of the evaluated player summed over the matched nodes.
+    pub combos: f64,
+
+    /// The value of the metric averaged over the matched nodes, weighted by the number of
+    /// combinations (the number of combinations itself for [`QueryMetric::Combos`]).
+    pub value: f64,
+}
+
+impl NodePattern {
+    /// Returns the pattern positions reachable from `states` without consuming an action.
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < states.len() {
+            let pos = states[i];
+            if self.segments.get(pos) == Some(&PatternSegment::AnyDepth)
+                && !states.contains(&(pos + 1))
+            {
+                states.push(pos + 1);
+            }
+            i += 1;
+        }
+        states.sort_unstable();
+        states
+    }
+
+    /// Returns the initial pattern positions.
+    fn initial_states(&self) -> Vec<usize> {
+        self.closure(vec![0])
+    }
+
+    /// Returns the pattern positions after consuming `action`.
+    fn step(&self, states: &[usize], action: Action) -> Vec<usize> {
+        let mut next = Vec::new();
+        for &pos in states {
+            let Some(&segment) = self.segments.get(pos) else {
+                continue;
+            };
+            let next_pos = match segment {
+                PatternSegment::AnyDepth => pos,
+                segment if segment.matches(action) => pos + 1,
+                _ => continue,
+            };
+            if !next.contains(&next_pos) {
+                next.push(next_pos);
+            }
+        }
+        self.closure(next)
+    }
+
+    #[inline]
+    fn is_match(&self, states: &[usize]) -> bool {
+        states.contains(&self.segments.len())
+    }
+}
+
+impl PatternSegment {
+    #[inline]
+    fn matches(self, action: Action) -> bool {
+        match (self, action) {
+            (PatternSegment::Any | PatternSegment::AnyDepth, _) => true,
+            (PatternSegment::Fold, Action::Fold) => true,
+            (PatternSegment::Check, Action::Check) => true,
+            (PatternSegment::Call, Action::Call) => true,
+            (PatternSegment::Bet(amount), Action::Bet(a)) => amount.is_none_or(|x| x == a),
+            (PatternSegment::Raise(amount), Action::Raise(a)) => amount.is_none_or(|x| x == a),
+            (PatternSegment::AllIn, Action::AllIn(_)) => true,
+            (PatternSegment::Card(card), Action::Chance(c)) => card == c,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for NodePattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split(':').map(str::trim);
+
+        if tokens.next() != Some("r") {
+            return Err(Error::Config(format!(
+                "Node pattern must start with `r`: {s}"
+            )));
+        }
+
+        let parse_amount = |amount: &str| -> Result<Option<i32>, Error> {
+            if amount.is_empty() {
+                Ok(None)
+            } else {
+                let amount = amount
+                    .parse::<i32>()
+                    .map_err(|_| Error::Config(format!("Invalid amount: {amount}")))?;
+                Ok(Some(amount))
+            }
+        };
+
+        let is_amount = |amount: &str| amount.chars().all(|c| c.is_ascii_digit());
+
+        let segments = tokens
+            .map(|token| match token {
+                "f" => Ok(PatternSegment::Fold),
+                "x" => Ok(PatternSegment::Check),
+                "c" => Ok(PatternSegment::Call),
+                "a" => Ok(PatternSegment::AllIn),
+                "*" => Ok(PatternSegment::Any),
+                "**" => Ok(PatternSegment::AnyDepth),
+                _ if token.starts_with('b') && is_amount(&token[1..]) => {
+                    Ok(PatternSegment::Bet(parse_amount(&token[1..])?))
+                }
+                _ if token.starts_with('r') && is_amount(&token[1..]) => {
+                    Ok(PatternSegment::Raise(parse_amount(&token[1..])?))
+                }
+                _ => card_from_str(token)
+                    .map(PatternSegment::Card)
+                    .map_err(|_| Error::Config(format!("Invalid pattern segment: {token}"))),
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self { segments })
+    }
+}
+
+impl fmt::Display for NodePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "r")?;
+        for segment in &self.segments {
+            match segment {
+                PatternSegment::Fold => write!(f, ":f")?,
+                PatternSegment::Check => write!(f, ":x")?,
+                PatternSegment::Call => write!(f, ":c")?,
+                PatternSegment::Bet(None) => write!(f, ":b")?,
+                PatternSegment::Bet(Some(amount)) => write!(f, ":b{amount}")?,
+                PatternSegment::Raise(None) => write!(f, ":r")?,
+                PatternSegment::Raise(Some(amount)) => write!(f, ":r{amount}")?,
+                PatternSegment::AllIn => write!(f, ":a")?,
+                PatternSegment::Card(card) => {
+                    write!(f, ":{}", card_to_string(*card).map_err(|_| fmt::Error)?)?
+                }
+                PatternSegment::Any => write!(f, ":*")?,
+                PatternSegment::AnyDepth => write!(f, ":**")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for QueryMetric {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fold_frequency" => Ok(QueryMetric::FoldFrequency),
+            "check_frequency" => Ok(QueryMetric::CheckFrequency),
+            "call_frequency" => Ok(QueryMetric::CallFrequency),
+            "bet_frequency" => Ok(QueryMetric::BetFrequency),
+            "ev" => Ok(QueryMetric::ExpectedValue),
+            "equity" => Ok(QueryMetric::Equity),
+            "combos" => Ok(QueryMetric::Combos),
+            _ => Err(Error::Config(format!("Unknown metric: {s}"))),
+        }
+    }
+}
+
+impl FromStr for QueryGroupBy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(QueryGroupBy::None),
+            "node" => Ok(QueryGroupBy::Node),
+            "turn_card" => Ok(QueryGroupBy::TurnCard),
+            "river_card" => Ok(QueryGroupBy::RiverCard),
+            "turn_card_class" => Ok(QueryGroupBy::TurnCardClass),
+            "river_card_class" => Ok(QueryGroupBy::RiverCardClass),
+            _ => Err(Error::Config(format!("Unknown grouping: {s}"))),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    /// Parses a query from a JSON object with the `node` and `metric` members and the optional
+    /// `player` and `group_by` members.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = JsonValue::parse(s).map_err(|e| Error::Config(e.to_string()))?;
+        if !matches!(value, JsonValue::Object(_)) {
+            return Err(Error::Config("Query must be a JSON object".to_string()));
+        }
+
+        let get_str = |key: &str| -> Result<Option<&str>, Error> {
+            match value.get(key) {
+                None => Ok(None),
+                Some(v) => v
+                    .as_str()
+                    .map(Some)
+                    .ok_or_else(|| Error::Config(format!("`{key}` must be a string"))),
+            }
+        };
+
+        let node = get_str("node")?
+            .ok_or_else(|| Error::Config("Missing `node`".to_string()))?
+            .parse()?;
+        let metric = get_str("metric")?
+            .ok_or_else(|| Error::Config("Missing `metric`".to_string()))?
+            .parse()?;
+        let group_by = get_str("group_by")?.unwrap_or("none").parse()?;
+
+        let player = match value.get("player") {
+            None | Some(JsonValue::Null) => None,
+            Some(v) => match v.as_f64() {
+                Some(p) if p == 0.0 || p == 1.0 => Some(p as usize),
+                _ => return Err(Error::Config("`player` must be 0 or 1".to_string())),
+            },
+        };
+
+        Ok(Self {
+            node,
+            metric,
+            player,
+            group_by,
+        })
+    }
+}
+
+/// Accumulator of a group during the evaluation of a query.
+struct QueryGroup {
+    sort_key: usize,
+    row: QueryRow,
+    sum: f64,
+}
+
+impl PostFlopGame {
+    /// Evaluates `query` over the decision nodes matching its pattern.
+    ///
+    /// The tree is traversed inside the crate, pruning the branches that cannot match the
+    /// pattern, so only the requested numbers cross the API boundary. Isomorphic chances are
+    /// expanded. The groups are ordered by the card, the [`RunoutClass`], or the traversal order
+    /// of the matched nodes, depending on [`Query::group_by`]. Groups without a matched node are
+    /// omitted.
+    ///
+    /// Returns an error if the game is not solved, the storage mode is not
+    /// [`BoardState::River`], or the player is invalid. The current node is restored after the
+    /// call.
+    pub fn run_query(&mut self, query: &Query) -> Result<Vec<QueryRow>, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        if query.player.is_some_and(|player| player >= 2) {
+            return Err(Error::Config("Invalid player".to_string()));
+        }
+
+        let saved_history = self.history().to_vec();
+        self.back_to_root();
+
+        let mut history = Vec::new();
+        let mut line = Vec::new();
+        let mut groups = Vec::new();
+        let states = query.node.initial_states();
+        self.query_recursive(&mut history, &mut line, &states, query, &mut groups);
+
+        self.apply_history(&saved_history);
+
+        groups.sort_by_key(|group| group.sort_key);
+        Ok(groups
+            .into_iter()
+            .filter(|group| group.row.num_nodes > 0)
+            .map(|mut group| {
+                let row = &mut group.row;
+                row.value = match query.metric {
+                    QueryMetric::Combos => row.combos,
+                    _ if row.combos > 0.0 => group.sum / row.combos,
+                    _ => 0.0,
+                };
+                group.row
+            })
+            .collect())
+    }
+
+    fn query_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+        states: &[usize],
+        query: &Query,
+        groups: &mut Vec<QueryGroup>,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        let children = if self.is_chance_node() {
+            let possible_cards = self.possible_cards();
+            (0..52)
+                .filter(|&card| possible_cards & (1 << card) != 0)
+                .map(|card| (card as usize, Action::Chance(card)))
+                .collect::<Vec<_>>()
+        } else {
+            if query.node.is_match(states) {
+                self.query_accumulate(line, query, groups);
+            }
+            self.available_actions().into_iter().enumerate().collect()
+        };
+
+        for (index, action) in children {
+            let next_states = query.node.step(states, action);
+            if next_states.is_empty() {
+                continue;
+            }
+
+            history.push(index);
+            line.push(action);
+            self.apply_history(history);
+            self.query_recursive(history, line, &next_states, query, groups);
+            line.pop();
+            history.pop();
+        }
+    }
+
+    fn query_accumulate(&mut self, line: &[Action], query: &Query, groups: &mut Vec<QueryGroup>) {
+        let current_player = self.current_player();
+        let player = query.player.unwrap_or(current_player);
+
+        let categories: &[fn(&Action) -> bool] = match query.metric {
+            QueryMetric::FoldFrequency => &[|a| *a == Action::Fold],
+            QueryMetric::CheckFrequency => &[|a| *a == Action::Check],
+            QueryMetric::CallFrequency => &[|a| *a == Action::Call],
+            QueryMetric::BetFrequency => {
+                &[|a| matches!(a, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_))]
+            }
+            _ => &[],
+        };
+
+        if !categories.is_empty() && player != current_player {
+            return;
+        }
+
+        self.cache_normalized_weights();
+        let weights = self.normalized_weights(player);
+        let combos = Summation::Compensated.sum(weights.iter().map(|&w| w as f64));
+        if combos == 0.0 {
+            return;
+        }
+
+        let weighted_sum = |values: &[f32]| Summation::Compensated.weighted_sum(values, weights);
+
+        let sum = match query.metric {
+            QueryMetric::ExpectedValue => weighted_sum(&self.expected_values(player)),
+            QueryMetric::Equity => weighted_sum(&self.equity(player)),
+            QueryMetric::Combos => combos,
+            _ => {
+                let strategy = self.strategy();
+                let num_hands = weights.len();
+                self.available_actions()
+                    .iter()
+                    .zip(strategy.chunks_exact(num_hands))
+                    .filter(|(action, _)| categories.iter().any(|f| f(action)))
+                    .map(|(_, row)| weighted_sum(row))
+                    .sum()
+            }
+        };
+
+        let board = self.current_board();
+        let card_key = |index: usize| match board.get(index) {
+            Some(&card) => (card as usize, card_to_string(card).unwrap()),
+            None => (usize::MAX, "-".to_string()),
+        };
+        let class_key = |index: usize| match board.get(index) {
+            Some(&card) => {
+                let class = RunoutClass::classify(&board[..index], card);
+                (class as usize, runout_class_to_string(class).to_string())
+            }
+            None => (usize::MAX, "-".to_string()),
+        };
+
+        let (sort_key, key) = match query.group_by {
+            QueryGroupBy::None => (0, "all".to_string()),
+            QueryGroupBy::Node => (groups.len(), line_to_pattern_string(line)),
+            QueryGroupBy::TurnCard => card_key(3),
+            QueryGroupBy::RiverCard => card_key(4),
+            QueryGroupBy::TurnCardClass => class_key(3),
+            QueryGroupBy::RiverCardClass => class_key(4),
+        };
+
+        let group = match groups.iter().position(|group| group.row.key == key) {
+            Some(index) => &mut groups[index],
+            None => {
+                groups.push(QueryGroup {
+                    sort_key,
+                    row: QueryRow {
+                        key,
+                        ..Default::default()
+                    },
+                    sum: 0.0,
+                });
+                groups.last_mut().unwrap()
+            }
+        };
+
+        group.row.num_nodes += 1;
+        group.row.combos += combos;
+        group.sum += sum;
+    }
+}
+
+/// Returns the name of `class` used in query results.
+fn runout_class_to_string(class: RunoutClass) -> &'static str {
+    match class {
+        RunoutClass::Pair => "pair",
+        RunoutClass::Flush => "flush",
+        RunoutClass::Overcard => "overcard",
+        RunoutClass::Blank => "blank",
+    }
+}
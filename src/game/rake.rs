@@ -0,0 +1,188 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::payoff::*;
+use crate::utility::*;
+
+/// Expected rake of the hands ending with a line, contained in [`RakeReport`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RakeLine {
+    /// The actions of the line from the root, excluding the dealt cards (i.e., the runouts are
+    /// aggregated).
+    pub actions: Vec<Action>,
+
+    /// The probability that the hand ends with the line.
+    pub probability: f64,
+
+    /// The expected rake paid by each player (`[OOP, IP]`) on the line, i.e., the contribution of
+    /// the line to [`RakeReport::rake_paid`].
+    pub rake_paid: [f64; 2],
+}
+
+/// Result of [`PostFlopGame::rake_report`].
+///
+/// The rake is attributed to the players by how much it reduces their payoffs: the player who
+/// takes the pot (by a fold or at showdown) pays the whole rake, and the rake is split evenly
+/// when the pot is split. When the equity realization of a showdown is modified (see
+/// [`PostFlopGame::set_showdown_realization`]), each player pays the rake in proportion to
+/// their share of the pot. All amounts are in chips per hand, and the expected values follow the
+/// convention of [`compute_current_ev`]: half the starting pot is subtracted.
+///
+/// [`compute_current_ev`]: crate::compute_current_ev
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RakeReport {
+    /// The expected rake taken per hand, i.e., the sum of `rake_paid`.
+    pub expected_rake: f64,
+
+    /// The expected rake paid by each player (`[OOP, IP]`).
+    pub rake_paid: [f64; 2],
+
+    /// The expected values of the solved strategies with the rake deducted.
+    pub ev: [f64; 2],
+
+    /// The expected values of the same strategies if no rake were taken, i.e., `ev + rake_paid`.
+    pub ev_without_rake: [f64; 2],
+
+    /// The lines ending the hand in depth-first order, including those that are never reached.
+    pub lines: Vec<RakeLine>,
+}
+
+impl PostFlopGame {
+    /// Computes the expected rake paid by each player under the solved strategies, in total and
+    /// per line, together with the rake-adjusted expected values (see [`RakeReport`]).
+    ///
+    /// This is useful for game-selection analysis of cash games, where the rake paid by a player
+    /// reduces the win rate. The game does not have to be raked; if it is not, all rake amounts
+    /// are zero.
+    ///
+    /// Returns an error if the game is not solved, the payoff model is not
+    /// [`PayoffModel::ChipEv`], the storage mode is not [`BoardState::River`], or the memory is
+    /// allocated lazily.
+    ///
+    /// **Time complexity:** *O*(#(terminal nodes) * #(private hands)).
+    pub fn rake_report(&self) -> Result<RakeReport, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.tree_config.payoff_model != PayoffModel::ChipEv {
+            return Err(Error::Config("Payoff model must be chip EV".to_string()));
+        }
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        if self.is_lazy_allocation {
+            return Err(Error::Config(
+                "Lazy allocation is not supported".to_string(),
+            ));
+        }
+
+        let mut report = RakeReport::default();
+        let mut line_indices = BTreeMap::new();
+        let reach = [0, 1].map(|player| self.initial_weights(player).to_vec());
+
+        self.rake_recursive(
+            &self.root(),
+            &reach,
+            1.0,
+            &mut Vec::new(),
+            &mut line_indices,
+            &mut report,
+        );
+
+        report.expected_rake = report.rake_paid[0] + report.rake_paid[1];
+        for player in 0..2 {
+            report.ev[player] = report.ev_without_rake[player] - report.rake_paid[player];
+        }
+
+        Ok(report)
+    }
+
+    /// Accumulates the rake and the expected values of the terminal nodes below `node` into
+    /// `report`, where `weight` is the probability of the dealt cards (counting the isomorphic
+    /// chances represented by the subtree).
+    fn rake_recursive(
+        &self,
+        node: &PostFlopNode,
+        reach: &[Vec<f32>; 2],
+        weight: f64,
+        line: &mut Vec<Action>,
+        line_indices: &mut BTreeMap<Vec<Action>, usize>,
+        report: &mut RakeReport,
+    ) {
+        if node.is_terminal() {
+            let index = *line_indices.entry(line.clone()).or_insert_with(|| {
+                report.lines.push(RakeLine {
+                    actions: line.clone(),
+                    ..Default::default()
+                });
+                report.lines.len() - 1
+            });
+
+            // the payoffs are affine in the rake, so evaluating without the rake and with a unit
+            // rake gives the expected values and the share of the rake paid by each player
+            let mut ev = [[0.0; 2]; 2];
+            for (k, rake) in [0.0, 1.0].into_iter().enumerate() {
+                for player in 0..2 {
+                    let mut result = Vec::with_capacity(reach[player].len());
+                    self.evaluate_with_rake(
+                        result.spare_capacity_mut(),
+                        node,
+                        player,
+                        &reach[player ^ 1],
+                        rake,
+                    );
+                    unsafe { result.set_len(reach[player].len()) };
+                    let ev_player = Summation::Compensated.weighted_sum(&result, &reach[player]);
+                    ev[k][player] = weight * ev_player;
+                }
+            }
+
+            // the payoffs sum to minus the rake, so the unit rake gives the probability
+            let rake = self.terminal_rake(node);
+            let entry = &mut report.lines[index];
+            entry.probability += (ev[0][0] + ev[0][1]) - (ev[1][0] + ev[1][1]);
+            for (player, &ev_without_rake) in ev[0].iter().enumerate() {
+                let rake_paid = rake * (ev_without_rake - ev[1][player]);
+                entry.rake_paid[player] += rake_paid;
+                report.rake_paid[player] += rake_paid;
+                report.ev_without_rake[player] += ev_without_rake;
+            }
+
+            return;
+        }
+
+        if node.is_chance() {
+            let isomorphic_chances = self.isomorphic_chances(node);
+            for action in 0..node.num_actions() {
+                // the reach probabilities are invariant under the isomorphic swaps, so the
+                // isomorphic chances contribute the same amounts as the subtree
+                let multiplicity = 1 + isomorphic_chances
+                    .iter()
+                    .filter(|&&i| i as usize == action)
+                    .count();
+                let child_weight = weight * multiplicity as f64 / self.chance_factor(node) as f64;
+                let child = node.play(action);
+                self.rake_recursive(&child, reach, child_weight, line, line_indices, report);
+            }
+            return;
+        }
+
+        let player = node.player();
+        let num_hands = reach[player].len();
+        let strategy = self.node_strategy(node);
+
+        for action in 0..node.num_actions() {
+            let child = node.play(action);
+            let mut child_reach = reach.clone();
+            child_reach[player]
+                .iter_mut()
+                .zip(&strategy[action * num_hands..(action + 1) * num_hands])
+                .for_each(|(r, &s)| *r *= s);
+
+            line.push(child.prev_action);
+            self.rake_recursive(&child, &child_reach, weight, line, line_indices, report);
+            line.pop();
+        }
+    }
+}
@@ -0,0 +1,813 @@
+use super::*;
+use crate::error::*;
+use crate::hand::*;
+use crate::interface::*;
+use crate::json::*;
+use crate::range::*;
+use crate::utility::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Expected value of a player at the current node, expressed in several units.
+///
+/// The following conventions are used:
+///
+/// - `ev` is the same quantity as returned by [`PostFlopGame::expected_values`], i.e., the
+///   expected amount of the pot that the player wins at the end of the hand.
+/// - `net_ev` subtracts the chips that the player has committed so far, where each player is
+///   regarded as having contributed half of the starting pot excluding the dead money (see
+///   [`TreeConfig::dead_money`]). A player who folds at the current
+///   node has a `net_ev` of `-committed`; a player who breaks even has a `net_ev` of zero.
+/// - `street_net_ev` is the expected net win from the beginning of the current street, i.e.,
+///   the chips committed on earlier streets are regarded as sunk (see
+///   [`PostFlopGame::net_expected_values`]).
+/// - `*_bb` values are the chip values divided by the big blind.
+/// - `net_bb_per_100` is the win rate in big blinds per 100 hands, assuming that the current
+///   spot is played every hand.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EvReport {
+    /// The big blind used for the conversion.
+    pub big_blind: f64,
+
+    /// The size of the pot at the current node, including the uncalled bet.
+    pub pot: f64,
+
+    /// The amount that the player has committed to the pot.
+    pub committed: f64,
+
+    /// The expected value in chips (pot share).
+    pub ev: f64,
+
+    /// The expected value as a fraction of the current pot.
+    pub ev_pot_fraction: f64,
+
+    /// The expected value in big blinds.
+    pub ev_bb: f64,
+
+    /// The expected net win in chips.
+    pub net_ev: f64,
+
+    /// The expected net win in chips from the beginning of the current street.
+    pub street_net_ev: f64,
+
+    /// The expected net win in big blinds.
+    pub net_ev_bb: f64,
+
+    /// The expected net win in big blinds per 100 hands.
+    pub net_bb_per_100: f64,
+
+    /// The stack of the player at the beginning of the game (effective stack plus the player's
+    /// contribution to the starting pot) in big blinds.
+    pub starting_stack_bb: f64,
+}
+
+/// Combinations of the current player taking an action, returned by
+/// [`PostFlopGame::strategy_combos`].
+///
+/// The numbers of combinations are weighted by the reach probabilities, i.e., each hand counts
+/// as many combinations as it reaches the current node with, taking the opponent's range and
+/// card removal into account (see [`PostFlopGame::normalized_weights`]).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionCombos {
+    /// The action.
+    pub action: Action,
+
+    /// The number of combinations taking the action.
+    pub combos: f64,
+
+    /// The frequency of the action, i.e., `combos` divided by the number of combinations
+    /// reaching the current node.
+    pub frequency: f64,
+
+    /// The hands taking the action most often by the number of combinations, paired with the
+    /// number of combinations, in descending order. Hands not taking the action are excluded.
+    pub top_hands: Vec<((Card, Card), f64)>,
+}
+
+impl PostFlopGame {
+    /// Returns the number of combinations taking each available action at the current node and
+    /// the `top_n` hands taking each action most often. See [`ActionCombos`] for the
+    /// conventions.
+    ///
+    /// This is useful for generating textual summaries of a strategy (e.g., "bets 41.2 combos,
+    /// mostly Kx"). Ties are broken by the order of [`private_cards`].
+    ///
+    /// Panics if the current node is a terminal node or a chance node, the memory is not
+    /// allocated, or the normalized weights are not cached (see [`cache_normalized_weights`]).
+    ///
+    /// **Time complexity:** *O*(#(actions) * #(private hands) * log(#(private hands))).
+    ///
+    /// [`private_cards`]: #method.private_cards
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn strategy_combos(&self, top_n: usize) -> Vec<ActionCombos> {
+        let strategy = self.strategy();
+        let player = self.current_player();
+        let private_cards = self.private_cards(player);
+        let normalized_weights = self.normalized_weights(player);
+        let num_hands = private_cards.len();
+
+        let total = normalized_weights
+            .iter()
+            .fold(0.0, |sum, &w| sum + w as f64);
+
+        self.available_actions()
+            .into_iter()
+            .zip(strategy.chunks_exact(num_hands))
+            .map(|(action, row)| {
+                let mut hands = row
+                    .iter()
+                    .zip(normalized_weights)
+                    .enumerate()
+                    .map(|(hand, (&s, &w))| (hand, s as f64 * w as f64))
+                    .filter(|&(_, combos)| combos > 0.0)
+                    .collect::<Vec<_>>();
+
+                let combos = hands.iter().fold(0.0, |sum, &(_, c)| sum + c);
+                hands.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+                hands.truncate(top_n);
+
+                ActionCombos {
+                    action,
+                    combos,
+                    frequency: if total > 0.0 { combos / total } else { 0.0 },
+                    top_hands: hands
+                        .into_iter()
+                        .map(|(hand, combos)| (private_cards[hand], combos))
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a concise textual summary of the strategy at the current node, built from
+    /// [`strategy_combos`] and the made-hand categories.
+    ///
+    /// The summary lists the frequency of each available action, e.g., `"OOP checks 62%, bets 75%
+    /// pot 31% (mostly one pair, flush), bets 150% pot 7% (mostly three of a kind)"`. Each bet,
+    /// raise, and all-in is followed by the made-hand categories accounting for at least half of
+    /// its combinations (at most two categories).
+    ///
+    /// Panics under the same conditions as [`strategy_combos`].
+    ///
+    /// [`strategy_combos`]: #method.strategy_combos
+    pub fn describe_node(&self) -> String {
+        let player = self.current_player();
+        let strategy = self.strategy();
+        let normalized_weights = self.normalized_weights(player);
+        let num_hands = normalized_weights.len();
+        let pot = self.pot() as f64;
+
+        let mut board = Hand::new();
+        for &card in &self.current_board() {
+            board = board.add_card(card as usize);
+        }
+
+        let categories = self
+            .private_cards(player)
+            .iter()
+            .map(|&(c1, c2)| board.add_card(c1 as usize).add_card(c2 as usize).category())
+            .collect::<Vec<_>>();
+
+        let descriptions = self
+            .strategy_combos(0)
+            .into_iter()
+            .enumerate()
+            .map(|(action, entry)| {
+                let verb = match entry.action {
+                    Action::Fold => "folds".to_string(),
+                    Action::Check => "checks".to_string(),
+                    Action::Call => "calls".to_string(),
+                    Action::Bet(amount) => format!("bets {:.0}% pot", amount as f64 / pot * 100.0),
+                    Action::Raise(amount) => format!("raises to {amount}"),
+                    Action::AllIn(_) => "goes all-in".to_string(),
+                    action => action_to_string(action),
+                };
+
+                let mut description = format!("{verb} {:.0}%", entry.frequency * 100.0);
+
+                let is_aggressive = matches!(
+                    entry.action,
+                    Action::Bet(_) | Action::Raise(_) | Action::AllIn(_)
+                );
+
+                if is_aggressive && entry.combos > 0.0 {
+                    let row = &strategy[action * num_hands..(action + 1) * num_hands];
+                    let mut combos = [0.0; HAND_CATEGORIES.len()];
+                    for ((&s, &w), &category) in row.iter().zip(normalized_weights).zip(&categories)
+                    {
+                        combos[category] += s as f64 * w as f64;
+                    }
+
+                    let mut order = (0..HAND_CATEGORIES.len()).collect::<Vec<_>>();
+                    order.sort_by(|&a, &b| combos[b].total_cmp(&combos[a]).then(a.cmp(&b)));
+
+                    let mut covered = 0.0;
+                    let mut names = Vec::new();
+                    for category in order.into_iter().take(2) {
+                        if covered >= 0.5 * entry.combos || combos[category] <= 0.0 {
+                            break;
+                        }
+                        covered += combos[category];
+                        names.push(HAND_CATEGORIES[category].replace('_', " "));
+                    }
+
+                    description += &format!(" (mostly {})", names.join(", "));
+                }
+
+                description
+            })
+            .collect::<Vec<_>>();
+
+        format!(
+            "{} {}",
+            self.card_config.player_name(player),
+            descriptions.join(", ")
+        )
+    }
+
+    /// Returns the expected value of the given player at the current node converted into big
+    /// blinds and bb/100. See [`EvReport`] for the conventions.
+    ///
+    /// Panics if the game is not solved, the big blind is not positive, or the normalized
+    /// weights are not cached (see [`cache_normalized_weights`]).
+    ///
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn ev_report(&self, player: usize, big_blind: f64) -> EvReport {
+        if big_blind <= 0.0 {
+            panic!("Big blind must be positive");
+        }
+
+        let ev = compute_average(
+            &self.expected_values(player),
+            self.normalized_weights(player),
+        );
+        let ev = ev as f64;
+
+        let effective_stack = self.tree_config.effective_stack as f64;
+        let amount = self.node().amount as f64;
+        let total_bet_amount = self.total_bet_amount();
+
+        let bias = (total_bet_amount[player] - total_bet_amount[player ^ 1]).max(0) as f64;
+
+        let pot = self.pot() as f64;
+        let contribution = self.tree_config.contribution();
+        let committed = contribution + amount + bias;
+        let net_ev = ev - committed;
+
+        EvReport {
+            big_blind,
+            pot,
+            committed,
+            ev,
+            ev_pot_fraction: ev / pot,
+            ev_bb: ev / big_blind,
+            net_ev,
+            street_net_ev: ev - (amount + bias - self.street_start_amount() as f64),
+            net_ev_bb: net_ev / big_blind,
+            net_bb_per_100: 100.0 * net_ev / big_blind,
+            starting_stack_bb: (effective_stack + contribution) / big_blind,
+        }
+    }
+
+    /// Returns the expected net win of each private hand of the given player from the beginning
+    /// of the current street.
+    ///
+    /// Whereas [`expected_values`] returns the expected amount of the pot that the player wins,
+    /// this method subtracts the chips that the player has put into the pot on the current
+    /// street, so the values are comparable with the EVs of solvers that treat the pot at the
+    /// start of the street as the starting point. A positive value means that the player wins
+    /// chips; for example, a player who bets `b` on the river and gets called by a worse hand
+    /// wins the starting pot of the street plus `b`, and a player who folds to a bet after
+    /// checking has a value of zero. At a chance node, the next street is regarded as the current
+    /// street. Hands with zero reach probability have a value of zero.
+    ///
+    /// Panics if the game is not solved or the normalized weights are not cached (see
+    /// [`cache_normalized_weights`]).
+    ///
+    /// **Time complexity:** see [`expected_values`].
+    ///
+    /// [`expected_values`]: #method.expected_values
+    /// [`cache_normalized_weights`]: #method.cache_normalized_weights
+    pub fn net_expected_values(&self, player: usize) -> Vec<f32> {
+        let total_bet_amount = self.total_bet_amount();
+        let bias = (total_bet_amount[player] - total_bet_amount[player ^ 1]).max(0);
+        let street_bet = (self.node().amount + bias - self.street_start_amount()) as f32;
+
+        self.expected_values(player)
+            .iter()
+            .zip(self.normalized_weights(player))
+            .map(|(&ev, &w)| if w > 0.0 { ev - street_bet } else { 0.0 })
+            .collect()
+    }
+
+    /// Returns the amount that each player had committed (excluding the starting pot) at the
+    /// beginning of the current street.
+    fn street_start_amount(&self) -> i32 {
+        let street_start = self
+            .node_history
+            .iter()
+            .rev()
+            .find(|&&index| self.node_arena[index].lock().is_chance());
+        match street_start {
+            Some(&index) => self.node_arena[index].lock().amount,
+            None => self.node_arena[0].lock().amount,
+        }
+    }
+}
+
+/// Specification of the contents of [`PostFlopGame::generate_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportSpec {
+    /// Whether to include the strategy of the root node aggregated by hand class.
+    pub root_grid: bool,
+
+    /// The pairs of a player and a decision class whose aggregate frequencies are included.
+    pub aggregates: Vec<(usize, DecisionClass)>,
+
+    /// Whether to include the expected values for each card dealt at the first chance node
+    /// reached by checking and calling.
+    pub runout_evs: bool,
+
+    /// Whether to include the breakdown of each player's range by made-hand category at the
+    /// root node.
+    pub buckets: bool,
+}
+
+/// A cell of the root strategy grid.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridCell {
+    /// The hand class (e.g., `AA`, `AKs`, or `AKo`).
+    pub hand: String,
+
+    /// The number of combinations in the range.
+    pub combos: f64,
+
+    /// The frequency of each root action.
+    pub frequencies: Vec<f64>,
+}
+
+/// Aggregate frequencies of a player at a decision class.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AggregateEntry {
+    /// The player.
+    pub player: usize,
+
+    /// The street of the decision class (`flop`, `turn`, or `river`).
+    pub street: String,
+
+    /// The decision class (`first_decision`, `unopened`, `facing_bet`, or `facing_raise`).
+    pub class: String,
+
+    /// The aggregate frequencies.
+    pub frequencies: AggregateFrequencies,
+}
+
+/// Expected values when a specific card is dealt.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RunoutEntry {
+    /// The dealt card (e.g., `Ah`).
+    pub card: String,
+
+    /// The average expected value of each player.
+    pub ev: [f64; 2],
+
+    /// The average equity of each player.
+    pub equity: [f64; 2],
+}
+
+/// Breakdown of a player's range for a made-hand category.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BucketEntry {
+    /// The player.
+    pub player: usize,
+
+    /// The made-hand category (e.g., `one_pair`).
+    pub category: String,
+
+    /// The number of combinations in the range.
+    pub combos: f64,
+
+    /// The average equity.
+    pub equity: f64,
+
+    /// The average expected value.
+    pub ev: f64,
+
+    /// The frequency of each root action if the player acts at the root; otherwise empty.
+    pub frequencies: Vec<f64>,
+}
+
+/// A report bundling the main study outputs of a solved game.
+///
+/// All averages are weighted by the number of combinations with which each hand reaches the
+/// node, taking the opponent's range and card removal into account.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StudyReport {
+    /// The board at the root node (e.g., `Th6d2c`).
+    pub board: String,
+
+    /// The starting pot.
+    pub starting_pot: i32,
+
+    /// The dead money included in the starting pot.
+    pub dead_money: i32,
+
+    /// The effective stack.
+    pub effective_stack: i32,
+
+    /// The exploitability of the strategy.
+    pub exploitability: f32,
+
+    /// The labels of the players (OOP, IP); see [`CardConfig::player_names`].
+    pub player_names: [String; 2],
+
+    /// The player to act at the root node.
+    pub root_player: usize,
+
+    /// The actions at the root node (e.g., `Bet 30`).
+    pub root_actions: Vec<String>,
+
+    /// The root strategy grid, ordered as in the usual 13x13 hand matrix.
+    pub root_grid: Vec<GridCell>,
+
+    /// The aggregate frequencies in the order of [`ReportSpec::aggregates`].
+    pub aggregates: Vec<AggregateEntry>,
+
+    /// The expected values for each dealt card.
+    pub runouts: Vec<RunoutEntry>,
+
+    /// The breakdowns of the ranges by made-hand category.
+    pub buckets: Vec<BucketEntry>,
+}
+
+const HAND_CATEGORIES: [&str; 9] = [
+    "high_card",
+    "one_pair",
+    "two_pair",
+    "three_of_a_kind",
+    "straight",
+    "flush",
+    "full_house",
+    "four_of_a_kind",
+    "straight_flush",
+];
+
+const RANK_CHARS: [char; 13] = [
+    '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+];
+
+impl Default for ReportSpec {
+    /// Includes every section, with the first decision and the decision facing a bet of each
+    /// player on the flop as the aggregates.
+    #[inline]
+    fn default() -> Self {
+        let street = BoardState::Flop;
+        Self {
+            root_grid: true,
+            aggregates: vec![
+                (0, DecisionClass::FirstDecision(street)),
+                (1, DecisionClass::FirstDecision(street)),
+                (0, DecisionClass::FacingBet(street)),
+                (1, DecisionClass::FacingBet(street)),
+            ],
+            runout_evs: true,
+            buckets: true,
+        }
+    }
+}
+
+impl StudyReport {
+    /// Serializes the report into a JSON document.
+    pub fn to_json(&self) -> String {
+        let string = |s: &str| JsonValue::String(s.to_string());
+        let number = |x: f64| JsonValue::Number(x);
+        let numbers = |v: &[f64]| JsonValue::Array(v.iter().map(|&x| number(x)).collect());
+        let object = |members: Vec<(&str, JsonValue)>| {
+            JsonValue::Object(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect(),
+            )
+        };
+
+        let root_grid = self.root_grid.iter().map(|cell| {
+            object(vec![
+                ("hand", string(&cell.hand)),
+                ("combos", number(cell.combos)),
+                ("frequencies", numbers(&cell.frequencies)),
+            ])
+        });
+
+        let aggregates = self.aggregates.iter().map(|entry| {
+            let f = &entry.frequencies;
+            object(vec![
+                ("player", number(entry.player as f64)),
+                ("street", string(&entry.street)),
+                ("class", string(&entry.class)),
+                (
+                    "frequencies",
+                    object(vec![
+                        ("num_nodes", number(f.num_nodes as f64)),
+                        ("fold", number(f.fold)),
+                        ("check", number(f.check)),
+                        ("call", number(f.call)),
+                        ("bet", number(f.bet)),
+                        ("raise", number(f.raise)),
+                    ]),
+                ),
+            ])
+        });
+
+        let runouts = self.runouts.iter().map(|entry| {
+            object(vec![
+                ("card", string(&entry.card)),
+                ("ev", numbers(&entry.ev)),
+                ("equity", numbers(&entry.equity)),
+            ])
+        });
+
+        let buckets = self.buckets.iter().map(|entry| {
+            object(vec![
+                ("player", number(entry.player as f64)),
+                ("category", string(&entry.category)),
+                ("combos", number(entry.combos)),
+                ("equity", number(entry.equity)),
+                ("ev", number(entry.ev)),
+                ("frequencies", numbers(&entry.frequencies)),
+            ])
+        });
+
+        object(vec![
+            ("board", string(&self.board)),
+            ("starting_pot", number(self.starting_pot as f64)),
+            ("dead_money", number(self.dead_money as f64)),
+            ("effective_stack", number(self.effective_stack as f64)),
+            ("exploitability", number(self.exploitability as f64)),
+            (
+                "player_names",
+                JsonValue::Array(self.player_names.iter().map(|s| string(s)).collect()),
+            ),
+            ("root_player", number(self.root_player as f64)),
+            (
+                "root_actions",
+                JsonValue::Array(self.root_actions.iter().map(|s| string(s)).collect()),
+            ),
+            ("root_grid", JsonValue::Array(root_grid.collect())),
+            ("aggregates", JsonValue::Array(aggregates.collect())),
+            ("runouts", JsonValue::Array(runouts.collect())),
+            ("buckets", JsonValue::Array(buckets.collect())),
+        ])
+        .to_json_string()
+    }
+}
+
+impl PostFlopGame {
+    /// Generates a report bundling the root strategy grid, the aggregate frequencies, the
+    /// expected values of each runout, and the breakdowns by made-hand category.
+    ///
+    /// The current node is restored after the call. Returns an error if the game is not solved.
+    /// Aggregates of decision classes that have no reachable node are omitted.
+    pub fn generate_report(&mut self, spec: &ReportSpec) -> Result<StudyReport, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        let saved_history = self.history().to_vec();
+        self.back_to_root();
+        self.cache_normalized_weights();
+
+        let root_player = self.current_player();
+        let root_actions = self.available_actions();
+        let root_strategy = self.strategy();
+
+        let mut report = StudyReport {
+            board: self
+                .current_board()
+                .iter()
+                .map(|&card| card_to_string(card).unwrap())
+                .collect(),
+            starting_pot: self.tree_config.starting_pot,
+            dead_money: self.tree_config.dead_money,
+            effective_stack: self.tree_config.effective_stack,
+            exploitability: compute_exploitability(self),
+            player_names: [0, 1].map(|player| self.card_config.player_name(player).to_string()),
+            root_player,
+            root_actions: root_actions.iter().map(|&a| action_to_string(a)).collect(),
+            ..Default::default()
+        };
+
+        if spec.root_grid {
+            report.root_grid = self.root_grid(root_player, &root_strategy, root_actions.len());
+        }
+
+        if spec.buckets {
+            for player in 0..2 {
+                let strategy = if player == root_player {
+                    Some(root_strategy.as_slice())
+                } else {
+                    None
+                };
+                let buckets = self.bucket_breakdown(player, strategy, root_actions.len());
+                report.buckets.extend(buckets);
+            }
+        }
+
+        if spec.runout_evs {
+            report.runouts = self.runout_evs();
+        }
+
+        for &(player, class) in &spec.aggregates {
+            if let Ok(frequencies) = self.aggregate_frequencies(player, class) {
+                let (street, class) = match class {
+                    DecisionClass::FirstDecision(street) => (street, "first_decision"),
+                    DecisionClass::Unopened(street) => (street, "unopened"),
+                    DecisionClass::FacingBet(street) => (street, "facing_bet"),
+                    DecisionClass::FacingRaise(street) => (street, "facing_raise"),
+                };
+                report.aggregates.push(AggregateEntry {
+                    player,
+                    street: ["flop", "turn", "river"][street as usize].to_string(),
+                    class: class.to_string(),
+                    frequencies,
+                });
+            }
+        }
+
+        self.apply_history(&saved_history);
+        Ok(report)
+    }
+
+    /// Aggregates the root strategy by hand class. The normalized weights must be cached.
+    fn root_grid(&self, player: usize, strategy: &[f32], num_actions: usize) -> Vec<GridCell> {
+        let weights = self.weights(player);
+        let normalized_weights = self.normalized_weights(player);
+        let num_hands = weights.len();
+
+        let mut combos = [0.0; 169];
+        let mut reach = [0.0; 169];
+        let mut sums = vec![[0.0; 169]; num_actions];
+
+        for (hand, &(c1, c2)) in self.private_cards(player).iter().enumerate() {
+            let (rank1, rank2) = ((c1 >> 2) as usize, (c2 >> 2) as usize);
+            let (high, low) = (rank1.max(rank2), rank1.min(rank2));
+            let (row, col) = if c1 & 3 == c2 & 3 {
+                (12 - high, 12 - low)
+            } else {
+                (12 - low, 12 - high)
+            };
+
+            let index = row * 13 + col;
+            let w = normalized_weights[hand] as f64;
+            combos[index] += weights[hand] as f64;
+            reach[index] += w;
+            for (action, sum) in sums.iter_mut().enumerate() {
+                sum[index] += strategy[action * num_hands + hand] as f64 * w;
+            }
+        }
+
+        (0..169)
+            .filter(|&index| combos[index] > 0.0)
+            .map(|index| {
+                let (row, col) = (index / 13, index % 13);
+                let (high, low) = (12 - row.min(col), 12 - row.max(col));
+                let suffix = match row.cmp(&col) {
+                    std::cmp::Ordering::Less => "s",
+                    std::cmp::Ordering::Equal => "",
+                    std::cmp::Ordering::Greater => "o",
+                };
+                let frequencies = sums
+                    .iter()
+                    .map(|sum| {
+                        if reach[index] > 0.0 {
+                            sum[index] / reach[index]
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect();
+                GridCell {
+                    hand: format!("{}{}{suffix}", RANK_CHARS[high], RANK_CHARS[low]),
+                    combos: combos[index],
+                    frequencies,
+                }
+            })
+            .collect()
+    }
+
+    /// Breaks down the range at the root node by made-hand category. The normalized weights
+    /// must be cached.
+    fn bucket_breakdown(
+        &self,
+        player: usize,
+        strategy: Option<&[f32]>,
+        num_actions: usize,
+    ) -> Vec<BucketEntry> {
+        let weights = self.weights(player);
+        let normalized_weights = self.normalized_weights(player);
+        let equity = self.equity(player);
+        let ev = self.expected_values(player);
+        let num_hands = weights.len();
+
+        let mut board = Hand::new();
+        for &card in &self.current_board() {
+            board = board.add_card(card as usize);
+        }
+
+        let mut buckets = (0..HAND_CATEGORIES.len())
+            .map(|category| BucketEntry {
+                player,
+                category: HAND_CATEGORIES[category].to_string(),
+                frequencies: vec![0.0; if strategy.is_some() { num_actions } else { 0 }],
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let mut reach = [0.0; HAND_CATEGORIES.len()];
+
+        for (hand, &(c1, c2)) in self.private_cards(player).iter().enumerate() {
+            if weights[hand] == 0.0 {
+                continue;
+            }
+
+            let category = board.add_card(c1 as usize).add_card(c2 as usize).category();
+            let w = normalized_weights[hand] as f64;
+            let bucket = &mut buckets[category];
+            bucket.combos += weights[hand] as f64;
+            bucket.equity += equity[hand] as f64 * w;
+            bucket.ev += ev[hand] as f64 * w;
+            if let Some(strategy) = strategy {
+                for (action, freq) in bucket.frequencies.iter_mut().enumerate() {
+                    *freq += strategy[action * num_hands + hand] as f64 * w;
+                }
+            }
+            reach[category] += w;
+        }
+
+        buckets
+            .into_iter()
+            .zip(reach)
+            .filter(|(bucket, _)| bucket.combos > 0.0)
+            .map(|(mut bucket, reach)| {
+                if reach > 0.0 {
+                    bucket.equity /= reach;
+                    bucket.ev /= reach;
+                    bucket.frequencies.iter_mut().for_each(|f| *f /= reach);
+                }
+                bucket
+            })
+            .collect()
+    }
+
+    /// Computes the expected values for each card dealt at the first chance node reached by
+    /// checking and calling from the root node.
+    fn runout_evs(&mut self) -> Vec<RunoutEntry> {
+        self.back_to_root();
+        let mut history = Vec::new();
+        while !self.is_terminal_node() && !self.is_chance_node() {
+            let actions = self.available_actions();
+            let Some(index) = actions
+                .iter()
+                .position(|&a| a == Action::Check || a == Action::Call)
+            else {
+                return Vec::new();
+            };
+            history.push(index);
+            self.play(index);
+        }
+
+        if !self.is_chance_node() {
+            return Vec::new();
+        }
+
+        let possible_cards = self.possible_cards();
+        let mut ret = Vec::new();
+        for card in 0..52u8 {
+            if possible_cards & (1 << card) == 0 {
+                continue;
+            }
+
+            self.apply_history(&history);
+            self.play(card as usize);
+            self.cache_normalized_weights();
+
+            let mut entry = RunoutEntry {
+                card: card_to_string(card).unwrap(),
+                ..Default::default()
+            };
+            for player in 0..2 {
+                let normalized_weights = self.normalized_weights(player);
+                entry.ev[player] =
+                    compute_average(&self.expected_values(player), normalized_weights) as f64;
+                entry.equity[player] =
+                    compute_average(&self.equity(player), normalized_weights) as f64;
+            }
+            ret.push(entry);
+        }
+
+        ret
+    }
+}
@@ -0,0 +1,61 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+
+impl PostFlopGame {
+    /// Resets the cumulative regrets and strategies of `player` to the freshly allocated state,
+    /// while keeping those of the opponent.
+    ///
+    /// This allows, e.g., measuring how IP adapts from scratch to the converged strategy of OOP
+    /// without re-solving the whole game: after the call, the solver iterations continue with the
+    /// uniform strategy of `player` against the current strategy of the opponent. The locked
+    /// strategies are kept. Note that the discount factors of the solver depend on the iteration
+    /// number passed to [`solve_step`], so restarting the iteration count from zero discounts the
+    /// regrets of the opponent as well.
+    ///
+    /// Returns an error if `player` is invalid, or the game is not in the `MemoryAllocated` or
+    /// `Solving` state (see [`check_operation`]); the finalized strategies cannot be reset.
+    ///
+    /// [`solve_step`]: crate::solve_step
+    /// [`check_operation`]: #method.check_operation
+    pub fn reset_player(&mut self, player: usize) -> Result<(), Error> {
+        if player >= 2 {
+            return Err(Error::Config(format!("Invalid player: {player}")));
+        }
+
+        self.check_operation(GameOperation::Solve)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            if node.is_terminal() || node.is_chance() || node.player() != player {
+                continue;
+            }
+
+            // the unallocated node is already in the freshly allocated state
+            if !self.is_node_allocated(&node) {
+                continue;
+            }
+
+            if self.is_node_compressed(&node) {
+                node.strategy_compressed_mut().fill(0);
+                node.regrets_compressed_mut().fill(0);
+                node.set_strategy_scale(0.0);
+                node.set_regret_scale(0.0);
+            } else if self.is_f64_regrets_enabled {
+                node.strategy_mut().fill(0.0);
+                node.regrets_f64_mut().fill(0.0);
+            } else {
+                node.strategy_mut().fill(0.0);
+                node.regrets_mut().fill(0.0);
+            }
+        }
+
+        self.lock_decoded_cache().clear();
+
+        Ok(())
+    }
+}
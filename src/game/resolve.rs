@@ -0,0 +1,184 @@
+use super::*;
+use crate::bet_size::*;
+use crate::error::*;
+use crate::solver::*;
+use crate::utility::*;
+
+/// Options of [`PostFlopGame::resolve_subgame_unsafe`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveConfig {
+    /// Bet size options of each player for the turn, replacing the inherited ones (`None` keeps
+    /// them).
+    pub turn_bet_sizes: Option<[BetSizeOptions; 2]>,
+
+    /// Bet size options of each player for the river, replacing the inherited ones (`None` keeps
+    /// them).
+    pub river_bet_sizes: Option<[BetSizeOptions; 2]>,
+
+    /// Maximum number of iterations to solve the subgame.
+    pub max_num_iterations: u32,
+
+    /// Target exploitability of the subgame.
+    pub target_exploitability: f32,
+}
+
+/// The result of [`PostFlopGame::resolve_subgame_unsafe`].
+///
+/// The vectors of each player are in the order of `game.private_cards(player)`, and the entries
+/// of the hands that do not reach the subgame are zero.
+pub struct ResolvedSubgame {
+    /// The re-solved subgame.
+    pub game: PostFlopGame,
+
+    /// The exploitability of the re-solved subgame.
+    pub exploitability: f32,
+
+    /// The expected value of each hand at the root of the subgame in the parent solve.
+    pub parent_ev: [Vec<f32>; 2],
+
+    /// The expected value of each hand when it plays the best response against the re-solved
+    /// strategy of the opponent.
+    pub best_response_ev: [Vec<f32>; 2],
+
+    /// The minimum of `parent_ev - best_response_ev` over the hands reaching the subgame.
+    ///
+    /// This is measured after the re-solve and does not constrain it. A negative value for
+    /// `player` means that some hand of `player` gains against the re-solved strategy of the
+    /// opponent compared to the parent solve, so replacing the parent strategy of the opponent
+    /// with the re-solved one may make the whole strategy more exploitable than the parent
+    /// solve. A non-negative value only tells that no hand gains at the root of the subgame.
+    pub min_margin: [f32; 2],
+}
+
+impl Default for ResolveConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            turn_bet_sizes: None,
+            river_bet_sizes: None,
+            max_num_iterations: 1000,
+            target_exploitability: 0.0,
+        }
+    }
+}
+
+impl PostFlopGame {
+    /// Re-solves the subtree rooted at the node reached by `history`, e.g., with finer bet sizes
+    /// or a lower target exploitability than the parent solve.
+    ///
+    /// The subgame is built as in [`extract_subtree`]: it starts at the street of the node, and
+    /// its ranges are the reach probabilities of the node in the parent solve (see
+    /// [`weights`]). The bet sizes of the later streets can be replaced with [`ResolveConfig`].
+    /// Only the subgame is allocated, so a coarse flop solve can be refined street by street
+    /// without the memory of a fine tree of the whole game.
+    ///
+    /// This is unsafe re-solving: the subgame is solved on its own from the reach probabilities,
+    /// and the counterfactual values of the parent solve do not constrain it. The opponent may
+    /// therefore gain by reaching the subgame with a different range than in the parent solve,
+    /// and the re-solved strategies can be more exploitable than the parent ones when they are
+    /// substituted into the whole game. As a diagnostic, the expected values of each hand in the
+    /// parent solve are compared with the values of the best response against the re-solved
+    /// strategies afterwards (see [`ResolvedSubgame::min_margin`]).
+    ///
+    /// The node must be the first decision of a street, i.e., the root or a node right after a
+    /// chance node, and the storage of the parent solve must reach the street of the node.
+    /// Returns an error if the game is not solved, the node is not such a node, or the bunching
+    /// effect or the turn abstraction is enabled. The current node is restored after the call.
+    ///
+    /// [`extract_subtree`]: #method.extract_subtree
+    /// [`weights`]: #method.weights
+    pub fn resolve_subgame_unsafe(
+        &mut self,
+        history: &[usize],
+        config: &ResolveConfig,
+    ) -> Result<ResolvedSubgame, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if !self.turn_buckets.is_empty() {
+            return Err(Error::Config(
+                "Turn abstraction is not supported".to_string(),
+            ));
+        }
+
+        let saved_history = self.history().to_vec();
+        self.apply_history(history);
+        let ret = self.resolve_current_subgame(history, config);
+        self.apply_history(&saved_history);
+        ret
+    }
+
+    /// Re-solves the subtree rooted at the current node, whose history is `history`.
+    fn resolve_current_subgame(
+        &mut self,
+        history: &[usize],
+        config: &ResolveConfig,
+    ) -> Result<ResolvedSubgame, Error> {
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree("Node is not a decision node".to_string()));
+        }
+
+        if !history.is_empty() && !matches!(self.node().prev_action, Action::Chance(_)) {
+            return Err(Error::Tree(
+                "Node is not the first decision of a street".to_string(),
+            ));
+        }
+
+        let (card_config, mut tree_config) = self.subgame_configs()?;
+        if let Some(bet_sizes) = &config.turn_bet_sizes {
+            tree_config.turn_bet_sizes = bet_sizes.clone();
+        }
+        if let Some(bet_sizes) = &config.river_bet_sizes {
+            tree_config.river_bet_sizes = bet_sizes.clone();
+        }
+
+        let mut game = self.build_subgame(card_config, tree_config)?;
+        game.allocate_memory(false);
+        let exploitability = solve(
+            &mut game,
+            config.max_num_iterations,
+            config.target_exploitability,
+            false,
+        )
+        .exploitability;
+
+        self.cache_normalized_weights();
+        game.cache_normalized_weights();
+
+        let mut parent_ev = [Vec::new(), Vec::new()];
+        let mut best_response_ev = [Vec::new(), Vec::new()];
+        let mut min_margin = [f32::INFINITY; 2];
+        let bias = game.tree_config.starting_pot as f32 * 0.5;
+
+        for player in 0..2 {
+            let ev = self.expected_values(player);
+            let cfvalues = compute_best_response_cfvalues(&game, player);
+            let hands = game.private_cards(player);
+            let raw_weights = game.weights(player);
+            let normalized_weights = game.normalized_weights(player);
+
+            parent_ev[player] = vec![0.0; hands.len()];
+            best_response_ev[player] = vec![0.0; hands.len()];
+
+            for (i, hand) in hands.iter().enumerate() {
+                if normalized_weights[i] == 0.0 {
+                    continue;
+                }
+
+                let index = self.private_cards(player).binary_search(hand).unwrap();
+                let scale = game.num_combinations as f32 * raw_weights[i] / normalized_weights[i];
+                parent_ev[player][i] = ev[index];
+                best_response_ev[player][i] = cfvalues[i] * scale + bias;
+                min_margin[player] =
+                    min_margin[player].min(parent_ev[player][i] - best_response_ev[player][i]);
+            }
+        }
+
+        Ok(ResolvedSubgame {
+            game,
+            exploitability,
+            parent_ev,
+            best_response_ev,
+            min_margin,
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use super::*;
+use crate::error::*;
+use crate::solver::*;
+
+impl PostFlopGame {
+    /// Solves the subgame following the chance node reached by `history`, with the chance card
+    /// fixed to `card` (e.g., "the turn is the 7c, now what").
+    ///
+    /// The subgame starts at the street of `card`: the board, the pot, and the effective stack
+    /// are taken from the chance node, the ranges are the reach weights at the chance node (see
+    /// [`weights`]), i.e., the strategies of the earlier streets are reused, and the tree
+    /// configuration is otherwise inherited. Only the branch of `card` is built and solved, so
+    /// this works even if the storage of this game does not cover the later streets (e.g., a game
+    /// saved with the `BoardState::Flop` storage mode). To fix the river as well, call this
+    /// method again on the returned game. The returned game is solved for at most
+    /// `max_num_iterations` iterations (or until its exploitability reaches
+    /// `target_exploitability`).
+    ///
+    /// Returns an error if the game is not solved, `history` does not lead to a chance node,
+    /// `card` cannot be dealt, the bunching effect or the turn abstraction is enabled, or the
+    /// configuration of the subgame is invalid. Panics if `history` is invalid (see
+    /// [`apply_history`]). The current node is restored after the call.
+    ///
+    /// [`weights`]: #method.weights
+    /// [`apply_history`]: #method.apply_history
+    pub fn solve_runout(
+        &mut self,
+        history: &[usize],
+        card: Card,
+        max_num_iterations: u32,
+        target_exploitability: f32,
+    ) -> Result<PostFlopGame, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if !self.turn_buckets.is_empty() {
+            return Err(Error::Config(
+                "Turn abstraction is not supported".to_string(),
+            ));
+        }
+
+        let saved_history = self.history().to_vec();
+        self.apply_history(history);
+        let ret = self.runout_subgame(card);
+        self.apply_history(&saved_history);
+
+        let mut game = ret?;
+        game.allocate_memory(false);
+        solve(&mut game, max_num_iterations, target_exploitability, false);
+        Ok(game)
+    }
+
+    /// Builds the subgame following the current chance node with the chance card fixed to
+    /// `card`.
+    fn runout_subgame(&self, card: Card) -> Result<PostFlopGame, Error> {
+        if !self.is_chance_node() {
+            return Err(Error::Tree("Node is not a chance node".to_string()));
+        }
+
+        if card >= 52 || self.possible_cards() & (1 << card) == 0 {
+            return Err(Error::Tree(format!("Card cannot be dealt: {card}")));
+        }
+
+        let (mut card_config, mut tree_config) = self.subgame_configs()?;
+
+        if self.turn == NOT_DEALT {
+            card_config.turn = card;
+            tree_config.initial_state = BoardState::Turn;
+        } else {
+            card_config.river = card;
+            tree_config.initial_state = BoardState::River;
+        }
+
+        self.build_subgame(card_config, tree_config)
+    }
+}
@@ -0,0 +1,125 @@
+use super::*;
+use crate::error::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Expected values of each private hand for each card dealt at a chance node, produced by
+/// [`PostFlopGame::runout_values`].
+///
+/// The vectors of each player are matrices of the cards and the private hands: the entry of the
+/// `i`-th card and the `j`-th private hand (in the order of [`PostFlopGame::private_cards`]) is
+/// stored in the `i * #(private hands) + j`-th element.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RunoutValues {
+    /// The cards that can be dealt, in ascending order.
+    pub cards: Vec<Card>,
+
+    /// The expected values of each player after each card, as returned by
+    /// [`PostFlopGame::expected_values`] at the child node. The entries of the hands that do not
+    /// reach the child (including those overlapping with the card) are zero.
+    pub values: [Vec<f32>; 2],
+
+    /// The number of combinations with which each hand reaches the child (see
+    /// [`PostFlopGame::normalized_weights`]).
+    pub weights: [Vec<f32>; 2],
+}
+
+impl RunoutValues {
+    /// Returns the number of private hands of the given player.
+    #[inline]
+    pub fn num_private_hands(&self, player: usize) -> usize {
+        self.values[player]
+            .len()
+            .checked_div(self.cards.len())
+            .unwrap_or(0)
+    }
+
+    /// Returns the expected values of each private hand of the given player after the
+    /// `card_index`-th card.
+    #[inline]
+    pub fn card_values(&self, player: usize, card_index: usize) -> &[f32] {
+        let num_hands = self.num_private_hands(player);
+        &self.values[player][card_index * num_hands..(card_index + 1) * num_hands]
+    }
+
+    /// Returns the expected values of the `hand`-th private hand of the given player after each
+    /// card, in the order of [`cards`].
+    ///
+    /// [`cards`]: #structfield.cards
+    pub fn hand_values(&self, player: usize, hand: usize) -> Vec<f32> {
+        let num_hands = self.num_private_hands(player);
+        self.values[player]
+            .iter()
+            .skip(hand)
+            .step_by(num_hands.max(1))
+            .copied()
+            .collect()
+    }
+}
+
+impl PostFlopGame {
+    /// Computes the expected values of each private hand of both players for each card that can
+    /// be dealt at the current chance node.
+    ///
+    /// This is equivalent to playing each card, calling [`expected_values`] and
+    /// [`normalized_weights`] at the child, and going back, but collects the results in a single
+    /// call, e.g., for drawing the expected value of each hand by turn card. Isomorphic chances
+    /// are expanded.
+    ///
+    /// Returns an error if the game is not solved, the current node is not a chance node, or the
+    /// storage mode does not cover the next street. The current node (and the cached normalized
+    /// weights, if any) are restored after the call.
+    ///
+    /// **Time complexity:** *O*(#(cards) * #(history) * (#(OOP private hands) + #(IP private
+    /// hands))), or more with the bunching effect.
+    ///
+    /// [`expected_values`]: #method.expected_values
+    /// [`normalized_weights`]: #method.normalized_weights
+    pub fn runout_values(&mut self) -> Result<RunoutValues, Error> {
+        if !self.is_current_node_finalized() {
+            return Err(Error::Config("Game is not solved".to_string()));
+        }
+
+        if !self.is_chance_node() {
+            return Err(Error::Tree("Current node is not a chance node".to_string()));
+        }
+
+        let is_turn = self.turn == NOT_DEALT;
+        if self.storage_mode == BoardState::Flop
+            || (!is_turn && self.storage_mode == BoardState::Turn)
+        {
+            return Err(Error::Config("Storage mode is not compatible".to_string()));
+        }
+
+        let history = self.history().to_vec();
+        let is_cached = self.is_normalized_weight_cached;
+        let possible_cards = self.possible_cards();
+        let mut ret = RunoutValues {
+            cards: (0..52)
+                .filter(|&c| possible_cards & (1 << c) != 0)
+                .collect(),
+            ..Default::default()
+        };
+
+        for (i, &card) in ret.cards.iter().enumerate() {
+            if i > 0 {
+                self.apply_history(&history);
+            }
+            self.play(card as usize);
+            self.cache_normalized_weights();
+            for player in 0..2 {
+                ret.values[player].extend(self.expected_values(player));
+                ret.weights[player].extend_from_slice(self.normalized_weights(player));
+            }
+        }
+
+        self.apply_history(&history);
+        if is_cached {
+            self.cache_normalized_weights();
+        }
+
+        Ok(ret)
+    }
+}
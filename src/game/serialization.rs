@@ -1,9 +1,11 @@
 use super::*;
 
+use crate::error::*;
+use crate::file::StrategyPrecision;
 use crate::interface::*;
-use crate::utility::*;
 use std::cell::Cell;
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 use bincode::{
     de::Decoder,
@@ -30,13 +32,17 @@ impl PostFlopGame {
 
     /// Sets the target storage mode.
     #[inline]
-    pub fn set_target_storage_mode(&mut self, mode: BoardState) -> Result<(), String> {
+    pub fn set_target_storage_mode(&mut self, mode: BoardState) -> Result<(), Error> {
         if mode > self.storage_mode {
-            return Err("Cannot set target to a higher value than the current storage".to_string());
+            return Err(Error::Config(
+                "Cannot set target to a higher value than the current storage".to_string(),
+            ));
         }
 
         if mode < self.tree_config.initial_state {
-            return Err("Cannot set target to a lower value than the initial state".to_string());
+            return Err(Error::Config(
+                "Cannot set target to a lower value than the initial state".to_string(),
+            ));
         }
 
         self.target_storage_mode = mode;
@@ -47,10 +53,7 @@ impl PostFlopGame {
     #[inline]
     pub fn target_memory_usage(&self) -> u64 {
         match self.target_storage_mode {
-            BoardState::River => match self.is_compression_enabled {
-                false => self.memory_usage().0,
-                true => self.memory_usage().1,
-            },
+            BoardState::River => self.memory_usage_with(self.compression_street()),
             _ => {
                 let num_target_storage = self.num_target_storage();
                 num_target_storage.iter().map(|&x| x as u64).sum::<u64>() + self.misc_memory_usage
@@ -64,10 +67,14 @@ impl PostFlopGame {
             return [0; 4];
         }
 
-        let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
-        if self.target_storage_mode == BoardState::River {
+        if self.target_storage_mode == BoardState::River && self.state == State::Solved {
             // omit storing the counterfactual values
-            return [num_bytes * self.num_storage as usize, 0, 0, 0];
+            return [self.storage1.len(), 0, 0, 0];
+        }
+
+        if self.target_storage_mode == BoardState::River {
+            // store the cumulative regrets to resume solving
+            return [self.storage1.len(), self.storage2.len(), 0, 0];
         }
 
         let mut node_index = match self.target_storage_mode {
@@ -80,13 +87,15 @@ impl PostFlopGame {
         while num_storage.iter().any(|&x| x == 0) {
             node_index -= 1;
             let node = self.node_arena[node_index].lock();
+            let num_bytes = if self.is_node_compressed(&node) { 2 } else { 4 };
             if num_storage[0] == 0 && !node.is_terminal() && !node.is_chance() {
                 let offset = unsafe { node.storage1.offset_from(self.storage1.as_ptr()) };
+                let offset2 = unsafe { node.storage2.offset_from(self.storage2.as_ptr()) };
                 let offset_ip = unsafe { node.storage3.offset_from(self.storage_ip.as_ptr()) };
                 let len = num_bytes * node.num_elements as usize;
                 let len_ip = num_bytes * node.num_elements_ip as usize;
                 num_storage[0] = offset as usize + len;
-                num_storage[1] = offset as usize + len;
+                num_storage[1] = offset2 as usize + len;
                 num_storage[2] = offset_ip as usize + len_ip;
             }
             if num_storage[3] == 0 && node.is_chance() {
@@ -100,13 +109,120 @@ impl PostFlopGame {
     }
 }
 
-static VERSION_STR: &str = "2023-03-19";
+static VERSION_STR: &str = "2026-10-17";
 
 thread_local! {
     static PTR_BASE: Cell<[*const u8; 2]> = Cell::new([ptr::null(); 2]);
     static CHANCE_BASE: Cell<*const u8> = Cell::new(ptr::null());
     static PTR_BASE_MUT: Cell<[*mut u8; 3]> = Cell::new([ptr::null_mut(); 3]);
     static CHANCE_BASE_MUT: Cell<*mut u8> = Cell::new(ptr::null_mut());
+    static STRATEGY_ONLY: Cell<bool> = Cell::new(false);
+    static STRATEGY_PRECISION: Cell<Option<StrategyPrecision>> = Cell::new(None);
+}
+
+// strategy storage tags
+const STRATEGY_FULL: u8 = 0;
+const STRATEGY_ONLY_FULL: u8 = 1;
+const STRATEGY_ONLY_QUANTIZED: u8 = 2;
+
+impl PostFlopGame {
+    /// Calls `f`, in which the solved games are decoded without allocating the storage of the
+    /// counterfactual values (see [`PostFlopGame::is_strategy_only`]).
+    pub(crate) fn with_strategy_only_decoding<R>(f: impl FnOnce() -> R) -> R {
+        STRATEGY_ONLY.with(|c| c.set(true));
+        let ret = f();
+        STRATEGY_ONLY.with(|c| c.set(false));
+        ret
+    }
+
+    /// Calls `f`, in which the solved games are encoded without the counterfactual values and
+    /// with the strategies of the given precision.
+    pub(crate) fn with_strategy_only_encoding<R>(
+        precision: StrategyPrecision,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        STRATEGY_PRECISION.with(|c| c.set(Some(precision)));
+        let ret = f();
+        STRATEGY_PRECISION.with(|c| c.set(None));
+        ret
+    }
+
+    /// Quantizes the strategies of all decision nodes to 8 bits in the order of the node arena.
+    ///
+    /// Each probability is scaled by the largest probability of the same hand, which is harmless
+    /// because the strategy of each hand is normalized when read.
+    fn quantize_strategy_storage(&self) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(self.storage1.len() / 2);
+
+        for node in &self.node_arena {
+            let node = node.lock();
+            if node.is_terminal() || node.is_chance() {
+                continue;
+            }
+
+            let values = if self.is_node_compressed(&node) {
+                node.strategy_compressed()
+                    .iter()
+                    .map(|&x| x as f32)
+                    .collect::<Vec<_>>()
+            } else {
+                node.strategy().to_vec()
+            };
+
+            let num_hands = values.len() / node.num_actions();
+            let offset = ret.len();
+            ret.resize(offset + values.len(), 0);
+
+            for hand in 0..num_hands {
+                let max = values[hand..]
+                    .iter()
+                    .step_by(num_hands)
+                    .fold(0.0f32, |acc, &x| acc.max(x));
+                if max > 0.0 {
+                    for index in (hand..values.len()).step_by(num_hands) {
+                        ret[offset + index] = (values[index] / max * 255.0).round() as u8;
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Restores the strategies quantized by `quantize_strategy_storage`.
+    fn dequantize_strategy_storage(&self, data: &[u8]) -> Result<(), DecodeError> {
+        let mut offset = 0;
+
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            if node.is_terminal() || node.is_chance() {
+                continue;
+            }
+
+            let len = node.num_elements as usize;
+            let src = data
+                .get(offset..offset + len)
+                .ok_or(DecodeError::Other("Quantized strategy is truncated"))?;
+
+            if self.is_node_compressed(&node) {
+                for (dst, &src) in node.strategy_compressed_mut().iter_mut().zip(src) {
+                    *dst = src as u16 * 257;
+                }
+            } else {
+                for (dst, &src) in node.strategy_mut().iter_mut().zip(src) {
+                    *dst = src as f32 / 255.0;
+                }
+            }
+
+            offset += len;
+        }
+
+        if offset != data.len() {
+            return Err(DecodeError::Other("Quantized strategy has extra data"));
+        }
+
+        Ok(())
+    }
 }
 
 impl Encode for PostFlopGame {
@@ -115,6 +231,31 @@ impl Encode for PostFlopGame {
             return Err(EncodeError::Other("Game is not successfully initialized"));
         }
 
+        if self.is_strategy_only && self.target_storage_mode != BoardState::River {
+            return Err(EncodeError::Other(
+                "Counterfactual values are not available (strategy-only game)",
+            ));
+        }
+
+        if self.is_lazy_allocation {
+            return Err(EncodeError::Other("Lazily allocated game cannot be saved"));
+        }
+
+        let precision = STRATEGY_PRECISION.with(Cell::get);
+        if precision.is_none() && self.has_pending_strategy_edits {
+            return Err(EncodeError::Other(
+                "Expected values are outdated by strategy edits (see `recompute_evs`)",
+            ));
+        }
+
+        if precision.is_some()
+            && (self.state != State::Solved || self.target_storage_mode != BoardState::River)
+        {
+            return Err(EncodeError::Other(
+                "Strategy-only save requires a solved game with the river target storage mode",
+            ));
+        }
+
         let num_storage = self.num_target_storage();
 
         // version
@@ -123,18 +264,34 @@ impl Encode for PostFlopGame {
         // contents
         self.state.encode(encoder)?;
         self.card_config.encode(encoder)?;
-        self.tree_config.encode(encoder)?;
-        self.added_lines.encode(encoder)?;
-        self.removed_lines.encode(encoder)?;
-        self.action_root.encode(encoder)?;
+        self.topology.encode(encoder)?;
+        self.turn_buckets.encode(encoder)?;
         self.target_storage_mode.encode(encoder)?;
         self.num_nodes.encode(encoder)?;
         self.is_compression_enabled.encode(encoder)?;
+        self.compression_street.encode(encoder)?;
+        self.is_f64_regrets_enabled.encode(encoder)?;
         self.num_storage.encode(encoder)?;
         self.num_storage_ip.encode(encoder)?;
         self.num_storage_chance.encode(encoder)?;
         self.misc_memory_usage.encode(encoder)?;
-        self.storage1[0..num_storage[0]].encode(encoder)?;
+
+        match precision {
+            None => {
+                STRATEGY_FULL.encode(encoder)?;
+                self.storage1[0..num_storage[0]].encode(encoder)?;
+            }
+            Some(StrategyPrecision::Full) => {
+                STRATEGY_ONLY_FULL.encode(encoder)?;
+                self.storage1[0..num_storage[0]].encode(encoder)?;
+            }
+            Some(StrategyPrecision::Quantized8) => {
+                STRATEGY_ONLY_QUANTIZED.encode(encoder)?;
+                self.storage1.len().encode(encoder)?;
+                self.quantize_strategy_storage().encode(encoder)?;
+            }
+        }
+
         self.storage2[0..num_storage[1]].encode(encoder)?;
         self.storage_ip[0..num_storage[2]].encode(encoder)?;
         self.storage_chance[0..num_storage[3]].encode(encoder)?;
@@ -150,6 +307,19 @@ impl Encode for PostFlopGame {
         locking_strategy.retain(|&i, _| i < num_nodes);
         locking_strategy.encode(encoder)?;
 
+        // showdown realization (need to filter)
+        let mut showdown_realization = self.showdown_realization.clone();
+        showdown_realization.retain(|&i, _| i < num_nodes);
+        showdown_realization.encode(encoder)?;
+
+        // allocated sizes of the counterfactual value storages (depend on the compressed streets)
+        [self.storage_ip.len(), self.storage_chance.len()].encode(encoder)?;
+
+        // number of solver iterations performed
+        self.num_iterations
+            .load(Ordering::Relaxed)
+            .encode(encoder)?;
+
         // store base pointers
         PTR_BASE.with(|c| {
             if self.state >= State::MemoryAllocated {
@@ -184,35 +354,77 @@ impl Decode for PostFlopGame {
             )));
         }
 
+        let state = Decode::decode(decoder)?;
+        let card_config = Decode::decode(decoder)?;
+        let topology = TreeTopology::decode(decoder)?;
+
         // game instance
         let mut game = Self {
-            state: Decode::decode(decoder)?,
-            card_config: Decode::decode(decoder)?,
-            tree_config: Decode::decode(decoder)?,
-            added_lines: Decode::decode(decoder)?,
-            removed_lines: Decode::decode(decoder)?,
-            action_root: Decode::decode(decoder)?,
+            state,
+            card_config,
+            tree_config: topology.config().clone(),
+            topology,
+            turn_buckets: Decode::decode(decoder)?,
             storage_mode: Decode::decode(decoder)?,
             num_nodes: Decode::decode(decoder)?,
             is_compression_enabled: Decode::decode(decoder)?,
+            compression_street: Decode::decode(decoder)?,
+            is_f64_regrets_enabled: Decode::decode(decoder)?,
             num_storage: Decode::decode(decoder)?,
             num_storage_ip: Decode::decode(decoder)?,
             num_storage_chance: Decode::decode(decoder)?,
             misc_memory_usage: Decode::decode(decoder)?,
-            storage1: Decode::decode(decoder)?,
-            storage2: Decode::decode(decoder)?,
-            storage_ip: Decode::decode(decoder)?,
-            storage_chance: Decode::decode(decoder)?,
-            locking_strategy: Decode::decode(decoder)?,
             ..Default::default()
         };
 
+        let strategy_tag: u8 = Decode::decode(decoder)?;
+        let quantized_strategy = match strategy_tag {
+            STRATEGY_FULL | STRATEGY_ONLY_FULL => {
+                game.storage1 = Vec::<u8>::decode(decoder)?.into();
+                None
+            }
+            STRATEGY_ONLY_QUANTIZED => {
+                game.storage1 = vec![0; usize::decode(decoder)?].into();
+                Some(Vec::<u8>::decode(decoder)?)
+            }
+            _ => return Err(DecodeError::Other("Invalid strategy storage tag")),
+        };
+
+        if strategy_tag != STRATEGY_FULL
+            && (game.state != State::Solved || game.storage_mode != BoardState::River)
+        {
+            return Err(DecodeError::Other("Invalid strategy-only game"));
+        }
+
+        game.storage2 = Vec::<u8>::decode(decoder)?.into();
+        game.storage_ip = Vec::<u8>::decode(decoder)?.into();
+        game.storage_chance = Vec::<u8>::decode(decoder)?.into();
+        game.locking_strategy = Decode::decode(decoder)?;
+        game.showdown_realization = Decode::decode(decoder)?;
+
+        let storage_cfvalues_bytes: [usize; 2] = Decode::decode(decoder)?;
+        let num_iterations: u32 = Decode::decode(decoder)?;
+        game.num_iterations = AtomicU32::new(num_iterations);
+        game.is_solving =
+            AtomicBool::new(num_iterations > 0 && game.state == State::MemoryAllocated);
+
         game.target_storage_mode = game.storage_mode;
-        if game.storage_mode == BoardState::River && game.state >= State::MemoryAllocated {
-            let num_bytes = if game.is_compression_enabled { 2 } else { 4 };
-            game.storage2 = vec![0; (num_bytes * game.num_storage) as usize];
-            game.storage_ip = vec![0; (num_bytes * game.num_storage_ip) as usize];
-            game.storage_chance = vec![0; (num_bytes * game.num_storage_chance) as usize];
+        game.is_strategy_only = strategy_tag != STRATEGY_FULL
+            || (STRATEGY_ONLY.with(Cell::get)
+                && game.storage_mode == BoardState::River
+                && game.state == State::Solved);
+
+        if game.storage_mode == BoardState::River
+            && game.state >= State::MemoryAllocated
+            && !game.is_strategy_only
+        {
+            // the cumulative regrets are stored only when the game is not solved
+            if game.storage2.is_empty() {
+                let regrets_coef = if game.is_f64_regrets_enabled { 2 } else { 1 };
+                game.storage2 = vec![0; regrets_coef * game.storage1.len()].into();
+            }
+            game.storage_ip = vec![0; storage_cfvalues_bytes[0]].into();
+            game.storage_chance = vec![0; storage_cfvalues_bytes[1]].into();
         }
 
         // store base pointers
@@ -239,16 +451,41 @@ impl Decode for PostFlopGame {
         // game tree
         game.node_arena = Decode::decode(decoder)?;
 
+        if let Some(quantized_strategy) = &quantized_strategy {
+            game.dequantize_strategy_storage(quantized_strategy)?;
+        }
+
+        // 64-bit regrets occupy twice as many bytes as the strategy
+        if game.is_f64_regrets_enabled
+            && game.state >= State::MemoryAllocated
+            && !game.is_strategy_only
+        {
+            let base1 = game.storage1.as_ptr();
+            let base2 = game.storage2.as_mut_ptr();
+            for node in &game.node_arena {
+                let mut node = node.lock();
+                if !node.is_terminal() && !node.is_chance() {
+                    let offset = unsafe { node.storage1.offset_from(base1) };
+                    node.storage2 = unsafe { base2.offset(2 * offset) };
+                }
+            }
+        }
+
         // initialization
-        game.check_card_config().map_err(DecodeError::OtherString)?;
+        game.update_parallel_nodes();
+        game.check_card_config()
+            .map_err(|e| DecodeError::OtherString(e.to_string()))?;
         game.init_card_fields();
         game.init_interpreter();
         game.back_to_root();
 
         // restore the counterfactual values
-        if game.storage_mode == BoardState::River && game.state == State::Solved {
-            game.state = State::MemoryAllocated;
-            finalize(&mut game);
+        if game.storage_mode == BoardState::River
+            && game.state == State::Solved
+            && !game.is_strategy_only
+        {
+            game.recompute_evs()
+                .map_err(|e| DecodeError::OtherString(e.to_string()))?;
         }
 
         Ok(game)
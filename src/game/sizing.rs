@@ -0,0 +1,259 @@
+use super::*;
+use crate::bet_size::*;
+use crate::error::*;
+use crate::solver::*;
+use crate::utility::*;
+
+/// Options of [`PostFlopGame::find_optimal_bet_size`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BetSizeSearchConfig {
+    /// Smallest bet size to consider, as a fraction of the pot (e.g., `0.1` for 10%).
+    pub min_size: f64,
+
+    /// Largest bet size to consider, as a fraction of the pot.
+    pub max_size: f64,
+
+    /// The search stops when the bracket of bet sizes becomes narrower than this value.
+    pub tolerance: f64,
+
+    /// Maximum number of candidate sizes to solve (each candidate solves one subgame).
+    pub max_candidates: usize,
+
+    /// Maximum number of iterations to solve each subgame.
+    pub max_num_iterations: u32,
+
+    /// Target exploitability of each subgame.
+    pub target_exploitability: f32,
+}
+
+/// A bet size evaluated by [`PostFlopGame::find_optimal_bet_size`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BetSizeCandidate {
+    /// The bet size as a fraction of the pot.
+    pub size: f64,
+
+    /// The bet amount that the size resolves to.
+    pub amount: i32,
+
+    /// The expected value of the player at the node, averaged over the combinations.
+    pub ev: f64,
+
+    /// The exploitability of the solved subgame.
+    pub exploitability: f32,
+}
+
+/// The result of [`PostFlopGame::find_optimal_bet_size`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BetSizeSearchResult {
+    /// The player whose bet size is searched.
+    pub player: usize,
+
+    /// The candidate with the highest expected value.
+    pub best: BetSizeCandidate,
+
+    /// All evaluated candidates in the order of evaluation. Sizes resolving to the same bet
+    /// amount are solved only once.
+    pub candidates: Vec<BetSizeCandidate>,
+}
+
+impl Default for BetSizeSearchConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            min_size: 0.1,
+            max_size: 2.0,
+            tolerance: 0.05,
+            max_candidates: 12,
+            max_num_iterations: 200,
+            target_exploitability: 0.0,
+        }
+    }
+}
+
+impl Default for BetSizeCandidate {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            size: 0.0,
+            amount: 0,
+            ev: f64::NEG_INFINITY,
+            exploitability: 0.0,
+        }
+    }
+}
+
+const INV_PHI: f64 = 0.618_033_988_749_895;
+
+impl PostFlopGame {
+    /// Searches for the single bet size that maximizes the expected value of the current player
+    /// at the current node.
+    ///
+    /// For each candidate size, a subgame starting from the current node is built and solved:
+    /// the board, the pot, the stacks, and the ranges (the reach weights at the current node) are
+    /// taken from the current node, and the bet sizes of the current player on the current street
+    /// are replaced with the candidate. The remaining configuration (the other bet sizes, rake,
+    /// and thresholds) is inherited from the tree configuration, while the locked strategies and
+    /// the removed lines are not. The candidates are chosen by golden-section search over
+    /// [`min_size`, `max_size`], which assumes that the expected value is unimodal in the size.
+    ///
+    /// The current node must be a decision node where the current player can bet, i.e., the
+    /// first action of a street or the action after the opponent's check. Returns an error if the
+    /// game is not solved, the current node is not such a node, the bunching effect is enabled,
+    /// or the configuration is invalid.
+    ///
+    /// [`min_size`]: BetSizeSearchConfig::min_size
+    /// [`max_size`]: BetSizeSearchConfig::max_size
+    pub fn find_optimal_bet_size(
+        &self,
+        config: &BetSizeSearchConfig,
+    ) -> Result<BetSizeSearchResult, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree(
+                "Current node is not a decision node".to_string(),
+            ));
+        }
+
+        if self.amount_to_call() != 0 {
+            return Err(Error::Tree("Current player is facing a bet".to_string()));
+        }
+
+        if !self.available_actions().contains(&Action::Check) {
+            return Err(Error::Tree("Current player cannot bet".to_string()));
+        }
+
+        if !(0.0 < config.min_size && config.min_size <= config.max_size) {
+            return Err(Error::Config(format!(
+                "Invalid bet size interval: [{}, {}]",
+                config.min_size, config.max_size
+            )));
+        }
+
+        if config.tolerance <= 0.0 || config.max_candidates == 0 {
+            return Err(Error::Config("Invalid search options".to_string()));
+        }
+
+        let player = self.current_player();
+        let mut candidates = Vec::<BetSizeCandidate>::new();
+        let mut evaluate = |size: f64| -> Result<f64, Error> {
+            let candidate = self.evaluate_bet_size(player, size, config, &candidates)?;
+            candidates.push(candidate);
+            Ok(candidate.ev)
+        };
+
+        let (mut lo, mut hi) = (config.min_size, config.max_size);
+        let mut x1 = hi - INV_PHI * (hi - lo);
+        let mut x2 = lo + INV_PHI * (hi - lo);
+        let mut f1 = evaluate(x1)?;
+        let mut f2 = if x2 > x1 { evaluate(x2)? } else { f1 };
+        let mut num_evaluations = 2;
+
+        while hi - lo > config.tolerance && num_evaluations < config.max_candidates {
+            if f1 >= f2 {
+                (hi, x2, f2) = (x2, x1, f1);
+                x1 = hi - INV_PHI * (hi - lo);
+                f1 = evaluate(x1)?;
+            } else {
+                (lo, x1, f1) = (x1, x2, f2);
+                x2 = lo + INV_PHI * (hi - lo);
+                f2 = evaluate(x2)?;
+            }
+            num_evaluations += 1;
+        }
+
+        let best = candidates
+            .iter()
+            .copied()
+            .max_by(|a, b| a.ev.total_cmp(&b.ev))
+            .unwrap();
+
+        Ok(BetSizeSearchResult {
+            player,
+            best,
+            candidates,
+        })
+    }
+
+    /// Solves the subgame with the given bet size, reusing the result of an evaluated candidate
+    /// that resolves to the same bet amount.
+    fn evaluate_bet_size(
+        &self,
+        player: usize,
+        size: f64,
+        config: &BetSizeSearchConfig,
+        evaluated: &[BetSizeCandidate],
+    ) -> Result<BetSizeCandidate, Error> {
+        let mut game = self.bet_size_subgame(player, size)?;
+
+        // IP acts after OOP's check: lock OOP to check at the root of the subgame
+        if player == 1 {
+            let num_hands = game.num_private_hands(0);
+            let num_actions = game.available_actions().len();
+            let mut strategy = vec![0.0; num_actions * num_hands];
+            strategy[..num_hands].fill(1.0);
+            game.allocate_memory(false);
+            game.lock_current_strategy(&strategy);
+            game.play(0);
+        }
+
+        let amount = game
+            .available_actions()
+            .iter()
+            .find_map(|action| match *action {
+                Action::Bet(amount) | Action::AllIn(amount) => Some(amount),
+                _ => None,
+            })
+            .ok_or_else(|| Error::Tree("Bet action is not available".to_string()))?;
+
+        if let Some(candidate) = evaluated.iter().find(|c| c.amount == amount) {
+            return Ok(BetSizeCandidate { size, ..*candidate });
+        }
+
+        let history = game.history().to_vec();
+        if game.is_memory_allocated().is_none() {
+            game.allocate_memory(false);
+        }
+
+        game.back_to_root();
+        let exploitability = solve(
+            &mut game,
+            config.max_num_iterations,
+            config.target_exploitability,
+            false,
+        )
+        .exploitability;
+
+        game.apply_history(&history);
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(player);
+        let ev = game.expected_values(player);
+        let total = Summation::Compensated.sum(weights.iter().map(|&w| w as f64));
+        let sum = Summation::Compensated.weighted_sum(&ev, weights);
+
+        Ok(BetSizeCandidate {
+            size,
+            amount,
+            ev: if total > 0.0 { sum / total } else { 0.0 },
+            exploitability,
+        })
+    }
+
+    /// Builds the subgame starting from the current node with the single bet size of `player`.
+    fn bet_size_subgame(&self, player: usize, size: f64) -> Result<PostFlopGame, Error> {
+        let (card_config, mut tree_config) = self.subgame_configs()?;
+
+        let bet_sizes = match tree_config.initial_state {
+            BoardState::Flop => &mut tree_config.flop_bet_sizes,
+            BoardState::Turn => &mut tree_config.turn_bet_sizes,
+            BoardState::River => &mut tree_config.river_bet_sizes,
+        };
+
+        bet_sizes[player].bet = vec![BetSize::PotRelative(size)];
+        if player == 1 {
+            bet_sizes[0].bet.clear();
+        }
+
+        self.build_subgame(card_config, tree_config)
+    }
+}
@@ -0,0 +1,104 @@
+use super::*;
+use crate::error::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An owned copy of the state of the current node, returned by [`NodeSnapshot::capture`].
+///
+/// The per-hand vectors follow the order of [`PostFlopGame::private_cards`], and `strategy` has
+/// the layout of [`PostFlopGame::strategy`]. Because a snapshot does not borrow the game, it can
+/// be sent to other threads or serialized (with the `serde` feature) after the lock on the game
+/// is released.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeSnapshot {
+    /// The history of the current node (see [`PostFlopGame::history`]).
+    pub history: Vec<usize>,
+
+    /// The current board (see [`PostFlopGame::current_board`]).
+    pub board: Vec<Card>,
+
+    /// The pot size, including the bet that has not been called yet.
+    pub pot: i32,
+
+    /// The total bet amount of each player (OOP, IP).
+    pub total_bet_amount: [i32; 2],
+
+    /// The remaining stack of each player (OOP, IP).
+    pub remaining_stacks: [i32; 2],
+
+    /// The player to act, or `None` if the current node is a terminal or a chance node.
+    pub player: Option<usize>,
+
+    /// Whether the current node is a terminal node.
+    pub is_terminal: bool,
+
+    /// Whether the current node is a chance node.
+    pub is_chance: bool,
+
+    /// The available actions (see [`PostFlopGame::available_actions`]).
+    pub actions: Vec<Action>,
+
+    /// The private hands of each player.
+    pub private_cards: [Vec<(Card, Card)>; 2],
+
+    /// The weights of each private hand (see [`PostFlopGame::weights`]).
+    pub weights: [Vec<f32>; 2],
+
+    /// The normalized weights of each private hand (see [`PostFlopGame::normalized_weights`]).
+    pub normalized_weights: [Vec<f32>; 2],
+
+    /// The strategy of the player to act. Empty if `player` is `None`.
+    pub strategy: Vec<f32>,
+
+    /// The expected values of each private hand (see [`PostFlopGame::expected_values`]). Empty
+    /// if the current node is not finalized.
+    pub expected_values: [Vec<f32>; 2],
+}
+
+impl NodeSnapshot {
+    /// Captures the state of the current node of `game`.
+    ///
+    /// The normalized weights must be cached (see [`PostFlopGame::cache_normalized_weights`]).
+    /// The expected values are captured only if the current node is finalized (see
+    /// [`PostFlopGame::is_current_node_finalized`]).
+    ///
+    /// Returns an error if the memory is not allocated or the normalized weights are not cached.
+    pub fn capture(game: &PostFlopGame) -> Result<Self, Error> {
+        game.check_operation(GameOperation::QueryStrategy)?;
+
+        if !game.is_normalized_weight_cached {
+            return Err(Error::Config(
+                "Normalized weights are not cached".to_string(),
+            ));
+        }
+
+        let is_terminal = game.is_terminal_node();
+        let is_chance = game.is_chance_node();
+        let player = (!is_terminal && !is_chance).then(|| game.current_player());
+
+        let expected_values = if game.is_current_node_finalized() {
+            [game.expected_values(0), game.expected_values(1)]
+        } else {
+            Default::default()
+        };
+
+        Ok(Self {
+            history: game.history().to_vec(),
+            board: game.current_board(),
+            pot: game.pot(),
+            total_bet_amount: game.total_bet_amount(),
+            remaining_stacks: game.remaining_stacks(),
+            player,
+            is_terminal,
+            is_chance,
+            actions: game.available_actions(),
+            private_cards: [0, 1].map(|p| game.private_cards(p).to_vec()),
+            weights: [0, 1].map(|p| game.weights(p).to_vec()),
+            normalized_weights: [0, 1].map(|p| game.normalized_weights(p).to_vec()),
+            strategy: player.map(|_| game.strategy()).unwrap_or_default(),
+            expected_values,
+        })
+    }
+}
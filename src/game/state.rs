@@ -0,0 +1,200 @@
+use super::*;
+use crate::interface::*;
+use std::fmt;
+use std::sync::atomic::Ordering;
+
+/// The lifecycle state of a [`PostFlopGame`], returned by [`PostFlopGame::game_state`].
+///
+/// The states are ordered: a game normally moves from `Uninitialized` to `Solved`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GameState {
+    /// The configuration is invalid.
+    ConfigError,
+
+    /// The game is not yet initialized.
+    Uninitialized,
+
+    /// The game tree is built, but the memory is not allocated.
+    TreeBuilt,
+
+    /// The memory is allocated and no solver iteration is performed yet.
+    MemoryAllocated,
+
+    /// At least one solver iteration is performed, but the game is not finalized.
+    Solving,
+
+    /// The staged finalization started by [`PostFlopGame::start_finalization`] is in progress.
+    Finalizing,
+
+    /// The game is solved (finalized).
+    Solved,
+}
+
+/// An operation whose validity depends on the [`GameState`] (see
+/// [`PostFlopGame::check_operation`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameOperation {
+    /// Allocating (or reallocating) the memory, which discards the progress of the solver.
+    AllocateMemory,
+
+    /// Locking or unlocking the strategy of a node.
+    LockStrategy,
+
+    /// Running solver iterations.
+    Solve,
+
+    /// Finalizing the solving process (at once or in stages).
+    Finalize,
+
+    /// Navigating the game tree.
+    Navigate,
+
+    /// Reading the strategies of the nodes.
+    QueryStrategy,
+
+    /// Reading the expected values of the current node.
+    QueryExpectedValues,
+
+    /// Measuring the iteration time with [`PostFlopGame::benchmark_iteration`], which resets the
+    /// storage.
+    Benchmark,
+
+    /// Recomputing the expected values with [`PostFlopGame::recompute_evs`].
+    RecomputeEvs,
+
+    /// Editing the strategies with [`PostFlopGame::edit_strategies`].
+    EditStrategy,
+
+    /// Reading or exporting the results of the solved game (e.g., reports, strategy tables, and
+    /// blueprints).
+    Analyze,
+}
+
+/// An error returned when an operation is not valid for the current state of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StateError {
+    /// The rejected operation.
+    pub operation: GameOperation,
+
+    /// The state of the game when the operation was requested.
+    pub state: GameState,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Operation {:?} is not allowed in state {:?}",
+            self.operation, self.state
+        )
+    }
+}
+
+impl std::error::Error for StateError {}
+
+impl PostFlopGame {
+    /// Returns the lifecycle state of the game.
+    ///
+    /// The `Solving` state is entered by the first solver iteration after the memory is
+    /// allocated, and it is restored when a partially solved game is loaded from a file.
+    pub fn game_state(&self) -> GameState {
+        match self.state {
+            State::ConfigError => GameState::ConfigError,
+            State::Uninitialized => GameState::Uninitialized,
+            State::TreeBuilt => GameState::TreeBuilt,
+            State::Solved => GameState::Solved,
+            State::MemoryAllocated if self.staged_finalization.is_some() => GameState::Finalizing,
+            State::MemoryAllocated if self.is_solving.load(Ordering::Relaxed) => GameState::Solving,
+            State::MemoryAllocated => GameState::MemoryAllocated,
+        }
+    }
+
+    /// Returns the number of solver iterations performed since the memory was allocated.
+    ///
+    /// The count is saved with the game, so a partially solved game loaded from a file can be
+    /// resumed by passing it to [`solve_step`] as the current iteration (see
+    /// [`PostFlopGame::save_to_file`]).
+    ///
+    /// [`solve_step`]: crate::solve_step
+    #[inline]
+    pub fn num_iterations(&self) -> u32 {
+        self.num_iterations.load(Ordering::Relaxed)
+    }
+
+    /// Checks whether `operation` is valid for the current state of the game.
+    ///
+    /// This allows, e.g., a server sharing a game between requests to reject a conflicting
+    /// operation with a typed error instead of panicking or invalidating the results:
+    ///
+    /// | Operation             | Valid states                                         |
+    /// |-----------------------|------------------------------------------------------|
+    /// | `AllocateMemory`      | `TreeBuilt`, `MemoryAllocated`                       |
+    /// | `LockStrategy`        | `MemoryAllocated`, `Solving`                         |
+    /// | `Solve`, `Finalize`   | `MemoryAllocated`, `Solving`                         |
+    /// | `Navigate`            | `MemoryAllocated` and later                          |
+    /// | `QueryStrategy`       | `MemoryAllocated` and later                          |
+    /// | `QueryExpectedValues` | see [`is_current_node_finalized`]                    |
+    /// | `Benchmark`           | `MemoryAllocated`                                    |
+    /// | `RecomputeEvs`        | `Solved`                                             |
+    /// | `EditStrategy`        | `Solved`                                             |
+    /// | `Analyze`             | `Solved`                                             |
+    ///
+    /// [`is_current_node_finalized`]: #method.is_current_node_finalized
+    pub fn check_operation(&self, operation: GameOperation) -> Result<(), StateError> {
+        use GameState::*;
+
+        let state = self.game_state();
+        let is_valid = match operation {
+            GameOperation::AllocateMemory => matches!(state, TreeBuilt | MemoryAllocated),
+            GameOperation::LockStrategy | GameOperation::Solve | GameOperation::Finalize => {
+                matches!(state, MemoryAllocated | Solving)
+            }
+            GameOperation::Navigate | GameOperation::QueryStrategy => state >= MemoryAllocated,
+            GameOperation::QueryExpectedValues => self.is_current_node_finalized(),
+            GameOperation::Benchmark => state == MemoryAllocated,
+            GameOperation::RecomputeEvs | GameOperation::EditStrategy | GameOperation::Analyze => {
+                state == Solved
+            }
+        };
+
+        if is_valid {
+            Ok(())
+        } else {
+            Err(StateError { operation, state })
+        }
+    }
+}
+
+/// Checks whether `operation` is valid for a game other than [`PostFlopGame`], whose state is
+/// inferred from [`Game::is_ready`] and [`Game::is_solved`] (see [`Game::check_operation`]).
+pub(crate) fn check_generic_operation<T: Game + ?Sized>(
+    game: &T,
+    operation: GameOperation,
+) -> Result<(), StateError> {
+    let state = if game.is_solved() {
+        GameState::Solved
+    } else if game.is_ready() {
+        GameState::MemoryAllocated
+    } else {
+        GameState::Uninitialized
+    };
+
+    let is_valid = match operation {
+        GameOperation::AllocateMemory | GameOperation::Benchmark => state < GameState::Solved,
+        GameOperation::LockStrategy | GameOperation::Solve | GameOperation::Finalize => {
+            state == GameState::MemoryAllocated
+        }
+        GameOperation::Navigate
+        | GameOperation::QueryStrategy
+        | GameOperation::QueryExpectedValues => state >= GameState::MemoryAllocated,
+        GameOperation::RecomputeEvs | GameOperation::EditStrategy | GameOperation::Analyze => {
+            state == GameState::Solved
+        }
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(StateError { operation, state })
+    }
+}
@@ -0,0 +1,286 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::json::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+use std::fmt::Write as _;
+use std::sync::mpsc;
+
+/// The version of the JSON schema of the messages sent by [`SolveStreamer`].
+pub const STREAM_SCHEMA_VERSION: u32 = 1;
+
+/// Streams periodic snapshots of a running solve as JSON messages, e.g., to feed a live
+/// dashboard of a long-running solve.
+///
+/// Each message is passed to a sink: either a callback given to [`new`], or the sending half of
+/// a channel created by [`channel`]. The crate does not depend on a network stack; to serve the
+/// snapshots over a websocket, forward each message from the sink (or from the receiver of the
+/// channel on another thread) as a text frame with the websocket library of your choice.
+///
+/// Register the nodes to include with [`watch`], then either solve with
+/// [`PostFlopGame::solve_streaming`], or call [`publish`] after the [`solve_step`] calls and
+/// [`finish`] after [`finalize`] in a custom loop.
+///
+/// # Message schema
+/// Each message is a single-line JSON object (version [`STREAM_SCHEMA_VERSION`]):
+///
+/// ```text
+/// {
+///   "version": 1,
+///   "type": "snapshot" | "finished",
+///   "iteration": <number of completed iterations>,
+///   "exploitability": <number, or null if not computed>,
+///   "nodes": [                           // the watched nodes in the order of registration
+///     {
+///       "history": [<action index>, ...],    // see `PostFlopGame::apply_history`
+///       "player": 0 | 1,                     // the player to act
+///       "actions": ["Check", "Bet 100", ...],
+///       "hands": ["AsKs", ...],              // the order of `PostFlopGame::private_cards`
+///       "strategy": [[<frequency>, ...], ...],         // average strategy: one array per
+///                                                      // action, one frequency per hand
+///       "current_strategy": [[<frequency>, ...], ...]  // regret-matching strategy with the same
+///                                                      // layout, or null once finalized
+///     },
+///     ...
+///   ]
+/// }
+/// ```
+///
+/// A solve sends `"snapshot"` messages while solving and ends with a single `"finished"`
+/// message describing the finalized game. Non-finite numbers are written as `null`.
+///
+/// # Examples
+/// ```ignore
+/// let (mut streamer, receiver) = SolveStreamer::channel(10);
+/// streamer.watch(&mut game, &[])?; // the root node
+/// std::thread::spawn(move || {
+///     for message in receiver {
+///         websocket.send(message); // or print, write to a file, ...
+///     }
+/// });
+/// game.solve_streaming(1000, target_exploitability, &SolverConfig::default(), &mut streamer)?;
+/// ```
+///
+/// [`new`]: #method.new
+/// [`channel`]: #method.channel
+/// [`watch`]: #method.watch
+/// [`publish`]: #method.publish
+/// [`finish`]: #method.finish
+/// [`solve_step`]: crate::solve_step
+/// [`finalize`]: crate::finalize
+pub struct SolveStreamer<'a> {
+    interval: u32,
+    nodes: Vec<StreamedNode>,
+    sink: Box<dyn FnMut(String) + Send + 'a>,
+}
+
+struct StreamedNode {
+    node: WatchedNode,
+    trace: StrategyTrace,
+    hands: Vec<String>,
+}
+
+impl<'a> SolveStreamer<'a> {
+    /// Creates a new streamer that passes a snapshot to `sink` every `interval` iterations.
+    ///
+    /// Panics if `interval` is zero.
+    pub fn new(interval: u32, sink: impl FnMut(String) + Send + 'a) -> Self {
+        if interval == 0 {
+            panic!("Interval must be positive");
+        }
+
+        Self {
+            interval,
+            nodes: Vec::new(),
+            sink: Box::new(sink),
+        }
+    }
+
+    /// Returns the snapshot interval.
+    #[inline]
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// Registers the node specified by `history` to be included in the snapshots.
+    ///
+    /// The current node of `game` is restored after the call. Returns an error if the memory is
+    /// not allocated or the specified node is not a decision node.
+    pub fn watch(&mut self, game: &mut PostFlopGame, history: &[usize]) -> Result<(), Error> {
+        let (node, trace) = WatchedNode::new(game, history)?;
+        let hands = holes_to_strings(game.private_cards(trace.player))?;
+        self.nodes.push(StreamedNode { node, trace, hands });
+        Ok(())
+    }
+
+    /// Sends a `"snapshot"` message if `iteration` is a multiple of the interval.
+    ///
+    /// `iteration` is the number of completed iterations, i.e., `t + 1` after calling
+    /// `solve_step(&game, t)`. `exploitability` is written as `null` if `None`.
+    pub fn publish(&mut self, game: &PostFlopGame, iteration: u32, exploitability: Option<f32>) {
+        if iteration.is_multiple_of(self.interval) {
+            let message = self.message(game, "snapshot", iteration, exploitability);
+            (self.sink)(message);
+        }
+    }
+
+    /// Sends the `"finished"` message, regardless of the interval.
+    pub fn finish(&mut self, game: &PostFlopGame, iteration: u32, exploitability: Option<f32>) {
+        let message = self.message(game, "finished", iteration, exploitability);
+        (self.sink)(message);
+    }
+
+    /// Builds a message following the documented schema.
+    fn message(
+        &self,
+        game: &PostFlopGame,
+        kind: &str,
+        iteration: u32,
+        exploitability: Option<f32>,
+    ) -> String {
+        let mut ret = String::new();
+        write!(ret, "{{\"version\":{STREAM_SCHEMA_VERSION},\"type\":").unwrap();
+        write_json_string(&mut ret, kind);
+        write!(ret, ",\"iteration\":{iteration},\"exploitability\":").unwrap();
+        write_number(&mut ret, exploitability.unwrap_or(f32::NAN));
+        ret.push_str(",\"nodes\":[");
+
+        for (i, streamed) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                ret.push(',');
+            }
+
+            let trace = &streamed.trace;
+            write!(
+                ret,
+                "{{\"history\":{:?},\"player\":{},\"actions\":[",
+                trace.history, trace.player
+            )
+            .unwrap();
+            for (j, &action) in trace.actions.iter().enumerate() {
+                if j > 0 {
+                    ret.push(',');
+                }
+                write_json_string(&mut ret, &action_to_string(action));
+            }
+
+            ret.push_str("],\"hands\":[");
+            for (j, hand) in streamed.hands.iter().enumerate() {
+                if j > 0 {
+                    ret.push(',');
+                }
+                write_json_string(&mut ret, hand);
+            }
+
+            let num_hands = streamed.hands.len();
+            ret.push_str("],\"strategy\":");
+            write_strategy(&mut ret, &streamed.node.average_strategy(game), num_hands);
+
+            // the regrets are overwritten by the counterfactual values once finalized
+            ret.push_str(",\"current_strategy\":");
+            if game.state == State::Solved {
+                ret.push_str("null");
+            } else {
+                write_strategy(&mut ret, &streamed.node.current_strategy(game), num_hands);
+            }
+
+            ret.push('}');
+        }
+
+        ret.push_str("]}");
+        ret
+    }
+}
+
+impl SolveStreamer<'static> {
+    /// Creates a new streamer that sends a snapshot to a channel every `interval` iterations, and
+    /// returns it with the receiving half of the channel.
+    ///
+    /// The messages are discarded once the receiver is dropped, so a disconnected consumer does
+    /// not interrupt the solve. Panics if `interval` is zero.
+    pub fn channel(interval: u32) -> (Self, mpsc::Receiver<String>) {
+        let (sender, receiver) = mpsc::channel();
+        let streamer = Self::new(interval, move |message| {
+            let _ = sender.send(message);
+        });
+        (streamer, receiver)
+    }
+}
+
+impl PostFlopGame {
+    /// Performs the algorithm specified by `config` like [`solve_with_config`], sending the
+    /// snapshots of the solve to `streamer` (see [`SolveStreamer`]).
+    ///
+    /// The exploitability is computed every `streamer.interval()` iterations, when a snapshot is
+    /// sent; a snapshot is also sent before the first iteration. After the game is finalized, the
+    /// `"finished"` message is sent with the exploitability of the obtained strategy.
+    ///
+    /// Returns the exploitability of the obtained strategy, or an error if a non-finite value is
+    /// found with [`SolverConfig::check_finite`]; on error, the game is left unfinalized and no
+    /// `"finished"` message is sent.
+    ///
+    /// Also returns an error if the game is already solved or not ready (see
+    /// [`check_operation`]).
+    ///
+    /// [`solve_with_config`]: crate::solve_with_config
+    /// [`check_operation`]: #method.check_operation
+    pub fn solve_streaming(
+        &mut self,
+        max_num_iterations: u32,
+        target_exploitability: f32,
+        config: &SolverConfig,
+        streamer: &mut SolveStreamer,
+    ) -> Result<f32, Error> {
+        check_solvable(self)?;
+
+        config.check()?;
+
+        let mut exploitability = compute_exploitability(self);
+        self.update_frozen_streets(exploitability);
+        streamer.publish(self, 0, Some(exploitability));
+
+        let mut t = 0;
+        while t < max_num_iterations && exploitability > target_exploitability {
+            let num_iterations = (max_num_iterations - t).min(streamer.interval);
+            exploitability = solve_steps(self, t, num_iterations, config)?;
+            t += num_iterations;
+            streamer.publish(self, t, Some(exploitability));
+        }
+
+        finalize(self);
+        streamer.finish(self, t, Some(exploitability));
+
+        Ok(exploitability)
+    }
+}
+
+/// Writes `strategy` as an array of the rows of each action.
+fn write_strategy(dst: &mut String, strategy: &[f32], num_hands: usize) {
+    dst.push('[');
+    for (i, row) in strategy.chunks_exact(num_hands).enumerate() {
+        if i > 0 {
+            dst.push(',');
+        }
+        dst.push('[');
+        for (j, &x) in row.iter().enumerate() {
+            if j > 0 {
+                dst.push(',');
+            }
+            write_number(dst, x);
+        }
+        dst.push(']');
+    }
+    dst.push(']');
+}
+
+/// Writes `x` as a JSON number, or `null` if it is not finite.
+#[inline]
+fn write_number(dst: &mut String, x: f32) {
+    if x.is_finite() {
+        write!(dst, "{x}").unwrap();
+    } else {
+        dst.push_str("null");
+    }
+}
@@ -0,0 +1,92 @@
+use super::*;
+use crate::error::*;
+use std::sync::atomic::Ordering;
+
+impl PostFlopGame {
+    /// Sets the exploitability targets of the turn and the river, which are typically looser than
+    /// the target passed to [`solve`].
+    ///
+    /// Once the exploitability measured by [`solve`] falls below the target of a street, the
+    /// strategies of the nodes of that street and the later streets are frozen: they are fixed
+    /// to their average strategies at that moment and are no longer updated, so the remaining
+    /// iterations only refine the earlier streets. This trades the accuracy of the marginal
+    /// mixing on the later streets for the time of the regret and strategy updates of their
+    /// nodes. Setting the target of the turn to `f32::INFINITY` freezes all streets but the flop
+    /// at the first exploitability check, which can be used to converge only the flop
+    /// strategies exactly.
+    ///
+    /// Note that the frozen strategies are not best responses to the later updates of the
+    /// earlier streets, so the final exploitability is bounded below by roughly the exploitability
+    /// at which the first street was frozen. `None` (the default) disables the target of the
+    /// street. The frozen streets are reset when the memory is allocated.
+    ///
+    /// Returns an error if a target is negative or NaN, or the target of the river is tighter
+    /// than the target of the turn.
+    ///
+    /// [`solve`]: crate::solve
+    pub fn set_street_targets(
+        &mut self,
+        turn: Option<f32>,
+        river: Option<f32>,
+    ) -> Result<(), Error> {
+        for target in [turn, river].into_iter().flatten() {
+            if target.is_nan() || target < 0.0 {
+                return Err(Error::Config(format!(
+                    "Invalid exploitability target: {target}"
+                )));
+            }
+        }
+
+        if let (Some(turn), Some(river)) = (turn, river) {
+            if river < turn {
+                return Err(Error::Config(format!(
+                    "River target must not be tighter than turn target: {river} < {turn}"
+                )));
+            }
+        }
+
+        self.street_targets = [turn, river];
+        Ok(())
+    }
+
+    /// Returns the exploitability targets of the turn and the river (see
+    /// [`set_street_targets`]).
+    ///
+    /// [`set_street_targets`]: #method.set_street_targets
+    #[inline]
+    pub fn street_targets(&self) -> [Option<f32>; 2] {
+        self.street_targets
+    }
+
+    /// Returns the earliest street whose strategies are frozen by its exploitability target (see
+    /// [`set_street_targets`]), or `None` if no street is frozen.
+    ///
+    /// [`set_street_targets`]: #method.set_street_targets
+    #[inline]
+    pub fn frozen_street(&self) -> Option<BoardState> {
+        match self.num_frozen_streets.load(Ordering::Relaxed) {
+            0 => None,
+            1 => Some(BoardState::River),
+            _ => Some(BoardState::Turn),
+        }
+    }
+
+    /// Freezes the streets whose targets are satisfied by `exploitability`. The frozen streets
+    /// are never unfrozen during solving.
+    pub(super) fn freeze_streets(&self, exploitability: f32) {
+        let [turn, river] = self
+            .street_targets
+            .map(|target| target.is_some_and(|target| exploitability <= target));
+
+        let num_frozen_streets = if turn {
+            2
+        } else if river {
+            1
+        } else {
+            0
+        };
+
+        self.num_frozen_streets
+            .fetch_max(num_frozen_streets, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,179 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::utility::*;
+
+impl PostFlopGame {
+    /// Extracts the subtree rooted at the node reached by `history` as a standalone solved game.
+    ///
+    /// The extracted game starts at the street of the node: the board, the pot, and the effective
+    /// stack are taken from the node, the ranges are the reach weights of the node (see
+    /// [`weights`]), and the tree configuration is otherwise inherited. The strategies of the
+    /// subtree are copied, and the expected values are computed from them, so the extracted game
+    /// can be navigated and saved like any solved game (e.g., to share only "the 4-bet pot turn
+    /// spot" without shipping the entire flop solve).
+    ///
+    /// The node must be the first decision of a street, i.e., the root or a node right after a
+    /// chance node. Returns an error if the game is not solved, the node is not such a node, the
+    /// bunching effect or the turn abstraction is enabled, or the subtree does not match the tree
+    /// built from the configuration (e.g., lines were removed). The current node is restored
+    /// after the call.
+    ///
+    /// [`weights`]: #method.weights
+    pub fn extract_subtree(&mut self, history: &[usize]) -> Result<PostFlopGame, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if !self.turn_buckets.is_empty() {
+            return Err(Error::Config(
+                "Turn abstraction is not supported".to_string(),
+            ));
+        }
+
+        let saved_history = self.history().to_vec();
+        self.apply_history(history);
+        let ret = self.extract_current_subtree(history);
+        self.apply_history(&saved_history);
+        ret
+    }
+
+    /// Extracts the subtree rooted at the current node, whose history is `history`.
+    fn extract_current_subtree(&mut self, history: &[usize]) -> Result<PostFlopGame, Error> {
+        if self.is_terminal_node() || self.is_chance_node() {
+            return Err(Error::Tree("Node is not a decision node".to_string()));
+        }
+
+        if !history.is_empty() && !matches!(self.node().prev_action, Action::Chance(_)) {
+            return Err(Error::Tree(
+                "Node is not the first decision of a street".to_string(),
+            ));
+        }
+
+        let (card_config, tree_config) = self.subgame_configs()?;
+        let mut game = self.build_subgame(card_config, tree_config)?;
+        game.allocate_memory(false);
+
+        let mut base = history.to_vec();
+        self.copy_subtree_strategy(&mut game, &mut base, &mut Vec::new())?;
+        self.apply_history(history);
+
+        game.back_to_root();
+        finalize(&mut game);
+        Ok(game)
+    }
+
+    /// Returns the configurations of a game starting at the current node.
+    ///
+    /// The board, the pot, the effective stack, and the ranges (the reach weights) are taken from
+    /// the current node; the remaining configuration is inherited.
+    pub(super) fn subgame_configs(&self) -> Result<(CardConfig, TreeConfig), Error> {
+        if self.bunching_num_dead_cards != 0 {
+            return Err(Error::Config(
+                "Bunching effect is not supported".to_string(),
+            ));
+        }
+
+        let board = self.current_board();
+        let stacks = self.remaining_stacks();
+
+        let mut range = [Range::new(), Range::new()];
+        for (player, range) in range.iter_mut().enumerate() {
+            *range = Range::from_hands_weights(self.private_cards(player), self.weights(player))?;
+            if range.is_empty() {
+                return Err(Error::Card(format!(
+                    "Range of player {player} is empty at the current node"
+                )));
+            }
+        }
+
+        let card_config = CardConfig {
+            range,
+            flop: [board[0], board[1], board[2]],
+            turn: board.get(3).copied().unwrap_or(NOT_DEALT),
+            river: board.get(4).copied().unwrap_or(NOT_DEALT),
+            ..self.card_config.clone()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: self.current_street(),
+            starting_pot: self.pot(),
+            effective_stack: stacks[0].min(stacks[1]),
+            ..self.tree_config.clone()
+        };
+
+        Ok((card_config, tree_config))
+    }
+
+    /// Builds a game from the configurations returned by `subgame_configs`, inheriting the
+    /// showdown evaluator.
+    pub(super) fn build_subgame(
+        &self,
+        card_config: CardConfig,
+        tree_config: TreeConfig,
+    ) -> Result<PostFlopGame, Error> {
+        let action_tree = ActionTree::new(tree_config)?;
+        let mut game = PostFlopGame::with_config(card_config, action_tree)?;
+        if let Some(evaluator) = &self.evaluator {
+            game.set_evaluator(Arc::clone(evaluator))?;
+        }
+        Ok(game)
+    }
+
+    /// Copies the strategies of the subtree at `base + history` into the subtree of `game` at
+    /// `history`. Only the representative cards of `game` are dealt, so that the strategies of
+    /// `game` are not swapped.
+    fn copy_subtree_strategy(
+        &mut self,
+        game: &mut PostFlopGame,
+        base: &mut Vec<usize>,
+        history: &mut Vec<usize>,
+    ) -> Result<(), Error> {
+        game.apply_history(history);
+        if game.is_terminal_node() {
+            return Ok(());
+        }
+
+        let actions = game.available_actions();
+
+        if !game.is_chance_node() {
+            self.apply_history(base);
+            if self.available_actions() != actions {
+                return Err(Error::Tree(format!(
+                    "Subtree does not match the tree configuration at {history:?}"
+                )));
+            }
+
+            let player = self.current_player();
+            let src = self.strategy();
+            let src_hands = self.private_cards(player);
+            let dst_hands = game.private_cards(player).to_vec();
+            let (num_src, num_dst) = (src_hands.len(), dst_hands.len());
+
+            let mut dst = vec![0.0; actions.len() * num_dst];
+            for (j, hand) in dst_hands.iter().enumerate() {
+                let i = src_hands.binary_search(hand).unwrap();
+                for action in 0..actions.len() {
+                    dst[action * num_dst + j] = src[action * num_src + i];
+                }
+            }
+
+            let dst = game.select_stored_hands(&dst, player);
+            game.node().strategy_mut().copy_from_slice(&dst);
+        }
+
+        for (index, &action) in actions.iter().enumerate() {
+            let step = match action {
+                Action::Chance(card) => card as usize,
+                _ => index,
+            };
+
+            base.push(step);
+            history.push(step);
+            self.copy_subtree_strategy(game, base, history)?;
+            base.pop();
+            history.pop();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+use super::*;
+use crate::error::*;
+use crate::range::*;
+use crate::utility::*;
+use std::mem::MaybeUninit;
+
+/// The classes of the private hands of a player that are merged by the suit reduction.
+#[derive(Default)]
+pub(super) struct HandClasses {
+    /// The class of each private hand.
+    pub(super) index: Vec<u16>,
+    /// The smallest index of the private hands of each class.
+    pub(super) representative: Vec<u16>,
+    /// The number of private hands of each class.
+    pub(super) size: Vec<u16>,
+    /// The sum of the initial weights of the private hands of each class.
+    pub(super) initial_weights: Vec<f32>,
+}
+
+impl PostFlopGame {
+    /// Returns the suit permutations, other than the identity, under which both ranges and the
+    /// board are invariant.
+    ///
+    /// Each permutation maps the suit `s` to `perm[s]`. The flop cards and the cards of the second
+    /// board may be permuted among themselves, while the turn and river cards must be fixed.
+    /// Assuming that the hand evaluation is suit-symmetric (checked for a custom [`Evaluator`]),
+    /// the private hands mapped to each other by these permutations have the same strategies and
+    /// expected values at the root, e.g., the three `AA` combos without the flop suit on a
+    /// monotone flop.
+    ///
+    /// When the game starts on the river, the solver merges these hands (see
+    /// [`is_suit_reduced`]). Otherwise, only the symmetries between the turn and river cards are
+    /// exploited (see [`CardConfig::disable_isomorphism`]).
+    ///
+    /// Panics if the game is not successfully initialized.
+    ///
+    /// [`Evaluator`]: crate::Evaluator
+    /// [`is_suit_reduced`]: #method.is_suit_reduced
+    pub fn suit_symmetries(&self) -> Vec<[u8; 4]> {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        self.suit_symmetries_internal()
+    }
+
+    /// Returns the classes of the private hands of `player` that are mapped to each other by
+    /// [`suit_symmetries`].
+    ///
+    /// Each class is a sorted list of indices of [`private_cards`], and the classes are sorted by
+    /// their first elements. If the game is suit-reduced (see [`is_suit_reduced`]), the solver
+    /// stores one strategy for each class.
+    ///
+    /// Panics if the game is not successfully initialized or `player` is invalid.
+    ///
+    /// [`suit_symmetries`]: #method.suit_symmetries
+    /// [`private_cards`]: #method.private_cards
+    /// [`is_suit_reduced`]: #method.is_suit_reduced
+    pub fn private_hand_classes(&self, player: usize) -> Vec<Vec<usize>> {
+        let cards = self.private_cards(player);
+        let symmetries = self.suit_symmetries();
+        hand_classes(cards, &symmetries)
+    }
+
+    /// Returns whether the symmetric private hands are merged by the suit reduction.
+    ///
+    /// When the game starts on the river, the isomorphism is enabled (see
+    /// [`CardConfig::disable_isomorphism`]), and [`suit_symmetries`] is not empty, the private
+    /// hands of each class of [`private_hand_classes`] share their strategies, regrets, and
+    /// counterfactual values, so the storage and the terminal evaluation of the solver are
+    /// proportional to the number of classes instead of the number of hands. The reduction is
+    /// transparent: the results, e.g., [`strategy`], [`expected_values`], and [`equity`], are
+    /// expanded to every hand of [`private_cards`], and [`memory_usage`] reports the reduced
+    /// size.
+    ///
+    /// A strategy given to the game (e.g., [`lock_current_strategy`]) must be the same for the
+    /// hands of each class, and the fold ranges of the bunching effect must be invariant under
+    /// [`suit_symmetries`]. Disable the isomorphism to use asymmetric ones.
+    ///
+    /// [`suit_symmetries`]: #method.suit_symmetries
+    /// [`private_hand_classes`]: #method.private_hand_classes
+    /// [`strategy`]: #method.strategy
+    /// [`expected_values`]: #method.expected_values
+    /// [`equity`]: #method.equity
+    /// [`private_cards`]: #method.private_cards
+    /// [`memory_usage`]: #method.memory_usage
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    #[inline]
+    pub fn is_suit_reduced(&self) -> bool {
+        !self.suit_reduction.is_empty()
+    }
+
+    /// Same as [`suit_symmetries`], but without checking the state.
+    ///
+    /// [`suit_symmetries`]: #method.suit_symmetries
+    fn suit_symmetries_internal(&self) -> Vec<[u8; 4]> {
+        let config = &self.card_config;
+        let mut ret = Vec::new();
+
+        for perm in suit_permutations().skip(1) {
+            let permute = |card: Card| (card & !3) | perm[card as usize & 3];
+            let is_fixed = |card: Card| card == NOT_DEALT || permute(card) == card;
+            let is_set_fixed = |cards: &[Card]| cards.iter().all(|&c| cards.contains(&permute(c)));
+
+            if is_set_fixed(&config.flop)
+                && is_fixed(config.turn)
+                && is_fixed(config.river)
+                && is_set_fixed(&config.second_board)
+                && config.range[0].is_suit_permutation_invariant(&perm)
+                && config.range[1].is_suit_permutation_invariant(&perm)
+            {
+                ret.push(perm);
+            }
+        }
+
+        ret
+    }
+
+    /// Initializes the suit reduction from the card configuration.
+    ///
+    /// The reduction is only applied to the games starting on the river: the suit permutations
+    /// fix every board card, so there are no chance nodes that could deal a card breaking the
+    /// symmetry.
+    pub(super) fn init_hand_classes(&mut self) {
+        self.suit_reduction = Vec::new();
+        self.hand_classes = Default::default();
+
+        if self.card_config.river == NOT_DEALT || self.card_config.disable_isomorphism {
+            return;
+        }
+
+        let symmetries = self.suit_symmetries_internal();
+        let classes = [0, 1].map(|player| hand_classes(&self.private_cards[player], &symmetries));
+        if (0..2).all(|player| classes[player].len() == self.private_cards[player].len()) {
+            return;
+        }
+
+        for (player, classes) in classes.iter().enumerate() {
+            let weights = &self.initial_weights[player];
+            let hand_classes = &mut self.hand_classes[player];
+            hand_classes.index = vec![0; weights.len()];
+
+            for (class_index, class) in classes.iter().enumerate() {
+                for &hand in class {
+                    hand_classes.index[hand] = class_index as u16;
+                }
+                hand_classes.representative.push(class[0] as u16);
+                hand_classes.size.push(class.len() as u16);
+                hand_classes
+                    .initial_weights
+                    .push(class.iter().map(|&hand| weights[hand]).sum());
+            }
+        }
+
+        self.suit_reduction = symmetries;
+    }
+
+    /// Checks that the hand strengths are the same for the private hands of each class merged by
+    /// the suit reduction.
+    pub(super) fn check_hand_classes(&self) -> Result<(), Error> {
+        if !self.is_suit_reduced() {
+            return Ok(());
+        }
+
+        let strength_lists = self
+            .hand_strength
+            .iter()
+            .chain(&self.hand_strength_low)
+            .chain(&self.second_board_strength);
+
+        for strength in strength_lists {
+            if strength[0].is_empty() {
+                continue;
+            }
+
+            for player in 0..2 {
+                let classes = &self.hand_classes[player];
+                let mut values = vec![u16::MAX; classes.representative.len()];
+                let len = strength[player].len();
+                for item in &strength[player][1..len - 1] {
+                    let class = classes.index[item.index as usize] as usize;
+                    if values[class] == u16::MAX {
+                        values[class] = item.strength;
+                    } else if values[class] != item.strength {
+                        return Err(Error::Config(
+                            "Evaluator is not symmetric with respect to suits; \
+                             set `disable_isomorphism` of `CardConfig`"
+                                .to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the suit reduction is preserved by the fold ranges of the bunching effect.
+    pub(super) fn check_hand_classes_bunching(&self, fold_ranges: &[Range]) -> Result<(), Error> {
+        let is_symmetric = fold_ranges.iter().all(|range| {
+            self.suit_reduction
+                .iter()
+                .all(|perm| range.is_suit_permutation_invariant(perm))
+        });
+
+        if !is_symmetric {
+            return Err(Error::Config(
+                "Fold ranges of the bunching effect break the suit reduction; \
+                 set `disable_isomorphism` of `CardConfig`"
+                    .to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of the private hands of `player` stored in each node, i.e., the number
+    /// of classes if the game is suit-reduced.
+    #[inline]
+    pub(super) fn num_stored_hands(&self, player: usize) -> usize {
+        match self.hand_classes[player].representative.len() {
+            0 => self.private_cards[player].len(),
+            n => n,
+        }
+    }
+
+    /// Expands the rows of the values of the stored hands of `player` (see [`num_stored_hands`])
+    /// into the rows of the values of all private hands.
+    ///
+    /// [`num_stored_hands`]: #method.num_stored_hands
+    #[inline]
+    pub(super) fn expand_stored_hands<T: Copy>(&self, values: Vec<T>, player: usize) -> Vec<T> {
+        let index = &self.hand_classes[player].index;
+        expand_stored_hands(values, index, self.num_stored_hands(player))
+    }
+
+    /// Reduces the rows of the values of all private hands of `player` into the rows of the
+    /// values of the stored hands (see [`num_stored_hands`]).
+    ///
+    /// Returns an error if the values differ between the hands of a class.
+    ///
+    /// [`num_stored_hands`]: #method.num_stored_hands
+    pub(super) fn reduce_stored_hands(
+        &self,
+        values: &[f32],
+        player: usize,
+    ) -> Result<Vec<f32>, Error> {
+        if !self.is_suit_reduced() {
+            return Ok(values.to_vec());
+        }
+
+        let classes = &self.hand_classes[player];
+        let num_hands = self.private_cards[player].len();
+
+        for row in values.chunks_exact(num_hands) {
+            let is_symmetric = row
+                .iter()
+                .zip(&classes.index)
+                .all(|(&v, &class)| v == row[classes.representative[class as usize] as usize]);
+            if !is_symmetric {
+                return Err(Error::Config(
+                    "Strategy differs between symmetric hands of the suit-reduced game; \
+                     set `disable_isomorphism` of `CardConfig`"
+                        .to_string(),
+                ));
+            }
+        }
+
+        Ok(self.select_stored_hands(values, player))
+    }
+
+    /// Same as [`reduce_stored_hands`], but takes the values of the representatives without
+    /// checking that the values are equal within each class.
+    ///
+    /// [`reduce_stored_hands`]: #method.reduce_stored_hands
+    pub(super) fn select_stored_hands(&self, values: &[f32], player: usize) -> Vec<f32> {
+        if !self.is_suit_reduced() {
+            return values.to_vec();
+        }
+
+        let representatives = &self.hand_classes[player].representative;
+        let num_hands = self.private_cards[player].len();
+        values
+            .chunks_exact(num_hands)
+            .flat_map(|row| representatives.iter().map(|&hand| row[hand as usize]))
+            .collect()
+    }
+
+    /// Evaluates the terminal `node` for the stored hands of the suit-reduced game.
+    ///
+    /// The reach probabilities of each class of the opponent are split equally among its hands,
+    /// all private hands are evaluated, and the value of each class is that of its
+    /// representative.
+    pub(super) fn evaluate_stored_hands(
+        &self,
+        result: &mut [MaybeUninit<f32>],
+        node: &PostFlopNode,
+        player: usize,
+        cfreach: &[f32],
+        rake: f64,
+    ) {
+        let opponent_classes = &self.hand_classes[player ^ 1];
+        let cfreach_expanded = opponent_classes
+            .index
+            .iter()
+            .map(|&class| {
+                let class = class as usize;
+                cfreach[class] / opponent_classes.size[class] as f32
+            })
+            .collect::<Vec<_>>();
+
+        let num_hands = self.private_cards[player].len();
+        let mut values = Vec::with_capacity(num_hands);
+        self.evaluate_with_rake(
+            values.spare_capacity_mut(),
+            node,
+            player,
+            &cfreach_expanded,
+            rake,
+        );
+        unsafe { values.set_len(num_hands) };
+
+        let representatives = &self.hand_classes[player].representative;
+        for (r, &hand) in result.iter_mut().zip(representatives) {
+            r.write(values[hand as usize]);
+        }
+    }
+
+    /// Returns the memory usage of the hand classes of the suit reduction in bytes.
+    pub(super) fn hand_classes_memory_usage(&self) -> u64 {
+        let mut memory_usage = vec_memory_usage(&self.suit_reduction);
+        for classes in &self.hand_classes {
+            memory_usage += vec_memory_usage(&classes.index);
+            memory_usage += vec_memory_usage(&classes.representative);
+            memory_usage += vec_memory_usage(&classes.size);
+            memory_usage += vec_memory_usage(&classes.initial_weights);
+        }
+        memory_usage
+    }
+}
+
+/// Returns the classes of `cards` that are mapped to each other by `symmetries` (see
+/// [`PostFlopGame::private_hand_classes`]).
+fn hand_classes(cards: &[(Card, Card)], symmetries: &[[u8; 4]]) -> Vec<Vec<usize>> {
+    let mut is_visited = vec![false; cards.len()];
+    let mut ret = Vec::new();
+
+    for (index, &(card1, card2)) in cards.iter().enumerate() {
+        if is_visited[index] {
+            continue;
+        }
+
+        // the permutations and the identity form a group, so the images are the whole class
+        let mut class = vec![index];
+        is_visited[index] = true;
+        for perm in symmetries {
+            let permute = |card: Card| (card & !3) | perm[card as usize & 3];
+            let (c1, c2) = (permute(card1), permute(card2));
+            if let Ok(image) = cards.binary_search(&(c1.min(c2), c1.max(c2))) {
+                if !is_visited[image] {
+                    is_visited[image] = true;
+                    class.push(image);
+                }
+            }
+        }
+
+        class.sort_unstable();
+        ret.push(class);
+    }
+
+    ret
+}
+
+/// Returns all 24 permutations of the suits, starting from the identity.
+fn suit_permutations() -> impl Iterator<Item = [u8; 4]> {
+    (0..4u8).flat_map(|a| {
+        (0..4u8).flat_map(move |b| {
+            (0..4u8)
+                .filter(move |&c| a != b && a != c && b != c)
+                .map(move |c| [a, b, c, 6 - a - b - c])
+        })
+    })
+}
@@ -0,0 +1,269 @@
+use super::*;
+use crate::error::*;
+use crate::pio::*;
+use crate::range::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A chunk of the per-node strategy table in columnar layout, produced by
+/// [`PostFlopGame::strategy_table_chunks`].
+///
+/// Each row corresponds to a triple of a decision node, a private hand of the player to act,
+/// and an action. All columns have the same length.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StrategyTable {
+    /// The index of the decision node in depth-first order.
+    pub node: Vec<u32>,
+
+    /// The line leading to the node, in the pattern syntax of [`NodePattern`] (e.g., `r:x:Ah`).
+    pub line: Vec<String>,
+
+    /// The line leading to the node, in PioSolver's node notation (e.g., `r:0:c:Ah`; see
+    /// [`line_to_pio_path`]).
+    pub pio_path: Vec<String>,
+
+    /// The board at the node (e.g., `Td9d6hAh`).
+    pub board: Vec<String>,
+
+    /// The player to act at the node.
+    pub player: Vec<u8>,
+
+    /// The private hand (e.g., `AsKh`).
+    pub hand: Vec<String>,
+
+    /// The action (e.g., `Bet 30`).
+    pub action: Vec<String>,
+
+    /// The probability of the action with the hand.
+    pub frequency: Vec<f32>,
+
+    /// The expected value of the action with the hand, as returned by
+    /// [`PostFlopGame::expected_values_detail`].
+    pub ev: Vec<f32>,
+
+    /// The number of combinations with which the hand reaches the node (see
+    /// [`PostFlopGame::normalized_weights`]).
+    pub reach: Vec<f32>,
+}
+
+impl StrategyTable {
+    /// Returns the number of rows.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.node.len()
+    }
+
+    /// Returns whether the table has no rows.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.node.is_empty()
+    }
+}
+
+impl PostFlopGame {
+    /// Traverses the decision nodes in depth-first order and passes the strategy table to `f`
+    /// in chunks of at least `max_rows` rows (except the last one).
+    ///
+    /// A chunk is split only between decision nodes, so all rows of a node are in the same
+    /// chunk. Hands that do not reach a node (including those overlapping with the board) are
+    /// omitted. Isomorphic chances are expanded. The chunked interface keeps the memory usage
+    /// bounded when exporting large trees; an error returned by `f` aborts the traversal.
+    ///
+    /// Returns an error if the game is not solved, the storage mode is not
+    /// [`BoardState::River`], `max_rows` is zero, or `f` returns an error. The current node is
+    /// restored after the call.
+    pub fn strategy_table_chunks<F>(&mut self, max_rows: usize, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(StrategyTable) -> Result<(), Error>,
+    {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        if max_rows == 0 {
+            return Err(Error::Config("Number of rows must be positive".to_string()));
+        }
+
+        let hand_strings = [
+            holes_to_strings(self.private_cards(0))?,
+            holes_to_strings(self.private_cards(1))?,
+        ];
+
+        let saved_history = self.history().to_vec();
+        self.back_to_root();
+
+        let mut history = Vec::new();
+        let mut line = Vec::new();
+        let mut table = StrategyTable::default();
+        let mut num_nodes = 0;
+
+        let result = self.strategy_table_recursive(
+            &mut history,
+            &mut line,
+            &hand_strings,
+            max_rows,
+            &mut num_nodes,
+            &mut table,
+            &mut f,
+        );
+
+        self.apply_history(&saved_history);
+        result?;
+
+        if !table.is_empty() {
+            f(table)?;
+        }
+
+        Ok(())
+    }
+
+    /// Collects the whole strategy table. See [`strategy_table_chunks`] for the details.
+    ///
+    /// [`strategy_table_chunks`]: #method.strategy_table_chunks
+    pub fn strategy_table(&mut self) -> Result<StrategyTable, Error> {
+        let mut ret = StrategyTable::default();
+        self.strategy_table_chunks(usize::MAX, |table| {
+            ret = table;
+            Ok(())
+        })?;
+        Ok(ret)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn strategy_table_recursive<F>(
+        &mut self,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+        hand_strings: &[Vec<String>; 2],
+        max_rows: usize,
+        num_nodes: &mut u32,
+        table: &mut StrategyTable,
+        f: &mut F,
+    ) -> Result<(), Error>
+    where
+        F: FnMut(StrategyTable) -> Result<(), Error>,
+    {
+        if self.is_terminal_node() {
+            return Ok(());
+        }
+
+        let children = if self.is_chance_node() {
+            let possible_cards = self.possible_cards();
+            (0..52)
+                .filter(|&card| possible_cards & (1 << card) != 0)
+                .map(|card| (card as usize, Action::Chance(card)))
+                .collect::<Vec<_>>()
+        } else {
+            self.append_strategy_rows(line, hand_strings, *num_nodes, table)?;
+            *num_nodes += 1;
+
+            if table.len() >= max_rows {
+                f(std::mem::take(table))?;
+            }
+
+            self.available_actions().into_iter().enumerate().collect()
+        };
+
+        for (index, action) in children {
+            history.push(index);
+            line.push(action);
+            self.apply_history(history);
+            self.strategy_table_recursive(
+                history,
+                line,
+                hand_strings,
+                max_rows,
+                num_nodes,
+                table,
+                f,
+            )?;
+            line.pop();
+            history.pop();
+        }
+
+        Ok(())
+    }
+
+    fn append_strategy_rows(
+        &mut self,
+        line: &[Action],
+        hand_strings: &[Vec<String>; 2],
+        node: u32,
+        table: &mut StrategyTable,
+    ) -> Result<(), Error> {
+        let player = self.current_player();
+        let actions = self.available_actions();
+        let num_hands = hand_strings[player].len();
+
+        self.cache_normalized_weights();
+        let reach = self.normalized_weights(player);
+        let strategy = self.strategy();
+        let ev = self.expected_values_detail(player);
+
+        let pio_path = line_to_pio_path(line);
+        let line = line_to_pattern_string(line);
+        let board = self
+            .current_board()
+            .iter()
+            .map(|&card| card_to_string(card))
+            .collect::<Result<String, _>>()?;
+
+        for (i, &action) in actions.iter().enumerate() {
+            let action = action_to_string(action);
+            for hand in 0..num_hands {
+                if reach[hand] == 0.0 {
+                    continue;
+                }
+                let index = i * num_hands + hand;
+                table.node.push(node);
+                table.line.push(line.clone());
+                table.pio_path.push(pio_path.clone());
+                table.board.push(board.clone());
+                table.player.push(player as u8);
+                table.hand.push(hand_strings[player][hand].clone());
+                table.action.push(action.clone());
+                table.frequency.push(strategy[index]);
+                table.ev.push(ev[index]);
+                table.reach.push(reach[hand]);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the name of `action` used in tables and reports (e.g., `Bet 100`).
+pub(crate) fn action_to_string(action: Action) -> String {
+    match action {
+        Action::Fold => "Fold".to_string(),
+        Action::Check => "Check".to_string(),
+        Action::Call => "Call".to_string(),
+        Action::Bet(amount) => format!("Bet {amount}"),
+        Action::Raise(amount) => format!("Raise {amount}"),
+        Action::AllIn(amount) => format!("All-in {amount}"),
+        _ => format!("{action:?}"),
+    }
+}
+
+/// Returns the line in the pattern syntax of [`NodePattern`].
+pub(crate) fn line_to_pattern_string(line: &[Action]) -> String {
+    let mut ret = "r".to_string();
+    for action in line {
+        ret.push(':');
+        match *action {
+            Action::Fold => ret.push('f'),
+            Action::Check => ret.push('x'),
+            Action::Call => ret.push('c'),
+            Action::Bet(amount) => ret.push_str(&format!("b{amount}")),
+            Action::Raise(amount) => ret.push_str(&format!("r{amount}")),
+            Action::AllIn(_) => ret.push('a'),
+            Action::Chance(card) => ret.push_str(&card_to_string(card).unwrap()),
+            _ => ret.push('?'),
+        }
+    }
+    ret
+}
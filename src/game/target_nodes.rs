@@ -0,0 +1,155 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+
+/// Convergence metrics of a target node, computed by [`TargetNodeMonitor::update`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeConvergence {
+    /// The history of the target node (see [`PostFlopGame::apply_history`]).
+    pub history: Vec<usize>,
+
+    /// The player to act at the target node.
+    pub player: usize,
+
+    /// The number of completed iterations at the last update (`0` if not updated yet).
+    pub iteration: u32,
+
+    /// The maximum absolute change of the average strategy of any hand and action between the
+    /// last two updates, or `f32::INFINITY` if the node has been updated less than twice.
+    pub strategy_change: f32,
+
+    /// The maximum positive cumulative regret of any hand and action at the last update.
+    ///
+    /// The solver discounts the cumulative regrets, so this value is meant to be compared over
+    /// the updates rather than to an absolute threshold.
+    pub max_regret: f32,
+}
+
+/// Monitors the convergence of selected nodes separately from the global exploitability.
+///
+/// When studying a single line, the strategies of the nodes of interest may stabilize long before
+/// the exploitability of the whole tree reaches the target. Register the nodes with [`add_node`]
+/// and call [`update`] periodically between [`solve_step`] calls; the solving loop can then stop
+/// as soon as [`is_converged`] returns `true`, i.e., the average strategy of every target node
+/// changed by at most the threshold since the previous update.
+///
+/// [`add_node`]: #method.add_node
+/// [`update`]: #method.update
+/// [`is_converged`]: #method.is_converged
+/// [`solve_step`]: crate::solve_step
+#[derive(Debug, Clone, Default)]
+pub struct TargetNodeMonitor {
+    threshold: f32,
+    indices: Vec<usize>,
+    strategies: Vec<Vec<f32>>,
+    reports: Vec<NodeConvergence>,
+}
+
+impl TargetNodeMonitor {
+    /// Creates a new monitor with the threshold of the strategy change for convergence.
+    ///
+    /// Panics if `threshold` is negative or not finite.
+    #[inline]
+    pub fn new(threshold: f32) -> Self {
+        if !threshold.is_finite() || threshold < 0.0 {
+            panic!("Threshold must be non-negative and finite: {threshold}");
+        }
+
+        Self {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the threshold of the strategy change.
+    #[inline]
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Registers the node specified by `history` as a target node.
+    ///
+    /// The current node of `game` is restored after the call. Returns an error if the memory is
+    /// not allocated or the specified node is not a decision node.
+    pub fn add_node(&mut self, game: &mut PostFlopGame, history: &[usize]) -> Result<(), Error> {
+        game.check_operation(GameOperation::Navigate)?;
+
+        let current_history = game.history().to_vec();
+        game.apply_history(history);
+
+        let ret = if game.is_terminal_node() || game.is_chance_node() {
+            Err(Error::Tree(format!(
+                "Node is not a decision node: {history:?}"
+            )))
+        } else {
+            self.indices.push(game.node_index(&game.node()));
+            self.strategies.push(Vec::new());
+            self.reports.push(NodeConvergence {
+                history: game.history().to_vec(),
+                player: game.current_player(),
+                strategy_change: f32::INFINITY,
+                ..Default::default()
+            });
+            Ok(())
+        };
+
+        game.apply_history(&current_history);
+        ret
+    }
+
+    /// Updates the metrics of the target nodes.
+    ///
+    /// `iteration` is the number of completed iterations, i.e., `t + 1` after calling
+    /// `solve_step(&game, t)`. The strategy change is measured from the previous update, so the
+    /// interval of the updates should be kept constant.
+    pub fn update(&mut self, game: &PostFlopGame, iteration: u32) {
+        let iter = self.indices.iter().zip(&mut self.strategies);
+        for ((&index, prev), report) in iter.zip(&mut self.reports) {
+            let node = game.node_arena[index].lock();
+            let strategy = game.node_strategy(&node);
+
+            report.iteration = iteration;
+            report.max_regret = max_regret(game, &node);
+            if !prev.is_empty() {
+                report.strategy_change = strategy
+                    .iter()
+                    .zip(prev.iter())
+                    .fold(0.0, |acc, (&x, &y)| acc.max((x - y).abs()));
+            }
+
+            *prev = strategy;
+        }
+    }
+
+    /// Returns the metrics of the target nodes in the order of registration.
+    #[inline]
+    pub fn reports(&self) -> &[NodeConvergence] {
+        &self.reports
+    }
+
+    /// Returns whether at least one node is registered and the strategy change of every target
+    /// node is at most the threshold.
+    #[inline]
+    pub fn is_converged(&self) -> bool {
+        !self.reports.is_empty()
+            && self
+                .reports
+                .iter()
+                .all(|report| report.strategy_change <= self.threshold)
+    }
+}
+
+/// Returns the maximum positive cumulative regret of the node.
+#[inline]
+fn max_regret(game: &PostFlopGame, node: &PostFlopNode) -> f32 {
+    if game.is_node_compressed(node) {
+        let decoder = node.regret_scale() / i16::MAX as f32;
+        let regrets = node.regrets_compressed().iter();
+        regrets.map(|&x| x as f32 * decoder).fold(0.0, f32::max)
+    } else if game.is_f64_regrets_enabled() {
+        let regrets = node.regrets_f64().iter();
+        regrets.map(|&x| x as f32).fold(0.0, f32::max)
+    } else {
+        node.regrets().iter().copied().fold(0.0, f32::max)
+    }
+}
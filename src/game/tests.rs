@@ -1,9 +1,55 @@
 use super::*;
+use crate::averaging::*;
+use crate::equity::*;
+use crate::error::*;
+use crate::hand_class::*;
+use crate::interface::*;
+use crate::json::*;
+use crate::payoff::*;
+use crate::pio::*;
 use crate::range::*;
+use crate::schema::*;
 use crate::solver::*;
 use crate::utility::*;
+use crate::verify::*;
 use crate::BunchingData;
 
+/// Returns the configurations of the turn spot shared by many tests: `TT+,AKo,AQs+` vs.
+/// `AA,KK,QQ` on `2c6dTh3s`, where only OOP can bet (50% pot).
+fn turn_spot_configs() -> (CardConfig, TreeConfig) {
+    let card_config = CardConfig {
+        range: ["TT+,AKo,AQs+".parse().unwrap(), "AA,KK,QQ".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        turn: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    (card_config, tree_config)
+}
+
+/// Builds a game from the configurations without allocating the memory.
+fn build_game(card_config: CardConfig, tree_config: TreeConfig) -> PostFlopGame {
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    PostFlopGame::with_config(card_config, action_tree).unwrap()
+}
+
+/// Builds the game of [`turn_spot_configs`] with the memory allocated.
+fn turn_spot_game() -> PostFlopGame {
+    let (card_config, tree_config) = turn_spot_configs();
+    let mut game = build_game(card_config, tree_config);
+    game.allocate_memory(false);
+    game
+}
+
 #[test]
 fn all_check_all_range() {
     let card_config = CardConfig {
@@ -350,6 +396,7 @@ fn one_raise_all_range_with_river() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: card_from_str("7s").unwrap(),
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
@@ -494,6 +541,38 @@ fn always_win() {
     assert!((ev_ip - 0.0).abs() < 1e-4);
 }
 
+#[test]
+fn ev_report_always_win() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let report = game.ev_report(0, 2.0);
+    assert!((report.committed - 30.0).abs() < 1e-9);
+    assert!((report.net_ev - 30.0).abs() < 1e-4);
+    assert!((report.net_ev_bb - 15.0).abs() < 1e-4);
+    assert!((report.net_bb_per_100 - 1500.0).abs() < 1e-2);
+    assert!((report.ev_pot_fraction - 1.0).abs() < 1e-6);
+    assert!((report.starting_stack_bb - 500.0).abs() < 1e-9);
+}
+
 #[test]
 fn always_win_raked() {
     // be careful for straight flushes
@@ -547,6 +626,87 @@ fn always_win_raked() {
     assert!((ev_ip - 0.0).abs() < 1e-4);
 }
 
+#[test]
+fn rake_report() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        rake_rate: 0.05,
+        rake_cap: 10.0,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    assert!(game.rake_report().is_err());
+    finalize(&mut game);
+
+    // the winner of the pot pays the whole rake
+    let report = game.rake_report().unwrap();
+    assert!((report.expected_rake - 3.0).abs() < 1e-6);
+    assert!((report.rake_paid[0] - 3.0).abs() < 1e-6);
+    assert!(report.rake_paid[1].abs() < 1e-6);
+    assert!((report.ev[0] - 27.0).abs() < 1e-4);
+    assert!((report.ev_without_rake[0] - 30.0).abs() < 1e-4);
+    assert!((report.ev[1] + 30.0).abs() < 1e-4);
+    assert_eq!(report.lines.len(), 1);
+    assert_eq!(report.lines[0].actions, vec![Action::Check; 6]);
+    assert!((report.lines[0].probability - 1.0).abs() < 1e-6);
+
+    let card_config = CardConfig {
+        range: ["66+,A8s+,AJo+,KTs+".parse().unwrap(); 2],
+        flop: flop_from_str("Td9d6d").unwrap(),
+        turn: card_from_str("Qd").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 200,
+        rake_rate: 0.05,
+        rake_cap: 5.0,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    let report = game.rake_report().unwrap();
+    let ev = compute_current_ev(&game);
+    for (&a, &b) in report.ev.iter().zip(&ev) {
+        assert!((a - b as f64).abs() < 1e-3);
+    }
+    assert!(report.expected_rake > 0.0);
+    assert!((report.ev[0] + report.ev[1] + report.expected_rake).abs() < 1e-3);
+
+    let total_probability = report.lines.iter().map(|l| l.probability).sum::<f64>();
+    assert!((total_probability - 1.0).abs() < 1e-5);
+
+    // the folding player pays no rake
+    for line in &report.lines {
+        if line.actions.last() == Some(&Action::Fold) {
+            assert!(line.rake_paid.iter().any(|&rake| rake.abs() < 1e-9));
+            let total_rake = line.rake_paid[0] + line.rake_paid[1];
+            assert!(total_rake <= 5.0 * line.probability + 1e-9);
+        }
+    }
+}
+
 #[test]
 fn always_lose() {
     // be careful for straight flushes
@@ -617,6 +777,54 @@ fn always_lose_raked() {
     assert!((root_ev_ip - 57.0).abs() < 1e-4);
 }
 
+#[test]
+fn always_lose_icm() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: [lose_range_str.parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let icm_config = IcmConfig {
+        stacks: [970.0, 970.0],
+        other_stacks: vec![1000.0],
+        payouts: vec![60.0, 40.0],
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        payoff_model: PayoffModel::Icm(icm_config.clone()),
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    // chip equivalents of the ICM equities when OOP loses the half pot of 30 chips
+    let before = icm_equities(&[1000.0, 1000.0, 1000.0], &icm_config.payouts);
+    let after = icm_equities(&[970.0, 1030.0, 1000.0], &icm_config.payouts);
+    let expected = [
+        30.0 + (after[0] - before[0]) * 30.0,
+        30.0 + (after[1] - before[1]) * 30.0,
+    ];
+    assert!(expected[1] - 30.0 < 30.0);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_ev_oop - expected[0] as f32).abs() < 1e-3);
+    assert!((root_ev_ip - expected[1] as f32).abs() < 1e-3);
+}
+
 #[test]
 fn always_tie() {
     let card_config = CardConfig {
@@ -683,6 +891,74 @@ fn always_tie_raked() {
     assert!((root_ev_ip - 28.5).abs() < 1e-4);
 }
 
+#[test]
+fn showdown_realization() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.set_showdown_realization([0.9, 1.0]);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((ev_oop - 54.0).abs() < 1e-4);
+    assert!((ev_ip - 6.0).abs() < 1e-4);
+
+    game.apply_history(&[0, 0, usize::MAX, 0, 0, usize::MAX, 0, 0]);
+    assert_eq!(game.current_showdown_realization(), [0.9, 1.0]);
+}
+
+#[test]
+fn showdown_realization_tie() {
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        rake_rate: 0.05,
+        rake_cap: 30.0,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.set_showdown_realization([0.9, 1.0]);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_ev_oop - 0.45 * 57.0).abs() < 1e-4);
+    assert!((root_ev_ip - 0.55 * 57.0).abs() < 1e-4);
+}
+
 #[test]
 fn no_assignment() {
     let card_config = CardConfig {
@@ -785,75 +1061,1248 @@ fn remove_lines() {
 }
 
 #[test]
-fn isomorphism_monotone() {
-    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
-    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
-
-    let card_config = CardConfig {
-        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
-        flop: flop_from_str("QhJh2h").unwrap(),
-        ..Default::default()
-    };
+fn tree_shape_options() {
+    use crate::bet_size::{BetSizeOptions, DonkSizeOptions};
 
     let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
         starting_pot: 100,
-        effective_stack: 100,
+        effective_stack: 1000,
+        turn_bet_sizes: [
+            BetSizeOptions::try_from(("50%", "2x")).unwrap(),
+            BetSizeOptions::try_from(("75%", "3x")).unwrap(),
+        ],
+        river_bet_sizes: [
+            BetSizeOptions::try_from(("50%", "")).unwrap(),
+            BetSizeOptions::try_from(("33%, 35%", "")).unwrap(),
+        ],
+        river_donk_sizes: Some(DonkSizeOptions::try_from("20%").unwrap()),
+        add_allin_threshold: 1.5,
+        force_allin_threshold: 0.15,
+        merging_threshold: 0.1,
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-
-    game.allocate_memory(false);
-    finalize(&mut game);
-
-    let mut check = |history: &[usize],
-                     expected_turn_swap: Option<u8>,
-                     expected_river_swap: Option<(u8, u8)>| {
-        game.apply_history(history);
-        game.cache_normalized_weights();
-        let weights = game.normalized_weights(0);
-        let ev = game.expected_values(0);
-        weights.iter().zip(ev.iter()).for_each(|(&w, &v)| {
-            assert!(!(w > 0.0 && v == 50.0));
-        });
-        assert_eq!(game.turn_swap, expected_turn_swap);
-        assert_eq!(game.river_swap, expected_river_swap);
+    let mut tree = ActionTree::new(tree_config).unwrap();
+    let mut line = |actions: &[Action]| {
+        tree.apply_history(actions).unwrap();
+        tree.available_actions().to_vec()
     };
 
-    check(&[0, 0, 4], None, None);
-    check(&[0, 0, 5], Some(1), None);
-    check(&[0, 0, 6], None, None);
-    check(&[0, 0, 7], Some(3), None);
+    // per-player sizes on the turn
+    assert_eq!(line(&[]), [Action::Check, Action::Bet(50)]);
+    assert_eq!(line(&[Action::Check]), [Action::Check, Action::Bet(75)]);
+    assert_eq!(
+        line(&[Action::Bet(50)]),
+        [Action::Fold, Action::Call, Action::Raise(150)]
+    );
+    assert_eq!(
+        line(&[Action::Bet(50), Action::Raise(150)]),
+        [Action::Fold, Action::Call, Action::Raise(300)]
+    );
 
-    check(&[0, 0, 4, 0, 0, 8], None, None);
-    check(&[0, 0, 4, 0, 0, 9], None, None);
-    check(&[0, 0, 4, 0, 0, 10], None, None);
-    check(&[0, 0, 4, 0, 0, 11], None, Some((0, 3)));
+    // no donk bet after a checked-through turn
+    assert_eq!(
+        line(&[Action::Check, Action::Check]),
+        [Action::Check, Action::Bet(50)]
+    );
 
-    check(&[0, 0, 5, 0, 0, 8], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 9], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 10], Some(1), None);
-    check(&[0, 0, 5, 0, 0, 11], Some(1), Some((1, 3)));
+    // 33% and 35% are merged
+    assert_eq!(
+        line(&[Action::Check, Action::Check, Action::Check]),
+        [Action::Check, Action::Bet(35)]
+    );
 
-    check(&[0, 0, 6, 0, 0, 8], None, None);
-    check(&[0, 0, 6, 0, 0, 9], None, Some((2, 1)));
-    check(&[0, 0, 6, 0, 0, 10], None, None);
-    check(&[0, 0, 6, 0, 0, 11], None, Some((2, 3)));
+    // donk bet (20% of 250) after calling the turn bet
+    assert_eq!(
+        line(&[Action::Check, Action::Bet(75), Action::Call]),
+        [Action::Check, Action::Bet(50)]
+    );
 
-    check(&[0, 0, 7, 0, 0, 8], Some(3), Some((3, 1)));
-    check(&[0, 0, 7, 0, 0, 9], Some(3), None);
-    check(&[0, 0, 7, 0, 0, 10], Some(3), None);
-    check(&[0, 0, 7, 0, 0, 11], Some(3), None);
+    // all-in is added when the stack is small relative to the pot
+    let tree_config = TreeConfig {
+        effective_stack: 140,
+        ..tree.config().clone()
+    };
+    let mut tree = ActionTree::new(tree_config).unwrap();
+    tree.apply_history(&[]).unwrap();
+    assert_eq!(
+        tree.available_actions(),
+        [Action::Check, Action::Bet(50), Action::AllIn(140)]
+    );
 }
 
 #[test]
-fn node_locking() {
+fn memory_usage_before_allocation() {
+    let (mut card_config, tree_config) = turn_spot_configs();
+    card_config.range[1] = "AA,QQ".parse().unwrap();
+    card_config.turn = card_from_str("8s").unwrap();
+    let mut game = build_game(card_config, tree_config);
+
+    // the estimate is available before the allocation and reflects removed lines
+    let estimate = game.memory_usage();
+    assert!(estimate.1 < estimate.0);
+    game.remove_lines(&[vec![Action::Bet(30)]]).unwrap();
+    let (uncompressed, compressed) = game.memory_usage();
+    assert!(uncompressed < estimate.0);
+    assert!(compressed < estimate.1);
+    assert_eq!(game.is_memory_allocated(), None);
+
+    for enable_compression in [false, true] {
+        game.allocate_memory(enable_compression);
+        assert_eq!(game.memory_usage(), (uncompressed, compressed));
+
+        let allocated = game.storage1.len()
+            + game.storage2.len()
+            + game.storage_ip.len()
+            + game.storage_chance.len();
+        let expected = if enable_compression {
+            compressed
+        } else {
+            uncompressed
+        };
+        assert_eq!(allocated as u64 + game.misc_memory_usage, expected);
+    }
+}
+
+#[test]
+fn randomize_regrets_seeded() {
     let card_config = CardConfig {
-        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
-        flop: flop_from_str("2s3h4d").unwrap(),
-        turn: card_from_str("6c").unwrap(),
-        river: card_from_str("7c").unwrap(),
+        range: ["TT+,AKo,AQs+".parse().unwrap(), "AA,KK,QQ".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        turn: card_from_str("3s").unwrap(),
+        river: card_from_str("9s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let first_strategy = |seed: u64, compression: bool| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(compression);
+        randomize_regrets(&mut game, seed);
+        solve_step(&game, 0);
+        finalize(&mut game);
+        game.strategy()
+    };
+
+    for compression in [false, true] {
+        let strategy1 = first_strategy(1, compression);
+        let strategy2 = first_strategy(1, compression);
+        let strategy3 = first_strategy(2, compression);
+        assert_eq!(strategy1, strategy2);
+        assert_ne!(strategy1, strategy3);
+        assert!(strategy1.iter().any(|&x| (x - 0.5).abs() > 0.01));
+    }
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    randomize_regrets(&mut game, 3);
+    let exploitability = solve(&mut game, 1000, 0.01, false).exploitability;
+    assert!(exploitability <= 0.01);
+}
+
+#[test]
+fn f64_regrets() {
+    // without discounting, the cumulative regrets keep growing, so the updates of late iterations
+    // are partially lost in 32-bit floating point and the convergence stalls
+    let card_config = CardConfig {
+        range: [
+            "TT+,AKo,AQs+,87s,65s".parse().unwrap(),
+            "AA,KK,QQ,JJ,AK,T9s".parse().unwrap(),
+        ],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        turn: card_from_str("3s").unwrap(),
+        river: card_from_str("9s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [
+            ("50%,a", "60%").try_into().unwrap(),
+            ("50%", "60%").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let mut game32 = build_game(card_config.clone(), tree_config.clone());
+    game32.allocate_memory(false);
+
+    let mut game64 = build_game(card_config, tree_config);
+    game64.allocate_memory_f64_regrets();
+    assert!(game64.is_f64_regrets_enabled());
+    assert_eq!(
+        game64.memory_usage_f64_regrets(),
+        game32.memory_usage().0 + 4 * game64.num_storage
+    );
+
+    let config = SolverConfig {
+        algorithm: SolverAlgorithm::VanillaCfr,
+        ..Default::default()
+    };
+    let exploitability32 = solve_with_config(&mut game32, 10000, 0.0, &config, false);
+    let exploitability64 = solve_with_config(&mut game64, 10000, 0.0, &config, false);
+    let target = 0.05;
+    assert!(exploitability32 > target);
+    assert!(exploitability64 <= target);
+    assert!(exploitability64 < exploitability32 * 0.5);
+}
+
+#[test]
+fn turn_abstraction() {
+    let card_config = CardConfig {
+        range: [
+            "TT+,AKo,AQs+".parse().unwrap(),
+            "AA,KK,QQ,JJ".parse().unwrap(),
+        ],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let num_turn_nodes = game.num_nodes[1];
+
+    let bucket = |ranks: &str| {
+        ["c", "d", "h", "s"]
+            .iter()
+            .map(|suit| card_from_str(&format!("{ranks}{suit}")).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let flop_card = vec![card_from_str("2c").unwrap()];
+    assert!(game.set_turn_abstraction(&[flop_card]).is_err());
+
+    let buckets = vec![bucket("3"), bucket("4"), bucket("5")];
+    game.set_turn_abstraction(&buckets).unwrap();
+    assert_eq!(game.turn_abstraction(), &buckets);
+    assert_eq!(game.num_nodes[1] * 49, num_turn_nodes * (49 - 9));
+
+    game.allocate_memory(false);
+    let exploitability = solve(&mut game, 200, 0.0, false).exploitability;
+    assert!(exploitability < 0.5);
+
+    // a non-representative card moves to the subtree of the representative
+    game.apply_history(&[0, 0, card_from_str("4c").unwrap() as usize]);
+    let repr_index = game.node_index(&game.node());
+    game.apply_history(&[0, 0, card_from_str("4h").unwrap() as usize]);
+    assert_eq!(game.node_index(&game.node()), repr_index);
+    assert_eq!(game.current_board()[3], card_from_str("4h").unwrap());
+
+    // the representative card can be dealt on the river
+    game.play(0);
+    game.play(0);
+    game.play(card_from_str("4c").unwrap() as usize);
+    assert_eq!(game.current_board()[4], card_from_str("4c").unwrap());
+    game.cache_normalized_weights();
+    assert!(game.expected_values(0).iter().all(|ev| ev.is_finite()));
+}
+
+#[test]
+fn turn_abstraction_card_removal() {
+    let card_config = CardConfig {
+        range: ["44,A4s,AA".parse().unwrap(), "QQ,JJ,A5s".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let card = |s: &str| card_from_str(s).unwrap();
+    let (repr, other) = (card("4c"), card("4h"));
+    game.set_turn_abstraction(&[vec![repr, other]]).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 300, 0.0, false);
+
+    let hand = |game: &PostFlopGame, c1: &str, c2: &str| {
+        let (c1, c2) = (card(c1), card(c2));
+        let key = (c1.min(c2), c1.max(c2));
+        game.private_cards(0).binary_search(&key).unwrap()
+    };
+
+    // the results of each hand on the turn, in the order of the checked hands
+    let results = |game: &mut PostFlopGame, turn: Card| {
+        game.apply_history(&[0, 0, turn as usize]);
+        game.cache_normalized_weights();
+        let strategy = game.strategy();
+        let ev = game.expected_values(0);
+        let num_hands = game.num_private_hands(0);
+        let hands = [("4d", "4s"), ("4c", "4d"), ("4h", "4d")];
+        hands.map(|(c1, c2)| {
+            let i = hand(game, c1, c2);
+            let bet = strategy[num_hands + i];
+            (game.weights(0)[i], bet, ev[i])
+        })
+    };
+
+    let [unblocked_repr, holding_repr, holding_other] = results(&mut game, repr);
+    let [unblocked, holding_repr_on_other, holding_other_on_other] = results(&mut game, other);
+
+    // a hand not holding the cards of the bucket gets the same results on both cards
+    assert_eq!(unblocked, unblocked_repr);
+
+    // on the representative card, the hand holding it is blocked and the hand holding the other
+    // card is solved
+    assert_eq!(holding_repr.0, 0.0);
+    assert_eq!(holding_other.0, 1.0);
+    assert!(holding_other.1 > 0.9);
+
+    // the hands are not remapped: on the other card, the hand holding the other card is blocked,
+    // while the hand holding the representative card is reachable but gets the entries that are
+    // blocked in the subtree of the representative, i.e., the untrained uniform strategy and a
+    // zero counterfactual value (half of the starting pot)
+    assert_eq!(holding_other_on_other.0, 0.0);
+    assert_eq!(holding_repr_on_other.0, 1.0);
+    assert_eq!(holding_repr_on_other.1, 0.5);
+    assert_eq!(holding_repr_on_other.2, 30.0);
+}
+
+#[test]
+fn strategy_watcher() {
+    let (mut card_config, _) = turn_spot_configs();
+    card_config.turn = NOT_DEALT;
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let mut game = build_game(card_config, tree_config);
+    game.allocate_memory(false);
+
+    let mut watcher = StrategyWatcher::new(5);
+    watcher.watch(&mut game, &[]).unwrap();
+    watcher.watch(&mut game, &[0, 0, usize::MAX]).unwrap();
+    assert!(watcher.watch(&mut game, &[0, 0]).is_err());
+    assert_eq!(game.history(), &[] as &[usize]);
+
+    for t in 0..20 {
+        solve_step(&game, t);
+        watcher.record(&game, t + 1);
+    }
+
+    finalize(&mut game);
+
+    let traces = watcher.traces();
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0].iterations, vec![5, 10, 15, 20]);
+    assert_eq!(traces[0].actions, vec![Action::Check, Action::Bet(30)]);
+
+    game.apply_history(&traces[1].history);
+    let strategy = game.strategy();
+    assert_eq!(traces[1].average_strategies[3].len(), strategy.len());
+    for (a, b) in traces[1].average_strategies[3].iter().zip(&strategy) {
+        assert!((a - b).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn solve_streamer() {
+    let (mut card_config, _) = turn_spot_configs();
+    card_config.turn = NOT_DEALT;
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let mut game = build_game(card_config, tree_config);
+    game.allocate_memory(false);
+
+    let (mut streamer, receiver) = SolveStreamer::channel(5);
+    streamer.watch(&mut game, &[]).unwrap();
+    streamer.watch(&mut game, &[0, 0, usize::MAX]).unwrap();
+    assert!(streamer.watch(&mut game, &[0, 0]).is_err());
+
+    let config = SolverConfig::default();
+    let exploitability = game
+        .solve_streaming(12, 0.0, &config, &mut streamer)
+        .unwrap();
+    drop(streamer);
+
+    let messages = receiver
+        .iter()
+        .map(|message| JsonValue::parse(&message).unwrap())
+        .collect::<Vec<_>>();
+    for message in &messages {
+        validate_json("solve_stream", message).unwrap();
+    }
+    let field = |i: usize, key: &str| messages[i].get(key).unwrap();
+
+    assert_eq!(messages.len(), 4);
+    for (i, iteration) in [0.0, 5.0, 10.0, 12.0].into_iter().enumerate() {
+        assert_eq!(
+            field(i, "version").as_f64(),
+            Some(STREAM_SCHEMA_VERSION as f64)
+        );
+        assert_eq!(field(i, "iteration").as_f64(), Some(iteration));
+        let kind = if i == 3 { "finished" } else { "snapshot" };
+        assert_eq!(field(i, "type").as_str(), Some(kind));
+        assert_eq!(field(i, "nodes").as_array().unwrap().len(), 2);
+    }
+
+    let reported = field(3, "exploitability").as_f64().unwrap();
+    assert!((reported - exploitability as f64).abs() < 1e-6);
+
+    let snapshot = &field(1, "nodes").as_array().unwrap()[0];
+    let current = snapshot
+        .get("current_strategy")
+        .unwrap()
+        .as_array()
+        .unwrap();
+    assert_eq!(current.len(), 2);
+
+    let finished = &field(3, "nodes").as_array().unwrap()[1];
+    assert_eq!(finished.get("current_strategy"), Some(&JsonValue::Null));
+    let actions = finished.get("actions").unwrap().as_array().unwrap();
+    let hands = finished.get("hands").unwrap().as_array().unwrap();
+    let rows = finished.get("strategy").unwrap().as_array().unwrap();
+    assert_eq!(actions.len(), rows.len());
+
+    game.apply_history(&[0, 0, usize::MAX]);
+    assert_eq!(hands.len(), game.private_cards(game.current_player()).len());
+    let strategy = game.strategy();
+    let values = rows
+        .iter()
+        .flat_map(|row| row.as_array().unwrap().iter().map(|x| x.as_f64().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(values.len(), strategy.len());
+    for (a, &b) in values.iter().zip(&strategy) {
+        assert!((a - b as f64).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn target_node_monitor() {
+    let (card_config, tree_config) = turn_spot_configs();
+    let mut game = build_game(card_config, tree_config);
+
+    let mut monitor = TargetNodeMonitor::new(1e-3);
+    assert!(monitor.add_node(&mut game, &[]).is_err());
+
+    game.allocate_memory(false);
+    monitor.add_node(&mut game, &[1]).unwrap();
+    assert!(monitor.add_node(&mut game, &[0, 0]).is_err());
+    assert_eq!(game.history(), &[] as &[usize]);
+    assert!(!monitor.is_converged());
+
+    let mut t = 0;
+    while !monitor.is_converged() && t < 1000 {
+        solve_step(&game, t);
+        t += 1;
+        if t % 10 == 0 {
+            monitor.update(&game, t);
+        }
+    }
+
+    let report = &monitor.reports()[0];
+    assert!(t < 1000);
+    assert_eq!(report.iteration, t);
+    assert_eq!(report.history, vec![1]);
+    assert_eq!(report.player, 1);
+    assert!(report.strategy_change <= 1e-3);
+    assert!(report.max_regret >= 0.0);
+}
+
+#[test]
+fn strategy_distance() {
+    let mut game1 = turn_spot_game();
+    let mut game2 = turn_spot_game();
+    solve(&mut game1, 100, 0.0, false);
+    finalize(&mut game2);
+
+    let same = compute_strategy_distance(&game1, &game1).unwrap();
+    assert!(same.total.kl_divergence.abs() < 1e-6);
+    assert!(same.total.l2_distance.abs() < 1e-6);
+    assert_eq!(same.nodes[0].history, Vec::<usize>::new());
+    assert!((same.nodes[0].reach - 1.0).abs() < 1e-6);
+    assert_eq!(same.streets[0].num_nodes, 0);
+
+    let diff = compute_strategy_distance(&game1, &game2).unwrap();
+    assert!(diff.total.l2_distance > 0.0);
+    assert_eq!(diff.total.num_nodes, diff.nodes.len());
+    assert_eq!(
+        diff.streets[1].num_nodes + diff.streets[2].num_nodes,
+        diff.nodes.len()
+    );
+    assert_eq!(diff.line(&[]), diff.total);
+    assert!(diff.line(&[1]).num_nodes < diff.nodes.len());
+}
+
+#[test]
+fn solve_in_chunks() {
+    let mut game1 = turn_spot_game();
+    let mut game2 = turn_spot_game();
+
+    let config = SolverConfig::default();
+    let mut exploitability = f32::INFINITY;
+    for chunk in 0..3 {
+        exploitability = solve_steps(&game1, chunk * 10, 10, &config).unwrap();
+    }
+
+    for t in 0..30 {
+        solve_step(&game2, t);
+    }
+
+    assert_eq!(exploitability, compute_exploitability(&game2));
+
+    finalize(&mut game1);
+    finalize(&mut game2);
+    assert_eq!(game1.strategy(), game2.strategy());
+}
+
+#[test]
+fn solve_options() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    // the progress is reported at each evaluation of the exploitability
+    let mut game = turn_spot_game();
+    let mut reports = Vec::new();
+    let mut progress = |t, exploitability| reports.push((t, exploitability));
+    let options = SolveOptions {
+        progress: Some(&mut progress),
+        ..Default::default()
+    };
+    let exploitability = solve_with_options(&mut game, 25, 0.0, options).unwrap();
+    let iterations = reports.iter().map(|&(t, _)| t).collect::<Vec<_>>();
+    assert_eq!(iterations, vec![0, 10, 20, 25]);
+    assert_eq!(reports.last().unwrap().1, exploitability);
+
+    let mut expected = turn_spot_game();
+    assert_eq!(
+        solve(&mut expected, 25, 0.0, false).exploitability,
+        exploitability
+    );
+    assert_eq!(game.strategy(), expected.strategy());
+
+    // cancellation between iterations
+    let mut game = turn_spot_game();
+    let cancel = AtomicBool::new(false);
+    let mut num_reports = 0;
+    let mut progress = |t, _| {
+        num_reports += 1;
+        if t >= 10 {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    };
+    let options = SolveOptions {
+        progress: Some(&mut progress),
+        cancel: Some(&cancel),
+        ..Default::default()
+    };
+    let exploitability = solve_with_options(&mut game, 1000, 0.0, options).unwrap();
+    assert_eq!(num_reports, 2);
+    assert!(game.is_solved());
+
+    let mut expected = turn_spot_game();
+    assert_eq!(
+        solve(&mut expected, 10, 0.0, false).exploitability,
+        exploitability
+    );
+
+    // time budget
+    let mut game = turn_spot_game();
+    let options = SolveOptions {
+        time_limit: Some(Duration::ZERO),
+        ..Default::default()
+    };
+    solve_with_options(&mut game, 1000, 0.0, options).unwrap();
+    assert!(game.is_solved());
+
+    let mut expected = turn_spot_game();
+    solve(&mut expected, 0, 0.0, false);
+    assert_eq!(game.strategy(), expected.strategy());
+}
+
+#[test]
+fn check_schedule() {
+    let solve_with_schedule = |schedule, max_num_iterations, target| {
+        let mut game = turn_spot_game();
+        let mut checks = Vec::new();
+        let mut progress = |t, exploitability| checks.push((t, exploitability));
+        let options = SolveOptions {
+            config: SolverConfig {
+                check_schedule: schedule,
+                ..Default::default()
+            },
+            progress: Some(&mut progress),
+            ..Default::default()
+        };
+        let exploitability =
+            solve_with_options(&mut game, max_num_iterations, target, options).unwrap();
+        (exploitability, checks)
+    };
+
+    // fixed interval
+    let (_, checks) = solve_with_schedule(CheckSchedule::Fixed(7), 30, 0.0);
+    let iterations = checks.iter().map(|&(t, _)| t).collect::<Vec<_>>();
+    assert_eq!(iterations, vec![0, 7, 14, 21, 28, 30]);
+
+    // without a reachable target, the interval grows geometrically
+    let (_, checks) = solve_with_schedule(CheckSchedule::Adaptive, 100, 0.0);
+    let iterations = checks.iter().map(|&(t, _)| t).collect::<Vec<_>>();
+    assert_eq!(iterations, vec![0, 10, 20, 30, 45, 67, 100]);
+
+    // the target is reached with fewer checks than the fixed interval of 10 iterations
+    let target = 0.001 * 60.0;
+    let (fixed, fixed_checks) = solve_with_schedule(CheckSchedule::Fixed(10), 1000, target);
+    let (adaptive, adaptive_checks) = solve_with_schedule(CheckSchedule::Adaptive, 1000, target);
+    assert!(fixed <= target && adaptive <= target);
+    assert!(adaptive_checks.len() < fixed_checks.len());
+    let fixed_cost = fixed_checks.last().unwrap().0 as usize + fixed_checks.len();
+    let adaptive_cost = adaptive_checks.last().unwrap().0 as usize + adaptive_checks.len();
+    assert!(adaptive_cost <= fixed_cost, "{adaptive_checks:?}");
+
+    // the schedule only depends on the exploitability
+    let (again, again_checks) = solve_with_schedule(CheckSchedule::Adaptive, 1000, target);
+    assert_eq!(again, adaptive);
+    assert_eq!(again_checks, adaptive_checks);
+}
+
+#[test]
+fn solve_summary() {
+    let mut game = turn_spot_game();
+    let summary = solve(&mut game, 100, 0.3, false);
+
+    let mut expected = turn_spot_game();
+    let exploitability = solve_with_config(&mut expected, 100, 0.3, &Default::default(), false);
+
+    assert_eq!(summary.exploitability, exploitability);
+    assert!(summary.exploitability <= 0.3);
+    assert!(summary.num_iterations > 0 && summary.num_iterations < 100);
+    assert_eq!(summary.num_iterations, expected.num_iterations());
+    assert!(summary.elapsed.is_some());
+    assert_eq!(summary.peak_memory_usage, game.memory_usage().0);
+    assert_eq!(summary.root_ev, compute_current_ev(&game));
+
+    // the board has no isomorphic rivers, so every node in the arena is visited
+    let num_nodes =
+        summary.num_decision_nodes + summary.num_chance_nodes + summary.num_terminal_nodes;
+    assert_eq!(num_nodes, game.node_arena.len());
+    assert!(summary.num_decision_nodes > 0);
+    assert!(summary.num_chance_nodes > 0);
+    assert!(summary.num_terminal_nodes > 0);
+
+    let text = summary.to_string();
+    assert!(text.starts_with("exploitability = "));
+    assert!(text.contains(&format!("iterations = {}", summary.num_iterations)));
+
+    // the lazily allocated storage grows during solving
+    let (card_config, tree_config) = turn_spot_configs();
+    let mut lazy = build_game(card_config, tree_config);
+    lazy.allocate_memory_lazy(false, 0.0);
+    let before = lazy.lazy_memory_usage();
+    let summary = solve(&mut lazy, 20, 0.0, false);
+    assert!(summary.peak_memory_usage > before);
+    assert_eq!(summary.num_iterations, 20);
+}
+
+#[test]
+fn matchup_ev() {
+    let (card_config, tree_config) = turn_spot_configs();
+    let mut game1 = build_game(card_config.clone(), tree_config.clone());
+    let mut game2 = build_game(card_config.clone(), tree_config.clone());
+
+    assert!(compute_matchup_ev(&game1, &game2).is_err());
+
+    game1.allocate_memory(false);
+    game2.allocate_memory(false);
+    let exploitability = solve(&mut game1, 100, 0.0, false).exploitability;
+    finalize(&mut game2);
+
+    // self-play agrees with the expected values of the game
+    let same = compute_matchup_ev(&game1, &game1).unwrap();
+    let current_ev = compute_current_ev(&game1);
+    assert!((same[0] - (current_ev[0] + 30.0)).abs() < 1e-3);
+    assert!((same[1] - (current_ev[1] + 30.0)).abs() < 1e-3);
+    assert!((same[0] + same[1] - 60.0).abs() < 1e-3);
+
+    // the solved strategy does not lose much against the untrained one
+    let oop_solved = compute_matchup_ev(&game1, &game2).unwrap();
+    let ip_solved = compute_matchup_ev(&game2, &game1).unwrap();
+    assert!((oop_solved[0] + oop_solved[1] - 60.0).abs() < 1e-3);
+    assert!(oop_solved[0] >= same[0] - 2.0 * exploitability - 1e-3);
+    assert!(ip_solved[1] >= same[1] - 2.0 * exploitability - 1e-3);
+
+    // different trees
+    let tree_config = TreeConfig {
+        river_bet_sizes: [("100%", "").try_into().unwrap(), Default::default()],
+        ..tree_config
+    };
+    let mut game3 = build_game(card_config, tree_config);
+    game3.allocate_memory(false);
+    finalize(&mut game3);
+    assert!(compute_matchup_ev(&game1, &game3).is_err());
+}
+
+#[test]
+fn exploitability_certificate() {
+    let mut game = turn_spot_game();
+    assert!(compute_exploitability_certificate(&game).is_err());
+
+    solve(&mut game, 100, 0.0, false);
+    let certificate = compute_exploitability_certificate(&game).unwrap();
+    assert!((certificate.exploitability - compute_exploitability(&game)).abs() < 1e-4);
+    assert_eq!(certificate.best_response_ev, compute_mes_ev(&game));
+    for player in 0..2 {
+        let cfvalues = compute_best_response_cfvalues(&game, player);
+        assert_eq!(cfvalues, certificate.best_response_cfvalues[player]);
+    }
+    assert!(!certificate.best_response.is_empty());
+    assert!(certificate
+        .best_response
+        .windows(2)
+        .all(|w| w[0].node_index < w[1].node_index));
+    assert!(certificate.verify(&game).is_ok());
+
+    let mut forged = certificate.clone();
+    forged.exploitability *= 0.5;
+    assert!(forged.verify(&game).is_err());
+
+    let mut forged = certificate.clone();
+    forged.best_response_cfvalues[0][0] += 1.0;
+    assert!(forged.verify(&game).is_err());
+
+    let mut forged = certificate.clone();
+    for action in &mut forged.best_response[0].actions {
+        *action = (*action == 0) as u8;
+    }
+    assert!(forged.verify(&game).is_err());
+
+    let mut forged = certificate.clone();
+    forged.best_response.pop();
+    assert!(forged.verify(&game).is_err());
+
+    let mut other = turn_spot_game();
+    solve(&mut other, 10, 0.0, false);
+    assert!(certificate.verify(&other).is_err());
+}
+
+#[test]
+fn quantized_strategy() {
+    let mut game = turn_spot_game();
+    solve(&mut game, 100, 0.0, false);
+
+    let exploitability = compute_exploitability(&game);
+    let quantized = game.export_quantized_strategy().unwrap();
+    assert_eq!(compute_exploitability(&game), exploitability);
+
+    assert!(quantized.max_ev_loss >= 0.0);
+    assert!(quantized.max_ev_loss < 0.01 * 60.0);
+    assert_eq!(
+        quantized.max_ev_loss,
+        quantized.ev_loss[0].max(quantized.ev_loss[1])
+    );
+
+    let root = quantized.strategy(0).unwrap();
+    let original = game.strategy();
+    assert_eq!(root.len(), original.len());
+    for (q, o) in root.iter().zip(&original) {
+        assert!((q - o).abs() < 0.01);
+    }
+
+    assert!(quantized.memory_usage() < game.memory_usage().0);
+}
+
+#[test]
+fn aggregate_frequencies() {
+    // IP also bets
+    let (card_config, mut tree_config) = turn_spot_configs();
+    tree_config.turn_bet_sizes[1] = ("50%", "").try_into().unwrap();
+    tree_config.river_bet_sizes[1] = ("50%", "").try_into().unwrap();
+    let mut game = build_game(card_config, tree_config);
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    // the first turn decision of OOP is the root
+    let root = game
+        .aggregate_frequencies(0, DecisionClass::FirstDecision(BoardState::Turn))
+        .unwrap();
+    assert_eq!(root.num_nodes, 1);
+
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0);
+    let strategy = game.strategy();
+    let num_hands = weights.len();
+    let total = weights.iter().map(|&w| w as f64).sum::<f64>();
+    let check = (0..num_hands)
+        .map(|i| strategy[i] as f64 * weights[i] as f64)
+        .sum::<f64>();
+    assert!((root.check - check / total).abs() < 1e-6);
+    assert!((root.check + root.bet - 1.0).abs() < 1e-6);
+    assert_eq!(root.fold + root.call + root.raise, 0.0);
+
+    // facing a river bet: all river cards are aggregated
+    let facing_bet = game
+        .aggregate_frequencies(1, DecisionClass::FacingBet(BoardState::River))
+        .unwrap();
+    assert!(facing_bet.num_nodes >= 48);
+    let sum = facing_bet.fold + facing_bet.call + facing_bet.raise;
+    assert!((sum - 1.0).abs() < 1e-6);
+    assert_eq!(facing_bet.check + facing_bet.bet, 0.0);
+
+    // the current node is restored
+    game.play(0);
+    let history = game.history().to_vec();
+    game.aggregate_frequencies(1, DecisionClass::Unopened(BoardState::River))
+        .unwrap();
+    assert_eq!(game.history(), history);
+
+    assert!(game
+        .aggregate_frequencies(0, DecisionClass::Unopened(BoardState::Flop))
+        .is_err());
+}
+
+#[test]
+fn generate_report() {
+    let (mut card_config, tree_config) = turn_spot_configs();
+    card_config.player_names = ["BB".to_string(), "BTN".to_string()];
+    let mut game = build_game(card_config, tree_config);
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    let spec = ReportSpec {
+        aggregates: vec![
+            (0, DecisionClass::FirstDecision(BoardState::Turn)),
+            (0, DecisionClass::FirstDecision(BoardState::Flop)),
+        ],
+        ..Default::default()
+    };
+    let report = game.generate_report(&spec).unwrap();
+
+    assert_eq!(report.board, "2c6dTh3s");
+    assert_eq!(report.player_names, ["BB", "BTN"]);
+    assert_eq!(report.root_player, 0);
+    assert_eq!(report.root_actions, vec!["Check", "Bet 30"]);
+
+    // root grid: TT+ (5 pairs), AKo, AQs, AKs
+    let hands = report
+        .root_grid
+        .iter()
+        .map(|cell| cell.hand.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        hands,
+        vec!["AA", "AKs", "AQs", "AKo", "KK", "QQ", "JJ", "TT"]
+    );
+    assert_eq!(report.root_grid[0].combos, 6.0);
+    assert_eq!(report.root_grid[7].combos, 3.0);
+    for cell in &report.root_grid {
+        let sum = cell.frequencies.iter().sum::<f64>();
+        assert!((sum - 1.0).abs() < 1e-4);
+    }
+
+    // the aggregate of the flop is omitted
+    assert_eq!(report.aggregates.len(), 1);
+    assert_eq!(report.aggregates[0].street, "turn");
+    let frequencies = report.aggregates[0].frequencies;
+    let grid_check = report
+        .root_grid
+        .iter()
+        .map(|cell| cell.frequencies[0] * cell.combos)
+        .sum::<f64>()
+        / report.root_grid.iter().map(|cell| cell.combos).sum::<f64>();
+    assert!((frequencies.check - grid_check).abs() < 0.05);
+
+    // runouts: 52 - 4 board cards
+    assert_eq!(report.runouts.len(), 48);
+    for runout in &report.runouts {
+        assert!((runout.equity[0] + runout.equity[1] - 1.0).abs() < 1e-4);
+    }
+
+    // buckets: TT is a set, AA-JJ are overpairs, and AK/AQ are high cards
+    let buckets = report
+        .buckets
+        .iter()
+        .filter(|bucket| bucket.player == 0)
+        .map(|bucket| (bucket.category.as_str(), bucket.combos))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        buckets,
+        vec![
+            ("high_card", 20.0),
+            ("one_pair", 24.0),
+            ("three_of_a_kind", 3.0)
+        ]
+    );
+    assert!(report
+        .buckets
+        .iter()
+        .all(|b| b.player == 0 || b.frequencies.is_empty()));
+
+    let json = report.to_json();
+    assert!(json.starts_with("{\"aggregates\":[{"));
+    assert!(json.contains("\"board\":\"2c6dTh3s\""));
+    validate_json("study_report", &JsonValue::parse(&json).unwrap()).unwrap();
+}
+
+#[test]
+fn flop_library() {
+    let solve_flop = |flop: &str| {
+        let card_config = CardConfig {
+            range: ["AA,KK,AKs".parse().unwrap(), "QQ,JJ,AQs".parse().unwrap()],
+            flop: flop_from_str(flop).unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 100,
+            effective_stack: 100,
+            flop_bet_sizes: [
+                ("50%", "").try_into().unwrap(),
+                ("50%", "").try_into().unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 20, 0.0, false);
+        game.export_strategy_table().unwrap()
+    };
+
+    let tables = vec![
+        solve_flop("Th6d2c"),
+        solve_flop("Tc7h2d"),
+        solve_flop("Th6h2h"),
+    ];
+    let raw_size = tables.iter().map(|t| t.memory_usage()).sum::<u64>();
+    assert_eq!(
+        FlopTexture::new(&tables[0].flop),
+        FlopTexture::new(&tables[1].flop)
+    );
+
+    for tolerance in [0, 8] {
+        let library = FlopLibrary::build(tables.clone(), tolerance).unwrap();
+        assert_eq!(library.len(), 3);
+        assert_eq!(library.references.len(), 2);
+        assert!(library.memory_usage() < raw_size);
+
+        // the reference is stored as is
+        let mut flop = tables[0].flop;
+        flop.reverse();
+        assert_eq!(library.get(flop).unwrap(), tables[0]);
+
+        // the delta-encoded flop is within the tolerance
+        let decoded = library.get(tables[1].flop).unwrap();
+        assert_eq!(decoded.private_cards, tables[1].private_cards);
+        assert_eq!(decoded.nodes.len(), tables[1].nodes.len());
+        for (x, y) in decoded.nodes.iter().zip(&tables[1].nodes) {
+            assert_eq!(x.line, y.line);
+            for (&a, &b) in x.data.iter().zip(&y.data) {
+                assert!(a.abs_diff(b) <= tolerance);
+            }
+        }
+    }
+
+    assert!(FlopLibrary::build(vec![tables[0].clone(), tables[0].clone()], 0).is_err());
+    assert!(FlopLibrary::build(tables, 0)
+        .unwrap()
+        .get(flop_from_str("AhKhQh").unwrap())
+        .is_none());
+}
+
+#[test]
+fn isomorphism_monotone() {
+    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
+    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QhJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 100,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let mut check = |history: &[usize],
+                     expected_turn_swap: Option<u8>,
+                     expected_river_swap: Option<(u8, u8)>| {
+        game.apply_history(history);
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0);
+        let ev = game.expected_values(0);
+        weights.iter().zip(ev.iter()).for_each(|(&w, &v)| {
+            assert!(!(w > 0.0 && v == 50.0));
+        });
+        assert_eq!(game.turn_swap, expected_turn_swap);
+        assert_eq!(game.river_swap, expected_river_swap);
+    };
+
+    check(&[0, 0, 4], None, None);
+    check(&[0, 0, 5], Some(1), None);
+    check(&[0, 0, 6], None, None);
+    check(&[0, 0, 7], Some(3), None);
+
+    check(&[0, 0, 4, 0, 0, 8], None, None);
+    check(&[0, 0, 4, 0, 0, 9], None, None);
+    check(&[0, 0, 4, 0, 0, 10], None, None);
+    check(&[0, 0, 4, 0, 0, 11], None, Some((0, 3)));
+
+    check(&[0, 0, 5, 0, 0, 8], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 9], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 10], Some(1), None);
+    check(&[0, 0, 5, 0, 0, 11], Some(1), Some((1, 3)));
+
+    check(&[0, 0, 6, 0, 0, 8], None, None);
+    check(&[0, 0, 6, 0, 0, 9], None, Some((2, 1)));
+    check(&[0, 0, 6, 0, 0, 10], None, None);
+    check(&[0, 0, 6, 0, 0, 11], None, Some((2, 3)));
+
+    check(&[0, 0, 7, 0, 0, 8], Some(3), Some((3, 1)));
+    check(&[0, 0, 7, 0, 0, 9], Some(3), None);
+    check(&[0, 0, 7, 0, 0, 10], Some(3), None);
+    check(&[0, 0, 7, 0, 0, 11], Some(3), None);
+}
+
+#[test]
+fn suit_symmetries() {
+    let card_config = CardConfig {
+        range: ["AA,AKs,KQo".parse().unwrap(), "QQ-JJ".parse().unwrap()],
+        flop: flop_from_str("Td9d6d").unwrap(),
+        turn: card_from_str("2d").unwrap(),
+        river: card_from_str("3h").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 40,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+
+    // the board fixes diamonds and hearts, so only clubs and spades can be swapped
+    assert_eq!(game.suit_symmetries(), vec![[3, 1, 2, 0]]);
+
+    let classes = game.private_hand_classes(0);
+    assert_eq!(classes.iter().map(Vec::len).sum::<usize>(), 6 + 4 + 12);
+    assert!(classes.len() < game.num_private_hands(0));
+    let cards = game.private_cards(0);
+    let ac_as = cards.binary_search(&(card_from_str("Ac").unwrap(), card_from_str("As").unwrap()));
+    assert!(classes.contains(&vec![ac_as.unwrap()]));
+
+    game.allocate_memory(false);
+    solve(&mut game, 200, 0.0, false);
+    let strategy = game.strategy();
+    let num_hands = game.num_private_hands(0);
+    for class in &classes {
+        for &hand in class {
+            for action in 0..game.available_actions().len() {
+                let x = strategy[action * num_hands + hand];
+                let y = strategy[action * num_hands + class[0]];
+                assert!((x - y).abs() < 1e-3);
+            }
+        }
+    }
+
+    // an asymmetric range breaks the symmetry
+    let mut card_config = card_config;
+    card_config.range[1] = "QQ-JJ,QcJs".parse().unwrap();
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.suit_symmetries().is_empty());
+    let num_hands = game.num_private_hands(1);
+    assert_eq!(game.private_hand_classes(1).len(), num_hands);
+}
+
+#[test]
+fn suit_reduction() {
+    let card_config = CardConfig {
+        range: ["AA,AKs,KQo".parse().unwrap(), "QQ-JJ,AQs".parse().unwrap()],
+        flop: flop_from_str("Td9d6d").unwrap(),
+        turn: card_from_str("2d").unwrap(),
+        river: card_from_str("3h").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 40,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    let mut card_config_full = card_config;
+    card_config_full.disable_isomorphism = true;
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game_full = PostFlopGame::with_config(card_config_full, action_tree).unwrap();
+
+    assert!(game.is_suit_reduced());
+    assert!(!game_full.is_suit_reduced());
+    assert_eq!(game.num_private_hands(0), game_full.num_private_hands(0));
+
+    game.allocate_memory(false);
+    game_full.allocate_memory(false);
+    assert!(game.memory_usage().0 < game_full.memory_usage().0);
+
+    // a strategy differing between symmetric hands cannot be locked
+    let num_hands = game.num_private_hands(0);
+    let mut locking = vec![0.0; game.available_actions().len() * num_hands];
+    locking[..num_hands].fill(1.0);
+    assert!(game.try_lock_current_strategy(&locking).is_ok());
+    game.unlock_current_strategy();
+    let class = game
+        .private_hand_classes(0)
+        .into_iter()
+        .find(|class| class.len() > 1)
+        .unwrap();
+    locking[class[0]] = 0.0;
+    locking[num_hands + class[0]] = 1.0;
+    assert!(game.try_lock_current_strategy(&locking).is_err());
+    assert!(game.current_locking_strategy().is_none());
+
+    let exploitability = solve(&mut game, 1000, 0.001, false).exploitability;
+    let exploitability_full = solve(&mut game_full, 1000, 0.001, false).exploitability;
+    assert!(exploitability <= 0.001);
+    assert!(exploitability_full <= 0.001);
+
+    // the results are expanded to every private hand
+    game.cache_normalized_weights();
+    game_full.cache_normalized_weights();
+    let strategy = game.strategy();
+    assert_eq!(strategy.len(), game.available_actions().len() * num_hands);
+    for class in game.private_hand_classes(0) {
+        for action in 0..game.available_actions().len() {
+            let x = strategy[action * num_hands + class[0]];
+            assert!(class
+                .iter()
+                .all(|&hand| strategy[action * num_hands + hand] == x));
+        }
+    }
+
+    for player in 0..2 {
+        let weights = game.normalized_weights(player);
+        assert_eq!(weights, game_full.normalized_weights(player));
+
+        for (x, y) in game.equity(player).iter().zip(game_full.equity(player)) {
+            assert!((x - y).abs() < 1e-5);
+        }
+
+        let ev = compute_average(&game.expected_values(player), weights);
+        let ev_full = compute_average(&game_full.expected_values(player), weights);
+        assert!((ev - ev_full).abs() < 0.01);
+    }
+}
+
+#[test]
+fn node_locking() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.play(1); // all-in
+    game.lock_current_strategy(&[0.25, 0.75]); // 25% fold, 75% call
+    game.back_to_root();
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 27.5).abs() < 5e-2);
+    assert!((ev_ip[0] - 6.25).abs() < 1e-2);
+
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[0] - 1.0).abs() < 1e-3); // QQ check
+    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // QQ bet
+    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+
+    game.allocate_memory(false);
+    game.play(1); // all-in
+    game.lock_current_strategy(&[0.5, 0.5]); // 50% fold, 50% call
+    game.back_to_root();
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 5.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 25.0).abs() < 5e-2);
+    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[0] - 0.0).abs() < 1e-3); // QQ check
+    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[2] - 1.0).abs() < 1e-3); // QQ bet
+    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+}
+
+#[test]
+fn node_locking_by_history() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
@@ -866,385 +2315,4607 @@ fn node_locking() {
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.lock_strategy(&[1], 1, &[0.25, 0.75]).is_err());
+
+    game.allocate_memory(false);
+    assert!(game.lock_strategy(&[1], 0, &[0.25, 0.75]).is_err()); // wrong player
+    assert!(game.lock_strategy(&[1], 1, &[0.25]).is_err()); // wrong length
+    assert!(game.lock_strategy(&[1, 1], 1, &[0.25, 0.75]).is_err()); // terminal node
+    assert!(game.current_locking_strategy().is_none());
+
+    game.lock_strategy(&[1], 1, &[0.5, 0.5]).unwrap();
+    game.unlock_strategy(&[1]).unwrap();
+    game.lock_strategy(&[1], 1, &[0.25, 0.75]).unwrap(); // 25% fold, 75% call
+    assert!(game.history().is_empty());
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+    assert!(game.unlock_strategy(&[1]).is_err());
+
+    let ev_oop = game.expected_values(0);
+    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 27.5).abs() < 5e-2);
+
+    game.play(1);
+    let strategy_ip = game.strategy();
+    assert!((strategy_ip[0] - 0.25).abs() < 1e-6);
+    assert!((strategy_ip[1] - 0.75).abs() < 1e-6);
+}
+
+#[test]
+fn node_locking_partial() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh,JsJh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 10,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.lock_current_strategy(&[0.8, 0.0, 0.0, 0.2, 0.0, 0.0]); // JJ -> 80% check, 20% all-in
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[2] - 15.0).abs() < 5e-2);
+    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[0] - 0.8).abs() < 1e-3); // JJ check
+    assert!((strategy_oop[1] - 0.7).abs() < 1e-3); // QQ check
+    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[3] - 0.2).abs() < 1e-3); // JJ bet
+    assert!((strategy_oop[4] - 0.3).abs() < 1e-3); // QQ bet
+    assert!((strategy_oop[5] - 1.0).abs() < 1e-3); // AA bet
+}
+
+#[test]
+fn node_locking_isomorphism() {
+    let card_config = CardConfig {
+        range: ["AKs".parse().unwrap(), "AKs".parse().unwrap()],
+        flop: flop_from_str("2c3c4c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 10,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.apply_history(&[0, 0, 15, 0, 0, 14]); // Turn: Spades, River: Hearts
+    game.lock_current_strategy(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]); // AhKh -> check
+
+    finalize(&mut game);
+
+    game.apply_history(&[0, 0, 13, 0, 0, 14]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5]
+    );
+
+    game.apply_history(&[0, 0, 13, 0, 0, 15]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0]
+    );
+
+    game.apply_history(&[0, 0, 14, 0, 0, 13]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5, 0.5]
+    );
+
+    game.apply_history(&[0, 0, 14, 0, 0, 15]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0]
+    );
+
+    game.apply_history(&[0, 0, 15, 0, 0, 13]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5, 0.5]
+    );
+
+    game.apply_history(&[0, 0, 15, 0, 0, 14]);
+    assert_eq!(
+        game.strategy(),
+        vec![0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5]
+    );
+}
+
+#[test]
+fn set_bunching_effect() {
+    let flop = flop_from_str("Td9d6h").unwrap();
+    let card_config = CardConfig {
+        flop,
+        range: [Range::ones(); 2],
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let co_range = "33:0.59,22:0.635,A8o:0.265,A7o-A6o,A5o:0.445,A4o-A2o,K2s,K9o:0.905,K8o-K2o,Q4s-Q2s,Q9o-Q2o,J6s-J2s,J9o:0.88,J8o-J2o,T7s:0.405,T6s-T2s,T9o:0.96,T8o-T2o,96s-92s,92o+,86s:0.57,85s-82s,82o+,76s:0.37,75s-72s,72o+,65s:0.475,64s-62s,62o+,54s:0.68,53s-52s,52o+,42+,32";
+    let sb_range = "66:0.46,55:0.821,44:0.92,33:0.93,22:0.925,A6s:0.73,A3s:0.47,A2s,ATo:0.105,A9o-A2o,K8s:0.795,K7s,K6s:0.85,K5s:0.965,K4s-K2s,KJo:0.085,KTo:0.645,K9o-K2o,Q8s-Q2s,QJo:0.765,QTo-Q2o,J8s-J2s,J2o+,T8s:0.69,T7s-T2s,T2o+,98s:0.905,97s-92s,92o+,87s:0.78,86s-82s,82o+,76s:0.77,75s-72s,72o+,65s:0.845,64s-62s,62o+,54s:0.735,53s-52s,52o+,42+,32";
+
+    let mut bunching_data = BunchingData::new(
+        &[co_range.parse().unwrap(), sb_range.parse().unwrap()],
+        flop,
+    )
+    .unwrap();
+
+    bunching_data.process(false);
+    game.set_bunching_effect(&bunching_data).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let current_ev = compute_current_ev(&game);
+    assert!((current_ev[0] - 7.5).abs() < 1e-4);
+    assert!((current_ev[1] - -7.5).abs() < 1e-4);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
+    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    assert!((root_equity_oop - 0.5).abs() < 1e-5);
+    assert!((root_equity_ip - 0.5).abs() < 1e-5);
+    assert!((root_ev_oop - 37.5).abs() < 1e-4);
+    assert!((root_ev_ip - 22.5).abs() < 1e-4);
+}
+
+#[test]
+fn set_bunching_fold_ranges() {
+    let card_config = CardConfig {
+        flop: flop_from_str("Td9d6h").unwrap(),
+        range: [Range::ones(); 2],
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.set_bunching_fold_ranges(&[]).is_err());
+
+    let fold_range = "22+,A2+,K2+,Q2+,J2+,T2+".parse().unwrap();
+    game.set_bunching_fold_ranges(&[fold_range]).unwrap();
+    assert!(game.memory_usage_bunching() > 0);
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let current_ev = compute_current_ev(&game);
+    assert!(current_ev[0].abs() < 1e-4);
+    assert!(current_ev[1].abs() < 1e-4);
+}
+
+#[test]
+fn set_bunching_effect_always_win() {
+    let flop = flop_from_str("AcAdKh").unwrap();
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop,
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let co_range = "33:0.59,22:0.635,A8o:0.265,A7o-A6o,A5o:0.445,A4o-A2o,K2s,K9o:0.905,K8o-K2o,Q4s-Q2s,Q9o-Q2o,J6s-J2s,J9o:0.88,J8o-J2o,T7s:0.405,T6s-T2s,T9o:0.96,T8o-T2o,96s-92s,92o+,86s:0.57,85s-82s,82o+,76s:0.37,75s-72s,72o+,65s:0.475,64s-62s,62o+,54s:0.68,53s-52s,52o+,42+,32";
+    let sb_range = "66:0.46,55:0.821,44:0.92,33:0.93,22:0.925,A6s:0.73,A3s:0.47,A2s,ATo:0.105,A9o-A2o,K8s:0.795,K7s,K6s:0.85,K5s:0.965,K4s-K2s,KJo:0.085,KTo:0.645,K9o-K2o,Q8s-Q2s,QJo:0.765,QTo-Q2o,J8s-J2s,J2o+,T8s:0.69,T7s-T2s,T2o+,98s:0.905,97s-92s,92o+,87s:0.78,86s-82s,82o+,76s:0.77,75s-72s,72o+,65s:0.845,64s-62s,62o+,54s:0.735,53s-52s,52o+,42+,32";
+
+    let mut bunching_data = BunchingData::new(
+        &[co_range.parse().unwrap(), sb_range.parse().unwrap()],
+        flop,
+    )
+    .unwrap();
+
+    bunching_data.process(false);
+    game.set_bunching_effect(&bunching_data).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let current_ev = compute_current_ev(&game);
+    assert!((current_ev[0] - 30.0).abs() < 1e-4);
+    assert!((current_ev[1] - -30.0).abs() < 1e-4);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 1.0).abs() < 1e-5);
+    assert!((equity_ip - 0.0).abs() < 1e-5);
+    assert!((ev_oop - 60.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    game.play(usize::MAX);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    game.play(usize::MAX);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_terminal_node());
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 1.0).abs() < 1e-5);
+    assert!((equity_ip - 0.0).abs() < 1e-5);
+    assert!((ev_oop - 60.0).abs() < 1e-4);
+    assert!((ev_ip - 0.0).abs() < 1e-4);
+}
+
+#[test]
+#[ignore]
+fn solve_pio_preset_normal() {
+    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
+    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QsJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 180,
+        effective_stack: 910,
+        flop_bet_sizes: [
+            ("52%", "45%").try_into().unwrap(),
+            ("52%", "45%").try_into().unwrap(),
+        ],
+        turn_bet_sizes: [
+            ("55%", "45%").try_into().unwrap(),
+            ("55%", "45%").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("70%", "45%").try_into().unwrap(),
+            ("70%", "45%").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    println!(
+        "memory usage: {:.2}GB",
+        game.memory_usage().0 as f64 / (1024.0 * 1024.0 * 1024.0)
+    );
+    game.allocate_memory(false);
+
+    solve(&mut game, 1000, 180.0 * 0.001, true);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
+    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    // verified by PioSOLVER Free
+    assert!((root_equity_oop - 0.55347).abs() < 1e-5);
+    assert!((root_equity_ip - 0.44653).abs() < 1e-5);
+    assert!((root_ev_oop - 105.11).abs() < 0.2);
+    assert!((root_ev_ip - 74.89).abs() < 0.2);
+}
+
+#[test]
+#[ignore]
+fn solve_pio_preset_raked() {
+    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
+    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QsJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 180,
+        effective_stack: 910,
+        rake_rate: 0.05,
+        rake_cap: 30.0,
+        flop_bet_sizes: [
+            ("52%", "45%").try_into().unwrap(),
+            ("52%", "45%").try_into().unwrap(),
+        ],
+        turn_bet_sizes: [
+            ("55%", "45%").try_into().unwrap(),
+            ("55%", "45%").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("70%", "45%").try_into().unwrap(),
+            ("70%", "45%").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    println!(
+        "memory usage: {:.2}GB",
+        game.memory_usage().0 as f64 / (1024.0 * 1024.0 * 1024.0)
+    );
+    game.allocate_memory(false);
+
+    solve(&mut game, 1000, 180.0 * 0.001, true);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+    // verified by PioSOLVER Free (but not theoretically guaranteed to be the same)
+    assert!((root_ev_oop - 95.57).abs() < 0.2);
+    assert!((root_ev_ip - 66.98).abs() < 0.2);
+}
+
+#[test]
+fn player_names() {
+    let mut card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+    assert_eq!(card_config.player_name(0), "OOP");
+    assert_eq!(card_config.player_name(1), "IP");
+
+    card_config.player_names = ["BB".to_string(), String::new()];
+    assert_eq!(card_config.player_name(0), "BB");
+    assert_eq!(card_config.player_name(1), "IP");
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+
+    card_config.player_names = ["BB".to_string(), "BB".to_string()];
+    assert!(PostFlopGame::with_config(card_config, action_tree).is_err());
+}
+
+#[test]
+fn perspective() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ".parse().unwrap(), "TT+,AK".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        player_names: ["BB".to_string(), "BTN".to_string()],
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+
+    game.play(1);
+    game.cache_normalized_weights();
+
+    let oop = game.perspective(0);
+    let ip = game.perspective(1);
+    assert_eq!(ip.hero_seat(), 1);
+    assert_eq!((ip.seat(0), ip.seat(1)), (1, 0));
+    assert_eq!((ip.relative(0), ip.relative(1)), (1, 0));
+    assert_eq!((oop.seat(0), oop.relative(1)), (0, 1));
+    assert_eq!(ip.player_name(0), "BTN");
+    assert_eq!(ip.player_name(1), "BB");
+
+    // IP is to act after the bet of OOP
+    assert!(ip.is_hero_to_act());
+    assert!(!oop.is_hero_to_act());
+    assert_eq!(ip.current_player(), 0);
+
+    assert_eq!(ip.private_cards(0), game.private_cards(1));
+    assert_eq!(ip.private_cards(1), game.private_cards(0));
+    assert_eq!(ip.normalized_weights(0), game.normalized_weights(1));
+    assert_eq!(ip.expected_values(0), game.expected_values(1));
+    assert_eq!(ip.expected_values(1), game.expected_values(0));
+    assert_eq!(ip.equity(1), game.equity(0));
+
+    let [hero_bet, villain_bet] = ip.total_bet_amount();
+    assert_eq!([villain_bet, hero_bet], game.total_bet_amount());
+    assert_eq!(ip.orient(game.remaining_stacks()), ip.remaining_stacks());
+    assert_eq!(oop.remaining_stacks(), game.remaining_stacks());
+}
+
+#[test]
+fn dead_money() {
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let mut tree_config = TreeConfig {
+        starting_pot: 60,
+        dead_money: 70,
+        effective_stack: 970,
+        ..Default::default()
+    };
+    assert!(ActionTree::new(tree_config.clone()).is_err());
+
+    tree_config.dead_money = 20;
+    assert!((tree_config.contribution() - 20.0).abs() < 1e-9);
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    // the dead money does not change the pot-share EV
+    game.cache_normalized_weights();
+    let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+    assert!((ev_oop - 60.0).abs() < 1e-4);
+
+    let report = game.ev_report(0, 2.0);
+    assert!((report.committed - 20.0).abs() < 1e-9);
+    assert!((report.net_ev - 40.0).abs() < 1e-4);
+    assert!((report.starting_stack_bb - 495.0).abs() < 1e-9);
+}
+
+#[test]
+fn net_expected_values() {
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3d").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    // nothing has been put into the pot on the river yet
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0).to_vec();
+    let ev = compute_average(&game.expected_values(0), &weights);
+    let net_ev = compute_average(&game.net_expected_values(0), &weights);
+    assert!((ev - net_ev).abs() < 1e-4);
+    assert!((game.ev_report(0, 2.0).street_net_ev - net_ev as f64).abs() < 1e-4);
+
+    // bet 30 and the opponent folds: the player wins the starting pot of the street
+    let bet = game
+        .available_actions()
+        .iter()
+        .position(|a| matches!(a, Action::Bet(_)))
+        .unwrap();
+    game.play(bet);
+    game.play(0);
+    assert!(game.is_terminal_node());
+
+    game.cache_normalized_weights();
+    let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+    let net_oop = compute_average(&game.net_expected_values(0), game.normalized_weights(0));
+    let net_ip = compute_average(&game.net_expected_values(1), game.normalized_weights(1));
+    assert!((ev_oop - 90.0).abs() < 1e-4);
+    assert!((net_oop - 60.0).abs() < 1e-4);
+    assert!(net_ip.abs() < 1e-4);
+}
+
+#[test]
+fn external_strategy_exploitability_upper_bound() {
+    fn collect(game: &mut PostFlopGame, nodes: &mut Vec<ExternalNodeStrategy>) {
+        if game.is_terminal_node() {
+            return;
+        }
+        let history = game.history().to_vec();
+        nodes.push(ExternalNodeStrategy {
+            history: history.clone(),
+            strategy: game.strategy(),
+        });
+        for action in 0..game.available_actions().len() {
+            game.play(action);
+            collect(game, nodes);
+            game.apply_history(&history);
+        }
+    }
+
+    let card_config = CardConfig {
+        range: [
+            "TT+,AKo,AQs+".parse().unwrap(),
+            "AA,KK,QQ,AJs".parse().unwrap(),
+        ],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        turn: card_from_str("3s").unwrap(),
+        river: card_from_str("Jc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [
+            ("50%, a", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut solved = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    solved.allocate_memory(false);
+    let exploitability = solve(&mut solved, 200, 0.0, false).exploitability;
+
+    let mut nodes = Vec::new();
+    collect(&mut solved, &mut nodes);
+    assert!(nodes.len() > 1);
+
+    // full strategy: the exploitability of the strategy itself
+    let mut fresh = PostFlopGame::with_config(
+        solved.card_config().clone(),
+        ActionTree::new(solved.tree_config().clone()).unwrap(),
+    )
+    .unwrap();
+    fresh.allocate_memory(false);
+    let full = fresh
+        .external_strategy_exploitability_upper_bound(&nodes, 0, 0.0)
+        .unwrap();
+    assert!((full.upper_bound - exploitability).abs() < 0.05 * 60.0 / 100.0);
+    assert_eq!(full.num_iterations, 0);
+    assert!(full.convergence_gap.abs() < 1e-4);
+
+    // partial strategy: only the root is supplied and the rest is solved; the best response is
+    // not constrained by the lock
+    let mut fresh = PostFlopGame::with_config(
+        solved.card_config().clone(),
+        ActionTree::new(solved.tree_config().clone()).unwrap(),
+    )
+    .unwrap();
+    fresh.allocate_memory(false);
+    let partial = fresh
+        .external_strategy_exploitability_upper_bound(&nodes[..1], 200, 0.0)
+        .unwrap();
+    assert!(partial.upper_bound.is_finite());
+    assert!(partial.upper_bound >= compute_exploitability(&fresh) - 1e-3);
+    assert_eq!(partial.num_iterations, 200);
+    assert_eq!(partial.convergence_gap, compute_exploitability(&fresh));
+    assert!(fresh.current_locking_strategy().is_some());
+
+    // invalid input
+    let mut fresh = PostFlopGame::with_config(
+        solved.card_config().clone(),
+        ActionTree::new(solved.tree_config().clone()).unwrap(),
+    )
+    .unwrap();
+    fresh.allocate_memory(false);
+    let invalid = ExternalNodeStrategy {
+        history: vec![100],
+        strategy: Vec::new(),
+    };
+    assert!(fresh.lock_external_strategy(&[invalid]).is_err());
+    let invalid = ExternalNodeStrategy {
+        history: Vec::new(),
+        strategy: vec![1.0],
+    };
+    assert!(fresh.lock_external_strategy(&[invalid]).is_err());
+    assert!(fresh.current_locking_strategy().is_none());
+}
+
+#[test]
+fn partial_strategy_import() {
+    let card_config = CardConfig {
+        range: [
+            "TT+,AKo,AQs+".parse().unwrap(),
+            "AA,KK,QQ,AJs".parse().unwrap(),
+        ],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        turn: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 200,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut solved = new_game();
+    assert!(solved.export_external_strategy(BoardState::Turn).is_err());
+    solve(&mut solved, 100, 0.0, false);
+
+    // fix the turn strategy and solve the river again
+    let nodes = solved.export_external_strategy(BoardState::Turn).unwrap();
+    assert!(nodes.iter().all(|node| node.history.len() <= 3));
+
+    let mut game = new_game();
+    game.lock_external_strategy(&nodes).unwrap();
+    solve(&mut game, 100, 0.0, false);
+
+    for node in &nodes {
+        game.apply_history(&node.history);
+        let strategy = game.strategy();
+        assert!(strategy
+            .iter()
+            .zip(&node.strategy)
+            .all(|(a, b)| (a - b).abs() < 1e-3));
+    }
+
+    game.apply_history(&[0, 0, 30]);
+    assert!(game.current_locking_strategy().is_none());
+
+    // import from PioSolver
+    let mut tree = ActionTree::new(tree_config.clone()).unwrap();
+    let row = |value: f32| vec![value.to_string(); PIO_NUM_HANDS].join(" ");
+    let text = format!("{}\n{}\n", row(0.25), row(0.75));
+    let pio_root = read_pio_node_strategy(&mut tree, "r:0", &text).unwrap();
+    let pio_river = read_pio_node_strategy(&mut tree, "r:0:c:c:Kh", &text).unwrap();
+
+    let mut game = new_game();
+    let imported = game.import_pio_strategy(&[pio_root, pio_river]).unwrap();
+    assert_eq!(imported[0].history, Vec::<usize>::new());
+    assert_eq!(
+        imported[1].history[2],
+        card_from_str("Kh").unwrap() as usize
+    );
+    assert!(imported[0].strategy.iter().all(|&x| x == 0.25 || x == 0.75));
+    game.lock_external_strategy(&imported).unwrap();
+    assert!(game.current_locking_strategy().is_some());
+
+    let mut pio_invalid = read_pio_node_strategy(&mut tree, "r:0", &text).unwrap();
+    pio_invalid.actions[1] = Action::Bet(45);
+    assert!(game.import_pio_strategy(&[pio_invalid]).is_err());
+}
+
+#[test]
+fn apply_chance() {
+    let (card_config, tree_config) = turn_spot_configs();
+    let tree_config = TreeConfig {
+        turn_bet_sizes: Default::default(),
+        river_bet_sizes: Default::default(),
+        ..tree_config
+    };
+    let mut game = build_game(card_config, tree_config);
+    assert!(game.apply_chance("Jd").is_err());
+    game.allocate_memory(false);
+
+    assert_eq!(game.current_street(), BoardState::Turn);
+    assert!(game.apply_chance("Jd").is_err());
+
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    assert_eq!(game.current_street(), BoardState::Turn);
+    assert!(game.apply_chance("2c").is_err());
+    assert!(game.apply_chance("Xx").is_err());
+
+    game.apply_chance("Jd").unwrap();
+    assert_eq!(game.current_street(), BoardState::River);
+    assert_eq!(
+        game.current_board().last(),
+        Some(&card_from_str("Jd").unwrap())
+    );
+    assert_eq!(
+        game.history().last(),
+        Some(&(card_from_str("Jd").unwrap() as usize))
+    );
+}
+
+#[test]
+fn possible_chance_cards() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,JJ".parse().unwrap()],
+        flop: flop_from_str("2c3c4c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.possible_chance_cards().is_empty());
+
+    game.play(0);
+    game.play(0);
+    let cards = game.possible_chance_cards();
+    assert_eq!(cards.len(), 49);
+    assert!(!cards.contains(&card_from_str("2c").unwrap()));
+    assert_eq!(
+        cards.iter().map(|&c| 1u64 << c).sum::<u64>(),
+        game.possible_cards()
+    );
+
+    // the flop is monotone, so the non-club suits are isomorphic
+    let card = |s| card_from_str(s).unwrap();
+    assert_eq!(
+        game.chance_card_representative(card("Kc")),
+        Some(card("Kc"))
+    );
+    let kd = game.chance_card_representative(card("Kd")).unwrap();
+    assert_eq!(kd >> 2, card("Kd") >> 2);
+    assert_eq!(game.chance_card_representative(card("Kh")), Some(kd));
+    assert_eq!(game.chance_card_representative(card("Ks")), Some(kd));
+    assert_eq!(game.chance_card_representative(card("2c")), None);
+
+    // after an isomorphic turn, the representatives are in the actual suits
+    game.play(card("Ks") as usize);
+    game.play(0);
+    game.play(0);
+    assert!(!game.possible_chance_cards().contains(&card("Ks")));
+    let repr = game.chance_card_representative(card("Qs")).unwrap();
+    assert_eq!(repr, card("Qs"));
+}
+
+#[test]
+fn custom_evaluator() {
+    let card_config = CardConfig {
+        range: ["75".parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("2c3d4h").unwrap(),
+        turn: card_from_str("Ts").unwrap(),
+        river: card_from_str("Ks").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let equity = |evaluator: Option<Arc<dyn Evaluator>>| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        if let Some(evaluator) = evaluator {
+            game.set_evaluator(evaluator).unwrap();
+        }
+        game.allocate_memory(false);
+        assert!(game.set_evaluator(Arc::new(HighHandEvaluator)).is_err());
+        finalize(&mut game);
+        game.cache_normalized_weights();
+        compute_average(&game.equity(0), game.normalized_weights(0))
+    };
+
+    // seven-high loses to aces in the standard ranking but is the nuts in deuce-to-seven
+    assert!(equity(None) < 1e-6);
+    assert!((equity(Some(Arc::new(DeuceToSevenEvaluator))) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn split_pot() {
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let solve_game = |oop_range: &str, ip_range: &str, board: &str| {
+        let card_config = CardConfig {
+            range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+            flop: flop_from_str(&board[..6]).unwrap(),
+            turn: card_from_str(&board[6..8]).unwrap(),
+            river: card_from_str(&board[8..]).unwrap(),
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.set_evaluator(Arc::new(EightOrBetterEvaluator))
+            .unwrap();
+        assert!(game.is_split_pot());
+        game.allocate_memory(false);
+        finalize(&mut game);
+        game.cache_normalized_weights();
+        game
+    };
+
+    // A-4 makes the only low and kings win high: the pot is split
+    let game = solve_game("A4", "KK", "2c3d7hTsKs");
+    let weights = game.normalized_weights(0);
+    let equity = compute_average(&game.equity(0), weights);
+    let ev = compute_average(&game.expected_values(0), weights);
+    assert!((equity - 0.5).abs() < 1e-5);
+    assert!((ev - 30.0).abs() < 1e-3);
+
+    // no low is possible: the high hand scoops
+    let game = solve_game("AA", "QQ", "2c9dThJsKs");
+    let weights = game.normalized_weights(0);
+    let equity = compute_average(&game.equity(0), weights);
+    let ev = compute_average(&game.expected_values(0), weights);
+    assert!(equity < 1e-5);
+    assert!(ev.abs() < 1e-3);
+}
+
+#[test]
+fn second_board() {
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), "KK".parse().unwrap()],
+        flop: flop_from_str("2c3d4h").unwrap(),
+        turn: card_from_str("9s").unwrap(),
+        river: card_from_str("Js").unwrap(),
+        second_board: ["Kd", "8c", "6h", "5s", "2d"]
+            .map(|c| card_from_str(c).unwrap())
+            .to_vec(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    // aces win the first board and kings make a set on the second board
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    assert_eq!(game.private_cards(1).len(), 3);
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0);
+    let equity = compute_average(&game.equity(0), weights);
+    let ev = compute_average(&game.expected_values(0), weights);
+    assert!((equity - 0.5).abs() < 1e-5);
+    assert!((ev - 30.0).abs() < 1e-3);
+
+    // the second board must not share cards with the first board
+    let mut invalid_config = card_config.clone();
+    invalid_config.second_board[4] = card_from_str("2c").unwrap();
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    assert!(PostFlopGame::with_config(invalid_config, action_tree).is_err());
+
+    // the second board requires the game to start on the river
+    let card_config = CardConfig {
+        river: NOT_DEALT,
+        ..card_config
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        ..tree_config
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    assert!(PostFlopGame::with_config(card_config, action_tree).is_err());
+}
+
+#[test]
+fn allin_equity_adjudication() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,76s".parse().unwrap(),
+            "AA-TT,AK,AQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let solve_game = |allin_equity_adjudication: bool| {
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 100,
+            rake_rate: 0.05,
+            rake_cap: 5.0,
+            flop_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+            allin_equity_adjudication,
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.0, false);
+        game.cache_normalized_weights();
+        game
+    };
+
+    let mut dealt = solve_game(false);
+    let mut adjudicated = solve_game(true);
+    assert!(adjudicated.memory_usage().0 < dealt.memory_usage().0);
+
+    // the root EVs are unchanged
+    for player in 0..2 {
+        let weights = dealt.normalized_weights(player);
+        let ev_dealt = compute_average(&dealt.expected_values(player), weights);
+        let ev_adjudicated = compute_average(&adjudicated.expected_values(player), weights);
+        assert!((ev_dealt - ev_adjudicated).abs() < 1e-2);
+    }
+
+    // after the all-in call
+    for game in [&mut dealt, &mut adjudicated] {
+        game.apply_history(&[1, 1]);
+        game.cache_normalized_weights();
+        assert!(game.is_terminal_node());
+    }
+
+    let weights = dealt.normalized_weights(0);
+    let equity_dealt = compute_average(&dealt.equity(0), weights);
+    let equity_adjudicated = compute_average(&adjudicated.equity(0), weights);
+    assert!((equity_dealt - equity_adjudicated).abs() < 1e-5);
+
+    let ev_dealt = compute_average(&dealt.expected_values(0), weights);
+    let ev_adjudicated = compute_average(&adjudicated.expected_values(0), weights);
+    assert!((ev_dealt - ev_adjudicated).abs() < 1e-3);
+}
+
+#[test]
+fn allin_equity_cache() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,76s".parse().unwrap(),
+            "AA-TT,AK,AQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 100,
+        rake_rate: 0.05,
+        rake_cap: 5.0,
+        flop_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        allin_equity_adjudication: true,
+        ..Default::default()
+    };
+
+    let solve_game = |cache: bool| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.set_allin_equity_cache_enabled(cache);
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.0, false);
+        game.cache_normalized_weights();
+        game
+    };
+
+    let mut uncached = solve_game(false);
+    let mut cached = solve_game(true);
+    assert!(!uncached.is_allin_equity_cache_enabled());
+    assert!(cached.is_allin_equity_cache_enabled());
+    assert_eq!(uncached.allin_equity_cache_memory_usage(), 0);
+    let num_pairs = cached.private_cards(0).len() * cached.private_cards(1).len();
+    assert_eq!(
+        cached.allin_equity_cache_memory_usage(),
+        6 * num_pairs as u64
+    );
+
+    for player in 0..2 {
+        let ev_uncached = uncached.expected_values(player);
+        let ev_cached = cached.expected_values(player);
+        for (a, b) in ev_uncached.iter().zip(&ev_cached) {
+            assert!((a - b).abs() < 1e-3);
+        }
+    }
+
+    // the evaluation of the all-in call with arbitrary reach probabilities
+    for game in [&mut uncached, &mut cached] {
+        game.apply_history(&[1, 1]);
+        assert!(game.is_terminal_node());
+    }
+
+    for player in 0..2 {
+        let cfreach = (0..cached.num_private_hands(player ^ 1))
+            .map(|i| (i % 7) as f32 / 7.0)
+            .collect::<Vec<_>>();
+        let evaluate = |game: &PostFlopGame| {
+            let num_hands = game.num_private_hands(player);
+            let mut result = Vec::with_capacity(num_hands);
+            game.evaluate(result.spare_capacity_mut(), &game.node(), player, &cfreach);
+            unsafe { result.set_len(num_hands) };
+            result
+        };
+        let (expected, actual) = (evaluate(&uncached), evaluate(&cached));
+        for (a, b) in expected.iter().zip(&actual) {
+            assert!((a - b).abs() <= 1e-5 * a.abs().max(1e-3));
+        }
+    }
+
+    cached.set_allin_equity_cache_enabled(false);
+    assert_eq!(cached.allin_equity_cache_memory_usage(), 0);
+}
+
+#[test]
+fn allin_chop_fee() {
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), "KK".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let base_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 100,
+        rake_rate: 0.05,
+        rake_cap: 5.0,
+        flop_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        allin_equity_adjudication: true,
+        ..Default::default()
+    };
+
+    let invalid_configs = [
+        TreeConfig {
+            allin_chop_fee_rate: 0.1,
+            allin_chop_fee_cap: 10.0,
+            allin_equity_adjudication: false,
+            ..base_config.clone()
+        },
+        TreeConfig {
+            allin_chop_fee_rate: 1.5,
+            ..base_config.clone()
+        },
+        TreeConfig {
+            allin_chop_fee_cap: -1.0,
+            ..base_config.clone()
+        },
+    ];
+    for tree_config in invalid_configs {
+        assert!(ActionTree::new(tree_config).is_err());
+    }
+
+    let build_game = |allin_chop_fee_rate: f64| {
+        let tree_config = TreeConfig {
+            allin_chop_fee_rate,
+            allin_chop_fee_cap: 10.0,
+            ..base_config.clone()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        game.apply_history(&[1, 1]);
+        game.cache_normalized_weights();
+        assert!(game.is_terminal_node());
+        game
+    };
+
+    let no_fee = build_game(0.0);
+    let fee = build_game(0.1);
+    assert!(fee.is_raked());
+
+    // pot = 260, rake = min(260 * 0.05, 5) = 5, fee = min(260 * 0.1, 10) = 10
+    let ev = |game: &PostFlopGame, player: usize| {
+        compute_average(
+            &game.expected_values(player),
+            game.normalized_weights(player),
+        ) as f64
+    };
+    assert!((ev(&no_fee, 0) + ev(&no_fee, 1) - 255.0).abs() < 1e-3);
+    assert!((ev(&fee, 0) + ev(&fee, 1) - 245.0).abs() < 1e-3);
+
+    // each player pays the fee in proportion to their equity
+    for player in 0..2 {
+        let equity = compute_average(&fee.equity(player), fee.normalized_weights(player)) as f64;
+        assert!((ev(&fee, player) - 245.0 * equity).abs() < 1e-3);
+        assert!((ev(&no_fee, player) - 255.0 * equity).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn pot_geometry() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,JJ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    assert_eq!(game.pot(), 60);
+    assert_eq!(game.amount_to_call(), 0);
+    assert_eq!(game.remaining_stacks(), [970, 970]);
+    assert!((game.spr() - 970.0 / 60.0).abs() < 1e-9);
+
+    // OOP bets 30
+    game.play(1);
+    assert_eq!(game.pot(), 90);
+    assert_eq!(game.amount_to_call(), 30);
+    assert_eq!(game.remaining_stacks(), [940, 970]);
+    assert!((game.spr() - 940.0 / 120.0).abs() < 1e-9);
+
+    // IP calls
+    game.play(1);
+    assert_eq!(game.pot(), 120);
+    assert_eq!(game.amount_to_call(), 0);
+    assert_eq!(game.remaining_stacks(), [940, 940]);
+}
+
+#[test]
+fn validate_solution() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK".parse().unwrap(),
+            "KK-99,AQ,KQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.validate_solution().is_err());
+
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let report = game.validate_solution().unwrap();
+    assert!(report.is_valid(), "{:?}", report.issues);
+    assert_eq!(report.num_checked_nodes, 3);
+    assert!((report.exploitability - compute_exploitability(&game)).abs() < 1e-4);
+
+    // break the strategy of the root node
+    game.node_arena[0].lock().strategy_mut()[0] = f32::NAN;
+    let report = game.validate_solution().unwrap();
+    assert!(!report.is_valid());
+    let issue = &report.issues[0];
+    assert_eq!(issue.kind, ValidationIssueKind::NonFiniteStrategy);
+    assert_eq!(issue.history, Some(Vec::new()));
+    assert!(report
+        .issues
+        .iter()
+        .any(|issue| issue.kind == ValidationIssueKind::UnnormalizedStrategy));
+}
+
+/// Solves a small canonical configuration with a fixed number of iterations and checks the
+/// strategies against `tests/golden/{name}.txt`. Set `UPDATE_GOLDEN=1` to regenerate the file
+/// after an intentional change.
+fn check_golden(name: &str, card_config: CardConfig, tree_config: TreeConfig, compressed: bool) {
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(compressed);
+    solve(&mut game, 200, 0.0, false);
+
+    let path = format!("{}/tests/golden/{name}.txt", env!("CARGO_MANIFEST_DIR"));
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some() && !compressed;
+    let tolerance = if compressed { 5e-2 } else { 1e-3 };
+    if let Err(e) = check_golden_strategies(&mut game, &path, tolerance, update) {
+        panic!("{name} (compressed = {compressed}): {e}");
+    }
+}
+
+#[test]
+fn golden_strategies() {
+    let river = || {
+        let card_config = CardConfig {
+            range: [
+                "AA,KK,QQ,AK".parse().unwrap(),
+                "KK-99,AQ,KQ".parse().unwrap(),
+            ],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            river: card_from_str("3s").unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 300,
+            river_bet_sizes: [("50%, a", "60%").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        (card_config, tree_config)
+    };
+
+    let turn = || {
+        let card_config = CardConfig {
+            range: ["AA,QQ,AK".parse().unwrap(), "KK,JJ,AQs".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 120,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        (card_config, tree_config)
+    };
+
+    for compressed in [false, true] {
+        let (card_config, tree_config) = river();
+        check_golden("river", card_config, tree_config, compressed);
+        let (card_config, tree_config) = turn();
+        check_golden("turn", card_config, tree_config, compressed);
+    }
+}
+
+#[test]
+fn golden_strategies_compare() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,JJ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(GoldenStrategies::capture(&mut game).is_err());
+
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let golden = GoldenStrategies::capture(&mut game).unwrap();
+    assert_eq!(
+        golden.to_string().parse::<GoldenStrategies>(),
+        Ok(golden.clone())
+    );
+    assert_eq!(golden.compare(&golden, 0.0), Ok(0.0));
+
+    let mut perturbed = golden.clone();
+    perturbed.nodes[0].strategy[0] += 0.01;
+    assert!(golden.compare(&perturbed, 1e-3).is_err());
+    assert!(golden.compare(&perturbed, 2e-2).unwrap() > 0.005);
+
+    perturbed.nodes[0].strategy[0] = f32::NAN;
+    assert!(golden.compare(&perturbed, 1.0).is_err());
+
+    perturbed.nodes.pop();
+    assert!(golden.compare(&perturbed, 1.0).is_err());
+    assert!("invalid".parse::<GoldenStrategies>().is_err());
+}
+
+#[test]
+fn disable_isomorphism() {
+    /// Evaluator in which a club in the hand wins over any hand without a club.
+    struct ClubsEvaluator;
+
+    impl Evaluator for ClubsEvaluator {
+        fn evaluate(&self, board: &[Card; 5], hand: (Card, Card)) -> u32 {
+            let has_club = hand.0 & 3 == 0 || hand.1 & 3 == 0;
+            HighHandEvaluator.evaluate(board, hand) + ((has_club as u32) << 31)
+        }
+    }
+
+    // clubs and hearts are isomorphic on the river
+    let card_config = CardConfig {
+        range: ["AA,KK,AK".parse().unwrap(), "QQ-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6d").unwrap(),
+        turn: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let new_game = |disable_isomorphism| {
+        let card_config = CardConfig {
+            disable_isomorphism,
+            ..card_config.clone()
+        };
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        PostFlopGame::with_config(card_config, action_tree).unwrap()
+    };
+
+    let solve_game = |game: &mut PostFlopGame| {
+        game.allocate_memory(false);
+        solve(game, 1000, 0.05, false);
+        compute_current_ev(game)
+    };
+
+    // the solution does not depend on the isomorphism
+    let mut game = new_game(false);
+    let mut game_disabled = new_game(true);
+    assert!(game_disabled.memory_usage().0 > game.memory_usage().0);
+
+    let ev = solve_game(&mut game);
+    let ev_disabled = solve_game(&mut game_disabled);
+    assert!(compute_exploitability(&game_disabled) <= 0.05);
+    for player in 0..2 {
+        assert!((ev[player] - ev_disabled[player]).abs() < 0.5);
+    }
+
+    // the river cards are not grouped into representatives
+    for game in [&mut game, &mut game_disabled] {
+        game.back_to_root();
+        game.play(0);
+        game.play(0);
+        assert!(game.is_chance_node());
+    }
+    assert_eq!(game_disabled.available_actions().len(), 48);
+    assert!(game.available_actions().len() < 48);
+
+    // a suit-dependent evaluator requires the isomorphism to be disabled
+    let mut game = new_game(false);
+    assert!(game.set_evaluator(Arc::new(ClubsEvaluator)).is_err());
+    assert!(game.set_evaluator(Arc::new(HighHandEvaluator)).is_ok());
+
+    let mut game_disabled = new_game(true);
+    game_disabled
+        .set_evaluator(Arc::new(ClubsEvaluator))
+        .unwrap();
+    solve_game(&mut game_disabled);
+    assert!(compute_exploitability(&game_disabled) <= 0.05);
+}
+
+#[test]
+fn isomorphism_memory_reduction() {
+    let card_config = CardConfig {
+        range: ["AA,KK,AKs".parse().unwrap(), "QQ-TT,AQs".parse().unwrap()],
+        flop: flop_from_str("Td9d6d").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 300,
+        ..Default::default()
+    };
+
+    let new_game = |disable_isomorphism| {
+        let card_config = CardConfig {
+            disable_isomorphism,
+            ..card_config.clone()
+        };
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        PostFlopGame::with_config(card_config, action_tree).unwrap()
+    };
+
+    // the three suits other than the flop suit are isomorphic on the turn
+    let mut game = new_game(false);
+    let game_disabled = new_game(true);
+    assert!(game_disabled.memory_usage().0 > 2 * game.memory_usage().0);
+
+    game.allocate_memory(false);
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    assert_eq!(game.available_actions().len(), 13 + 10);
+
+    let card = |s: &str| card_from_str(s).unwrap();
+    for s in ["8c", "8h", "8s"] {
+        assert_eq!(game.chance_card_representative(card(s)), Some(card("8c")));
+    }
+    assert_eq!(
+        game.chance_card_representative(card("8d")),
+        Some(card("8d"))
+    );
+}
+
+#[test]
+fn alternation() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK".parse().unwrap(),
+            "KK-99,AQ,KQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [
+            ("50%", "60%").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let new_game = |compressed| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(compressed);
+        game
+    };
+
+    // regrets of the root (OOP) and the node after a check (IP)
+    let regrets = |game: &PostFlopGame| {
+        let root = game.node_arena[0].lock();
+        let ip_node = root.play(0);
+        (root.regrets().to_vec(), ip_node.regrets().to_vec())
+    };
+
+    // simultaneous updates: OOP is updated as in the alternating updates, and IP is updated
+    // against the initial strategy of OOP
+    let alternating = new_game(false);
+    let ip_only = new_game(false);
+    let simultaneous = new_game(false);
+    let with_alternation = |alternation| SolverConfig {
+        alternation,
+        ..Default::default()
+    };
+    let configs = [
+        Alternation::Alternating,
+        Alternation::Ratio { player: 1, k: 2 },
+        Alternation::Simultaneous,
+    ]
+    .map(with_alternation);
+    solve_step_with_config(&alternating, 0, &configs[0]);
+    solve_step_with_config(&ip_only, 0, &configs[1]);
+    solve_step_with_config(&simultaneous, 0, &configs[2]);
+
+    let (oop, ip) = regrets(&simultaneous);
+    assert_eq!(oop, regrets(&alternating).0);
+    assert_eq!(ip, regrets(&ip_only).1);
+    assert_ne!(ip, regrets(&alternating).1);
+    assert!(regrets(&ip_only).0.iter().all(|&r| r == 0.0));
+
+    // `k = 1` is equivalent to the alternating updates
+    let alternating = new_game(false);
+    let ratio = new_game(false);
+    for t in 0..10 {
+        solve_step(&alternating, t);
+        let config = with_alternation(Alternation::Ratio { player: 0, k: 1 });
+        solve_step_with_config(&ratio, t, &config);
+    }
+    assert_eq!(regrets(&alternating), regrets(&ratio));
+
+    // every pattern converges
+    for compressed in [false, true] {
+        for alternation in [
+            Alternation::Simultaneous,
+            Alternation::Alternating,
+            Alternation::Ratio { player: 1, k: 3 },
+        ] {
+            let mut game = new_game(compressed);
+            let config = with_alternation(alternation);
+            let exploitability = solve_with_config(&mut game, 1000, 0.3, &config, false);
+            assert!(exploitability <= 0.3, "{alternation:?}: {exploitability}");
+        }
+    }
+}
+
+#[test]
+fn prune_solution() {
+    let card_config = CardConfig {
+        range: ["AA,QQ,AK".parse().unwrap(), "KK,JJ,AQs".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.prune_solution(0.0).is_err());
+    solve(&mut game, 200, 0.0, false);
+    assert!(game.prune_solution(1.5).is_err());
+
+    // no pruning
+    let full = game.prune_solution(0.0).unwrap();
+    assert!(full.pruned_subtrees.is_empty());
+    assert_eq!(full.discarded_reach, 0.0);
+    assert_eq!(
+        full.nodes.len(),
+        game.export_external_strategy(BoardState::River)
+            .unwrap()
+            .len()
+    );
+
+    let root = &full.nodes[0];
+    assert!(root.line.is_empty());
+    assert!((root.reach_probability - 1.0).abs() < 1e-5);
+    game.cache_normalized_weights();
+    assert_eq!(root.expected_values[0], game.expected_values(0));
+
+    // the reach probabilities of the river nodes after check-check sum up to the probability of
+    // check-check
+    let check_check = {
+        game.apply_history(&[0]);
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(1).to_vec();
+        let strategy = game.strategy();
+        let check = weights
+            .iter()
+            .zip(&strategy)
+            .map(|(w, s)| w * s)
+            .sum::<f32>();
+        let reach = full.node(&[Action::Check]).unwrap().reach_probability;
+        reach * check / weights.iter().sum::<f32>()
+    };
+    let river_sum = full
+        .nodes
+        .iter()
+        .filter(|node| node.line.len() == 3 && node.line[..2] == [Action::Check, Action::Check])
+        .map(|node| node.reach_probability)
+        .sum::<f32>();
+    assert!((river_sum - check_check).abs() < 1e-4);
+
+    // pruning
+    let threshold = 0.02;
+    let pruned = game.prune_solution(threshold).unwrap();
+    assert!(!pruned.pruned_subtrees.is_empty());
+    assert!(pruned.nodes.len() < full.nodes.len());
+    assert!(pruned.memory_usage() < full.memory_usage());
+    assert!(pruned
+        .nodes
+        .iter()
+        .all(|node| node.reach_probability >= threshold));
+    assert!(pruned
+        .pruned_subtrees
+        .iter()
+        .all(|subtree| subtree.reach_probability < threshold));
+    let discarded = pruned
+        .pruned_subtrees
+        .iter()
+        .map(|subtree| subtree.reach_probability)
+        .sum::<f32>();
+    assert!((pruned.discarded_reach - discarded).abs() < 1e-5);
+
+    // the retained nodes are identical to those of the full solution
+    for node in &pruned.nodes {
+        assert_eq!(full.node(&node.line), Some(node));
+    }
+
+    assert_eq!(game.history(), &[0]);
+}
+
+#[test]
+fn strategy_combos() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,KQs".parse().unwrap(),
+            "KK-TT,AQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    game.cache_normalized_weights();
+    let strategy = game.strategy();
+    let weights = game.normalized_weights(0).to_vec();
+    let num_hands = weights.len();
+    let total = weights.iter().fold(0.0, |sum, &w| sum + w as f64);
+
+    let combos = game.strategy_combos(3);
+    assert_eq!(combos.len(), game.available_actions().len());
+    let sum_combos = combos.iter().fold(0.0, |sum, c| sum + c.combos);
+    let sum_frequency = combos.iter().fold(0.0, |sum, c| sum + c.frequency);
+    assert!((sum_combos - total).abs() < 1e-3 * total);
+    assert!((sum_frequency - 1.0).abs() < 1e-6);
+
+    for (action, entry) in combos.iter().enumerate() {
+        assert_eq!(entry.action, game.available_actions()[action]);
+        assert!(entry.top_hands.len() <= 3);
+        assert!(entry.top_hands.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let row = &strategy[action * num_hands..(action + 1) * num_hands];
+        for &(hand, c) in &entry.top_hands {
+            let index = game.private_cards(0).iter().position(|&h| h == hand);
+            let index = index.unwrap();
+            assert!((c - row[index] as f64 * weights[index] as f64).abs() < 1e-9);
+        }
+
+        // the top hands cover all combinations when `top_n` is large enough
+        let all = &game.strategy_combos(num_hands)[action];
+        let sum = all.top_hands.iter().fold(0.0, |sum, h| sum + h.1);
+        assert!((sum - entry.combos).abs() < 1e-9 * total.max(1.0));
+        assert!(all.top_hands.iter().all(|h| h.1 > 0.0));
+    }
+
+    // no hands are listed with `top_n == 0`
+    assert!(game
+        .strategy_combos(0)
+        .iter()
+        .all(|c| c.top_hands.is_empty()));
+}
+
+#[test]
+fn describe_node() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,KQs".parse().unwrap(),
+            "KK-TT,AQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    game.cache_normalized_weights();
+    let description = game.describe_node();
+    assert!(description.starts_with("OOP checks "));
+    assert!(description.contains(", bets 50% pot "));
+
+    let combos = game.strategy_combos(0);
+    let check = format!("OOP checks {:.0}%", combos[0].frequency * 100.0);
+    assert!(description.starts_with(&check));
+
+    // bets list the made-hand categories, while checks do not
+    let (check, _) = description.split_once(", bets").unwrap();
+    assert!(!check.contains("mostly"));
+    if combos[1].combos > 0.0 {
+        assert!(description.contains("(mostly "));
+    }
+
+    // the opponent facing the bet
+    game.play(1);
+    game.cache_normalized_weights();
+    let description = game.describe_node();
+    assert!(description.starts_with("IP folds "));
+    assert!(description.contains(", calls "));
+}
+
+#[test]
+fn analyze_node() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,KQs,QJs".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+    game.cache_normalized_weights();
+
+    let analysis = game.analyze_node(0);
+    let weights = game.normalized_weights(0);
+    let total = weights.iter().fold(0.0, |acc, &w| acc + w as f64);
+    let ev = compute_average(&game.expected_values(0), weights) as f64;
+    let equity = compute_average(&game.equity(0), weights) as f64;
+    assert!((analysis.combos - total).abs() < 1e-6);
+    assert!((analysis.ev - ev).abs() < 1e-3);
+    assert!((analysis.equity - equity).abs() < 1e-5);
+    assert!((analysis.eqr - ev / (equity * 60.0)).abs() < 1e-4);
+
+    // the classes and the actions partition the range
+    let fraction = analysis
+        .classes
+        .iter()
+        .map(|c| c.range_fraction)
+        .sum::<f64>();
+    assert!((fraction - 1.0).abs() < 1e-9);
+    let frequency = analysis.actions.iter().map(|a| a.frequency).sum::<f64>();
+    assert!((frequency - 1.0).abs() < 1e-5);
+    let ev_by_action = analysis
+        .actions
+        .iter()
+        .map(|a| a.ev * a.frequency)
+        .sum::<f64>();
+    assert!((ev_by_action - ev).abs() < 1e-3);
+
+    // AA is an overpair, KQs is top pair, and AKs of diamonds is a flush draw
+    let class_of = |hand: &str| {
+        let hand = (
+            card_from_str(&hand[..2]).unwrap(),
+            card_from_str(&hand[2..]).unwrap(),
+        );
+        analysis
+            .hands
+            .iter()
+            .find(|h| h.hand == hand || h.hand == (hand.1, hand.0))
+            .unwrap()
+            .class
+    };
+    assert_eq!(class_of("AsAh"), Some(HandClass::Overpair));
+    assert_eq!(class_of("KsQs"), Some(HandClass::TopPair));
+    assert_eq!(class_of("AdKd"), Some(HandClass::FlushDraw));
+
+    // the opponent does not act at the root
+    let analysis = game.analyze_node(1);
+    assert!(analysis.actions.is_empty());
+    assert!(analysis.hands.iter().all(|h| h.strategy.is_empty()));
+}
+
+#[test]
+fn quantile_summary() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,KQs,QJs".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+    game.cache_normalized_weights();
+
+    assert!(game
+        .quantile_summary(QuantileKey::Equity, &[0.5, 0.5])
+        .is_err());
+    assert!(game.quantile_summary(QuantileKey::Equity, &[1.0]).is_err());
+
+    let analysis = game.analyze_node(0);
+    for key in [QuantileKey::Equity, QuantileKey::ExpectedValue] {
+        let summary = game.quantile_summary(key, &[0.2, 0.5]).unwrap();
+        assert_eq!(summary.player, 0);
+        assert_eq!(summary.bands.len(), 3);
+
+        // the bands partition the range
+        let combos = summary.bands.iter().map(|b| b.combos).collect::<Vec<_>>();
+        assert!((combos[0] - 0.2 * analysis.combos).abs() < 1e-6);
+        assert!((combos[1] - 0.3 * analysis.combos).abs() < 1e-6);
+        assert!((combos[2] - 0.5 * analysis.combos).abs() < 1e-6);
+
+        for (action, row) in analysis.actions.iter().enumerate() {
+            let frequency = summary
+                .bands
+                .iter()
+                .map(|b| b.frequencies[action] * b.combos)
+                .sum::<f64>();
+            assert!((frequency / analysis.combos - row.frequency).abs() < 1e-5);
+        }
+
+        let equity = summary.bands.iter().map(|b| b.equity).collect::<Vec<_>>();
+        let ev = summary.bands.iter().map(|b| b.ev).collect::<Vec<_>>();
+        match key {
+            QuantileKey::Equity => assert!(equity[0] >= equity[1] && equity[1] >= equity[2]),
+            QuantileKey::ExpectedValue => assert!(ev[0] >= ev[1] && ev[1] >= ev[2]),
+        }
+
+        let text = summary.to_string();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.starts_with("0-20%: Check "));
+    }
+
+    // a single band covers the whole range
+    let summary = game.quantile_summary(QuantileKey::Equity, &[]).unwrap();
+    assert!((summary.bands[0].combos - analysis.combos).abs() < 1e-6);
+    assert!((summary.bands[0].equity - analysis.equity).abs() < 1e-5);
+
+    // the current node must be a decision node
+    game.play(0);
+    game.play(0);
+    game.cache_normalized_weights();
+    assert!(game.is_chance_node());
+    assert!(game.quantile_summary(QuantileKey::Equity, &[0.5]).is_err());
+}
+
+#[test]
+fn future_plan() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,KQs,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let plan = game.future_plan().unwrap();
+    let actions = game.available_actions();
+    let num_hands = plan.private_cards.len();
+    assert!(game.history().is_empty());
+    assert_eq!(plan.player, 0);
+    assert_eq!(plan.plans.len(), actions.len());
+
+    // going all-in leaves no decision on the river
+    let all_in = actions.iter().position(|a| matches!(a, Action::AllIn(_)));
+    assert!(plan.plans[all_in.unwrap()].is_none());
+
+    let bet = actions.iter().position(|a| matches!(a, Action::Bet(_)));
+    let bet_plan = plan.plans[bet.unwrap()].as_ref().unwrap();
+    assert_eq!(bet_plan.continuation, vec![Action::Call]);
+
+    let check_plan = plan.plans[0].as_ref().unwrap();
+    assert_eq!(check_plan.action, Action::Check);
+    assert_eq!(check_plan.continuation, vec![Action::Check]);
+    assert_eq!(check_plan.frequency, game.strategy()[..num_hands].to_vec());
+    assert!(check_plan
+        .aggression
+        .iter()
+        .all(|&a| (0.0..=1.0 + 1e-6).contains(&a)));
+
+    // recompute the plan for the overcards (K and A) by hand
+    let board = game.current_board();
+    let mut combos = vec![0.0; num_hands];
+    let mut sums = vec![0.0; num_hands];
+    for card in 0..52 {
+        if board.contains(&card) || RunoutClass::classify(&board, card) != RunoutClass::Overcard {
+            continue;
+        }
+        game.apply_history(&[0, 0, card as usize]);
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0).to_vec();
+        let strategy = game.strategy();
+        for (action, row) in game
+            .available_actions()
+            .iter()
+            .zip(strategy.chunks(num_hands))
+        {
+            if matches!(action, Action::Bet(_) | Action::AllIn(_)) {
+                for hand in 0..num_hands {
+                    sums[hand] += weights[hand] as f64 * row[hand] as f64;
+                }
+            }
+        }
+        for hand in 0..num_hands {
+            combos[hand] += weights[hand] as f64;
+        }
+    }
+
+    let offset = RunoutClass::Overcard as usize * num_hands;
+    for hand in 0..num_hands {
+        assert!((check_plan.combos[offset + hand] - combos[hand]).abs() < 1e-6);
+        if combos[hand] > 0.0 {
+            let expected = sums[hand] / combos[hand];
+            assert!((check_plan.aggression[offset + hand] as f64 - expected).abs() < 1e-5);
+        }
+    }
+
+    let aggression = check_plan.class_aggression(RunoutClass::Overcard).unwrap();
+    let total = combos.iter().sum::<f64>();
+    assert!((aggression - sums.iter().sum::<f64>() / total).abs() < 1e-5);
+
+    // no future street on the river
+    game.apply_history(&[0, 0, 0]);
+    assert!(game.future_plan().is_err());
+}
+
+#[cfg(feature = "lp")]
+#[test]
+fn solve_lp_river() {
+    use crate::bet_size::BetSizeOptions;
+    use crate::lp::*;
+
+    let card_config = CardConfig {
+        range: [
+            "AA,QQ,JJ,AKs".parse().unwrap(),
+            "KK,QQ,AQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = BetSizeOptions::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 200,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game = new_game();
+    let value = solve_lp(&mut game).unwrap();
+    assert!(game.is_solved());
+    assert!((compute_current_ev(&game)[0] - value).abs() < 1e-4);
+    assert!(compute_exploitability(&game) < 1e-4);
+
+    // the solution of CFR converges to the exact value
+    let mut game = new_game();
+    solve(&mut game, 1000, 0.01, false);
+    assert!((compute_current_ev(&game)[0] - value).abs() < 0.05);
+}
+
+#[test]
+fn solve_runout() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,KQs,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let river = card_from_str("2s").unwrap();
+    game.allocate_memory(false);
+    assert!(game.solve_runout(&[0, 0], river, 50, 0.0).is_err());
+    solve(&mut game, 100, 0.0, false);
+
+    // not a chance node, a board card
+    assert!(game.solve_runout(&[], river, 50, 0.0).is_err());
+    let turn = card_from_str("Qc").unwrap();
+    assert!(game.solve_runout(&[0, 0], turn, 50, 0.0).is_err());
+
+    let mut runout = game.solve_runout(&[0, 0], river, 50, 0.0).unwrap();
+    assert!(game.history().is_empty());
+    assert!(runout.is_solved());
+    assert_eq!(runout.card_config().river, river);
+    assert_eq!(runout.tree_config().initial_state, BoardState::River);
+    assert_eq!(runout.tree_config().starting_pot, 60);
+
+    // the ranges are the reach weights after the turn line
+    game.apply_history(&[0, 0]);
+    let weights = game.weights(0).to_vec();
+    let hands = runout.private_cards(0);
+    for (hand, &w) in hands.iter().zip(runout.initial_weights(0)) {
+        let index = game.private_cards(0).binary_search(hand).unwrap();
+        assert!((weights[index] - w).abs() < 1e-6);
+    }
+
+    runout.cache_normalized_weights();
+    assert!(!runout.strategy().is_empty());
+}
+
+#[test]
+fn resolve_subgame_unsafe() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,KQs,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let config = ResolveConfig {
+        max_num_iterations: 500,
+        target_exploitability: 0.01,
+        ..Default::default()
+    };
+    game.allocate_memory(false);
+    assert!(game.resolve_subgame_unsafe(&[], &config).is_err());
+    solve(&mut game, 500, 0.01, false);
+
+    // not the first decision of a street
+    assert!(game.resolve_subgame_unsafe(&[0], &config).is_err());
+
+    // re-solving the same tree reproduces the parent values
+    let river = card_from_str("2s").unwrap() as usize;
+    let resolved = game
+        .resolve_subgame_unsafe(&[0, 0, river], &config)
+        .unwrap();
+    assert!(game.history().is_empty());
+    assert_eq!(resolved.game.tree_config().initial_state, BoardState::River);
+    assert!(resolved.exploitability < 0.05);
+    for player in 0..2 {
+        assert!(resolved.min_margin[player] > -6.0);
+    }
+
+    // finer bet sizes on the river
+    let finer = crate::BetSizeOptions::try_from(("33%, 75%, a", "50%")).unwrap();
+    let config = ResolveConfig {
+        river_bet_sizes: Some([finer.clone(), finer]),
+        ..config
+    };
+    let resolved = game
+        .resolve_subgame_unsafe(&[0, 0, river], &config)
+        .unwrap();
+    let mut subgame = resolved.game;
+    assert_eq!(subgame.available_actions().len(), 4);
+    subgame.cache_normalized_weights();
+    assert!(resolved.min_margin.iter().all(|m| m.is_finite()));
+}
+
+#[test]
+fn find_optimal_bet_size() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,76s".parse().unwrap(),
+            "KK-TT,AQ".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let config = BetSizeSearchConfig {
+        min_size: 0.2,
+        max_size: 1.5,
+        max_candidates: 6,
+        max_num_iterations: 50,
+        ..Default::default()
+    };
+    assert!(game.find_optimal_bet_size(&config).is_err());
+
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    let result = game.find_optimal_bet_size(&config).unwrap();
+    assert_eq!(result.player, 0);
+    assert!(!result.candidates.is_empty() && result.candidates.len() <= 6);
+    assert!(result
+        .candidates
+        .iter()
+        .all(|c| (0.2..=1.5).contains(&c.size) && c.ev <= result.best.ev));
+
+    // IP after OOP's check
+    game.play(0);
+    let result = game.find_optimal_bet_size(&config).unwrap();
+    assert_eq!(result.player, 1);
+    assert!(result.best.ev.is_finite());
+
+    // facing a bet
+    game.play(1);
+    assert!(game.find_optimal_bet_size(&config).is_err());
+}
+
+#[test]
+fn bet_compositions() {
+    let card_config = CardConfig {
+        range: ["AA,87s,54s".parse().unwrap(), "KK-JJ,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 200, 0.0, false);
+
+    let compositions = game.bet_compositions().unwrap();
+    let actions = game.available_actions();
+    assert!(game.history().is_empty());
+    assert_eq!(compositions.len(), 1); // bet 30
+
+    let bet = actions.iter().position(|a| matches!(a, Action::Bet(_)));
+    let bet = bet.unwrap();
+    let composition = &compositions[0];
+    assert_eq!(composition.action, actions[bet]);
+    assert_eq!(composition.bet_amount, 30);
+    assert_eq!(composition.pot, 60);
+    assert!((composition.minimum_defense_frequency - 2.0 / 3.0).abs() < 1e-9);
+    assert!((composition.indifference_bluff_ratio - 0.25).abs() < 1e-9);
+    assert!((0.0..=1.0).contains(&composition.defense_frequency));
+
+    // the betting range matches the strategy
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0).to_vec();
+    let num_hands = weights.len();
+    let strategy = game.strategy();
+    let row = &strategy[bet * num_hands..(bet + 1) * num_hands];
+    let bet_combos = row
+        .iter()
+        .zip(&weights)
+        .fold(0.0, |sum, (&s, &w)| sum + s as f64 * w as f64);
+    assert!((composition.bet_combos - bet_combos).abs() < 1e-3);
+
+    // aces and straights are value, five-high is a bluff
+    assert!(composition.call_combos > 0.0);
+    let mut value_combos = 0.0;
+    let mut bluff_combos = 0.0;
+    for (hand, &(c1, _)) in game.private_cards(0).iter().enumerate() {
+        let combos = row[hand] as f64 * weights[hand] as f64;
+        if c1 >> 2 == 2 {
+            bluff_combos += combos;
+            assert!(composition.equity[hand] == 0.0);
+        } else {
+            value_combos += combos;
+            assert!(combos == 0.0 || composition.equity[hand] > 0.5);
+        }
+    }
+    assert!((composition.value_combos - value_combos).abs() < 1e-3);
+    assert!((composition.bluff_combos - bluff_combos).abs() < 1e-3);
+    let value_ratio = composition.value_ratio().unwrap();
+    assert!((value_ratio - value_combos / (value_combos + bluff_combos)).abs() < 1e-6);
+
+    // not a decision node
+    game.play(bet);
+    game.play(1);
+    assert!(game.is_terminal_node());
+    assert!(game.bet_compositions().is_err());
+}
+
+#[test]
+fn run_query() {
+    // parsing
+    let pattern = "r:b75:c:*:x:**:Ah".parse::<NodePattern>().unwrap();
+    assert_eq!(pattern.to_string(), "r:b75:c:*:x:**:Ah");
+    assert!("b75:c".parse::<NodePattern>().is_err());
+    assert!("r:b7x".parse::<NodePattern>().is_err());
+    assert!(r#"{"node": "r", "metric": "unknown"}"#.parse::<Query>().is_err());
+    assert!(r#"{"metric": "ev"}"#.parse::<Query>().is_err());
+    assert!(r#"{"node": "r", "metric": "ev", "player": 2}"#.parse::<Query>().is_err());
+
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,KQs,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 100, 0.0, false);
+
+    let run = |game: &mut PostFlopGame, query: &str| game.run_query(&query.parse().unwrap());
+
+    // root
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0).to_vec();
+    let num_hands = weights.len();
+    let strategy = game.strategy();
+    let total = weights.iter().fold(0.0, |sum, &w| sum + w as f64);
+    let check = strategy[..num_hands]
+        .iter()
+        .zip(&weights)
+        .fold(0.0, |sum, (&s, &w)| sum + s as f64 * w as f64);
+
+    let rows = run(&mut game, r#"{"node": "r", "metric": "check_frequency"}"#).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].key, "all");
+    assert_eq!(rows[0].num_nodes, 1);
+    assert!((rows[0].combos - total).abs() < 1e-6 * total);
+    assert!((rows[0].value - check / total).abs() < 1e-6);
+
+    let rows = run(&mut game, r#"{"node": "r", "metric": "combos"}"#).unwrap();
+    assert!((rows[0].value - total).abs() < 1e-6 * total);
+
+    // the frequencies of the IP player are not evaluated at the root
+    let query = r#"{"node": "r", "metric": "bet_frequency", "player": 1}"#;
+    assert!(run(&mut game, query).unwrap().is_empty());
+
+    // consistent with the future plan
+    let plan = game.future_plan().unwrap();
+    let check_plan = plan.plans[0].as_ref().unwrap();
+    let query = r#"{"node": "r:x:x:*", "metric": "bet_frequency", "group_by": "river_card_class"}"#;
+    let rows = run(&mut game, query).unwrap();
+    assert!(!rows.is_empty());
+    for row in &rows {
+        let class = match row.key.as_str() {
+            "pair" => RunoutClass::Pair,
+            "flush" => RunoutClass::Flush,
+            "overcard" => RunoutClass::Overcard,
+            "blank" => RunoutClass::Blank,
+            key => panic!("unexpected key: {key}"),
+        };
+        let expected = check_plan.class_aggression(class).unwrap();
+        assert!((row.value - expected).abs() < 1e-5);
+    }
+
+    let query = r#"{"node": "r:x:x:*", "metric": "ev", "group_by": "river_card"}"#;
+    let rows = run(&mut game, query).unwrap();
+    assert_eq!(rows.len(), 48);
+    assert!(rows
+        .windows(2)
+        .all(|w| { card_from_str(&w[0].key).unwrap() < card_from_str(&w[1].key).unwrap() }));
+
+    let query = r#"{"node": "r:**:x", "metric": "equity", "player": 0, "group_by": "node"}"#;
+    let rows = run(&mut game, query).unwrap();
+    assert!(!rows.is_empty());
+    assert!(rows
+        .iter()
+        .all(|row| row.key.ends_with(":x") && row.num_nodes == 1));
+    assert!(rows.iter().all(|row| (0.0..=1.0).contains(&row.value)));
+    assert!(game.history().is_empty());
+}
+
+#[test]
+fn strategy_table() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 20, 0.0, false);
+
+    let table = game.strategy_table().unwrap();
+    assert!(!table.is_empty());
+    assert_eq!(table.line[0], "r");
+    let board = game.current_board();
+    let board = board.iter().map(|&c| card_to_string(c).unwrap());
+    assert_eq!(table.board[0], board.collect::<String>());
+
+    // the root rows match the strategy
+    game.cache_normalized_weights();
+    let weights = game.normalized_weights(0).to_vec();
+    let strategy = game.strategy();
+    let num_hands = weights.len();
+    let mut rows = 0;
+    for action in 0..game.available_actions().len() {
+        for hand in 0..num_hands {
+            if weights[hand] > 0.0 {
+                assert_eq!(table.frequency[rows], strategy[action * num_hands + hand]);
+                assert_eq!(table.reach[rows], weights[hand]);
+                rows += 1;
+            }
+        }
+    }
+    assert!(table.node[..rows].iter().all(|&node| node == 0));
+    assert_eq!(table.node[rows], 1);
+
+    // the frequencies of each hand sum to one
+    let mut sums = BTreeMap::new();
+    for i in 0..table.len() {
+        *sums.entry((table.node[i], &table.hand[i])).or_insert(0.0) += table.frequency[i];
+    }
+    assert!(sums.values().all(|&sum| (sum - 1.0f32).abs() < 1e-4));
+
+    // chunks are split between nodes and concatenate to the whole table
+    let mut chunks = Vec::new();
+    game.strategy_table_chunks(100, |chunk| {
+        chunks.push(chunk);
+        Ok(())
+    })
+    .unwrap();
+    assert!(chunks.len() > 1);
+    assert!(chunks
+        .windows(2)
+        .all(|w| w[0].node.last() != w[1].node.first()));
+    assert_eq!(chunks.iter().map(|c| c.len()).sum::<usize>(), table.len());
+    let frequency = chunks.iter().flat_map(|c| c.frequency.clone());
+    assert_eq!(frequency.collect::<Vec<_>>(), table.frequency);
+
+    // errors abort the traversal
+    let mut num_calls = 0;
+    let result = game.strategy_table_chunks(100, |_| {
+        num_calls += 1;
+        Err(Error::Config("abort".to_string()))
+    });
+    assert_eq!(result, Err(Error::Config("abort".to_string())));
+    assert_eq!(num_calls, 1);
+    assert!(game.strategy_table_chunks(0, |_| Ok(())).is_err());
+    assert!(game.history().is_empty());
+}
+
+#[test]
+fn strategy_export() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.export_pio_strategy(BoardState::River).is_err());
+    solve(&mut game, 20, 0.0, false);
+
+    let table = game.strategy_table().unwrap();
+    assert_eq!(table.pio_path[0], "r:0");
+    let index = table.line.iter().position(|l| l == "r:b30:c:2c").unwrap();
+    assert_eq!(table.pio_path[index], "r:0:b30:c:2c");
+
+    // CSV: a header and one line per row
+    let mut csv = Vec::new();
+    game.export_strategy(ExportFormat::Csv, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), table.len() + 1);
+    assert_eq!(
+        lines[0],
+        "node,line,pio_path,board,player,hand,action,frequency,ev,reach"
+    );
+    let fields = lines[1].split(',').collect::<Vec<_>>();
+    assert_eq!(fields[2], "r:0");
+    assert_eq!(fields[5], table.hand[0]);
+    assert_eq!(fields[7].parse::<f32>().unwrap(), table.frequency[0]);
+
+    // JSON: an array of row objects
+    let mut json = Vec::new();
+    game.export_strategy(ExportFormat::Json, &mut json).unwrap();
+    let json = crate::json::JsonValue::parse(std::str::from_utf8(&json).unwrap()).unwrap();
+    validate_json("strategy_export", &json).unwrap();
+    let rows = json.as_array().unwrap();
+    assert_eq!(rows.len(), table.len());
+    let row = &rows[index];
+    assert_eq!(row.get("pio_path").unwrap().as_str(), Some("r:0:b30:c:2c"));
+    assert_eq!(
+        row.get("action").unwrap().as_str(),
+        Some(&*table.action[index])
+    );
+    let ev = row.get("ev").unwrap().as_f64().unwrap();
+    assert!((ev - table.ev[index] as f64).abs() < 1e-4);
+
+    // the PioSolver export can be imported back
+    let nodes = game.export_pio_strategy(BoardState::River).unwrap();
+    assert_eq!(nodes.len() as u32, table.node.last().unwrap() + 1);
+    assert_eq!(nodes[0].path, "r:0");
+    assert!(game.history().is_empty());
+    let imported = game.import_pio_strategy(&nodes).unwrap();
+    for (node, external) in nodes.iter().zip(&imported) {
+        let text = write_pio_strategy(&node.strategy).unwrap();
+        assert_eq!(parse_pio_strategy(&text).unwrap(), node.strategy);
+        game.apply_history(&external.history);
+        assert_eq!(external.strategy, game.strategy());
+    }
+
+    let nodes = game.export_pio_strategy(BoardState::Turn).unwrap();
+    assert!(nodes.len() > 1);
+    assert!(nodes
+        .iter()
+        .all(|node| !node.line.iter().any(|a| matches!(a, Action::Chance(_)))));
+}
+
+#[test]
+fn runout_values() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 50, 0.0, false);
+
+    assert!(game.runout_values().is_err());
+    game.apply_history(&[1, 1]);
+    assert!(game.is_chance_node());
+    game.cache_normalized_weights();
+    let chance_ev = game.expected_values(0);
+
+    let values = game.runout_values().unwrap();
+    assert_eq!(values.cards.len(), 48);
+    assert_eq!(game.history(), &[1, 1]);
+    assert_eq!(game.expected_values(0), chance_ev);
+
+    // the entries match the values at each child
+    for (i, &card) in values.cards.iter().enumerate().step_by(7) {
+        game.play(card as usize);
+        game.cache_normalized_weights();
+        for player in 0..2 {
+            assert_eq!(values.card_values(player, i), game.expected_values(player));
+        }
+        game.apply_history(&[1, 1]);
+    }
+
+    // the values at the chance node are the weighted averages over the cards
+    let num_hands = values.num_private_hands(0);
+    assert_eq!(num_hands, game.private_cards(0).len());
+    for (hand, &ev) in chance_ev.iter().enumerate() {
+        let hand_values = values.hand_values(0, hand);
+        assert_eq!(hand_values.len(), values.cards.len());
+        let hand_weights = values.weights[0].iter().skip(hand).step_by(num_hands);
+        let (sum, total) = hand_values
+            .iter()
+            .zip(hand_weights)
+            .fold((0.0, 0.0), |(s, t), (&v, &w)| (s + v * w, t + w));
+        if total > 0.0 {
+            assert!((sum / total - ev).abs() < 1e-3 * ev.abs().max(1.0));
+        }
+    }
+}
+
+/// Solves the canonical spot for 100 iterations and checks the exploitability and the expected
+/// value of OOP against the reference values.
+fn check_canonical_spot(name: &str, exploitability: f32, ev: f32) {
+    let spot = CanonicalSpot::by_name(name).unwrap();
+    let mut game = spot.build().unwrap();
+    game.allocate_memory(false);
+    let actual = solve(&mut game, 100, 0.0, false).exploitability;
+    let actual_ev = compute_current_ev(&game);
+    assert!((actual - exploitability).abs() < 1e-3, "{name}: {actual}");
+    assert!((actual_ev[0] - ev).abs() < 1e-2, "{name}: {actual_ev:?}");
+    assert!((actual_ev[0] + actual_ev[1]).abs() < 1e-3);
+}
+
+#[test]
+fn canonical_spots() {
+    let spots = CanonicalSpot::all();
+    for spot in &spots {
+        let found = CanonicalSpot::by_name(&spot.name).unwrap();
+        assert_eq!(found.name, spot.name);
+        assert!(spot.build().is_ok());
+    }
+    assert!(CanonicalSpot::by_name("unknown").is_none());
+
+    check_canonical_spot("SRP Q9572 river 50bb", 0.15916, -28.2446);
+}
+
+#[test]
+#[ignore]
+fn canonical_spots_flop() {
+    check_canonical_spot("SRP K72r 20bb", 0.12203, -10.2707);
+    check_canonical_spot("3BP A84tt 100bb-mini", 0.36738, 44.2710);
+}
+
+#[test]
+fn decoded_cache() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(true);
+    solve(&mut game, 50, 0.0, false);
+
+    let river = card_from_str("2s").unwrap() as usize;
+    let lines: [&[usize]; 4] = [&[], &[0], &[0, 0, river], &[0, 0, river, 1]];
+
+    let collect = |game: &mut PostFlopGame| {
+        let mut ret = Vec::new();
+        for _ in 0..2 {
+            for &line in &lines {
+                game.apply_history(line);
+                game.cache_normalized_weights();
+                let player = game.current_player();
+                ret.push(game.strategy());
+                ret.push(game.expected_values(player));
+                ret.push(game.expected_values_detail(player));
+                ret.push(game.equity(player ^ 1));
+            }
+        }
+        ret
+    };
+
+    assert_eq!(game.decoded_cache_memory_usage(), 0);
+    let cached = collect(&mut game);
+    assert!(game.decoded_cache_memory_usage() > 0);
+
+    game.set_decoded_cache_enabled(false);
+    assert_eq!(game.decoded_cache_memory_usage(), 0);
+    let uncached = collect(&mut game);
+    assert_eq!(game.decoded_cache_memory_usage(), 0);
+    assert_eq!(cached, uncached);
+
+    // navigating to another runout replaces the river layer
+    game.set_decoded_cache_enabled(true);
+    game.apply_history(&[0, 0, river, 1]);
+    let usage = game.decoded_cache_memory_usage();
+    let other = card_from_str("3s").unwrap() as usize;
+    game.apply_history(&[0, 0, other, 1]);
+    assert_eq!(game.decoded_cache_memory_usage(), usage);
+}
+
+#[test]
+fn compression_from_turn() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%", "")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 300,
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let (uncompressed, compressed) = game.memory_usage();
+    let from_flop = game.memory_usage_compressed_from(BoardState::Flop);
+    let from_turn = game.memory_usage_compressed_from(BoardState::Turn);
+    let from_river = game.memory_usage_compressed_from(BoardState::River);
+    assert_eq!(from_flop, compressed);
+    assert!(compressed < from_turn && from_turn < from_river && from_river < uncompressed);
+
+    let mut reference = PostFlopGame::with_config(
+        game.card_config().clone(),
+        ActionTree::new(game.tree_config().clone()).unwrap(),
+    )
+    .unwrap();
+    reference.allocate_memory(false);
+    solve(&mut reference, 100, 0.0, false);
+
+    game.allocate_memory_compressed_from(BoardState::Turn);
+    assert_eq!(game.is_memory_allocated(), Some(true));
+    assert_eq!(game.compression_street(), Some(BoardState::Turn));
+    let exploitability = solve(&mut game, 100, 0.0, false).exploitability;
+    assert!(exploitability < 1.0);
+
+    // flop nodes are stored in full precision
+    assert!(!game.is_node_compressed(&game.node()));
+    game.play(0);
+    game.play(0);
+    game.play(card_from_str("2c").unwrap() as usize);
+    assert!(game.is_node_compressed(&game.node()));
+
+    game.back_to_root();
+    let strategy = game.strategy();
+    let reference_strategy = reference.strategy();
+    assert!(strategy
+        .iter()
+        .zip(&reference_strategy)
+        .all(|(a, b)| (a - b).abs() < 0.05));
+
+    game.allocate_memory(false);
+    assert_eq!(game.compression_street(), None);
+}
+
+#[test]
+fn lazy_allocation() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("3s").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("50%", "60%")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let (uncompressed, compressed) = game.memory_usage();
+
+    // OOP always checks, and IP always folds to a bet: the raise line is never reached
+    let lock = |game: &mut PostFlopGame| {
+        let num_hands = game.num_private_hands(0);
+        let mut strategy = vec![0.0; 2 * num_hands];
+        strategy[..num_hands].fill(1.0);
+        game.lock_strategy(&[], 0, &strategy).unwrap();
+        let num_hands = game.num_private_hands(1);
+        let mut strategy = vec![0.0; 3 * num_hands];
+        strategy[..num_hands].fill(1.0);
+        game.lock_strategy(&[1], 1, &strategy).unwrap();
+    };
+
+    game.allocate_memory(false);
+    lock(&mut game);
+    solve(&mut game, 100, 0.0, false);
+    let expected = compute_current_ev(&game);
+
+    for (enable_compression, full) in [(false, uncompressed), (true, compressed)] {
+        game.allocate_memory_lazy(enable_compression, 0.0);
+        assert!(game.is_lazy_allocation_enabled());
+        lock(&mut game);
+        solve(&mut game, 100, 0.0, false);
+        assert!(game.lazy_memory_usage() < full);
+
+        let ev = compute_current_ev(&game);
+        assert!((ev[0] - expected[0]).abs() < 1e-3);
+        assert!((ev[1] - expected[1]).abs() < 1e-3);
+
+        // the unallocated node has the uniform strategy
+        game.apply_history(&[1, 2]);
+        let strategy = game.strategy();
+        let uniform = 1.0 / game.available_actions().len() as f32;
+        assert!(strategy.iter().all(|&x| (x - uniform).abs() < 1e-6));
+        game.back_to_root();
+    }
+
+    assert!(game.export_quantized_strategy().is_err());
+    game.allocate_memory(false);
+    assert!(!game.is_lazy_allocation_enabled());
+    assert_eq!(game.lazy_memory_usage(), 0);
+}
+
+#[test]
+fn recompute_evs() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.recompute_evs().is_err());
+    solve(&mut game, 50, 0.0, false);
+
+    let river = card_from_str("2s").unwrap() as usize;
+    let history = [1, 1, river];
+    let collect = |game: &mut PostFlopGame| {
+        game.cache_normalized_weights();
+        [
+            game.expected_values(0),
+            game.expected_values(1),
+            game.expected_values_detail(game.current_player()),
+            game.equity(0),
+        ]
+    };
+
+    game.apply_history(&history);
+    let expected = collect(&mut game);
+
+    // emulate a strategy-only save by discarding the counterfactual values
+    game.storage2.iter_mut().for_each(|x| *x = 0);
+    game.storage_ip.iter_mut().for_each(|x| *x = 0);
+    game.storage_chance.iter_mut().for_each(|x| *x = 0);
+    game.apply_history(&history);
+    assert_ne!(collect(&mut game), expected);
+
+    game.recompute_evs().unwrap();
+    assert_eq!(game.history(), &history);
+    assert_eq!(collect(&mut game), expected);
+}
+
+#[test]
+fn edit_strategies() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.edit_strategy(&[], &[]).is_err());
+    solve(&mut game, 50, 0.0, false);
+    let exploitability = compute_exploitability(&game);
+
+    let num_hands = [0, 1].map(|player| game.num_private_hands(player));
+    let num_actions = game.available_actions().len();
+
+    // chance node and invalid strategies
+    assert!(game.edit_strategy(&[0, 0], &[]).is_err());
+    assert!(game.edit_strategy(&[], &[]).is_err());
+    let mut negative = vec![0.0; num_actions * num_hands[0]];
+    negative[0] = -1.0;
+    assert!(game.edit_strategy(&[], &negative).is_err());
+
+    // OOP always checks at the root; IP bets half of the time after the check
+    let mut check = vec![0.0; num_actions * num_hands[0]];
+    check[..num_hands[0]].fill(2.0);
+    game.apply_history(&[0]);
+    let num_actions_ip = game.available_actions().len();
+    let mut bet = vec![0.0; num_actions_ip * num_hands[1]];
+    bet[..num_hands[1]].fill(1.0);
+    bet[num_hands[1]..2 * num_hands[1]].fill(1.0);
+    game.back_to_root();
+
+    let edits = [
+        StrategyEdit {
+            history: vec![],
+            strategy: check,
+        },
+        StrategyEdit {
+            history: vec![0],
+            strategy: bet,
+        },
+        StrategyEdit {
+            history: vec![1],
+            strategy: vec![],
+        },
+    ];
+
+    // an invalid edit discards the whole batch
+    let strategy = game.strategy();
+    assert!(game.edit_strategies(&edits).is_err());
+    assert!(!game.has_pending_strategy_edits());
+    assert_eq!(game.strategy(), strategy);
+
+    game.apply_history(&[0]);
+    game.edit_strategies(&edits[..2]).unwrap();
+    assert_eq!(game.history(), &[0]);
+    assert!(game.has_pending_strategy_edits());
+    assert!(!game.is_current_node_finalized());
+
+    let strategy = game.strategy();
+    assert!(strategy[..2 * num_hands[1]]
+        .iter()
+        .all(|&x| (x - 0.5).abs() < 1e-6));
+    assert!(strategy[2 * num_hands[1]..].iter().all(|&x| x == 0.0));
+
+    game.back_to_root();
+    let strategy = game.strategy();
+    assert!(strategy[..num_hands[0]].iter().all(|&x| x == 1.0));
+    assert!(strategy[num_hands[0]..].iter().all(|&x| x == 0.0));
+
+    let new_exploitability = game.recompute_evs_and_exploitability().unwrap();
+    assert!(new_exploitability > exploitability);
+    assert_eq!(new_exploitability, compute_exploitability(&game));
+    assert!(!game.has_pending_strategy_edits());
+    assert!(game.is_current_node_finalized());
+
+    game.cache_normalized_weights();
+    let ev = [0, 1].map(|player| {
+        compute_average(
+            &game.expected_values(player),
+            game.normalized_weights(player),
+        )
+    });
+    let expected = compute_current_ev(&game);
+    for player in 0..2 {
+        assert!((ev[player] - (expected[player] + 30.0)).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn verify_postflop_game() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 300,
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(verify_game_impl(&mut game).is_err());
+
+    game.allocate_memory(false);
+    verify_game_impl(&mut game).unwrap();
+    game.allocate_memory(true);
+    verify_game_impl(&mut game).unwrap();
+    game.allocate_memory_lazy(false, 0.0);
+    verify_game_impl(&mut game).unwrap();
+}
+
+#[test]
+fn staged_finalization() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 300,
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        for t in 0..20 {
+            solve_step(&game, t);
+        }
+        game
+    };
+
+    let mut reference = new_game();
+    finalize(&mut reference);
+
+    let mut game = new_game();
+    assert!(!game.is_finalizing());
+    assert_eq!(game.finalization_progress_percent(), 0);
+    game.start_finalization();
+    assert!(game.is_finalizing());
+
+    // the turn subtree after the check-check line
+    let history = [0, 0, card_from_str("2c").unwrap() as usize];
+    let collect = |game: &mut PostFlopGame| {
+        game.apply_history(&history);
+        game.cache_normalized_weights();
+        [
+            game.expected_values(0),
+            game.expected_values(1),
+            game.expected_values_detail(0),
+        ]
+    };
+
+    let mut num_steps = 0;
+    let mut progress = 0;
+    let mut checked = false;
+    while !game.finalize_step() {
+        num_steps += 1;
+        assert!(game.finalization_progress_percent() >= progress);
+        progress = game.finalization_progress_percent();
+
+        game.back_to_root();
+        assert!(!game.is_current_node_finalized());
+        game.apply_history(&history);
+        if game.is_current_node_finalized() && !checked {
+            assert_eq!(collect(&mut game), collect(&mut reference));
+            checked = true;
+        }
+    }
+
+    assert!(checked);
+    assert!(num_steps > 1);
+    assert!(!game.is_finalizing());
+    assert_eq!(game.finalization_progress_percent(), 100);
+
+    game.back_to_root();
+    reference.back_to_root();
+    game.cache_normalized_weights();
+    reference.cache_normalized_weights();
+    assert_eq!(game.expected_values(0), reference.expected_values(0));
+    assert_eq!(game.expected_values(1), reference.expected_values(1));
+}
+
+#[test]
+fn parallel_grain() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 300,
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let solve_with = |grain: Option<usize>| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game.set_parallel_grain(grain);
+        let num_parallel_nodes = game.num_parallel_nodes();
+        solve(&mut game, 20, 0.0, false);
+        game.play(1);
+        (num_parallel_nodes, game.strategy())
+    };
+
+    let (num_auto, strategy_auto) = solve_with(None);
+    let (num_none, strategy_none) = solve_with(Some(usize::MAX));
+    let (num_all, strategy_all) = solve_with(Some(1));
+
+    assert_eq!(num_none, 0);
+    assert!(num_auto <= num_all);
+    if cfg!(feature = "rayon") {
+        assert!(num_auto > 0);
+        assert!(num_auto < num_all);
+    }
+
+    // the granularity does not affect the results
+    assert_eq!(strategy_auto, strategy_none);
+    assert_eq!(strategy_auto, strategy_all);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn benchmark_iteration() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let new_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut game = new_game();
+    assert!(game.benchmark_iteration(&[], 1).is_err());
+    assert!(game.benchmark_iteration(&[0], 1).is_err());
+    assert!(game.benchmark_iteration(&[1], 0).is_err());
+
+    let report = game.benchmark_iteration(&[1, 2], 2).unwrap();
+    assert_eq!(report.samples.len(), 2);
+    assert_eq!(report.samples[0].num_threads, 1);
+    assert_eq!(report.samples[0].speedup, 1.0);
+    assert!(report
+        .samples
+        .iter()
+        .all(|sample| sample.seconds_per_iteration > 0.0));
+    assert!([1, 2].contains(&report.optimal_num_threads));
+
+    // the storage is reset, so the solution is the same as without the benchmark
+    let mut fresh = new_game();
+    solve(&mut game, 10, 0.0, false);
+    solve(&mut fresh, 10, 0.0, false);
+    assert_eq!(game.strategy(), fresh.strategy());
+
+    assert!(game.benchmark_iteration(&[1], 1).is_err());
+}
+
+#[test]
+fn game_state() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert_eq!(game.game_state(), GameState::TreeBuilt);
+    assert!(game.check_operation(GameOperation::AllocateMemory).is_ok());
+    assert!(game.check_operation(GameOperation::Navigate).is_err());
+
+    game.allocate_memory(false);
+    assert_eq!(game.game_state(), GameState::MemoryAllocated);
+    assert!(game.check_operation(GameOperation::Benchmark).is_ok());
+
+    solve_step(&game, 0);
+    assert_eq!(game.game_state(), GameState::Solving);
+    assert!(game.check_operation(GameOperation::LockStrategy).is_ok());
+    assert_eq!(
+        game.check_operation(GameOperation::Benchmark),
+        Err(StateError {
+            operation: GameOperation::Benchmark,
+            state: GameState::Solving,
+        })
+    );
+    assert!(game.check_operation(GameOperation::AllocateMemory).is_err());
+
+    game.start_finalization();
+    assert_eq!(game.game_state(), GameState::Finalizing);
+    assert!(!game.is_ready());
+    assert!(game.check_operation(GameOperation::Solve).is_err());
+    assert!(game.check_operation(GameOperation::LockStrategy).is_err());
+    assert!(game
+        .check_operation(GameOperation::QueryExpectedValues)
+        .is_err());
+    assert!(game.check_operation(GameOperation::QueryStrategy).is_ok());
+
+    while !game.finalize_step() {}
+    assert_eq!(game.game_state(), GameState::Solved);
+    assert!(game
+        .check_operation(GameOperation::QueryExpectedValues)
+        .is_ok());
+    assert!(game.check_operation(GameOperation::Solve).is_err());
+    assert!(game.recompute_evs().is_ok());
+
+    // reallocating the memory discards the progress
+    let action_tree = ActionTree::new(game.tree_config().clone()).unwrap();
+    let mut game = PostFlopGame::with_config(game.card_config().clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    solve_step(&game, 0);
+    game.allocate_memory(true);
+    assert_eq!(game.game_state(), GameState::MemoryAllocated);
+}
+
+#[test]
+fn game_state_errors() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let state_error = |operation, state| Err(Error::State(StateError { operation, state }));
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert_eq!(
+        game.try_play(0),
+        state_error(GameOperation::Navigate, GameState::TreeBuilt)
+    );
+    assert_eq!(
+        game.try_lock_current_strategy(&[]),
+        state_error(GameOperation::LockStrategy, GameState::TreeBuilt)
+    );
+
+    game.try_allocate_memory(false).unwrap();
+    let num_actions = game.available_actions().len();
+    assert!(matches!(game.try_play(num_actions), Err(Error::Tree(_))));
+    assert!(matches!(
+        game.try_apply_history(&[1, 52]),
+        Err(Error::Tree(_))
+    ));
+    assert!(matches!(
+        game.lock_strategy(&[num_actions], 0, &[]),
+        Err(Error::Tree(_))
+    ));
+    game.back_to_root();
+    assert!(matches!(
+        game.try_lock_current_strategy(&[1.0]),
+        Err(Error::Config(_))
+    ));
+
+    solve_step(&game, 0);
+    game.start_finalization();
+    assert_eq!(
+        try_solve_step_with_config(&game, 1, &SolverConfig::default()),
+        state_error(GameOperation::Solve, GameState::Finalizing)
+    );
+    assert_eq!(
+        game.try_allocate_memory(false),
+        state_error(GameOperation::AllocateMemory, GameState::Finalizing)
+    );
+    assert_eq!(
+        game.try_unlock_current_strategy(),
+        state_error(GameOperation::LockStrategy, GameState::Finalizing)
+    );
+
+    while !game.finalize_step() {}
+    assert!(game.try_play(0).is_ok());
+    assert_eq!(
+        try_solve(&mut game, 10, 0.0, false).err(),
+        Some(Error::State(StateError {
+            operation: GameOperation::Solve,
+            state: GameState::Solved,
+        }))
+    );
+}
+
+#[test]
+fn exploitability_with_callback() {
+    let card_config = CardConfig {
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,98s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let bet_sizes = crate::BetSizeOptions::try_from(("60%, a", "")).unwrap();
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 300,
+        flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve_step(&game, 0);
+
+    let mut progress = Vec::new();
+    let exploitability = compute_exploitability_with_callback(&game, |percent| {
+        progress.push(percent);
+        true
+    });
+    assert_eq!(exploitability, Some(compute_exploitability(&game)));
+    assert_eq!(progress.last(), Some(&100));
+    assert!(progress.windows(2).all(|w| w[0] <= w[1]));
+
+    // aborted at the first call
+    let mut num_calls = 0;
+    let exploitability = compute_exploitability_with_callback(&game, |_| {
+        num_calls += 1;
+        false
+    });
+    if num_calls > 0 && exploitability.is_some() {
+        // the only call is the final one after the completion
+        assert_eq!(num_calls, 1);
+    }
+}
+
+#[test]
+fn strategy_only() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(true);
+    solve(&mut game, 20, 0.0, false);
+    let exploitability = compute_exploitability(&game);
+
+    game.cache_normalized_weights();
+    let expected = game.expected_values(0);
+
+    // emulate a strategy-only load by releasing the storage of the counterfactual values
+    game.storage2 = Storage::default();
+    game.storage_ip = Storage::default();
+    game.storage_chance = Storage::default();
+    game.is_strategy_only = true;
+    game.lock_decoded_cache().clear();
+
+    assert_eq!(compute_exploitability(&game), exploitability);
+    assert!(!game.is_current_node_finalized());
+    assert!(game
+        .check_operation(GameOperation::QueryExpectedValues)
+        .is_err());
+
+    game.play(1);
+    game.back_to_root();
+
+    game.recompute_evs().unwrap();
+    assert!(!game.is_strategy_only());
+    game.cache_normalized_weights();
+    assert_eq!(game.expected_values(0), expected);
+}
+
+#[test]
+fn action_translation_table() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%, a", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.action_translation_table().is_err());
+
+    game.allocate_memory(false);
+    solve(&mut game, 20, 0.0, false);
+    game.apply_history(&[0]);
+
+    let table = game.action_translation_table().unwrap();
+    assert_eq!(game.history(), &[0]);
+    assert!(!table.is_empty());
+    assert_eq!(table.num_collisions, 0);
+
+    // turn: OOP bets 50%, IP to act
+    game.apply_history(&[1]);
+    let hand_index = 3;
+    let hand = game.private_cards(1)[hand_index];
+    let strategy = game.strategy();
+    let num_hands = game.num_private_hands(1);
+
+    let key = ActionTranslationTable::key(
+        BoardState::Turn,
+        game.pot() as f64 / 60.0,
+        *game.remaining_stacks().iter().min().unwrap() as f64 / game.pot() as f64,
+        &[Action::Bet(30)],
+        hand,
+    );
+
+    let (node, probabilities) = table.lookup(&key).unwrap();
+    assert_eq!(node.actions, game.available_actions());
+    assert_eq!(node.pot, game.pot());
+    for (i, &p) in probabilities.iter().enumerate() {
+        assert!((p - strategy[i * num_hands + hand_index]).abs() < 0.01);
+    }
+
+    // an off-tree bet size in the same bucket is translated to the same node
+    let off_tree_key = ActionTranslationTable::key(
+        BoardState::Turn,
+        game.pot() as f64 / 60.0 * 1.02,
+        *game.remaining_stacks().iter().min().unwrap() as f64 / game.pot() as f64,
+        &[Action::Bet(31)],
+        hand,
+    );
+    assert_eq!(table.lookup(&off_tree_key).unwrap().1, probabilities);
+
+    // the key of a different line is not found
+    let mut other = key;
+    other.line_hash = ActionTranslationTable::line_hash(&[Action::Check, Action::Check]);
+    assert!(table.lookup(&other).is_none());
+}
+
+#[test]
+fn nodes_iterator() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let nodes = game.nodes().collect::<Vec<_>>();
+    assert_eq!(nodes.len(), game.node_arena.len());
+
+    let mut indices = nodes.iter().map(|n| n.node_index).collect::<Vec<_>>();
+    indices.sort_unstable();
+    assert_eq!(indices, (0..game.node_arena.len()).collect::<Vec<_>>());
+
+    let root = &nodes[0];
+    assert_eq!(root.node_index, 0);
+    assert_eq!(root.street, BoardState::Turn);
+    assert_eq!(root.player, Some(0));
+    assert!(root.line.is_empty());
+    assert_eq!(root.actions, game.available_actions());
+
+    // the metadata agrees with the interpreter
+    game.allocate_memory(false);
+    for node in nodes.iter().filter(|n| n.line.len() <= 3) {
+        let mut history = Vec::new();
+        game.back_to_root();
+        for &action in &node.line {
+            let index = match action {
+                Action::Chance(card) => card as usize,
+                _ => game
+                    .available_actions()
+                    .iter()
+                    .position(|&a| a == action)
+                    .unwrap(),
+            };
+            history.push(index);
+            game.play(index);
+        }
+
+        assert_eq!(node.actions, game.available_actions());
+        if game.is_terminal_node() || game.is_chance_node() {
+            assert_eq!(node.player, None);
+        } else {
+            assert_eq!(node.player, Some(game.current_player()));
+            assert_eq!(node.street as usize + 3, game.current_board().len());
+        }
+    }
+}
+
+#[test]
+fn seek_to_node() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.seek_to_node(0).is_err());
 
     game.allocate_memory(false);
-    game.play(1); // all-in
-    game.lock_current_strategy(&[0.25, 0.75]); // 25% fold, 75% call
-    game.back_to_root();
+    assert!(game.seek_to_node(game.node_arena.len()).is_err());
+    assert_eq!(game.path_to_node(game.node_arena.len()), None);
+    assert_eq!(game.path_to_node(0), Some(Vec::new()));
+
+    let nodes = game.nodes().collect::<Vec<_>>();
+    for node in nodes.iter().step_by(7) {
+        game.seek_to_node(node.node_index).unwrap();
+        assert_eq!(game.current_node_index(), node.node_index);
+        assert_eq!(game.available_actions(), node.actions);
+
+        // the weights match the ones reached by `apply_history`
+        let history = game.history().to_vec();
+        let weights = [game.weights(0).to_vec(), game.weights(1).to_vec()];
+        game.back_to_root();
+        game.apply_history(&history);
+        assert_eq!(game.current_node_index(), node.node_index);
+        assert_eq!(game.weights(0), weights[0]);
+        assert_eq!(game.weights(1), weights[1]);
+    }
+}
 
-    solve(&mut game, 1000, 0.0, false);
-    game.cache_normalized_weights();
+#[test]
+fn street_targets() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
 
-    let ev_oop = game.expected_values(0);
-    let ev_ip = game.expected_values(1);
-    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
-    assert!((ev_oop[1] - 27.5).abs() < 5e-2);
-    assert!((ev_ip[0] - 6.25).abs() < 1e-2);
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
 
-    let strategy_oop = game.strategy();
-    assert!((strategy_oop[0] - 1.0).abs() < 1e-3); // QQ check
-    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
-    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // QQ bet
-    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
 
+    assert!(game.set_street_targets(Some(-1.0), None).is_err());
+    assert!(game.set_street_targets(None, Some(f32::NAN)).is_err());
+    assert!(game.set_street_targets(Some(1.0), Some(0.5)).is_err());
+    assert_eq!(game.street_targets(), [None, None]);
+
+    // the river is frozen at the first check and keeps the uniform strategy
+    game.set_street_targets(None, Some(f32::INFINITY)).unwrap();
     game.allocate_memory(false);
-    game.play(1); // all-in
-    game.lock_current_strategy(&[0.5, 0.5]); // 50% fold, 50% call
-    game.back_to_root();
+    assert_eq!(game.frozen_street(), None);
+    let exploitability = solve(&mut game, 50, 0.0, false).exploitability;
+    assert!(exploitability.is_finite());
+    assert_eq!(game.frozen_street(), Some(BoardState::River));
 
-    solve(&mut game, 1000, 0.0, false);
-    game.cache_normalized_weights();
+    let root_strategy = game.strategy();
+    assert!(root_strategy.iter().any(|&x| (x - 0.5).abs() > 1e-3));
 
-    let ev_oop = game.expected_values(0);
-    let ev_ip = game.expected_values(1);
-    assert!((ev_oop[0] - 5.0).abs() < 1e-2);
-    assert!((ev_oop[1] - 25.0).abs() < 5e-2);
-    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+    game.play(0);
+    game.play(0);
+    game.play(usize::MAX);
+    let num_actions = game.available_actions().len();
+    let strategy = game.strategy();
+    assert!(strategy
+        .iter()
+        .all(|&x| (x - 1.0 / num_actions as f32).abs() < 1e-6));
+
+    // the frozen streets are reset when the memory is allocated
+    let mut game = PostFlopGame::with_config(
+        game.card_config().clone(),
+        ActionTree::new(game.tree_config().clone()).unwrap(),
+    )
+    .unwrap();
+    game.set_street_targets(Some(0.0), None).unwrap();
+    game.allocate_memory(false);
+    let exploitability = solve(&mut game, 30, 0.0, false).exploitability;
+    assert_eq!(game.frozen_street(), None);
 
-    let strategy_oop = game.strategy();
-    assert!((strategy_oop[0] - 0.0).abs() < 1e-3); // QQ check
-    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
-    assert!((strategy_oop[2] - 1.0).abs() < 1e-3); // QQ bet
-    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+    game.set_street_targets(None, Some(exploitability * 2.0))
+        .unwrap();
+    game.allocate_memory(false);
+    let frozen_exploitability = solve(&mut game, 30, 0.0, false).exploitability;
+    assert_eq!(game.frozen_street(), Some(BoardState::River));
+    assert!(frozen_exploitability.is_finite());
 }
 
 #[test]
-fn node_locking_partial() {
+fn reset_player() {
     let card_config = CardConfig {
-        range: ["AsAh,QsQh,JsJh".parse().unwrap(), "KsKh".parse().unwrap()],
-        flop: flop_from_str("2s3h4d").unwrap(),
-        turn: card_from_str("6c").unwrap(),
-        river: card_from_str("7c").unwrap(),
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    for compression in [false, true] {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        assert!(game.reset_player(0).is_err());
+
+        game.allocate_memory(compression);
+        for t in 0..50 {
+            solve_step(&game, t);
+        }
+
+        assert!(game.reset_player(2).is_err());
+        game.reset_player(1).unwrap();
+        assert_eq!(game.game_state(), GameState::Solving);
+
+        // OOP keeps the strategy, and IP is back to the uniform strategy
+        let oop_strategy = game.strategy();
+        assert!(oop_strategy.iter().any(|&x| (x - 0.5).abs() > 1e-3));
+        game.play(0);
+        let num_actions = game.available_actions().len();
+        let ip_strategy = game.strategy();
+        assert!(ip_strategy
+            .iter()
+            .all(|&x| (x - 1.0 / num_actions as f32).abs() < 1e-6));
+
+        // IP adapts from scratch
+        for t in 0..50 {
+            solve_step(&game, t);
+        }
+        assert!(game.strategy().iter().any(|&x| (x - 0.5).abs() > 1e-3));
+
+        finalize(&mut game);
+        assert!(game.reset_player(1).is_err());
+    }
+}
+
+#[test]
+fn num_iterations() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
     };
 
     let tree_config = TreeConfig {
         initial_state: BoardState::River,
-        starting_pot: 10,
-        effective_stack: 10,
-        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert_eq!(game.num_iterations(), 0);
+
+    for t in 0..10 {
+        solve_step(&game, t);
+    }
+    assert_eq!(game.num_iterations(), 10);
+
+    // the count is reset when the memory is reallocated
+    game.allocate_memory(true);
+    assert_eq!(game.num_iterations(), 0);
+}
+
+#[test]
+fn evaluate_archetypes() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    assert!(game.evaluate_archetypes(0, &Archetype::presets()).is_err());
+
+    solve(&mut game, 1000, 0.001, false);
+    let root_strategy = game.strategy();
+    let current_ev = compute_current_ev(&game);
+
+    let invalid = Archetype {
+        fold: -1.0,
+        ..Archetype::new("invalid")
+    };
+    assert!(game.evaluate_archetypes(0, &[invalid]).is_err());
+    assert!(game.evaluate_archetypes(2, &[]).is_err());
+
+    let mut archetypes = Archetype::presets();
+    archetypes.push(Archetype::new("equilibrium"));
+    let report = game.evaluate_archetypes(0, &archetypes).unwrap();
+
+    assert_eq!(report.player, 0);
+    assert_eq!(report.equilibrium_ev, current_ev[0]);
+    assert_eq!(report.archetypes.len(), 4);
+
+    let tolerance = 0.01 * 60.0;
+    for result in &report.archetypes {
+        assert!(result.gain > -tolerance);
+        assert!(result.best_response_ev >= result.ev - tolerance);
+    }
+
+    // the unbiased archetype plays the solved strategy
+    let equilibrium = &report.archetypes[3];
+    assert!(equilibrium.gain.abs() < 1e-4);
+    assert!((equilibrium.best_response_ev - equilibrium.ev).abs() < tolerance);
+
+    // the biased archetypes are exploitable
+    for result in &report.archetypes[..3] {
+        assert!(result.best_response_ev > report.equilibrium_ev + 0.1);
+    }
+
+    let worst = report.worst().unwrap();
+    assert!(report.archetypes.iter().all(|r| r.gain >= worst.gain));
+
+    // the strategies are restored
+    assert_eq!(game.strategy(), root_strategy);
+    assert_eq!(compute_current_ev(&game), current_ev);
+
+    let report = game.evaluate_archetypes(1, &[]).unwrap();
+    assert_eq!(report.equilibrium_ev, current_ev[1]);
+    assert!(report.worst().is_none());
+}
+
+#[test]
+fn solver_config() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let new_game = |compressed| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(compressed);
+        game
+    };
+
+    let root_regrets = |game: &PostFlopGame| game.node_arena[0].lock().regrets().to_vec();
+
+    // the default configuration is the one used by `solve_step`
+    let default = new_game(false);
+    let configured = new_game(false);
+    for t in 0..20 {
+        solve_step(&default, t);
+        solve_step_with_config(&configured, t, &SolverConfig::default());
+    }
+    assert_eq!(root_regrets(&default), root_regrets(&configured));
+
+    // the same seed produces the same samples
+    let config = SolverConfig {
+        algorithm: SolverAlgorithm::ChanceSamplingMccfr { seed: 7 },
+        ..Default::default()
+    };
+    let sampled = [new_game(false), new_game(false)];
+    for t in 0..20 {
+        sampled
+            .iter()
+            .for_each(|game| solve_step_with_config(game, t, &config));
+    }
+    assert_eq!(root_regrets(&sampled[0]), root_regrets(&sampled[1]));
+    assert_ne!(root_regrets(&sampled[0]), root_regrets(&default));
+
+    // every algorithm converges
+    for compressed in [false, true] {
+        for (algorithm, max_num_iterations, target) in [
+            (SolverAlgorithm::default(), 1000, 0.3),
+            (
+                SolverAlgorithm::Dcfr {
+                    alpha: 1.5,
+                    beta: 0.5,
+                    gamma: 2.0,
+                },
+                1000,
+                0.3,
+            ),
+            (SolverAlgorithm::CfrPlus, 1000, 0.3),
+            (SolverAlgorithm::LinearCfr, 1000, 0.3),
+            (SolverAlgorithm::VanillaCfr, 3000, 1.0),
+            (SolverAlgorithm::ChanceSamplingMccfr { seed: 1 }, 3000, 1.5),
+        ] {
+            // the exploitability of the sampled algorithm fluctuates, so it is checked regularly
+            let mut game = new_game(compressed);
+            let config = SolverConfig {
+                algorithm,
+                check_schedule: CheckSchedule::Fixed(10),
+                ..Default::default()
+            };
+            let exploitability =
+                solve_with_config(&mut game, max_num_iterations, target, &config, false);
+            assert!(exploitability <= target, "{algorithm:?}: {exploitability}");
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn solver_config_invalid() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,JJ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        ..Default::default()
+    };
 
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
     game.allocate_memory(false);
-    game.lock_current_strategy(&[0.8, 0.0, 0.0, 0.2, 0.0, 0.0]); // JJ -> 80% check, 20% all-in
 
-    solve(&mut game, 1000, 0.0, false);
-    game.cache_normalized_weights();
+    let config = SolverConfig {
+        algorithm: SolverAlgorithm::Dcfr {
+            alpha: 1.5,
+            beta: 0.0,
+            gamma: -1.0,
+        },
+        ..Default::default()
+    };
+    solve_step_with_config(&game, 0, &config);
+}
 
-    let ev_oop = game.expected_values(0);
-    let ev_ip = game.expected_values(1);
-    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
-    assert!((ev_oop[1] - 0.0).abs() < 1e-2);
-    assert!((ev_oop[2] - 15.0).abs() < 5e-2);
-    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+#[test]
+fn try_solver_config_invalid() {
+    let card_config = CardConfig {
+        range: ["AA,KK".parse().unwrap(), "QQ,JJ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
 
-    let strategy_oop = game.strategy();
-    assert!((strategy_oop[0] - 0.8).abs() < 1e-3); // JJ check
-    assert!((strategy_oop[1] - 0.7).abs() < 1e-3); // QQ check
-    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // AA check
-    assert!((strategy_oop[3] - 0.2).abs() < 1e-3); // JJ bet
-    assert!((strategy_oop[4] - 0.3).abs() < 1e-3); // QQ bet
-    assert!((strategy_oop[5] - 1.0).abs() < 1e-3); // AA bet
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    let configs = [
+        SolverConfig {
+            algorithm: SolverAlgorithm::Dcfr {
+                alpha: f32::NAN,
+                beta: 0.0,
+                gamma: 2.0,
+            },
+            ..Default::default()
+        },
+        SolverConfig {
+            alternation: Alternation::Ratio { player: 0, k: 0 },
+            ..Default::default()
+        },
+        SolverConfig {
+            check_schedule: CheckSchedule::Fixed(0),
+            ..Default::default()
+        },
+    ];
+
+    for config in &configs {
+        let result = try_solve_with_config(&mut game, 10, 0.0, config, false);
+        assert!(matches!(result, Err(Error::Config(_))), "{config:?}");
+        let result = try_solve_step_with_config(&game, 0, config);
+        assert!(matches!(result, Err(Error::Config(_))), "{config:?}");
+        let result = solve_steps(&game, 0, 1, config);
+        assert!(matches!(result, Err(Error::Config(_))), "{config:?}");
+    }
+}
+
+#[test]
+fn solver_check_finite() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("7s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let config = SolverConfig {
+        check_finite: true,
+        ..Default::default()
+    };
+
+    for compressed in [false, true] {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(compressed);
+
+        for t in 0..5 {
+            try_solve_step_with_config(&game, t, &config).unwrap();
+        }
+
+        // poison the node after the check of OOP
+        {
+            let root = game.node_arena[0].lock();
+            let mut node = root.play(0);
+            if compressed {
+                node.set_regret_scale(f32::NAN);
+            } else {
+                node.regrets_mut()[0] = f32::NAN;
+            }
+        }
+
+        let Err(Error::NonFinite(err)) = try_solve_step_with_config(&game, 5, &config) else {
+            panic!("non-finite value is not reported");
+        };
+        assert_eq!(err.iteration, 5);
+        assert_eq!(err.player, 1);
+        assert_eq!(err.path, vec![0]);
+        assert_eq!(err.field, NonFiniteField::Regrets);
+
+        // the error is not reported without the check
+        solve_step(&game, 6);
+    }
+}
+
+#[test]
+fn node_snapshot() {
+    let card_config = CardConfig {
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        river: card_from_str("2s").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 300,
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(NodeSnapshot::capture(&game).is_err());
+
+    game.allocate_memory(false);
+    assert!(NodeSnapshot::capture(&game).is_err());
+
+    game.cache_normalized_weights();
+    let snapshot = NodeSnapshot::capture(&game).unwrap();
+    assert_eq!(snapshot.player, Some(0));
+    assert_eq!(snapshot.strategy, game.strategy());
+    assert!(snapshot.expected_values[0].is_empty());
+
+    solve(&mut game, 100, 0.0, false);
+    game.play(1);
+    game.cache_normalized_weights();
+    let snapshot = NodeSnapshot::capture(&game).unwrap();
+    assert_eq!(snapshot.history, [1]);
+    assert_eq!(snapshot.board.len(), 5);
+    assert_eq!(snapshot.pot, 90);
+    assert_eq!(snapshot.total_bet_amount, [30, 0]);
+    assert_eq!(snapshot.player, Some(1));
+    assert_eq!(snapshot.actions, game.available_actions());
+    assert_eq!(snapshot.private_cards[1], game.private_cards(1));
+    assert_eq!(snapshot.normalized_weights[0], game.normalized_weights(0));
+    assert_eq!(snapshot.strategy, game.strategy());
+    assert_eq!(snapshot.expected_values[1], game.expected_values(1));
+
+    game.play(0);
+    game.cache_normalized_weights();
+    let snapshot = NodeSnapshot::capture(&game).unwrap();
+    assert!(snapshot.is_terminal);
+    assert_eq!(snapshot.player, None);
+    assert!(snapshot.actions.is_empty() && snapshot.strategy.is_empty());
 }
 
 #[test]
-fn node_locking_isomorphism() {
+fn navigation_by_hand() {
     let card_config = CardConfig {
-        range: ["AKs".parse().unwrap(), "AKs".parse().unwrap()],
-        flop: flop_from_str("2c3c4c").unwrap(),
+        range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        starting_pot: 10,
-        effective_stack: 10,
-        river_bet_sizes: [("a", "").try_into().unwrap(), Default::default()],
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 300,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    assert!(game.play_action(Action::Check).is_err());
 
     game.allocate_memory(false);
-    game.apply_history(&[0, 0, 15, 0, 0, 14]); // Turn: Spades, River: Hearts
-    game.lock_current_strategy(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]); // AhKh -> check
+    solve(&mut game, 100, 0.0, false);
 
-    finalize(&mut game);
+    let ak = (card_from_str("Ks").unwrap(), card_from_str("As").unwrap());
+    let index = game.private_hand_index(0, ak.1, ak.0).unwrap();
+    assert_eq!(game.private_cards(0)[index], ak);
+    assert_eq!(game.private_hand_index(1, ak.0, ak.1), None);
 
-    game.apply_history(&[0, 0, 13, 0, 0, 14]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5]
-    );
+    let strategy = game.strategy_for_hand(ak.0, ak.1).unwrap();
+    let num_hands = game.private_cards(0).len();
+    assert_eq!(strategy.len(), game.available_actions().len());
+    assert_eq!(strategy[1], game.strategy()[num_hands + index]);
+    assert!((strategy.iter().sum::<f32>() - 1.0).abs() < 1e-5);
 
-    game.apply_history(&[0, 0, 13, 0, 0, 15]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0]
-    );
+    let qq = (card_from_str("Qd").unwrap(), card_from_str("Qc").unwrap());
+    assert!(game.strategy_for_hand(qq.0, qq.1).is_err());
 
-    game.apply_history(&[0, 0, 14, 0, 0, 13]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5, 0.5]
-    );
+    assert!(game.expected_value_for_hand(0, ak.0, ak.1).is_err());
+    game.cache_normalized_weights();
+    let ev = game.expected_value_for_hand(0, ak.0, ak.1).unwrap();
+    assert_eq!(ev, game.expected_values(0)[index]);
 
-    game.apply_history(&[0, 0, 14, 0, 0, 15]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0]
-    );
+    assert!(game.play_action(Action::Bet(1)).is_err());
+    game.play_action(Action::Bet(30)).unwrap();
+    game.play_action(Action::Call).unwrap();
+    assert!(game.is_chance_node());
+    assert!(game.play_action(Action::Check).is_err());
+    let deal = |card| Action::Chance(card_from_str(card).unwrap());
+    assert!(game.play_action(deal("Td")).is_err());
+    game.play_action(deal("2s")).unwrap();
+    assert_eq!(game.current_board().len(), 5);
+    assert_eq!(game.current_player(), 0);
+}
 
-    game.apply_history(&[0, 0, 15, 0, 0, 13]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5, 0.5]
-    );
+#[test]
+fn storage_backend() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingStorage(AtomicUsize);
+
+    impl StorageBackend for CountingStorage {
+        fn allocate(&self, num_bytes: usize) -> std::io::Result<Box<dyn StorageBuffer>> {
+            self.0.fetch_add(num_bytes, Ordering::Relaxed);
+            HeapStorage.allocate(num_bytes)
+        }
+    }
+
+    let spot = CanonicalSpot::srp_q9572_river_50bb();
+    let solve_with = |backend: Option<Arc<dyn StorageBackend>>| {
+        let mut game = spot.build().unwrap();
+        if let Some(backend) = backend {
+            game.set_storage_backend(backend).unwrap();
+        }
+        game.allocate_memory(false);
+        assert!(game.set_storage_backend(Arc::new(HeapStorage)).is_err());
+        solve(&mut game, 50, 0.0, false);
+        (compute_exploitability(&game), game.strategy())
+    };
 
-    game.apply_history(&[0, 0, 15, 0, 0, 14]);
-    assert_eq!(
-        game.strategy(),
-        vec![0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.0, 0.5]
-    );
+    let expected = solve_with(None);
+
+    let counting = Arc::new(CountingStorage(AtomicUsize::new(0)));
+    assert_eq!(solve_with(Some(counting.clone())), expected);
+    let game = spot.build().unwrap();
+    let (memory_usage, _) = game.memory_usage();
+    assert!(counting.0.load(Ordering::Relaxed) as u64 <= memory_usage);
+    assert!(counting.0.load(Ordering::Relaxed) > 0);
+
+    #[cfg(feature = "mmap")]
+    {
+        let backend = Arc::new(MmapStorage::new(std::env::temp_dir()));
+        assert_eq!(solve_with(Some(backend)), expected);
+    }
 }
 
 #[test]
-fn set_bunching_effect() {
-    let flop = flop_from_str("Td9d6h").unwrap();
+fn storage_backend_failure() {
+    struct FailingStorage;
+
+    impl StorageBackend for FailingStorage {
+        fn allocate(&self, num_bytes: usize) -> std::io::Result<Box<dyn StorageBuffer>> {
+            if num_bytes == 0 {
+                return HeapStorage.allocate(0);
+            }
+            Err(std::io::Error::new(
+                std::io::ErrorKind::StorageFull,
+                "disk is full",
+            ))
+        }
+    }
+
+    let mut game = CanonicalSpot::srp_q9572_river_50bb().build().unwrap();
+    game.set_storage_backend(Arc::new(FailingStorage)).unwrap();
+    let err = game.try_allocate_memory(false).unwrap_err();
+    assert!(matches!(err, Error::Io(e) if e.kind() == std::io::ErrorKind::StorageFull));
+    assert_eq!(game.is_memory_allocated(), None);
+
+    // the game can be allocated again with a working backend
+    game.set_storage_backend(Arc::new(HeapStorage)).unwrap();
+    game.try_allocate_memory(false).unwrap();
+    assert_eq!(game.is_memory_allocated(), Some(false));
+}
+
+#[test]
+fn compute_equity_matches_game() {
     let card_config = CardConfig {
-        flop,
-        range: [Range::ones(); 2],
-        turn: card_from_str("Qc").unwrap(),
+        range: [
+            "QQ+,AK,T9s,76s".parse().unwrap(),
+            "JJ-88,AQs,KQ,Td9d".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
         initial_state: BoardState::Turn,
-        starting_pot: 60,
-        effective_stack: 970,
-        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        starting_pot: 100,
+        effective_stack: 100,
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-
-    let co_range = "33:0.59,22:0.635,A8o:0.265,A7o-A6o,A5o:0.445,A4o-A2o,K2s,K9o:0.905,K8o-K2o,Q4s-Q2s,Q9o-Q2o,J6s-J2s,J9o:0.88,J8o-J2o,T7s:0.405,T6s-T2s,T9o:0.96,T8o-T2o,96s-92s,92o+,86s:0.57,85s-82s,82o+,76s:0.37,75s-72s,72o+,65s:0.475,64s-62s,62o+,54s:0.68,53s-52s,52o+,42+,32";
-    let sb_range = "66:0.46,55:0.821,44:0.92,33:0.93,22:0.925,A6s:0.73,A3s:0.47,A2s,ATo:0.105,A9o-A2o,K8s:0.795,K7s,K6s:0.85,K5s:0.965,K4s-K2s,KJo:0.085,KTo:0.645,K9o-K2o,Q8s-Q2s,QJo:0.765,QTo-Q2o,J8s-J2s,J2o+,T8s:0.69,T7s-T2s,T2o+,98s:0.905,97s-92s,92o+,87s:0.78,86s-82s,82o+,76s:0.77,75s-72s,72o+,65s:0.845,64s-62s,62o+,54s:0.735,53s-52s,52o+,42+,32";
-
-    let mut bunching_data = BunchingData::new(
-        &[co_range.parse().unwrap(), sb_range.parse().unwrap()],
-        flop,
-    )
-    .unwrap();
-
-    bunching_data.process(false);
-    game.set_bunching_effect(&bunching_data).unwrap();
-
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
     game.allocate_memory(false);
-    finalize(&mut game);
-
-    let current_ev = compute_current_ev(&game);
-    assert!((current_ev[0] - 7.5).abs() < 1e-4);
-    assert!((current_ev[1] - -7.5).abs() < 1e-4);
-
     game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
-    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
 
-    assert!((root_equity_oop - 0.5).abs() < 1e-5);
-    assert!((root_equity_ip - 0.5).abs() < 1e-5);
-    assert!((root_ev_oop - 37.5).abs() < 1e-4);
-    assert!((root_ev_ip - 22.5).abs() < 1e-4);
+    let board = [card_config.flop.as_slice(), &[card_config.turn]].concat();
+    let [oop_range, ip_range] = &card_config.range;
+    let result = compute_equity(oop_range, ip_range, &board).unwrap();
+
+    for player in 0..2 {
+        assert_eq!(result.hands[player], game.private_cards(player));
+        let equity = game.equity(player);
+        let weights = game.normalized_weights(player);
+        for i in 0..equity.len() {
+            assert!((result.weights[player][i] - weights[i]).abs() < 1e-4);
+            if weights[i] > 0.0 {
+                assert!((result.equity[player][i] - equity[i]).abs() < 1e-4);
+            }
+        }
+    }
 }
 
 #[test]
-fn set_bunching_effect_always_win() {
-    let flop = flop_from_str("AcAdKh").unwrap();
-    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
-
+fn evaluate_baselines() {
     let card_config = CardConfig {
-        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
-        flop,
+        range: [
+            "AA,KK,QQ,AK,T9s".parse().unwrap(),
+            "KK-TT,AQ,87s".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
         starting_pot: 60,
-        effective_stack: 970,
+        effective_stack: 300,
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-
-    let co_range = "33:0.59,22:0.635,A8o:0.265,A7o-A6o,A5o:0.445,A4o-A2o,K2s,K9o:0.905,K8o-K2o,Q4s-Q2s,Q9o-Q2o,J6s-J2s,J9o:0.88,J8o-J2o,T7s:0.405,T6s-T2s,T9o:0.96,T8o-T2o,96s-92s,92o+,86s:0.57,85s-82s,82o+,76s:0.37,75s-72s,72o+,65s:0.475,64s-62s,62o+,54s:0.68,53s-52s,52o+,42+,32";
-    let sb_range = "66:0.46,55:0.821,44:0.92,33:0.93,22:0.925,A6s:0.73,A3s:0.47,A2s,ATo:0.105,A9o-A2o,K8s:0.795,K7s,K6s:0.85,K5s:0.965,K4s-K2s,KJo:0.085,KTo:0.645,K9o-K2o,Q8s-Q2s,QJo:0.765,QTo-Q2o,J8s-J2s,J2o+,T8s:0.69,T7s-T2s,T2o+,98s:0.905,97s-92s,92o+,87s:0.78,86s-82s,82o+,76s:0.77,75s-72s,72o+,65s:0.845,64s-62s,62o+,54s:0.735,53s-52s,52o+,42+,32";
-
-    let mut bunching_data = BunchingData::new(
-        &[co_range.parse().unwrap(), sb_range.parse().unwrap()],
-        flop,
-    )
-    .unwrap();
-
-    bunching_data.process(false);
-    game.set_bunching_effect(&bunching_data).unwrap();
-
     game.allocate_memory(false);
-    finalize(&mut game);
+    assert!(game.evaluate_baselines(0, &Baseline::presets()).is_err());
 
-    let current_ev = compute_current_ev(&game);
-    assert!((current_ev[0] - 30.0).abs() < 1e-4);
-    assert!((current_ev[1] - -30.0).abs() < 1e-4);
+    solve(&mut game, 1000, 0.01, false);
+    assert!(game.evaluate_baselines(2, &[]).is_err());
+    assert!(game
+        .evaluate_baselines(0, &[Baseline::FixedBet(-1.0)])
+        .is_err());
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 1.0).abs() < 1e-5);
-    assert!((equity_ip - 0.0).abs() < 1e-5);
-    assert!((ev_oop - 60.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
+    assert_eq!(Baseline::AlwaysCheck.name(), "always-check");
+    assert_eq!(Baseline::cbet_33().name(), "fixed-bet-33%");
 
     game.play(0);
-    game.play(0);
-    assert!(game.is_chance_node());
-    game.play(usize::MAX);
-    game.play(0);
-    game.play(0);
-    assert!(game.is_chance_node());
-    game.play(usize::MAX);
-    game.play(0);
-    game.play(0);
-    assert!(game.is_terminal_node());
-
     game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let equity_oop = compute_average(&game.equity(0), weights_oop);
-    let equity_ip = compute_average(&game.equity(1), weights_ip);
-    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
-    assert!((equity_oop - 1.0).abs() < 1e-5);
-    assert!((equity_ip - 0.0).abs() < 1e-5);
-    assert!((ev_oop - 60.0).abs() < 1e-4);
-    assert!((ev_ip - 0.0).abs() < 1e-4);
+    let history = game.history().to_vec();
+    let strategy = game.strategy();
+    let current_ev = compute_current_ev(&game);
+
+    let tolerance = 0.02 * 60.0;
+    for (player, &current_ev) in current_ev.iter().enumerate() {
+        let mut baselines = Baseline::presets();
+        baselines.push(Baseline::FixedBet(0.5));
+        let report = game.evaluate_baselines(player, &baselines).unwrap();
+
+        assert_eq!(report.player, player);
+        assert_eq!(report.equilibrium_ev, current_ev);
+        assert_eq!(report.baselines.len(), 4);
+
+        for result in &report.baselines {
+            assert!(result.loss > -tolerance);
+            assert!(result.loss > 0.1, "{result:?}");
+            assert!(result.best_response_ev >= -result.ev - tolerance);
+        }
+
+        // the only bet size is the closest to both ratios
+        assert_eq!(report.baselines[2].ev, report.baselines[3].ev);
+    }
+
+    // the strategies, the current node, and the cached weights are restored
+    assert_eq!(game.history(), history);
+    assert_eq!(game.strategy(), strategy);
+    assert_eq!(compute_current_ev(&game), current_ev);
+    game.expected_values(0);
 }
 
 #[test]
-#[ignore]
-fn solve_pio_preset_normal() {
-    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
-    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
-
+fn deterministic_solving() {
     let card_config = CardConfig {
-        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
-        flop: flop_from_str("QsJh2h").unwrap(),
+        range: [
+            "QQ+,AK,T9s,76s".parse().unwrap(),
+            "JJ-88,AQs,KQ,Td9d".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        starting_pot: 180,
-        effective_stack: 910,
-        flop_bet_sizes: [
-            ("52%", "45%").try_into().unwrap(),
-            ("52%", "45%").try_into().unwrap(),
-        ],
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 300,
         turn_bet_sizes: [
-            ("55%", "45%").try_into().unwrap(),
-            ("55%", "45%").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
         ],
         river_bet_sizes: [
-            ("70%", "45%").try_into().unwrap(),
-            ("70%", "45%").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
         ],
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-    println!(
-        "memory usage: {:.2}GB",
-        game.memory_usage().0 as f64 / (1024.0 * 1024.0 * 1024.0)
-    );
-    game.allocate_memory(false);
-
-    solve(&mut game, 1000, 180.0 * 0.001, true);
-
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_equity_oop = compute_average(&game.equity(0), weights_oop);
-    let root_equity_ip = compute_average(&game.equity(1), weights_ip);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    let solve_with = |deterministic: bool| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        let config = SolverConfig {
+            deterministic,
+            ..Default::default()
+        };
+        for t in 0..30 {
+            solve_step_with_config(&game, t, &config);
+        }
+        finalize(&mut game);
+        (game.storage1.to_vec(), game.storage2.to_vec())
+    };
 
-    // verified by PioSOLVER Free
-    assert!((root_equity_oop - 0.55347).abs() < 1e-5);
-    assert!((root_equity_ip - 0.44653).abs() < 1e-5);
-    assert!((root_ev_oop - 105.11).abs() < 0.2);
-    assert!((root_ev_ip - 74.89).abs() < 0.2);
+    let expected = solve_with(true);
+    assert_eq!(solve_with(true), expected);
+
+    // the results do not depend on the number of threads
+    #[cfg(feature = "rayon")]
+    for num_threads in [1, 3] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .unwrap();
+        for deterministic in [false, true] {
+            assert_eq!(pool.install(|| solve_with(deterministic)), expected);
+        }
+    }
 }
 
 #[test]
-#[ignore]
-fn solve_pio_preset_raked() {
-    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
-    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
-
+fn solve_averaged_compressed() {
     let card_config = CardConfig {
-        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
-        flop: flop_from_str("QsJh2h").unwrap(),
+        range: [
+            "QQ+,AK,T9s,76s".parse().unwrap(),
+            "JJ-88,AQs,KQ,Td9d".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        starting_pot: 180,
-        effective_stack: 910,
-        rake_rate: 0.05,
-        rake_cap: 30.0,
-        flop_bet_sizes: [
-            ("52%", "45%").try_into().unwrap(),
-            ("52%", "45%").try_into().unwrap(),
-        ],
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 300,
         turn_bet_sizes: [
-            ("55%", "45%").try_into().unwrap(),
-            ("55%", "45%").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
         ],
         river_bet_sizes: [
-            ("70%", "45%").try_into().unwrap(),
-            ("70%", "45%").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
         ],
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
-    println!(
-        "memory usage: {:.2}GB",
-        game.memory_usage().0 as f64 / (1024.0 * 1024.0 * 1024.0)
-    );
+    let build = |_| {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(true);
+        game
+    };
+
+    let target = 0.5;
+    let config = SolverConfig::default();
+    let mut result = solve_averaged(build, 3, 300, target, &config, 7);
+    assert_eq!(result.run_exploitabilities.len(), 3);
+    assert!(result.run_exploitabilities.iter().all(|&e| e <= target));
+    let mean = result.run_exploitabilities.iter().sum::<f32>() / 3.0;
+    assert!(result.exploitability <= mean + 1e-3);
+    assert!(result.game.is_solved());
+
+    // the averaged strategy is normalized
+    result.game.cache_normalized_weights();
+    let strategy = result.game.strategy();
+    let num_hands = result.game.num_private_hands(0);
+    for hand in 0..num_hands {
+        let sum = (0..strategy.len() / num_hands)
+            .map(|action| strategy[action * num_hands + hand])
+            .sum::<f32>();
+        assert!((sum - 1.0).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn prefetch_depth() {
+    use std::sync::Mutex;
+
+    struct RecordingBuffer(Vec<u8>, Arc<Mutex<Vec<(usize, usize, usize)>>>);
+
+    impl StorageBuffer for RecordingBuffer {
+        fn as_slice(&self) -> &[u8] {
+            &self.0
+        }
+
+        fn as_mut_slice(&mut self) -> &mut [u8] {
+            &mut self.0
+        }
+
+        fn prefetch(&self, offset: usize, len: usize) {
+            self.1.lock().unwrap().push((offset, len, self.0.len()));
+        }
+    }
+
+    struct RecordingStorage(Arc<Mutex<Vec<(usize, usize, usize)>>>);
+
+    impl StorageBackend for RecordingStorage {
+        fn allocate(&self, num_bytes: usize) -> std::io::Result<Box<dyn StorageBuffer>> {
+            let buffer = RecordingBuffer(vec![0; num_bytes], self.0.clone());
+            Ok(Box::new(buffer))
+        }
+    }
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let spot = CanonicalSpot::srp_q9572_river_50bb();
+    let mut game = spot.build().unwrap();
+    game.set_storage_backend(Arc::new(RecordingStorage(calls.clone())))
+        .unwrap();
     game.allocate_memory(false);
+    solve(&mut game, 10, 0.0, false);
+
+    let num_calls = |game: &mut PostFlopGame, depth: usize| {
+        calls.lock().unwrap().clear();
+        game.set_prefetch_depth(depth);
+        game.back_to_root();
+        let calls = calls.lock().unwrap();
+        assert!(calls
+            .iter()
+            .all(|&(offset, len, buf_len)| len > 0 && offset + len <= buf_len));
+        calls.len()
+    };
 
-    solve(&mut game, 1000, 180.0 * 0.001, true);
+    assert_eq!(game.prefetch_depth(), 0);
+    assert_eq!(num_calls(&mut game, 0), 0);
 
-    game.cache_normalized_weights();
-    let weights_oop = game.normalized_weights(0);
-    let weights_ip = game.normalized_weights(1);
-    let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
-    let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    // the strategy and the counterfactual values of each non-terminal child
+    let num_children = game.available_actions().len();
+    let depth1 = num_calls(&mut game, 1);
+    assert!(depth1 > 0 && depth1 <= 3 * num_children);
+    assert!(num_calls(&mut game, 2) > depth1);
 
-    // verified by PioSOLVER Free (but not theoretically guaranteed to be the same)
-    assert!((root_ev_oop - 95.57).abs() < 0.2);
-    assert!((root_ev_ip - 66.98).abs() < 0.2);
+    // navigation triggers the prefetching as well
+    calls.lock().unwrap().clear();
+    game.set_prefetch_depth(1);
+    game.play(0);
+    assert!(!calls.lock().unwrap().is_empty());
+
+    // prefetching does not affect the results
+    let strategy = game.strategy();
+    game.set_prefetch_depth(0);
+    game.apply_history(&[0]);
+    assert_eq!(game.strategy(), strategy);
 }
@@ -0,0 +1,319 @@
+use super::*;
+use crate::card::*;
+use crate::error::*;
+use std::mem;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+/// The number of buckets per doubling of the pot ratio and the stack-to-pot ratio.
+const BUCKETS_PER_OCTAVE: f64 = 4.0;
+
+/// The lookup key of an [`ActionTranslationTable`].
+///
+/// The key is computed from the state of a hand at runtime, so that a bot can look up the
+/// strategy without the game tree. The bet sizes are not part of the line; they are represented
+/// by the pot and stack buckets instead, which maps off-tree bet sizes to the closest bucket.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TranslationKey {
+    /// The current street.
+    pub street: BoardState,
+
+    /// The bucket of the pot relative to the starting pot (see
+    /// [`ActionTranslationTable::pot_bucket`]).
+    pub pot_bucket: u8,
+
+    /// The bucket of the stack-to-pot ratio (see [`ActionTranslationTable::stack_bucket`]).
+    pub stack_bucket: u8,
+
+    /// The hash of the line (see [`ActionTranslationTable::line_hash`]).
+    pub line_hash: u64,
+
+    /// The index of the private hand (2d2c => `0`, 2h2c => `1`, ..., AsAh => `1325`).
+    pub hand: u16,
+}
+
+/// A decision node referenced by an [`ActionTranslationTable`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct TranslationNode {
+    /// The available actions, with the amounts in the chips of the solved game.
+    pub actions: Vec<Action>,
+
+    /// The pot at the node in the chips of the solved game.
+    pub pot: i32,
+}
+
+/// A compact lookup table from [`TranslationKey`] to the action distribution, exported by
+/// [`PostFlopGame::action_translation_table`].
+///
+/// The keys are stored sorted in a flat array and looked up by binary search, and the
+/// probabilities are quantized to 8 bits, so the table is much smaller than the game tree and
+/// can be loaded by a bot for low-latency lookups.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct ActionTranslationTable {
+    /// The starting pot of the solved game.
+    pub starting_pot: i32,
+
+    /// The effective stack of the solved game.
+    pub effective_stack: i32,
+
+    /// The decision nodes.
+    pub nodes: Vec<TranslationNode>,
+
+    /// The number of (node, hand) pairs dropped because their key was already used by another
+    /// node. Two nodes collide when their lines differ only in bet sizes that fall into the same
+    /// buckets; the node visited first in depth-first order is kept.
+    pub num_collisions: usize,
+
+    keys: Vec<u128>,
+    entries: Vec<(u32, u32)>,
+    probabilities: Vec<u8>,
+}
+
+impl TranslationKey {
+    /// Packs the key into an integer whose order matches the order of the keys.
+    #[inline]
+    fn pack(&self) -> u128 {
+        (self.street as u128) << 96
+            | (self.pot_bucket as u128) << 88
+            | (self.stack_bucket as u128) << 80
+            | (self.line_hash as u128) << 16
+            | self.hand as u128
+    }
+}
+
+impl ActionTranslationTable {
+    /// Returns the bucket of `pot_ratio`, i.e., the pot divided by the starting pot (the pot at
+    /// the root of the solved game). The buckets are geometric with four buckets per doubling.
+    #[inline]
+    pub fn pot_bucket(pot_ratio: f64) -> u8 {
+        (pot_ratio.max(1.0).log2() * BUCKETS_PER_OCTAVE)
+            .round()
+            .min(u8::MAX as f64) as u8
+    }
+
+    /// Returns the bucket of `spr`, i.e., the smaller remaining stack divided by the pot. The
+    /// buckets are geometric in `1 + spr` with four buckets per doubling.
+    #[inline]
+    pub fn stack_bucket(spr: f64) -> u8 {
+        ((1.0 + spr.max(0.0)).log2() * BUCKETS_PER_OCTAVE)
+            .round()
+            .min(u8::MAX as f64) as u8
+    }
+
+    /// Returns the hash of `line`, which includes [`Action::Chance`] for the dealt turn and river
+    /// cards. The amounts of the bets and raises are ignored.
+    pub fn line_hash(line: &[Action]) -> u64 {
+        // FNV-1a
+        let mut hash = 0xcbf29ce484222325u64;
+        for action in line {
+            let byte = match action {
+                Action::None => 0,
+                Action::Fold => 1,
+                Action::Check => 2,
+                Action::Call => 3,
+                Action::Bet(_) => 4,
+                Action::Raise(_) => 5,
+                Action::AllIn(_) => 6,
+                Action::Chance(card) => 16 + card,
+            };
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash
+    }
+
+    /// Creates the key of a hand from its state: `pot_ratio` and `spr` are passed to
+    /// [`pot_bucket`] and [`stack_bucket`], and `hand` is the pair of the hole cards.
+    ///
+    /// [`pot_bucket`]: #method.pot_bucket
+    /// [`stack_bucket`]: #method.stack_bucket
+    pub fn key(
+        street: BoardState,
+        pot_ratio: f64,
+        spr: f64,
+        line: &[Action],
+        hand: (Card, Card),
+    ) -> TranslationKey {
+        TranslationKey {
+            street,
+            pot_bucket: Self::pot_bucket(pot_ratio),
+            stack_bucket: Self::stack_bucket(spr),
+            line_hash: Self::line_hash(line),
+            hand: card_pair_to_index(hand.0, hand.1) as u16,
+        }
+    }
+
+    /// Returns the number of entries.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether the table has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Looks up `key` and returns the node and the probability of each of its actions.
+    pub fn lookup(&self, key: &TranslationKey) -> Option<(&TranslationNode, Vec<f32>)> {
+        let index = self.keys.binary_search(&key.pack()).ok()?;
+        let (node, offset) = self.entries[index];
+        let node = &self.nodes[node as usize];
+
+        let offset = offset as usize;
+        let quantized = &self.probabilities[offset..offset + node.actions.len()];
+        let sum = quantized.iter().map(|&q| q as f32).sum::<f32>();
+        let probabilities = quantized.iter().map(|&q| q as f32 / sum).collect();
+
+        Some((node, probabilities))
+    }
+
+    /// Returns the size of the data in bytes.
+    pub fn memory_usage(&self) -> u64 {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                mem::size_of::<TranslationNode>() + node.actions.len() * mem::size_of::<Action>()
+            })
+            .sum::<usize>();
+        let keys = self.keys.len() * (mem::size_of::<u128>() + mem::size_of::<(u32, u32)>());
+        (nodes + keys + self.probabilities.len()) as u64
+    }
+}
+
+impl PostFlopGame {
+    /// Exports the solved strategies as an [`ActionTranslationTable`].
+    ///
+    /// An entry is created for each decision node and each private hand of the player to act
+    /// that reaches the node. The pot ratio and the stack-to-pot ratio of a node are computed from
+    /// the starting pot of the game. Isomorphic chances are expanded.
+    ///
+    /// Returns an error if the game is not solved or the storage mode is not
+    /// [`BoardState::River`]. The current node is restored after the call.
+    pub fn action_translation_table(&mut self) -> Result<ActionTranslationTable, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        let saved_history = self.history().to_vec();
+        self.back_to_root();
+
+        let mut table = ActionTranslationTable {
+            starting_pot: self.tree_config.starting_pot,
+            effective_stack: self.tree_config.effective_stack,
+            ..Default::default()
+        };
+
+        let mut history = Vec::new();
+        let mut line = Vec::new();
+        let mut keys = Vec::new();
+        self.translation_recursive(&mut history, &mut line, &mut table, &mut keys);
+        self.apply_history(&saved_history);
+
+        // the stable sort keeps the node visited first at the front
+        keys.sort_by_key(|&(key, _)| key);
+        let num_keys = keys.len();
+        keys.dedup_by_key(|&mut (key, _)| key);
+        table.num_collisions = num_keys - keys.len();
+
+        (table.keys, table.entries) = keys.into_iter().unzip();
+        Ok(table)
+    }
+
+    fn translation_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        line: &mut Vec<Action>,
+        table: &mut ActionTranslationTable,
+        keys: &mut Vec<(u128, (u32, u32))>,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        if self.is_chance_node() {
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card as usize);
+                    line.push(Action::Chance(card));
+                    self.apply_history(history);
+                    self.translation_recursive(history, line, table, keys);
+                    line.pop();
+                    history.pop();
+                }
+            }
+            return;
+        }
+
+        self.append_translation_entries(line, table, keys);
+
+        for (i, action) in self.available_actions().into_iter().enumerate() {
+            history.push(i);
+            line.push(action);
+            self.apply_history(history);
+            self.translation_recursive(history, line, table, keys);
+            line.pop();
+            history.pop();
+        }
+    }
+
+    fn append_translation_entries(
+        &mut self,
+        line: &[Action],
+        table: &mut ActionTranslationTable,
+        keys: &mut Vec<(u128, (u32, u32))>,
+    ) {
+        let player = self.current_player();
+        let actions = self.available_actions();
+        let num_actions = actions.len();
+        let num_hands = self.num_private_hands(player);
+
+        let street = match self.current_board().len() {
+            3 => BoardState::Flop,
+            4 => BoardState::Turn,
+            _ => BoardState::River,
+        };
+
+        let pot = self.pot();
+        let stack = self.remaining_stacks().into_iter().min().unwrap();
+        let pot_ratio = pot as f64 / self.tree_config.starting_pot as f64;
+        let spr = stack as f64 / pot as f64;
+
+        let node = table.nodes.len() as u32;
+        table.nodes.push(TranslationNode { actions, pot });
+
+        self.cache_normalized_weights();
+        let reach = self.normalized_weights(player);
+        let strategy = self.strategy();
+
+        for hand in 0..num_hands {
+            if reach[hand] == 0.0 {
+                continue;
+            }
+
+            let key = ActionTranslationTable::key(
+                street,
+                pot_ratio,
+                spr,
+                line,
+                self.private_cards(player)[hand],
+            );
+
+            let offset = table.probabilities.len() as u32;
+            table.probabilities.extend((0..num_actions).map(|action| {
+                let p = strategy[action * num_hands + hand];
+                (p * u8::MAX as f32).round() as u8
+            }));
+
+            keys.push((key.pack(), (node, offset)));
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::sliceop::*;
+use crate::utility::*;
+
+/// Kind of a problem found by [`PostFlopGame::validate_solution`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationIssueKind {
+    /// The strategy (or the stored cumulative strategy) contains NaN, infinity, or a negative
+    /// value.
+    NonFiniteStrategy,
+
+    /// The strategy of a hand does not sum to one.
+    UnnormalizedStrategy,
+
+    /// The reach probabilities of the children of a node are not consistent with those of the
+    /// node, or the number of card combinations does not match the initial weights.
+    InconsistentReach,
+
+    /// An expected value is NaN or infinity.
+    NonFiniteValue,
+
+    /// The expected values or the exploitability fail a cross-check (e.g., a best response is
+    /// worse than the current strategy, or the expected values are not zero-sum without rake).
+    InconsistentValue,
+}
+
+/// A problem found by [`PostFlopGame::validate_solution`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// The kind of the problem.
+    pub kind: ValidationIssueKind,
+
+    /// The history leading to the node, in the format accepted by
+    /// [`PostFlopGame::apply_history`]. `None` if the problem is not specific to a node.
+    pub history: Option<Vec<usize>>,
+
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+/// Result of [`PostFlopGame::validate_solution`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// The problems found, in the order of the checks. At most one problem of each kind is
+    /// reported for each node.
+    pub issues: Vec<ValidationIssue>,
+
+    /// The number of decision nodes whose strategies were checked.
+    pub num_checked_nodes: usize,
+
+    /// The expected values of the current strategy (see [`compute_current_ev`]).
+    ///
+    /// [`compute_current_ev`]: crate::compute_current_ev
+    pub current_ev: [f32; 2],
+
+    /// The expected values of the best responses (see [`compute_mes_ev`]).
+    ///
+    /// [`compute_mes_ev`]: crate::compute_mes_ev
+    pub best_response_ev: [f32; 2],
+
+    /// The exploitability of the current strategy.
+    pub exploitability: f32,
+}
+
+/// Absolute tolerance of probabilities used by [`PostFlopGame::validate_solution`].
+const PROBABILITY_TOLERANCE: f32 = 1e-4;
+
+/// Relative tolerance (with respect to the starting pot) of expected values used by
+/// [`PostFlopGame::validate_solution`].
+const VALUE_TOLERANCE: f32 = 1e-4;
+
+impl ValidationReport {
+    /// Returns whether no problem was found.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl PostFlopGame {
+    /// Checks the sanity of the current strategy.
+    ///
+    /// The following checks are performed, and the problems found are returned as structured
+    /// findings rather than an error:
+    ///
+    /// - The strategy of every decision node is finite, non-negative, and sums to one for each
+    ///   hand.
+    /// - The reach probabilities propagated from the root are finite, do not increase, and are
+    ///   conserved at each decision node. The number of card combinations matches the initial
+    ///   weights.
+    /// - The expected values of the current strategy and the best responses are finite, the best
+    ///   responses are not worse than the current strategy, and the expected values are zero-sum
+    ///   without rake.
+    ///
+    /// This is useful when extending the solver or its storage, since a broken strategy often
+    /// results in a plausible-looking but wrong exploitability.
+    ///
+    /// Returns an error if the memory is not allocated or the storage mode is not
+    /// [`BoardState::River`].
+    pub fn validate_solution(&self) -> Result<ValidationReport, Error> {
+        self.check_operation(GameOperation::QueryStrategy)?;
+
+        if self.storage_mode != BoardState::River {
+            return Err(Error::Config("Storage mode must be river".to_string()));
+        }
+
+        let mut report = ValidationReport::default();
+
+        self.validate_num_combinations(&mut report);
+
+        let reach = [0, 1].map(|player| self.initial_weights(player).to_vec());
+        let mut history = Vec::new();
+        self.validate_recursive(&self.root(), &reach, &mut history, &mut report);
+
+        self.validate_values(&mut report);
+
+        Ok(report)
+    }
+
+    /// Checks that the number of card combinations matches the initial weights.
+    fn validate_num_combinations(&self, report: &mut ValidationReport) {
+        let mut num_combinations = 0.0;
+        for (&(c1, c2), &w1) in self.private_cards[0].iter().zip(&self.initial_weights[0]) {
+            let oop_mask: u64 = (1 << c1) | (1 << c2);
+            for (&(c3, c4), &w2) in self.private_cards[1].iter().zip(&self.initial_weights[1]) {
+                let ip_mask: u64 = (1 << c3) | (1 << c4);
+                if oop_mask & ip_mask == 0 {
+                    num_combinations += w1 as f64 * w2 as f64;
+                }
+            }
+        }
+
+        let expected = self.num_combinations;
+        if (num_combinations - expected).abs() > 1e-6 * expected.max(1.0) {
+            report.issues.push(ValidationIssue {
+                kind: ValidationIssueKind::InconsistentReach,
+                history: None,
+                message: format!(
+                    "Number of combinations mismatch: expected = {expected}, actual = \
+                     {num_combinations}"
+                ),
+            });
+        }
+    }
+
+    fn validate_recursive(
+        &self,
+        node: &PostFlopNode,
+        reach: &[Vec<f32>; 2],
+        history: &mut Vec<usize>,
+        report: &mut ValidationReport,
+    ) {
+        if node.is_terminal() {
+            return;
+        }
+
+        if node.is_chance() {
+            for action in 0..node.num_actions() {
+                let child = node.play(action);
+                if let Action::Chance(card) = child.prev_action {
+                    history.push(card as usize);
+                    self.validate_recursive(&child, reach, history, report);
+                    history.pop();
+                }
+            }
+            return;
+        }
+
+        let player = node.player();
+        let num_actions = node.num_actions();
+        let num_hands = self.num_private_hands(player);
+        let strategy = self.decode_node_strategy(node);
+        report.num_checked_nodes += 1;
+
+        let mut push_issue = |kind: ValidationIssueKind, message: String| {
+            report.issues.push(ValidationIssue {
+                kind,
+                history: Some(history.clone()),
+                message,
+            });
+        };
+
+        // strategy
+        let is_stored_valid = self.is_node_compressed(node)
+            || node.strategy().iter().all(|&s| s.is_finite() && s >= 0.0);
+        let num_invalid = strategy
+            .iter()
+            .filter(|&&s| !s.is_finite() || s < 0.0)
+            .count();
+        if !is_stored_valid || num_invalid > 0 {
+            push_issue(
+                ValidationIssueKind::NonFiniteStrategy,
+                format!("Strategy contains {num_invalid} invalid probabilities"),
+            );
+        }
+
+        let mut max_error = 0.0f32;
+        let mut num_unnormalized = 0;
+        for hand in 0..num_hands {
+            let sum =
+                (0..num_actions).fold(0.0, |sum, action| sum + strategy[action * num_hands + hand]);
+            if sum.is_nan() || (sum - 1.0).abs() > PROBABILITY_TOLERANCE {
+                num_unnormalized += 1;
+                max_error = max_error.max((sum - 1.0).abs());
+            }
+        }
+        if num_unnormalized > 0 {
+            push_issue(
+                ValidationIssueKind::UnnormalizedStrategy,
+                format!(
+                    "Strategy of {num_unnormalized} hands does not sum to one: \
+                     max error = {max_error}"
+                ),
+            );
+        }
+
+        // reach probabilities
+        let parent_reach = &reach[player];
+        let children_reach = (0..num_actions)
+            .map(|action| {
+                let row = row(&strategy, action, num_hands);
+                parent_reach.iter().zip(row).map(|(&r, &s)| r * s).collect()
+            })
+            .collect::<Vec<Vec<f32>>>();
+
+        let is_consistent = (0..num_hands).all(|hand| {
+            let parent = parent_reach[hand];
+            let mut sum = 0.0;
+            for child in &children_reach {
+                let r = child[hand];
+                if !(0.0..=parent + PROBABILITY_TOLERANCE).contains(&r) {
+                    return false;
+                }
+                sum += r;
+            }
+            (sum - parent).abs() <= PROBABILITY_TOLERANCE * parent.max(1.0)
+        });
+        if !is_consistent {
+            push_issue(
+                ValidationIssueKind::InconsistentReach,
+                "Reach probabilities of the children are not consistent".to_string(),
+            );
+        }
+
+        for (action, child_reach) in children_reach.into_iter().enumerate() {
+            let mut child_reach_pair = reach.clone();
+            child_reach_pair[player] = child_reach;
+            history.push(action);
+            self.validate_recursive(&node.play(action), &child_reach_pair, history, report);
+            history.pop();
+        }
+    }
+
+    /// Cross-checks the expected values of the current strategy and the best responses.
+    fn validate_values(&self, report: &mut ValidationReport) {
+        let current_ev = compute_current_ev(self);
+        let best_response_ev = compute_mes_ev(self);
+        let exploitability = if self.is_raked() {
+            ((best_response_ev[0] - current_ev[0]) + (best_response_ev[1] - current_ev[1])) * 0.5
+        } else {
+            (best_response_ev[0] + best_response_ev[1]) * 0.5
+        };
+
+        report.current_ev = current_ev;
+        report.best_response_ev = best_response_ev;
+        report.exploitability = exploitability;
+
+        let mut push_issue = |kind: ValidationIssueKind, message: String| {
+            report.issues.push(ValidationIssue {
+                kind,
+                history: None,
+                message,
+            });
+        };
+
+        let values = current_ev.iter().chain(&best_response_ev);
+        if !values.chain([&exploitability]).all(|v| v.is_finite()) {
+            push_issue(
+                ValidationIssueKind::NonFiniteValue,
+                format!(
+                    "Non-finite expected value: current = {current_ev:?}, best response = \
+                     {best_response_ev:?}"
+                ),
+            );
+            return;
+        }
+
+        let tolerance = VALUE_TOLERANCE * self.tree_config.starting_pot as f32;
+
+        for player in 0..2 {
+            if best_response_ev[player] < current_ev[player] - tolerance {
+                push_issue(
+                    ValidationIssueKind::InconsistentValue,
+                    format!(
+                        "Best response of player {player} is worse than the current strategy: \
+                         current = {}, best response = {}",
+                        current_ev[player], best_response_ev[player]
+                    ),
+                );
+            }
+        }
+
+        if !self.is_raked() && (current_ev[0] + current_ev[1]).abs() > tolerance {
+            push_issue(
+                ValidationIssueKind::InconsistentValue,
+                format!("Expected values are not zero-sum: {current_ev:?}"),
+            );
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use super::*;
+use crate::error::*;
+use crate::interface::*;
+use crate::solver::*;
+use crate::utility::*;
+
+/// Time series of the strategies of a watched node.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StrategyTrace {
+    /// The history of the watched node (see [`PostFlopGame::apply_history`]).
+    pub history: Vec<usize>,
+
+    /// The player to act at the watched node.
+    pub player: usize,
+
+    /// The available actions at the watched node.
+    pub actions: Vec<Action>,
+
+    /// The iterations at which the strategies were recorded.
+    pub iterations: Vec<u32>,
+
+    /// The current strategies (obtained by regret matching) at the recorded iterations.
+    ///
+    /// Each element has the same layout as [`PostFlopGame::strategy`].
+    pub current_strategies: Vec<Vec<f32>>,
+
+    /// The average strategies at the recorded iterations.
+    ///
+    /// Each element has the same layout as [`PostFlopGame::strategy`].
+    pub average_strategies: Vec<Vec<f32>>,
+}
+
+/// Records the strategies of the watched nodes every `interval` iterations while solving.
+///
+/// Register the nodes with [`watch`] before solving, and call [`record`] after each
+/// [`solve_step`] call. The recorded traces can be used for convergence animations or for
+/// debugging oscillating nodes.
+///
+/// [`watch`]: #method.watch
+/// [`record`]: #method.record
+/// [`solve_step`]: crate::solve_step
+#[derive(Debug, Clone, Default)]
+pub struct StrategyWatcher {
+    interval: u32,
+    nodes: Vec<WatchedNode>,
+    traces: Vec<StrategyTrace>,
+}
+
+/// A decision node whose strategies are read while solving, shared with [`SolveStreamer`].
+#[derive(Debug, Clone)]
+pub(super) struct WatchedNode {
+    index: usize,
+    permutation: Vec<usize>,
+}
+
+impl StrategyWatcher {
+    /// Creates a new watcher that records the strategies every `interval` iterations.
+    ///
+    /// Panics if `interval` is zero.
+    #[inline]
+    pub fn new(interval: u32) -> Self {
+        if interval == 0 {
+            panic!("Interval must be positive");
+        }
+
+        Self {
+            interval,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the recording interval.
+    #[inline]
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// Registers the node specified by `history` as a watched node.
+    ///
+    /// The current node of `game` is restored after the call. Returns an error if the memory is
+    /// not allocated or the specified node is not a decision node.
+    pub fn watch(&mut self, game: &mut PostFlopGame, history: &[usize]) -> Result<(), Error> {
+        let (node, trace) = WatchedNode::new(game, history)?;
+        self.nodes.push(node);
+        self.traces.push(trace);
+        Ok(())
+    }
+
+    /// Records the strategies of the watched nodes if `iteration` is a multiple of the interval.
+    ///
+    /// `iteration` is the number of completed iterations, i.e., `t + 1` after calling
+    /// `solve_step(&game, t)`.
+    pub fn record(&mut self, game: &PostFlopGame, iteration: u32) {
+        if !iteration.is_multiple_of(self.interval) {
+            return;
+        }
+
+        for (node, trace) in self.nodes.iter().zip(self.traces.iter_mut()) {
+            trace.iterations.push(iteration);
+            trace.current_strategies.push(node.current_strategy(game));
+            trace.average_strategies.push(node.average_strategy(game));
+        }
+    }
+
+    /// Returns the recorded traces in the order of registration.
+    #[inline]
+    pub fn traces(&self) -> &[StrategyTrace] {
+        &self.traces
+    }
+
+    /// Consumes the watcher and returns the recorded traces.
+    #[inline]
+    pub fn into_traces(self) -> Vec<StrategyTrace> {
+        self.traces
+    }
+}
+
+impl WatchedNode {
+    /// Resolves the decision node specified by `history` and returns it with an empty trace.
+    ///
+    /// The current node of `game` is restored after the call. Returns an error if the memory is
+    /// not allocated or the specified node is not a decision node.
+    pub(super) fn new(
+        game: &mut PostFlopGame,
+        history: &[usize],
+    ) -> Result<(Self, StrategyTrace), Error> {
+        game.check_operation(GameOperation::Navigate)?;
+
+        let current_history = game.history().to_vec();
+        game.apply_history(history);
+
+        let ret = if game.is_terminal_node() || game.is_chance_node() {
+            Err(Error::Tree(format!(
+                "Node is not a decision node: {history:?}"
+            )))
+        } else {
+            let player = game.current_player();
+            let num_hands = game.num_private_hands(player);
+
+            // the isomorphic swap is encoded as a permutation of hand indices
+            let mut permutation = (0..num_hands).map(|i| i as f32).collect::<Vec<_>>();
+            game.apply_swap(&mut permutation, player, false);
+
+            let node = Self {
+                index: game.node_index(&game.node()),
+                permutation: permutation.iter().map(|&i| i as usize).collect(),
+            };
+
+            let trace = StrategyTrace {
+                history: game.history().to_vec(),
+                player,
+                actions: game.available_actions(),
+                ..Default::default()
+            };
+
+            Ok((node, trace))
+        };
+
+        game.apply_history(&current_history);
+        ret
+    }
+
+    /// Returns the current strategy (obtained by regret matching) of the node.
+    pub(super) fn current_strategy(&self, game: &PostFlopGame) -> Vec<f32> {
+        let node = game.node_arena[self.index].lock();
+        let num_actions = node.num_actions();
+
+        let mut current = if game.is_node_compressed(&node) {
+            regret_matching_compressed(node.regrets_compressed(), num_actions).to_vec()
+        } else if game.is_f64_regrets_enabled() {
+            regret_matching_f64(node.regrets_f64(), num_actions).to_vec()
+        } else {
+            regret_matching(node.regrets(), num_actions).to_vec()
+        };
+        apply_locking_strategy(&mut current, game.locking_strategy(&node));
+
+        let current = game.expand_stored_hands(current, node.player());
+        permute(&current, &self.permutation)
+    }
+
+    /// Returns the average strategy of the node.
+    pub(super) fn average_strategy(&self, game: &PostFlopGame) -> Vec<f32> {
+        let node = game.node_arena[self.index].lock();
+        permute(&game.node_strategy(&node), &self.permutation)
+    }
+}
+
+/// Permutes each row of `strategy` so that the `i`-th hand refers to `permutation[i]`.
+#[inline]
+fn permute(strategy: &[f32], permutation: &[usize]) -> Vec<f32> {
+    strategy
+        .chunks_exact(permutation.len())
+        .flat_map(|row| permutation.iter().map(move |&i| row[i]))
+        .collect()
+}
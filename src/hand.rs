@@ -54,13 +54,22 @@ impl Hand {
         HAND_TABLE.binary_search(&self.evaluate_internal()).unwrap() as u16
     }
 
-    fn evaluate_internal(&self) -> i32 {
+    /// Returns the category of the hand (0: high card, 1: one pair, ..., 8: straight flush).
+    /// Unlike `evaluate`, the hand may consist of fewer than 7 cards.
+    #[inline]
+    pub fn category(&self) -> usize {
+        (self.evaluate_internal() >> 26) as usize
+    }
+
+    /// Returns the raw value of the best five-card hand. Unlike `evaluate`, the hand may consist
+    /// of fewer than 7 cards (at least 5 cards for a meaningful value).
+    pub fn evaluate_internal(&self) -> i32 {
         let mut rankset = 0i32;
         let mut rankset_suit = [0i32; 4];
         let mut rankset_of_count = [0i32; 5];
         let mut rank_count = [0i32; 13];
 
-        for &card in &self.cards {
+        for &card in &self.cards[..self.num_cards] {
             let rank = card / 4;
             let suit = card % 4;
             rankset |= 1 << rank;
@@ -0,0 +1,255 @@
+use crate::card::*;
+use crate::hand::*;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A strategic class of a private hand on a board, used by [`PostFlopGame::analyze_node`].
+///
+/// The classes are mutually exclusive and checked in the order of declaration, so made hands take
+/// precedence over draws (e.g., top pair with a flush draw is [`HandClass::TopPair`]). A made hand
+/// counts only if the private cards improve the hand category of the board alone; for example,
+/// `KQ` on `AA7` is not a pair. Draws are only considered before the river.
+///
+/// [`PostFlopGame::analyze_node`]: crate::PostFlopGame::analyze_node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HandClass {
+    /// Full house, four of a kind, or straight flush.
+    FullHouseOrBetter,
+
+    /// Flush.
+    Flush,
+
+    /// Straight.
+    Straight,
+
+    /// Three of a kind with a pocket pair.
+    Set,
+
+    /// Three of a kind with a single private card.
+    Trips,
+
+    /// Two pair.
+    TwoPair,
+
+    /// Pocket pair higher than every card on the board.
+    Overpair,
+
+    /// Pair with the highest card on the board.
+    TopPair,
+
+    /// Pair with the second highest card on the board, or a pocket pair between the highest and
+    /// the second highest cards.
+    MiddlePair,
+
+    /// Any other pair.
+    WeakPair,
+
+    /// Flush draw and straight draw.
+    ComboDraw,
+
+    /// Four cards to a flush, including at least one private card.
+    FlushDraw,
+
+    /// Open-ended straight draw or double gutshot, i.e., at least two ranks complete a straight.
+    StraightDraw,
+
+    /// Two private cards higher than every card on the board.
+    Overcards,
+
+    /// Any other hand.
+    Air,
+}
+
+impl HandClass {
+    /// All classes in the order of declaration.
+    pub const ALL: [HandClass; 15] = [
+        HandClass::FullHouseOrBetter,
+        HandClass::Flush,
+        HandClass::Straight,
+        HandClass::Set,
+        HandClass::Trips,
+        HandClass::TwoPair,
+        HandClass::Overpair,
+        HandClass::TopPair,
+        HandClass::MiddlePair,
+        HandClass::WeakPair,
+        HandClass::ComboDraw,
+        HandClass::FlushDraw,
+        HandClass::StraightDraw,
+        HandClass::Overcards,
+        HandClass::Air,
+    ];
+
+    /// Classifies the private `hand` on `board` (three to five cards).
+    pub fn classify(board: &[Card], hand: (Card, Card)) -> Self {
+        let (c1, c2) = hand;
+        let (r1, r2) = (c1 >> 2, c2 >> 2);
+
+        let mut board_hand = Hand::new();
+        for &card in board {
+            board_hand = board_hand.add_card(card as usize);
+        }
+
+        let board_category = board_category(board, board_hand);
+        let category = board_hand
+            .add_card(c1 as usize)
+            .add_card(c2 as usize)
+            .category();
+
+        let mut board_ranks = board.iter().map(|&c| c >> 2).collect::<Vec<_>>();
+        board_ranks.sort_unstable_by(|a, b| b.cmp(a));
+        board_ranks.dedup();
+        let top = board_ranks[0];
+        let second = board_ranks.get(1).copied();
+
+        if category > board_category {
+            match category {
+                6.. => return HandClass::FullHouseOrBetter,
+                5 => return HandClass::Flush,
+                4 => return HandClass::Straight,
+                3 if r1 == r2 => return HandClass::Set,
+                3 => return HandClass::Trips,
+                2 => return HandClass::TwoPair,
+                1 if r1 == r2 && r1 > top => return HandClass::Overpair,
+                1 => {
+                    let pair = if r1 == r2 || board_ranks.contains(&r1) {
+                        r1
+                    } else {
+                        r2
+                    };
+                    return if pair == top {
+                        HandClass::TopPair
+                    } else if Some(pair) == second || (r1 == r2 && second < Some(pair)) {
+                        HandClass::MiddlePair
+                    } else {
+                        HandClass::WeakPair
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        if board.len() < 5 {
+            let is_flush_draw = (0..4).any(|suit| {
+                let count = |cards: &[Card]| cards.iter().filter(|&&c| c & 3 == suit).count();
+                let num_private = count(&[c1, c2]);
+                num_private > 0 && num_private + count(board) == 4
+            });
+
+            let ranks = board
+                .iter()
+                .chain(&[c1, c2])
+                .fold(0u16, |acc, &c| acc | 1 << (c >> 2));
+            let board_only = board.iter().fold(0u16, |acc, &c| acc | 1 << (c >> 2));
+            let num_outs = (0..13)
+                .filter(|&rank| {
+                    is_straight(ranks | 1 << rank) && !is_straight(board_only | 1 << rank)
+                })
+                .count();
+            let is_straight_draw = !is_straight(ranks) && num_outs >= 2;
+
+            match (is_flush_draw, is_straight_draw) {
+                (true, true) => return HandClass::ComboDraw,
+                (true, false) => return HandClass::FlushDraw,
+                (false, true) => return HandClass::StraightDraw,
+                _ => {}
+            }
+        }
+
+        if r1 > top && r2 > top {
+            HandClass::Overcards
+        } else {
+            HandClass::Air
+        }
+    }
+
+    /// Returns the name of the class in snake case (e.g., `"top_pair"`).
+    pub fn name(self) -> &'static str {
+        match self {
+            HandClass::FullHouseOrBetter => "full_house_or_better",
+            HandClass::Flush => "flush",
+            HandClass::Straight => "straight",
+            HandClass::Set => "set",
+            HandClass::Trips => "trips",
+            HandClass::TwoPair => "two_pair",
+            HandClass::Overpair => "overpair",
+            HandClass::TopPair => "top_pair",
+            HandClass::MiddlePair => "middle_pair",
+            HandClass::WeakPair => "weak_pair",
+            HandClass::ComboDraw => "combo_draw",
+            HandClass::FlushDraw => "flush_draw",
+            HandClass::StraightDraw => "straight_draw",
+            HandClass::Overcards => "overcards",
+            HandClass::Air => "air",
+        }
+    }
+}
+
+/// Returns the hand category of the board alone. `Hand::category` needs at least five cards, so
+/// the category of a shorter board is computed from the rank counts.
+fn board_category(board: &[Card], board_hand: Hand) -> usize {
+    if board.len() >= 5 {
+        return board_hand.category();
+    }
+
+    let mut rank_count = [0; 13];
+    for &card in board {
+        rank_count[(card >> 2) as usize] += 1;
+    }
+
+    let num_pairs = rank_count.iter().filter(|&&c| c == 2).count();
+    match rank_count.iter().max() {
+        Some(4) => 7,
+        Some(3) => 3,
+        _ => num_pairs.min(2),
+    }
+}
+
+/// Returns whether the set of ranks contains a straight (including the wheel).
+#[inline]
+fn is_straight(ranks: u16) -> bool {
+    const WHEEL: u16 = 0b1_0000_0000_1111;
+    let r = ranks as u32;
+    r & (r << 1) & (r << 2) & (r << 3) & (r << 4) != 0 || ranks & WHEEL == WHEEL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range::*;
+
+    #[test]
+    fn classify() {
+        let board = board_from_str("Td9d6h").unwrap();
+        let hand = |s: &str| {
+            (
+                card_from_str(&s[..2]).unwrap(),
+                card_from_str(&s[2..]).unwrap(),
+            )
+        };
+        let class = |s: &str| HandClass::classify(&board, hand(s));
+
+        assert_eq!(class("8c7c"), HandClass::Straight);
+        assert_eq!(class("TcTs"), HandClass::Set);
+        assert_eq!(class("Tc9c"), HandClass::TwoPair);
+        assert_eq!(class("AcAs"), HandClass::Overpair);
+        assert_eq!(class("AsTc"), HandClass::TopPair);
+        assert_eq!(class("9c8s"), HandClass::MiddlePair);
+        assert_eq!(class("7c7s"), HandClass::WeakPair);
+        assert_eq!(class("QdJd"), HandClass::ComboDraw);
+        assert_eq!(class("Ad2d"), HandClass::FlushDraw);
+        assert_eq!(class("QcJc"), HandClass::StraightDraw);
+        assert_eq!(class("AcKc"), HandClass::Overcards);
+        assert_eq!(class("4c2s"), HandClass::Air);
+
+        // board pair does not count
+        let board = board_from_str("AhAd7c").unwrap();
+        assert_eq!(HandClass::classify(&board, hand("KcQs")), HandClass::Air);
+
+        // no draws on the river
+        let board = board_from_str("Td9d6h2s3c").unwrap();
+        assert_eq!(HandClass::classify(&board, hand("Ad5d")), HandClass::Air);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::game::*;
 use crate::mutex_like::*;
 use std::mem::MaybeUninit;
 use std::ops::Range;
@@ -12,7 +13,7 @@ pub trait Game: Send + Sync {
     #[doc(hidden)]
     fn root(&self) -> MutexGuardLike<Self::Node>;
 
-    /// Returns the number of private hands of given player.
+    /// Returns the number of private hands of given player stored in each node.
     #[doc(hidden)]
     fn num_private_hands(&self, player: usize) -> usize;
 
@@ -20,6 +21,13 @@ pub trait Game: Send + Sync {
     #[doc(hidden)]
     fn initial_weights(&self, player: usize) -> &[f32];
 
+    /// Returns the index of the stored private hand that each private hand of given player
+    /// shares its storage with, or an empty slice if every private hand is stored separately.
+    #[doc(hidden)]
+    fn stored_hand_indices(&self, _player: usize) -> &[u16] {
+        &[]
+    }
+
     /// Computes the counterfactual values of given node.
     #[doc(hidden)]
     fn evaluate(
@@ -38,9 +46,6 @@ pub trait Game: Send + Sync {
     #[doc(hidden)]
     fn is_solved(&self) -> bool;
 
-    #[doc(hidden)]
-    fn save_to_file(&self);
-
     /// Sets the instance to be solved.
     #[doc(hidden)]
     fn set_solved(&mut self);
@@ -51,6 +56,17 @@ pub trait Game: Send + Sync {
         true
     }
 
+    /// Checks whether `operation` is valid for the current state of the game.
+    ///
+    /// The default implementation infers the state from [`is_ready`] and [`is_solved`].
+    ///
+    /// [`is_ready`]: Game::is_ready
+    /// [`is_solved`]: Game::is_solved
+    #[doc(hidden)]
+    fn check_operation(&self, operation: GameOperation) -> Result<(), StateError> {
+        check_generic_operation(self, operation)
+    }
+
     /// Returns whether the game is raked.
     #[doc(hidden)]
     fn is_raked(&self) -> bool {
@@ -80,6 +96,67 @@ pub trait Game: Send + Sync {
     fn is_compression_enabled(&self) -> bool {
         false
     }
+
+    /// Returns whether the storage of the given node is compressed.
+    #[doc(hidden)]
+    fn is_node_compressed(&self, _node: &Self::Node) -> bool {
+        self.is_compression_enabled()
+    }
+
+    /// Returns whether the cumulative regrets are stored in 64-bit floating point.
+    #[doc(hidden)]
+    fn is_f64_regrets_enabled(&self) -> bool {
+        false
+    }
+
+    /// Hint for parallelization of the given node. By default, it refers to the hint of the node.
+    #[doc(hidden)]
+    fn enable_parallelization(&self, node: &Self::Node) -> bool {
+        node.enable_parallelization()
+    }
+
+    /// Notifies that a solver iteration is started.
+    #[doc(hidden)]
+    fn mark_solving(&self) {}
+
+    /// Returns the number of nodes in the game tree, or zero if unknown.
+    #[doc(hidden)]
+    fn num_nodes_hint(&self) -> usize {
+        0
+    }
+
+    /// Returns the memory usage of the game in bytes, or zero if unknown.
+    #[doc(hidden)]
+    fn memory_usage_hint(&self) -> u64 {
+        0
+    }
+
+    /// Notifies the exploitability measured during solving, so that the streets whose accuracy
+    /// targets are satisfied can be frozen.
+    #[doc(hidden)]
+    fn update_frozen_streets(&self, _exploitability: f32) {}
+
+    /// Returns whether the strategy of the given node is frozen, i.e., fixed to its average
+    /// strategy and no longer updated.
+    #[doc(hidden)]
+    fn is_node_frozen(&self, _node: &Self::Node) -> bool {
+        false
+    }
+
+    /// Returns whether the storage of the given node is allocated. An unallocated node must
+    /// behave as freshly allocated when read, and must not be written.
+    #[doc(hidden)]
+    fn is_node_allocated(&self, _node: &Self::Node) -> bool {
+        true
+    }
+
+    /// Allocates the storage of the given node if necessary when the solver visits it with the
+    /// given counterfactual reach probabilities. Returns whether the storage is allocated; if not,
+    /// the solver skips the subtree.
+    #[doc(hidden)]
+    fn allocate_node(&self, _node: &mut Self::Node, _cfreach: &[f32]) -> bool {
+        true
+    }
 }
 
 /// The trait representing a node in game tree.
@@ -120,6 +197,18 @@ pub trait GameNode: Send + Sync {
     #[doc(hidden)]
     fn regrets_mut(&mut self) -> &mut [f32];
 
+    /// Returns the cumulative regrets stored in 64-bit floating point.
+    #[doc(hidden)]
+    fn regrets_f64(&self) -> &[f64] {
+        unreachable!()
+    }
+
+    /// Returns the mutable reference to the cumulative regrets stored in 64-bit floating point.
+    #[doc(hidden)]
+    fn regrets_f64_mut(&mut self) -> &mut [f64] {
+        unreachable!()
+    }
+
     /// Returns the counterfactual values.
     #[doc(hidden)]
     fn cfvalues(&self) -> &[f32];
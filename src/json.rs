@@ -0,0 +1,266 @@
+//! Minimal JSON reader and writer used by the bot adapters and the exporters.
+
+use crate::error::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(BTreeMap<String, JsonValue>),
+}
+
+impl JsonValue {
+    /// Parses a JSON document.
+    pub(crate) fn parse(s: &str) -> Result<Self, Error> {
+        let mut chars = s.chars().peekable();
+        let value = parse_value(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next().is_some() {
+            return Err(Error::invalid_data("Trailing characters after JSON value"));
+        }
+        Ok(value)
+    }
+
+    /// Returns the member of an object.
+    #[inline]
+    pub(crate) fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Serializes the value into a compact JSON string. Non-finite numbers are written as `null`.
+    pub(crate) fn to_json_string(&self) -> String {
+        let mut ret = String::new();
+        self.write_to(&mut ret);
+        ret
+    }
+
+    fn write_to(&self, dst: &mut String) {
+        match self {
+            JsonValue::Null => dst.push_str("null"),
+            JsonValue::Bool(b) => write!(dst, "{b}").unwrap(),
+            JsonValue::Number(x) if x.is_finite() => write!(dst, "{x}").unwrap(),
+            JsonValue::Number(_) => dst.push_str("null"),
+            JsonValue::String(s) => write_json_string(dst, s),
+            JsonValue::Array(array) => {
+                dst.push('[');
+                for (i, value) in array.iter().enumerate() {
+                    if i > 0 {
+                        dst.push(',');
+                    }
+                    value.write_to(dst);
+                }
+                dst.push(']');
+            }
+            JsonValue::Object(map) => {
+                dst.push('{');
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        dst.push(',');
+                    }
+                    write_json_string(dst, key);
+                    dst.push(':');
+                    value.write_to(dst);
+                }
+                dst.push('}');
+            }
+        }
+    }
+}
+
+/// Writes `s` as a JSON string literal.
+pub(crate) fn write_json_string(dst: &mut String, s: &str) {
+    dst.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => dst.push_str("\\\""),
+            '\\' => dst.push_str("\\\\"),
+            '\n' => dst.push_str("\\n"),
+            '\r' => dst.push_str("\\r"),
+            '\t' => dst.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(dst, "\\u{:04x}", c as u32).unwrap(),
+            c => dst.push(c),
+        }
+    }
+    dst.push('"');
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+        chars.next();
+    }
+}
+
+fn expect_literal(chars: &mut Peekable<Chars>, literal: &str) -> Result<(), Error> {
+    for expected in literal.chars() {
+        if chars.next() != Some(expected) {
+            return Err(Error::invalid_data(format!("Expected `{literal}`")));
+        }
+    }
+    Ok(())
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<JsonValue, Error> {
+    skip_whitespace(chars);
+    match chars.peek() {
+        Some('n') => expect_literal(chars, "null").map(|_| JsonValue::Null),
+        Some('t') => expect_literal(chars, "true").map(|_| JsonValue::Bool(true)),
+        Some('f') => expect_literal(chars, "false").map(|_| JsonValue::Bool(false)),
+        Some('"') => parse_string(chars).map(JsonValue::String),
+        Some('[') => {
+            chars.next();
+            let mut array = Vec::new();
+            skip_whitespace(chars);
+            if chars.peek() == Some(&']') {
+                chars.next();
+                return Ok(JsonValue::Array(array));
+            }
+            loop {
+                array.push(parse_value(chars)?);
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some(']') => return Ok(JsonValue::Array(array)),
+                    _ => return Err(Error::invalid_data("Expected `,` or `]`")),
+                }
+            }
+        }
+        Some('{') => {
+            chars.next();
+            let mut map = BTreeMap::new();
+            skip_whitespace(chars);
+            if chars.peek() == Some(&'}') {
+                chars.next();
+                return Ok(JsonValue::Object(map));
+            }
+            loop {
+                skip_whitespace(chars);
+                let key = parse_string(chars)?;
+                skip_whitespace(chars);
+                if chars.next() != Some(':') {
+                    return Err(Error::invalid_data("Expected `:`"));
+                }
+                map.insert(key, parse_value(chars)?);
+                skip_whitespace(chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some('}') => return Ok(JsonValue::Object(map)),
+                    _ => return Err(Error::invalid_data("Expected `,` or `}`")),
+                }
+            }
+        }
+        Some(c) if *c == '-' || c.is_ascii_digit() => {
+            let mut s = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                    s.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            s.parse::<f64>()
+                .map(JsonValue::Number)
+                .map_err(|_| Error::invalid_data(format!("Invalid number: {s}")))
+        }
+        _ => Err(Error::invalid_data("Unexpected character")),
+    }
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, Error> {
+    if chars.next() != Some('"') {
+        return Err(Error::invalid_data("Expected string"));
+    }
+
+    let mut s = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(s),
+            Some('\\') => match chars.next() {
+                Some('"') => s.push('"'),
+                Some('\\') => s.push('\\'),
+                Some('/') => s.push('/'),
+                Some('b') => s.push('\u{8}'),
+                Some('f') => s.push('\u{c}'),
+                Some('n') => s.push('\n'),
+                Some('r') => s.push('\r'),
+                Some('t') => s.push('\t'),
+                Some('u') => {
+                    let hex = chars.by_ref().take(4).collect::<String>();
+                    let code = u32::from_str_radix(&hex, 16)
+                        .map_err(|_| Error::invalid_data(format!("Invalid escape: \\u{hex}")))?;
+                    s.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                _ => return Err(Error::invalid_data("Invalid escape")),
+            },
+            Some(c) => s.push(c),
+            None => return Err(Error::invalid_data("Unterminated string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_parse() {
+        let value = JsonValue::parse(
+            r#" {"a": [1, -2.5e1, "x\"yA"], "b": {"c": true, "d": null}, "e": []} "#,
+        )
+        .unwrap();
+
+        let a = value.get("a").unwrap().as_array().unwrap();
+        assert_eq!(a[0].as_f64(), Some(1.0));
+        assert_eq!(a[1].as_f64(), Some(-25.0));
+        assert_eq!(a[2].as_str(), Some("x\"yA"));
+        assert_eq!(
+            value.get("b").unwrap().get("c"),
+            Some(&JsonValue::Bool(true))
+        );
+        assert_eq!(value.get("b").unwrap().get("d"), Some(&JsonValue::Null));
+        assert_eq!(value.get("e").unwrap().as_array(), Some(&[][..]));
+
+        assert!(JsonValue::parse("{\"a\": 1,}").is_err());
+        assert!(JsonValue::parse("[1] 2").is_err());
+        assert!(JsonValue::parse("\"abc").is_err());
+
+        let json = value.to_json_string();
+        assert_eq!(JsonValue::parse(&json).unwrap(), value);
+        assert_eq!(JsonValue::Number(f64::NAN).to_json_string(), "null");
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! # Examples
 //!
-//! See the [examples] directory.
+//! See the [examples] directory. For the common path, [`GameBuilder`] builds a game from strings
+//! and [`prelude`] exports the frequently used items.
 //!
 //! [examples]: https://github.com/b-inary/postflop-solver/tree/main/examples
 //!
@@ -10,6 +11,8 @@
 //! - **Algorithm**: The solver uses the state-of-the-art [Discounted CFR] algorithm.
 //!   Currently, the value of γ is set to 3.0 instead of the 2.0 recommended in the original paper.
 //!   Also, the solver resets the cumulative strategy when the number of iterations is a power of 4.
+//!   The parameters and other variants of CFR (CFR+, Linear CFR, vanilla CFR, and Monte Carlo CFR)
+//!   can be selected with [`SolverConfig`].
 //! - **Performance**: The solver engine is highly optimized for performance with maintainable code.
 //!   The engine supports multithreading by default, and it takes full advantage of unsafe Rust in hot spots.
 //!   The developer reviews the assembly output from the compiler and ensures that SIMD instructions are used as much as possible.
@@ -31,59 +34,218 @@
 //!
 //! [Discounted CFR]: https://arxiv.org/abs/1809.04040
 //!
+//! # Exported JSON formats
+//! The JSON formats written by the crate (`StudyReport::to_json`, `PostFlopGame::export_strategy`
+//! with `ExportFormat::Json`, the messages of `SolveStreamer`, and `OpenSpielPolicy::to_json`)
+//! are described by [JSON Schema] documents, which are available through [`schemas`].
+//!
+//! [JSON Schema]: https://json-schema.org/
+//!
 //! # Crate features
+//! - `acpc`: Enables the client of the ACPC dealer protocol.
+//!   Disabled by default.
+//! - `affinity`: Uses [core_affinity] crate to pin the worker threads of `rayon` to the physical cores
+//!   (implies `rayon`).
+//!   Disabled by default.
+//! - `analysis`: Enables the analyses and reports of solved games, such as `generate_report`,
+//!   `analyze_node`, `rake_report`, `run_query`, `validate_solution`, and
+//!   `compute_exploitability_certificate`.
+//!   Enabled by default.
+//! - `arrow`: Uses [arrow-rs] crates to export the strategy table as Arrow record batches.
+//!   Disabled by default.
 //! - `bincode`: Uses [bincode] crate (2.0.0-rc.3) to serialize and deserialize the `PostFlopGame` struct.
-//!   This feature is required to save and load the game tree.
+//!   This feature is required to save and load the game tree (saving to and loading from files
+//!   additionally requires `io`).
+//!   The saved data is protected by [xxhash-rust] checksums, which are verified at load time.
 //!   Enabled by default.
 //! - `custom-alloc`: Uses custom memory allocator in solving process (only available in nightly Rust).
 //!   It significantly reduces the number of calls of the default allocator,
 //!   so it is recommended to use this feature when the default allocator is not so efficient.
 //!   Note that this feature assumes that, at most, only one instance of `PostFlopGame` is available
-//!   when solving in a program.
+//!   when solving in a program. Without this feature, the temporary buffers of the solver are
+//!   recycled across iterations by a per-thread pool instead.
+//!   Disabled by default.
+//! - `io`: Enables the functions that access the file system, such as `save_data_to_file`,
+//!   `load_data_from_file`, and `GoldenStrategies::load`.
+//!   The files are saved atomically (written to a temporary file, flushed to the disk, and
+//!   renamed), so an interrupted save never leaves a truncated file; `interrupted_save_path` and
+//!   `verify_saved_file` detect interrupted saves and damaged files.
+//!   Without this feature, the games can still be saved and loaded through `std::io` readers and
+//!   writers (e.g., `save_data_into_std_write`), which suits targets without a file system such as
+//!   `wasm32-unknown-unknown`.
+//!   Enabled by default.
+//! - `lp`: Enables `solve_lp`, an exact sequence-form linear programming solver for small games
+//!   (e.g., Kuhn poker or small river spots), which is useful to validate the results of CFR.
+//!   Disabled by default.
+//! - `mmap`: Uses [memmap2] crate to enable `MmapStorage`, a storage backend that maps the
+//!   strategies and the regrets to files, so that trees larger than the physical memory can be solved
+//!   (implies `io`).
+//!   Disabled by default.
+//! - `parquet`: Uses [parquet] crate to write the strategy table in the Parquet format (implies
+//!   `arrow` and `io`).
 //!   Disabled by default.
 //! - `rayon`: Uses [rayon] crate for parallelization.
 //!   Enabled by default.
+//! - `serde`: Uses [serde] crate to derive `Serialize` and `Deserialize` for the configuration
+//!   types (`CardConfig`, `TreeConfig`, and their fields), the exported strategies (e.g.,
+//!   `StrategyTable`, `PrunedSolution`, and `QuantizedStrategy`), and the report types.
+//!   The field names are those of the Rust structs, enums are externally tagged, and `Range` is
+//!   represented as a range string (e.g., `"QQ+,AKs"`).
+//!   Disabled by default.
+//! - `simd`: Uses [wide] crate to vectorize the slice operations of the solving process, such as
+//!   the regret matching and the accumulation of the counterfactual values, and the evaluation of
+//!   the terminal nodes.
+//!   Disabled by default.
+//! - `solver`: Enables the CFR solver (e.g., `solve`, `solve_step`, and `SolverConfig`) and the
+//!   utilities built on it, such as `solve_averaged`, `BatchRunner`, `resolve_subgame_unsafe`, and
+//!   `SolveStreamer`.
+//!   Without this feature, the crate can still build game trees and navigate and query loaded
+//!   solves, so applications that only browse saved solves can disable it (together with
+//!   `analysis` if not needed) to reduce the compile time and the binary size.
+//!   Enabled by default.
+//! - `sqlite`: Uses [rusqlite] crate to export the results of solved flops into a SQLite database
+//!   (implies `analysis`, `io`, and `solver`).
+//!   Disabled by default.
 //! - `zstd`: Uses [zstd] crate to compress and decompress the game tree.
 //!   This feature is required to save and load the game tree with compression.
 //!   Disabled by default.
 //!
+//! [arrow-rs]: https://github.com/apache/arrow-rs
 //! [bincode]: https://github.com/bincode-org/bincode
+//! [core_affinity]: https://github.com/Elzair/core_affinity_rs
+//! [memmap2]: https://github.com/RazrFalcon/memmap2-rs
+//! [parquet]: https://github.com/apache/arrow-rs/tree/master/parquet
 //! [rayon]: https://github.com/rayon-rs/rayon
+//! [rusqlite]: https://github.com/rusqlite/rusqlite
+//! [serde]: https://github.com/serde-rs/serde
+//! [wide]: https://github.com/Lokathor/wide
+//! [xxhash-rust]: https://github.com/DoumanAsh/xxhash-rust
 //! [zstd]: https://github.com/gyscos/zstd-rs
 
 #![cfg_attr(feature = "custom-alloc", feature(allocator_api))]
 
+#[cfg(feature = "acpc")]
+mod acpc;
+
+#[cfg(feature = "affinity")]
+mod affinity;
+
+#[cfg(feature = "arrow")]
+mod arrow;
+
+#[cfg(feature = "io")]
+mod atomic_file;
+
 #[cfg(feature = "custom-alloc")]
 mod alloc;
 
 #[cfg(feature = "bincode")]
 mod file;
 
+#[cfg(feature = "lp")]
+mod lp;
+
+#[cfg(feature = "solver")]
+mod averaging;
+
+#[cfg(feature = "solver")]
+mod batch;
+
+#[cfg(all(feature = "solver", not(feature = "custom-alloc")))]
+mod pool;
+
+#[cfg(feature = "solver")]
+mod solver;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
 mod action_tree;
 mod atomic_float;
 mod bet_size;
+mod blueprint;
+mod builder;
 mod bunching;
 mod card;
+mod equity;
+mod error;
+mod evaluator;
 mod game;
 mod hand;
+mod hand_class;
 mod hand_table;
 mod interface;
+mod json;
+mod multiway;
 mod mutex_like;
+mod openspiel;
+mod payoff;
+mod pio;
+mod preflop;
 mod range;
+mod rng;
+mod schema;
 mod sliceop;
-mod solver;
+mod slumbot;
+mod storage;
+mod topology;
 mod utility;
+mod verify;
+
+pub mod prelude;
+
+#[cfg(feature = "acpc")]
+pub use acpc::*;
+
+#[cfg(feature = "affinity")]
+pub use affinity::*;
+
+#[cfg(feature = "arrow")]
+pub use arrow::*;
+
+#[cfg(feature = "io")]
+pub use atomic_file::*;
 
 #[cfg(feature = "bincode")]
 pub use file::*;
 
+#[cfg(feature = "lp")]
+pub use lp::*;
+
+#[cfg(feature = "solver")]
+pub use averaging::*;
+
+#[cfg(feature = "solver")]
+pub use batch::*;
+
+#[cfg(feature = "solver")]
+pub use solver::*;
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
+
 pub use action_tree::*;
 pub use bet_size::*;
+pub use builder::*;
 pub use bunching::*;
 pub use card::*;
+pub use equity::*;
+pub use error::*;
+pub use evaluator::*;
 pub use game::*;
+pub use hand_class::*;
 pub use interface::*;
+pub use multiway::*;
 pub use mutex_like::*;
+pub use openspiel::*;
+pub use payoff::*;
+pub use pio::*;
+pub use preflop::*;
 pub use range::*;
-pub use solver::*;
+pub use rng::*;
+pub use schema::*;
+pub use slumbot::*;
+pub use storage::*;
+pub use topology::*;
 pub use utility::*;
+pub use verify::*;
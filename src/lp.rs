@@ -0,0 +1,622 @@
+use crate::error::*;
+use crate::game::GameOperation;
+use crate::interface::*;
+use crate::rng::*;
+use crate::utility::*;
+use std::collections::HashMap;
+
+/// Solves the game exactly by linear programming and sets the equilibrium strategy.
+///
+/// The game is converted to its sequence form and each player's equilibrium strategy is computed
+/// by the dense simplex method. This gives the ground-truth equilibrium of small games (e.g.,
+/// Kuhn poker, Leduc poker, or a river spot with narrow ranges and few bet sizes) that the
+/// results of [`solve`] and new solver variants can be validated against. The size of the linear
+/// program grows with the number of nodes times the number of private hands, so this is not
+/// practical for real-sized trees.
+///
+/// The equilibrium strategy is written to the strategy storage and the game is finalized as in
+/// [`solve`], so the usual functions such as [`compute_exploitability`] and
+/// [`compute_current_ev`] can be applied to the result. The return value is the value of the
+/// game for the first player (OOP), i.e., the first element of [`compute_current_ev`] at the
+/// equilibrium.
+///
+/// The game must be ready to be solved, not raked (the game must be zero-sum), and must neither
+/// use compression nor locked strategies. When the game has isomorphic chances, the initial
+/// weights are assumed to be invariant under the swaps, and the strategies are constrained to be
+/// symmetric accordingly, as in [`solve`]. Returns an error if these conditions are not met or
+/// the linear program fails numerically.
+///
+/// [`solve`]: crate::solve
+pub fn solve_lp<T: Game>(game: &mut T) -> Result<f32, Error> {
+    game.check_operation(GameOperation::Solve)?;
+
+    if !game.is_ready() {
+        return Err(Error::Config("Game is not ready".to_string()));
+    }
+
+    if game.is_raked() {
+        return Err(Error::Config("Raked games are not supported".to_string()));
+    }
+
+    if game.is_compression_enabled() {
+        return Err(Error::Config("Compression is not supported".to_string()));
+    }
+
+    let mut form = SequenceForm::new(game);
+    let identity = [0, 1].map(|player| (0..form.num_hands[player] as u16).collect());
+    form.build_recursive(game, &game.root(), [0, 0], &[identity], 1.0)?;
+
+    let (value, plan) = form.solve_player(0)?;
+    let (value_ip, plan_ip) = form.solve_player(1)?;
+
+    let tolerance = 1e-6 * (1.0 + value.abs());
+    if (value + value_ip).abs() > tolerance {
+        return Err(Error::Solver(format!(
+            "Inconsistent game values: {value} and {}",
+            -value_ip
+        )));
+    }
+
+    let plans = [plan, plan_ip];
+    let mut nodes = form.decision_nodes.iter();
+    write_strategy_recursive(&mut *game.root(), &form, &plans, &mut nodes);
+    finalize(game);
+
+    Ok(value as f32)
+}
+
+/// A sparse row of a constraint matrix and its right-hand side.
+type Constraint = (Vec<(usize, f64)>, f64);
+
+/// The sequence form of a game.
+///
+/// The variables of each player are the realization probabilities of the pairs of a sequence and
+/// a private hand, where the variable of sequence `s` and hand `h` is at `s * num_hands + h` and
+/// sequence `0` is the empty sequence.
+struct SequenceForm {
+    num_hands: [usize; 2],
+    num_sequences: [usize; 2],
+    weights: [Vec<f64>; 2],
+
+    /// The sparse rows and right-hand sides of the equality constraints of each player.
+    constraints: [Vec<Constraint>; 2],
+
+    /// The nonzero entries of the payoff matrix of the first player, as triples of the variables
+    /// of the first and the second player and the payoff.
+    payoff: Vec<(usize, usize, f64)>,
+
+    /// The player and the first sequence of each decision node, in the depth-first order.
+    decision_nodes: Vec<(usize, usize)>,
+
+    /// The variables identified with another variable by the symmetry of the isomorphic chances
+    /// (union-find).
+    aliases: [HashMap<usize, usize>; 2],
+}
+
+impl SequenceForm {
+    fn new<T: Game>(game: &T) -> Self {
+        let num_hands = [game.num_private_hands(0), game.num_private_hands(1)];
+        let weights = [0, 1].map(|player| {
+            let weights = game.initial_weights(player);
+            weights.iter().map(|&w| w as f64).collect()
+        });
+
+        // the empty sequence is always played
+        let constraints = num_hands.map(|n| (0..n).map(|h| (vec![(h, 1.0)], 1.0)).collect());
+
+        Self {
+            num_hands,
+            num_sequences: [1, 1],
+            weights,
+            constraints,
+            payoff: Vec::new(),
+            decision_nodes: Vec::new(),
+            aliases: Default::default(),
+        }
+    }
+
+    /// Adds the constraints and the payoffs of the subtree rooted at `node`.
+    ///
+    /// `parent` is the last sequence of each player, and `perms` is the list of the permutations
+    /// of the private hands of each player under which the subtree is played (the identity and
+    /// the swaps of the isomorphic chances).
+    fn build_recursive<T: Game>(
+        &mut self,
+        game: &T,
+        node: &T::Node,
+        parent: [usize; 2],
+        perms: &[[Vec<u16>; 2]],
+        scale: f64,
+    ) -> Result<(), Error> {
+        if node.is_terminal() {
+            self.add_payoffs(game, node, parent, perms, scale);
+            return Ok(());
+        }
+
+        if node.is_chance() {
+            let scale = scale / game.chance_factor(node) as f64;
+            let isomorphic_chances = game.isomorphic_chances(node);
+
+            // the reach probabilities must be invariant under the swaps
+            for index in 0..isomorphic_chances.len() {
+                let swap_list = game.isomorphic_swap(node, index);
+                for player in 0..2 {
+                    for &(h1, h2) in &swap_list[player] {
+                        self.check_symmetric_weights(player, h1, h2)?;
+                        let v1 = self.var(player, parent[player], h1 as usize);
+                        let v2 = self.var(player, parent[player], h2 as usize);
+                        self.identify(player, v1, v2);
+                    }
+                }
+            }
+
+            for action in node.action_indices() {
+                let mut child_perms = perms.to_vec();
+                for (index, &child_index) in isomorphic_chances.iter().enumerate() {
+                    if child_index as usize == action {
+                        let swap_list = game.isomorphic_swap(node, index);
+                        child_perms.extend(perms.iter().map(|perm| {
+                            [0, 1].map(|player| compose_swap(&perm[player], &swap_list[player]))
+                        }));
+                    }
+                }
+
+                let child = node.play(action);
+                self.build_recursive(game, &child, parent, &child_perms, scale)?;
+            }
+
+            return Ok(());
+        }
+
+        if !game.locking_strategy(node).is_empty() {
+            return Err(Error::Config(
+                "Locked strategies are not supported".to_string(),
+            ));
+        }
+
+        let player = node.player();
+        let num_actions = node.num_actions();
+        let first = self.num_sequences[player];
+        self.num_sequences[player] += num_actions;
+        self.decision_nodes.push((player, first));
+
+        for hand in 0..self.num_hands[player] {
+            let mut row = vec![(self.var(player, parent[player], hand), -1.0)];
+            row.extend((0..num_actions).map(|a| (self.var(player, first + a, hand), 1.0)));
+            self.constraints[player].push((row, 0.0));
+        }
+
+        for action in node.action_indices() {
+            let mut child_parent = parent;
+            child_parent[player] = first + action;
+            let child = node.play(action);
+            self.build_recursive(game, &child, child_parent, perms, scale)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the payoffs of the terminal node `node`.
+    fn add_payoffs<T: Game>(
+        &mut self,
+        game: &T,
+        node: &T::Node,
+        parent: [usize; 2],
+        perms: &[[Vec<u16>; 2]],
+        scale: f64,
+    ) {
+        let [num_hands, num_hands_ip] = self.num_hands;
+
+        // the payoffs of the first player against each hand of the second player
+        let mut matrix = vec![0.0; num_hands * num_hands_ip];
+        let mut cfreach = vec![0.0; num_hands_ip];
+        let mut result = Vec::with_capacity(num_hands);
+        for j in 0..num_hands_ip {
+            cfreach[j] = 1.0;
+            game.evaluate(result.spare_capacity_mut(), node, 0, &cfreach);
+            unsafe { result.set_len(num_hands) };
+            for (i, &v) in result.iter().enumerate() {
+                matrix[i * num_hands_ip + j] = v as f64;
+            }
+            result.clear();
+            cfreach[j] = 0.0;
+        }
+
+        for [perm, perm_ip] in perms {
+            for (&pi, &weight_oop) in perm.iter().zip(&self.weights[0]) {
+                let pi = pi as usize;
+                let v = self.var(0, parent[0], pi);
+                for (&pj, &weight_ip) in perm_ip.iter().zip(&self.weights[1]) {
+                    let pj = pj as usize;
+                    let value = matrix[pi * num_hands_ip + pj];
+                    if value != 0.0 {
+                        let weight = weight_oop * weight_ip;
+                        let w = self.var(1, parent[1], pj);
+                        self.payoff.push((v, w, scale * weight * value));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns an error if the initial weights of `player` are not invariant under the swap.
+    fn check_symmetric_weights(&self, player: usize, h1: u16, h2: u16) -> Result<(), Error> {
+        let weights = &self.weights[player];
+        if weights[h1 as usize] != weights[h2 as usize] {
+            return Err(Error::Config(
+                "Initial weights are not symmetric under the isomorphism".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn var(&self, player: usize, sequence: usize, hand: usize) -> usize {
+        sequence * self.num_hands[player] + hand
+    }
+
+    /// Returns the representative of the variable `v`.
+    #[inline]
+    fn find(&self, player: usize, mut v: usize) -> usize {
+        while let Some(&parent) = self.aliases[player].get(&v) {
+            v = parent;
+        }
+        v
+    }
+
+    /// Identifies the variables `v1` and `v2`.
+    #[inline]
+    fn identify(&mut self, player: usize, v1: usize, v2: usize) {
+        let (r1, r2) = (self.find(player, v1), self.find(player, v2));
+        if r1 != r2 {
+            self.aliases[player].insert(r1.max(r2), r1.min(r2));
+        }
+    }
+
+    #[inline]
+    fn num_vars(&self, player: usize) -> usize {
+        self.num_sequences[player] * self.num_hands[player]
+    }
+
+    /// Computes the value and the realization plan of the maximin strategy of `player`.
+    ///
+    /// The linear program is `max f^T q` subject to `E x = e`, `F^T q <= B^T x`, and `x >= 0`,
+    /// where `E x = e` and `F y = f` are the constraints of `player` and the opponent, and `B` is
+    /// the payoff matrix of `player`.
+    fn solve_player(&self, player: usize) -> Result<(f64, Vec<f64>), Error> {
+        let opponent = player ^ 1;
+        let num_x = self.num_vars(player);
+        let num_y = self.num_vars(opponent);
+        let own_rows = &self.constraints[player];
+        let opp_rows = &self.constraints[opponent];
+        let num_q = opp_rows.len();
+
+        // variables: x, q+, q-, and the slacks of `F^T q <= B^T x`
+        let num_cols = num_x + 2 * num_q + num_y;
+        let num_rows = own_rows.len() + num_y;
+        let mut lp = LinearProgram::new(num_rows, num_cols);
+
+        for (r, (row, rhs)) in own_rows.iter().enumerate() {
+            for &(v, coef) in row {
+                *lp.coef_mut(r, self.find(player, v)) += coef;
+            }
+            lp.rhs[r] = *rhs;
+        }
+
+        let offset = own_rows.len();
+        for (q, (row, _)) in opp_rows.iter().enumerate() {
+            for &(w, coef) in row {
+                let w = self.find(opponent, w);
+                *lp.coef_mut(offset + w, num_x + q) += coef;
+                *lp.coef_mut(offset + w, num_x + num_q + q) -= coef;
+            }
+        }
+
+        for &(v0, v1, payoff) in &self.payoff {
+            let (v, w, payoff) = match player {
+                0 => (v0, v1, payoff),
+                _ => (v1, v0, -payoff),
+            };
+            let (v, w) = (self.find(player, v), self.find(opponent, w));
+            *lp.coef_mut(offset + w, v) -= payoff;
+        }
+
+        for w in 0..num_y {
+            let slack = num_x + 2 * num_q + w;
+            *lp.coef_mut(offset + w, slack) = 1.0;
+            lp.basis[offset + w] = Some(slack);
+        }
+
+        let mut objective = vec![0.0; num_cols];
+        for (q, (_, rhs)) in opp_rows.iter().enumerate() {
+            objective[num_x + q] = *rhs;
+            objective[num_x + num_q + q] = -*rhs;
+        }
+
+        let (value, solution) = lp.maximize(&objective)?;
+        Ok((value, solution[..num_x].to_vec()))
+    }
+}
+
+/// Returns the permutation `perm` followed by the swaps in `swap_list`.
+fn compose_swap(perm: &[u16], swap_list: &[(u16, u16)]) -> Vec<u16> {
+    let mut swap = (0..perm.len() as u16).collect::<Vec<_>>();
+    for &(h1, h2) in swap_list {
+        swap.swap(h1 as usize, h2 as usize);
+    }
+    perm.iter().map(|&h| swap[h as usize]).collect()
+}
+
+/// Writes the behavioral strategies obtained from the realization plans.
+fn write_strategy_recursive<'a, N: GameNode>(
+    node: &mut N,
+    form: &SequenceForm,
+    plans: &[Vec<f64>; 2],
+    nodes: &mut impl Iterator<Item = &'a (usize, usize)>,
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if !node.is_chance() {
+        let &(player, first) = nodes.next().unwrap();
+        let num_actions = node.num_actions();
+        let num_hands = form.num_hands[player];
+        let plan = &plans[player];
+        let strategy = node.strategy_mut();
+
+        for hand in 0..num_hands {
+            let probs = (0..num_actions)
+                .map(|a| plan[form.find(player, form.var(player, first + a, hand))].max(0.0))
+                .collect::<Vec<_>>();
+            let sum = probs.iter().sum::<f64>();
+            for (a, prob) in probs.into_iter().enumerate() {
+                strategy[a * num_hands + hand] = if sum > 1e-12 {
+                    (prob / sum) as f32
+                } else {
+                    1.0 / num_actions as f32
+                };
+            }
+        }
+    }
+
+    for action in node.action_indices() {
+        write_strategy_recursive(&mut *node.play(action), form, plans, nodes);
+    }
+}
+
+/// Relative tolerance of the simplex method.
+const EPSILON: f64 = 1e-9;
+
+/// Maximum perturbation of the right-hand side.
+const PERTURBATION: f64 = 1e-6;
+
+/// Number of consecutive degenerate pivots after which Bland's rule is used.
+const DEGENERATE_LIMIT: usize = 50;
+
+/// A linear program in the equality form: `a z = rhs` and `z >= 0` with `rhs >= 0`.
+struct LinearProgram {
+    num_rows: usize,
+    num_cols: usize,
+    a: Vec<f64>,
+    rhs: Vec<f64>,
+
+    /// The initial basic variable of each row, if any (the column must be a unit vector).
+    basis: Vec<Option<usize>>,
+}
+
+impl LinearProgram {
+    fn new(num_rows: usize, num_cols: usize) -> Self {
+        Self {
+            num_rows,
+            num_cols,
+            a: vec![0.0; num_rows * num_cols],
+            rhs: vec![0.0; num_rows],
+            basis: vec![None; num_rows],
+        }
+    }
+
+    #[inline]
+    fn coef_mut(&mut self, row: usize, col: usize) -> &mut f64 {
+        &mut self.a[row * self.num_cols + col]
+    }
+
+    /// Maximizes `objective^T z` by the two-phase simplex method.
+    fn maximize(&self, objective: &[f64]) -> Result<(f64, Vec<f64>), Error> {
+        // phase 1: add artificial variables to the rows without a basic variable
+        let artificial_rows = (0..self.num_rows)
+            .filter(|&r| self.basis[r].is_none())
+            .collect::<Vec<_>>();
+        let num_cols = self.num_cols + artificial_rows.len();
+        let width = num_cols + 1;
+
+        // the right-hand side is perturbed to avoid stalling at degenerate vertices; the exact
+        // solution is recovered from the optimal basis
+        let mut rng = SolverRng::new(0);
+        let mut perturbation = || PERTURBATION * rng.next_f32() as f64;
+
+        let mut tableau = Tableau {
+            num_rows: self.num_rows,
+            width,
+            data: vec![0.0; (self.num_rows + 1) * width],
+            basis: vec![0; self.num_rows],
+        };
+
+        for r in 0..self.num_rows {
+            let src = &self.a[r * self.num_cols..(r + 1) * self.num_cols];
+            tableau.data[r * width..r * width + self.num_cols].copy_from_slice(src);
+            tableau.data[r * width + num_cols] = self.rhs[r] + perturbation();
+            if let Some(col) = self.basis[r] {
+                tableau.basis[r] = col;
+            }
+        }
+
+        for (k, &r) in artificial_rows.iter().enumerate() {
+            let col = self.num_cols + k;
+            tableau.data[r * width + col] = 1.0;
+            tableau.basis[r] = col;
+        }
+
+        // the columns of the initial basis hold the inverse of the basis matrix
+        let initial_basis = tableau.basis.clone();
+
+        let mut phase1 = vec![0.0; num_cols];
+        phase1[self.num_cols..].fill(-1.0);
+        let infeasibility = tableau.optimize(&phase1, num_cols)?;
+        if infeasibility < -1e3 * PERTURBATION {
+            return Err(Error::Solver("Linear program is infeasible".to_string()));
+        }
+
+        // drive the artificial variables out of the basis
+        for r in 0..self.num_rows {
+            if tableau.basis[r] >= self.num_cols {
+                let row = &tableau.data[r * width..r * width + self.num_cols];
+                if let Some(col) = row.iter().position(|v| v.abs() > EPSILON) {
+                    tableau.pivot(r, col);
+                }
+            }
+        }
+
+        // phase 2
+        let mut phase2 = objective.to_vec();
+        phase2.resize(num_cols, 0.0);
+        tableau.optimize(&phase2, self.num_cols)?;
+
+        // recover the basic solution of the unperturbed right-hand side
+        let mut solution = vec![0.0; self.num_cols];
+        for r in 0..self.num_rows {
+            if tableau.basis[r] < self.num_cols {
+                let row = &tableau.data[r * width..(r + 1) * width];
+                let value = initial_basis
+                    .iter()
+                    .zip(&self.rhs)
+                    .map(|(&col, &rhs)| row[col] * rhs)
+                    .sum::<f64>();
+                solution[tableau.basis[r]] = value;
+            }
+        }
+
+        let value = objective.iter().zip(&solution).map(|(c, z)| c * z).sum();
+        Ok((value, solution))
+    }
+}
+
+/// A dense simplex tableau whose last row is the objective row.
+struct Tableau {
+    num_rows: usize,
+    width: usize,
+    data: Vec<f64>,
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    /// Maximizes `objective^T z`, where only the first `num_enterable` columns may enter the
+    /// basis. Returns the optimal value.
+    fn optimize(&mut self, objective: &[f64], num_enterable: usize) -> Result<f64, Error> {
+        let width = self.width;
+        let rhs_col = width - 1;
+        let obj = self.num_rows * width;
+
+        // reduced costs: -objective + (objective of the basic variables) * rows
+        self.data[obj..].fill(0.0);
+        for (col, &c) in objective.iter().enumerate() {
+            self.data[obj + col] = -c;
+        }
+        for r in 0..self.num_rows {
+            let c = objective[self.basis[r]];
+            if c != 0.0 {
+                for col in 0..width {
+                    self.data[obj + col] += c * self.data[r * width + col];
+                }
+            }
+        }
+
+        let max_iterations = 100 * (self.num_rows + width);
+        let mut num_degenerate = 0;
+
+        for _ in 0..max_iterations {
+            let reduced_costs = &self.data[obj..obj + num_enterable];
+            let entering = if num_degenerate < DEGENERATE_LIMIT {
+                // Dantzig's rule
+                let (col, &min) = reduced_costs
+                    .iter()
+                    .enumerate()
+                    .min_by(|a, b| a.1.total_cmp(b.1))
+                    .unwrap();
+                (min < -EPSILON).then_some(col)
+            } else {
+                // Bland's rule
+                reduced_costs.iter().position(|&v| v < -EPSILON)
+            };
+
+            let Some(entering) = entering else {
+                return Ok(self.data[obj + rhs_col]);
+            };
+
+            // ratio test (ties are broken by the smallest basic variable)
+            let mut leaving = None;
+            let mut best_ratio = f64::INFINITY;
+            for r in 0..self.num_rows {
+                let coef = self.data[r * width + entering];
+                if coef > EPSILON {
+                    let ratio = self.data[r * width + rhs_col] / coef;
+                    let is_better = match leaving {
+                        None => true,
+                        Some(l) => {
+                            ratio < best_ratio - EPSILON
+                                || (ratio < best_ratio + EPSILON && self.basis[r] < self.basis[l])
+                        }
+                    };
+                    if is_better {
+                        leaving = Some(r);
+                        best_ratio = ratio;
+                    }
+                }
+            }
+
+            let Some(leaving) = leaving else {
+                return Err(Error::Solver("Linear program is unbounded".to_string()));
+            };
+
+            if best_ratio.abs() < EPSILON {
+                num_degenerate += 1;
+            } else {
+                num_degenerate = 0;
+            }
+
+            self.pivot(leaving, entering);
+        }
+
+        Err(Error::Solver("Simplex method did not converge".to_string()))
+    }
+
+    /// Pivots on the element at (`row`, `col`).
+    fn pivot(&mut self, row: usize, col: usize) {
+        let width = self.width;
+        let pivot = self.data[row * width + col];
+        let pivot_row = &mut self.data[row * width..(row + 1) * width];
+        pivot_row.iter_mut().for_each(|v| *v /= pivot);
+
+        // the tableau is sparse, so only the nonzero elements of the pivot row are used
+        let nonzeros = pivot_row
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| v != 0.0)
+            .map(|(i, &v)| (i, v))
+            .collect::<Vec<_>>();
+
+        for r in 0..=self.num_rows {
+            if r != row {
+                let factor = self.data[r * width + col];
+                if factor != 0.0 {
+                    let target = &mut self.data[r * width..(r + 1) * width];
+                    for &(i, v) in &nonzeros {
+                        target[i] -= factor * v;
+                    }
+                }
+            }
+        }
+
+        self.basis[row] = col;
+    }
+}
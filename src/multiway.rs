@@ -0,0 +1,1075 @@
+//! River-only restricted mode for multiway (3-way and 4-way) pots.
+//!
+//! This is not a generalization of [`PostFlopGame`] to more than two players. The [`Game`]
+//! trait and the solver of [`PostFlopGame`] exploit the heads-up structure of the game
+//! throughout (the opponent of a player, the reach probabilities of a single opponent, and the
+//! heads-up showdown evaluation), so multiway pots are handled by the separate [`MultiwayGame`]
+//! instead. It supports two to four players in a single betting round on a fixed five-card
+//! board, keeps a fold flag per seat, and evaluates the showdowns among all remaining players,
+//! taking the rake from the pot as [`TreeConfig::rake_rate`] does. It is deliberately
+//! restricted: there are no chance nodes, no isomorphism, and no compression, and all values are
+//! stored in `f32`.
+//!
+//! In particular, multiway flop and turn spots (e.g., BB vs BTN vs CO on the flop) cannot be
+//! built, and the features of [`PostFlopGame`] are not available for [`MultiwayGame`]: the
+//! storage modes, the compression, saving and loading, and the analysis API (e.g., the node
+//! navigation and the aggregated reports) all remain heads-up only.
+//!
+//! The terminal values are computed by enumerating the compatible hand combinations of the
+//! opponents, so the cost grows with the product of the range sizes; the solver is suitable for
+//! narrow ranges only. This is also why the flop and the turn are out of scope: every runout
+//! multiplies the number of terminal nodes (by about 45 for a turn spot and 2,000 for a flop
+//! spot), which makes the enumeration impractical even for narrow ranges, while supporting them
+//! in [`Game`] would require per-seat reach probabilities in every heads-up code path of
+//! [`PostFlopGame`] and the solver.
+//!
+//! For the same reason, [`MultiwayGame`] has its own CFR+ loop instead of [`solve`], so the
+//! options of [`SolverConfig`] (e.g., [`SolverConfig::check_finite`]) and [`SolveSummary`] do not
+//! apply. The loop is single-threaded, so solving is always deterministic. Note also that the
+//! convergence to a Nash equilibrium is not guaranteed for more than two players, so
+//! [`MultiwayGame::compute_exploitability`] reports the average gain of the best responses
+//! (NashConv divided by the number of players) rather than an exploitability in the heads-up
+//! sense.
+//!
+//! [`Game`]: crate::Game
+//! [`PostFlopGame`]: crate::PostFlopGame
+//! [`TreeConfig::rake_rate`]: crate::TreeConfig::rake_rate
+//! [`solve`]: crate::solve
+//! [`SolverConfig`]: crate::SolverConfig
+//! [`SolverConfig::check_finite`]: crate::SolverConfig::check_finite
+//! [`SolveSummary`]: crate::SolveSummary
+
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::error::*;
+use crate::evaluator::*;
+use crate::range::*;
+use std::io::{self, Write};
+
+/// The maximum number of players of a [`MultiwayGame`].
+pub const MAX_MULTIWAY_PLAYERS: usize = 4;
+
+/// Configuration of a [`MultiwayGame`].
+#[derive(Debug, Clone, Default)]
+pub struct MultiwayConfig {
+    /// The ranges of the players in the order of action.
+    pub ranges: Vec<Range>,
+
+    /// The five-card board.
+    pub board: [Card; 5],
+
+    /// The pot at the start of the river.
+    pub starting_pot: i32,
+
+    /// The remaining stack of each player at the start of the river.
+    pub effective_stack: i32,
+
+    /// The bet sizes shared by all players. Only [`BetSize::PotRelative`],
+    /// [`BetSize::PrevBetRelative`] (for raises), and [`BetSize::AllIn`] are supported.
+    pub bet_sizes: BetSizeOptions,
+
+    /// The maximum number of bets and raises in the betting round.
+    pub max_num_bets: usize,
+
+    /// Rake rate. Must be between `0.0` and `1.0`, inclusive.
+    ///
+    /// The rake, i.e., `min(pot * rake_rate, rake_cap)`, is taken from the pot both at showdown
+    /// and when all but one player fold, and the winners split the rest.
+    pub rake_rate: f64,
+
+    /// Rake cap. Must be non-negative.
+    pub rake_cap: f64,
+}
+
+/// A restricted solver of multiway river spots.
+///
+/// This is a river-only restricted mode, not a generalization of [`PostFlopGame`] to more than
+/// two players: multiway flop and turn spots cannot be built, and the storage modes, the
+/// compression, saving and loading, and the analysis API of [`PostFlopGame`] are not available.
+/// The terminal values are computed by enumerating the compatible hand combinations of the
+/// opponents, so the solver is suitable for narrow ranges only.
+///
+/// The strategies are computed by CFR+ with alternating updates and the linear averaging of the
+/// strategies.
+///
+/// [`PostFlopGame`]: crate::PostFlopGame
+#[derive(Debug, Clone)]
+pub struct MultiwayGame {
+    config: MultiwayConfig,
+    private_cards: Vec<Vec<(Card, Card)>>,
+    initial_weights: Vec<Vec<f32>>,
+    hand_masks: Vec<Vec<u64>>,
+    strengths: Vec<Vec<u32>>,
+    normalizer: f64,
+    nodes: Vec<MultiwayNode>,
+}
+
+#[derive(Debug, Clone)]
+struct MultiwayNode {
+    player: usize,
+    actions: Vec<Action>,
+    children: Vec<usize>,
+    contributions: Vec<i32>,
+    folded: u8,
+    cum_regret: Vec<f32>,
+    cum_strategy: Vec<f32>,
+}
+
+/// The state of the betting round while building the tree.
+#[derive(Debug, Clone)]
+struct BuildState {
+    player: usize,
+    contributions: Vec<i32>,
+    folded: u8,
+    pending: u8,
+    num_bets: usize,
+    min_raise: i32,
+}
+
+/// How the player of interest plays when computing the values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueMode {
+    Average,
+    BestResponse,
+}
+
+const PLAYER_TERMINAL: usize = usize::MAX;
+
+impl MultiwayGame {
+    /// Builds the game tree. Returns an error if the configuration is invalid.
+    pub fn new(config: MultiwayConfig) -> Result<Self, Error> {
+        let num_players = config.ranges.len();
+        if !(2..=MAX_MULTIWAY_PLAYERS).contains(&num_players) {
+            return Err(Error::Config(format!(
+                "Number of players must be between 2 and {MAX_MULTIWAY_PLAYERS}: {num_players}"
+            )));
+        }
+
+        let mut board_mask: u64 = 0;
+        for &card in &config.board {
+            if card >= 52 {
+                return Err(Error::Config(format!(
+                    "Board card must be in the range 0..52: {card}"
+                )));
+            }
+            if board_mask & (1 << card) != 0 {
+                return Err(Error::Config(format!(
+                    "Board cards must be unique: {:?}",
+                    config.board
+                )));
+            }
+            board_mask |= 1 << card;
+        }
+
+        if config.starting_pot <= 0 {
+            return Err(Error::Config(format!(
+                "Starting pot must be positive: {}",
+                config.starting_pot
+            )));
+        }
+
+        if config.effective_stack <= 0 {
+            return Err(Error::Config(format!(
+                "Effective stack must be positive: {}",
+                config.effective_stack
+            )));
+        }
+
+        if config.rake_rate < 0.0 {
+            return Err(Error::Config(format!(
+                "Rake rate must be non-negative: {}",
+                config.rake_rate
+            )));
+        }
+
+        if config.rake_rate > 1.0 {
+            return Err(Error::Config(format!(
+                "Rake rate must be less than or equal to 1.0: {}",
+                config.rake_rate
+            )));
+        }
+
+        if config.rake_cap < 0.0 {
+            return Err(Error::Config(format!(
+                "Rake cap must be non-negative: {}",
+                config.rake_cap
+            )));
+        }
+
+        for &size in &config.bet_sizes.bet {
+            if !matches!(size, BetSize::PotRelative(_) | BetSize::AllIn) {
+                return Err(Error::Config(format!("Unsupported bet size: {size:?}")));
+            }
+        }
+
+        for &size in &config.bet_sizes.raise {
+            if !matches!(
+                size,
+                BetSize::PotRelative(_) | BetSize::PrevBetRelative(_) | BetSize::AllIn
+            ) {
+                return Err(Error::Config(format!("Unsupported raise size: {size:?}")));
+            }
+        }
+
+        let mut private_cards = Vec::with_capacity(num_players);
+        let mut initial_weights = Vec::with_capacity(num_players);
+        for (player, range) in config.ranges.iter().enumerate() {
+            let (cards, weights) = range.get_hands_weights(board_mask);
+            if cards.is_empty() {
+                return Err(Error::Config(format!("Range of player {player} is empty")));
+            }
+            private_cards.push(cards);
+            initial_weights.push(weights);
+        }
+
+        let hand_masks = private_cards
+            .iter()
+            .map(|cards| {
+                cards
+                    .iter()
+                    .map(|&(c1, c2)| (1 << c1) | (1 << c2))
+                    .collect()
+            })
+            .collect();
+
+        let strengths = private_cards
+            .iter()
+            .map(|cards| {
+                cards
+                    .iter()
+                    .map(|&hand| HighHandEvaluator.evaluate(&config.board, hand))
+                    .collect()
+            })
+            .collect();
+
+        let mut game = Self {
+            config,
+            private_cards,
+            initial_weights,
+            hand_masks,
+            strengths,
+            normalizer: 0.0,
+            nodes: Vec::new(),
+        };
+
+        game.normalizer = game.compute_normalizer();
+        if game.normalizer == 0.0 {
+            return Err(Error::Config(
+                "Ranges have no compatible hand combination".to_string(),
+            ));
+        }
+
+        let all_players = ((1 << num_players) - 1) as u8;
+        game.build_tree(BuildState {
+            player: 0,
+            contributions: vec![0; num_players],
+            folded: 0,
+            pending: all_players,
+            num_bets: 0,
+            min_raise: 0,
+        });
+
+        Ok(game)
+    }
+
+    /// Returns the configuration.
+    #[inline]
+    pub fn config(&self) -> &MultiwayConfig {
+        &self.config
+    }
+
+    /// Returns the number of players.
+    #[inline]
+    pub fn num_players(&self) -> usize {
+        self.config.ranges.len()
+    }
+
+    /// Returns the number of nodes of the game tree.
+    #[inline]
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the private hands of `player`.
+    #[inline]
+    pub fn private_cards(&self, player: usize) -> &[(Card, Card)] {
+        &self.private_cards[player]
+    }
+
+    /// Returns the initial weights of the private hands of `player`.
+    #[inline]
+    pub fn initial_weights(&self, player: usize) -> &[f32] {
+        &self.initial_weights[player]
+    }
+
+    /// Performs CFR+ until the given number of iterations or exploitability is satisfied, and
+    /// returns the exploitability (see [`compute_exploitability`]).
+    ///
+    /// [`compute_exploitability`]: #method.compute_exploitability
+    pub fn solve(
+        &mut self,
+        max_num_iterations: u32,
+        target_exploitability: f32,
+        print_progress: bool,
+    ) -> f32 {
+        let mut exploitability = self.compute_exploitability();
+
+        if print_progress {
+            print!("iteration: 0 / {max_num_iterations} ");
+            print!("(exploitability = {exploitability:.4e})");
+            io::stdout().flush().unwrap();
+        }
+
+        for t in 0..max_num_iterations {
+            if exploitability <= target_exploitability {
+                break;
+            }
+
+            self.solve_step(t);
+
+            if (t + 1) % 10 == 0 || t + 1 == max_num_iterations {
+                exploitability = self.compute_exploitability();
+            }
+
+            if print_progress {
+                print!("\riteration: {} / {} ", t + 1, max_num_iterations);
+                print!("(exploitability = {exploitability:.4e})");
+                io::stdout().flush().unwrap();
+            }
+        }
+
+        if print_progress {
+            println!();
+            io::stdout().flush().unwrap();
+        }
+
+        exploitability
+    }
+
+    /// Proceeds CFR+ for one iteration, updating the players in the order of action.
+    pub fn solve_step(&mut self, current_iteration: u32) {
+        let weight = (current_iteration + 1) as f32;
+        for player in 0..self.num_players() {
+            let reach = self.initial_weights.clone();
+            self.cfr_recursive(0, player, &reach, weight);
+        }
+    }
+
+    /// Returns the expected value of each player at the root under the average strategies.
+    ///
+    /// The expected value is the amount of the pot won minus the amount put in on the river, so
+    /// the values of all players sum up to the starting pot minus the expected rake.
+    pub fn expected_values(&self) -> Vec<f32> {
+        (0..self.num_players())
+            .map(|player| self.root_value(player, ValueMode::Average) as f32)
+            .collect()
+    }
+
+    /// Computes the average over the players of the gain of a best response against the average
+    /// strategies of the other players (NashConv divided by the number of players).
+    ///
+    /// For two players, this is the exploitability in the usual sense.
+    pub fn compute_exploitability(&self) -> f32 {
+        let num_players = self.num_players();
+        let gain = (0..num_players)
+            .map(|player| {
+                self.root_value(player, ValueMode::BestResponse)
+                    - self.root_value(player, ValueMode::Average)
+            })
+            .sum::<f64>();
+        (gain / num_players as f64) as f32
+    }
+
+    /// Returns the player to act at the node reached by `history` (a list of action indices
+    /// from the root), or `None` if the node is terminal.
+    pub fn current_player(&self, history: &[usize]) -> Result<Option<usize>, Error> {
+        let node = &self.nodes[self.node_index(history)?];
+        Ok((node.player != PLAYER_TERMINAL).then_some(node.player))
+    }
+
+    /// Returns the available actions at the node reached by `history`. The amounts are the total
+    /// contributions of the acting player on the river.
+    pub fn available_actions(&self, history: &[usize]) -> Result<Vec<Action>, Error> {
+        Ok(self.nodes[self.node_index(history)?].actions.clone())
+    }
+
+    /// Returns whether each player has folded at the node reached by `history`.
+    pub fn folded_players(&self, history: &[usize]) -> Result<Vec<bool>, Error> {
+        let node = &self.nodes[self.node_index(history)?];
+        Ok((0..self.num_players())
+            .map(|player| node.folded & (1 << player) != 0)
+            .collect())
+    }
+
+    /// Returns the average strategy at the node reached by `history`.
+    ///
+    /// The return value is a vector of the length of `#(actions) * #(private hands)` of the
+    /// player to act, in the same layout as [`PostFlopGame::strategy`].
+    ///
+    /// [`PostFlopGame::strategy`]: crate::PostFlopGame::strategy
+    pub fn strategy(&self, history: &[usize]) -> Result<Vec<f32>, Error> {
+        let node = &self.nodes[self.node_index(history)?];
+        if node.player == PLAYER_TERMINAL {
+            return Err(Error::Tree("Terminal node is not allowed".to_string()));
+        }
+        Ok(average_strategy(node))
+    }
+
+    fn node_index(&self, history: &[usize]) -> Result<usize, Error> {
+        let mut index = 0;
+        for &action in history {
+            let node = &self.nodes[index];
+            index = *node
+                .children
+                .get(action)
+                .ok_or_else(|| Error::Tree(format!("Invalid action index: {action}")))?;
+        }
+        Ok(index)
+    }
+
+    /// Builds the subtree of `state` and returns the index of its root.
+    fn build_tree(&mut self, state: BuildState) -> usize {
+        let num_players = self.num_players();
+        let index = self.nodes.len();
+        let num_remaining = num_players - state.folded.count_ones() as usize;
+
+        if num_remaining == 1 || state.pending == 0 {
+            self.nodes.push(MultiwayNode {
+                player: PLAYER_TERMINAL,
+                actions: Vec::new(),
+                children: Vec::new(),
+                contributions: state.contributions,
+                folded: state.folded,
+                cum_regret: Vec::new(),
+                cum_strategy: Vec::new(),
+            });
+            return index;
+        }
+
+        // the next player to act in the pending players
+        let player = (0..num_players)
+            .map(|i| (state.player + i) % num_players)
+            .find(|&p| state.pending & (1 << p) != 0)
+            .unwrap();
+
+        let actions = self.push_actions(player, &state);
+        let num_elements = actions.len() * self.private_cards[player].len();
+        self.nodes.push(MultiwayNode {
+            player,
+            actions: actions.clone(),
+            children: Vec::new(),
+            contributions: state.contributions.clone(),
+            folded: state.folded,
+            cum_regret: vec![0.0; num_elements],
+            cum_strategy: vec![0.0; num_elements],
+        });
+
+        let stack = self.config.effective_stack;
+        let max_bet = *state.contributions.iter().max().unwrap();
+        let children = actions
+            .iter()
+            .map(|&action| {
+                let mut next = state.clone();
+                next.player = (player + 1) % num_players;
+                next.pending &= !(1 << player);
+                match action {
+                    Action::Fold => next.folded |= 1 << player,
+                    Action::Check => {}
+                    Action::Call => next.contributions[player] = max_bet.min(stack),
+                    Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => {
+                        next.contributions[player] = amount;
+                        next.min_raise = next.min_raise.max(amount - max_bet);
+                        next.num_bets += 1;
+                        next.pending = (0..num_players)
+                            .filter(|&p| p != player && next.folded & (1 << p) == 0)
+                            .filter(|&p| next.contributions[p] < stack)
+                            .fold(0, |pending, p| pending | 1 << p);
+                    }
+                    _ => unreachable!(),
+                }
+                self.build_tree(next)
+            })
+            .collect();
+
+        self.nodes[index].children = children;
+        index
+    }
+
+    /// Returns the available actions of `player` in `state`.
+    fn push_actions(&self, player: usize, state: &BuildState) -> Vec<Action> {
+        let num_players = self.num_players();
+        let stack = self.config.effective_stack;
+        let max_bet = *state.contributions.iter().max().unwrap();
+        let to_call = max_bet - state.contributions[player];
+        let pot = self.config.starting_pot + state.contributions.iter().sum::<i32>() + to_call;
+
+        let mut actions = Vec::new();
+        if to_call > 0 {
+            actions.push(Action::Fold);
+            actions.push(Action::Call);
+        } else {
+            actions.push(Action::Check);
+        }
+
+        // a bet or raise requires the chips behind and an opponent who can call it
+        let can_raise = state.num_bets < self.config.max_num_bets
+            && max_bet < stack
+            && (0..num_players).any(|p| {
+                p != player && state.folded & (1 << p) == 0 && state.contributions[p] < stack
+            });
+
+        if can_raise {
+            let min_amount = (max_bet + state.min_raise.max(1)).min(stack);
+            let (sizes, is_raise) = if max_bet == 0 {
+                (&self.config.bet_sizes.bet, false)
+            } else {
+                (&self.config.bet_sizes.raise, true)
+            };
+
+            for &size in sizes {
+                let amount = match size {
+                    BetSize::PotRelative(ratio) => max_bet + (pot as f64 * ratio).round() as i32,
+                    BetSize::PrevBetRelative(ratio) => (max_bet as f64 * ratio).round() as i32,
+                    _ => stack,
+                };
+
+                let amount = amount.clamp(min_amount, stack);
+                actions.push(match amount {
+                    _ if amount == stack => Action::AllIn(stack),
+                    _ if is_raise => Action::Raise(amount),
+                    _ => Action::Bet(amount),
+                });
+            }
+        }
+
+        actions.sort_unstable();
+        actions.dedup();
+        actions
+    }
+
+    /// Updates the strategies of `player` in the subtree and returns the counterfactual values.
+    fn cfr_recursive(
+        &mut self,
+        index: usize,
+        player: usize,
+        reach: &[Vec<f32>],
+        weight: f32,
+    ) -> Vec<f32> {
+        let node = &self.nodes[index];
+        if node.player == PLAYER_TERMINAL {
+            return self.terminal_values(node, player, reach);
+        }
+
+        let node_player = node.player;
+        let children = node.children.clone();
+        let num_actions = children.len();
+        let num_hands = self.private_cards[node_player].len();
+        let strategy = regret_matching(&node.cum_regret, num_actions);
+
+        let mut child_reach = reach.to_vec();
+        let mut action_values = Vec::with_capacity(num_actions);
+        for (action, &child) in children.iter().enumerate() {
+            let row = &strategy[action * num_hands..(action + 1) * num_hands];
+            child_reach[node_player]
+                .iter_mut()
+                .zip(reach[node_player].iter().zip(row))
+                .for_each(|(c, (&r, &s))| *c = r * s);
+            action_values.push(self.cfr_recursive(child, player, &child_reach, weight));
+        }
+
+        // sum up the values of the opponent's actions
+        if node_player != player {
+            let mut result = vec![0.0; self.private_cards[player].len()];
+            for values in &action_values {
+                result.iter_mut().zip(values).for_each(|(r, &v)| *r += v);
+            }
+            return result;
+        }
+
+        let mut result = vec![0.0; num_hands];
+        for (action, values) in action_values.iter().enumerate() {
+            for hand in 0..num_hands {
+                result[hand] += strategy[action * num_hands + hand] * values[hand];
+            }
+        }
+
+        let node = &mut self.nodes[index];
+        for (action, values) in action_values.iter().enumerate() {
+            for hand in 0..num_hands {
+                let i = action * num_hands + hand;
+                node.cum_regret[i] = (node.cum_regret[i] + values[hand] - result[hand]).max(0.0);
+                node.cum_strategy[i] += weight * reach[player][hand] * strategy[i];
+            }
+        }
+
+        result
+    }
+
+    /// Computes the values of `player` in the subtree, where `player` plays according to `mode`
+    /// and the other players play the average strategies.
+    fn values_recursive(
+        &self,
+        index: usize,
+        player: usize,
+        reach: &[Vec<f32>],
+        mode: ValueMode,
+    ) -> Vec<f32> {
+        let node = &self.nodes[index];
+        if node.player == PLAYER_TERMINAL {
+            return self.terminal_values(node, player, reach);
+        }
+
+        let num_hands = self.private_cards[node.player].len();
+        let strategy = average_strategy(node);
+
+        let mut child_reach = reach.to_vec();
+        let mut result = vec![0.0; self.private_cards[player].len()];
+        if node.player == player && mode == ValueMode::BestResponse {
+            result.fill(f32::NEG_INFINITY);
+        }
+
+        for (action, &child) in node.children.iter().enumerate() {
+            let row = &strategy[action * num_hands..(action + 1) * num_hands];
+            if node.player != player {
+                child_reach[node.player]
+                    .iter_mut()
+                    .zip(reach[node.player].iter().zip(row))
+                    .for_each(|(c, (&r, &s))| *c = r * s);
+            }
+
+            let values = self.values_recursive(child, player, &child_reach, mode);
+            match (node.player == player, mode) {
+                (false, _) => result.iter_mut().zip(&values).for_each(|(r, &v)| *r += v),
+                (true, ValueMode::Average) => result
+                    .iter_mut()
+                    .zip(values.iter().zip(row))
+                    .for_each(|(r, (&v, &s))| *r += s * v),
+                (true, ValueMode::BestResponse) => result
+                    .iter_mut()
+                    .zip(&values)
+                    .for_each(|(r, &v)| *r = r.max(v)),
+            }
+        }
+
+        result
+    }
+
+    /// Returns the expected value of `player` at the root.
+    fn root_value(&self, player: usize, mode: ValueMode) -> f64 {
+        let values = self.values_recursive(0, player, &self.initial_weights, mode);
+        let sum = values
+            .iter()
+            .zip(&self.initial_weights[player])
+            .map(|(&v, &w)| v as f64 * w as f64)
+            .sum::<f64>();
+        sum / self.normalizer
+    }
+
+    /// Computes the counterfactual values of `player` at the terminal `node`, i.e., the payoffs
+    /// summed over the compatible hands of the opponents weighted by their reach probabilities.
+    fn terminal_values(&self, node: &MultiwayNode, player: usize, reach: &[Vec<f32>]) -> Vec<f32> {
+        let pot = (self.config.starting_pot + node.contributions.iter().sum::<i32>()) as f64;
+        let rake = (pot * self.config.rake_rate).min(self.config.rake_cap);
+        let contribution = node.contributions[player] as f64;
+        let opponents = (0..self.num_players())
+            .filter(|&p| p != player)
+            .collect::<Vec<_>>();
+
+        let num_remaining = self.num_players() - node.folded.count_ones() as usize;
+        let is_folded = node.folded & (1 << player) != 0;
+
+        (0..self.private_cards[player].len())
+            .map(|hand| {
+                let mut prob = 0.0;
+                let mut share = 0.0;
+                let strength = self.strengths[player][hand];
+                let mask = self.hand_masks[player][hand];
+                self.enumerate_opponents(&opponents, reach, mask, 1.0, &mut |p, hands| {
+                    prob += p;
+                    if is_folded {
+                        return;
+                    }
+                    if num_remaining == 1 {
+                        share += p;
+                        return;
+                    }
+
+                    // showdown among the remaining players
+                    let mut num_ties = 1;
+                    for (&opponent, &opponent_hand) in opponents.iter().zip(hands) {
+                        if node.folded & (1 << opponent) != 0 {
+                            continue;
+                        }
+                        let opponent_strength = self.strengths[opponent][opponent_hand];
+                        if opponent_strength > strength {
+                            return;
+                        }
+                        if opponent_strength == strength {
+                            num_ties += 1;
+                        }
+                    }
+                    share += p / num_ties as f64;
+                });
+                (share * (pot - rake) - prob * contribution) as f32
+            })
+            .collect()
+    }
+
+    /// Calls `f` with the probability and the hands of each combination of the hands of
+    /// `players` that is compatible with `mask`.
+    fn enumerate_opponents<F: FnMut(f64, &[usize])>(
+        &self,
+        players: &[usize],
+        reach: &[Vec<f32>],
+        mask: u64,
+        prob: f64,
+        f: &mut F,
+    ) {
+        let mut hands = Vec::with_capacity(players.len());
+        self.enumerate_recursive(players, reach, mask, prob, &mut hands, f);
+    }
+
+    fn enumerate_recursive<F: FnMut(f64, &[usize])>(
+        &self,
+        players: &[usize],
+        reach: &[Vec<f32>],
+        mask: u64,
+        prob: f64,
+        hands: &mut Vec<usize>,
+        f: &mut F,
+    ) {
+        let depth = hands.len();
+        if depth == players.len() {
+            f(prob, hands);
+            return;
+        }
+
+        let player = players[depth];
+        for (hand, &hand_mask) in self.hand_masks[player].iter().enumerate() {
+            let r = reach[player][hand];
+            if r == 0.0 || mask & hand_mask != 0 {
+                continue;
+            }
+            hands.push(hand);
+            self.enumerate_recursive(players, reach, mask | hand_mask, prob * r as f64, hands, f);
+            hands.pop();
+        }
+    }
+
+    /// Returns the total weight of the compatible combinations of the hands of all players.
+    fn compute_normalizer(&self) -> f64 {
+        let players = (0..self.num_players()).collect::<Vec<_>>();
+        let mut normalizer = 0.0;
+        self.enumerate_opponents(&players, &self.initial_weights, 0, 1.0, &mut |p, _| {
+            normalizer += p;
+        });
+        normalizer
+    }
+}
+
+/// Computes the current strategy of `cum_regret` by regret matching.
+fn regret_matching(cum_regret: &[f32], num_actions: usize) -> Vec<f32> {
+    normalize(cum_regret, num_actions)
+}
+
+/// Computes the average strategy of `node`.
+fn average_strategy(node: &MultiwayNode) -> Vec<f32> {
+    normalize(&node.cum_strategy, node.actions.len())
+}
+
+/// Normalizes the nonnegative values of each hand; the uniform strategy is used if all values
+/// of a hand are zero.
+fn normalize(values: &[f32], num_actions: usize) -> Vec<f32> {
+    let num_hands = values.len() / num_actions;
+    let mut ret = values.iter().map(|&x| x.max(0.0)).collect::<Vec<_>>();
+    for hand in 0..num_hands {
+        let sum = (0..num_actions)
+            .map(|action| ret[action * num_hands + hand] as f64)
+            .sum::<f64>();
+        for action in 0..num_actions {
+            let x = &mut ret[action * num_hands + hand];
+            *x = if sum > 0.0 {
+                (*x as f64 / sum) as f32
+            } else {
+                1.0 / num_actions as f32
+            };
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(ranges: &[&str], bet: &str, raise: &str, max_num_bets: usize) -> MultiwayConfig {
+        let board = board_from_str("Qs Jh 2h 8d 3c").unwrap();
+        MultiwayConfig {
+            ranges: ranges.iter().map(|r| r.parse().unwrap()).collect(),
+            board: board.try_into().unwrap(),
+            starting_pot: 100,
+            effective_stack: 200,
+            bet_sizes: (bet, raise).try_into().unwrap(),
+            max_num_bets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn multiway_tree() {
+        let game = MultiwayGame::new(config(&["AA", "KK", "QQ"], "50%", "", 1)).unwrap();
+        assert_eq!(game.num_players(), 3);
+        assert_eq!(game.current_player(&[]), Ok(Some(0)));
+        assert_eq!(
+            game.available_actions(&[]),
+            Ok(vec![Action::Check, Action::Bet(50)])
+        );
+
+        // check-check-check goes to showdown
+        assert_eq!(game.current_player(&[0, 0]), Ok(Some(2)));
+        assert_eq!(game.current_player(&[0, 0, 0]), Ok(None));
+
+        // a bet is answered by the other players in turn
+        assert_eq!(
+            game.available_actions(&[1]),
+            Ok(vec![Action::Fold, Action::Call])
+        );
+        assert_eq!(game.current_player(&[1, 0]), Ok(Some(2)));
+        assert_eq!(game.current_player(&[1, 0, 0]), Ok(None));
+        assert_eq!(game.folded_players(&[1, 0, 0]), Ok(vec![false, true, true]));
+
+        // the bet of the second player reopens the action of the first player
+        assert_eq!(game.current_player(&[0, 1]), Ok(Some(2)));
+        assert_eq!(game.current_player(&[0, 1, 1]), Ok(Some(0)));
+        assert_eq!(game.current_player(&[0, 1, 1, 1]), Ok(None));
+        assert!(game.current_player(&[2]).is_err());
+        assert!(game.strategy(&[0, 0, 0]).is_err());
+
+        assert!(MultiwayGame::new(config(&["AA"], "50%", "", 1)).is_err());
+        assert!(MultiwayGame::new(config(&["AA"; 5], "50%", "", 1)).is_err());
+        assert!(MultiwayGame::new(config(&["AA", "KK", "QQ"], "e", "", 1)).is_err());
+        assert!(MultiwayGame::new(config(&["AA", "KK", "QsQd"], "50%", "", 1)).is_err());
+    }
+
+    #[test]
+    fn multiway_showdown() {
+        let ranges = ["AA,KK,87s", "KK,QQ,JJ", "JJ,T9s,AK"];
+        let game = MultiwayGame::new(config(&ranges, "", "", 0)).unwrap();
+        assert_eq!(game.num_nodes(), 4);
+
+        // the expected values are the shares of the pot
+        let mut total = 0.0;
+        let mut share = [0.0; 3];
+        let hands = (0..3)
+            .map(|p| game.private_cards(p).iter().enumerate().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        for &(i, &h0) in &hands[0] {
+            for &(j, &h1) in &hands[1] {
+                for &(k, &h2) in &hands[2] {
+                    let cards = [h0.0, h0.1, h1.0, h1.1, h2.0, h2.1];
+                    if (0..6).any(|a| (a + 1..6).any(|b| cards[a] == cards[b])) {
+                        continue;
+                    }
+                    let w = game.initial_weights(0)[i] as f64
+                        * game.initial_weights(1)[j] as f64
+                        * game.initial_weights(2)[k] as f64;
+                    let strength = [
+                        game.strengths[0][i],
+                        game.strengths[1][j],
+                        game.strengths[2][k],
+                    ];
+                    let best = *strength.iter().max().unwrap();
+                    let num_best = strength.iter().filter(|&&s| s == best).count();
+                    for p in 0..3 {
+                        if strength[p] == best {
+                            share[p] += w / num_best as f64;
+                        }
+                    }
+                    total += w;
+                }
+            }
+        }
+
+        let ev = game.expected_values();
+        for p in 0..3 {
+            assert!((ev[p] as f64 - 100.0 * share[p] / total).abs() < 1e-3);
+        }
+        assert!((ev.iter().sum::<f32>() - 100.0).abs() < 1e-3);
+        assert!(game.compute_exploitability().abs() < 1e-4);
+
+        // the rake is taken from the pot and the winners split the rest
+        let raked = MultiwayGame::new(MultiwayConfig {
+            rake_rate: 0.05,
+            rake_cap: 3.0,
+            ..config(&ranges, "", "", 0)
+        })
+        .unwrap();
+        let raked_ev = raked.expected_values();
+        for p in 0..3 {
+            assert!((raked_ev[p] - ev[p] * 0.97).abs() < 1e-3);
+        }
+        assert!((raked_ev.iter().sum::<f32>() - 97.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn multiway_rake() {
+        let ranges = ["AA,QQ,87s", "KK,JJ,AQ", "22,AJ,T9s"];
+        let rake_config = |rake_rate, rake_cap| MultiwayConfig {
+            rake_rate,
+            rake_cap,
+            ..config(&ranges, "50%", "", 1)
+        };
+        assert!(MultiwayGame::new(rake_config(-0.1, 0.0)).is_err());
+        assert!(MultiwayGame::new(rake_config(1.1, 0.0)).is_err());
+        assert!(MultiwayGame::new(rake_config(0.05, -1.0)).is_err());
+
+        // the uncontested pot after a bet and two folds is also raked: 0.05 * 150 = 7.5
+        let game = MultiwayGame::new(rake_config(0.0, 0.0)).unwrap();
+        let raked = MultiwayGame::new(rake_config(0.05, 10.0)).unwrap();
+        assert_eq!(game.folded_players(&[1, 0, 0]), Ok(vec![false, true, true]));
+        let index = game.node_index(&[1, 0, 0]).unwrap();
+        let reach = &game.initial_weights;
+        let values = game.terminal_values(&game.nodes[index], 0, reach);
+        let raked_values = raked.terminal_values(&raked.nodes[index], 0, reach);
+        for (&v, &r) in values.iter().zip(&raked_values) {
+            assert!((r - v * (150.0 - 7.5 - 50.0) / (150.0 - 50.0)).abs() < 1e-3);
+        }
+
+        // the rake is capped
+        let mut raked = MultiwayGame::new(rake_config(0.05, 4.0)).unwrap();
+        raked.solve(100, 0.0, false);
+        let ev = raked.expected_values();
+        assert!((ev.iter().sum::<f32>() - 96.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn multiway_solve() {
+        let ranges = ["AA,QQ,87s", "KK,JJ,AQ", "22,AJ,T9s"];
+        let mut game = MultiwayGame::new(config(&ranges, "50%", "100%", 2)).unwrap();
+        let initial = game.compute_exploitability();
+        let exploitability = game.solve(200, 0.0, false);
+        assert!(exploitability < initial * 0.5);
+
+        let ev = game.expected_values();
+        assert!((ev.iter().sum::<f32>() - 100.0).abs() < 1e-2);
+
+        let strategy = game.strategy(&[]).unwrap();
+        let num_hands = game.private_cards(0).len();
+        for hand in 0..num_hands {
+            let sum = strategy[hand] + strategy[num_hands + hand];
+            assert!((sum - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn multiway_heads_up() {
+        let ranges = ["AA,QQ,87s,54s", "KK,JJ,AQ,T9s"];
+        let mut game = MultiwayGame::new(config(&ranges, "50%,a", "60%", 3)).unwrap();
+        let exploitability = game.solve(1000, 0.1, false);
+        assert!(exploitability <= 0.1);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn multiway_heads_up_parity() {
+        use crate::{compute_average, compute_mes_ev, solve};
+        use crate::{ActionTree, CardConfig, PostFlopGame, TreeConfig};
+
+        fn lock_strategies(
+            multiway: &MultiwayGame,
+            game: &mut PostFlopGame,
+            players: &[usize],
+            history: &mut Vec<usize>,
+        ) {
+            game.apply_history(history);
+            if game.is_terminal_node() {
+                assert_eq!(multiway.current_player(history), Ok(None));
+                return;
+            }
+
+            // the trees and the private hands are identical
+            let actions = multiway.available_actions(history).unwrap();
+            assert_eq!(game.available_actions(), actions);
+            assert_eq!(
+                multiway.current_player(history),
+                Ok(Some(game.current_player()))
+            );
+            if players.contains(&game.current_player()) {
+                game.lock_current_strategy(&multiway.strategy(history).unwrap());
+            }
+
+            for action in 0..actions.len() {
+                history.push(action);
+                lock_strategies(multiway, game, players, history);
+                history.pop();
+            }
+        }
+
+        let ranges = ["AA,QQ,87s,54s", "KK,JJ,AQ,T9s"];
+        let mut multiway = MultiwayGame::new(config(&ranges, "50%,a", "60%", 3)).unwrap();
+        let exploitability = multiway.solve(1000, 0.1, false);
+        let multiway_ev = multiway.expected_values();
+
+        let board = multiway.config().board;
+        let card_config = CardConfig {
+            range: [ranges[0].parse().unwrap(), ranges[1].parse().unwrap()],
+            flop: [board[0], board[1], board[2]],
+            turn: board[3],
+            river: board[4],
+            ..Default::default()
+        };
+        let bet_sizes: BetSizeOptions = ("50%,a", "60%").try_into().unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 100,
+            effective_stack: 200,
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+        let build = || {
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+            game.allocate_memory(false);
+            for player in 0..2 {
+                assert_eq!(game.private_cards(player), multiway.private_cards(player));
+            }
+            game
+        };
+        let root_ev = |game: &mut PostFlopGame, player: usize| {
+            game.back_to_root();
+            game.cache_normalized_weights();
+            compute_average(
+                &game.expected_values(player),
+                game.normalized_weights(player),
+            )
+        };
+
+        // the strategies of the multiway solver have the same values in `PostFlopGame`
+        let mut locked = build();
+        lock_strategies(&multiway, &mut locked, &[0, 1], &mut Vec::new());
+        solve(&mut locked, 1, 0.0, false);
+        for player in 0..2 {
+            assert!((root_ev(&mut locked, player) - multiway_ev[player]).abs() < 1e-3);
+        }
+
+        // and so do the best responses against them (`compute_mes_ev` subtracts half the pot)
+        let mut best_response_sum = 0.0;
+        for player in 0..2 {
+            let mut locked = build();
+            lock_strategies(&multiway, &mut locked, &[player], &mut Vec::new());
+            locked.back_to_root();
+            best_response_sum += compute_mes_ev(&locked)[player ^ 1] + 50.0;
+        }
+        let locked_exploitability = (best_response_sum - 100.0) / 2.0;
+        assert!((locked_exploitability - exploitability).abs() < 1e-3);
+
+        // both solvers converge to the same game value
+        let mut solved = build();
+        let solved_exploitability = solve(&mut solved, 1000, 0.01, false).exploitability;
+        for player in 0..2 {
+            let diff = (root_ev(&mut solved, player) - multiway_ev[player]).abs();
+            assert!(diff <= 2.0 * (exploitability + solved_exploitability));
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! Exporter to OpenSpiel's tabular policy format.
+//!
+//! OpenSpiel represents a tabular policy as a map from information state strings to lists of
+//! `(action, probability)` pairs (see `open_spiel.python.policy.TabularPolicy`). The exported
+//! policy can be serialized into JSON with [`OpenSpielPolicy::to_json`] and loaded in Python with
+//! `json.load`.
+
+use crate::action_tree::*;
+use crate::error::*;
+use crate::game::*;
+use crate::interface::*;
+use crate::json::*;
+use crate::range::*;
+use crate::utility::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Information passed to the mapper of [`export_openspiel_policy`].
+#[derive(Debug, Clone, Copy)]
+pub struct OpenSpielInfo<'a> {
+    /// The action indices from the root node.
+    ///
+    /// At a chance node, the index `num_actions() + i` denotes the `i`-th isomorphic chance
+    /// returned by [`Game::isomorphic_chances`].
+    pub history: &'a [usize],
+
+    /// The player to act.
+    pub player: usize,
+
+    /// The index of the private hand of the player.
+    pub hand: usize,
+
+    /// The number of actions at the node.
+    pub num_actions: usize,
+}
+
+/// Tabular policy in OpenSpiel's format.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpenSpielPolicy {
+    /// The map from information state strings to `(action, probability)` pairs.
+    pub states: BTreeMap<String, Vec<(i64, f64)>>,
+}
+
+impl OpenSpielPolicy {
+    /// Returns the action probabilities of the given information state.
+    #[inline]
+    pub fn action_probabilities(&self, info_state: &str) -> Option<&[(i64, f64)]> {
+        self.states.get(info_state).map(Vec::as_slice)
+    }
+
+    /// Serializes the policy into a JSON object of the form
+    /// `{"info_state": [[action, probability], ...], ...}`.
+    pub fn to_json(&self) -> String {
+        let mut ret = String::from("{");
+        for (i, (info_state, action_probs)) in self.states.iter().enumerate() {
+            if i > 0 {
+                ret.push(',');
+            }
+            write_json_string(&mut ret, info_state);
+            ret.push_str(":[");
+            for (j, (action, prob)) in action_probs.iter().enumerate() {
+                if j > 0 {
+                    ret.push(',');
+                }
+                write!(ret, "[{action},{prob}]").unwrap();
+            }
+            ret.push(']');
+        }
+        ret.push('}');
+        ret
+    }
+
+    /// Inserts an information state. Returns an error if the state already exists with different
+    /// probabilities.
+    fn insert(&mut self, info_state: String, action_probs: Vec<(i64, f64)>) -> Result<(), Error> {
+        if let Some(existing) = self.states.get(&info_state) {
+            let is_same = existing.len() == action_probs.len()
+                && existing
+                    .iter()
+                    .zip(&action_probs)
+                    .all(|(x, y)| x.0 == y.0 && (x.1 - y.1).abs() < 1e-4);
+            if !is_same {
+                return Err(Error::Config(format!(
+                    "Conflicting information state: {info_state}"
+                )));
+            }
+        } else {
+            self.states.insert(info_state, action_probs);
+        }
+        Ok(())
+    }
+}
+
+/// Exports the average strategy of a solved game into OpenSpiel's tabular policy format.
+///
+/// `mapper` is called for every pair of a decision node and a private hand of the player to act,
+/// and returns the information state string and the OpenSpiel action IDs of the actions at the
+/// node, or `None` to skip the hand (e.g., when it is blocked by the board). Isomorphic chances
+/// are expanded, so the policy covers every information state of the original game.
+///
+/// Returns an error if the game is not solved or the mapper is inconsistent.
+pub fn export_openspiel_policy<T: Game, F>(
+    game: &T,
+    mut mapper: F,
+) -> Result<OpenSpielPolicy, Error>
+where
+    F: FnMut(&OpenSpielInfo) -> Option<(String, Vec<i64>)>,
+{
+    game.check_operation(GameOperation::Analyze)?;
+
+    // the stored hands are expanded, so the permutations refer to all private hands
+    let identity = |player: usize| {
+        let num_hands = match game.stored_hand_indices(player).len() {
+            0 => game.num_private_hands(player),
+            n => n,
+        };
+        (0..num_hands).collect::<Vec<_>>()
+    };
+    let mut permutation = [identity(0), identity(1)];
+    let mut history = Vec::new();
+    let mut policy = OpenSpielPolicy::default();
+
+    export_openspiel_recursive(
+        game,
+        &game.root(),
+        &mut permutation,
+        &mut history,
+        &mut mapper,
+        &mut policy,
+    )?;
+
+    Ok(policy)
+}
+
+fn export_openspiel_recursive<T: Game, F>(
+    game: &T,
+    node: &T::Node,
+    permutation: &mut [Vec<usize>; 2],
+    history: &mut Vec<usize>,
+    mapper: &mut F,
+    policy: &mut OpenSpielPolicy,
+) -> Result<(), Error>
+where
+    F: FnMut(&OpenSpielInfo) -> Option<(String, Vec<i64>)>,
+{
+    if node.is_terminal() {
+        return Ok(());
+    }
+
+    let num_actions = node.num_actions();
+
+    if node.is_chance() {
+        for action in 0..num_actions {
+            history.push(action);
+            let child = node.play(action);
+            export_openspiel_recursive(game, &child, permutation, history, mapper, policy)?;
+            history.pop();
+        }
+
+        // the subtree of an isomorphic chance is shared with its representative, so the hands
+        // are permuted by the swap list
+        for (i, &repr) in game.isomorphic_chances(node).iter().enumerate() {
+            let swap_list = game.isomorphic_swap(node, i);
+            let saved = permutation.clone();
+            for player in 0..2 {
+                let mut swap = (0..permutation[player].len()).collect::<Vec<_>>();
+                apply_swap(&mut swap, &swap_list[player]);
+                permutation[player].iter_mut().for_each(|p| *p = swap[*p]);
+            }
+
+            history.push(num_actions + i);
+            let child = node.play(repr as usize);
+            export_openspiel_recursive(game, &child, permutation, history, mapper, policy)?;
+            history.pop();
+
+            *permutation = saved;
+        }
+
+        return Ok(());
+    }
+
+    let player = node.player();
+    let mut strategy = if game.is_node_compressed(node) {
+        normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+    } else {
+        normalized_strategy(node.strategy(), num_actions)
+    };
+    apply_locking_strategy(&mut strategy, game.locking_strategy(node));
+
+    let stored_indices = game.stored_hand_indices(player);
+    let strategy = expand_stored_hands(strategy, stored_indices, game.num_private_hands(player));
+
+    let num_hands = permutation[player].len();
+    for hand in 0..num_hands {
+        let info = OpenSpielInfo {
+            history,
+            player,
+            hand,
+            num_actions,
+        };
+
+        if let Some((info_state, action_ids)) = mapper(&info) {
+            if action_ids.len() != num_actions {
+                return Err(Error::Config(format!(
+                    "Number of action IDs mismatch: expected = {num_actions}, actual = {}",
+                    action_ids.len()
+                )));
+            }
+
+            let index = permutation[player][hand];
+            let action_probs = action_ids
+                .iter()
+                .enumerate()
+                .map(|(action, &id)| (id, strategy[action * num_hands + index] as f64))
+                .collect();
+            policy.insert(info_state, action_probs)?;
+        }
+    }
+
+    for action in 0..num_actions {
+        history.push(action);
+        let child = node.play(action);
+        export_openspiel_recursive(game, &child, permutation, history, mapper, policy)?;
+        history.pop();
+    }
+
+    Ok(())
+}
+
+impl PostFlopGame {
+    /// Exports the average strategy into OpenSpiel's tabular policy format.
+    ///
+    /// The information state strings follow the style of OpenSpiel's `universal_poker`, e.g.,
+    /// `[Player: 0][Private: AsKh][Public: 2c6dTh3s][Sequences: cr45c/r90]`, where the betting
+    /// sequence uses the ACPC notation (`f`: fold, `c`: check or call, `rN`: bet or raise making
+    /// the player's total commitment `N`, including half of the starting pot). The action IDs are
+    /// `0` for fold, `1` for check or call, and `N` for a bet or a raise. Hands blocked by the
+    /// board are omitted.
+    ///
+    /// The whole game tree is traversed, so this method can take a long time for large trees.
+    /// The current node is restored after the call.
+    pub fn export_openspiel_policy(&mut self) -> Result<OpenSpielPolicy, Error> {
+        self.check_operation(GameOperation::Analyze)?;
+
+        let saved_history = self.history().to_vec();
+        let mut policy = OpenSpielPolicy::default();
+        let ret = self.export_openspiel_policy_recursive(
+            &mut Vec::new(),
+            &mut String::new(),
+            &mut policy,
+        );
+        self.apply_history(&saved_history);
+        ret.map(|_| policy)
+    }
+
+    fn export_openspiel_policy_recursive(
+        &mut self,
+        history: &mut Vec<usize>,
+        sequence: &mut String,
+        policy: &mut OpenSpielPolicy,
+    ) -> Result<(), Error> {
+        if self.is_terminal_node() {
+            return Ok(());
+        }
+
+        let sequence_len = sequence.len();
+
+        if self.is_chance_node() {
+            let possible_cards = self.possible_cards();
+            for card in 0..52 {
+                if possible_cards & (1 << card) != 0 {
+                    history.push(card);
+                    self.apply_history(&history[..]);
+                    sequence.push('/');
+                    self.export_openspiel_policy_recursive(history, sequence, policy)?;
+                    sequence.truncate(sequence_len);
+                    history.pop();
+                }
+            }
+            return Ok(());
+        }
+
+        let player = self.current_player();
+        let actions = self.available_actions();
+        let num_actions = actions.len();
+        let strategy = self.strategy();
+        let board = self
+            .current_board()
+            .iter()
+            .map(|&card| card_to_string_with(card, CardFormat::default()).unwrap())
+            .collect::<String>();
+
+        // tokens and IDs of the actions
+        let mut tokens = Vec::with_capacity(num_actions);
+        let mut action_ids = Vec::with_capacity(num_actions);
+        for (i, action) in actions.iter().enumerate() {
+            match action {
+                Action::Fold => {
+                    tokens.push("f".to_string());
+                    action_ids.push(0);
+                }
+                Action::Check | Action::Call => {
+                    tokens.push("c".to_string());
+                    action_ids.push(1);
+                }
+                _ => {
+                    self.play(i);
+                    let committed = self.committed_amount(player);
+                    tokens.push(format!("r{committed}"));
+                    action_ids.push(committed as i64);
+                    self.apply_history(&history[..]);
+                }
+            }
+        }
+
+        let num_hands = self.num_private_hands(player);
+        let weights = self.weights(player);
+        for (hand, &(c1, c2)) in self.private_cards(player).iter().enumerate() {
+            if weights[hand] == 0.0 {
+                continue;
+            }
+
+            let info_state = format!(
+                "[Player: {player}][Private: {}{}][Public: {board}][Sequences: {sequence}]",
+                card_to_string_with(c1, CardFormat::default()).unwrap(),
+                card_to_string_with(c2, CardFormat::default()).unwrap(),
+            );
+            let action_probs = action_ids
+                .iter()
+                .enumerate()
+                .map(|(action, &id)| (id, strategy[action * num_hands + hand] as f64))
+                .collect();
+            policy.insert(info_state, action_probs)?;
+        }
+
+        for (action, token) in tokens.iter().enumerate() {
+            history.push(action);
+            self.apply_history(&history[..]);
+            sequence.push_str(token);
+            self.export_openspiel_policy_recursive(history, sequence, policy)?;
+            sequence.truncate(sequence_len);
+            history.pop();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "solver"))]
+mod tests {
+    use super::*;
+    use crate::bet_size::*;
+    use crate::card::*;
+    use crate::solver::*;
+
+    fn solved_game() -> PostFlopGame {
+        let card_config = CardConfig {
+            range: ["QQ+,AK".parse().unwrap(), "JJ+,AQs".parse().unwrap()],
+            flop: flop_from_str("2c6dTh").unwrap(),
+            turn: card_from_str("3s").unwrap(),
+            ..Default::default()
+        };
+
+        let bet_sizes = BetSizeOptions::try_from(("50%", "")).unwrap();
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 100,
+            turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 50, 0.0, false);
+        game
+    }
+
+    #[test]
+    fn openspiel_generic() {
+        let game = solved_game();
+        let policy = export_openspiel_policy(&game, |info| {
+            let state = format!("{}:{}:{:?}", info.player, info.hand, info.history);
+            Some((state, (0..info.num_actions as i64).collect()))
+        })
+        .unwrap();
+
+        let root = game.strategy();
+        let num_hands = game.num_private_hands(0);
+        for hand in 0..num_hands {
+            let probs = policy
+                .action_probabilities(&format!("0:{hand}:[]"))
+                .unwrap();
+            for (action, &(id, prob)) in probs.iter().enumerate() {
+                assert_eq!(id, action as i64);
+                assert!((prob - root[action * num_hands + hand] as f64).abs() < 1e-6);
+            }
+        }
+
+        for probs in policy.states.values() {
+            let sum = probs.iter().map(|p| p.1).sum::<f64>();
+            assert!((sum - 1.0).abs() < 1e-4);
+        }
+
+        let result = export_openspiel_policy(&game, |_| Some(("x".to_string(), vec![0])));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn openspiel_postflop() {
+        let mut game = solved_game();
+        game.play(0);
+        let policy = game.export_openspiel_policy().unwrap();
+        assert_eq!(game.history(), &[0]);
+
+        let (c1, c2) = game.private_cards(1)[0];
+        let state = format!(
+            "[Player: 1][Private: {}{}][Public: 2c6dTh3s][Sequences: c]",
+            card_to_string(c1).unwrap(),
+            card_to_string(c2).unwrap(),
+        );
+        let probs = policy.action_probabilities(&state).unwrap();
+        assert_eq!(probs[0].0, 1);
+        assert_eq!(probs[1].0, 30 + 30);
+
+        assert!(policy
+            .states
+            .keys()
+            .any(|s| s.ends_with("[Sequences: cc/]")));
+        let json = policy.to_json();
+        assert!(json.starts_with("{\"[Player: 0]"));
+        assert!(json.ends_with("]]}"));
+        let json = JsonValue::parse(&json).unwrap();
+        crate::schema::validate_json("openspiel_policy", &json).unwrap();
+    }
+}
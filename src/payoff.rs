@@ -0,0 +1,285 @@
+use crate::error::*;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of players in the tournament supported by [`PayoffModel::Icm`].
+pub const MAX_ICM_PLAYERS: usize = 10;
+
+/// Model converting the chips won or lost at a terminal node into the payoff of a player.
+///
+/// The payoffs are measured in chips (or chip equivalents), so the reported expected values and
+/// the exploitability keep their units. Except for [`PayoffModel::ChipEv`], the payoffs are not
+/// zero-sum, and the exploitability is computed as in raked games.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // final table with five players left, where the players of the hand have 2000 and 1500 chips
+/// // behind and the other three players have 3000, 500, and 1000 chips
+/// let payoff_model = PayoffModel::Icm(IcmConfig {
+///     stacks: [2000.0, 1500.0],
+///     other_stacks: vec![3000.0, 500.0, 1000.0],
+///     payouts: vec![50.0, 30.0, 20.0],
+/// });
+///
+/// let tree_config = TreeConfig {
+///     starting_pot: 200,
+///     effective_stack: 1500,
+///     payoff_model,
+///     ..Default::default()
+/// };
+///
+/// assert!(ActionTree::new(tree_config).is_ok());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PayoffModel {
+    /// (Default value) The payoffs are the chips won or lost.
+    #[default]
+    ChipEv,
+
+    /// The payoffs are the changes of the tournament equities computed by the independent chip
+    /// model (ICM), converted into chips at the average rate of the tournament (i.e., the total
+    /// number of chips divided by the total prize pool).
+    Icm(IcmConfig),
+
+    /// The chips lost are multiplied by the bubble factor of each player (`[OOP, IP]`), while the
+    /// chips won are unchanged. Each factor must be positive; a factor greater than `1.0` makes
+    /// the player more risk-averse.
+    BubbleFactor([f64; 2]),
+}
+
+/// Tournament configuration of [`PayoffModel::Icm`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IcmConfig {
+    /// Chips behind of the players of the hand (`[OOP, IP]`) at the root of the game tree, not
+    /// including the starting pot. Each must be greater than or equal to the effective stack.
+    pub stacks: [f64; 2],
+
+    /// Chips of the other players remaining in the tournament. Each must be positive.
+    pub other_stacks: Vec<f64>,
+
+    /// Prizes of the places, starting from the first place. Must be non-negative and
+    /// non-increasing, and at least one prize must be positive. The places not listed receive
+    /// nothing.
+    pub payouts: Vec<f64>,
+}
+
+impl PayoffModel {
+    /// Returns whether the model is [`PayoffModel::ChipEv`].
+    #[inline]
+    pub fn is_chip_ev(&self) -> bool {
+        matches!(self, Self::ChipEv)
+    }
+
+    /// Returns the payoffs of `player` for each outcome where `player` wins `nets[i]` chips and
+    /// the opponent wins `-nets[i] - rake` chips.
+    ///
+    /// `starting_pot` is the starting pot of the game tree, half of which is regarded as owned by
+    /// each player as in the chip payoffs.
+    pub(crate) fn payoffs<const N: usize>(
+        &self,
+        nets: [f64; N],
+        rake: f64,
+        player: usize,
+        starting_pot: f64,
+    ) -> [f64; N] {
+        match self {
+            Self::ChipEv => nets,
+            Self::Icm(config) => {
+                let base = [
+                    config.stacks[0] + 0.5 * starting_pot,
+                    config.stacks[1] + 0.5 * starting_pot,
+                ];
+                let mut stacks = Vec::with_capacity(2 + config.other_stacks.len());
+                stacks.extend_from_slice(&base);
+                stacks.extend_from_slice(&config.other_stacks);
+                let total_chips = stacks.iter().sum::<f64>();
+                let total_prize = config.payouts.iter().sum::<f64>();
+                let initial = icm_equities(&stacks, &config.payouts)[player];
+
+                nets.map(|net| {
+                    stacks[player] = (base[player] + net).max(0.0);
+                    stacks[player ^ 1] = (base[player ^ 1] - net - rake).max(0.0);
+                    let equity = icm_equities(&stacks, &config.payouts)[player];
+                    (equity - initial) * total_chips / total_prize
+                })
+            }
+            Self::BubbleFactor(factors) => nets.map(|net| {
+                if net < 0.0 {
+                    net * factors[player]
+                } else {
+                    net
+                }
+            }),
+        }
+    }
+
+    /// Checks the model for the given effective stack.
+    pub(crate) fn check(&self, effective_stack: i32) -> Result<(), Error> {
+        match self {
+            Self::ChipEv => Ok(()),
+            Self::Icm(config) => {
+                for &stack in &config.stacks {
+                    if !stack.is_finite() || stack < effective_stack as f64 {
+                        return Err(Error::Config(format!(
+                            "ICM stacks must be at least the effective stack: {:?}",
+                            config.stacks
+                        )));
+                    }
+                }
+
+                if config
+                    .other_stacks
+                    .iter()
+                    .any(|&s| !s.is_finite() || s <= 0.0)
+                {
+                    return Err(Error::Config(format!(
+                        "ICM stacks of the other players must be positive: {:?}",
+                        config.other_stacks
+                    )));
+                }
+
+                if 2 + config.other_stacks.len() > MAX_ICM_PLAYERS {
+                    return Err(Error::Config(format!(
+                        "ICM supports at most {MAX_ICM_PLAYERS} players: {}",
+                        2 + config.other_stacks.len()
+                    )));
+                }
+
+                let payouts = &config.payouts;
+                if payouts.iter().any(|&p| !p.is_finite() || p < 0.0)
+                    || payouts.windows(2).any(|w| w[0] < w[1])
+                    || !payouts.iter().any(|&p| p > 0.0)
+                {
+                    return Err(Error::Config(format!(
+                        "ICM payouts must be non-negative, non-increasing, and not all zero: \
+                         {payouts:?}"
+                    )));
+                }
+
+                Ok(())
+            }
+            Self::BubbleFactor(factors) => {
+                if factors.iter().any(|&f| !f.is_finite() || f <= 0.0) {
+                    return Err(Error::Config(format!(
+                        "Bubble factors must be positive: {factors:?}"
+                    )));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Computes the tournament equities of the players by the Malmuth-Harville model.
+///
+/// The probability of a player finishing in each place is proportional to the stack among the
+/// players not yet placed; players without chips finish in the remaining places in equal
+/// probabilities.
+pub fn icm_equities(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    let num_players = stacks.len();
+    let num_places = payouts.len().min(num_players);
+    assert!(num_players <= MAX_ICM_PLAYERS);
+
+    let mut equities = vec![0.0; num_players];
+    let total = stacks.iter().sum::<f64>();
+
+    // prob[mask]: probability that the players in `mask` take the first `popcount(mask)` places
+    let mut prob = vec![0.0; 1 << num_players];
+    let mut remaining = vec![0.0; 1 << num_players];
+    prob[0] = 1.0;
+    remaining[0] = total;
+
+    for mask in 0usize..1 << num_players {
+        let place = mask.count_ones() as usize;
+        if place >= num_places || prob[mask] == 0.0 {
+            continue;
+        }
+
+        let rest = remaining[mask];
+        let num_rest = num_players - place;
+        for (i, &stack) in stacks.iter().enumerate() {
+            if mask & (1 << i) != 0 {
+                continue;
+            }
+            let p = if rest > 0.0 {
+                prob[mask] * stack / rest
+            } else {
+                prob[mask] / num_rest as f64
+            };
+            equities[i] += p * payouts[place];
+            let next = mask | (1 << i);
+            prob[next] += p;
+            remaining[next] = rest - stack;
+        }
+    }
+
+    equities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icm_equities_basic() {
+        // winner-take-all is linear in chips
+        let equities = icm_equities(&[300.0, 100.0, 600.0], &[1.0]);
+        assert!((equities[0] - 0.3).abs() < 1e-12);
+        assert!((equities[2] - 0.6).abs() < 1e-12);
+
+        // well-known example: stacks 5000/3000/2000 with 50/30/20 payouts
+        let equities = icm_equities(&[5000.0, 3000.0, 2000.0], &[50.0, 30.0, 20.0]);
+        assert!((equities.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        assert!((equities[0] - 38.3929).abs() < 1e-3);
+        assert!((equities[1] - 32.7500).abs() < 1e-3);
+        assert!((equities[2] - 28.8571).abs() < 1e-3);
+
+        // a busted player finishes last
+        let equities = icm_equities(&[0.0, 500.0, 500.0], &[50.0, 30.0, 20.0]);
+        assert!((equities[0] - 20.0).abs() < 1e-9);
+        assert!((equities[1] - 40.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn payoff_models() {
+        let icm = PayoffModel::Icm(IcmConfig {
+            stacks: [900.0, 900.0],
+            other_stacks: vec![],
+            payouts: vec![1.0],
+        });
+        let [win, lose] = icm.payoffs([150.0, -150.0], 0.0, 0, 200.0);
+        assert!((win - 150.0).abs() < 1e-9);
+        assert!((lose + 150.0).abs() < 1e-9);
+
+        let icm = PayoffModel::Icm(IcmConfig {
+            stacks: [900.0, 900.0],
+            other_stacks: vec![1000.0],
+            payouts: vec![60.0, 40.0],
+        });
+        let [win, lose] = icm.payoffs([500.0, -500.0], 0.0, 0, 200.0);
+        assert!(win > 0.0 && win < 500.0);
+        assert!(-lose > win);
+        assert_eq!(icm.payoffs([0.0], 0.0, 1, 200.0), [0.0]);
+
+        let bubble = PayoffModel::BubbleFactor([1.5, 1.0]);
+        assert_eq!(
+            bubble.payoffs([-100.0, 100.0], 0.0, 0, 200.0),
+            [-150.0, 100.0]
+        );
+        assert_eq!(bubble.payoffs([-100.0], 0.0, 1, 200.0), [-100.0]);
+
+        assert!(icm.check(900).is_ok());
+        assert!(icm.check(1000).is_err());
+        assert!(PayoffModel::BubbleFactor([0.0, 1.0]).check(100).is_err());
+    }
+}
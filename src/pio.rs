@@ -0,0 +1,358 @@
+//! Reader and writer for PioSolver's node-level exports.
+//!
+//! PioSolver identifies a node by a path such as `r:0:c:b30:c:Kh:b60`, where `r:0` is the root,
+//! `c` is a check or a call, `f` is a fold, `bN` is a bet or a raise making the player's total
+//! commitment on the current street `N`, and a card (e.g., `Kh`) is the dealing of a turn or
+//! river card. The strategy of a node (the output of `show_strategy`) consists of one line per
+//! action, each of which has 1326 probabilities in PioSolver's hand order.
+
+use crate::action_tree::*;
+use crate::card::*;
+use crate::error::*;
+use crate::range::*;
+
+/// The number of hands in PioSolver's hand order.
+pub const PIO_NUM_HANDS: usize = 52 * 51 / 2;
+
+/// Strategy of a single node read from a PioSolver dump.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PioNodeStrategy {
+    /// The node path in PioSolver's notation.
+    pub path: String,
+
+    /// The line of the node mapped onto the `ActionTree`, including the chance actions.
+    pub line: Vec<Action>,
+
+    /// The actions available at the node, in the same order as the rows of `strategy`.
+    pub actions: Vec<Action>,
+
+    /// The strategy in PioSolver's hand order: `strategy[action * PIO_NUM_HANDS + hand]`.
+    pub strategy: Vec<f32>,
+}
+
+/// Returns the index of the hand in PioSolver's hand order.
+///
+/// PioSolver sorts the hands by the higher card and then by the lower card, i.e., `0` => 2d2c,
+/// `1` => 2h2c, `2` => 2h2d, `3` => 2s2c, ..., `1325` => AsAh.
+#[inline]
+pub fn pio_hand_index(card1: Card, card2: Card) -> usize {
+    let (low, high) = if card1 < card2 {
+        (card1 as usize, card2 as usize)
+    } else {
+        (card2 as usize, card1 as usize)
+    };
+    high * (high - 1) / 2 + low
+}
+
+/// Maps a PioSolver node path onto the given `ActionTree`.
+///
+/// The returned line contains [`Action::Chance`] for the dealt cards. The current node of
+/// `tree` is restored after the call.
+pub fn pio_path_to_line(tree: &mut ActionTree, path: &str) -> Result<Vec<Action>, Error> {
+    let saved_history = tree.history().to_vec();
+    let ret = pio_path_to_line_internal(tree, path);
+    tree.apply_history(&saved_history)?;
+    ret
+}
+
+/// Converts a line of the `ActionTree` into a PioSolver node path (e.g., `r:0:c:b30:c:Kh:b60`).
+///
+/// The line must contain [`Action::Chance`] for the dealt cards, as returned by
+/// [`pio_path_to_line`]. Checks and calls are written as `c`, and bets, raises, and all-ins are
+/// written as `bN` with the player's total commitment on the current street `N`. The cards are
+/// always written in the standard format regardless of [`set_card_format`].
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let line = [Action::Check, Action::Bet(30), Action::Call, Action::Chance(46)];
+/// assert_eq!(line_to_pio_path(&line), "r:0:c:b30:c:Kh");
+/// ```
+pub fn line_to_pio_path(line: &[Action]) -> String {
+    let mut ret = "r:0".to_string();
+    for action in line {
+        ret.push(':');
+        match *action {
+            Action::Fold => ret.push('f'),
+            Action::Check | Action::Call => ret.push('c'),
+            Action::Bet(amount) | Action::Raise(amount) | Action::AllIn(amount) => {
+                ret.push_str(&format!("b{amount}"))
+            }
+            Action::Chance(card) => {
+                ret.push_str(&card_to_string_with(card, CardFormat::default()).unwrap())
+            }
+            _ => ret.push('?'),
+        }
+    }
+    ret
+}
+
+/// Writes a strategy in PioSolver's hand order in the format of the `show_strategy` command,
+/// i.e., one line of 1326 probabilities per action. This is the inverse of
+/// [`parse_pio_strategy`].
+///
+/// Returns an error if the length of `strategy` is not a positive multiple of 1326.
+pub fn write_pio_strategy(strategy: &[f32]) -> Result<String, Error> {
+    if strategy.is_empty() || !strategy.len().is_multiple_of(PIO_NUM_HANDS) {
+        return Err(Error::Config(format!(
+            "Length of strategy must be a positive multiple of {PIO_NUM_HANDS}: {}",
+            strategy.len()
+        )));
+    }
+
+    let mut ret = String::new();
+    for row in strategy.chunks_exact(PIO_NUM_HANDS) {
+        for (i, prob) in row.iter().enumerate() {
+            if i > 0 {
+                ret.push(' ');
+            }
+            ret.push_str(&prob.to_string());
+        }
+        ret.push('\n');
+    }
+
+    Ok(ret)
+}
+
+/// Parses the output of PioSolver's `show_strategy` command.
+///
+/// Each non-empty line must contain 1326 probabilities. Returns the probabilities in row-major
+/// order (one row per action).
+pub fn parse_pio_strategy(text: &str) -> Result<Vec<f32>, Error> {
+    let mut ret = Vec::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line == "END" {
+            continue;
+        }
+
+        let row = line
+            .split_whitespace()
+            .map(|token| {
+                token.parse::<f32>().map_err(|_| {
+                    Error::invalid_data(format!(
+                        "Invalid number at line {}: {token}",
+                        line_number + 1
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if row.len() != PIO_NUM_HANDS {
+            return Err(Error::invalid_data(format!(
+                "Expected {PIO_NUM_HANDS} values at line {}, but got {}",
+                line_number + 1,
+                row.len()
+            )));
+        }
+
+        ret.extend(row);
+    }
+
+    if ret.is_empty() {
+        return Err(Error::invalid_data("Strategy is empty"));
+    }
+
+    Ok(ret)
+}
+
+/// Reads a node-level strategy dump of PioSolver and maps it onto the given `ActionTree`.
+///
+/// `path` is the node path and `strategy_text` is the output of `show_strategy` for that node.
+/// Returns an error if the path does not exist in `tree` or the number of strategy rows differs
+/// from the number of actions at the node. The current node of `tree` is restored after the
+/// call.
+pub fn read_pio_node_strategy(
+    tree: &mut ActionTree,
+    path: &str,
+    strategy_text: &str,
+) -> Result<PioNodeStrategy, Error> {
+    let saved_history = tree.history().to_vec();
+    let ret = pio_path_to_line_internal(tree, path);
+    let actions = tree.available_actions().to_vec();
+    let is_terminal = tree.is_terminal_node();
+    tree.apply_history(&saved_history)?;
+    let line = ret?;
+
+    if is_terminal {
+        return Err(Error::Tree(format!("Node is terminal: {path}")));
+    }
+
+    let strategy = parse_pio_strategy(strategy_text)?;
+    let num_rows = strategy.len() / PIO_NUM_HANDS;
+    if num_rows != actions.len() {
+        return Err(Error::invalid_data(format!(
+            "Number of strategy rows mismatch: expected = {}, actual = {num_rows}",
+            actions.len()
+        )));
+    }
+
+    Ok(PioNodeStrategy {
+        path: path.to_string(),
+        line,
+        actions,
+        strategy,
+    })
+}
+
+impl PioNodeStrategy {
+    /// Returns the strategy rearranged for the given hands (e.g.,
+    /// [`PostFlopGame::private_cards`](crate::PostFlopGame::private_cards)), in the same layout
+    /// as [`PostFlopGame::strategy`](crate::PostFlopGame::strategy).
+    pub fn strategy_for_hands(&self, hands: &[(Card, Card)]) -> Vec<f32> {
+        let num_actions = self.actions.len();
+        let mut ret = Vec::with_capacity(num_actions * hands.len());
+        for action in 0..num_actions {
+            let row = &self.strategy[action * PIO_NUM_HANDS..(action + 1) * PIO_NUM_HANDS];
+            ret.extend(hands.iter().map(|&(c1, c2)| row[pio_hand_index(c1, c2)]));
+        }
+        ret
+    }
+}
+
+/// Walks `tree` along `path`. On success, the current node of `tree` is the node of `path`.
+fn pio_path_to_line_internal(tree: &mut ActionTree, path: &str) -> Result<Vec<Action>, Error> {
+    let mut tokens = path.trim().split(':').peekable();
+
+    if tokens.next() != Some("r") {
+        return Err(Error::Tree(format!(
+            "Node path must start with `r`: {path}"
+        )));
+    }
+
+    if tokens.peek() == Some(&"0") {
+        tokens.next();
+    }
+
+    tree.back_to_root();
+    let mut line = Vec::new();
+    let mut is_card_dealt = false;
+
+    for token in tokens {
+        if tree.is_terminal_node() {
+            return Err(Error::Tree(format!(
+                "Node path goes beyond a terminal node: {path}"
+            )));
+        }
+
+        let actions = tree.available_actions();
+        let action = match token {
+            "f" => Action::Fold,
+            "c" if actions.contains(&Action::Check) => Action::Check,
+            "c" => Action::Call,
+            _ if token.starts_with('b') => {
+                let amount = token[1..]
+                    .parse::<i32>()
+                    .map_err(|_| Error::Tree(format!("Invalid bet amount: {token}")))?;
+                *actions
+                    .iter()
+                    .find(|action| {
+                        matches!(action, Action::Bet(a) | Action::Raise(a) | Action::AllIn(a) if *a == amount)
+                    })
+                    .ok_or_else(|| Error::Tree(format!("Bet amount not found in the tree: {token}")))?
+            }
+            _ => {
+                let card = card_from_str(token)?;
+                if !tree.is_chance_node() || is_card_dealt {
+                    return Err(Error::Tree(format!(
+                        "Unexpected card in node path: {token}"
+                    )));
+                }
+                line.push(Action::Chance(card));
+                is_card_dealt = true;
+                continue;
+            }
+        };
+
+        if tree.is_chance_node() && !is_card_dealt {
+            return Err(Error::Tree(format!(
+                "Card is expected before `{token}`: {path}"
+            )));
+        }
+
+        tree.play(action)?;
+        line.push(action);
+        is_card_dealt = false;
+    }
+
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bet_size::*;
+
+    fn tree() -> ActionTree {
+        let bet_sizes = BetSizeOptions::try_from(("50%", "")).unwrap();
+        let config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 200,
+            turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+            river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+        ActionTree::new(config).unwrap()
+    }
+
+    #[test]
+    fn pio_hand_order() {
+        let card = |s| card_from_str(s).unwrap();
+        assert_eq!(pio_hand_index(card("2d"), card("2c")), 0);
+        assert_eq!(pio_hand_index(card("2c"), card("2h")), 1);
+        assert_eq!(pio_hand_index(card("2h"), card("2d")), 2);
+        assert_eq!(pio_hand_index(card("2s"), card("2c")), 3);
+        assert_eq!(pio_hand_index(card("As"), card("Ah")), PIO_NUM_HANDS - 1);
+    }
+
+    #[test]
+    fn pio_path() {
+        let mut tree = tree();
+        let line = pio_path_to_line(&mut tree, "r:0:c:b30:c:Kh:b60").unwrap();
+        assert_eq!(
+            line,
+            vec![
+                Action::Check,
+                Action::Bet(30),
+                Action::Call,
+                Action::Chance(card_from_str("Kh").unwrap()),
+                Action::Bet(60),
+            ]
+        );
+        assert!(tree.history().is_empty());
+
+        assert!(pio_path_to_line(&mut tree, "r:0:b45").is_err());
+        assert!(pio_path_to_line(&mut tree, "r:0:c:c:b60").is_err());
+        assert!(pio_path_to_line(&mut tree, "x:0").is_err());
+
+        let path = "r:0:c:b30:c:Kh:c:b60:f";
+        let line = pio_path_to_line(&mut tree, path).unwrap();
+        assert_eq!(line_to_pio_path(&line), path);
+    }
+
+    #[test]
+    fn pio_node_strategy() {
+        let mut tree = tree();
+        let row = |value: f32| vec![value.to_string(); PIO_NUM_HANDS].join(" ");
+        let text = format!("{}\n{}\n", row(0.25), row(0.75));
+
+        let node = read_pio_node_strategy(&mut tree, "r:0", &text).unwrap();
+        assert_eq!(node.actions, vec![Action::Check, Action::Bet(30)]);
+
+        let hands = [(0, 1), (50, 51)];
+        assert_eq!(
+            node.strategy_for_hands(&hands),
+            vec![0.25, 0.25, 0.75, 0.75]
+        );
+
+        assert!(read_pio_node_strategy(&mut tree, "r:0", &row(1.0)).is_err());
+        assert!(read_pio_node_strategy(&mut tree, "r:0:c", "0.5 0.5").is_err());
+
+        let text = write_pio_strategy(&node.strategy).unwrap();
+        assert_eq!(parse_pio_strategy(&text).unwrap(), node.strategy);
+        assert!(write_pio_strategy(&node.strategy[1..]).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use std::cell::RefCell;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::thread::LocalKey;
+
+/// The maximum number of buffers kept by the pool of each thread and element type.
+const MAX_POOLED_BUFFERS: usize = 256;
+
+thread_local! {
+    static F32_POOL: RefCell<Vec<Vec<f32>>> = const { RefCell::new(Vec::new()) };
+    static F64_POOL: RefCell<Vec<Vec<f64>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Element types of [`PooledVec`].
+pub(crate) trait PoolElement: Sized + 'static {
+    fn pool() -> &'static LocalKey<RefCell<Vec<Vec<Self>>>>;
+}
+
+impl PoolElement for f32 {
+    #[inline]
+    fn pool() -> &'static LocalKey<RefCell<Vec<Vec<Self>>>> {
+        &F32_POOL
+    }
+}
+
+impl PoolElement for f64 {
+    #[inline]
+    fn pool() -> &'static LocalKey<RefCell<Vec<Vec<Self>>>> {
+        &F64_POOL
+    }
+}
+
+/// A vector whose buffer is taken from a per-thread pool and returned to it when dropped.
+///
+/// The solver allocates temporary buffers (counterfactual values, strategies, reach
+/// probabilities) at every node it visits. Since the same nodes are visited in every iteration,
+/// the buffers released by one visit fit the next ones, and recycling them removes most calls of
+/// the default allocator after the first iteration. This is the counterpart of the `custom-alloc`
+/// feature that is available in stable Rust.
+///
+/// The buffer must be dropped in the thread that created it, which always holds for the local
+/// variables of the traversal (a stack frame does not migrate between threads of `rayon`).
+pub(crate) struct PooledVec<T: PoolElement> {
+    vec: Vec<T>,
+}
+
+impl<T: PoolElement> PooledVec<T> {
+    /// Creates an empty vector with at least the specified capacity, reusing a pooled buffer if
+    /// possible.
+    ///
+    /// The smallest pooled buffer that is large enough is chosen; if there is none, the most
+    /// recently released buffer is grown.
+    #[inline]
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let vec = T::pool().with(|pool| {
+            let mut pool = pool.borrow_mut();
+            let best_fit = pool
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.capacity() >= capacity)
+                .min_by_key(|(_, v)| v.capacity())
+                .map(|(i, _)| i);
+            match best_fit {
+                Some(index) => pool.swap_remove(index),
+                None => pool.pop().unwrap_or_default(),
+            }
+        });
+
+        let mut ret = Self { vec };
+        ret.vec.reserve(capacity);
+        ret
+    }
+}
+
+impl<T: PoolElement> From<Vec<T>> for PooledVec<T> {
+    #[inline]
+    fn from(vec: Vec<T>) -> Self {
+        Self { vec }
+    }
+}
+
+impl<T: PoolElement> Deref for PooledVec<T> {
+    type Target = Vec<T>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+impl<T: PoolElement> DerefMut for PooledVec<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.vec
+    }
+}
+
+impl<'a, T: PoolElement> IntoIterator for &'a PooledVec<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.iter()
+    }
+}
+
+impl<'a, T: PoolElement> IntoIterator for &'a mut PooledVec<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.iter_mut()
+    }
+}
+
+impl<T: PoolElement> Drop for PooledVec<T> {
+    #[inline]
+    fn drop(&mut self) {
+        let mut vec = mem::take(&mut self.vec);
+        if vec.capacity() == 0 {
+            return;
+        }
+
+        vec.clear();
+        // the pool may be already destroyed when the thread exits
+        let _ = T::pool().try_with(|pool| {
+            let mut pool = pool.borrow_mut();
+            if pool.len() < MAX_POOLED_BUFFERS {
+                pool.push(vec);
+            }
+        });
+    }
+}
+
+/// Frees the buffers pooled by the current thread.
+#[inline]
+pub(crate) fn free_buffer_pool() {
+    F32_POOL.with(|pool| *pool.borrow_mut() = Vec::new());
+    F64_POOL.with(|pool| *pool.borrow_mut() = Vec::new());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num_pooled() -> usize {
+        F32_POOL.with(|pool| pool.borrow().len())
+    }
+
+    #[test]
+    fn pooled_vec_reuse() {
+        free_buffer_pool();
+
+        let mut vec = PooledVec::<f32>::with_capacity(100);
+        vec.extend([1.0, 2.0, 3.0]);
+        let ptr = vec.as_ptr();
+        drop(vec);
+        assert_eq!(num_pooled(), 1);
+
+        // the released buffer is reused and emptied
+        let vec = PooledVec::<f32>::with_capacity(50);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert!(vec.is_empty());
+        assert!(vec.capacity() >= 100);
+        assert_eq!(num_pooled(), 0);
+
+        // the smallest sufficient buffer is chosen
+        let small = PooledVec::<f32>::with_capacity(10);
+        let small_ptr = small.as_ptr();
+        drop(small);
+        drop(vec);
+        let vec = PooledVec::<f32>::with_capacity(5);
+        assert_eq!(vec.as_ptr(), small_ptr);
+        drop(vec);
+
+        // an insufficient buffer is grown
+        let vec = PooledVec::<f32>::with_capacity(1000);
+        assert!(vec.capacity() >= 1000);
+        assert_eq!(num_pooled(), 1);
+        drop(vec);
+
+        free_buffer_pool();
+        assert_eq!(num_pooled(), 0);
+    }
+
+    #[test]
+    fn pooled_vec_limit() {
+        free_buffer_pool();
+
+        let vecs = (0..MAX_POOLED_BUFFERS + 10)
+            .map(|_| PooledVec::<f64>::with_capacity(1))
+            .collect::<Vec<_>>();
+        drop(vecs);
+        F64_POOL.with(|pool| assert_eq!(pool.borrow().len(), MAX_POOLED_BUFFERS));
+
+        free_buffer_pool();
+    }
+}
@@ -0,0 +1,974 @@
+//! Heads-up preflop solver.
+
+use crate::action_tree::*;
+use crate::bet_size::*;
+use crate::card::*;
+use crate::error::*;
+use crate::hand::*;
+use crate::interface::*;
+use crate::mutex_like::*;
+use crate::range::*;
+use crate::rng::SolverRng;
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::sync::Arc;
+
+/// Estimator of the outcome of the postflop play, used by [`PreflopTerminalModel::Postflop`].
+pub trait PostflopEstimator: Send + Sync {
+    /// Returns the expected share of the pot won by the small blind (player `0`) when the hands
+    /// `hands` (small blind, big blind) see a flop.
+    ///
+    /// `equity` is the all-in equity of the small blind, `pot` is the pot at the start of the
+    /// flop, and `effective_stack` is the remaining stack of each player. The share may be
+    /// outside `[0, 1]` when chips behind are expected to change hands; the share of the big
+    /// blind is one minus the returned value.
+    fn pot_share(
+        &self,
+        hands: [(Card, Card); 2],
+        equity: f32,
+        pot: i32,
+        effective_stack: i32,
+    ) -> f32;
+}
+
+/// [`PostflopEstimator`] scaling the all-in equities by constant realization factors.
+///
+/// The pot share of the small blind is `eq * r[0] / (eq * r[0] + (1 - eq) * r[1])`, where `eq`
+/// is the all-in equity of the small blind and `r` is `factors`. For example, `[1.1, 0.9]`
+/// models the positional advantage of the small blind, who acts last postflop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquityRealization {
+    /// The realization factors of the small blind and the big blind (must be positive).
+    pub factors: [f32; 2],
+}
+
+impl PostflopEstimator for EquityRealization {
+    #[inline]
+    fn pot_share(&self, _hands: [(Card, Card); 2], equity: f32, _pot: i32, _stack: i32) -> f32 {
+        let realized = [equity * self.factors[0], (1.0 - equity) * self.factors[1]];
+        match realized[0] + realized[1] {
+            sum if sum > 0.0 => realized[0] / sum,
+            _ => 0.5,
+        }
+    }
+}
+
+/// How the terminal nodes where both players see a flop with chips behind are evaluated.
+#[derive(Clone, Default)]
+pub enum PreflopTerminalModel {
+    /// The pot is split according to the all-in equities.
+    #[default]
+    AllInEquity,
+
+    /// The pot is split according to the pot shares returned by the estimator.
+    Postflop(Arc<dyn PostflopEstimator>),
+}
+
+impl fmt::Debug for PreflopTerminalModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::AllInEquity => write!(f, "AllInEquity"),
+            Self::Postflop(_) => write!(f, "Postflop(..)"),
+        }
+    }
+}
+
+/// Configuration of a [`PreflopGame`].
+#[derive(Debug, Clone, Default)]
+pub struct PreflopConfig {
+    /// The ranges of the small blind and the big blind.
+    pub ranges: [Range; 2],
+
+    /// The small blind.
+    pub small_blind: i32,
+
+    /// The big blind.
+    pub big_blind: i32,
+
+    /// The ante posted by each player, which is dead money in the pot.
+    pub ante: i32,
+
+    /// The stack of each player at the start of the hand, including the blinds and the ante.
+    pub effective_stack: i32,
+
+    /// The raise sizes: the `i`-th element lists the sizes of the `(i + 1)`-th raise (the open
+    /// raise of the small blind or the first raise after a limp is the first raise), and the
+    /// length is the maximum number of raises.
+    ///
+    /// The sizes are "raise to" amounts: [`BetSize::PrevBetRelative`] is relative to the amount
+    /// to call (e.g., `2.5x` opens to 2.5 big blinds), [`BetSize::PotRelative`] is relative to
+    /// the pot after calling, [`BetSize::Additive`] is added to the amount to call (the raise cap
+    /// is ignored), and [`BetSize::AllIn`] is all-in. [`BetSize::Geometric`] is not supported.
+    /// The sizes are raised to the minimum raise, and the sizes exceeding the stack are replaced
+    /// by all-in.
+    pub raise_sizes: Vec<Vec<BetSize>>,
+
+    /// The number of sampled boards for estimating the all-in equities.
+    ///
+    /// The boards conflicting with a pair of hands are skipped for the pair, so the equity of
+    /// each pair is estimated from about two thirds of the samples.
+    pub equity_samples: usize,
+
+    /// The seed of the sampling of the boards (see [`SolverRng`]).
+    pub seed: u64,
+
+    /// How the terminal nodes that see a flop are evaluated.
+    pub terminal_model: PreflopTerminalModel,
+
+    /// Rake rate. Must be between `0.0` and `1.0`, inclusive.
+    pub rake_rate: f64,
+
+    /// Rake cap. Must be non-negative.
+    pub rake_cap: f64,
+
+    /// If `true`, the pots that end preflop by a fold are not raked ("no flop, no drop"), and
+    /// only the pots that see a flop are raked.
+    pub no_flop_no_drop: bool,
+}
+
+/// A heads-up preflop game with a range-vs-range tree builder.
+///
+/// This game builds the preflop betting tree of a heads-up hand from the stack depth, the
+/// blinds and antes, and the raise-size candidates, and implements the [`Game`] trait so that it
+/// is solved by the same functions as [`PostFlopGame`] (e.g., [`solve`]). The small blind is
+/// player `0` and acts first; the big blind is player `1`.
+///
+/// Since the postflop streets are not part of the tree, the terminal nodes where both players see
+/// a flop with chips behind are evaluated by a [`PreflopTerminalModel`]: either by the all-in
+/// equity (i.e., as if both players realized their equity exactly), or by the pot shares returned
+/// by a [`PostflopEstimator`], which allows plugging in EV estimates obtained from postflop
+/// solutions. The terminal nodes where a player is all-in are always evaluated by the all-in
+/// equity.
+///
+/// The all-in equities are estimated per pair of hands by Monte Carlo sampling of the boards
+/// (see [`PreflopConfig::equity_samples`]). The cost of the sampling and of the evaluation of
+/// each terminal node is proportional to the product of the range sizes, so narrow ranges are
+/// solved much faster than full ranges. The memory is allocated when the game is created.
+///
+/// [`Game`]: crate::Game
+/// [`PostFlopGame`]: crate::PostFlopGame
+/// [`solve`]: crate::solve
+pub struct PreflopGame {
+    config: PreflopConfig,
+    private_cards: [Vec<(Card, Card)>; 2],
+    initial_weights: [Vec<f32>; 2],
+    hand_masks: [Vec<u64>; 2],
+    same_hand_index: [Vec<u16>; 2],
+    num_combinations: f64,
+    equity: Vec<f32>,
+    share_tables: Vec<(i32, Vec<f32>)>,
+    num_nodes: usize,
+    root: MutexLike<PreflopNode>,
+    is_solved: bool,
+}
+
+#[doc(hidden)]
+pub struct PreflopNode {
+    player: usize,
+    kind: NodeKind,
+    bets: [i32; 2],
+    children: Vec<(Action, MutexLike<PreflopNode>)>,
+    strategy: Vec<f32>,
+    storage: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Decision,
+    Fold(usize),
+    Showdown(Option<usize>),
+}
+
+/// The state of the betting while building the tree.
+#[derive(Debug, Clone, Copy)]
+struct BuildState {
+    player: usize,
+    bets: [i32; 2],
+    min_raise: i32,
+    num_raises: usize,
+    is_first_action: bool,
+}
+
+const NO_SAME_HAND: u16 = u16::MAX;
+
+impl PreflopGame {
+    /// Builds the game tree and estimates the all-in equities. Returns an error if the
+    /// configuration is invalid.
+    pub fn new(config: PreflopConfig) -> Result<Self, Error> {
+        Self::check_config(&config)?;
+
+        let mut private_cards = [Vec::new(), Vec::new()];
+        let mut initial_weights = [Vec::new(), Vec::new()];
+        for player in 0..2 {
+            let (cards, weights) = config.ranges[player].get_hands_weights(0);
+            if cards.is_empty() {
+                return Err(Error::Config(format!("Range of player {player} is empty")));
+            }
+            private_cards[player] = cards;
+            initial_weights[player] = weights;
+        }
+
+        let hand_masks = private_cards.clone().map(|cards| {
+            cards
+                .iter()
+                .map(|&(c1, c2)| (1 << c1) | (1 << c2))
+                .collect::<Vec<u64>>()
+        });
+
+        let mut same_hand_index = [Vec::new(), Vec::new()];
+        for player in 0..2 {
+            let mut index_of = vec![NO_SAME_HAND; 52 * 51 / 2];
+            for (i, &(c1, c2)) in private_cards[player ^ 1].iter().enumerate() {
+                index_of[card_pair_to_index(c1, c2)] = i as u16;
+            }
+            same_hand_index[player] = private_cards[player]
+                .iter()
+                .map(|&(c1, c2)| index_of[card_pair_to_index(c1, c2)])
+                .collect();
+        }
+
+        let mut num_combinations = 0.0;
+        for (i, &w0) in initial_weights[0].iter().enumerate() {
+            for (j, &w1) in initial_weights[1].iter().enumerate() {
+                if hand_masks[0][i] & hand_masks[1][j] == 0 {
+                    num_combinations += w0 as f64 * w1 as f64;
+                }
+            }
+        }
+
+        if num_combinations == 0.0 {
+            return Err(Error::Config(
+                "Ranges have no compatible hand combination".to_string(),
+            ));
+        }
+
+        let mut game = Self {
+            config,
+            private_cards,
+            initial_weights,
+            hand_masks,
+            same_hand_index,
+            num_combinations,
+            equity: Vec::new(),
+            share_tables: Vec::new(),
+            num_nodes: 0,
+            root: MutexLike::new(PreflopNode::terminal(NodeKind::Fold(0), [0; 2])),
+            is_solved: false,
+        };
+
+        game.equity = game.compute_equity();
+
+        let state = BuildState {
+            player: 0,
+            bets: [game.config.small_blind, game.config.big_blind],
+            min_raise: game.config.big_blind,
+            num_raises: 0,
+            is_first_action: true,
+        };
+        let root = game.build_tree(state);
+        game.root = MutexLike::new(root);
+
+        Ok(game)
+    }
+
+    /// Returns the configuration.
+    #[inline]
+    pub fn config(&self) -> &PreflopConfig {
+        &self.config
+    }
+
+    /// Returns the number of nodes of the game tree.
+    #[inline]
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    /// Returns the private hands of `player`.
+    #[inline]
+    pub fn private_cards(&self, player: usize) -> &[(Card, Card)] {
+        &self.private_cards[player]
+    }
+
+    /// Returns the estimated all-in equity of the `hand0`-th hand of the small blind against the
+    /// `hand1`-th hand of the big blind, or `None` if the hands share a card.
+    #[inline]
+    pub fn equity(&self, hand0: usize, hand1: usize) -> Option<f32> {
+        if self.hand_masks[0][hand0] & self.hand_masks[1][hand1] != 0 {
+            return None;
+        }
+        Some(self.equity[hand0 * self.private_cards[1].len() + hand1])
+    }
+
+    /// Returns the player to act at the node reached by `history` (a list of action indices
+    /// from the root), or `None` if the node is terminal.
+    pub fn current_player(&self, history: &[usize]) -> Result<Option<usize>, Error> {
+        self.with_node(history, |node| {
+            (node.kind == NodeKind::Decision).then_some(node.player)
+        })
+    }
+
+    /// Returns the available actions at the node reached by `history`. The amounts are the total
+    /// amounts put in by the acting player, including the blinds and excluding the ante.
+    pub fn available_actions(&self, history: &[usize]) -> Result<Vec<Action>, Error> {
+        self.with_node(history, |node| {
+            node.children.iter().map(|(action, _)| *action).collect()
+        })
+    }
+
+    /// Returns the amounts put in by each player at the node reached by `history`, including the
+    /// blinds and excluding the ante.
+    pub fn total_bet_amount(&self, history: &[usize]) -> Result<[i32; 2], Error> {
+        self.with_node(history, |node| node.bets)
+    }
+
+    /// Returns the average strategy at the node reached by `history`.
+    ///
+    /// The return value is a vector of the length of `#(actions) * #(private hands)` of the
+    /// player to act, in the same layout as [`PostFlopGame::strategy`].
+    ///
+    /// [`PostFlopGame::strategy`]: crate::PostFlopGame::strategy
+    pub fn strategy(&self, history: &[usize]) -> Result<Vec<f32>, Error> {
+        let (player, mut strategy) = self
+            .with_node(history, |node| {
+                (node.kind == NodeKind::Decision).then(|| (node.player, node.strategy.clone()))
+            })?
+            .ok_or_else(|| Error::Tree("Terminal node is not allowed".to_string()))?;
+
+        let num_hands = self.private_cards[player].len();
+        let num_actions = strategy.len() / num_hands;
+        for hand in 0..num_hands {
+            let sum = (0..num_actions)
+                .map(|action| strategy[action * num_hands + hand])
+                .sum::<f32>();
+            for action in 0..num_actions {
+                let value = &mut strategy[action * num_hands + hand];
+                *value = if sum > 0.0 {
+                    *value / sum
+                } else {
+                    1.0 / num_actions as f32
+                };
+            }
+        }
+
+        Ok(strategy)
+    }
+
+    fn with_node<R>(
+        &self,
+        history: &[usize],
+        f: impl FnOnce(&PreflopNode) -> R,
+    ) -> Result<R, Error> {
+        fn recurse<R>(
+            node: &PreflopNode,
+            history: &[usize],
+            f: impl FnOnce(&PreflopNode) -> R,
+        ) -> Result<R, Error> {
+            match history.split_first() {
+                None => Ok(f(node)),
+                Some((&action, rest)) => {
+                    let (_, child) = node
+                        .children
+                        .get(action)
+                        .ok_or_else(|| Error::Tree(format!("Invalid action index: {action}")))?;
+                    recurse(&child.lock(), rest, f)
+                }
+            }
+        }
+
+        recurse(&self.root.lock(), history, f)
+    }
+
+    fn check_config(config: &PreflopConfig) -> Result<(), Error> {
+        let (sb, bb) = (config.small_blind, config.big_blind);
+        if sb <= 0 || bb < sb {
+            return Err(Error::Config(format!(
+                "Blinds must satisfy 0 < small blind <= big blind: {sb}, {bb}"
+            )));
+        }
+
+        if config.ante < 0 {
+            return Err(Error::Config(format!(
+                "Ante must be non-negative: {}",
+                config.ante
+            )));
+        }
+
+        if config.effective_stack < config.ante + bb {
+            return Err(Error::Config(format!(
+                "Effective stack must cover the big blind and the ante: {}",
+                config.effective_stack
+            )));
+        }
+
+        for size in config.raise_sizes.iter().flatten() {
+            match *size {
+                BetSize::PotRelative(x) | BetSize::PrevBetRelative(x) if x > 0.0 => {}
+                BetSize::Additive(x, _) if x > 0 => {}
+                BetSize::AllIn => {}
+                _ => return Err(Error::Config(format!("Unsupported raise size: {size:?}"))),
+            }
+        }
+
+        if config.equity_samples == 0 {
+            return Err(Error::Config(
+                "Number of equity samples must be positive".to_string(),
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&config.rake_rate) {
+            return Err(Error::Config(format!(
+                "Rake rate must be between 0.0 and 1.0: {}",
+                config.rake_rate
+            )));
+        }
+
+        if config.rake_cap < 0.0 {
+            return Err(Error::Config(format!(
+                "Rake cap must be non-negative: {}",
+                config.rake_cap
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the rake taken from the pot `pot`.
+    #[inline]
+    fn rake(&self, pot: f64) -> f64 {
+        (pot * self.config.rake_rate).min(self.config.rake_cap)
+    }
+
+    /// Estimates the all-in equity of each pair of hands by sampling the boards.
+    fn compute_equity(&self) -> Vec<f32> {
+        let [cards0, cards1] = &self.private_cards;
+        let (n0, n1) = (cards0.len(), cards1.len());
+        let mut wins = vec![0.0f32; n0 * n1];
+        let mut counts = vec![0u32; n0 * n1];
+        let mut rng = SolverRng::new(self.config.seed);
+
+        let mut strengths = [vec![0; n0], vec![0; n1]];
+        for _ in 0..self.config.equity_samples {
+            let mut board_mask: u64 = 0;
+            let mut board = Hand::new();
+            while board_mask.count_ones() < 5 {
+                let card = rng.next_below(52);
+                if board_mask & (1 << card) == 0 {
+                    board_mask |= 1 << card;
+                    board = board.add_card(card);
+                }
+            }
+
+            for (player, strengths) in strengths.iter_mut().enumerate() {
+                let hands = self.private_cards[player].iter();
+                let masks = self.hand_masks[player].iter();
+                for (strength, (&(c1, c2), &mask)) in strengths.iter_mut().zip(hands.zip(masks)) {
+                    if mask & board_mask == 0 {
+                        *strength = board
+                            .add_card(c1 as usize)
+                            .add_card(c2 as usize)
+                            .evaluate_internal();
+                    }
+                }
+            }
+
+            for i in 0..n0 {
+                let mask0 = self.hand_masks[0][i];
+                if mask0 & board_mask != 0 {
+                    continue;
+                }
+                for j in 0..n1 {
+                    let mask1 = self.hand_masks[1][j];
+                    if (mask0 | board_mask) & mask1 != 0 {
+                        continue;
+                    }
+                    let index = i * n1 + j;
+                    counts[index] += 1;
+                    wins[index] += match strengths[0][i].cmp(&strengths[1][j]) {
+                        std::cmp::Ordering::Greater => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Less => 0.0,
+                    };
+                }
+            }
+        }
+
+        wins.iter()
+            .zip(&counts)
+            .map(|(&w, &c)| if c > 0 { w / c as f32 } else { 0.5 })
+            .collect()
+    }
+
+    /// Returns the index of the pot-share table of a flop with the pot `pot`, or `None` if the
+    /// all-in equities are used.
+    fn share_table_index(&mut self, pot: i32) -> Option<usize> {
+        let estimator = match &self.config.terminal_model {
+            PreflopTerminalModel::AllInEquity => return None,
+            PreflopTerminalModel::Postflop(estimator) => estimator.clone(),
+        };
+
+        if let Some(index) = self.share_tables.iter().position(|(p, _)| *p == pot) {
+            return Some(index);
+        }
+
+        let stack = self.config.effective_stack - pot / 2;
+        let [cards0, cards1] = &self.private_cards;
+        let mut table = vec![0.0; cards0.len() * cards1.len()];
+        for (i, &hand0) in cards0.iter().enumerate() {
+            for (j, &hand1) in cards1.iter().enumerate() {
+                if let Some(equity) = self.equity(i, j) {
+                    let hands = [hand0, hand1];
+                    table[i * cards1.len() + j] = estimator.pot_share(hands, equity, pot, stack);
+                }
+            }
+        }
+
+        self.share_tables.push((pot, table));
+        Some(self.share_tables.len() - 1)
+    }
+
+    /// Returns the available actions of `state`.
+    fn actions(&self, state: &BuildState) -> Vec<Action> {
+        let player = state.player;
+        let max_bet = self.config.effective_stack - self.config.ante;
+        let to_call = state.bets[player ^ 1];
+        let mut actions = Vec::new();
+
+        if to_call > state.bets[player] {
+            actions.push(Action::Fold);
+            actions.push(Action::Call);
+        } else {
+            actions.push(Action::Check);
+        }
+
+        if to_call >= max_bet || state.num_raises >= self.config.raise_sizes.len() {
+            return actions;
+        }
+
+        let pot = 2 * to_call + 2 * self.config.ante;
+        let min_raise_to = (to_call + state.min_raise).min(max_bet);
+        let mut amounts = self.config.raise_sizes[state.num_raises]
+            .iter()
+            .map(|&size| {
+                let amount = match size {
+                    BetSize::PotRelative(x) => to_call + (pot as f64 * x).round() as i32,
+                    BetSize::PrevBetRelative(x) => (to_call as f64 * x).round() as i32,
+                    BetSize::Additive(x, _) => to_call + x,
+                    _ => max_bet,
+                };
+                amount.clamp(min_raise_to, max_bet)
+            })
+            .collect::<Vec<_>>();
+
+        amounts.sort_unstable();
+        amounts.dedup();
+
+        actions.extend(amounts.into_iter().map(|amount| {
+            if amount == max_bet {
+                Action::AllIn(amount)
+            } else {
+                Action::Raise(amount)
+            }
+        }));
+
+        actions
+    }
+
+    /// Builds the subtree of `state`.
+    fn build_tree(&mut self, state: BuildState) -> PreflopNode {
+        let player = state.player;
+        let actions = self.actions(&state);
+        let num_hands = self.private_cards[player].len();
+        let max_bet = self.config.effective_stack - self.config.ante;
+        self.num_nodes += 1;
+
+        let children = actions
+            .iter()
+            .map(|&action| {
+                let mut next = state;
+                next.player = player ^ 1;
+                next.is_first_action = false;
+
+                let child = match action {
+                    Action::Fold => {
+                        self.num_nodes += 1;
+                        PreflopNode::terminal(NodeKind::Fold(player), state.bets)
+                    }
+                    Action::Check | Action::Call => {
+                        next.bets[player] = state.bets[player ^ 1];
+                        if state.is_first_action {
+                            // the big blind has the option to raise after a limp
+                            self.build_tree(next)
+                        } else {
+                            let pot = 2 * (next.bets[0] + self.config.ante);
+                            let index = if next.bets[0] == max_bet {
+                                None
+                            } else {
+                                self.share_table_index(pot)
+                            };
+                            self.num_nodes += 1;
+                            PreflopNode::terminal(NodeKind::Showdown(index), next.bets)
+                        }
+                    }
+                    Action::Raise(amount) | Action::AllIn(amount) => {
+                        next.bets[player] = amount;
+                        next.min_raise = state.min_raise.max(amount - state.bets[player ^ 1]);
+                        next.num_raises += 1;
+                        self.build_tree(next)
+                    }
+                    _ => unreachable!(),
+                };
+
+                (action, MutexLike::new(child))
+            })
+            .collect::<Vec<_>>();
+
+        PreflopNode {
+            player,
+            kind: NodeKind::Decision,
+            bets: state.bets,
+            strategy: vec![0.0; children.len() * num_hands],
+            storage: vec![0.0; children.len() * num_hands],
+            children,
+        }
+    }
+}
+
+impl Game for PreflopGame {
+    type Node = PreflopNode;
+
+    #[inline]
+    fn root(&self) -> MutexGuardLike<Self::Node> {
+        self.root.lock()
+    }
+
+    #[inline]
+    fn num_private_hands(&self, player: usize) -> usize {
+        self.private_cards[player].len()
+    }
+
+    #[inline]
+    fn initial_weights(&self, player: usize) -> &[f32] {
+        &self.initial_weights[player]
+    }
+
+    fn evaluate(
+        &self,
+        result: &mut [MaybeUninit<f32>],
+        node: &Self::Node,
+        player: usize,
+        cfreach: &[f32],
+    ) {
+        let ante = self.config.ante;
+        let opponent_cards = &self.private_cards[player ^ 1];
+
+        match node.kind {
+            NodeKind::Fold(folded_player) => {
+                // the uncalled bet is returned to the winner
+                let half_pot = (node.bets[folded_player] + ante) as f64;
+                let rake = if self.config.no_flop_no_drop {
+                    0.0
+                } else {
+                    self.rake(2.0 * half_pot)
+                };
+                let payoff = if folded_player == player {
+                    -half_pot
+                } else {
+                    half_pot - rake
+                } / self.num_combinations;
+
+                // sum of the reach probabilities of the compatible hands of the opponent
+                let mut cfreach_sum = 0.0;
+                let mut cfreach_minus = [0.0; 52];
+                for (&(c1, c2), &reach) in opponent_cards.iter().zip(cfreach) {
+                    let reach = reach as f64;
+                    cfreach_sum += reach;
+                    cfreach_minus[c1 as usize] += reach;
+                    cfreach_minus[c2 as usize] += reach;
+                }
+
+                let player_cards = &self.private_cards[player];
+                let same_hand_index = &self.same_hand_index[player];
+                for (i, r) in result.iter_mut().enumerate() {
+                    let (c1, c2) = player_cards[i];
+                    let same = match same_hand_index[i] {
+                        NO_SAME_HAND => 0.0,
+                        index => cfreach[index as usize] as f64,
+                    };
+                    let reach =
+                        cfreach_sum - cfreach_minus[c1 as usize] - cfreach_minus[c2 as usize]
+                            + same;
+                    r.write((payoff * reach) as f32);
+                }
+            }
+
+            NodeKind::Showdown(index) => {
+                let table = match index {
+                    Some(index) => &self.share_tables[index].1,
+                    None => &self.equity,
+                };
+
+                let contribution = (node.bets[0] + ante) as f64;
+                let pot = 2.0 * contribution - self.rake(2.0 * contribution);
+                let n1 = self.private_cards[1].len();
+                let player_masks = &self.hand_masks[player];
+                let opponent_masks = &self.hand_masks[player ^ 1];
+
+                for (i, r) in result.iter_mut().enumerate() {
+                    let mut value = 0.0;
+                    for (j, &reach) in cfreach.iter().enumerate() {
+                        if reach == 0.0 || player_masks[i] & opponent_masks[j] != 0 {
+                            continue;
+                        }
+                        let share = match player {
+                            0 => table[i * n1 + j] as f64,
+                            _ => 1.0 - table[j * n1 + i] as f64,
+                        };
+                        value += reach as f64 * (pot * share - contribution);
+                    }
+                    r.write((value / self.num_combinations) as f32);
+                }
+            }
+
+            NodeKind::Decision => unreachable!(),
+        }
+    }
+
+    #[inline]
+    fn chance_factor(&self, _node: &Self::Node) -> usize {
+        unreachable!()
+    }
+
+    #[inline]
+    fn is_solved(&self) -> bool {
+        self.is_solved
+    }
+
+    #[inline]
+    fn set_solved(&mut self) {
+        self.is_solved = true;
+    }
+
+    #[inline]
+    fn num_nodes_hint(&self) -> usize {
+        self.num_nodes
+    }
+
+    #[inline]
+    fn is_raked(&self) -> bool {
+        self.config.rake_rate > 0.0 && self.config.rake_cap > 0.0
+    }
+}
+
+impl PreflopNode {
+    #[inline]
+    fn terminal(kind: NodeKind, bets: [i32; 2]) -> Self {
+        Self {
+            player: 0,
+            kind,
+            bets,
+            children: Vec::new(),
+            strategy: Vec::new(),
+            storage: Vec::new(),
+        }
+    }
+}
+
+impl GameNode for PreflopNode {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        self.kind != NodeKind::Decision
+    }
+
+    #[inline]
+    fn is_chance(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn player(&self) -> usize {
+        self.player
+    }
+
+    #[inline]
+    fn num_actions(&self) -> usize {
+        self.children.len()
+    }
+
+    #[inline]
+    fn play(&self, action: usize) -> MutexGuardLike<Self> {
+        self.children[action].1.lock()
+    }
+
+    #[inline]
+    fn strategy(&self) -> &[f32] {
+        &self.strategy
+    }
+
+    #[inline]
+    fn strategy_mut(&mut self) -> &mut [f32] {
+        &mut self.strategy
+    }
+
+    #[inline]
+    fn regrets(&self) -> &[f32] {
+        &self.storage
+    }
+
+    #[inline]
+    fn regrets_mut(&mut self) -> &mut [f32] {
+        &mut self.storage
+    }
+
+    #[inline]
+    fn cfvalues(&self) -> &[f32] {
+        &self.storage
+    }
+
+    #[inline]
+    fn cfvalues_mut(&mut self) -> &mut [f32] {
+        &mut self.storage
+    }
+}
+
+#[cfg(all(test, feature = "solver"))]
+mod tests {
+    use super::*;
+    use crate::solver::*;
+    use crate::utility::*;
+
+    fn config(ranges: [&str; 2], raise_sizes: &[&str]) -> PreflopConfig {
+        PreflopConfig {
+            ranges: ranges.map(|r| r.parse().unwrap()),
+            small_blind: 5,
+            big_blind: 10,
+            ante: 0,
+            effective_stack: 200,
+            raise_sizes: raise_sizes
+                .iter()
+                .map(|s| BetSizeOptions::try_from(("", *s)).unwrap().raise)
+                .collect(),
+            equity_samples: 2000,
+            seed: 1,
+            terminal_model: PreflopTerminalModel::AllInEquity,
+            rake_rate: 0.0,
+            rake_cap: 0.0,
+            no_flop_no_drop: false,
+        }
+    }
+
+    #[test]
+    fn preflop_tree() {
+        let game = PreflopGame::new(config(["AA,KK", "QQ,AK"], &["2.5x, a", "3x"])).unwrap();
+        assert_eq!(game.current_player(&[]), Ok(Some(0)));
+        assert_eq!(
+            game.available_actions(&[]),
+            Ok(vec![
+                Action::Fold,
+                Action::Call,
+                Action::Raise(25),
+                Action::AllIn(200)
+            ])
+        );
+
+        // the big blind has the option after a limp
+        assert_eq!(game.current_player(&[1]), Ok(Some(1)));
+        assert_eq!(
+            game.available_actions(&[1]),
+            Ok(vec![Action::Check, Action::Raise(25), Action::AllIn(200)])
+        );
+        assert_eq!(game.current_player(&[1, 0]), Ok(None));
+        assert_eq!(game.total_bet_amount(&[1, 0]), Ok([10, 10]));
+
+        // the second raise is the last one
+        assert_eq!(
+            game.available_actions(&[2]),
+            Ok(vec![Action::Fold, Action::Call, Action::Raise(75)])
+        );
+        assert_eq!(
+            game.available_actions(&[2, 2]),
+            Ok(vec![Action::Fold, Action::Call])
+        );
+        assert_eq!(game.current_player(&[2, 2, 1]), Ok(None));
+
+        assert_eq!(
+            game.available_actions(&[3]),
+            Ok(vec![Action::Fold, Action::Call])
+        );
+        assert!(game.available_actions(&[4]).is_err());
+    }
+
+    #[test]
+    fn preflop_equity() {
+        let game = PreflopGame::new(config(["AA", "KK"], &[])).unwrap();
+        let equity = game.equity(0, 0).unwrap();
+        assert!((equity - 0.82).abs() < 0.03, "{equity}");
+
+        let game = PreflopGame::new(config(["AsKs", "AhKh"], &[])).unwrap();
+        let equity = game.equity(0, 0).unwrap();
+        assert!((equity - 0.5).abs() < 0.02, "{equity}");
+
+        let err = PreflopGame::new(config(["AsKs", "AsQs"], &[])).err();
+        assert_eq!(
+            err,
+            Some(Error::Config(
+                "Ranges have no compatible hand combination".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn preflop_solve() {
+        // 72o is folded rather than limped or shoved against a range of big pairs
+        let mut game = PreflopGame::new(config(["AA,KK,72o", "AA,KK,QQ"], &["a"])).unwrap();
+        let exploitability = solve(&mut game, 1000, 0.01, false).exploitability;
+        assert!(exploitability <= 0.01);
+
+        let ev = compute_current_ev(&game);
+        assert!((ev[0] + ev[1]).abs() < 1e-3);
+
+        let strategy = game.strategy(&[]).unwrap();
+        let trash = game
+            .private_cards(0)
+            .iter()
+            .position(|&hand| hole_to_string(hand).unwrap().starts_with('7'))
+            .unwrap();
+        assert!(strategy[trash] > 0.95);
+
+        // the postflop estimator changes the values of the limped pots only
+        let mut config = config(["AA,KK,72o", "AA,KK,QQ"], &["a"]);
+        config.terminal_model = PreflopTerminalModel::Postflop(Arc::new(EquityRealization {
+            factors: [1.2, 0.8],
+        }));
+        let mut game = PreflopGame::new(config).unwrap();
+        solve(&mut game, 1000, 0.01, false);
+        assert!(game.strategy(&[1]).is_ok());
+    }
+
+    #[test]
+    fn preflop_rake() {
+        // the small blind folds: the pot is 10 chips after returning the uncalled 5 chips
+        let fold_value = |rake_rate: f64, no_flop_no_drop: bool| {
+            let mut config = config(["AA", "KK"], &[]);
+            config.rake_rate = rake_rate;
+            config.rake_cap = 100.0;
+            config.no_flop_no_drop = no_flop_no_drop;
+            let game = PreflopGame::new(config).unwrap();
+            let mut result = [MaybeUninit::uninit(); 6];
+            game.evaluate(&mut result, &game.root().play(0), 1, &[1.0; 6]);
+            unsafe { result[0].assume_init() }
+        };
+
+        let unraked = fold_value(0.0, false);
+        assert!((fold_value(0.1, false) - unraked * 0.8).abs() < 1e-6);
+        assert_eq!(fold_value(0.1, true), unraked);
+
+        let mut config = config(["AA,KK,72o", "AA,KK,QQ"], &["a"]);
+        config.rake_rate = 0.05;
+        config.rake_cap = 10.0;
+        let mut game = PreflopGame::new(config).unwrap();
+        assert!(game.is_raked());
+        solve(&mut game, 1000, 0.01, false);
+        let ev = compute_current_ev(&game);
+        assert!(ev[0] + ev[1] < 0.0);
+    }
+}
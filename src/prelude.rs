@@ -0,0 +1,47 @@
+//! The items needed on the common path: building a game, solving it, and reading the results.
+//!
+//! The crate root exports everything, while this module exports only the frequently used items
+//! so that glob-importing it does not pollute the namespace.
+//!
+//! # Examples
+//! ```
+//! use postflop_solver::prelude::*;
+//!
+//! let mut game = GameBuilder::new()
+//!     .oop_range("QQ+,AKs")
+//!     .ip_range("JJ-99,AQs")
+//!     .flop("Td9d6h")
+//!     .turn("Qc")
+//!     .river("2s")
+//!     .starting_pot(100)
+//!     .effective_stack(200)
+//!     .bets("50%,a")
+//!     .build()
+//!     .unwrap();
+//!
+//! game.allocate_memory(false);
+//! # #[cfg(feature = "solver")]
+//! # {
+//! let summary = solve(&mut game, 100, 0.5, false);
+//! assert!(summary.exploitability <= 0.5);
+//!
+//! game.cache_normalized_weights();
+//! let ev = compute_current_ev(&game);
+//! let strategy = game.strategy();
+//! # }
+//! ```
+
+pub use crate::action_tree::{Action, ActionTree, BoardState, TreeConfig};
+pub use crate::bet_size::{BetSize, BetSizeOptions, DonkSizeOptions};
+pub use crate::builder::GameBuilder;
+pub use crate::card::{Card, CardConfig, NOT_DEALT};
+pub use crate::error::Error;
+pub use crate::game::PostFlopGame;
+pub use crate::interface::Game;
+pub use crate::range::{card_from_str, card_to_string, flop_from_str, holes_to_strings, Range};
+pub use crate::utility::{compute_current_ev, compute_exploitability, finalize};
+
+#[cfg(feature = "solver")]
+pub use crate::solver::{
+    solve, solve_with_options, try_solve, SolveOptions, SolveSummary, SolverConfig,
+};
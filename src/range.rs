@@ -1,23 +1,34 @@
 use crate::card::*;
+use crate::error::*;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use std::borrow::Cow;
 use std::fmt::Write;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 /// A struct representing a player's range.
 ///
 /// The [`Range`] struct implements the [`FromStr`] trait, so you can construct a range from a string
 /// using `parse::<Range>()`. The string must be in the following format (similar to PioSOLVER):
 ///
 /// - Each group is separated by a comma. (e.g., "AA,AKs")
-/// - Each group can have an optional weight separated by a colon. (e.g., "AA:0.5")
+/// - Each group can have an optional weight separated by a colon, given either as a number or as
+///   a percentage. (e.g., "AA:0.5", "AA:50%")
 /// - Each group must be one of the following:
 ///   - Singleton (e.g., "AA", "AKs", "AKo", "AsAh")
 ///   - Plus range (e.g., "TT+", "ATs+", "T9o+")
-///   - Dash range (e.g., "QQ-88", "A9s-A6s", "98o-65o")
+///   - Dash range in either order (e.g., "QQ-88", "A9s-A6s", "98o-65o", "77-99")
+/// - Whitespace around the separators is ignored. If groups overlap, the earlier one takes
+///   precedence.
+/// - Suits may also be written as Unicode symbols and the ten as `10` (e.g., "A10s", "A♠K♠"),
+///   regardless of [`card_format`].
 ///
 /// # Examples
 /// ```
@@ -37,6 +48,9 @@ use bincode::{Decode, Encode};
 /// // check that the hand "AKo" is not in the range
 /// assert_eq!(range.get_weight_offsuit(ace_rank, king_rank), 0.0);
 /// ```
+///
+/// With the `serde` feature, a range is serialized as the string returned by `to_string()` (e.g.,
+/// `"QQ+,AKs"`) and deserialized by parsing such a string.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "bincode", derive(Decode, Encode))]
 pub struct Range {
@@ -52,7 +66,7 @@ enum Suitedness {
 }
 
 const COMBO_PAT: &str = r"(?:(?:[AaKkQqJjTt2-9]{2}[os]?)|(?:(?:[AaKkQqJjTt2-9][cdhs]){2}))";
-const WEIGHT_PAT: &str = r"(?:(?:[01](\.\d*)?)|(?:\.\d+))";
+const WEIGHT_PAT: &str = r"(?:(?:[01](\.\d*)?)|(?:\.\d+)|(?:\d+(?:\.\d*)?%))";
 
 static RANGE_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(&format!(
@@ -133,7 +147,7 @@ fn indices_with_suitedness(rank1: u8, rank2: u8, suitedness: Suitedness) -> Vec<
 ///
 /// `'A'` => `12`, `'K'` => `11`, ..., `'2'` => `0`.
 #[inline]
-fn char_to_rank(c: char) -> Result<u8, String> {
+fn char_to_rank(c: char) -> Result<u8, Error> {
     match c {
         'A' | 'a' => Ok(12),
         'K' | 'k' => Ok(11),
@@ -141,21 +155,26 @@ fn char_to_rank(c: char) -> Result<u8, String> {
         'J' | 'j' => Ok(9),
         'T' | 't' => Ok(8),
         '2'..='9' => Ok(c as u8 - b'2'),
-        _ => Err(format!("Expected rank character: {c}")),
+        _ => Err(Error::Card(format!("Expected rank character: {c}"))),
     }
 }
 
 /// Attempts to conver a suit character to a suit index.
 ///
-/// `'c'` => `0`, `'d'` => `1`, `'h'` => `2`, `'s'` => `3`.
+/// `'c'` => `0`, `'d'` => `1`, `'h'` => `2`, `'s'` => `3`, and likewise for `'♣'`, `'♦'`, `'♥'`,
+/// and `'♠'`.
 #[inline]
-fn char_to_suit(c: char) -> Result<u8, String> {
+fn char_to_suit(c: char) -> Result<u8, Error> {
     match c {
         'c' => Ok(0),
         'd' => Ok(1),
         'h' => Ok(2),
         's' => Ok(3),
-        _ => Err(format!("Expected suit character: {c}")),
+        '♣' => Ok(0),
+        '♦' => Ok(1),
+        '♥' => Ok(2),
+        '♠' => Ok(3),
+        _ => Err(Error::Card(format!("Expected suit character: {c}"))),
     }
 }
 
@@ -163,7 +182,7 @@ fn char_to_suit(c: char) -> Result<u8, String> {
 ///
 /// `12` => `'A'`, `11` => `'K'`, ..., `0` => `'2'`.
 #[inline]
-fn rank_to_char(rank: u8) -> Result<char, String> {
+fn rank_to_char(rank: u8) -> Result<char, Error> {
     match rank {
         12 => Ok('A'),
         11 => Ok('K'),
@@ -171,7 +190,7 @@ fn rank_to_char(rank: u8) -> Result<char, String> {
         9 => Ok('J'),
         8 => Ok('T'),
         0..=7 => Ok((rank + b'2') as char),
-        _ => Err(format!("Invalid input: {rank}")),
+        _ => Err(Error::Card(format!("Invalid input: {rank}"))),
     }
 }
 
@@ -179,17 +198,113 @@ fn rank_to_char(rank: u8) -> Result<char, String> {
 ///
 /// `0` => `'c'`, `1` => `'d'`, `2` => `'h'`, `3` => `'s'`.
 #[inline]
-fn suit_to_char(suit: u8) -> Result<char, String> {
+fn suit_to_char(suit: u8) -> Result<char, Error> {
     match suit {
         0 => Ok('c'),
         1 => Ok('d'),
         2 => Ok('h'),
         3 => Ok('s'),
-        _ => Err(format!("Invalid input: {suit}")),
+        _ => Err(Error::Card(format!("Invalid input: {suit}"))),
     }
 }
 
-/// Attempts to convert a card into a string.
+/// Format of the card strings emitted by the crate.
+///
+/// The default format is the standard one, e.g., `"Td"` and `"As"`. The format applies to
+/// [`card_to_string`] and everything built on it (e.g., hand labels, reports, and error messages),
+/// while the machine-readable outputs (e.g., the OpenSpiel policy, the SQLite store, and the
+/// golden files) and the range strings always use the standard format. The parsers accept both
+/// formats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CardFormat {
+    /// Whether to write the suits as Unicode symbols (`♣`, `♦`, `♥`, `♠`) instead of letters.
+    pub suit_symbols: bool,
+
+    /// Whether to write the ten as `10` instead of `T`.
+    pub ten_as_10: bool,
+}
+
+static CARD_FORMAT: AtomicU8 = AtomicU8::new(0);
+
+impl CardFormat {
+    #[inline]
+    fn to_bits(self) -> u8 {
+        self.suit_symbols as u8 | (self.ten_as_10 as u8) << 1
+    }
+
+    #[inline]
+    fn from_bits(bits: u8) -> Self {
+        Self {
+            suit_symbols: bits & 1 != 0,
+            ten_as_10: bits & 2 != 0,
+        }
+    }
+}
+
+/// Sets the format of the card strings emitted by the crate (see [`CardFormat`]).
+///
+/// The format is global to the process, so that embedding applications can localize every
+/// label at once.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// set_card_format(CardFormat {
+///     suit_symbols: true,
+///     ten_as_10: true,
+/// });
+/// assert_eq!(hole_to_string((34, 51)), Ok("A♠10♥".to_string()));
+///
+/// // the parsers accept both formats
+/// assert_eq!(card_from_str("10♥"), card_from_str("Th"));
+/// ```
+#[inline]
+pub fn set_card_format(format: CardFormat) {
+    CARD_FORMAT.store(format.to_bits(), Ordering::Relaxed);
+}
+
+/// Returns the format of the card strings emitted by the crate (see [`set_card_format`]).
+#[inline]
+pub fn card_format() -> CardFormat {
+    CardFormat::from_bits(CARD_FORMAT.load(Ordering::Relaxed))
+}
+
+/// Attempts to convert a card into a string in the given format.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::{card_to_string_with, CardFormat};
+///
+/// let format = CardFormat {
+///     suit_symbols: true,
+///     ten_as_10: true,
+/// };
+/// assert_eq!(card_to_string_with(34, format), Ok("10♥".to_string()));
+/// assert_eq!(card_to_string_with(51, format), Ok("A♠".to_string()));
+/// assert_eq!(card_to_string_with(51, CardFormat::default()), Ok("As".to_string()));
+/// ```
+pub fn card_to_string_with(card: Card, format: CardFormat) -> Result<String, Error> {
+    check_card(card)?;
+    let rank = card >> 2;
+    let suit = card & 3;
+
+    let mut ret = String::with_capacity(4);
+    match rank {
+        8 if format.ten_as_10 => ret.push_str("10"),
+        _ => ret.push(rank_to_char(rank)?),
+    }
+
+    if format.suit_symbols {
+        ret.push(['♣', '♦', '♥', '♠'][suit as usize]);
+    } else {
+        ret.push(suit_to_char(suit)?);
+    }
+
+    Ok(ret)
+}
+
+/// Attempts to convert a card into a string in the format set by [`set_card_format`].
 ///
 /// # Examples
 /// ```
@@ -202,11 +317,8 @@ fn suit_to_char(suit: u8) -> Result<char, String> {
 /// assert!(card_to_string(52).is_err());
 /// ```
 #[inline]
-pub fn card_to_string(card: Card) -> Result<String, String> {
-    check_card(card)?;
-    let rank = card >> 2;
-    let suit = card & 3;
-    Ok(format!("{}{}", rank_to_char(rank)?, suit_to_char(suit)?))
+pub fn card_to_string(card: Card) -> Result<String, Error> {
+    card_to_string_with(card, card_format())
 }
 
 /// Attempts to convert hole cards into a string.
@@ -224,7 +336,7 @@ pub fn card_to_string(card: Card) -> Result<String, String> {
 /// assert!(hole_to_string((52, 53)).is_err());
 /// ```
 #[inline]
-pub fn hole_to_string(hole: (Card, Card)) -> Result<String, String> {
+pub fn hole_to_string(hole: (Card, Card)) -> Result<String, Error> {
     let max_card = Card::max(hole.0, hole.1);
     let min_card = Card::min(hole.0, hole.1);
     Ok(format!(
@@ -251,12 +363,14 @@ pub fn hole_to_string(hole: (Card, Card)) -> Result<String, String> {
 /// assert!(holes_to_strings(&[(52, 53)]).is_err());
 /// ```
 #[inline]
-pub fn holes_to_strings(holes: &[(Card, Card)]) -> Result<Vec<String>, String> {
+pub fn holes_to_strings(holes: &[(Card, Card)]) -> Result<Vec<String>, Error> {
     holes.iter().map(|&hole| hole_to_string(hole)).collect()
 }
 
 /// Attempts to read the next card from a char iterator.
 ///
+/// The ten may be written as `10`, and the suits as Unicode symbols.
+///
 /// # Examples
 /// ```
 /// use postflop_solver::card_from_chars;
@@ -269,12 +383,21 @@ pub fn holes_to_strings(holes: &[(Card, Card)]) -> Result<Vec<String>, String> {
 /// assert!(card_from_chars(&mut chars).is_err());
 /// ```
 #[inline]
-pub fn card_from_chars<T: Iterator<Item = char>>(chars: &mut T) -> Result<Card, String> {
-    let rank_char = chars.next().ok_or_else(|| "Unexpected end".to_string())?;
-    let suit_char = chars.next().ok_or_else(|| "Unexpected end".to_string())?;
-
-    let rank = char_to_rank(rank_char)?;
-    let suit = char_to_suit(suit_char)?;
+pub fn card_from_chars<T: Iterator<Item = char>>(chars: &mut T) -> Result<Card, Error> {
+    let mut next = || {
+        chars
+            .next()
+            .ok_or_else(|| Error::Card("Unexpected end".to_string()))
+    };
+
+    let rank = match next()? {
+        '1' => match next()? {
+            '0' => 8,
+            c => return Err(Error::Card(format!("Expected rank character: 1{c}"))),
+        },
+        c => char_to_rank(c)?,
+    };
+    let suit = char_to_suit(next()?)?;
 
     Ok((rank << 2) | suit)
 }
@@ -291,12 +414,12 @@ pub fn card_from_chars<T: Iterator<Item = char>>(chars: &mut T) -> Result<Card,
 /// assert_eq!(card_from_str("As"), Ok(51));
 /// ```
 #[inline]
-pub fn card_from_str(s: &str) -> Result<Card, String> {
+pub fn card_from_str(s: &str) -> Result<Card, Error> {
     let mut chars = s.chars();
     let result = card_from_chars(&mut chars)?;
 
     if chars.next().is_some() {
-        return Err("Expected exactly two characters".to_string());
+        return Err(Error::Card("Expected exactly one card".to_string()));
     }
 
     Ok(result)
@@ -313,7 +436,7 @@ pub fn card_from_str(s: &str) -> Result<Card, String> {
 /// assert!(flop_from_str("2c3d4h5s").is_err());
 /// ```
 #[inline]
-pub fn flop_from_str(s: &str) -> Result<[Card; 3], String> {
+pub fn flop_from_str(s: &str) -> Result<[Card; 3], Error> {
     let mut result = [0; 3];
     let mut chars = s.chars();
 
@@ -322,20 +445,59 @@ pub fn flop_from_str(s: &str) -> Result<[Card; 3], String> {
     result[2] = card_from_chars(&mut chars.by_ref().skip_while(|c| c.is_whitespace()))?;
 
     if chars.next().is_some() {
-        return Err("Expected exactly three cards".to_string());
+        return Err(Error::Card("Expected exactly three cards".to_string()));
     }
 
     result.sort_unstable();
 
     if result[0] == result[1] || result[1] == result[2] {
-        return Err("Cards must be unique".to_string());
+        return Err(Error::Card("Cards must be unique".to_string()));
+    }
+
+    Ok(result)
+}
+
+/// Attempts to convert an optionally space-separated string into a board (three to five unique
+/// cards, in the given order).
+///
+/// # Examples
+/// ```
+/// use postflop_solver::board_from_str;
+///
+/// assert_eq!(board_from_str("2c3d4h"), Ok(vec![0, 5, 10]));
+/// assert_eq!(board_from_str("As Ah Ks 2c"), Ok(vec![51, 50, 47, 0]));
+/// assert!(board_from_str("2c3d").is_err());
+/// assert!(board_from_str("2c3d4h5s6c7d").is_err());
+/// assert!(board_from_str("2c3d2c").is_err());
+/// ```
+pub fn board_from_str(s: &str) -> Result<Vec<Card>, Error> {
+    let mut result = Vec::with_capacity(5);
+    let mut chars = s.chars().filter(|c| !c.is_whitespace()).peekable();
+    let mut mask: u64 = 0;
+
+    while chars.peek().is_some() {
+        if result.len() == 5 {
+            return Err(Error::Card("Expected at most five cards".to_string()));
+        }
+
+        let card = card_from_chars(&mut chars)?;
+        if mask & (1 << card) != 0 {
+            return Err(Error::Card("Cards must be unique".to_string()));
+        }
+
+        mask |= 1 << card;
+        result.push(card);
+    }
+
+    if result.len() < 3 {
+        return Err(Error::Card("Expected at least three cards".to_string()));
     }
 
     Ok(result)
 }
 
 #[inline]
-fn parse_singleton(combo: &str) -> Result<(u8, u8, Suitedness), String> {
+fn parse_singleton(combo: &str) -> Result<(u8, u8, Suitedness), Error> {
     if combo.len() == 4 {
         parse_simple_singleton(combo)
     } else {
@@ -344,59 +506,114 @@ fn parse_singleton(combo: &str) -> Result<(u8, u8, Suitedness), String> {
 }
 
 #[inline]
-fn parse_simple_singleton(combo: &str) -> Result<(u8, u8, Suitedness), String> {
+fn parse_simple_singleton(combo: &str) -> Result<(u8, u8, Suitedness), Error> {
     let mut chars = combo.chars();
-    let rank1 = char_to_rank(chars.next().ok_or_else(|| "Unexpected end".to_string())?)?;
-    let suit1 = char_to_suit(chars.next().ok_or_else(|| "Unexpected end".to_string())?)?;
-    let rank2 = char_to_rank(chars.next().ok_or_else(|| "Unexpected end".to_string())?)?;
-    let suit2 = char_to_suit(chars.next().ok_or_else(|| "Unexpected end".to_string())?)?;
+    let rank1 = char_to_rank(
+        chars
+            .next()
+            .ok_or_else(|| Error::Card("Unexpected end".to_string()))?,
+    )?;
+    let suit1 = char_to_suit(
+        chars
+            .next()
+            .ok_or_else(|| Error::Card("Unexpected end".to_string()))?,
+    )?;
+    let rank2 = char_to_rank(
+        chars
+            .next()
+            .ok_or_else(|| Error::Card("Unexpected end".to_string()))?,
+    )?;
+    let suit2 = char_to_suit(
+        chars
+            .next()
+            .ok_or_else(|| Error::Card("Unexpected end".to_string()))?,
+    )?;
     if rank1 < rank2 {
-        return Err(format!(
+        return Err(Error::Card(format!(
             "The first rank must be equal or higher than the second rank: {combo}"
-        ));
+        )));
     }
     if rank1 == rank2 && suit1 == suit2 {
-        return Err(format!("Duplicate cards are not allowed: {combo}"));
+        return Err(Error::Card(format!(
+            "Duplicate cards are not allowed: {combo}"
+        )));
     }
     Ok((rank1, rank2, Suitedness::Specific(suit1, suit2)))
 }
 
 #[inline]
-fn parse_compound_singleton(combo: &str) -> Result<(u8, u8, Suitedness), String> {
+fn parse_compound_singleton(combo: &str) -> Result<(u8, u8, Suitedness), Error> {
     let mut chars = combo.chars();
-    let rank1 = char_to_rank(chars.next().ok_or_else(|| "Unexpected end".to_string())?)?;
-    let rank2 = char_to_rank(chars.next().ok_or_else(|| "Unexpected end".to_string())?)?;
+    let rank1 = char_to_rank(
+        chars
+            .next()
+            .ok_or_else(|| Error::Card("Unexpected end".to_string()))?,
+    )?;
+    let rank2 = char_to_rank(
+        chars
+            .next()
+            .ok_or_else(|| Error::Card("Unexpected end".to_string()))?,
+    )?;
     let suitedness = chars.next().map_or(Ok(Suitedness::All), |c| match c {
         's' => Ok(Suitedness::Suited),
         'o' => Ok(Suitedness::Offsuit),
-        _ => Err(format!("Invalid suitedness: {combo}")),
+        _ => Err(Error::Card(format!("Invalid suitedness: {combo}"))),
     })?;
     if rank1 < rank2 {
-        return Err(format!(
+        return Err(Error::Card(format!(
             "The first rank must be equal or higher than the second rank: {combo}"
-        ));
+        )));
     }
     if rank1 == rank2 && suitedness != Suitedness::All {
-        return Err(format!("A pair with suitedness is not allowed: {combo}"));
+        return Err(Error::Card(format!(
+            "A pair with suitedness is not allowed: {combo}"
+        )));
     }
     Ok((rank1, rank2, suitedness))
 }
 
+/// Rewrites the tens written as `10` and the Unicode suit symbols in the hands of a range group
+/// into the standard format, keeping the weight as is.
+fn normalize_cards(group: &str) -> Cow<str> {
+    let (hands, weight) = group.split_at(group.find(':').unwrap_or(group.len()));
+    if hands.is_ascii() && !hands.contains("10") {
+        return Cow::Borrowed(group);
+    }
+
+    let mut ret = hands
+        .replace("10", "T")
+        .replace('♣', "c")
+        .replace('♦', "d")
+        .replace('♥', "h")
+        .replace('♠', "s");
+    ret.push_str(weight);
+    Cow::Owned(ret)
+}
+
 #[inline]
-fn check_card(card: Card) -> Result<(), String> {
+fn check_card(card: Card) -> Result<(), Error> {
     if card < 52 {
         Ok(())
     } else {
-        Err(format!("Invalid card: {card}"))
+        Err(Error::Card(format!("Invalid card: {card}")))
     }
 }
 
+/// Parses a weight matched by `WEIGHT_PAT`, which is either a number or a percentage.
 #[inline]
-fn check_weight(weight: f32) -> Result<(), String> {
+fn parse_weight(s: &str) -> f32 {
+    match s.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().unwrap() / 100.0,
+        None => s.parse().unwrap(),
+    }
+}
+
+#[inline]
+fn check_weight(weight: f32) -> Result<(), Error> {
     if (0.0..=1.0).contains(&weight) {
         Ok(())
     } else {
-        Err(format!("Invalid weight: {weight}"))
+        Err(Error::Card(format!("Invalid weight: {weight}")))
     }
 }
 
@@ -426,9 +643,12 @@ impl Range {
 
     /// Attempts to create a range from raw data.
     #[inline]
-    pub fn from_raw_data(data: &[f32]) -> Result<Self, String> {
+    pub fn from_raw_data(data: &[f32]) -> Result<Self, Error> {
         if data.len() != 52 * 51 / 2 {
-            return Err(format!("Expected exactly {} elements", 52 * 51 / 2));
+            return Err(Error::Card(format!(
+                "Expected exactly {} elements",
+                52 * 51 / 2
+            )));
         }
 
         for &weight in data {
@@ -448,14 +668,16 @@ impl Range {
 
     /// Attempts to create a range from a list of hands with their weights.
     #[inline]
-    pub fn from_hands_weights(hands: &[(Card, Card)], weights: &[f32]) -> Result<Self, String> {
+    pub fn from_hands_weights(hands: &[(Card, Card)], weights: &[f32]) -> Result<Self, Error> {
         let mut range = Self::default();
         for (&(card1, card2), &weight) in hands.iter().zip(weights.iter()) {
             check_card(card1)?;
             check_card(card2)?;
             check_weight(weight)?;
             if card1 == card2 {
-                return Err("Hand must consist of two different cards".to_string());
+                return Err(Error::Card(
+                    "Hand must consist of two different cards".to_string(),
+                ));
             }
             range.set_weight_by_cards(card1, card2, weight);
         }
@@ -492,7 +714,7 @@ impl Range {
     /// "Sanitized" means that the range string does not contain any invalid patterns and whitespace
     /// characters. Therefore, this method can bypass the regular expression processing. If you want
     /// to create a range from a regular string, use `parse::<Range>()` instead.
-    pub fn from_sanitized_str(ranges: &str) -> Result<Self, String> {
+    pub fn from_sanitized_str(ranges: &str) -> Result<Self, Error> {
         let mut ranges = ranges.split(',').collect::<Vec<_>>();
 
         // remove last empty element if any
@@ -506,13 +728,13 @@ impl Range {
             let mut split = range.split(':');
             let range = split.next().unwrap();
 
-            let weight = split
-                .next()
-                .map_or(Ok(1.0), |s| s.parse::<f32>().map_err(|e| e.to_string()))?;
+            let weight = split.next().map_or(Ok(1.0), |s| {
+                s.parse::<f32>().map_err(|e| Error::Card(e.to_string()))
+            })?;
             check_weight(weight)?;
 
             if split.next().is_some() {
-                return Err(format!("Invalid range: {range}"));
+                return Err(Error::Card(format!("Invalid range: {range}")));
             }
 
             if range.contains('-') {
@@ -527,6 +749,64 @@ impl Range {
         Ok(result)
     }
 
+    /// Sets the weight of the combos specified by `combos` to `weight`.
+    ///
+    /// `combos` is a single element of a range string without the weight, e.g., `"AA"`, `"AKs"`,
+    /// `"QQ+"`, `"77-99"`, or `"AsKh"`. The weights of the other combos are not changed.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let mut range = Range::new();
+    /// range.set_weight_by_str("AA", 0.5).unwrap();
+    /// range.set_weight_by_str("KQo", 0.25).unwrap();
+    /// assert_eq!(range, "AA:0.5,KQo:0.25".parse().unwrap());
+    /// ```
+    pub fn set_weight_by_str(&mut self, combos: &str, weight: f32) -> Result<(), Error> {
+        let combos = TRIM_REGEX.replace_all(combos, "$1");
+        let combos = normalize_cards(combos.trim());
+        let combos = combos.as_ref();
+        let is_valid = RANGE_REGEX
+            .captures(combos)
+            .is_some_and(|caps| caps.name("weight").is_none());
+        if !is_valid {
+            return Err(Error::Card(format!("Failed to parse combos: {combos}")));
+        }
+
+        check_weight(weight)?;
+
+        if combos.contains('-') {
+            self.update_with_dash_range(combos, weight)
+        } else if combos.contains('+') {
+            self.update_with_plus_range(combos, weight)
+        } else {
+            self.update_with_singleton(combos, weight)
+        }
+    }
+
+    /// Returns the range with the weight of `combos` set to `weight` (see
+    /// [`set_weight_by_str`]), allowing a range to be built by chaining the calls.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let range = Range::new()
+    ///     .with_weight("AA", 0.5)?
+    ///     .with_weight("77-99", 1.0)?
+    ///     .with_weight("AsKh", 0.3)?;
+    /// assert_eq!(range, "AA:0.5,99-77,AsKh:0.3".parse()?);
+    /// # Ok::<(), Error>(())
+    /// ```
+    ///
+    /// [`set_weight_by_str`]: #method.set_weight_by_str
+    #[inline]
+    pub fn with_weight(mut self, combos: &str, weight: f32) -> Result<Self, Error> {
+        self.set_weight_by_str(combos, weight)?;
+        Ok(self)
+    }
+
     /// Clears the range.
     #[inline]
     pub fn clear(&mut self) {
@@ -683,6 +963,23 @@ impl Range {
         true
     }
 
+    /// Returns whether the weights are invariant under the suit permutation `perm`, which maps
+    /// the suit `s` to `perm[s]`.
+    pub(crate) fn is_suit_permutation_invariant(&self, perm: &[u8; 4]) -> bool {
+        let permute = |card: u8| (card & !3) | perm[card as usize & 3];
+        for card1 in 0..52 {
+            for card2 in card1 + 1..52 {
+                let weight = self.get_weight_by_cards(card1, card2);
+                let weight_permuted = self.get_weight_by_cards(permute(card1), permute(card2));
+                if weight != weight_permuted {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
     #[inline]
     fn is_same_weight(&self, indices: &[usize]) -> bool {
         let weight = self.data[indices[0]];
@@ -706,14 +1003,14 @@ impl Range {
     }
 
     #[inline]
-    fn update_with_singleton(&mut self, combo: &str, weight: f32) -> Result<(), String> {
+    fn update_with_singleton(&mut self, combo: &str, weight: f32) -> Result<(), Error> {
         let (rank1, rank2, suitedness) = parse_singleton(combo)?;
         self.set_weight(&indices_with_suitedness(rank1, rank2, suitedness), weight);
         Ok(())
     }
 
     #[inline]
-    fn update_with_plus_range(&mut self, range: &str, weight: f32) -> Result<(), String> {
+    fn update_with_plus_range(&mut self, range: &str, weight: f32) -> Result<(), Error> {
         let lowest_combo = &range[..range.len() - 1];
         let (rank1, rank2, suitedness) = parse_singleton(lowest_combo)?;
         let gap = rank1 - rank2;
@@ -732,36 +1029,28 @@ impl Range {
     }
 
     #[inline]
-    fn update_with_dash_range(&mut self, range: &str, weight: f32) -> Result<(), String> {
+    fn update_with_dash_range(&mut self, range: &str, weight: f32) -> Result<(), Error> {
         let combo_pair = range.split('-').collect::<Vec<_>>();
         let (rank11, rank12, suitedness) = parse_singleton(combo_pair[0])?;
         let (rank21, rank22, suitedness2) = parse_singleton(combo_pair[1])?;
         let gap = rank11 - rank12;
         let gap2 = rank21 - rank22;
         if suitedness != suitedness2 {
-            Err(format!("Suitedness does not match: {range}"))
-        } else if gap == gap2 {
-            // same gap (e.g., 88-55, KQo-JTo)
-            if rank11 > rank21 {
-                for i in rank21..=rank11 {
-                    self.set_weight(&indices_with_suitedness(i, i - gap, suitedness), weight);
-                }
-                Ok(())
-            } else {
-                Err(format!("Range must be in descending order: {range}"))
+            Err(Error::Card(format!("Suitedness does not match: {range}")))
+        } else if gap == gap2 && rank11 != rank21 {
+            // same gap (e.g., 88-55, KQo-JTo; ascending order such as 55-88 is also accepted)
+            for i in rank11.min(rank21)..=rank11.max(rank21) {
+                self.set_weight(&indices_with_suitedness(i, i - gap, suitedness), weight);
             }
-        } else if rank11 == rank21 {
-            // same first rank (e.g., A5s-A2s)
-            if rank12 > rank22 {
-                for i in rank22..=rank12 {
-                    self.set_weight(&indices_with_suitedness(rank11, i, suitedness), weight);
-                }
-                Ok(())
-            } else {
-                Err(format!("Range must be in descending order: {range}"))
+            Ok(())
+        } else if rank11 == rank21 && rank12 != rank22 {
+            // same first rank (e.g., A5s-A2s; ascending order such as A2s-A5s is also accepted)
+            for i in rank12.min(rank22)..=rank12.max(rank22) {
+                self.set_weight(&indices_with_suitedness(rank11, i, suitedness), weight);
             }
+            Ok(())
         } else {
-            Err(format!("Invalid range: {range}"))
+            Err(Error::Card(format!("Invalid range: {range}")))
         }
     }
 
@@ -953,7 +1242,7 @@ impl Range {
 }
 
 impl FromStr for Range {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = TRIM_REGEX.replace_all(s, "$1").trim().to_string();
@@ -967,14 +1256,15 @@ impl FromStr for Range {
         let mut result = Self::new();
 
         for range in ranges.into_iter().rev() {
+            let range = normalize_cards(range);
             let caps = RANGE_REGEX
-                .captures(range)
-                .ok_or_else(|| format!("Failed to parse range: {range}"))?;
+                .captures(&range)
+                .ok_or_else(|| Error::Card(format!("Failed to parse range: {range}")))?;
 
             let range = caps.name("range").unwrap().as_str();
             let weight = caps
                 .name("weight")
-                .map_or(1.0, |s| s.as_str().parse().unwrap());
+                .map_or(1.0, |s| parse_weight(s.as_str()));
             check_weight(weight)?;
 
             if range.contains('-') {
@@ -1001,6 +1291,23 @@ impl ToString for Range {
     }
 }
 
+#[cfg(feature = "serde")]
+impl Serialize for Range {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Range {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1016,11 +1323,14 @@ mod tests {
             ("AcKh", Some(("AcKh", None))),
             ("8h8s+:.67", Some(("8h8s+", Some(".67")))),
             ("9d8d-6d5d:0.25", Some(("9d8d-6d5d", Some("0.25")))),
+            ("AA:50%", Some(("AA", Some("50%")))),
+            ("KQo:2.5%", Some(("KQo", Some("2.5%")))),
             ("AKQ", None),
             ("AK+-AJ", None),
             ("K9s.67", None),
             ("88+:2.0", None),
             ("98s-21s", None),
+            ("AA:%", None),
         ];
 
         for (s, expected) in tests {
@@ -1126,8 +1436,18 @@ mod tests {
         let dash_error_3 = "AQo-KQo".parse::<Range>();
         assert!(dash_error_3.is_err());
 
-        let dash_error_4 = "K2-K5".parse::<Range>();
-        assert!(dash_error_4.is_err());
+        let dash_ascending = "K2-K5,55-88:0.5".parse::<Range>();
+        let dash_ascending_equiv = "K5-K2,88-55:0.5".parse::<Range>();
+        assert!(dash_ascending.is_ok());
+        assert_eq!(dash_ascending, dash_ascending_equiv);
+
+        let percent = "AA:50%,KK:25.5%".parse::<Range>();
+        let percent_equiv = "AA:0.5,KK:0.255".parse::<Range>();
+        assert!(percent.is_ok());
+        assert_eq!(percent, percent_equiv);
+
+        let percent_error = "AA:150%".parse::<Range>();
+        assert!(percent_error.is_err());
 
         let dash_error_5 = "AhAs-QsQh".parse::<Range>();
         assert!(dash_error_5.is_err());
@@ -1142,6 +1462,21 @@ mod tests {
         assert_eq!(data.get_weight_offsuit(6, 3), 0.0);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn range_serde() {
+        use serde::de::value::{Error, StrDeserializer};
+        use serde::de::IntoDeserializer;
+
+        let range = "QQ+,AKs,AhKh:0.25".parse::<Range>().unwrap();
+        let s = range.to_string();
+        let deserializer: StrDeserializer<Error> = s.as_str().into_deserializer();
+        assert_eq!(Range::deserialize(deserializer).unwrap(), range);
+
+        let deserializer: StrDeserializer<Error> = "AK+-AJ".into_deserializer();
+        assert!(Range::deserialize(deserializer).is_err());
+    }
+
     #[test]
     fn range_to_string() {
         let tests = [
@@ -1163,4 +1498,92 @@ mod tests {
             assert_eq!(range.unwrap().to_string(), expected);
         }
     }
+
+    #[test]
+    fn range_round_trip() {
+        let input = "AA:0.5, AKs, KQo:0.25, 77-99, AsKh:0.3";
+        let range = input.parse::<Range>().unwrap();
+        assert_eq!(range.get_weight_pair(12), 0.5);
+        assert_eq!(range.get_weight_pair(6), 1.0);
+        assert_eq!(range.get_weight_suited(12, 11), 1.0);
+        assert_eq!(range.get_weight_offsuit(11, 10), 0.25);
+        let ace_spades = card_from_str("As").unwrap();
+        let king_hearts = card_from_str("Kh").unwrap();
+        assert_eq!(range.get_weight_by_cards(ace_spades, king_hearts), 0.3);
+        assert_eq!(range.to_string().parse::<Range>().unwrap(), range);
+
+        let mut built = Range::new();
+        for (combos, weight) in [("AA", 0.5), ("AKs", 1.0), ("KQo", 0.25), ("77-99", 1.0)] {
+            built.set_weight_by_str(combos, weight).unwrap();
+        }
+        built.set_weight_by_str(" AsKh ", 0.3).unwrap();
+        assert_eq!(built, range);
+
+        assert!(built.set_weight_by_str("AA:0.5", 1.0).is_err());
+        assert!(built.set_weight_by_str("AA,KK", 1.0).is_err());
+        assert!(built.set_weight_by_str("AA", 1.5).is_err());
+    }
+
+    #[test]
+    fn card_format() {
+        let format = CardFormat {
+            suit_symbols: true,
+            ten_as_10: true,
+        };
+        let ten_hearts = card_from_str("Th").unwrap();
+        assert_eq!(
+            card_to_string_with(ten_hearts, format),
+            Ok("10♥".to_string())
+        );
+        assert_eq!(card_to_string_with(0, format), Ok("2♣".to_string()));
+
+        for card in 0..52 {
+            let s = card_to_string_with(card, format).unwrap();
+            assert_eq!(card_from_str(&s), Ok(card));
+        }
+
+        assert_eq!(flop_from_str("10♦ 9♦ 6♥"), flop_from_str("Td9d6h"));
+        assert_eq!(board_from_str("10♦9♦6♥Q♣"), board_from_str("Td9d6hQc"));
+        assert!(card_from_str("1h").is_err());
+        assert!(card_from_str("11h").is_err());
+
+        let range = "1010+:0.5,A10s-A8s,K♠Q♠:10%".parse::<Range>().unwrap();
+        assert_eq!(range, "TT+:0.5,ATs-A8s,KsQs:0.1".parse().unwrap());
+
+        let mut built = Range::new();
+        built.set_weight_by_str("A♥10♥", 1.0).unwrap();
+        assert_eq!(built, "AhTh".parse().unwrap());
+    }
+
+    #[test]
+    fn parse_malformed_input() {
+        // deterministic smoke test of the inputs exercised by the fuzz targets
+        const ALPHABET: &[u8] = b"AKQJT98765432akcdhso+-:,. 01\xc3\xa9";
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..20000 {
+            let len = next() % 16;
+            let bytes = (0..len)
+                .map(|_| ALPHABET[(next() % ALPHABET.len() as u64) as usize])
+                .collect::<Vec<_>>();
+            let s = String::from_utf8_lossy(&bytes);
+
+            if let Ok(range) = s.parse::<Range>() {
+                assert_eq!(range.to_string().parse::<Range>(), Ok(range), "{s}");
+            }
+
+            if let Ok(board) = board_from_str(&s) {
+                assert!((3..=5).contains(&board.len()), "{s}");
+            }
+
+            let _ = flop_from_str(&s);
+            let _ = card_from_str(&s);
+        }
+    }
 }
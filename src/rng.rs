@@ -0,0 +1,122 @@
+/// The golden ratio constant used as the increment of SplitMix64.
+const GOLDEN_GAMMA: u64 = 0x9e3779b97f4a7c15;
+
+/// Pseudo-random number generator used by all stochastic features of the crate.
+///
+/// This is a SplitMix64 generator: it is small, fast, and fully determined by its seed, so any
+/// sampled result can be reproduced from the seed returned by [`seed`]. The stochastic features
+/// ([`SolverAlgorithm::ChanceSamplingMccfr`], [`randomize_regrets`], [`solve_averaged`],
+/// [`PreflopConfig::seed`], and the agents of the `acpc` and `slumbot` features) all take a single
+/// `u64` seed and derive their random numbers through this type.
+///
+/// Independent streams are derived with [`split`], which depends only on the seed and the stream
+/// index, not on the numbers drawn so far. This makes the results independent of the order in
+/// which the streams are consumed, e.g., by parallel threads.
+///
+/// This generator is not cryptographically secure.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::SolverRng;
+///
+/// let mut rng = SolverRng::new(42);
+/// let x = rng.next_u64();
+/// assert_eq!(SolverRng::new(42).next_u64(), x);
+///
+/// // streams do not depend on the numbers drawn from the parent
+/// assert_eq!(rng.split(1), SolverRng::new(42).split(1));
+/// assert_ne!(rng.split(1), rng.split(2));
+/// ```
+///
+/// [`seed`]: #method.seed
+/// [`split`]: #method.split
+/// [`SolverAlgorithm::ChanceSamplingMccfr`]: crate::SolverAlgorithm::ChanceSamplingMccfr
+/// [`randomize_regrets`]: crate::randomize_regrets
+/// [`solve_averaged`]: crate::solve_averaged
+/// [`PreflopConfig::seed`]: crate::PreflopConfig::seed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverRng {
+    seed: u64,
+    state: u64,
+}
+
+impl SolverRng {
+    /// Creates a new generator from the given seed.
+    #[inline]
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Returns the seed of the generator.
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the generator of the stream `stream`.
+    ///
+    /// The returned generator depends only on the seed of `self` and `stream`, and different
+    /// streams produce (practically) independent sequences. Streams can be split further.
+    #[inline]
+    pub fn split(&self, stream: u64) -> Self {
+        let mut rng = Self::new(self.seed ^ stream.wrapping_mul(GOLDEN_GAMMA));
+        Self::new(rng.next_u64())
+    }
+
+    /// Returns a random `u64` value.
+    #[inline]
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random value in the range `(0.0, 1.0]`.
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        ((self.next_u64() >> 40) + 1) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a random value in the range `0..n`. Panics if `n` is zero.
+    ///
+    /// The value is computed by the modulo operation, so the bias is negligible only when `n` is
+    /// much smaller than 2^64 (which is always the case in this crate).
+    #[inline]
+    pub fn next_below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "`n` must be positive");
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solver_rng() {
+        // reference values of SplitMix64
+        let mut rng = SolverRng::new(1234567);
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+
+        let mut rng = SolverRng::new(0);
+        for _ in 0..1000 {
+            let x = rng.next_f32();
+            assert!(0.0 < x && x <= 1.0);
+            assert!(rng.next_below(7) < 7);
+        }
+
+        // splitting depends only on the seed
+        let parent = SolverRng::new(99);
+        let mut drawn = parent.clone();
+        drawn.next_u64();
+        assert_eq!(parent.split(3), drawn.split(3));
+
+        let mut a = parent.split(0);
+        let mut b = parent.split(1);
+        let (a, b): (Vec<_>, Vec<_>) = (0..8).map(|_| (a.next_u64(), b.next_u64())).unzip();
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,275 @@
+//! JSON Schema documents of the JSON formats exported by the crate.
+
+/// A [JSON Schema] (draft 2020-12) document describing a JSON format exported by the crate.
+///
+/// The documents are embedded from the `schemas` directory of the crate, so that consumers in
+/// other languages can validate the exported files or generate types from them. A change of an
+/// exported format that is not backward compatible is accompanied by a change of its schema.
+///
+/// [JSON Schema]: https://json-schema.org/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonSchema {
+    /// The name of the format (e.g., `study_report`), which is also the file name of the schema
+    /// without the `.schema.json` extension.
+    pub name: &'static str,
+
+    /// The function or method that writes the format.
+    pub producer: &'static str,
+
+    /// The schema document.
+    pub document: &'static str,
+}
+
+const SCHEMAS: [JsonSchema; 4] = [
+    JsonSchema {
+        name: "openspiel_policy",
+        producer: "OpenSpielPolicy::to_json",
+        document: include_str!("../schemas/openspiel_policy.schema.json"),
+    },
+    JsonSchema {
+        name: "solve_stream",
+        producer: "SolveStreamer",
+        document: include_str!("../schemas/solve_stream.schema.json"),
+    },
+    JsonSchema {
+        name: "strategy_export",
+        producer: "PostFlopGame::export_strategy",
+        document: include_str!("../schemas/strategy_export.schema.json"),
+    },
+    JsonSchema {
+        name: "study_report",
+        producer: "StudyReport::to_json",
+        document: include_str!("../schemas/study_report.schema.json"),
+    },
+];
+
+/// Returns the JSON Schema documents of all JSON formats exported by the crate, sorted by name.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// for schema in schemas() {
+///     // e.g., write `{name}.schema.json` for a code generator
+///     assert!(schema.document.contains("\"$schema\""));
+/// }
+/// ```
+#[inline]
+pub fn schemas() -> &'static [JsonSchema] {
+    &SCHEMAS
+}
+
+/// Returns the JSON Schema document of the exported format with the given name (see
+/// [`schemas`]), or `None` if there is no such format.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let report = schema("study_report").unwrap();
+/// assert_eq!(report.producer, "StudyReport::to_json");
+/// assert!(schema("unknown").is_none());
+/// ```
+#[inline]
+pub fn schema(name: &str) -> Option<&'static JsonSchema> {
+    SCHEMAS.iter().find(|schema| schema.name == name)
+}
+
+/// Validates `value` against the subset of JSON Schema used by the embedded documents.
+#[cfg(test)]
+pub(crate) fn validate_json(
+    name: &str,
+    value: &crate::json::JsonValue,
+) -> Result<(), crate::Error> {
+    let document = crate::json::JsonValue::parse(schema(name).unwrap().document)?;
+    validate_recursive(&document, &document, value, "$")
+}
+
+#[cfg(test)]
+fn validate_recursive(
+    root: &crate::json::JsonValue,
+    schema: &crate::json::JsonValue,
+    value: &crate::json::JsonValue,
+    path: &str,
+) -> Result<(), crate::Error> {
+    use crate::json::JsonValue;
+
+    if let Some(reference) = schema.get("$ref").and_then(JsonValue::as_str) {
+        let name = reference.strip_prefix("#/$defs/").unwrap();
+        let target = root.get("$defs").and_then(|defs| defs.get(name)).unwrap();
+        return validate_recursive(root, target, value, path);
+    }
+
+    if let Some(types) = schema.get("type") {
+        let type_name = match value {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Number(x) if x.fract() == 0.0 => "integer",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        };
+        let matches = |t: &JsonValue| {
+            let t = t.as_str().unwrap();
+            t == type_name || (t == "number" && type_name == "integer")
+        };
+        let is_valid = match types {
+            JsonValue::Array(types) => types.iter().any(matches),
+            t => matches(t),
+        };
+        if !is_valid {
+            return Err(crate::Error::Verification(format!(
+                "{path}: unexpected type {type_name}"
+            )));
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            return Err(crate::Error::Verification(format!(
+                "{path}: expected {expected:?}"
+            )));
+        }
+    }
+
+    if let Some(candidates) = schema.get("enum").and_then(JsonValue::as_array) {
+        if !candidates.contains(value) {
+            return Err(crate::Error::Verification(format!(
+                "{path}: {value:?} is not one of the enumerated values"
+            )));
+        }
+    }
+
+    if let Some(branches) = schema.get("anyOf").and_then(JsonValue::as_array) {
+        if !branches
+            .iter()
+            .any(|branch| validate_recursive(root, branch, value, path).is_ok())
+        {
+            return Err(crate::Error::Verification(format!(
+                "{path}: no branch of anyOf matches"
+            )));
+        }
+    }
+
+    if let (Some(minimum), Some(x)) = (schema.get("minimum"), value.as_f64()) {
+        if x < minimum.as_f64().unwrap() {
+            return Err(crate::Error::Verification(format!(
+                "{path}: {x} is less than the minimum"
+            )));
+        }
+    }
+
+    if let Some(array) = value.as_array() {
+        let len = array.len() as f64;
+        if schema
+            .get("minItems")
+            .is_some_and(|n| len < n.as_f64().unwrap())
+            || schema
+                .get("maxItems")
+                .is_some_and(|n| len > n.as_f64().unwrap())
+        {
+            return Err(crate::Error::Verification(format!(
+                "{path}: invalid number of items"
+            )));
+        }
+
+        let prefix = schema.get("prefixItems").and_then(JsonValue::as_array);
+        for (i, item) in array.iter().enumerate() {
+            let item_schema = match prefix.and_then(|prefix| prefix.get(i)) {
+                Some(item_schema) => Some(item_schema),
+                None => schema.get("items"),
+            };
+            if let Some(item_schema) = item_schema {
+                validate_recursive(root, item_schema, item, &format!("{path}[{i}]"))?;
+            }
+        }
+    }
+
+    if let JsonValue::Object(map) = value {
+        if let Some(required) = schema.get("required").and_then(JsonValue::as_array) {
+            for key in required {
+                let key = key.as_str().unwrap();
+                if !map.contains_key(key) {
+                    return Err(crate::Error::Verification(format!(
+                        "{path}: missing member {key}"
+                    )));
+                }
+            }
+        }
+
+        let properties = schema.get("properties");
+        for (key, member) in map {
+            let member_path = format!("{path}.{key}");
+            match (
+                properties.and_then(|p| p.get(key)),
+                schema.get("additionalProperties"),
+            ) {
+                (Some(member_schema), _) => {
+                    validate_recursive(root, member_schema, member, &member_path)?
+                }
+                (None, Some(JsonValue::Bool(false))) => {
+                    return Err(crate::Error::Verification(format!(
+                        "{member_path}: unexpected member"
+                    )));
+                }
+                (None, Some(member_schema @ JsonValue::Object(_))) => {
+                    validate_recursive(root, member_schema, member, &member_path)?
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::*;
+
+    #[test]
+    fn schema_documents() {
+        let names = schemas().iter().map(|s| s.name).collect::<Vec<_>>();
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        assert_eq!(names, sorted);
+
+        for schema in schemas() {
+            let document = JsonValue::parse(schema.document).unwrap();
+            assert_eq!(
+                document.get("$schema").and_then(JsonValue::as_str),
+                Some("https://json-schema.org/draft/2020-12/schema")
+            );
+            assert!(document.get("title").is_some());
+            assert!(document.get("description").is_some());
+        }
+    }
+
+    #[test]
+    fn schema_validation() {
+        let policy = JsonValue::parse(r#"{"0: As":[[0,0.25],[1,0.75]],"1: Kh":[]}"#).unwrap();
+        assert!(validate_json("openspiel_policy", &policy).is_ok());
+
+        let invalid = JsonValue::parse(r#"{"0: As":[[0.5,0.25]]}"#).unwrap();
+        assert!(validate_json("openspiel_policy", &invalid).is_err());
+
+        let invalid = JsonValue::parse(r#"{"0: As":[[0]]}"#).unwrap();
+        assert!(validate_json("openspiel_policy", &invalid).is_err());
+
+        let message = r#"{"version":1,"type":"snapshot","iteration":5,"exploitability":null,
+            "nodes":[{"history":[0],"player":1,"actions":["Check"],"hands":["AsKs"],
+            "strategy":[[1]],"current_strategy":null}]}"#;
+        let message = JsonValue::parse(message).unwrap();
+        assert!(validate_json("solve_stream", &message).is_ok());
+
+        let invalid = r#"{"version":2,"type":"snapshot","iteration":5,"exploitability":null,
+            "nodes":[]}"#;
+        let invalid = JsonValue::parse(invalid).unwrap();
+        assert!(validate_json("solve_stream", &invalid).is_err());
+
+        let invalid = JsonValue::parse(r#"[{"node":0}]"#).unwrap();
+        assert!(validate_json("strategy_export", &invalid).is_err());
+    }
+}
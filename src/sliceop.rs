@@ -1,16 +1,94 @@
 use crate::utility::*;
 use std::mem::MaybeUninit;
 
+#[cfg(feature = "simd")]
+use wide::*;
+
+/// Applies `vector` to each chunk of 8 elements and `scalar` to the remaining elements.
+#[cfg(feature = "simd")]
+#[inline]
+fn simd_apply(
+    lhs: &mut [f32],
+    rhs: &[f32],
+    vector: impl Fn(f32x8, f32x8) -> f32x8,
+    scalar: impl Fn(f32, f32) -> f32,
+) {
+    let len = lhs.len().min(rhs.len());
+    let (lhs_chunks, lhs_rest) = lhs[..len].as_chunks_mut::<8>();
+    let (rhs_chunks, rhs_rest) = rhs[..len].as_chunks::<8>();
+    lhs_chunks.iter_mut().zip(rhs_chunks).for_each(|(l, r)| {
+        *l = vector(f32x8::new(*l), f32x8::new(*r)).to_array();
+    });
+    lhs_rest
+        .iter_mut()
+        .zip(rhs_rest)
+        .for_each(|(l, r)| *l = scalar(*l, *r));
+}
+
+/// Same as [`simd_apply`] but with two source slices.
+#[cfg(feature = "simd")]
+#[inline]
+fn simd_apply3(
+    dst: &mut [f32],
+    src1: &[f32],
+    src2: &[f32],
+    vector: impl Fn(f32x8, f32x8, f32x8) -> f32x8,
+    scalar: impl Fn(f32, f32, f32) -> f32,
+) {
+    let len = dst.len().min(src1.len()).min(src2.len());
+    let (dst_chunks, dst_rest) = dst[..len].as_chunks_mut::<8>();
+    let (src1_chunks, src1_rest) = src1[..len].as_chunks::<8>();
+    let (src2_chunks, src2_rest) = src2[..len].as_chunks::<8>();
+    dst_chunks
+        .iter_mut()
+        .zip(src1_chunks.iter().zip(src2_chunks))
+        .for_each(|(d, (s1, s2))| {
+            let (s1, s2) = (f32x8::new(*s1), f32x8::new(*s2));
+            *d = vector(f32x8::new(*d), s1, s2).to_array();
+        });
+    dst_rest
+        .iter_mut()
+        .zip(src1_rest.iter().zip(src2_rest))
+        .for_each(|(d, (s1, s2))| *d = scalar(*d, *s1, *s2));
+}
+
+#[cfg(all(feature = "solver", not(feature = "simd")))]
 #[inline]
 pub(crate) fn sub_slice(lhs: &mut [f32], rhs: &[f32]) {
     lhs.iter_mut().zip(rhs).for_each(|(l, r)| *l -= *r);
 }
 
+#[cfg(all(feature = "solver", feature = "simd"))]
+#[inline]
+pub(crate) fn sub_slice(lhs: &mut [f32], rhs: &[f32]) {
+    simd_apply(lhs, rhs, |l, r| l - r, |l, r| l - r);
+}
+
+#[cfg(not(feature = "simd"))]
 #[inline]
 pub(crate) fn mul_slice(lhs: &mut [f32], rhs: &[f32]) {
     lhs.iter_mut().zip(rhs).for_each(|(l, r)| *l *= *r);
 }
 
+#[cfg(feature = "simd")]
+#[inline]
+pub(crate) fn mul_slice(lhs: &mut [f32], rhs: &[f32]) {
+    simd_apply(lhs, rhs, |l, r| l * r, |l, r| l * r);
+}
+
+#[cfg(not(feature = "simd"))]
+#[inline]
+pub(crate) fn add_slice(lhs: &mut [f32], rhs: &[f32]) {
+    lhs.iter_mut().zip(rhs).for_each(|(l, r)| *l += *r);
+}
+
+#[cfg(feature = "simd")]
+#[inline]
+pub(crate) fn add_slice(lhs: &mut [f32], rhs: &[f32]) {
+    simd_apply(lhs, rhs, |l, r| l + r, |l, r| l + r);
+}
+
+#[cfg(not(feature = "simd"))]
 #[inline]
 pub(crate) fn div_slice(lhs: &mut [f32], rhs: &[f32], default: f32) {
     lhs.iter_mut()
@@ -18,6 +96,45 @@ pub(crate) fn div_slice(lhs: &mut [f32], rhs: &[f32], default: f32) {
         .for_each(|(l, r)| *l = if is_zero(*r) { default } else { *l / *r });
 }
 
+#[cfg(feature = "simd")]
+#[inline]
+pub(crate) fn div_slice(lhs: &mut [f32], rhs: &[f32], default: f32) {
+    let default_x8 = f32x8::splat(default);
+    simd_apply(
+        lhs,
+        rhs,
+        // `is_zero` tests the bits, so `-0.0` is not regarded as zero
+        |l, r| {
+            let zero: f32x8 = bytemuck::cast(bytemuck::cast::<_, u32x8>(r).cmp_eq(u32x8::splat(0)));
+            zero.blend(default_x8, l / r)
+        },
+        |l, r| if is_zero(r) { default } else { l / r },
+    );
+}
+
+#[cfg(all(feature = "solver", not(feature = "simd")))]
+#[inline]
+pub(crate) fn positive_part_uninit(dst: &mut [MaybeUninit<f32>], src: &[f32]) {
+    dst.iter_mut().zip(src).for_each(|(d, s)| {
+        d.write(max(*s, 0.0));
+    });
+}
+
+#[cfg(all(feature = "solver", feature = "simd"))]
+#[inline]
+pub(crate) fn positive_part_uninit(dst: &mut [MaybeUninit<f32>], src: &[f32]) {
+    dst.iter_mut().for_each(|d| {
+        d.write(0.0);
+    });
+    let dst = unsafe { &mut *(dst as *mut _ as *mut [f32]) };
+    simd_apply(
+        dst,
+        src,
+        |_, s| s.cmp_gt(f32x8::ZERO).blend(s, f32x8::ZERO),
+        |_, s| max(s, 0.0),
+    );
+}
+
 #[inline]
 pub(crate) fn div_slice_uninit(
     dst: &mut [MaybeUninit<f32>],
@@ -47,9 +164,7 @@ pub(crate) fn sum_slices_uninit<'a>(dst: &'a mut [MaybeUninit<f32>], src: &[f32]
     });
     let dst = unsafe { &mut *(dst as *mut _ as *mut [f32]) };
     src[len..].chunks_exact(len).for_each(|s| {
-        dst.iter_mut().zip(s).for_each(|(d, s)| {
-            *d += *s;
-        });
+        add_slice(dst, s);
     });
     dst
 }
@@ -89,15 +204,33 @@ pub(crate) fn fma_slices_uninit<'a>(
         .chunks_exact(len)
         .zip(src2[len..].chunks_exact(len))
         .for_each(|(s1, s2)| {
-            dst.iter_mut()
-                .zip(s1.iter().zip(s2))
-                .for_each(|(d, (s1, s2))| {
-                    *d += *s1 * *s2;
-                });
+            fma_slice(dst, s1, s2);
         });
     dst
 }
 
+#[cfg(not(feature = "simd"))]
+#[inline]
+fn fma_slice(dst: &mut [f32], src1: &[f32], src2: &[f32]) {
+    dst.iter_mut()
+        .zip(src1.iter().zip(src2))
+        .for_each(|(d, (s1, s2))| {
+            *d += *s1 * *s2;
+        });
+}
+
+#[cfg(feature = "simd")]
+#[inline]
+fn fma_slice(dst: &mut [f32], src1: &[f32], src2: &[f32]) {
+    simd_apply3(
+        dst,
+        src1,
+        src2,
+        |d, s1, s2| d + s1 * s2,
+        |d, s1, s2| d + s1 * s2,
+    );
+}
+
 #[inline]
 pub(crate) fn max_slices_uninit<'a>(dst: &'a mut [MaybeUninit<f32>], src: &[f32]) -> &'a mut [f32] {
     let len = dst.len();
@@ -106,13 +239,23 @@ pub(crate) fn max_slices_uninit<'a>(dst: &'a mut [MaybeUninit<f32>], src: &[f32]
     });
     let dst = unsafe { &mut *(dst as *mut _ as *mut [f32]) };
     src[len..].chunks_exact(len).for_each(|s| {
-        dst.iter_mut().zip(s).for_each(|(d, s)| {
-            *d = max(*d, *s);
-        });
+        max_slice(dst, s);
     });
     dst
 }
 
+#[cfg(not(feature = "simd"))]
+#[inline]
+fn max_slice(lhs: &mut [f32], rhs: &[f32]) {
+    lhs.iter_mut().zip(rhs).for_each(|(l, r)| *l = max(*l, *r));
+}
+
+#[cfg(feature = "simd")]
+#[inline]
+fn max_slice(lhs: &mut [f32], rhs: &[f32]) {
+    simd_apply(lhs, rhs, |l, r| l.cmp_gt(r).blend(l, r), max);
+}
+
 #[inline]
 pub(crate) fn max_fma_slices_uninit<'a>(
     dst: &'a mut [MaybeUninit<f32>],
@@ -245,3 +388,62 @@ pub(crate) fn row<T>(slice: &[T], index: usize, row_size: usize) -> &[T] {
 pub(crate) fn row_mut<T>(slice: &mut [T], index: usize, row_size: usize) -> &mut [T] {
     &mut slice[index * row_size..(index + 1) * row_size]
 }
+
+#[cfg(all(test, feature = "solver"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_operations() {
+        // the length is not a multiple of the vector width
+        let len = 13;
+        let x = (0..2 * len).map(|i| i as f32 - 9.5).collect::<Vec<_>>();
+        let mut y = (0..len).map(|i| (i % 4) as f32).collect::<Vec<_>>();
+        y[2] = -0.0;
+
+        let mut z = x[..len].to_vec();
+        sub_slice(&mut z, &y);
+        assert!(z.iter().enumerate().all(|(i, &v)| v == x[i] - y[i]));
+
+        let mut z = x[..len].to_vec();
+        mul_slice(&mut z, &y);
+        assert!(z.iter().enumerate().all(|(i, &v)| v == x[i] * y[i]));
+
+        let mut z = x[..len].to_vec();
+        div_slice(&mut z, &y, 0.5);
+        assert!(z.iter().enumerate().all(|(i, &v)| {
+            v == if y[i].to_bits() == 0 {
+                0.5
+            } else {
+                x[i] / y[i]
+            }
+        }));
+
+        let mut z = Vec::with_capacity(2 * len);
+        positive_part_uninit(z.spare_capacity_mut(), &x);
+        unsafe { z.set_len(2 * len) };
+        assert!(z.iter().enumerate().all(|(i, &v)| v == x[i].max(0.0)));
+
+        let mut z = Vec::with_capacity(len);
+        sum_slices_uninit(z.spare_capacity_mut(), &x);
+        unsafe { z.set_len(len) };
+        assert!(z.iter().enumerate().all(|(i, &v)| v == x[i] + x[i + len]));
+
+        let mut z = Vec::with_capacity(len);
+        max_slices_uninit(z.spare_capacity_mut(), &x);
+        unsafe { z.set_len(len) };
+        assert!(z
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == x[i].max(x[i + len])));
+
+        let w = [y.clone(), y.clone()].concat();
+        let mut z = Vec::with_capacity(len);
+        fma_slices_uninit(z.spare_capacity_mut(), &x, &w);
+        unsafe { z.set_len(len) };
+        assert!(z
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v == x[i] * y[i] + x[i + len] * y[i]));
+    }
+}
@@ -0,0 +1,527 @@
+//! Adapter for Slumbot-style JSON bot protocols.
+//!
+//! The adapter receives a game state such as
+//! `{"action": "b200c/kb300", "client_pos": 1, "hole_cards": ["Ac", "Kd"], "board": ["2c", "6d", "Th"]}`,
+//! maps it onto a solved postflop blueprint, and returns an action sampled from the blueprint
+//! strategy. The transport (e.g., HTTP requests to the server) is left to the user.
+//!
+//! In the action string, `k` is a check, `c` is a call, `f` is a fold, `bN` is a bet or a raise
+//! making the player's total commitment on the current street `N` (including the blinds on the
+//! preflop), and `/` separates the streets. Position 0 (the big blind) is regarded as OOP and
+//! position 1 (the small blind) as IP.
+
+use crate::blueprint::*;
+use crate::card::*;
+use crate::error::*;
+use crate::game::*;
+use crate::json::*;
+use crate::range::*;
+
+/// An action in the Slumbot protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlumbotAction {
+    /// Fold action.
+    Fold,
+
+    /// Check action.
+    Check,
+
+    /// Call action.
+    Call,
+
+    /// Bet or raise action making the player's total commitment on the current street the
+    /// specified amount.
+    Bet(i32),
+}
+
+/// Rules of the no-limit game played on the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlumbotGameDef {
+    /// The stack of each player at the beginning of the hand.
+    pub stack: i32,
+
+    /// The blinds posted by position 0 and position 1, respectively.
+    pub blinds: [i32; 2],
+}
+
+impl Default for SlumbotGameDef {
+    /// Returns the rules of Slumbot (200 big blinds deep with 50/100 blinds).
+    #[inline]
+    fn default() -> Self {
+        Self {
+            stack: 20000,
+            blinds: [100, 50],
+        }
+    }
+}
+
+/// A game state received from the server.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SlumbotState {
+    /// The session token, if any.
+    pub token: Option<String>,
+
+    /// The actions of each street.
+    pub action: Vec<Vec<SlumbotAction>>,
+
+    /// The position of the client.
+    pub client_pos: usize,
+
+    /// The hole cards of the client.
+    pub hole_cards: (Card, Card),
+
+    /// The board cards.
+    pub board: Vec<Card>,
+
+    /// The winnings of the client if the hand is over.
+    pub winnings: Option<f64>,
+}
+
+/// Summary of the betting computed from a [`SlumbotState`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlumbotBettingState {
+    /// The current street (0: preflop, 1: flop, 2: turn, 3: river).
+    pub street: usize,
+
+    /// The total commitment of each position in the hand.
+    pub committed: [i32; 2],
+
+    /// The commitment of each position on the current street.
+    pub street_committed: [i32; 2],
+
+    /// The position to act, or `None` if the hand is over.
+    pub to_act: Option<usize>,
+
+    /// The size of the last bet or raise on the current street (used for the minimum raise).
+    pub last_bet_size: i32,
+}
+
+impl SlumbotAction {
+    /// Returns the action in the protocol notation (e.g., `b300`).
+    pub fn to_incr(self) -> String {
+        match self {
+            SlumbotAction::Fold => "f".to_string(),
+            SlumbotAction::Check => "k".to_string(),
+            SlumbotAction::Call => "c".to_string(),
+            SlumbotAction::Bet(amount) => format!("b{amount}"),
+        }
+    }
+}
+
+impl SlumbotState {
+    /// Parses a JSON game state.
+    ///
+    /// `action`, `client_pos`, and `hole_cards` are required; `token`, `board`, and `winnings`
+    /// are optional.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let value = JsonValue::parse(json)?;
+        if !matches!(value, JsonValue::Object(_)) {
+            return Err(Error::invalid_data("Game state must be a JSON object"));
+        }
+
+        let token = value
+            .get("token")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string);
+
+        let action = value
+            .get("action")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| Error::invalid_data("Missing field: action"))?;
+        let action = parse_action(action)?;
+
+        let client_pos = value
+            .get("client_pos")
+            .and_then(JsonValue::as_f64)
+            .filter(|&p| p == 0.0 || p == 1.0)
+            .ok_or_else(|| Error::invalid_data("Missing or invalid field: client_pos"))?
+            as usize;
+
+        let hole_cards = parse_card_array(value.get("hole_cards"))?;
+        if hole_cards.len() != 2 || hole_cards[0] == hole_cards[1] {
+            return Err(Error::invalid_data("Invalid hole cards"));
+        }
+
+        let board = match value.get("board") {
+            None | Some(JsonValue::Null) => Vec::new(),
+            board => parse_card_array(board)?,
+        };
+        if board.len() > 5 || board.iter().any(|c| hole_cards.contains(c)) {
+            return Err(Error::invalid_data("Invalid board"));
+        }
+
+        let winnings = value.get("winnings").and_then(JsonValue::as_f64);
+
+        Ok(Self {
+            token,
+            action,
+            client_pos,
+            hole_cards: (hole_cards[0], hole_cards[1]),
+            board,
+            winnings,
+        })
+    }
+
+    /// Computes the betting state under the given rules.
+    pub fn betting_state(&self, def: &SlumbotGameDef) -> SlumbotBettingState {
+        let mut committed_before = [0; 2];
+        let mut street_committed = def.blinds;
+        let mut to_act = Some(1);
+        let mut last_bet_size = def.blinds[0];
+
+        for (street, actions) in self.action.iter().enumerate() {
+            if street > 0 {
+                street_committed = [0; 2];
+                last_bet_size = def.blinds[0];
+            }
+
+            let first = if street == 0 { 1 } else { 0 };
+            for (i, &action) in actions.iter().enumerate() {
+                let player = (first + i) % 2;
+                match action {
+                    SlumbotAction::Fold => {
+                        to_act = None;
+                        break;
+                    }
+                    SlumbotAction::Check => {}
+                    SlumbotAction::Call => street_committed[player] = street_committed[player ^ 1],
+                    SlumbotAction::Bet(amount) => {
+                        last_bet_size = last_bet_size.max(amount - street_committed[player ^ 1]);
+                        street_committed[player] = amount;
+                    }
+                }
+            }
+
+            if actions.last() == Some(&SlumbotAction::Fold) {
+                break;
+            }
+
+            let is_street_over = actions.len() >= 2 && street_committed[0] == street_committed[1];
+            let committed = [
+                committed_before[0] + street_committed[0],
+                committed_before[1] + street_committed[1],
+            ];
+            let is_all_in = is_street_over && committed[0] >= def.stack;
+
+            to_act = if is_all_in || (is_street_over && street == 3) {
+                None
+            } else if is_street_over {
+                Some(0)
+            } else {
+                Some((first + actions.len()) % 2)
+            };
+
+            if street + 1 < self.action.len() {
+                committed_before = committed;
+            }
+        }
+
+        SlumbotBettingState {
+            street: self.action.len().max(1) - 1,
+            committed: [
+                committed_before[0] + street_committed[0],
+                committed_before[1] + street_committed[1],
+            ],
+            street_committed,
+            to_act,
+            last_bet_size,
+        }
+    }
+
+    /// Returns the JSON response for the given action (e.g., `{"token":"abc","incr":"b300"}`).
+    pub fn response(&self, action: SlumbotAction) -> String {
+        match &self.token {
+            Some(token) => format!(
+                "{{\"token\":\"{}\",\"incr\":\"{}\"}}",
+                token.replace('\\', "\\\\").replace('"', "\\\""),
+                action.to_incr()
+            ),
+            None => format!("{{\"incr\":\"{}\"}}", action.to_incr()),
+        }
+    }
+}
+
+/// Adapter that plays using solved postflop blueprints.
+///
+/// Each blueprint is a solved [`PostFlopGame`] rooted at the flop. The chips of the actual hand
+/// are translated onto the blueprint by the ratio of the blueprint's starting pot to the actual
+/// pot after the preflop betting, so a blueprint can be used at different pot sizes and stack
+/// depths. Off-tree bets of the opponent are mapped to the bet size whose resulting commitment is
+/// closest to the translated amount, and an all-in of the opponent is mapped to the all-in action
+/// of the blueprint when available. The sampled action is translated back to the actual chips
+/// and clamped to a legal amount; an all-in of the blueprint is always translated to an actual
+/// all-in.
+pub struct SlumbotAdapter {
+    def: SlumbotGameDef,
+    player: BlueprintPlayer,
+}
+
+impl SlumbotAdapter {
+    /// Creates a new adapter.
+    pub fn new(def: SlumbotGameDef, seed: u64) -> Self {
+        Self {
+            def,
+            player: BlueprintPlayer::new(def.stack, seed),
+        }
+    }
+
+    /// Returns the seed of the sampling of the actions.
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.player.seed()
+    }
+
+    /// Adds a solved blueprint. Returns an error if the game is not solved or not rooted at the
+    /// flop.
+    pub fn add_blueprint(&mut self, game: PostFlopGame) -> Result<(), Error> {
+        self.player.add_blueprint(game)
+    }
+
+    /// Returns the action sampled from the blueprint in the given state.
+    ///
+    /// Returns an error if it is not the client's turn or no blueprint is applicable (e.g., on
+    /// the preflop, on an unknown flop, or with a hand outside of the blueprint range).
+    pub fn act(&mut self, state: &SlumbotState) -> Result<SlumbotAction, Error> {
+        let betting = state.betting_state(&self.def);
+        if betting.to_act != Some(state.client_pos) {
+            return Err(Error::Tree("Not the client's turn".to_string()));
+        }
+
+        if betting.street == 0 {
+            return Err(Error::Config("No blueprint for the preflop".to_string()));
+        }
+
+        let preflop_committed = SlumbotState {
+            action: state.action[..1].to_vec(),
+            ..Default::default()
+        }
+        .betting_state(&self.def)
+        .committed[0];
+
+        // convert the bets into the total commitments in the hand
+        let mut committed_before = preflop_committed;
+        let streets = state.action[1..]
+            .iter()
+            .map(|actions| {
+                let street = actions
+                    .iter()
+                    .map(|&action| match action {
+                        SlumbotAction::Fold => HandAction::Fold,
+                        SlumbotAction::Check => HandAction::Check,
+                        SlumbotAction::Call => HandAction::Call,
+                        SlumbotAction::Bet(amount) => HandAction::Raise(committed_before + amount),
+                    })
+                    .collect::<Vec<_>>();
+                committed_before += actions
+                    .iter()
+                    .filter_map(|&action| match action {
+                        SlumbotAction::Bet(amount) => Some(amount),
+                        _ => None,
+                    })
+                    .max()
+                    .unwrap_or(0);
+                street
+            })
+            .collect::<Vec<_>>();
+
+        let action =
+            self.player
+                .action(state.hole_cards, &state.board, preflop_committed, &streets)?;
+
+        let position = state.client_pos;
+        let action = legalize(
+            action,
+            betting.committed,
+            betting.last_bet_size,
+            self.def.stack,
+            position,
+        );
+
+        let committed_before = betting.committed[position] - betting.street_committed[position];
+        Ok(match action {
+            HandAction::Fold => SlumbotAction::Fold,
+            HandAction::Check => SlumbotAction::Check,
+            HandAction::Call => SlumbotAction::Call,
+            HandAction::Raise(amount) => SlumbotAction::Bet(amount - committed_before),
+        })
+    }
+
+    /// Parses a JSON game state and returns the JSON response of the sampled action.
+    pub fn act_json(&mut self, json: &str) -> Result<String, Error> {
+        let state = SlumbotState::from_json(json)?;
+        let action = self.act(&state)?;
+        Ok(state.response(action))
+    }
+}
+
+fn parse_action(s: &str) -> Result<Vec<Vec<SlumbotAction>>, Error> {
+    let mut streets = vec![Vec::new()];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            'f' => streets.last_mut().unwrap().push(SlumbotAction::Fold),
+            'k' => streets.last_mut().unwrap().push(SlumbotAction::Check),
+            'c' => streets.last_mut().unwrap().push(SlumbotAction::Call),
+            'b' => {
+                let mut digits = String::new();
+                while let Some(&d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                    digits.push(d);
+                    chars.next();
+                }
+                let amount = digits
+                    .parse::<i32>()
+                    .map_err(|_| Error::invalid_data(format!("Invalid bet amount: {s}")))?;
+                streets.last_mut().unwrap().push(SlumbotAction::Bet(amount));
+            }
+            '/' => streets.push(Vec::new()),
+            _ => return Err(Error::invalid_data(format!("Invalid action string: {s}"))),
+        }
+    }
+
+    if streets.len() > 4 {
+        return Err(Error::invalid_data(format!("Too many streets: {s}")));
+    }
+
+    Ok(streets)
+}
+
+fn parse_card_array(value: Option<&JsonValue>) -> Result<Vec<Card>, Error> {
+    value
+        .and_then(JsonValue::as_array)
+        .ok_or_else(|| Error::invalid_data("Expected an array of cards"))?
+        .iter()
+        .map(|card| {
+            card_from_str(
+                card.as_str()
+                    .ok_or_else(|| Error::invalid_data("Expected a card string"))?,
+            )
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "solver"))]
+mod tests {
+    use super::*;
+    use crate::action_tree::*;
+    use crate::bet_size::*;
+    use crate::solver::*;
+
+    #[test]
+    fn slumbot_state() {
+        let state = SlumbotState::from_json(
+            r#"{"token": "abc", "action": "b300c/kb200", "client_pos": 1,
+                "hole_cards": ["Ac", "Kd"], "board": ["2c", "6d", "Th"]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            state.action,
+            vec![
+                vec![SlumbotAction::Bet(300), SlumbotAction::Call],
+                vec![SlumbotAction::Check, SlumbotAction::Bet(200)],
+            ]
+        );
+        assert_eq!(state.client_pos, 1);
+        assert_eq!(state.board, flop_from_str("2c6dTh").unwrap().to_vec());
+
+        let betting = state.betting_state(&SlumbotGameDef::default());
+        assert_eq!(betting.street, 1);
+        assert_eq!(betting.committed, [300, 500]);
+        assert_eq!(betting.street_committed, [0, 200]);
+        assert_eq!(betting.to_act, Some(0));
+        assert_eq!(betting.last_bet_size, 200);
+        assert_eq!(
+            state.response(SlumbotAction::Bet(600)),
+            r#"{"token":"abc","incr":"b600"}"#
+        );
+
+        // the big blind has the option after a limp
+        let state = SlumbotState::from_json(
+            r#"{"action": "c", "client_pos": 0, "hole_cards": ["Ac", "Kd"]}"#,
+        )
+        .unwrap();
+        let betting = state.betting_state(&SlumbotGameDef::default());
+        assert_eq!(betting.to_act, Some(0));
+        assert_eq!(betting.committed, [100, 100]);
+
+        // street is over
+        let state = SlumbotState::from_json(
+            r#"{"action": "ck/", "client_pos": 0, "hole_cards": ["Ac", "Kd"], "board": ["2c", "6d", "Th"]}"#,
+        )
+        .unwrap();
+        let betting = state.betting_state(&SlumbotGameDef::default());
+        assert_eq!((betting.street, betting.to_act), (1, Some(0)));
+
+        assert!(SlumbotState::from_json(
+            r#"{"action": "x", "client_pos": 0, "hole_cards": ["Ac", "Kd"]}"#
+        )
+        .is_err());
+        assert!(SlumbotState::from_json(
+            r#"{"action": "", "client_pos": 2, "hole_cards": ["Ac", "Kd"]}"#
+        )
+        .is_err());
+        assert!(SlumbotState::from_json(
+            r#"{"action": "", "client_pos": 0, "hole_cards": ["Ac"]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn slumbot_adapter() {
+        let card_config = CardConfig {
+            range: ["QQ+,AK".parse().unwrap(), "JJ+,AQs+".parse().unwrap()],
+            flop: flop_from_str("2c6dTh").unwrap(),
+            ..Default::default()
+        };
+
+        // 100bb blueprint used for a 200bb game
+        let bet_sizes = BetSizeOptions::try_from(("50%", "")).unwrap();
+        let tree_config = TreeConfig {
+            starting_pot: 600,
+            effective_stack: 9700,
+            flop_bet_sizes: [bet_sizes.clone(), bet_sizes],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 10, 0.0, false);
+
+        let mut adapter = SlumbotAdapter::new(SlumbotGameDef::default(), 0);
+        adapter.add_blueprint(game).unwrap();
+
+        let json = |action: &str, pos: usize, hole: &str| {
+            format!(
+                r#"{{"action": "{action}", "client_pos": {pos}, "hole_cards": {hole}, "board": ["Th", "6d", "2c"]}}"#
+            )
+        };
+
+        // preflop and not our turn
+        assert!(adapter.act_json(&json("", 1, r#"["As", "Ah"]"#)).is_err());
+        assert!(adapter
+            .act_json(&json("b300c/", 1, r#"["As", "Ah"]"#))
+            .is_err());
+
+        // facing an off-tree bet of 80% pot, which is translated to the 50% pot bet
+        let state = SlumbotState::from_json(&json("b300c/b480", 1, r#"["As", "Ah"]"#)).unwrap();
+        match adapter.act(&state).unwrap() {
+            SlumbotAction::Fold | SlumbotAction::Check => panic!("AA should not fold or check"),
+            SlumbotAction::Call => {}
+            SlumbotAction::Bet(amount) => assert!((480 + 480..=19700).contains(&amount)),
+        }
+
+        // facing an all-in, which is translated to the all-in of the blueprint
+        let state = SlumbotState::from_json(&json("b300c/b19700", 1, r#"["As", "Ah"]"#)).unwrap();
+        assert!(matches!(
+            adapter.act(&state).unwrap(),
+            SlumbotAction::Call | SlumbotAction::Fold
+        ));
+
+        // a hand outside of the blueprint range
+        assert!(adapter
+            .act_json(&json("b300c/", 0, r#"["7c", "8c"]"#))
+            .is_err());
+    }
+}
@@ -1,13 +1,22 @@
+use crate::error::*;
+use crate::game::GameOperation;
 use crate::interface::*;
 use crate::mutex_like::*;
+use crate::rng::*;
 use crate::sliceop::*;
 use crate::utility::*;
+use std::fmt;
 use std::io::{self, Write};
 use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "custom-alloc")]
 use crate::alloc::*;
 
+#[cfg(not(feature = "custom-alloc"))]
+use crate::pool::*;
+
 struct DiscountParams {
     alpha_t: f32,
     beta_t: f32,
@@ -15,47 +24,684 @@ struct DiscountParams {
 }
 
 impl DiscountParams {
-    pub fn new(current_iteration: u32) -> Self {
-        // 0, 1, 4, 16, 64, 256, ...
-        let nearest_lower_power_of_4 = match current_iteration {
-            0 => 0,
-            x => 1 << ((x.leading_zeros() ^ 31) & !1),
-        };
+    pub fn new(current_iteration: u32, algorithm: SolverAlgorithm) -> Self {
+        match algorithm {
+            SolverAlgorithm::Dcfr { alpha, beta, gamma } => {
+                // 0, 1, 4, 16, 64, 256, ...
+                let nearest_lower_power_of_4 = match current_iteration {
+                    0 => 0,
+                    x => 1 << ((x.leading_zeros() ^ 31) & !1),
+                };
+
+                let t_alpha = (current_iteration as i32 - 1).max(0) as f64;
+                let t_gamma = (current_iteration - nearest_lower_power_of_4) as f64;
+
+                let pow_alpha = t_alpha.powf(alpha as f64);
+                let pow_beta = t_alpha.powf(beta as f64);
+                let pow_gamma = (t_gamma / (t_gamma + 1.0)).powf(gamma as f64);
+
+                Self {
+                    alpha_t: (pow_alpha / (pow_alpha + 1.0)) as f32,
+                    beta_t: (pow_beta / (pow_beta + 1.0)) as f32,
+                    gamma_t: pow_gamma as f32,
+                }
+            }
+
+            SolverAlgorithm::CfrPlus => Self {
+                alpha_t: 1.0,
+                beta_t: 0.0,
+                gamma_t: Self::linear(current_iteration),
+            },
+
+            SolverAlgorithm::LinearCfr | SolverAlgorithm::ChanceSamplingMccfr { .. } => {
+                let linear = Self::linear(current_iteration);
+                Self {
+                    alpha_t: linear,
+                    beta_t: linear,
+                    gamma_t: linear,
+                }
+            }
+
+            SolverAlgorithm::VanillaCfr => Self {
+                alpha_t: 1.0,
+                beta_t: 1.0,
+                gamma_t: 1.0,
+            },
+        }
+    }
+
+    /// Returns the discount factor `t / (t + 1)`, which weights the contribution of the
+    /// iteration `t` (0-indexed) proportionally to `t + 1`.
+    #[inline]
+    fn linear(current_iteration: u32) -> f32 {
+        let t = current_iteration as f64;
+        (t / (t + 1.0)) as f32
+    }
+}
+
+/// Algorithm of the solver, specified by [`SolverConfig`].
+///
+/// All algorithms are variants of CFR that differ in how the cumulative regrets and the
+/// cumulative strategy are discounted in each iteration; the memory usage is the same.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolverAlgorithm {
+    /// Discounted CFR: in the iteration `t`, the positive cumulative regrets are multiplied by
+    /// `t^alpha / (t^alpha + 1)`, the negative ones by `t^beta / (t^beta + 1)`, and the
+    /// cumulative strategy by `(t / (t + 1))^gamma`. The cumulative strategy is also reset when
+    /// the number of iterations is a power of 4.
+    ///
+    /// The default is `alpha = 1.5`, `beta = 0.0`, and `gamma = 3.0`, which is used by [`solve`].
+    /// The original paper recommends `gamma = 2.0`.
+    Dcfr {
+        /// The exponent of the discount of the positive cumulative regrets.
+        alpha: f32,
+
+        /// The exponent of the discount of the negative cumulative regrets.
+        beta: f32,
+
+        /// The exponent of the discount of the cumulative strategy.
+        gamma: f32,
+    },
+
+    /// CFR+: the negative cumulative regrets are reset to zero, and the strategy is averaged with
+    /// weights proportional to the iteration number.
+    CfrPlus,
+
+    /// Linear CFR: both the regrets and the strategy are weighted proportionally to the
+    /// iteration number.
+    LinearCfr,
+
+    /// Vanilla CFR without any discounting. This converges much slower than the other algorithms
+    /// and is mainly useful as a reference.
+    VanillaCfr,
+
+    /// Chance-sampling Monte Carlo CFR, weighted as [`LinearCfr`].
+    ///
+    /// At each chance node (i.e., the turn and the river card), only one card is sampled for the
+    /// updating player instead of traversing all of them, with the reach probabilities scaled so
+    /// that the updates are unbiased. Only the chance events are sampled: every action of both
+    /// players is traversed, since the strategies of all private hands are updated at once. An
+    /// iteration is much faster than a full traversal on trees with turn or river cards to deal,
+    /// but many more iterations are required and the convergence is noisier. On trees without
+    /// chance nodes (e.g., river spots), an iteration is a full traversal. Note that the memory
+    /// of all nodes is still allocated in advance, so the memory usage is not reduced. The same
+    /// `seed` always produces the same samples.
+    ///
+    /// [`LinearCfr`]: #variant.LinearCfr
+    ChanceSamplingMccfr {
+        /// The seed of the random number generator (see [`SolverRng`]).
+        seed: u64,
+    },
+}
+
+impl Default for SolverAlgorithm {
+    #[inline]
+    fn default() -> Self {
+        Self::Dcfr {
+            alpha: 1.5,
+            beta: 0.0,
+            gamma: 3.0,
+        }
+    }
+}
+
+/// Configuration of the solver, passed to [`solve_with_config`] and [`solve_step_with_config`].
+///
+/// The default configuration is the one used by [`solve`] and [`solve_step`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SolverConfig {
+    /// The algorithm.
+    pub algorithm: SolverAlgorithm,
+
+    /// The order in which the strategies of the players are updated.
+    pub alternation: Alternation,
+
+    /// Whether to check that the cumulative regrets and strategies of all nodes are finite after
+    /// each iteration (default: `false`).
+    ///
+    /// NaN or infinity can be introduced by a buggy [`Game`] implementation or a bad compression
+    /// scale, and it silently spreads to the whole tree in the following iterations. With this
+    /// option, the first offending node is reported as a [`NonFiniteError`] instead. The check
+    /// traverses the whole tree in a single thread, so it slows down the iterations.
+    pub check_finite: bool,
+
+    /// Whether to run the iterations in a single thread so that the children of every node are
+    /// visited in a fixed order (default: `false`).
+    ///
+    /// The solver itself sums the counterfactual values of the children in a fixed order, so
+    /// [`PostFlopGame`] produces bit-identical strategies for identical inputs regardless of this
+    /// option and of the number of threads. This option extends the guarantee to [`Game`]
+    /// implementations whose own parallel reductions depend on the scheduling of the threads, at
+    /// the cost of the parallelism. The samples of [`SolverAlgorithm::ChanceSamplingMccfr`]
+    /// are determined by its seed in either case. The single-thread pool is built once per call
+    /// to the solving functions, which return [`Error::Solver`] if it cannot be built. Has no
+    /// effect without the `rayon` feature.
+    ///
+    /// [`PostFlopGame`]: crate::PostFlopGame
+    pub deterministic: bool,
+
+    /// When the exploitability is computed to check the target during solving (default:
+    /// [`CheckSchedule::Adaptive`]).
+    pub check_schedule: CheckSchedule,
+}
+
+impl SolverConfig {
+    #[inline]
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        self.alternation.check()?;
+        if self.check_schedule == CheckSchedule::Fixed(0) {
+            return Err(Error::Config(format!(
+                "Invalid check schedule: {:?}",
+                self.check_schedule
+            )));
+        }
+        if let SolverAlgorithm::Dcfr { alpha, beta, gamma } = self.algorithm {
+            if !alpha.is_finite() || !beta.is_finite() || !gamma.is_finite() || gamma < 0.0 {
+                return Err(Error::Config(format!(
+                    "Invalid solver algorithm: {:?}",
+                    self.algorithm
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the seed of the samples of the iteration `current_iteration` for `player`, or
+    /// `None` if the algorithm does not sample.
+    #[inline]
+    fn sampling_seed(&self, current_iteration: u32, player: usize) -> Option<u64> {
+        match self.algorithm {
+            SolverAlgorithm::ChanceSamplingMccfr { seed } => {
+                let key = ((current_iteration as u64) << 1) | player as u64;
+                Some(SolverRng::new(seed).split(key).seed())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Schedule of the exploitability checks during solving (see [`SolverConfig::check_schedule`]).
+///
+/// Each check computes the exploitability, which costs about as much as an iteration. Checking
+/// too often wastes this cost, while checking too rarely performs unneeded iterations after the
+/// target is reached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CheckSchedule {
+    /// The exploitability is computed every `n` iterations (`n` must be positive).
+    Fixed(u32),
+
+    /// The number of iterations between the checks adapts to the observed decay of the
+    /// exploitability. This is the default.
+    ///
+    /// The first check is performed after 10 iterations. Then, the decay is modeled as a power
+    /// law of the number of iterations fitted to the latest check and an earlier one (the
+    /// latest within the first half of the iterations, to smooth out the fluctuations of the
+    /// exploitability), and the next check is
+    /// scheduled halfway to the iteration where the target is predicted to be reached, so that
+    /// the checks become sparse while the target is far and dense when it is near. The interval
+    /// is between 2 iterations and a half of the performed iterations (at least 10), so the
+    /// progress is still reported regularly when the target is not reachable. The schedule only
+    /// depends on the computed exploitabilities, so solving remains deterministic.
+    ///
+    /// The exploitability of [`SolverAlgorithm::ChanceSamplingMccfr`] fluctuates between
+    /// nearby iterations, which makes the prediction unreliable; a [`Fixed`] schedule may reach
+    /// the target earlier with it.
+    ///
+    /// [`Fixed`]: #variant.Fixed
+    #[default]
+    Adaptive,
+}
 
-        let t_alpha = (current_iteration as i32 - 1).max(0) as f64;
-        let t_gamma = (current_iteration - nearest_lower_power_of_4) as f64;
+/// The number of iterations before the first check of [`CheckSchedule::Adaptive`].
+const INITIAL_CHECK_INTERVAL: u32 = 10;
 
-        let pow_alpha = t_alpha * t_alpha.sqrt();
-        let pow_gamma = (t_gamma / (t_gamma + 1.0)).powi(3);
+/// The minimum number of iterations between the checks of [`CheckSchedule::Adaptive`].
+const MIN_CHECK_INTERVAL: u32 = 2;
 
+/// Decides the number of iterations until the next exploitability check.
+pub(crate) struct CheckController {
+    schedule: CheckSchedule,
+    target_exploitability: f32,
+    history: Vec<(u32, f32)>,
+}
+
+impl CheckController {
+    #[inline]
+    pub(crate) fn new(schedule: CheckSchedule, target_exploitability: f32) -> Self {
         Self {
-            alpha_t: (pow_alpha / (pow_alpha + 1.0)) as f32,
-            beta_t: 0.5,
-            gamma_t: pow_gamma as f32,
+            schedule,
+            target_exploitability,
+            history: Vec::new(),
+        }
+    }
+
+    /// Returns the number of iterations until the next check, given the result of the check
+    /// after `num_iterations` iterations.
+    pub(crate) fn next_interval(&mut self, num_iterations: u32, exploitability: f32) -> u32 {
+        if let CheckSchedule::Fixed(interval) = self.schedule {
+            return interval;
+        }
+
+        // the check before the first iteration is not used for the fitting
+        let n = num_iterations;
+        if n == 0 {
+            return INITIAL_CHECK_INTERVAL;
+        }
+
+        // the exploitability fluctuates between nearby checks, so the decay is fitted over the
+        // latest check within the first half of the iterations
+        let prev = self
+            .history
+            .iter()
+            .rev()
+            .find(|&&(prev_n, _)| prev_n <= n / 2)
+            .or(self.history.first())
+            .copied();
+        self.history.push((n, exploitability));
+
+        let Some((prev_n, prev)) = prev else {
+            return INITIAL_CHECK_INTERVAL;
+        };
+
+        let max_interval = INITIAL_CHECK_INTERVAL.max(n / 2);
+        let target = self.target_exploitability as f64;
+        let (prev, current) = (prev as f64, exploitability as f64);
+        if target <= 0.0 || current <= target {
+            return max_interval;
+        }
+
+        // exploitability ~ C * n^(-rate); no decay is observed (e.g., just after the cumulative
+        // strategy is reset), so the default interval is used
+        let rate = (prev / current).ln() / (n as f64 / prev_n as f64).ln();
+        if rate.is_nan() || rate <= 0.0 {
+            return INITIAL_CHECK_INTERVAL;
+        }
+
+        // the prediction is rough, so the target iteration is approached by halves
+        let predicted = n as f64 * (current / target).powf(1.0 / rate);
+        let interval = ((predicted - n as f64) / 2.0).ceil();
+        interval.clamp(MIN_CHECK_INTERVAL as f64, max_interval as f64) as u32
+    }
+}
+
+/// Field of a node that contains a non-finite value (see [`NonFiniteError`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonFiniteField {
+    /// The cumulative regrets (or their compression scale).
+    Regrets,
+
+    /// The cumulative strategy (or its compression scale).
+    Strategy,
+}
+
+/// An error returned when NaN or infinity is found in a node during solving (see
+/// [`SolverConfig::check_finite`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonFiniteError {
+    /// The iteration (0-indexed) after which the value was found.
+    pub iteration: u32,
+
+    /// The player to act at the node.
+    pub player: usize,
+
+    /// The path from the root to the node, i.e., the actions passed to [`GameNode::play`].
+    pub path: Vec<usize>,
+
+    /// The field containing the value.
+    pub field: NonFiniteField,
+}
+
+impl fmt::Display for NonFiniteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Non-finite {:?} found at node {:?} (player {}) after iteration {}",
+            self.field, self.path, self.player, self.iteration
+        )
+    }
+}
+
+impl std::error::Error for NonFiniteError {}
+
+/// Returns the seed of the samples in the subtree of the child `action`.
+#[inline]
+fn child_seed(seed: Option<u64>, action: usize) -> Option<u64> {
+    seed.map(|seed| SolverRng::new(seed).split(action as u64 + 1).seed())
+}
+
+/// Order in which the strategies of the players are updated, set with
+/// [`SolverConfig::alternation`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alternation {
+    /// Both players are updated in each iteration against the strategies at the start of the
+    /// iteration (as in vanilla CFR).
+    ///
+    /// This requires a temporary copy of the cumulative regrets of IP.
+    Simultaneous,
+
+    /// Both players are updated in each iteration, OOP first and then IP against the updated
+    /// strategy of OOP. This is the default.
+    #[default]
+    Alternating,
+
+    /// `player` is updated in every iteration, and the other player only in every `k`-th
+    /// iteration. The players are updated in the same order as [`Alternating`], and `k = 1` is
+    /// equivalent to [`Alternating`].
+    ///
+    /// [`Alternating`]: #variant.Alternating
+    Ratio {
+        /// The player updated in every iteration.
+        player: usize,
+
+        /// The number of iterations per update of the other player (must be positive).
+        k: u32,
+    },
+}
+
+impl Alternation {
+    /// Returns the players updated in the iteration `current_iteration`, in the order of the
+    /// updates. The flag indicates whether the simultaneous update is used.
+    #[inline]
+    fn players(self, current_iteration: u32) -> ([bool; 2], bool) {
+        match self {
+            Self::Simultaneous => ([true; 2], true),
+            Self::Alternating => ([true; 2], false),
+            Self::Ratio { player, k } => {
+                let is_other_updated = current_iteration % k == k - 1;
+                let mut players = [is_other_updated; 2];
+                players[player] = true;
+                (players, false)
+            }
+        }
+    }
+
+    #[inline]
+    fn check(self) -> Result<(), Error> {
+        if let Self::Ratio { player, k } = self {
+            if player >= 2 || k == 0 {
+                return Err(Error::Config(format!("Invalid alternation: {self:?}")));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Options of [`solve_with_options`] for embedding the solver in applications such as GUIs and
+/// servers.
+///
+/// The cancellation flag and the time budget are checked between iterations, so the solving
+/// stops within one iteration after the request.
+#[derive(Default)]
+pub struct SolveOptions<'a> {
+    /// The configuration of the solver.
+    pub config: SolverConfig,
+
+    /// Called with the number of performed iterations and the exploitability whenever the
+    /// exploitability is computed, i.e., before the first iteration, at the checks scheduled by
+    /// [`SolverConfig::check_schedule`], and after the last iteration.
+    pub progress: Option<&'a mut dyn FnMut(u32, f32)>,
+
+    /// The solving stops when this flag is set (e.g., from another thread).
+    pub cancel: Option<&'a AtomicBool>,
+
+    /// The solving stops when this wall-clock time has elapsed. Not available on
+    /// `wasm32-unknown-unknown`, where the time cannot be measured.
+    pub time_limit: Option<Duration>,
+}
+
+/// Summary of a solve returned by [`solve`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolveSummary {
+    /// The exploitability of the obtained strategy.
+    pub exploitability: f32,
+
+    /// The number of performed iterations.
+    pub num_iterations: u32,
+
+    /// The wall-clock time of the solve, including the exploitability checks and the
+    /// finalization. `None` on `wasm32-unknown-unknown`, where the time cannot be measured.
+    pub elapsed: Option<Duration>,
+
+    /// The memory usage of the game in bytes at its high-water mark, i.e., after solving, since
+    /// the storage only grows during solving (e.g., with the lazy allocation). The temporary
+    /// buffers of the traversal are not included. Zero if the [`Game`] implementation does not
+    /// report its memory usage.
+    pub peak_memory_usage: u64,
+
+    /// The number of decision nodes in the game tree.
+    pub num_decision_nodes: usize,
+
+    /// The number of chance nodes in the game tree.
+    pub num_chance_nodes: usize,
+
+    /// The number of terminal nodes in the game tree.
+    pub num_terminal_nodes: usize,
+
+    /// The expected values of the players at the root (see [`compute_current_ev`]).
+    pub root_ev: [f32; 2],
+}
+
+impl fmt::Display for SolveSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "exploitability = {:.4e}, iterations = {}",
+            self.exploitability, self.num_iterations
+        )?;
+        if let Some(elapsed) = self.elapsed {
+            write!(f, ", time = {:.2}s", elapsed.as_secs_f64())?;
         }
+        write!(
+            f,
+            ", memory = {:.2}MB, nodes = {} (decision) / {} (chance) / {} (terminal), \
+             root EV = [{:.4}, {:.4}]",
+            self.peak_memory_usage as f64 / (1024.0 * 1024.0),
+            self.num_decision_nodes,
+            self.num_chance_nodes,
+            self.num_terminal_nodes,
+            self.root_ev[0],
+            self.root_ev[1]
+        )
     }
 }
 
 /// Performs Discounted CFR algorithm until the given number of iterations or exploitability is
 /// satisfied.
 ///
-/// This method returns the exploitability of the obtained strategy.
+/// This method returns a [`SolveSummary`] of the solve, which contains the exploitability of the
+/// obtained strategy. Computing the summary takes a traversal of the game tree besides the
+/// solving; use [`solve_with_config`] if only the exploitability is needed.
+///
+/// # Examples
+/// ```ignore
+/// let summary = solve(&mut game, 1000, target_exploitability, false);
+/// println!("{summary}");
+/// assert!(summary.exploitability <= target_exploitability);
+/// ```
 pub fn solve<T: Game>(
     game: &mut T,
     max_num_iterations: u32,
     target_exploitability: f32,
     print_progress: bool,
-) -> f32 {
-    if game.is_solved() {
-        panic!("Game is already solved");
-    }
+) -> SolveSummary {
+    try_solve(
+        game,
+        max_num_iterations,
+        target_exploitability,
+        print_progress,
+    )
+    .unwrap_or_else(|e| panic!("{e}"))
+}
 
-    if !game.is_ready() {
-        panic!("Game is not ready");
+/// Performs Discounted CFR algorithm like [`solve`], returning an error instead of panicking if
+/// the game cannot be solved, e.g., it is already solved or not ready (see
+/// [`PostFlopGame::check_operation`]).
+///
+/// [`PostFlopGame::check_operation`]: crate::PostFlopGame::check_operation
+pub fn try_solve<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    print_progress: bool,
+) -> Result<SolveSummary, Error> {
+    // `Instant::now` panics on `wasm32-unknown-unknown`
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    let start = Some(Instant::now());
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    let start: Option<Instant> = None;
+
+    let mut options = SolveOptions::default();
+    let (exploitability, num_iterations) = solve_loop(
+        game,
+        max_num_iterations,
+        target_exploitability,
+        &mut options,
+        print_progress,
+    )?;
+
+    let elapsed = start.map(|start| start.elapsed());
+    let mut num_nodes = [0; 3];
+    count_nodes_recursive(&*game.root(), &mut num_nodes);
+
+    Ok(SolveSummary {
+        exploitability,
+        num_iterations,
+        elapsed,
+        peak_memory_usage: game.memory_usage_hint(),
+        num_decision_nodes: num_nodes[0],
+        num_chance_nodes: num_nodes[1],
+        num_terminal_nodes: num_nodes[2],
+        root_ev: compute_current_ev(game),
+    })
+}
+
+/// Counts the decision, chance, and terminal nodes in the subtree of `node`.
+fn count_nodes_recursive<T: GameNode>(node: &T, num_nodes: &mut [usize; 3]) {
+    let kind = if node.is_terminal() {
+        2
+    } else {
+        node.is_chance() as usize
+    };
+    num_nodes[kind] += 1;
+
+    for action in 0..node.num_actions() {
+        count_nodes_recursive(&*node.play(action), num_nodes);
     }
+}
+
+/// Performs the algorithm specified by `config` (see [`SolverConfig`]) until the given number of
+/// iterations or exploitability is satisfied.
+///
+/// This method returns the exploitability of the obtained strategy. Panics if the game cannot be
+/// solved or a non-finite value is found with [`SolverConfig::check_finite`]; use
+/// [`try_solve_with_config`] to handle these cases.
+#[inline]
+pub fn solve_with_config<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    config: &SolverConfig,
+    print_progress: bool,
+) -> f32 {
+    try_solve_with_config(
+        game,
+        max_num_iterations,
+        target_exploitability,
+        config,
+        print_progress,
+    )
+    .unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Performs the algorithm specified by `config` like [`solve_with_config`], returning an error if
+/// `config` is invalid ([`Error::Config`]), the game cannot be solved (see [`try_solve`]), or a
+/// non-finite value is found with [`SolverConfig::check_finite`].
+///
+/// On error, the game is left unfinalized so that the offending node can be inspected.
+pub fn try_solve_with_config<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    config: &SolverConfig,
+    print_progress: bool,
+) -> Result<f32, Error> {
+    let mut options = SolveOptions {
+        config: *config,
+        ..Default::default()
+    };
+    solve_loop(
+        game,
+        max_num_iterations,
+        target_exploitability,
+        &mut options,
+        print_progress,
+    )
+    .map(|(exploitability, _)| exploitability)
+}
+
+/// Performs the algorithm specified by `options.config` like [`try_solve_with_config`], reporting
+/// the progress to `options.progress` and stopping early when `options.cancel` is set or
+/// `options.time_limit` has elapsed (see [`SolveOptions`]).
+///
+/// The game is finalized even if the solving stops early, so the average strategy obtained so
+/// far can be used as usual. Returns the exploitability of the obtained strategy; if the solving
+/// stops early, the exploitability is computed once more for the returned value.
+///
+/// # Examples
+/// ```ignore
+/// let cancel = AtomicBool::new(false); // set from another thread to abort
+/// let mut report = |iteration, exploitability| println!("{iteration}: {exploitability}");
+/// let options = SolveOptions {
+///     progress: Some(&mut report),
+///     cancel: Some(&cancel),
+///     time_limit: Some(Duration::from_secs(60)),
+///     ..Default::default()
+/// };
+/// let exploitability = solve_with_options(&mut game, 1000, target_exploitability, options)?;
+/// ```
+pub fn solve_with_options<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    mut options: SolveOptions,
+) -> Result<f32, Error> {
+    solve_loop(
+        game,
+        max_num_iterations,
+        target_exploitability,
+        &mut options,
+        false,
+    )
+    .map(|(exploitability, _)| exploitability)
+}
+
+/// The solving loop shared by [`solve`], [`try_solve_with_config`], and [`solve_with_options`].
+///
+/// Returns the exploitability and the number of performed iterations.
+fn solve_loop<T: Game>(
+    game: &mut T,
+    max_num_iterations: u32,
+    target_exploitability: f32,
+    options: &mut SolveOptions,
+    print_progress: bool,
+) -> Result<(f32, u32), Error> {
+    check_solvable(game)?;
+
+    let config = &options.config;
+    config.check()?;
+    let pool = IterationPool::new(config)?;
+
+    // `Instant::now` panics on `wasm32-unknown-unknown`, so it is called only when needed
+    let deadline = options.time_limit.map(|limit| Instant::now() + limit);
+    let should_stop = || {
+        options
+            .cancel
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+            || deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    };
 
-    let mut root = game.root();
     let mut exploitability = compute_exploitability(game);
+    game.update_frozen_streets(exploitability);
 
     if print_progress {
         print!("iteration: 0 / {max_num_iterations} ");
@@ -63,28 +709,44 @@ pub fn solve<T: Game>(
         io::stdout().flush().unwrap();
     }
 
+    let mut num_iterations = 0;
+    let mut is_evaluated = true;
+    let mut report = |num_iterations: u32, exploitability: f32| {
+        if let Some(progress) = options.progress.as_mut() {
+            progress(num_iterations, exploitability);
+        }
+    };
+
+    report(0, exploitability);
+
+    let mut controller = CheckController::new(config.check_schedule, target_exploitability);
+    let mut next_check = controller.next_interval(0, exploitability);
+
     for t in 0..max_num_iterations {
-        if exploitability <= target_exploitability {
+        if exploitability <= target_exploitability || should_stop() {
             break;
         }
 
-        let params = DiscountParams::new(t);
+        pool.solve_iteration(game, t, config);
+        num_iterations = t + 1;
+        is_evaluated = false;
 
-        // alternating updates
-        for player in 0..2 {
-            let mut result = Vec::with_capacity(game.num_private_hands(player));
-            solve_recursive(
-                result.spare_capacity_mut(),
-                game,
-                &mut root,
-                player,
-                game.initial_weights(player ^ 1),
-                &params,
-            );
+        if config.check_finite {
+            if let Err(e) = check_finite(game, t) {
+                if print_progress {
+                    println!();
+                }
+                return Err(e.into());
+            }
         }
 
-        if (t + 1) % 10 == 0 || t + 1 == max_num_iterations {
+        if num_iterations == next_check || num_iterations == max_num_iterations {
             exploitability = compute_exploitability(game);
+            game.update_frozen_streets(exploitability);
+            report(num_iterations, exploitability);
+            is_evaluated = true;
+            next_check = num_iterations
+                .saturating_add(controller.next_interval(num_iterations, exploitability));
         }
 
         if print_progress {
@@ -94,6 +756,12 @@ pub fn solve<T: Game>(
         }
     }
 
+    // stopped early between the evaluations
+    if !is_evaluated {
+        exploitability = compute_exploitability(game);
+        report(num_iterations, exploitability);
+    }
+
     if print_progress {
         println!();
         io::stdout().flush().unwrap();
@@ -101,34 +769,412 @@ pub fn solve<T: Game>(
 
     finalize(game);
 
-    exploitability
+    Ok((exploitability, num_iterations))
+}
+
+/// Checks that solver iterations can be performed on `game`.
+///
+/// Returns [`Error::State`] if solving is not valid for the current state of the game, e.g., the
+/// game is already solved or the finalization is in progress, and returns [`Error::Config`] if
+/// the game is not ready otherwise (e.g., the storage mode is not river).
+pub(crate) fn check_solvable<T: Game + ?Sized>(game: &T) -> Result<(), Error> {
+    game.check_operation(GameOperation::Solve)?;
+
+    if !game.is_ready() {
+        return Err(Error::Config("Game is not ready".to_string()));
+    }
+
+    Ok(())
 }
 
 /// Proceeds Discounted CFR algorithm for one iteration.
 #[inline]
 pub fn solve_step<T: Game>(game: &T, current_iteration: u32) {
-    if game.is_solved() {
-        panic!("Game is already solved");
+    solve_step_with_config(game, current_iteration, &SolverConfig::default());
+}
+
+/// Proceeds the algorithm specified by `config` (see [`SolverConfig`]) for one iteration.
+///
+/// The discount factors depend on `current_iteration`, so the iterations must be numbered
+/// consecutively from zero. Panics if the game cannot be solved or a non-finite value is found
+/// with [`SolverConfig::check_finite`]; use [`try_solve_step_with_config`] to handle these cases.
+#[inline]
+pub fn solve_step_with_config<T: Game>(game: &T, current_iteration: u32, config: &SolverConfig) {
+    try_solve_step_with_config(game, current_iteration, config).unwrap_or_else(|e| panic!("{e}"));
+}
+
+/// Proceeds the algorithm specified by `config` for one iteration like
+/// [`solve_step_with_config`], returning an error if the game cannot be solved (see
+/// [`try_solve`]) or a non-finite value is found with [`SolverConfig::check_finite`].
+pub fn try_solve_step_with_config<T: Game>(
+    game: &T,
+    current_iteration: u32,
+    config: &SolverConfig,
+) -> Result<(), Error> {
+    check_solvable(game)?;
+
+    config.check()?;
+    let pool = IterationPool::new(config)?;
+    solve_step_in(game, current_iteration, config, &pool)
+}
+
+/// Proceeds one iteration in `pool` after the game and `config` are checked.
+fn solve_step_in<T: Game>(
+    game: &T,
+    current_iteration: u32,
+    config: &SolverConfig,
+    pool: &IterationPool,
+) -> Result<(), Error> {
+    pool.solve_iteration(game, current_iteration, config);
+
+    if config.check_finite {
+        check_finite(game, current_iteration)?;
     }
 
-    if !game.is_ready() {
-        panic!("Game is not ready");
+    Ok(())
+}
+
+/// Proceeds the algorithm specified by `config` for `num_iterations` iterations starting from the
+/// iteration `start_iteration`, and returns the exploitability of the resulting strategy.
+///
+/// This is the building block of a solving loop driven by the caller, e.g., the event loop of a
+/// WebAssembly host with the single-threaded build (without the `rayon` feature) or an async
+/// runtime, which reports the progress and checks for cancellation between the chunks. As in
+/// [`solve_with_config`], the exploitability is also used to freeze the streets whose targets
+/// are satisfied. Call [`finalize`] after the last chunk:
+///
+/// ```ignore
+/// let mut t = 0;
+/// while t < max_num_iterations {
+///     let exploitability = solve_steps(&game, t, 10, &config)?;
+///     t += 10;
+///     report_progress(t, exploitability);
+///     if exploitability <= target_exploitability || is_cancelled() {
+///         break;
+///     }
+/// }
+/// finalize(&mut game);
+/// ```
+pub fn solve_steps<T: Game>(
+    game: &T,
+    start_iteration: u32,
+    num_iterations: u32,
+    config: &SolverConfig,
+) -> Result<f32, Error> {
+    check_solvable(game)?;
+
+    config.check()?;
+    let pool = IterationPool::new(config)?;
+    for t in start_iteration..start_iteration + num_iterations {
+        solve_step_in(game, t, config, &pool)?;
     }
 
-    let mut root = game.root();
-    let params = DiscountParams::new(current_iteration);
+    let exploitability = compute_exploitability(game);
+    game.update_frozen_streets(exploitability);
+    Ok(exploitability)
+}
 
-    // alternating updates
-    for player in 0..2 {
+/// Checks that the cumulative regrets and strategies of all nodes are finite.
+fn check_finite<T: Game>(game: &T, current_iteration: u32) -> Result<(), NonFiniteError> {
+    let mut path = Vec::new();
+    match find_non_finite_recursive(game, &game.root(), &mut path) {
+        Some((player, field)) => Err(NonFiniteError {
+            iteration: current_iteration,
+            player,
+            path,
+            field,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Returns the player and the field of the first node (in depth-first order) containing a
+/// non-finite value, leaving the path to the node in `path`.
+fn find_non_finite_recursive<T: Game>(
+    game: &T,
+    node: &T::Node,
+    path: &mut Vec<usize>,
+) -> Option<(usize, NonFiniteField)> {
+    if node.is_terminal() {
+        return None;
+    }
+
+    if !node.is_chance() && node.num_actions() > 1 {
+        let is_regrets_finite = if game.is_node_compressed(node) {
+            node.regret_scale().is_finite()
+        } else if game.is_f64_regrets_enabled() {
+            node.regrets_f64().iter().all(|r| r.is_finite())
+        } else {
+            node.regrets().iter().all(|r| r.is_finite())
+        };
+
+        if !is_regrets_finite {
+            return Some((node.player(), NonFiniteField::Regrets));
+        }
+
+        let is_strategy_finite = if game.is_node_compressed(node) {
+            node.strategy_scale().is_finite()
+        } else {
+            node.strategy().iter().all(|s| s.is_finite())
+        };
+
+        if !is_strategy_finite {
+            return Some((node.player(), NonFiniteField::Strategy));
+        }
+    }
+
+    for action in 0..node.num_actions() {
+        path.push(action);
+        let found = find_non_finite_recursive(game, &node.play(action), path);
+        if found.is_some() {
+            return found;
+        }
+        path.pop();
+    }
+
+    None
+}
+
+/// The thread pool in which the solver iterations run.
+///
+/// With [`SolverConfig::deterministic`], the iterations run in a single-thread pool, which is
+/// built once for the whole solving loop; otherwise, they run in the current pool.
+struct IterationPool {
+    #[cfg(feature = "rayon")]
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl IterationPool {
+    #[cfg(feature = "rayon")]
+    fn new(config: &SolverConfig) -> Result<Self, Error> {
+        let pool = if config.deterministic && rayon::current_num_threads() > 1 {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .build()
+                .map_err(|e| Error::Solver(e.to_string()))?;
+            Some(pool)
+        } else {
+            None
+        };
+
+        Ok(Self { pool })
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    #[inline]
+    fn new(_config: &SolverConfig) -> Result<Self, Error> {
+        Ok(Self {})
+    }
+
+    /// Updates the players of the iteration `current_iteration` according to `config` in the
+    /// pool.
+    #[inline]
+    fn solve_iteration<T: Game>(&self, game: &T, current_iteration: u32, config: &SolverConfig) {
+        #[cfg(feature = "rayon")]
+        if let Some(pool) = &self.pool {
+            pool.install(|| solve_iteration(game, current_iteration, config));
+            return;
+        }
+
+        solve_iteration(game, current_iteration, config);
+    }
+}
+
+/// Updates the players of the iteration `current_iteration` according to `config`.
+fn solve_iteration<T: Game>(game: &T, current_iteration: u32, config: &SolverConfig) {
+    game.mark_solving();
+    let params = DiscountParams::new(current_iteration, config.algorithm);
+    let (players, is_simultaneous) = config.alternation.players(current_iteration);
+
+    let update = |player: usize| {
         let mut result = Vec::with_capacity(game.num_private_hands(player));
         solve_recursive(
             result.spare_capacity_mut(),
             game,
-            &mut root,
+            &mut game.root(),
             player,
             game.initial_weights(player ^ 1),
             &params,
+            config.sampling_seed(current_iteration, player),
         );
+    };
+
+    if is_simultaneous {
+        // update IP first, and then OOP against the regrets of IP before the update
+        let mut snapshot = Vec::new();
+        take_regrets_recursive(game, &mut game.root(), 1, &mut snapshot);
+        update(1);
+        swap_regrets_recursive(game, &mut game.root(), 1, &mut snapshot.iter_mut());
+        update(0);
+        swap_regrets_recursive(game, &mut game.root(), 1, &mut snapshot.iter_mut());
+    } else {
+        for player in 0..2 {
+            if players[player] {
+                update(player);
+            }
+        }
+    }
+}
+
+/// Cumulative regrets of a node in any of the storage formats.
+enum RegretSnapshot {
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    Compressed(Vec<i16>, f32),
+    Unallocated,
+}
+
+/// Copies the cumulative regrets of the nodes of `player` in depth-first order.
+fn take_regrets_recursive<T: Game>(
+    game: &T,
+    node: &mut T::Node,
+    player: usize,
+    snapshot: &mut Vec<RegretSnapshot>,
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if !node.is_chance() && node.player() == player {
+        snapshot.push(if !game.is_node_allocated(node) {
+            RegretSnapshot::Unallocated
+        } else if game.is_node_compressed(node) {
+            RegretSnapshot::Compressed(node.regrets_compressed().to_vec(), node.regret_scale())
+        } else if game.is_f64_regrets_enabled() {
+            RegretSnapshot::F64(node.regrets_f64().to_vec())
+        } else {
+            RegretSnapshot::F32(node.regrets().to_vec())
+        });
+    }
+
+    for action in 0..node.num_actions() {
+        take_regrets_recursive(game, &mut node.play(action), player, snapshot);
+    }
+}
+
+/// Swaps the cumulative regrets of the nodes of `player` with the snapshot taken by
+/// [`take_regrets_recursive`].
+fn swap_regrets_recursive<'a, T: Game>(
+    game: &T,
+    node: &mut T::Node,
+    player: usize,
+    snapshot: &mut impl Iterator<Item = &'a mut RegretSnapshot>,
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if !node.is_chance() && node.player() == player {
+        let entry = snapshot.next().unwrap();
+
+        // the regrets of a node allocated after taking the snapshot were zero
+        if matches!(entry, RegretSnapshot::Unallocated) && game.is_node_allocated(node) {
+            *entry = if game.is_node_compressed(node) {
+                RegretSnapshot::Compressed(vec![0; node.regrets_compressed().len()], 0.0)
+            } else if game.is_f64_regrets_enabled() {
+                RegretSnapshot::F64(vec![0.0; node.regrets_f64().len()])
+            } else {
+                RegretSnapshot::F32(vec![0.0; node.regrets().len()])
+            };
+        }
+
+        match entry {
+            RegretSnapshot::F32(regrets) => node.regrets_mut().swap_with_slice(regrets),
+            RegretSnapshot::F64(regrets) => node.regrets_f64_mut().swap_with_slice(regrets),
+            RegretSnapshot::Compressed(regrets, scale) => {
+                node.regrets_compressed_mut().swap_with_slice(regrets);
+                let node_scale = node.regret_scale();
+                node.set_regret_scale(*scale);
+                *scale = node_scale;
+            }
+            RegretSnapshot::Unallocated => {}
+        }
+    }
+
+    for action in 0..node.num_actions() {
+        swap_regrets_recursive(game, &mut node.play(action), player, snapshot);
+    }
+}
+
+/// Initializes the cumulative regrets with random values generated from the given seed.
+///
+/// By default, the solver starts from the uniform strategy. After calling this function, the
+/// first iteration instead uses a random strategy obtained by regret matching, which is useful for
+/// studying equilibrium selection and checking that a `Game` implementation converges regardless
+/// of the starting point. The same seed always produces the same initialization (see
+/// [`SolverRng`]). The nodes whose storage is not allocated yet (see
+/// [`PostFlopGame::allocate_memory_lazy`]) keep zero regrets.
+///
+/// This function must be called after allocating memory and before the first iteration.
+///
+/// [`PostFlopGame::allocate_memory_lazy`]: crate::PostFlopGame::allocate_memory_lazy
+pub fn randomize_regrets<T: Game>(game: &mut T, seed: u64) {
+    let classes = [0, 1].map(|player| (0..game.num_private_hands(player)).collect());
+    randomize_regrets_with_classes(game, seed, &classes);
+}
+
+/// Initializes the cumulative regrets like [`randomize_regrets`], but assigns the same random
+/// values to the private hands of the same class.
+///
+/// `classes[player][hand]` is the smallest index of the hands in the class of `hand`.
+pub(crate) fn randomize_regrets_with_classes<T: Game>(
+    game: &mut T,
+    seed: u64,
+    classes: &[Vec<usize>; 2],
+) {
+    if game.is_solved() {
+        panic!("Game is already solved");
+    }
+
+    if !game.is_ready() {
+        panic!("Game is not ready");
+    }
+
+    let mut rng = SolverRng::new(seed);
+    randomize_regrets_recursive(game, &mut game.root(), &mut rng, classes);
+}
+
+/// Recursively fills the cumulative regrets with random values.
+fn randomize_regrets_recursive<T: Game>(
+    game: &T,
+    node: &mut T::Node,
+    rng: &mut SolverRng,
+    classes: &[Vec<usize>; 2],
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if !node.is_chance() && node.num_actions() > 1 && game.is_node_allocated(node) {
+        let class = &classes[node.player()];
+        let mut values = Vec::with_capacity(node.num_actions() * class.len());
+        for _ in 0..node.num_actions() {
+            let offset = values.len();
+            for (hand, &representative) in class.iter().enumerate() {
+                let value = if representative < hand {
+                    values[offset + representative]
+                } else {
+                    rng.next_f32()
+                };
+                values.push(value);
+            }
+        }
+
+        if game.is_node_compressed(node) {
+            let scale = encode_signed_slice(node.regrets_compressed_mut(), &values);
+            node.set_regret_scale(scale);
+        } else if game.is_f64_regrets_enabled() {
+            node.regrets_f64_mut()
+                .iter_mut()
+                .zip(&values)
+                .for_each(|(r, &v)| *r = v as f64);
+        } else {
+            node.regrets_mut().copy_from_slice(&values);
+        }
+    }
+
+    for action in 0..node.num_actions() {
+        randomize_regrets_recursive(game, &mut node.play(action), rng, classes);
     }
 }
 
@@ -140,6 +1186,7 @@ fn solve_recursive<T: Game>(
     player: usize,
     cfreach: &[f32],
     params: &DiscountParams,
+    seed: Option<u64>,
 ) {
     // return the counterfactual values when the `node` is terminal
     if node.is_terminal() {
@@ -147,21 +1194,37 @@ fn solve_recursive<T: Game>(
         return;
     }
 
+    // skip the subtree whose storage is not allocated yet and is not reached
+    if !game.allocate_node(node, cfreach) {
+        result.iter_mut().for_each(|r| {
+            r.write(0.0);
+        });
+        return;
+    }
+
     let num_actions = node.num_actions();
     let num_hands = result.len();
 
     // simply recurse when the number of actions is one
     if num_actions == 1 && !node.is_chance() {
         let child = &mut node.play(0);
-        solve_recursive(result, game, child, player, cfreach, params);
+        solve_recursive(result, game, child, player, cfreach, params, seed);
         return;
     }
 
+    // sample a chance event
+    if node.is_chance() {
+        if let Some(seed) = seed {
+            solve_chance_sampled(result, game, node, player, cfreach, params, seed);
+            return;
+        }
+    }
+
     // allocate memory for storing the counterfactual values
     #[cfg(feature = "custom-alloc")]
     let cfv_actions = MutexLike::new(Vec::with_capacity_in(num_actions * num_hands, StackAlloc));
     #[cfg(not(feature = "custom-alloc"))]
-    let cfv_actions = MutexLike::new(Vec::with_capacity(num_actions * num_hands));
+    let cfv_actions = MutexLike::new(PooledVec::with_capacity(num_actions * num_hands));
 
     // if the `node` is chance
     if node.is_chance() {
@@ -169,16 +1232,16 @@ fn solve_recursive<T: Game>(
         #[cfg(feature = "custom-alloc")]
         let mut cfreach_updated = Vec::with_capacity_in(cfreach.len(), StackAlloc);
         #[cfg(not(feature = "custom-alloc"))]
-        let mut cfreach_updated = Vec::with_capacity(cfreach.len());
+        let mut cfreach_updated = PooledVec::with_capacity(cfreach.len());
         mul_slice_scalar_uninit(
-            cfreach_updated.spare_capacity_mut(),
+            &mut cfreach_updated.spare_capacity_mut()[..cfreach.len()],
             cfreach,
             1.0 / game.chance_factor(node) as f32,
         );
         unsafe { cfreach_updated.set_len(cfreach.len()) };
 
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             solve_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -186,6 +1249,7 @@ fn solve_recursive<T: Game>(
                 player,
                 &cfreach_updated,
                 params,
+                None,
             );
         });
 
@@ -193,12 +1257,15 @@ fn solve_recursive<T: Game>(
         #[cfg(feature = "custom-alloc")]
         let mut result_f64 = Vec::with_capacity_in(num_hands, StackAlloc);
         #[cfg(not(feature = "custom-alloc"))]
-        let mut result_f64 = Vec::with_capacity(num_hands);
+        let mut result_f64 = PooledVec::with_capacity(num_hands);
 
         // sum up the counterfactual values
         let mut cfv_actions = cfv_actions.lock();
         unsafe { cfv_actions.set_len(num_actions * num_hands) };
-        sum_slices_f64_uninit(result_f64.spare_capacity_mut(), &cfv_actions);
+        sum_slices_f64_uninit(
+            &mut result_f64.spare_capacity_mut()[..num_hands],
+            &cfv_actions,
+        );
         unsafe { result_f64.set_len(num_hands) };
 
         // get information about isomorphic chances
@@ -225,7 +1292,7 @@ fn solve_recursive<T: Game>(
     // if the current player is `player`
     else if node.player() == player {
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             solve_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -233,12 +1300,18 @@ fn solve_recursive<T: Game>(
                 player,
                 cfreach,
                 params,
+                child_seed(seed, action),
             );
         });
 
         // compute the strategy by regret-maching algorithm
-        let mut strategy = if game.is_compression_enabled() {
+        let is_frozen = game.is_node_frozen(node);
+        let mut strategy = if is_frozen {
+            average_strategy(game, node, num_actions)
+        } else if game.is_node_compressed(node) {
             regret_matching_compressed(node.regrets_compressed(), num_actions)
+        } else if game.is_f64_regrets_enabled() {
+            regret_matching_f64(node.regrets_f64(), num_actions)
         } else {
             regret_matching(node.regrets(), num_actions)
         };
@@ -252,7 +1325,12 @@ fn solve_recursive<T: Game>(
         unsafe { cfv_actions.set_len(num_actions * num_hands) };
         let result = fma_slices_uninit(result, &strategy, &cfv_actions);
 
-        if game.is_compression_enabled() {
+        // the frozen strategy is not updated
+        if is_frozen {
+            return;
+        }
+
+        if game.is_node_compressed(node) {
             // update the cumulative strategy
             let scale = node.strategy_scale();
             let decoder = params.gamma_t * scale / u16::MAX as f32;
@@ -307,21 +1385,41 @@ fn solve_recursive<T: Game>(
 
             // update the cumulative regret
             let (alpha, beta) = (params.alpha_t, params.beta_t);
-            let cum_regret = node.regrets_mut();
-            cum_regret.iter_mut().zip(&*cfv_actions).for_each(|(x, y)| {
-                let coef = if x.is_sign_positive() { alpha } else { beta };
-                *x = *x * coef + *y;
-            });
-            cum_regret.chunks_exact_mut(num_hands).for_each(|row| {
-                sub_slice(row, result);
-            });
+            if game.is_f64_regrets_enabled() {
+                let (alpha, beta) = (alpha as f64, beta as f64);
+                let cum_regret = node.regrets_f64_mut();
+                cum_regret
+                    .chunks_exact_mut(num_hands)
+                    .zip(cfv_actions.chunks_exact(num_hands))
+                    .for_each(|(row, cfv_row)| {
+                        row.iter_mut()
+                            .zip(cfv_row.iter().zip(&*result))
+                            .for_each(|(x, (y, r))| {
+                                let coef = if x.is_sign_positive() { alpha } else { beta };
+                                *x = *x * coef + (*y as f64 - *r as f64);
+                            });
+                    });
+            } else {
+                let cum_regret = node.regrets_mut();
+                cum_regret.iter_mut().zip(&*cfv_actions).for_each(|(x, y)| {
+                    let coef = if x.is_sign_positive() { alpha } else { beta };
+                    *x = *x * coef + *y;
+                });
+                cum_regret.chunks_exact_mut(num_hands).for_each(|row| {
+                    sub_slice(row, result);
+                });
+            }
         }
     }
     // if the current player is not `player`
     else {
         // compute the strategy by regret-matching algorithm
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_node_frozen(node) {
+            average_strategy(game, node, num_actions)
+        } else if game.is_node_compressed(node) {
             regret_matching_compressed(node.regrets_compressed(), num_actions)
+        } else if game.is_f64_regrets_enabled() {
+            regret_matching_f64(node.regrets_f64(), num_actions)
         } else {
             regret_matching(node.regrets(), num_actions)
         };
@@ -337,7 +1435,7 @@ fn solve_recursive<T: Game>(
         });
 
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             solve_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -345,6 +1443,7 @@ fn solve_recursive<T: Game>(
                 player,
                 row(&cfreach_actions, action, row_size),
                 params,
+                child_seed(seed, action),
             );
         });
 
@@ -355,15 +1454,97 @@ fn solve_recursive<T: Game>(
     }
 }
 
+/// Computes the counterfactual values of a chance node by sampling one of the stored chance
+/// events uniformly, weighting the reach probabilities by the inverse of the sampling
+/// probability. The isomorphic chances of the sampled event are accounted by swapping its values.
+fn solve_chance_sampled<T: Game>(
+    result: &mut [MaybeUninit<f32>],
+    game: &T,
+    node: &mut T::Node,
+    player: usize,
+    cfreach: &[f32],
+    params: &DiscountParams,
+    seed: u64,
+) {
+    let num_actions = node.num_actions();
+    let mut rng = SolverRng::new(seed);
+    let action = rng.next_below(num_actions);
+
+    // update the reach probabilities
+    #[cfg(feature = "custom-alloc")]
+    let mut cfreach_updated = Vec::with_capacity_in(cfreach.len(), StackAlloc);
+    #[cfg(not(feature = "custom-alloc"))]
+    let mut cfreach_updated = PooledVec::with_capacity(cfreach.len());
+    mul_slice_scalar_uninit(
+        &mut cfreach_updated.spare_capacity_mut()[..cfreach.len()],
+        cfreach,
+        num_actions as f32 / game.chance_factor(node) as f32,
+    );
+    unsafe { cfreach_updated.set_len(cfreach.len()) };
+
+    solve_recursive(
+        result,
+        game,
+        &mut node.play(action),
+        player,
+        &cfreach_updated,
+        params,
+        Some(rng.next_u64()),
+    );
+
+    let result = unsafe { &mut *(result as *mut _ as *mut [f32]) };
+    let mut result_f64 = result.iter().map(|&v| v as f64).collect::<Vec<_>>();
+    let mut tmp = result.to_vec();
+
+    // process isomorphic chances
+    for (i, &isomorphic_index) in game.isomorphic_chances(node).iter().enumerate() {
+        if isomorphic_index as usize != action {
+            continue;
+        }
+
+        let swap_list = &game.isomorphic_swap(node, i)[player];
+        apply_swap(&mut tmp, swap_list);
+
+        result_f64.iter_mut().zip(&tmp).for_each(|(r, &v)| {
+            *r += v as f64;
+        });
+
+        apply_swap(&mut tmp, swap_list);
+    }
+
+    result.iter_mut().zip(&result_f64).for_each(|(r, &v)| {
+        *r = v as f32;
+    });
+}
+
+/// Computes the average strategy of a frozen node.
+#[cfg(feature = "custom-alloc")]
+#[inline]
+fn average_strategy<T: Game>(game: &T, node: &T::Node, num_actions: usize) -> Vec<f32, StackAlloc> {
+    if game.is_node_compressed(node) {
+        normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
+    } else {
+        normalized_strategy_custom_alloc(node.strategy(), num_actions)
+    }
+}
+
+/// Computes the average strategy of a frozen node.
+#[cfg(not(feature = "custom-alloc"))]
+#[inline]
+fn average_strategy<T: Game>(game: &T, node: &T::Node, num_actions: usize) -> PooledVec<f32> {
+    if game.is_node_compressed(node) {
+        normalized_strategy_compressed(node.strategy_compressed(), num_actions).into()
+    } else {
+        normalized_strategy(node.strategy(), num_actions).into()
+    }
+}
+
 /// Computes the strategy by regret-matching algorithm.
 #[cfg(feature = "custom-alloc")]
 #[inline]
-fn regret_matching(regret: &[f32], num_actions: usize) -> Vec<f32, StackAlloc> {
+pub(crate) fn regret_matching(regret: &[f32], num_actions: usize) -> Vec<f32, StackAlloc> {
     let mut strategy = Vec::with_capacity_in(regret.len(), StackAlloc);
-    let uninit = strategy.spare_capacity_mut();
-    uninit.iter_mut().zip(regret).for_each(|(s, r)| {
-        s.write(max(*r, 0.0));
-    });
+    positive_part_uninit(strategy.spare_capacity_mut(), regret);
     unsafe { strategy.set_len(regret.len()) };
 
     let row_size = regret.len() / num_actions;
@@ -382,17 +1563,14 @@ fn regret_matching(regret: &[f32], num_actions: usize) -> Vec<f32, StackAlloc> {
 /// Computes the strategy by regret-matching algorithm.
 #[cfg(not(feature = "custom-alloc"))]
 #[inline]
-fn regret_matching(regret: &[f32], num_actions: usize) -> Vec<f32> {
-    let mut strategy = Vec::with_capacity(regret.len());
-    let uninit = strategy.spare_capacity_mut();
-    uninit.iter_mut().zip(regret).for_each(|(s, r)| {
-        s.write(max(*r, 0.0));
-    });
+pub(crate) fn regret_matching(regret: &[f32], num_actions: usize) -> PooledVec<f32> {
+    let mut strategy = PooledVec::with_capacity(regret.len());
+    positive_part_uninit(&mut strategy.spare_capacity_mut()[..regret.len()], regret);
     unsafe { strategy.set_len(regret.len()) };
 
     let row_size = regret.len() / num_actions;
-    let mut denom = Vec::with_capacity(row_size);
-    sum_slices_uninit(denom.spare_capacity_mut(), &strategy);
+    let mut denom = PooledVec::with_capacity(row_size);
+    sum_slices_uninit(&mut denom.spare_capacity_mut()[..row_size], &strategy);
     unsafe { denom.set_len(row_size) };
 
     let default = 1.0 / num_actions as f32;
@@ -406,7 +1584,10 @@ fn regret_matching(regret: &[f32], num_actions: usize) -> Vec<f32> {
 /// Computes the strategy by regret-matching algorithm.
 #[cfg(feature = "custom-alloc")]
 #[inline]
-fn regret_matching_compressed(regret: &[i16], num_actions: usize) -> Vec<f32, StackAlloc> {
+pub(crate) fn regret_matching_compressed(
+    regret: &[i16],
+    num_actions: usize,
+) -> Vec<f32, StackAlloc> {
     let mut strategy = Vec::with_capacity_in(regret.len(), StackAlloc);
     strategy.extend(regret.iter().map(|&r| r.max(0) as f32));
 
@@ -426,13 +1607,13 @@ fn regret_matching_compressed(regret: &[i16], num_actions: usize) -> Vec<f32, St
 /// Computes the strategy by regret-matching algorithm.
 #[cfg(not(feature = "custom-alloc"))]
 #[inline]
-fn regret_matching_compressed(regret: &[i16], num_actions: usize) -> Vec<f32> {
-    let mut strategy = Vec::with_capacity(regret.len());
+pub(crate) fn regret_matching_compressed(regret: &[i16], num_actions: usize) -> PooledVec<f32> {
+    let mut strategy = PooledVec::with_capacity(regret.len());
     strategy.extend(regret.iter().map(|&r| r.max(0) as f32));
 
     let row_size = strategy.len() / num_actions;
-    let mut denom = Vec::with_capacity(row_size);
-    sum_slices_uninit(denom.spare_capacity_mut(), &strategy);
+    let mut denom = PooledVec::with_capacity(row_size);
+    sum_slices_uninit(&mut denom.spare_capacity_mut()[..row_size], &strategy);
     unsafe { denom.set_len(row_size) };
 
     let default = 1.0 / num_actions as f32;
@@ -442,3 +1623,60 @@ fn regret_matching_compressed(regret: &[i16], num_actions: usize) -> Vec<f32> {
 
     strategy
 }
+
+/// Computes the strategy by regret-matching algorithm.
+#[cfg(feature = "custom-alloc")]
+#[inline]
+pub(crate) fn regret_matching_f64(regret: &[f64], num_actions: usize) -> Vec<f32, StackAlloc> {
+    let row_size = regret.len() / num_actions;
+    let mut denom = vec![0.0f64; row_size];
+    regret.chunks_exact(row_size).for_each(|row| {
+        denom
+            .iter_mut()
+            .zip(row)
+            .for_each(|(d, r)| *d += r.max(0.0));
+    });
+
+    let default = 1.0 / num_actions as f32;
+    let mut strategy = Vec::with_capacity_in(regret.len(), StackAlloc);
+    regret.chunks_exact(row_size).for_each(|row| {
+        strategy.extend(row.iter().zip(&denom).map(|(r, d)| {
+            if *d == 0.0 {
+                default
+            } else {
+                (r.max(0.0) / d) as f32
+            }
+        }));
+    });
+
+    strategy
+}
+
+/// Computes the strategy by regret-matching algorithm.
+#[cfg(not(feature = "custom-alloc"))]
+#[inline]
+pub(crate) fn regret_matching_f64(regret: &[f64], num_actions: usize) -> PooledVec<f32> {
+    let row_size = regret.len() / num_actions;
+    let mut denom = PooledVec::with_capacity(row_size);
+    denom.resize(row_size, 0.0f64);
+    regret.chunks_exact(row_size).for_each(|row| {
+        denom
+            .iter_mut()
+            .zip(row)
+            .for_each(|(d, r)| *d += r.max(0.0));
+    });
+
+    let default = 1.0 / num_actions as f32;
+    let mut strategy = PooledVec::with_capacity(regret.len());
+    regret.chunks_exact(row_size).for_each(|row| {
+        strategy.extend(row.iter().zip(&denom).map(|(r, d)| {
+            if *d == 0.0 {
+                default
+            } else {
+                (r.max(0.0) / d) as f32
+            }
+        }));
+    });
+
+    strategy
+}
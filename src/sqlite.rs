@@ -0,0 +1,336 @@
+//! Export of solve libraries into SQLite databases.
+//!
+//! [`SqliteExporter`] writes the aggregate results of solved flops into a SQLite database, so
+//! that GUI and statistics tools can read them without Rust bindings. The database has the
+//! following tables; each flop is identified by its string representation (e.g., `Td9d6h`,
+//! with the cards in the order of [`CardConfig::flop`]), and re-exporting a flop replaces its
+//! rows.
+//!
+//! ```sql
+//! -- one row per solved flop
+//! CREATE TABLE flops (
+//!     flop TEXT PRIMARY KEY,
+//!     exploitability REAL NOT NULL,
+//!     ev_oop REAL NOT NULL,           -- expected value of OOP at the root node
+//!     ev_ip REAL NOT NULL             -- expected value of IP at the root node
+//! );
+//!
+//! -- overall frequencies of the root actions
+//! CREATE TABLE root_actions (
+//!     flop TEXT NOT NULL,
+//!     action_index INTEGER NOT NULL,  -- index in the available actions
+//!     player INTEGER NOT NULL,        -- 0 (OOP) or 1 (IP)
+//!     action TEXT NOT NULL,           -- e.g., 'Check', 'Bet 30'
+//!     frequency REAL NOT NULL,
+//!     PRIMARY KEY (flop, action_index)
+//! );
+//!
+//! -- root strategy by hand class, as in the 13x13 hand matrix
+//! CREATE TABLE root_grid (
+//!     flop TEXT NOT NULL,
+//!     hand TEXT NOT NULL,             -- e.g., 'AA', 'AKs', 'AKo'
+//!     action_index INTEGER NOT NULL,
+//!     combos REAL NOT NULL,           -- number of combinations of the hand class
+//!     frequency REAL NOT NULL,
+//!     PRIMARY KEY (flop, hand, action_index)
+//! );
+//!
+//! -- summaries of the batch metrics (see `BatchRunner::add_metric`)
+//! CREATE TABLE metrics (
+//!     flop TEXT NOT NULL,
+//!     name TEXT NOT NULL,
+//!     count INTEGER NOT NULL,
+//!     sum REAL NOT NULL,
+//!     min REAL NOT NULL,
+//!     max REAL NOT NULL,
+//!     PRIMARY KEY (flop, name)
+//! );
+//!
+//! -- flops that failed in a batch job
+//! CREATE TABLE failures (
+//!     flop TEXT PRIMARY KEY,
+//!     message TEXT NOT NULL
+//! );
+//! ```
+//!
+//! Flops added from a [`BatchReport`] have rows in `flops` and `metrics` only, since the solved
+//! games are no longer available.
+//!
+//! [`CardConfig::flop`]: crate::CardConfig::flop
+
+use crate::action_tree::*;
+use crate::batch::*;
+use crate::error::*;
+use crate::game::*;
+use crate::utility::*;
+use rusqlite::{params, Connection, Transaction};
+use std::io;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS flops (
+    flop TEXT PRIMARY KEY,
+    exploitability REAL NOT NULL,
+    ev_oop REAL NOT NULL,
+    ev_ip REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS root_actions (
+    flop TEXT NOT NULL,
+    action_index INTEGER NOT NULL,
+    player INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    frequency REAL NOT NULL,
+    PRIMARY KEY (flop, action_index)
+);
+CREATE TABLE IF NOT EXISTS root_grid (
+    flop TEXT NOT NULL,
+    hand TEXT NOT NULL,
+    action_index INTEGER NOT NULL,
+    combos REAL NOT NULL,
+    frequency REAL NOT NULL,
+    PRIMARY KEY (flop, hand, action_index)
+);
+CREATE TABLE IF NOT EXISTS metrics (
+    flop TEXT NOT NULL,
+    name TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    sum REAL NOT NULL,
+    min REAL NOT NULL,
+    max REAL NOT NULL,
+    PRIMARY KEY (flop, name)
+);
+CREATE TABLE IF NOT EXISTS failures (
+    flop TEXT PRIMARY KEY,
+    message TEXT NOT NULL
+);
+";
+
+/// Exporter of solved flops into a SQLite database. See the [module-level documentation] for
+/// the schema.
+///
+/// [module-level documentation]: self
+pub struct SqliteExporter {
+    conn: Connection,
+}
+
+impl SqliteExporter {
+    /// Opens (or creates) the database at `path` and creates the tables if they do not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let conn = Connection::open(path).map_err(|e| {
+            Error::io(
+                io::Error::other(e),
+                &format!("Failed to open database {}", path.display()),
+            )
+        })?;
+        Self::with_connection(conn)
+    }
+
+    /// Creates an in-memory database.
+    pub fn open_in_memory() -> Result<Self, Error> {
+        let conn = Connection::open_in_memory().map_err(db_error)?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, Error> {
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Self { conn })
+    }
+
+    /// Returns the underlying connection, e.g., for running queries.
+    #[inline]
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Adds a solved flop game, i.e., a row of `flops` and the rows of `root_actions` and
+    /// `root_grid`.
+    ///
+    /// Returns an error if the game is not solved, the game does not start on the flop, or the
+    /// database operation fails. The current node of the game is restored after the call.
+    pub fn add_game(&mut self, game: &mut PostFlopGame) -> Result<(), Error> {
+        if game.tree_config().initial_state != BoardState::Flop {
+            return Err(Error::Config("Game must start on the flop".to_string()));
+        }
+
+        let spec = ReportSpec {
+            root_grid: true,
+            aggregates: Vec::new(),
+            runout_evs: false,
+            buckets: false,
+        };
+        let report = game.generate_report(&spec)?;
+        let flop = flop_to_string(game.card_config().flop)?;
+
+        let history = game.history().to_vec();
+        game.back_to_root();
+        game.cache_normalized_weights();
+        let ev = [0, 1].map(|player| {
+            compute_average(
+                &game.expected_values(player),
+                game.normalized_weights(player),
+            )
+        });
+        game.apply_history(&history);
+
+        let tx = self.conn.transaction().map_err(db_error)?;
+        delete_flop(&tx, &flop)?;
+        insert_flop(&tx, &flop, report.exploitability, ev)?;
+
+        let num_actions = report.root_actions.len();
+        let mut sums = vec![0.0; num_actions];
+        let mut total = 0.0;
+        for cell in &report.root_grid {
+            total += cell.combos;
+            for (action, &frequency) in cell.frequencies.iter().enumerate() {
+                sums[action] += cell.combos * frequency;
+                tx.execute(
+                    "INSERT INTO root_grid VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![flop, cell.hand, action as i64, cell.combos, frequency],
+                )
+                .map_err(db_error)?;
+            }
+        }
+
+        for (action, name) in report.root_actions.iter().enumerate() {
+            let frequency = if total > 0.0 {
+                sums[action] / total
+            } else {
+                0.0
+            };
+            tx.execute(
+                "INSERT INTO root_actions VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    flop,
+                    action as i64,
+                    report.root_player as i64,
+                    name,
+                    frequency
+                ],
+            )
+            .map_err(db_error)?;
+        }
+
+        tx.commit().map_err(db_error)
+    }
+
+    /// Adds the results of a batch job, i.e., the rows of `flops`, `metrics`, and `failures`.
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn add_batch_report(&mut self, report: &BatchReport) -> Result<(), Error> {
+        let tx = self.conn.transaction().map_err(db_error)?;
+
+        for result in &report.results {
+            let flop = flop_to_string(result.flop)?;
+            delete_flop(&tx, &flop)?;
+            insert_flop(&tx, &flop, result.exploitability, result.ev)?;
+
+            for (name, summary) in &result.metrics {
+                tx.execute(
+                    "INSERT INTO metrics VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        flop,
+                        name,
+                        summary.count as i64,
+                        summary.sum,
+                        summary.min,
+                        summary.max
+                    ],
+                )
+                .map_err(db_error)?;
+            }
+        }
+
+        for (flop, message) in &report.failures {
+            let flop = flop_to_string(*flop)?;
+            delete_flop(&tx, &flop)?;
+            tx.execute(
+                "INSERT INTO failures VALUES (?1, ?2)",
+                params![flop, message],
+            )
+            .map_err(db_error)?;
+        }
+
+        tx.commit().map_err(db_error)
+    }
+}
+
+/// Converts a database error into an [`Error::Io`].
+#[inline]
+fn db_error(e: rusqlite::Error) -> Error {
+    Error::Io(io::Error::other(e))
+}
+
+/// Deletes the rows of `flop` from all tables.
+fn delete_flop(tx: &Transaction, flop: &str) -> Result<(), Error> {
+    for table in ["flops", "root_actions", "root_grid", "metrics", "failures"] {
+        tx.execute(
+            &format!("DELETE FROM {table} WHERE flop = ?1"),
+            params![flop],
+        )
+        .map_err(db_error)?;
+    }
+    Ok(())
+}
+
+fn insert_flop(
+    tx: &Transaction,
+    flop: &str,
+    exploitability: f32,
+    ev: [f32; 2],
+) -> Result<(), Error> {
+    tx.execute(
+        "INSERT INTO flops VALUES (?1, ?2, ?3, ?4)",
+        params![flop, exploitability as f64, ev[0] as f64, ev[1] as f64],
+    )
+    .map_err(db_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::*;
+    use crate::range::*;
+    use crate::solver::*;
+
+    #[test]
+    fn add_game() {
+        let card_config = CardConfig {
+            range: ["AA,KK,QQ,AK".parse().unwrap(), "KK-TT,AQ".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 30,
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 10, 0.0, false);
+
+        let mut exporter = SqliteExporter::open_in_memory().unwrap();
+        exporter.add_game(&mut game).unwrap();
+        exporter.add_game(&mut game).unwrap(); // replaces the rows
+
+        let conn = exporter.connection();
+        let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0)).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM flops"), 1);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM root_actions"),
+            game.available_actions().len() as i64
+        );
+        assert!(count("SELECT COUNT(*) FROM root_grid") > 0);
+
+        let sum = conn
+            .query_row("SELECT SUM(frequency) FROM root_actions", [], |row| {
+                row.get::<_, f64>(0)
+            })
+            .unwrap();
+        assert!((sum - 1.0).abs() < 1e-4);
+    }
+}
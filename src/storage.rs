@@ -0,0 +1,380 @@
+use std::io;
+use std::ops::{Deref, DerefMut};
+
+#[cfg(feature = "mmap")]
+use std::{
+    fs::{self, OpenOptions},
+    mem::ManuallyDrop,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// A trait representing where the strategies, the regrets, and the counterfactual values of a
+/// [`PostFlopGame`] are stored.
+///
+/// A backend can be plugged into [`PostFlopGame`] with [`PostFlopGame::set_storage_backend`].
+/// The heap ([`HeapStorage`]) is used by default; with the `mmap` feature, `MmapStorage` backs
+/// the storage with files so that trees larger than the physical memory can be solved.
+///
+/// [`PostFlopGame`]: crate::PostFlopGame
+/// [`PostFlopGame::set_storage_backend`]: crate::PostFlopGame::set_storage_backend
+pub trait StorageBackend: Send + Sync {
+    /// Allocates a zero-initialized buffer of `num_bytes` bytes.
+    ///
+    /// The buffer is reinterpreted as arrays of `f32`, `f64`, `u16`, and `i16`, so its address
+    /// must be aligned to 8 bytes (unless it is empty) and it must be at least `num_bytes` bytes
+    /// long. A buffer violating these requirements is rejected with an error of the kind
+    /// [`io::ErrorKind::InvalidData`].
+    fn allocate(&self, num_bytes: usize) -> io::Result<Box<dyn StorageBuffer>>;
+}
+
+/// A trait representing a byte buffer allocated by a [`StorageBackend`].
+pub trait StorageBuffer: Send + Sync {
+    /// Returns the contents of the buffer.
+    fn as_slice(&self) -> &[u8];
+
+    /// Returns the mutable contents of the buffer.
+    fn as_mut_slice(&mut self) -> &mut [u8];
+
+    /// Hints that the bytes `offset..offset + len` will be accessed soon, so that a buffer
+    /// loading its contents on demand can start loading them in the background.
+    ///
+    /// This is called by [`PostFlopGame`] for the nodes below the current node (see
+    /// [`PostFlopGame::set_prefetch_depth`]). The range is within the buffer. The default
+    /// implementation does nothing.
+    ///
+    /// [`PostFlopGame`]: crate::PostFlopGame
+    /// [`PostFlopGame::set_prefetch_depth`]: crate::PostFlopGame::set_prefetch_depth
+    #[inline]
+    fn prefetch(&self, _offset: usize, _len: usize) {}
+}
+
+/// The default storage backend allocating the buffers on the heap.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeapStorage;
+
+/// A storage backend mapping each buffer to a temporary file in the given directory.
+///
+/// The pages are loaded from and written back to the files by the operating system, so the
+/// frequently accessed nodes stay in the page cache while the rest of the tree spills to the
+/// disk. A fast local disk (e.g., NVMe SSD) is strongly recommended, since the solver accesses
+/// the whole storage on every iteration. The files are removed when the buffers are dropped.
+///
+/// On Unix, the buffers implement [`StorageBuffer::prefetch`] with `madvise(MADV_WILLNEED)`, so
+/// the storage of the nodes likely to be viewed next can be read ahead in the background while
+/// browsing a solved game (see [`PostFlopGame::set_prefetch_depth`]).
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+/// use std::sync::Arc;
+///
+/// let spot = CanonicalSpot::by_name("SRP Q9572 river 50bb").unwrap();
+/// let mut game = spot.build().unwrap();
+/// game.set_storage_backend(Arc::new(MmapStorage::new(std::env::temp_dir())))
+///     .unwrap();
+/// game.allocate_memory(false);
+/// # #[cfg(feature = "solver")]
+/// solve(&mut game, 10, 0.0, false);
+/// ```
+///
+/// [`PostFlopGame::set_prefetch_depth`]: crate::PostFlopGame::set_prefetch_depth
+#[cfg(feature = "mmap")]
+#[derive(Debug, Clone)]
+pub struct MmapStorage {
+    dir: PathBuf,
+}
+
+impl StorageBuffer for Vec<u8> {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+/// A heap buffer backed by `u64` words so that its address is aligned to 8 bytes.
+struct HeapBuffer {
+    words: Vec<u64>,
+    num_bytes: usize,
+}
+
+impl HeapBuffer {
+    /// Creates a zero-initialized buffer of `num_bytes` bytes.
+    #[inline]
+    fn zeroed(num_bytes: usize) -> Self {
+        Self {
+            words: vec![0; num_bytes.div_ceil(8)],
+            num_bytes,
+        }
+    }
+}
+
+impl StorageBuffer for HeapBuffer {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.words.as_ptr() as *const u8, self.num_bytes) }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe {
+            std::slice::from_raw_parts_mut(self.words.as_mut_ptr() as *mut u8, self.num_bytes)
+        }
+    }
+}
+
+impl StorageBackend for HeapStorage {
+    #[inline]
+    fn allocate(&self, num_bytes: usize) -> io::Result<Box<dyn StorageBuffer>> {
+        Ok(Box::new(HeapBuffer::zeroed(num_bytes)))
+    }
+}
+
+#[cfg(feature = "mmap")]
+static MMAP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "mmap")]
+struct MmapBuffer {
+    mmap: ManuallyDrop<memmap2::MmapMut>,
+    path: PathBuf,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapStorage {
+    /// Creates a new backend creating the files in `dir`.
+    #[inline]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the directory in which the files are created.
+    #[inline]
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl StorageBackend for MmapStorage {
+    fn allocate(&self, num_bytes: usize) -> io::Result<Box<dyn StorageBuffer>> {
+        // an empty file cannot be mapped
+        if num_bytes == 0 {
+            return Ok(Box::new(Vec::new()));
+        }
+
+        let counter = MMAP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let file_name = format!("postflop-solver-{}-{counter}.bin", std::process::id());
+        let path = self.dir.join(file_name);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+
+        // the extended part of a file is filled with zeros
+        let result = file
+            .set_len(num_bytes as u64)
+            .and_then(|_| unsafe { memmap2::MmapMut::map_mut(&file) });
+
+        match result {
+            Ok(mmap) => Ok(Box::new(MmapBuffer {
+                mmap: ManuallyDrop::new(mmap),
+                path,
+            })),
+            Err(e) => {
+                let _ = fs::remove_file(&path);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl StorageBuffer for MmapBuffer {
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.mmap
+    }
+
+    #[inline]
+    fn prefetch(&self, offset: usize, len: usize) {
+        // the hint is advisory, so the failure is ignored
+        #[cfg(unix)]
+        let _ = self
+            .mmap
+            .advise_range(memmap2::Advice::WillNeed, offset, len);
+        #[cfg(not(unix))]
+        let _ = (offset, len);
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Drop for MmapBuffer {
+    fn drop(&mut self) {
+        // unmap before removing the file (required on Windows)
+        unsafe { ManuallyDrop::drop(&mut self.mmap) };
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A buffer allocated by a [`StorageBackend`], dereferenced as a byte slice.
+pub(crate) struct Storage(Box<dyn StorageBuffer>);
+
+impl Storage {
+    /// Allocates a zero-initialized buffer with `backend`, or on the heap if `backend` is `None`.
+    #[inline]
+    pub(crate) fn allocate(
+        backend: Option<&dyn StorageBackend>,
+        num_bytes: usize,
+    ) -> io::Result<Self> {
+        let storage = backend
+            .unwrap_or(&HeapStorage)
+            .allocate(num_bytes)
+            .map(Self)?;
+
+        // a buffer of a third-party backend is reinterpreted only after these checks
+        let invalid_data = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        if storage.len() < num_bytes {
+            return Err(invalid_data("storage buffer is shorter than requested"));
+        }
+        if !storage.is_aligned() {
+            return Err(invalid_data("storage buffer is not aligned to 8 bytes"));
+        }
+
+        Ok(storage)
+    }
+
+    /// Returns whether the buffer can be reinterpreted as arrays of `f64`.
+    #[inline]
+    fn is_aligned(&self) -> bool {
+        self.is_empty() || self.as_ptr().cast::<u64>().is_aligned()
+    }
+
+    /// Prefetches the `len` bytes starting at `ptr` (see [`StorageBuffer::prefetch`]) if `ptr`
+    /// points into the buffer; the range is truncated at the end of the buffer.
+    #[inline]
+    pub(crate) fn prefetch(&self, ptr: *const u8, len: usize) {
+        let offset = (ptr as usize).wrapping_sub(self.as_ptr() as usize);
+        if offset < self.len() && len > 0 {
+            self.0.prefetch(offset, len.min(self.len() - offset));
+        }
+    }
+}
+
+impl Default for Storage {
+    #[inline]
+    fn default() -> Self {
+        Self(Box::<Vec<u8>>::default())
+    }
+}
+
+impl From<Vec<u8>> for Storage {
+    /// Wraps `vec`, copying it into an aligned buffer if its address is not aligned to 8 bytes.
+    #[inline]
+    fn from(vec: Vec<u8>) -> Self {
+        let storage = if vec.as_ptr().cast::<u64>().is_aligned() {
+            Self(Box::new(vec))
+        } else {
+            let mut buffer = HeapBuffer::zeroed(vec.len());
+            buffer.as_mut_slice().copy_from_slice(&vec);
+            Self(Box::new(buffer))
+        };
+        debug_assert!(storage.is_aligned());
+        storage
+    }
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl DerefMut for Storage {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.0.as_mut_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heap_storage() {
+        let mut storage = Storage::allocate(None, 13).unwrap();
+        assert_eq!(&storage[..], &[0; 13]);
+        assert_eq!(storage.as_ptr() as usize % 8, 0);
+        storage[3] = 1;
+        assert_eq!(storage.iter().map(|&x| x as usize).sum::<usize>(), 1);
+        assert!(Storage::default().is_empty());
+
+        let bytes = (0..17).collect::<Vec<u8>>();
+        let storage = Storage::from(bytes.clone());
+        assert_eq!(storage.as_ptr() as usize % 8, 0);
+        assert_eq!(&storage[..], &bytes[..]);
+    }
+
+    #[test]
+    fn test_invalid_storage() {
+        /// A buffer starting at a misaligned address.
+        struct MisalignedBuffer(Vec<u8>, usize);
+
+        impl StorageBuffer for MisalignedBuffer {
+            fn as_slice(&self) -> &[u8] {
+                let offset = 1 - self.0.as_ptr() as usize % 2;
+                &self.0[offset..offset + self.1]
+            }
+
+            fn as_mut_slice(&mut self) -> &mut [u8] {
+                let offset = 1 - self.0.as_ptr() as usize % 2;
+                &mut self.0[offset..offset + self.1]
+            }
+        }
+
+        /// A backend returning misaligned buffers that are `self.0` bytes shorter than requested.
+        struct InvalidStorage(usize);
+
+        impl StorageBackend for InvalidStorage {
+            fn allocate(&self, num_bytes: usize) -> io::Result<Box<dyn StorageBuffer>> {
+                let len = num_bytes - self.0;
+                Ok(Box::new(MisalignedBuffer(vec![0; len + 1], len)))
+            }
+        }
+
+        for backend in [InvalidStorage(0), InvalidStorage(1)] {
+            let result = Storage::allocate(Some(&backend), 16);
+            assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidData);
+        }
+        assert!(Storage::allocate(Some(&InvalidStorage(0)), 0).is_ok());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_storage() {
+        let backend = MmapStorage::new(std::env::temp_dir());
+        let mut storage = Storage::allocate(Some(&backend), 1 << 20).unwrap();
+        assert!(storage.iter().all(|&x| x == 0));
+        assert_eq!(storage.as_ptr() as usize % 8, 0);
+        storage[12345] = 42;
+        assert_eq!(storage[12345], 42);
+        storage.prefetch(storage[10000..].as_ptr(), 1 << 20);
+        storage.prefetch(std::ptr::null(), 1 << 20);
+        assert_eq!(storage[12345], 42);
+        assert!(Storage::allocate(Some(&backend), 0).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::action_tree::*;
+use std::ops::Range;
+use std::sync::Arc;
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::Decoder,
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+    Decode, Encode,
+};
+
+/// An immutable, compact representation of the structure of an [`ActionTree`].
+///
+/// The nodes are stored in a flat array, and the children of each node are stored contiguously,
+/// so a [`TreeTopology`] holds the actions, players, and amounts of the tree without any values.
+/// The data is shared by reference counting: cloning a [`TreeTopology`] is cheap, and the same
+/// topology can be used to build games on many flops (see [`PostFlopGame::with_topology`]).
+///
+/// As in [`ActionTree`], each chance node has a single child that represents all possible chance
+/// events.
+///
+/// [`PostFlopGame::with_topology`]: crate::PostFlopGame::with_topology
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let tree_config = TreeConfig {
+///     starting_pot: 100,
+///     effective_stack: 100,
+///     flop_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+///     ..Default::default()
+/// };
+///
+/// let action_tree = ActionTree::new(tree_config).unwrap();
+/// let topology = action_tree.topology();
+/// let root = topology.node(0);
+///
+/// assert_eq!(root.player(), Some(0));
+/// assert_eq!(topology.actions(0), vec![Action::Check, Action::Bet(50)]);
+/// assert!(TreeTopology::ptr_eq(&topology, &topology.clone()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TreeTopology {
+    inner: Arc<TopologyInner>,
+}
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+struct TopologyInner {
+    config: TreeConfig,
+    added_lines: Vec<Vec<Action>>,
+    removed_lines: Vec<Vec<Action>>,
+    nodes: Vec<TopologyNode>,
+}
+
+/// A node of [`TreeTopology`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct TopologyNode {
+    action: Action,
+    player: u8,
+    board_state: BoardState,
+    amount: i32,
+    children_start: u32,
+    num_children: u32,
+}
+
+impl TreeTopology {
+    /// Creates a topology from the fields of an action tree.
+    pub(crate) fn new(
+        config: TreeConfig,
+        added_lines: Vec<Vec<Action>>,
+        removed_lines: Vec<Vec<Action>>,
+        root: &ActionTreeNode,
+    ) -> Self {
+        let mut nodes = vec![TopologyNode::new(Action::None, root)];
+        Self::push_children(&mut nodes, 0, root);
+        Self {
+            inner: Arc::new(TopologyInner {
+                config,
+                added_lines,
+                removed_lines,
+                nodes,
+            }),
+        }
+    }
+
+    /// Obtains the configuration of the game tree.
+    #[inline]
+    pub fn config(&self) -> &TreeConfig {
+        &self.inner.config
+    }
+
+    /// Returns a list of added lines.
+    #[inline]
+    pub fn added_lines(&self) -> &[Vec<Action>] {
+        &self.inner.added_lines
+    }
+
+    /// Returns a list of removed lines.
+    #[inline]
+    pub fn removed_lines(&self) -> &[Vec<Action>] {
+        &self.inner.removed_lines
+    }
+
+    /// Returns the number of nodes. The root node has index `0`.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.nodes.len()
+    }
+
+    /// Returns whether the topology has no nodes, i.e., it is created by [`Default`].
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.nodes.is_empty()
+    }
+
+    /// Returns the node at `index`.
+    #[inline]
+    pub fn node(&self, index: usize) -> &TopologyNode {
+        &self.inner.nodes[index]
+    }
+
+    /// Returns the range of the indices of the children of the node at `index`.
+    #[inline]
+    pub fn children(&self, index: usize) -> Range<usize> {
+        let node = &self.inner.nodes[index];
+        let start = node.children_start as usize;
+        start..start + node.num_children as usize
+    }
+
+    /// Returns the actions leading to the children of the node at `index`.
+    #[inline]
+    pub fn actions(&self, index: usize) -> Vec<Action> {
+        self.inner.nodes[self.children(index)]
+            .iter()
+            .map(|child| child.action)
+            .collect()
+    }
+
+    /// Returns whether the two topologies share the same data.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.inner, &other.inner)
+    }
+
+    /// Returns whether there is a non-terminal node without children.
+    #[inline]
+    pub fn has_invalid_terminals(&self) -> bool {
+        self.inner
+            .nodes
+            .iter()
+            .any(|node| !node.is_terminal() && node.num_children == 0)
+    }
+
+    /// Returns the number of action nodes of [flop, turn, river].
+    pub(crate) fn count_num_action_nodes(&self) -> [u64; 3] {
+        let mut ret = [0, 0, 0];
+        self.count_num_action_nodes_recursive(0, 0, &mut ret);
+        if ret[1] == 0 {
+            ret = [0, 0, ret[0]];
+        } else if ret[2] == 0 {
+            ret = [0, ret[0], ret[1]];
+        }
+        ret
+    }
+
+    fn count_num_action_nodes_recursive(&self, index: usize, street: usize, count: &mut [u64; 3]) {
+        count[street] += 1;
+        let node = self.node(index);
+        if node.is_terminal() {
+            // do nothing
+        } else if node.is_chance() {
+            let child = node.children_start as usize;
+            self.count_num_action_nodes_recursive(child, street + 1, count);
+        } else {
+            for child in self.children(index) {
+                self.count_num_action_nodes_recursive(child, street, count);
+            }
+        }
+    }
+
+    /// Appends the children of `node` to `nodes`, followed by their descendants.
+    fn push_children(nodes: &mut Vec<TopologyNode>, index: usize, node: &ActionTreeNode) {
+        let start = nodes.len();
+        nodes[index].children_start = start as u32;
+        nodes[index].num_children = node.children.len() as u32;
+
+        for (&action, child) in node.actions.iter().zip(node.children.iter()) {
+            nodes.push(TopologyNode::new(action, &child.lock()));
+        }
+
+        for (i, child) in node.children.iter().enumerate() {
+            Self::push_children(nodes, start + i, &child.lock());
+        }
+    }
+}
+
+impl TopologyNode {
+    #[inline]
+    fn new(action: Action, node: &ActionTreeNode) -> Self {
+        Self {
+            action,
+            player: node.player,
+            board_state: node.board_state,
+            amount: node.amount,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the action leading to this node (`Action::None` for the root node).
+    #[inline]
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    /// Returns the player to act (0 = OOP, 1 = IP), or `None` if this node is a terminal or a
+    /// chance node.
+    #[inline]
+    pub fn player(&self) -> Option<usize> {
+        if self.is_terminal() || self.is_chance() {
+            None
+        } else {
+            Some(self.player as usize)
+        }
+    }
+
+    /// Returns the street of this node.
+    #[inline]
+    pub fn board_state(&self) -> BoardState {
+        self.board_state
+    }
+
+    /// Returns the bet amount of each player on the current street.
+    #[inline]
+    pub fn amount(&self) -> i32 {
+        self.amount
+    }
+
+    /// Returns the number of children.
+    #[inline]
+    pub fn num_children(&self) -> usize {
+        self.num_children as usize
+    }
+
+    /// Returns whether this node is a terminal node.
+    #[inline]
+    pub fn is_terminal(&self) -> bool {
+        self.player & PLAYER_TERMINAL_FLAG != 0
+    }
+
+    /// Returns whether this node is a chance node.
+    #[inline]
+    pub fn is_chance(&self) -> bool {
+        self.player & PLAYER_CHANCE_FLAG != 0
+    }
+
+    /// Returns the raw player flags.
+    #[inline]
+    pub(crate) fn player_flags(&self) -> u8 {
+        self.player
+    }
+}
+
+impl PartialEq for TreeTopology {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        Self::ptr_eq(self, other)
+            || (self.inner.config == other.inner.config
+                && self.inner.added_lines == other.inner.added_lines
+                && self.inner.removed_lines == other.inner.removed_lines
+                && self.inner.nodes == other.inner.nodes)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Encode for TreeTopology {
+    #[inline]
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.inner.encode(encoder)
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Decode for TreeTopology {
+    #[inline]
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        Ok(Self {
+            inner: Arc::new(TopologyInner::decode(decoder)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn topology_matches_tree() {
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 200,
+            turn_bet_sizes: [("50%", "2x").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("100%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let mut action_tree = ActionTree::new(tree_config).unwrap();
+        let topology = action_tree.topology();
+        assert!(!topology.has_invalid_terminals());
+        assert_eq!(topology.config(), action_tree.config());
+
+        // walks down both trees along the first action of each node; `ActionTree` skips the chance
+        // nodes when navigating
+        let mut index = 0;
+        loop {
+            while topology.node(index).is_chance() {
+                assert_eq!(topology.node(index).num_children(), 1);
+                index = topology.children(index).start;
+            }
+            let node = topology.node(index);
+            assert_eq!(node.is_terminal(), action_tree.is_terminal_node());
+            if node.is_terminal() {
+                break;
+            }
+            let actions = topology.actions(index);
+            assert_eq!(actions, action_tree.available_actions());
+            action_tree.play(actions[0]).unwrap();
+            index = topology.children(index).start;
+            assert_eq!(topology.node(index).action(), actions[0]);
+        }
+
+        let shared = topology.clone();
+        assert!(TreeTopology::ptr_eq(&topology, &shared));
+        assert!(!TreeTopology::ptr_eq(&topology, &action_tree.topology()));
+        assert_eq!(topology, action_tree.topology());
+    }
+}
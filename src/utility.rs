@@ -3,18 +3,28 @@ use crate::mutex_like::*;
 use crate::sliceop::*;
 use std::mem::{self, MaybeUninit};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
 
 #[cfg(feature = "custom-alloc")]
 use crate::alloc::*;
 
+#[cfg(all(feature = "solver", not(feature = "custom-alloc")))]
+use crate::pool::*;
+
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
 /// Executes `op` for each child potentially in parallel.
 #[cfg(feature = "rayon")]
 #[inline]
-pub(crate) fn for_each_child<T: GameNode, OP: Fn(usize) + Sync + Send>(node: &T, op: OP) {
-    if node.enable_parallelization() {
+pub(crate) fn for_each_child<T: Game, OP: Fn(usize) + Sync + Send>(
+    game: &T,
+    node: &T::Node,
+    op: OP,
+) {
+    if game.enable_parallelization(node) {
         node.action_indices().into_par_iter().for_each(op);
     } else {
         node.action_indices().for_each(op);
@@ -24,7 +34,11 @@ pub(crate) fn for_each_child<T: GameNode, OP: Fn(usize) + Sync + Send>(node: &T,
 /// Executes `op` for each child.
 #[cfg(not(feature = "rayon"))]
 #[inline]
-pub(crate) fn for_each_child<T: GameNode, OP: Fn(usize) + Sync + Send>(node: &T, op: OP) {
+pub(crate) fn for_each_child<T: Game, OP: Fn(usize) + Sync + Send>(
+    _game: &T,
+    node: &T::Node,
+    op: OP,
+) {
     node.action_indices().for_each(op);
 }
 
@@ -57,22 +71,72 @@ pub(crate) fn vec_memory_usage<T>(vec: &Vec<T>) -> u64 {
     vec.capacity() as u64 * mem::size_of::<T>() as u64
 }
 
+/// Summation algorithm of the aggregations over private hands (see [`compute_average_with`]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Summation {
+    /// Plain summation in 64-bit floating point. The rounding errors accumulate with the number
+    /// of terms and depend on their order.
+    Naive,
+
+    /// Compensated (Kahan-Babuska-Neumaier) summation in 64-bit floating point. The result is
+    /// accurate to the last few bits regardless of the number and the order of the terms. This
+    /// is the default and is used by all EV and equity aggregations of the crate.
+    #[default]
+    Compensated,
+}
+
+impl Summation {
+    /// Sums up the given values.
+    #[inline]
+    pub fn sum<I: IntoIterator<Item = f64>>(self, values: I) -> f64 {
+        match self {
+            Self::Naive => values.into_iter().sum(),
+            Self::Compensated => {
+                let mut sum = 0.0f64;
+                let mut compensation = 0.0f64;
+                for x in values {
+                    let t = sum + x;
+                    if sum.abs() >= x.abs() {
+                        compensation += (sum - t) + x;
+                    } else {
+                        compensation += (x - t) + sum;
+                    }
+                    sum = t;
+                }
+                sum + compensation
+            }
+        }
+    }
+
+    /// Computes the inner product of `values` and `weights`.
+    #[inline]
+    pub fn weighted_sum(self, values: &[f32], weights: &[f32]) -> f64 {
+        self.sum(
+            values
+                .iter()
+                .zip(weights)
+                .map(|(&v, &w)| v as f64 * w as f64),
+        )
+    }
+}
+
 /// Computes the average with given weights.
 #[inline]
 pub fn compute_average(slice: &[f32], weights: &[f32]) -> f32 {
-    let mut weight_sum = 0.0;
-    let mut value_sum = 0.0;
-    for (&v, &w) in slice.iter().zip(weights.iter()) {
-        weight_sum += w as f64;
-        value_sum += v as f64 * w as f64;
-    }
+    compute_average_with(slice, weights, Summation::default())
+}
+
+/// Computes the average with given weights using the given summation algorithm.
+#[inline]
+pub fn compute_average_with(slice: &[f32], weights: &[f32], summation: Summation) -> f32 {
+    let weight_sum = summation.sum(weights.iter().map(|&w| w as f64));
+    let value_sum = summation.weighted_sum(slice, weights);
     (value_sum / weight_sum) as f32
 }
 
 #[inline]
 fn weighted_sum(values: &[f32], weights: &[f32]) -> f32 {
-    let f = |sum: f64, (&v, &w): (&f32, &f32)| sum + v as f64 * w as f64;
-    values.iter().zip(weights).fold(0.0, f) as f32
+    Summation::Compensated.weighted_sum(values, weights) as f32
 }
 
 /// Obtains the maximum absolute value of the given slice.
@@ -227,6 +291,26 @@ pub(crate) fn encode_unsigned_slice(dst: &mut [u16], slice: &[f32]) -> f32 {
     scale
 }
 
+/// Expands the rows of the values of the stored private hands into the rows of the values of all
+/// private hands, where `stored_indices` is the return value of [`Game::stored_hand_indices`].
+#[inline]
+pub(crate) fn expand_stored_hands<T: Copy>(
+    values: Vec<T>,
+    stored_indices: &[u16],
+    num_stored_hands: usize,
+) -> Vec<T> {
+    if stored_indices.is_empty() {
+        return values;
+    }
+
+    let num_rows = values.len() / num_stored_hands;
+    let mut ret = Vec::with_capacity(num_rows * stored_indices.len());
+    for row in values.chunks_exact(num_stored_hands) {
+        ret.extend(stored_indices.iter().map(|&index| row[index as usize]));
+    }
+    ret
+}
+
 /// Applies the given swap to the given slice.
 #[inline]
 pub(crate) fn apply_swap<T>(slice: &mut [T], swap_list: &[(u16, u16)]) {
@@ -252,40 +336,178 @@ pub fn finalize<T: Game>(game: &mut T) {
     }
 
     // compute the expected values and save them
-    for player in 0..2 {
-        let mut cfvalues = Vec::with_capacity(game.num_private_hands(player));
-        compute_cfvalue_recursive(
-            cfvalues.spare_capacity_mut(),
-            game,
-            &mut game.root(),
-            player,
-            game.initial_weights(player ^ 1),
-            true,
-        );
-    }
+    save_cfvalues(game);
 
     // set the game solved
     game.set_solved();
+}
+
+/// Computes the counterfactual values of the current strategy and saves them into the storage.
+pub(crate) fn save_cfvalues<T: Game>(game: &T) {
+    for player in 0..2 {
+        let cfreach = game.initial_weights(player ^ 1);
+        save_cfvalues_subtree(game, &mut game.root(), player, cfreach, &no_memo);
+    }
+
+    free_cfvalue_buffer();
+}
+
+/// Computes the counterfactual values of `player` in the subtree rooted at `node`, saves them
+/// into the storage, and returns the counterfactual values at `node`.
+///
+/// `cfreach` is the reach probabilities of the opponent at `node`. If `memo` returns `true` for a
+/// node, the counterfactual values written by `memo` are used instead of traversing the subtree.
+pub(crate) fn save_cfvalues_subtree<T: Game, M>(
+    game: &T,
+    node: &mut T::Node,
+    player: usize,
+    cfreach: &[f32],
+    memo: &M,
+) -> Vec<f32>
+where
+    M: Fn(&T::Node, usize, &mut [MaybeUninit<f32>]) -> bool + Sync,
+{
+    let num_hands = game.num_private_hands(player);
+    let mut cfvalues = Vec::with_capacity(num_hands);
+    compute_cfvalue_recursive(
+        cfvalues.spare_capacity_mut(),
+        game,
+        node,
+        player,
+        cfreach,
+        true,
+        memo,
+    );
+    unsafe { cfvalues.set_len(num_hands) };
+    cfvalues
+}
 
-    // free buffer
+/// Frees the buffer used for computing the counterfactual values.
+#[inline]
+pub(crate) fn free_cfvalue_buffer() {
     #[cfg(all(feature = "custom-alloc", feature = "rayon"))]
     rayon::broadcast(|_| free_custom_alloc_buffer());
     #[cfg(all(feature = "custom-alloc", not(feature = "rayon")))]
     free_custom_alloc_buffer();
+    #[cfg(all(feature = "solver", not(feature = "custom-alloc"), feature = "rayon"))]
+    rayon::broadcast(|_| free_buffer_pool());
+    #[cfg(all(
+        feature = "solver",
+        not(feature = "custom-alloc"),
+        not(feature = "rayon")
+    ))]
+    free_buffer_pool();
 }
 
+/// The memo of [`save_cfvalues_subtree`] that never supplies the counterfactual values.
+#[inline]
+pub(crate) fn no_memo<N>(_node: &N, _player: usize, _result: &mut [MaybeUninit<f32>]) -> bool {
+    false
+}
+
+/// The interval of the calls of the callback of [`compute_exploitability_with_callback`].
+const CALLBACK_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Computes the exploitability of the current strategy.
+///
+/// The exploitability is the average over the players of the gains of the best response against
+/// the current strategy of the opponent. The expected values of the best responses can be
+/// obtained with [`compute_mes_ev`], and their per-hand values with
+/// [`compute_best_response_cfvalues`].
 #[inline]
 pub fn compute_exploitability<T: Game>(game: &T) -> f32 {
     if !game.is_ready() && !game.is_solved() {
         panic!("Game is not ready");
     }
 
-    let mes_ev = compute_mes_ev(game);
+    exploitability_with_memo(game, &no_memo)
+}
+
+/// Computes the exploitability of the current strategy like [`compute_exploitability`], reporting
+/// the progress to `callback` and allowing the computation to be aborted.
+///
+/// `callback` is called in the calling thread about every 100 milliseconds with the progress in
+/// percent (0-100), while the traversal runs in a helper thread (using the global thread pool of
+/// `rayon` if the `rayon` feature is enabled). Returning `false` from `callback` aborts the
+/// computation, in which case `None` is returned. The last call of `callback` is made with 100
+/// after the computation completes, and its return value is ignored.
+///
+/// Panics if the game is not ready.
+pub fn compute_exploitability_with_callback<T, F>(game: &T, mut callback: F) -> Option<f32>
+where
+    T: Game,
+    F: FnMut(u8) -> bool,
+{
+    if !game.is_ready() && !game.is_solved() {
+        panic!("Game is not ready");
+    }
+
+    let num_passes = if game.is_raked() { 4 } else { 2 };
+    let num_visits = game.num_nodes_hint() * num_passes;
+
+    let is_aborted = AtomicBool::new(false);
+    let visited = AtomicUsize::new(0);
+
+    // aborts the traversal by supplying dummy values
+    let memo = |_: &T::Node, _: usize, result: &mut [MaybeUninit<f32>]| {
+        if is_aborted.load(Ordering::Relaxed) {
+            result.iter_mut().for_each(|r| {
+                r.write(0.0);
+            });
+            true
+        } else {
+            visited.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    };
+
+    let caller = thread::current();
+    let exploitability = thread::scope(|scope| {
+        let handle = scope.spawn(|| {
+            let ret = exploitability_with_memo(game, &memo);
+            caller.unpark();
+            ret
+        });
+
+        while !handle.is_finished() {
+            thread::park_timeout(CALLBACK_INTERVAL);
+            if handle.is_finished() {
+                break;
+            }
+
+            let percent = match num_visits {
+                0 => 0,
+                n => (100 * visited.load(Ordering::Relaxed) / n).min(99) as u8,
+            };
+
+            if !is_aborted.load(Ordering::Relaxed) && !callback(percent) {
+                is_aborted.store(true, Ordering::Relaxed);
+            }
+        }
+
+        handle.join().unwrap()
+    });
+
+    free_cfvalue_buffer();
+
+    if is_aborted.into_inner() {
+        None
+    } else {
+        callback(100);
+        Some(exploitability)
+    }
+}
+
+/// Computes the exploitability, passing `memo` to the traversals (see [`save_cfvalues_subtree`]).
+fn exploitability_with_memo<T: Game, M>(game: &T, memo: &M) -> f32
+where
+    M: Fn(&T::Node, usize, &mut [MaybeUninit<f32>]) -> bool + Sync,
+{
+    let mes_ev = mes_ev_with_memo(game, memo);
     if !game.is_raked() {
         (mes_ev[0] + mes_ev[1]) * 0.5
     } else {
-        let current_ev = compute_current_ev(game);
+        let current_ev = current_ev_with_memo(game, memo);
         ((mes_ev[0] - current_ev[0]) + (mes_ev[1] - current_ev[1])) * 0.5
     }
 }
@@ -300,6 +522,13 @@ pub fn compute_current_ev<T: Game>(game: &T) -> [f32; 2] {
         panic!("Game is not ready");
     }
 
+    current_ev_with_memo(game, &no_memo)
+}
+
+fn current_ev_with_memo<T: Game, M>(game: &T, memo: &M) -> [f32; 2]
+where
+    M: Fn(&T::Node, usize, &mut [MaybeUninit<f32>]) -> bool + Sync,
+{
     let mut cfvalues = [
         Vec::with_capacity(game.num_private_hands(0)),
         Vec::with_capacity(game.num_private_hands(1)),
@@ -315,6 +544,7 @@ pub fn compute_current_ev<T: Game>(game: &T) -> [f32; 2] {
             player,
             reach[player ^ 1],
             false,
+            memo,
         );
         unsafe { cfvalues[player].set_len(game.num_private_hands(player)) };
     }
@@ -333,43 +563,104 @@ pub fn compute_mes_ev<T: Game>(game: &T) -> [f32; 2] {
         panic!("Game is not ready");
     }
 
-    let mut cfvalues = [
-        Vec::with_capacity(game.num_private_hands(0)),
-        Vec::with_capacity(game.num_private_hands(1)),
-    ];
-
-    let reach = [game.initial_weights(0), game.initial_weights(1)];
+    mes_ev_with_memo(game, &no_memo)
+}
 
-    for player in 0..2 {
-        compute_best_cfv_recursive(
-            cfvalues[player].spare_capacity_mut(),
-            game,
-            &game.root(),
-            player,
-            reach[player ^ 1],
-        );
-        unsafe { cfvalues[player].set_len(game.num_private_hands(player)) };
+/// Computes the counterfactual values of each private hand of `player` at the root node when
+/// `player` plays the best response against the current strategy of the opponent.
+///
+/// The values are not weighted by the initial weights of `player`, so the weighted sum of the
+/// return value is equal to the corresponding element of [`compute_mes_ev`]. As with
+/// [`compute_mes_ev`], the bias, i.e., (starting pot) / 2, is already subtracted.
+#[inline]
+pub fn compute_best_response_cfvalues<T: Game>(game: &T, player: usize) -> Vec<f32> {
+    if !game.is_ready() && !game.is_solved() {
+        panic!("Game is not ready");
     }
 
+    let cfvalues = compute_best_cfvalues(game, player, &|_, _| {});
+    let num_stored_hands = game.num_private_hands(player);
+    expand_stored_hands(cfvalues, game.stored_hand_indices(player), num_stored_hands)
+}
+
+fn mes_ev_with_memo<T: Game, M>(game: &T, memo: &M) -> [f32; 2]
+where
+    M: Fn(&T::Node, usize, &mut [MaybeUninit<f32>]) -> bool + Sync,
+{
+    let cfvalues = [
+        compute_best_cfvalues_with_memo(game, 0, &|_, _| {}, memo),
+        compute_best_cfvalues_with_memo(game, 1, &|_, _| {}, memo),
+    ];
+
+    let reach = [game.initial_weights(0), game.initial_weights(1)];
     let get_sum = |player: usize| weighted_sum(&cfvalues[player], reach[player]);
     [get_sum(0), get_sum(1)]
 }
 
+/// Computes the counterfactual values of the best response of `player` at the root node.
+///
+/// `record` is called at every decision node of `player` with the counterfactual values of each
+/// action, from which the best response action can be recovered.
+pub(crate) fn compute_best_cfvalues<T: Game, R: Fn(&T::Node, &[f32]) + Sync>(
+    game: &T,
+    player: usize,
+    record: &R,
+) -> Vec<f32> {
+    compute_best_cfvalues_with_memo(game, player, record, &no_memo)
+}
+
+/// Computes the counterfactual values of the best response of `player` at the root node, using
+/// the values supplied by `memo` (see [`save_cfvalues_subtree`]).
+pub(crate) fn compute_best_cfvalues_with_memo<T: Game, R, M>(
+    game: &T,
+    player: usize,
+    record: &R,
+    memo: &M,
+) -> Vec<f32>
+where
+    R: Fn(&T::Node, &[f32]) + Sync,
+    M: Fn(&T::Node, usize, &mut [MaybeUninit<f32>]) -> bool + Sync,
+{
+    let mut cfvalues = Vec::with_capacity(game.num_private_hands(player));
+    compute_best_cfv_recursive(
+        cfvalues.spare_capacity_mut(),
+        game,
+        &game.root(),
+        player,
+        game.initial_weights(player ^ 1),
+        record,
+        memo,
+    );
+    unsafe { cfvalues.set_len(game.num_private_hands(player)) };
+    cfvalues
+}
+
 /// The recursive helper function for computing the counterfactual values of the given strategy.
-fn compute_cfvalue_recursive<T: Game>(
+fn compute_cfvalue_recursive<T: Game, M>(
     result: &mut [MaybeUninit<f32>],
     game: &T,
     node: &mut T::Node,
     player: usize,
     cfreach: &[f32],
     save_cfvalues: bool,
-) {
+    memo: &M,
+) where
+    M: Fn(&T::Node, usize, &mut [MaybeUninit<f32>]) -> bool + Sync,
+{
+    // precomputed subtree
+    if memo(node, player, result) {
+        return;
+    }
+
     // terminal node
     if node.is_terminal() {
         game.evaluate(result, node, player, cfreach);
         return;
     }
 
+    // the unallocated node has no storage to save the counterfactual values
+    let save_cfvalues = save_cfvalues && game.is_node_allocated(node);
+
     let num_actions = node.num_actions();
     let num_hands = result.len();
 
@@ -394,7 +685,7 @@ fn compute_cfvalue_recursive<T: Game>(
         unsafe { cfreach_updated.set_len(cfreach.len()) };
 
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             compute_cfvalue_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -402,6 +693,7 @@ fn compute_cfvalue_recursive<T: Game>(
                 player,
                 &cfreach_updated,
                 save_cfvalues,
+                memo,
             );
         });
 
@@ -441,7 +733,7 @@ fn compute_cfvalue_recursive<T: Game>(
         // save the counterfactual values
         if save_cfvalues && node.cfvalue_storage_player() == Some(player) {
             let result = unsafe { &*(result as *const _ as *const [f32]) };
-            if game.is_compression_enabled() {
+            if game.is_node_compressed(node) {
                 let cfv_scale = encode_signed_slice(node.cfvalues_chance_compressed_mut(), result);
                 node.set_cfvalue_chance_scale(cfv_scale);
             } else {
@@ -452,7 +744,7 @@ fn compute_cfvalue_recursive<T: Game>(
     // player node
     else if node.player() == player {
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             compute_cfvalue_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -460,18 +752,19 @@ fn compute_cfvalue_recursive<T: Game>(
                 player,
                 cfreach,
                 save_cfvalues,
+                memo,
             );
         });
 
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut strategy = if game.is_compression_enabled() {
+        let mut strategy = if game.is_node_compressed(node) {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut strategy = if game.is_compression_enabled() {
+        let mut strategy = if game.is_node_compressed(node) {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -488,7 +781,7 @@ fn compute_cfvalue_recursive<T: Game>(
 
         // save the counterfactual values
         if save_cfvalues {
-            if game.is_compression_enabled() {
+            if game.is_node_compressed(node) {
                 let cfv_scale = encode_signed_slice(node.cfvalues_compressed_mut(), &cfv_actions);
                 node.set_cfvalue_scale(cfv_scale);
             } else {
@@ -506,17 +799,18 @@ fn compute_cfvalue_recursive<T: Game>(
             player,
             cfreach,
             save_cfvalues,
+            memo,
         );
     } else {
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_node_compressed(node) {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_node_compressed(node) {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -533,7 +827,7 @@ fn compute_cfvalue_recursive<T: Game>(
         });
 
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             compute_cfvalue_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
@@ -541,6 +835,7 @@ fn compute_cfvalue_recursive<T: Game>(
                 player,
                 row(&cfreach_actions, action, row_size),
                 save_cfvalues,
+                memo,
             );
         });
 
@@ -553,7 +848,7 @@ fn compute_cfvalue_recursive<T: Game>(
     // save the counterfactual values for IP
     if save_cfvalues && node.has_cfvalues_ip() && player == 1 {
         let result = unsafe { &*(result as *const _ as *const [f32]) };
-        if game.is_compression_enabled() {
+        if game.is_node_compressed(node) {
             let cfv_scale = encode_signed_slice(node.cfvalues_ip_compressed_mut(), result);
             node.set_cfvalue_ip_scale(cfv_scale);
         } else {
@@ -563,13 +858,23 @@ fn compute_cfvalue_recursive<T: Game>(
 }
 
 /// The recursive helper function for computing the counterfactual values of best response.
-fn compute_best_cfv_recursive<T: Game>(
+fn compute_best_cfv_recursive<T: Game, R, M>(
     result: &mut [MaybeUninit<f32>],
     game: &T,
     node: &T::Node,
     player: usize,
     cfreach: &[f32],
-) {
+    record: &R,
+    memo: &M,
+) where
+    R: Fn(&T::Node, &[f32]) + Sync,
+    M: Fn(&T::Node, usize, &mut [MaybeUninit<f32>]) -> bool + Sync,
+{
+    // precomputed subtree
+    if memo(node, player, result) {
+        return;
+    }
+
     // terminal node
     if node.is_terminal() {
         game.evaluate(result, node, player, cfreach);
@@ -582,7 +887,7 @@ fn compute_best_cfv_recursive<T: Game>(
     // simply recurse when the number of actions is one
     if num_actions == 1 && !node.is_chance() {
         let child = &node.play(0);
-        compute_best_cfv_recursive(result, game, child, player, cfreach);
+        compute_best_cfv_recursive(result, game, child, player, cfreach, record, memo);
         return;
     }
 
@@ -607,13 +912,15 @@ fn compute_best_cfv_recursive<T: Game>(
         unsafe { cfreach_updated.set_len(cfreach.len()) };
 
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             compute_best_cfv_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
                 &node.play(action),
                 player,
                 &cfreach_updated,
+                record,
+                memo,
             )
         });
 
@@ -653,19 +960,22 @@ fn compute_best_cfv_recursive<T: Game>(
     // player node
     else if node.player() == player {
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             compute_best_cfv_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
                 &node.play(action),
                 player,
                 cfreach,
+                record,
+                memo,
             )
         });
 
         let locking = game.locking_strategy(node);
         let mut cfv_actions = cfv_actions.lock();
         unsafe { cfv_actions.set_len(num_actions * num_hands) };
+        record(node, &cfv_actions);
 
         if locking.is_empty() {
             // compute element-wise maximum (take the best response)
@@ -679,13 +989,13 @@ fn compute_best_cfv_recursive<T: Game>(
     else {
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_node_compressed(node) {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_node_compressed(node) {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -702,13 +1012,15 @@ fn compute_best_cfv_recursive<T: Game>(
         });
 
         // compute the counterfactual values of each action
-        for_each_child(node, |action| {
+        for_each_child(game, node, |action| {
             compute_best_cfv_recursive(
                 row_mut(cfv_actions.lock().spare_capacity_mut(), action, num_hands),
                 game,
                 &node.play(action),
                 player,
                 row(&cfreach_actions, action, row_size),
+                record,
+                memo,
             );
         });
 
@@ -827,3 +1139,33 @@ pub(crate) fn apply_locking_strategy(dst: &mut [f32], locking: &[f32]) {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compensated_summation() {
+        let values = [1.0, 1e100, 1.0, -1e100];
+        assert_eq!(Summation::Naive.sum(values), 0.0);
+        assert_eq!(Summation::Compensated.sum(values), 2.0);
+
+        // the result does not depend on the order of the terms
+        let values = (0..10000)
+            .map(|i| 0.1f32 * (i % 7) as f32)
+            .collect::<Vec<_>>();
+        let weights = (0..10000)
+            .map(|i| 1.0 / (1 + i % 13) as f32)
+            .collect::<Vec<_>>();
+        let forward = compute_average(&values, &weights);
+        let mut reversed = values
+            .iter()
+            .zip(&weights)
+            .map(|(&v, &w)| (v, w))
+            .rev()
+            .collect::<Vec<_>>();
+        reversed.rotate_left(1234);
+        let (values, weights): (Vec<f32>, Vec<f32>) = reversed.into_iter().unzip();
+        assert_eq!(forward, compute_average(&values, &weights));
+    }
+}
@@ -0,0 +1,235 @@
+use crate::error::*;
+use crate::game::GameOperation;
+use crate::interface::*;
+
+/// Runs structural checks on an implementation of [`Game`], so that a violation of the contract
+/// expected by the solver is reported before it shows up as NaNs or a panic while solving.
+///
+/// This is intended as a smoke test for custom games (e.g., the Leduc hold'em implementation in
+/// the `tests` directory). The whole tree is traversed from the root, and the following are
+/// checked:
+///
+/// - The numbers of private hands are positive, and the initial weights have the same lengths
+///   and are finite and non-negative.
+/// - Terminal nodes are not chance nodes and have no actions, and the counterfactual values
+///   computed by [`Game::evaluate`] are finite. Other nodes have at least one action.
+/// - The player of each decision node is `0` or `1`.
+/// - The storage of each allocated decision node (the strategy and the cumulative regrets,
+///   compressed or 64-bit if enabled) has `num_actions * num_private_hands(player)` elements,
+///   and so does the locking strategy if any.
+/// - The chance factor of each chance node is positive and does not exceed the number of
+///   actions plus the number of isomorphic chances.
+/// - The isomorphic chances refer to existing actions, and their swap lists consist of disjoint
+///   pairs of distinct hands with the same initial weights.
+///
+/// Returns an error describing the first violation found, including the history of the node in
+/// action indices from the root. Returns an error if the game is not ready to be solved.
+///
+/// # Examples
+/// ```ignore
+/// let mut game = LeducGame::new(false);
+/// verify_game_impl(&mut game).unwrap();
+/// solve(&mut game, 1000, 1e-3, false);
+/// ```
+pub fn verify_game_impl<T: Game>(game: &mut T) -> Result<(), Error> {
+    game.check_operation(GameOperation::Solve)?;
+
+    for player in 0..2 {
+        let num_hands = game.num_private_hands(player);
+        if num_hands == 0 {
+            return Err(Error::Verification(format!(
+                "Player {player} has no private hands"
+            )));
+        }
+
+        let weights = game.initial_weights(player);
+        if weights.len() != num_hands {
+            return Err(Error::Verification(format!(
+                "Initial weights of player {player} have {} elements, expected {num_hands}",
+                weights.len()
+            )));
+        }
+
+        if let Some(&weight) = weights.iter().find(|&&w| w < 0.0 || !w.is_finite()) {
+            return Err(Error::Verification(format!(
+                "Initial weights of player {player} contain an invalid weight: {weight}"
+            )));
+        }
+    }
+
+    verify_node_recursive(game, &game.root(), &mut Vec::new())
+}
+
+fn verify_node_recursive<T: Game>(
+    game: &T,
+    node: &T::Node,
+    history: &mut Vec<usize>,
+) -> Result<(), Error> {
+    verify_node(game, node).map_err(|e| Error::Verification(format!("Node {history:?}: {e}")))?;
+
+    for action in 0..node.num_actions() {
+        history.push(action);
+        verify_node_recursive(game, &node.play(action), history)?;
+        history.pop();
+    }
+
+    Ok(())
+}
+
+/// Checks the contract of a single node.
+fn verify_node<T: Game>(game: &T, node: &T::Node) -> Result<(), Error> {
+    let num_actions = node.num_actions();
+
+    if node.is_terminal() {
+        if node.is_chance() {
+            return Err(Error::Verification(
+                "Terminal node is also a chance node".to_string(),
+            ));
+        }
+
+        if num_actions != 0 {
+            return Err(Error::Verification(format!(
+                "Terminal node has {num_actions} actions"
+            )));
+        }
+
+        for player in 0..2 {
+            let num_hands = game.num_private_hands(player);
+            let mut result = Vec::with_capacity(num_hands);
+            let cfreach = game.initial_weights(player ^ 1);
+            game.evaluate(result.spare_capacity_mut(), node, player, cfreach);
+            unsafe { result.set_len(num_hands) };
+            if let Some(hand) = result.iter().position(|x| !x.is_finite()) {
+                return Err(Error::Verification(format!(
+                    "Evaluation of player {player} is not finite: hand {hand} has {}",
+                    result[hand]
+                )));
+            }
+        }
+
+        return Ok(());
+    }
+
+    if num_actions == 0 {
+        return Err(Error::Verification(
+            "Non-terminal node has no actions".to_string(),
+        ));
+    }
+
+    if node.is_chance() {
+        let isomorphic_chances = game.isomorphic_chances(node);
+        let num_chances = num_actions + isomorphic_chances.len();
+        let chance_factor = game.chance_factor(node);
+        if chance_factor == 0 || chance_factor > num_chances {
+            return Err(Error::Verification(format!(
+                "Chance factor {chance_factor} is out of range (expected 1..={num_chances})"
+            )));
+        }
+
+        for (i, &index) in isomorphic_chances.iter().enumerate() {
+            if index as usize >= num_actions {
+                return Err(Error::Verification(format!(
+                    "Isomorphic chance {i} refers to action {index}, but the node has \
+                     {num_actions} actions"
+                )));
+            }
+
+            let swap = game.isomorphic_swap(node, i);
+            for (player, swap_list) in swap.iter().enumerate() {
+                verify_swap_list(game, player, swap_list).map_err(|e| {
+                    Error::Verification(format!(
+                        "Swap list of isomorphic chance {i} for player {player}: {e}"
+                    ))
+                })?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    let player = node.player();
+    if player >= 2 {
+        return Err(Error::Verification(format!(
+            "Decision node has an invalid player: {player}"
+        )));
+    }
+
+    let num_hands = game.num_private_hands(player);
+    let expected = num_actions * num_hands;
+    let check_len = |name: &str, len: usize| {
+        if len == expected {
+            Ok(())
+        } else {
+            Err(Error::Verification(format!(
+                "{name} has {len} elements, expected {expected} \
+                 ({num_actions} actions x {num_hands} hands)"
+            )))
+        }
+    };
+
+    if game.is_node_allocated(node) {
+        if game.is_node_compressed(node) {
+            check_len("Compressed strategy", node.strategy_compressed().len())?;
+            check_len("Compressed regrets", node.regrets_compressed().len())?;
+        } else {
+            check_len("Strategy", node.strategy().len())?;
+            if game.is_f64_regrets_enabled() {
+                check_len("64-bit regrets", node.regrets_f64().len())?;
+            } else {
+                check_len("Regrets", node.regrets().len())?;
+            }
+        }
+    }
+
+    let locking = game.locking_strategy(node);
+    if !locking.is_empty() {
+        check_len("Locking strategy", locking.len())?;
+    }
+
+    Ok(())
+}
+
+/// Checks that `swap_list` consists of disjoint pairs of distinct hands of `player` with the same
+/// initial weights.
+fn verify_swap_list<T: Game>(
+    game: &T,
+    player: usize,
+    swap_list: &[(u16, u16)],
+) -> Result<(), Error> {
+    let weights = game.initial_weights(player);
+    let mut is_swapped = vec![false; weights.len()];
+
+    for &(a, b) in swap_list {
+        let (a, b) = (a as usize, b as usize);
+        if a >= weights.len() || b >= weights.len() {
+            return Err(Error::Verification(format!(
+                "Pair ({a}, {b}) is out of range ({} hands)",
+                weights.len()
+            )));
+        }
+
+        if a == b {
+            return Err(Error::Verification(format!(
+                "Pair ({a}, {b}) swaps a hand with itself"
+            )));
+        }
+
+        if is_swapped[a] || is_swapped[b] {
+            return Err(Error::Verification(format!(
+                "Pair ({a}, {b}) swaps a hand more than once"
+            )));
+        }
+
+        if weights[a] != weights[b] {
+            return Err(Error::Verification(format!(
+                "Pair ({a}, {b}) swaps hands with different initial weights ({} and {})",
+                weights[a], weights[b]
+            )));
+        }
+
+        is_swapped[a] = true;
+        is_swapped[b] = true;
+    }
+
+    Ok(())
+}
@@ -266,3 +266,15 @@ fn kuhn() {
     let expected_ev = -1.0 / 18.0;
     assert!((root_ev - expected_ev).abs() < 2.0 * target);
 }
+
+#[cfg(feature = "lp")]
+#[test]
+fn kuhn_lp() {
+    let mut game = KuhnGame::new();
+    let value = solve_lp(&mut game).unwrap();
+
+    let expected_ev = -1.0 / 18.0;
+    assert!((value - expected_ev).abs() < 1e-6);
+    assert!((compute_current_ev(&game)[0] - expected_ev).abs() < 1e-6);
+    assert!(compute_exploitability(&game) < 1e-6);
+}
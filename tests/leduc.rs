@@ -491,3 +491,105 @@ fn leduc_compressed() {
     let expected_ev = -0.0856; // verified by OpenSpiel
     assert!((root_ev - expected_ev).abs() < 2.0 * target);
 }
+
+#[cfg(feature = "lp")]
+#[test]
+fn leduc_lp() {
+    let mut game = LeducGame::new(false);
+    let value = solve_lp(&mut game).unwrap();
+
+    let expected_ev = -0.0856; // verified by OpenSpiel
+    assert!((value - expected_ev).abs() < 1e-4);
+    assert!((compute_current_ev(&game)[0] - value).abs() < 1e-5);
+    assert!(compute_exploitability(&game) < 1e-5);
+
+    // the solution of CFR converges to the exact value
+    let target = 1e-4;
+    let mut game = LeducGame::new(false);
+    solve(&mut game, 10000, target, false);
+    assert!((compute_current_ev(&game)[0] - value).abs() < 2.0 * target);
+}
+
+#[test]
+fn leduc_deterministic() {
+    fn collect_strategies(node: &LeducNode, result: &mut Vec<u32>) {
+        result.extend(node.strategy().iter().map(|x| x.to_bits()));
+        for action in 0..node.num_actions() {
+            collect_strategies(&node.play(action), result);
+        }
+    }
+
+    let algorithms = [
+        SolverAlgorithm::default(),
+        SolverAlgorithm::ChanceSamplingMccfr { seed: 42 },
+    ];
+
+    for algorithm in algorithms {
+        let config = SolverConfig {
+            algorithm,
+            deterministic: true,
+            ..Default::default()
+        };
+
+        let run = || {
+            let mut game = LeducGame::new(false);
+            for t in 0..100 {
+                solve_step_with_config(&game, t, &config);
+            }
+            finalize(&mut game);
+            let mut result = Vec::new();
+            collect_strategies(&game.root(), &mut result);
+            result
+        };
+
+        assert_eq!(run(), run());
+    }
+}
+
+#[test]
+fn leduc_averaged() {
+    let target = 1e-3;
+    let config = SolverConfig::default();
+    let result = solve_averaged(|_| LeducGame::new(false), 4, 1000, target, &config, 42);
+    assert_eq!(result.run_exploitabilities.len(), 4);
+
+    // the averaged strategy is equivalent to a mixture of the runs
+    let mean = result.run_exploitabilities.iter().sum::<f32>() / 4.0;
+    assert!(result.exploitability <= mean + 1e-6);
+
+    // the seeds of the runs are derived from the given seed
+    let result2 = solve_averaged(|_| LeducGame::new(false), 4, 1000, target, &config, 42);
+    assert_eq!(result.run_exploitabilities, result2.run_exploitabilities);
+    assert_eq!(result.exploitability, result2.exploitability);
+
+    // a single run is the ordinary solve
+    let mut game = LeducGame::new(false);
+    let expected = solve_with_config(&mut game, 1000, target, &config, false);
+    let single = solve_averaged(|_| LeducGame::new(false), 1, 1000, target, &config, 42);
+    assert_eq!(single.run_exploitabilities, vec![expected]);
+    assert!((single.exploitability - expected).abs() < 1e-6);
+}
+
+#[test]
+fn leduc_verify() {
+    for is_compression_enabled in [false, true] {
+        let mut game = LeducGame::new(is_compression_enabled);
+        verify_game_impl(&mut game).unwrap();
+    }
+
+    // the strategy of the root is too short
+    let mut game = LeducGame::new(false);
+    game.root().strategy.pop();
+    let err = verify_game_impl(&mut game).unwrap_err();
+    assert!(matches!(err, Error::Verification(e) if e.starts_with("Node []: Strategy")));
+
+    // the swap list refers to a nonexistent hand
+    let mut game = LeducGame::new(false);
+    game.isomorphism_swap[1].push((5, NUM_PRIVATE_HANDS as u16));
+    assert!(verify_game_impl(&mut game).is_err());
+
+    // the isomorphic chance refers to a nonexistent action
+    let mut game = LeducGame::new(false);
+    game.isomorphism.push(3);
+    assert!(verify_game_impl(&mut game).is_err());
+}